@@ -0,0 +1,653 @@
+// The simulation from the command line, for running it without the GUI or from a shell script.
+// Run with `cargo run --release -p deep-sea-sim -- run --rows 20 --cols 20 --fish 10 --headless`.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::{Args, Parser, Subcommand};
+use game_data::brains::SocketBrain;
+use game_data::checkpoint::CheckpointDiff;
+use game_data::colony_code::ColonyCode;
+use game_data::entities::display_spec::GlyphStyle;
+use game_data::entities::Species;
+use game_data::equilibrium::Outlook;
+use game_data::event_policy::EventPolicy;
+use game_data::game_board::Topology;
+use game_data::interaction_matrix::{InteractionMatrix, SPECIES_FILE};
+use game_data::profiling::Phase;
+use game_data::scenario::Scenario;
+use game_data::scripting::ScriptRegistry;
+#[cfg(feature = "server")]
+use game_data::server::ObservationServer;
+use game_data::settings::{Difficulty, SimulationSettings, SETTINGS_FILE};
+use game_data::setup::{Preset, SetupConfig, SetupError};
+use game_data::sweep::ParameterGrid;
+use game_data::Sandbox;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[derive(Parser)]
+#[command(
+    name = "deep-sea-sim",
+    about = "Run the deep sea simulation without the GUI"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Set up a fresh, randomly filled board and run it.
+    Run(RunArgs),
+    /// Pick a scenario or saved game back up and run it.
+    Replay {
+        file: PathBuf,
+        #[command(flatten)]
+        play: PlayArgs,
+    },
+    /// Time how long each phase of a tick takes on a generated board.
+    Bench(BenchArgs),
+    /// Check scenario or settings files for mistakes, without running anything.
+    ValidateConfig(ValidateArgs),
+    /// Compare two saved games, listing everything that was born, died or changed in between.
+    Diff { before: PathBuf, after: PathBuf },
+    /// Run every combination of a grid of setups a few times over, and sum up how each one went.
+    Sweep(SweepArgs),
+}
+
+/// How to run a game once it's been set up.
+#[derive(Args)]
+struct PlayArgs {
+    /// How many ticks to run for.
+    #[arg(long, default_value_t = 100)]
+    ticks: usize,
+    /// Run as fast as possible, and only print a census at the end instead of the board every tick.
+    #[arg(long)]
+    headless: bool,
+    /// Print the board in plain characters instead of emoji, for terminals that can't line emoji up.
+    #[arg(long)]
+    ascii: bool,
+    /// Seed the game's randomness, so it plays out the same every time.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Play on this difficulty, instead of whatever the settings file says.
+    #[arg(long, value_parser = parse_difficulty)]
+    difficulty: Option<Difficulty>,
+    /// Where to read settings from.
+    #[arg(long, default_value = SETTINGS_FILE)]
+    settings: PathBuf,
+    /// Where to read who eats whom and who mates with whom from, on top of the built in rules.
+    #[arg(long, default_value = SPECIES_FILE)]
+    species: PathBuf,
+    /// Load custom events and behaviors from the scripts in this folder.
+    #[arg(long)]
+    scripts: Option<PathBuf>,
+    /// Save a picture of how the board ended up into this folder.
+    #[arg(long)]
+    screenshot: Option<PathBuf>,
+    /// Record a timelapse with a frame every this many ticks, saving it alongside any screenshot.
+    #[arg(long)]
+    timelapse: Option<usize>,
+    /// How to answer events: random, default, first, least_damaging, or by_tick:<tick>=<1 or 2>,...
+    /// Leaving it out answers them at random, or with their default answer if they're on a timer.
+    #[arg(long, value_parser = EventPolicy::parse)]
+    event_policy: Option<EventPolicy>,
+    /// Hand a species' decisions over to a controller listening on a socket, like shark=127.0.0.1:7000. Can be given
+    /// more than once.
+    #[arg(long, value_parser = parse_brain)]
+    brain: Vec<(Species, String)>,
+    /// Let anyone watch the run and send it console commands over a WebSocket at this address, like 0.0.0.0:9001.
+    #[cfg(feature = "server")]
+    #[arg(long)]
+    serve: Option<String>,
+    /// Put every event to a vote among everyone watching, rather than answering it with the event policy. Voting stays
+    /// open for as long as the event timer in the settings, or 30 seconds if there isn't one.
+    #[cfg(feature = "server")]
+    #[arg(long, requires = "serve")]
+    vote: bool,
+}
+
+#[derive(Args)]
+struct RunArgs {
+    #[arg(long, default_value_t = 20)]
+    rows: usize,
+    #[arg(long, default_value_t = 20)]
+    cols: usize,
+    #[arg(long)]
+    fish: Option<usize>,
+    #[arg(long)]
+    crab: Option<usize>,
+    #[arg(long)]
+    shark: Option<usize>,
+    /// Fill in the starting populations from a preset. Any counts given alongside it win out.
+    #[arg(long, value_parser = parse_preset)]
+    preset: Option<Preset>,
+    /// The odds of an empty tile starting out with kelp on it.
+    #[arg(long)]
+    kelp: Option<f64>,
+    /// Lay the board out in hexes instead of squares.
+    #[arg(long)]
+    hex: bool,
+    /// Set up exactly the same board as a colony code someone's shared, instead of from any of the above.
+    #[arg(long, value_parser = ColonyCode::from_str, conflicts_with_all = ["rows", "cols", "fish", "crab", "shark", "preset", "kelp", "hex", "seed"])]
+    code: Option<ColonyCode>,
+    #[command(flatten)]
+    play: PlayArgs,
+}
+
+#[derive(Args)]
+struct BenchArgs {
+    #[arg(long, default_value_t = 30)]
+    rows: usize,
+    #[arg(long, default_value_t = 30)]
+    cols: usize,
+    #[arg(long, default_value_t = 200)]
+    ticks: usize,
+    /// Benchmarks always use the same seed unless told otherwise, so runs can be compared.
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// How many threads to work out moves across, on boards big enough to be split into chunks. Every core by default.
+    #[arg(long)]
+    threads: Option<usize>,
+}
+
+#[derive(Args)]
+struct ValidateArgs {
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Check them as settings files, instead of as scenarios.
+    #[arg(long, conflicts_with = "species")]
+    settings: bool,
+    /// Check them as species configs, instead of as scenarios.
+    #[arg(long)]
+    species: bool,
+}
+
+/// Every list is comma separated, and numbers can use a..b to stand for every number from a up to b. Anything left out
+/// is kept to a single default value.
+#[derive(Args)]
+struct SweepArgs {
+    /// Board sizes, as rows x columns, like 10x10,20x20.
+    #[arg(long)]
+    sizes: Option<String>,
+    /// How many fish to start with, like 5,10.
+    #[arg(long)]
+    fish: Option<String>,
+    #[arg(long)]
+    crab: Option<String>,
+    #[arg(long)]
+    shark: Option<String>,
+    /// The odds of an empty tile starting out with kelp on it, like 0.1,0.2.
+    #[arg(long)]
+    kelp: Option<String>,
+    /// Difficulties, like easy,hard.
+    #[arg(long)]
+    difficulty: Option<String>,
+    /// Species configs to try each combination under, like species.txt,no_sharks.txt.
+    #[arg(long)]
+    species: Option<String>,
+    /// Every combination gets run once per seed, like 0..20.
+    #[arg(long)]
+    seeds: Option<String>,
+    /// How long each run goes on for.
+    #[arg(long, default_value_t = 300)]
+    ticks: usize,
+    /// Where to write the results as a CSV, instead of printing them out.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+/// Difficulties go by the same names as in the settings file.
+fn parse_difficulty(name: &str) -> Result<Difficulty, String> {
+    Difficulty::ALL
+        .into_iter()
+        .find(|d| d.name().eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("there's no difficulty called '{name}'"))
+}
+
+/// Brains are given as species=address.
+fn parse_brain(arg: &str) -> Result<(Species, String), String> {
+    let (name, addr) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("'{arg}' should look like species=address"))?;
+    let species = Species::from_name(name)
+        .filter(Species::is_animal)
+        .ok_or_else(|| format!("there's no animal called '{name}'"))?;
+    Ok((species, addr.to_owned()))
+}
+
+/// Presets can be written like `predator-heavy`, so they don't need quoting.
+fn parse_preset(name: &str) -> Result<Preset, String> {
+    Preset::ALL
+        .into_iter()
+        .find(|p| p.name().replace(' ', "-").eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let names: Vec<String> = Preset::ALL
+                .iter()
+                .map(|p| p.name().replace(' ', "-").to_lowercase())
+                .collect();
+            format!(
+                "there's no preset called '{name}', try one of {}",
+                names.join(", ")
+            )
+        })
+}
+
+fn print_census(sandbox: &Sandbox) {
+    let census = sandbox.census();
+    println!("After {} ticks:", census.ticks);
+    for species in Species::all() {
+        let count = census.count(species);
+        if count > 0 {
+            println!("  {}: {count}", species.name());
+        }
+    }
+    if let Some(health) = &census.health {
+        println!(
+            "Colony health: {}/100 ({})",
+            health.score,
+            census.health_trend.name()
+        );
+    }
+    for warning in sandbox.population_warnings() {
+        match warning.outlook {
+            Outlook::Extinction(ticks) => println!(
+                "Warning: {} population unsustainable, on course to die out in about {ticks} ticks",
+                warning.species.name()
+            ),
+            Outlook::Explosion(ticks) => println!(
+                "Warning: {} population exploding, on course to overrun the sea in about {ticks} ticks",
+                warning.species.name()
+            ),
+            Outlook::Stable => (),
+        }
+    }
+}
+
+/// Every event that went off and how it was answered, along with how to answer them all the same way next time.
+fn print_event_log(sandbox: &Sandbox) {
+    let log = sandbox.event_log();
+    if log.is_empty() {
+        return;
+    }
+    println!(
+        "Events, answered with the {} policy:",
+        sandbox.event_policy()
+    );
+    for answered in log {
+        println!(
+            "  tick {}: {} in {}, answered {}",
+            answered.tick,
+            answered.event,
+            answered.region,
+            answered.answer_number()
+        );
+    }
+    println!(
+        "Answer them the same way again with --event-policy {}",
+        EventPolicy::replaying(log)
+    );
+}
+
+/// Run a game that's already been set up, either watching the board tick by tick or skipping straight to the end.
+fn play(mut sandbox: Sandbox, args: &PlayArgs) {
+    let mut settings = SimulationSettings::load(&args.settings);
+    if let Some(difficulty) = args.difficulty {
+        settings.difficulty = difficulty;
+    }
+    #[cfg(feature = "server")]
+    if args.vote {
+        settings.event_voting = true;
+    }
+    let tick_time = Duration::from_secs_f64(1.0 / settings.tick_rate);
+    sandbox.apply_settings(settings);
+    if let Some(dir) = &args.scripts {
+        let (scripts, errors) = ScriptRegistry::load_dir(dir);
+        for e in &errors {
+            eprintln!("Couldn't load script {e}");
+        }
+        sandbox.set_scripts(Arc::new(scripts));
+    }
+
+    if let Some(every) = args.timelapse {
+        sandbox.start_timelapse(every);
+    }
+    if let Some(policy) = &args.event_policy {
+        sandbox.set_event_policy(policy.clone());
+    }
+    for (species, addr) in &args.brain {
+        match SocketBrain::connect(addr) {
+            Ok(brain) => sandbox.set_brain(*species, brain),
+            Err(e) => eprintln!(
+                "Couldn't reach the controller for {} at {addr}, leaving them to the built in AI: {e}",
+                species.name()
+            ),
+        }
+    }
+    #[cfg(feature = "server")]
+    let mut server = args
+        .serve
+        .as_ref()
+        .and_then(|addr| match ObservationServer::bind(addr) {
+            Ok(server) => {
+                println!("Serving the game at ws://{}", server.local_addr());
+                Some(server)
+            }
+            Err(e) => {
+                eprintln!("Couldn't start serving the game at {addr}: {e}");
+                None
+            }
+        });
+    let style = match args.ascii {
+        true => GlyphStyle::Ascii,
+        false => GlyphStyle::Emoji,
+    };
+    if !args.headless {
+        println!("{}", sandbox.get_board().render(style));
+    }
+    for _ in 0..args.ticks {
+        // anything sent in over the last tick gets carried out before the next one
+        #[cfg(feature = "server")]
+        if let Some(server) = &mut server {
+            server.take_commands(&mut sandbox);
+        }
+        let mut report = sandbox.tick();
+        let tick = report.tick;
+        if let Some(pending) = &report.event {
+            println!("Tick {tick}: {}", pending.kind().name());
+        }
+        #[cfg(feature = "server")]
+        if let Some(server) = &mut server {
+            server.publish(&sandbox, &report);
+        }
+        if !args.headless {
+            println!("Tick {tick}\n{}", sandbox.get_board().render(style));
+            thread::sleep(tick_time);
+        }
+        if let Some(pending) = report.event.take() {
+            // whatever wins the vote gets picked, if there's one going
+            #[cfg(feature = "server")]
+            if let Some(server) = &mut server {
+                server.hold_vote(&mut sandbox);
+            }
+            sandbox.answer_with_policy(pending);
+        }
+    }
+    print_census(&sandbox);
+    print_event_log(&sandbox);
+    if let Some(dir) = &args.screenshot {
+        match sandbox.save_screenshot(dir) {
+            Ok(path) => println!("Saved a screenshot to {}", path.display()),
+            Err(e) => eprintln!("Couldn't save a screenshot: {e}"),
+        }
+    }
+    if let Some(timelapse) = sandbox.stop_timelapse() {
+        let dir = args
+            .screenshot
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("."));
+        match timelapse.save(&dir) {
+            Ok(path) => println!("Saved a timelapse to {}", path.display()),
+            Err(e) => eprintln!("Couldn't save the timelapse: {e}"),
+        }
+    }
+}
+
+fn run(mut args: RunArgs) -> ExitCode {
+    // colony codes are made and checked against the species rules, so they go in first
+    let rules = InteractionMatrix::load(&args.play.species);
+    let code = match args.code.take() {
+        Some(code) => {
+            if !code.same_species(&rules) {
+                eprintln!("That colony code was made with different species rules, so it won't play out the same");
+            }
+            // the code's difficulty goes, unless another one was asked for
+            args.play.difficulty.get_or_insert(code.difficulty);
+            Ok(code)
+        }
+        None => code_from_args(&args, &rules),
+    };
+    let mut sandbox = match code.and_then(|code| Ok((code.build()?, code))) {
+        Ok((sandbox, code)) => {
+            println!("Colony code: {code}");
+            sandbox
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    sandbox.set_rules(rules);
+    play(sandbox, &args.play);
+    ExitCode::SUCCESS
+}
+
+/// Put together a colony code for the board the command line asks for.
+fn code_from_args(args: &RunArgs, rules: &InteractionMatrix) -> Result<ColonyCode, SetupError> {
+    let mut setup = SetupConfig::new(args.rows, args.cols);
+    if args.hex {
+        setup.topology = Topology::Hex;
+    }
+    let seed = args.play.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    if let Some(preset) = args.preset {
+        preset.apply_with_rng(&mut setup, &mut rng);
+    }
+    // anything given on the command line wins out over the preset
+    let populations = [
+        (Species::Fish, args.fish.unwrap_or(setup.fish)),
+        (Species::Crab, args.crab.unwrap_or(setup.crab)),
+        (Species::Shark, args.shark.unwrap_or(setup.shark)),
+    ];
+    let kelp_chance = args.kelp.unwrap_or(setup.kelp_chance);
+    let fresh = SetupConfig::try_new(args.rows, args.cols, &populations)?;
+    let setup = SetupConfig {
+        topology: setup.topology,
+        kelp_chance,
+        ..fresh
+    };
+    setup.validate()?;
+    let difficulty = args
+        .play
+        .difficulty
+        .unwrap_or_else(|| SimulationSettings::load(&args.play.settings).difficulty);
+    Ok(ColonyCode {
+        seed,
+        ..ColonyCode::new(setup, difficulty, rules)
+    })
+}
+
+fn replay(file: PathBuf, args: PlayArgs) -> ExitCode {
+    let scenario = match Scenario::load(&file) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("Couldn't load {}: {e}", file.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    // the tick rate gets replaced by whatever the settings say anyway
+    let mut sandbox = scenario.build(SimulationSettings::default().tick_rate);
+    sandbox.set_rules(InteractionMatrix::load(&args.species));
+    if let Some(seed) = args.seed {
+        sandbox.reseed(seed);
+    }
+    play(sandbox, &args);
+    ExitCode::SUCCESS
+}
+
+fn bench(args: BenchArgs) -> ExitCode {
+    // the same mix of animals as the tick benchmark, around 15% of the board
+    let animals = args.rows * args.cols * 15 / 100;
+    let (fish, crab) = (animals / 2, animals * 3 / 10);
+    let populations = [
+        (Species::Fish, fish),
+        (Species::Crab, crab),
+        (Species::Shark, animals - fish - crab),
+    ];
+    let sandbox = SetupConfig::try_new(args.rows, args.cols, &populations)
+        .and_then(|setup| Sandbox::from_setup(&setup, &mut StdRng::seed_from_u64(args.seed)));
+    let mut sandbox = match sandbox {
+        Ok(sandbox) => sandbox,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    sandbox.apply_settings(SimulationSettings::default());
+    if let Some(threads) = args.threads {
+        sandbox.set_worker_threads(threads);
+    }
+
+    let start = Instant::now();
+    sandbox.run_headless(args.ticks, |_, _| ());
+    let elapsed = start.elapsed();
+    println!(
+        "{} ticks on a {}x{} board in {elapsed:.2?} ({:.1} ticks/s)",
+        args.ticks,
+        args.rows,
+        args.cols,
+        args.ticks as f64 / elapsed.as_secs_f64()
+    );
+    for phase in Phase::ALL {
+        if let Some(stats) = sandbox.get_timings().stats(phase) {
+            println!(
+                "  {:<16} mean {:>10.2?}  p95 {:>10.2?}  max {:>10.2?}",
+                phase.name(),
+                stats.mean,
+                stats.p95,
+                stats.max
+            );
+        }
+    }
+    print_census(&sandbox);
+    ExitCode::SUCCESS
+}
+
+fn validate_config(args: ValidateArgs) -> ExitCode {
+    let mut all_ok = true;
+    for file in &args.files {
+        let path = file.display();
+        if args.settings || args.species {
+            let contents = match fs::read_to_string(file) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    println!("{path}: {e}");
+                    all_ok = false;
+                    continue;
+                }
+            };
+            let ignored = if args.settings {
+                SimulationSettings::parse(&contents).1
+            } else {
+                InteractionMatrix::parse(&contents).1
+            };
+            if ignored.is_empty() {
+                println!("{path}: ok");
+            }
+            for line in &ignored {
+                println!("{path}: can't make sense of '{line}'");
+            }
+            all_ok &= ignored.is_empty();
+        } else {
+            match Scenario::load(file) {
+                Ok(scenario) => {
+                    let (rows, cols) = scenario.dims();
+                    println!(
+                        "{path}: ok, '{}' is {rows}x{cols} with {} entities",
+                        scenario.get_name(),
+                        scenario.get_entities().len()
+                    );
+                }
+                Err(e) => {
+                    println!("{path}: {e}");
+                    all_ok = false;
+                }
+            }
+        }
+    }
+    if all_ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn diff(before: PathBuf, after: PathBuf) -> ExitCode {
+    let load = |file: &PathBuf| {
+        Scenario::load(file).map_err(|e| eprintln!("Couldn't load {}: {e}", file.display()))
+    };
+    let (Ok(before), Ok(after)) = (load(&before), load(&after)) else {
+        return ExitCode::FAILURE;
+    };
+    print!("{}", CheckpointDiff::new(&before, &after));
+    ExitCode::SUCCESS
+}
+
+fn sweep(args: SweepArgs) -> ExitCode {
+    let mut grid = ParameterGrid::default();
+    let lists = [
+        ("sizes", &args.sizes),
+        ("fish", &args.fish),
+        ("crab", &args.crab),
+        ("shark", &args.shark),
+        ("kelp", &args.kelp),
+        ("difficulty", &args.difficulty),
+        ("species", &args.species),
+        ("seeds", &args.seeds),
+    ];
+    for (key, value) in lists {
+        if let Some(value) = value {
+            if let Err(e) = grid.set(key, value) {
+                eprintln!("{e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let report = match grid.run(args.ticks) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    for (point, e) in &report.skipped {
+        let setup = &point.setup;
+        eprintln!(
+            "Skipping {}x{} with {} fish, {} crab and {} shark: {e}",
+            setup.rows, setup.cols, setup.fish, setup.crab, setup.shark
+        );
+    }
+    match args.out {
+        Some(path) => {
+            if let Err(e) = report.save(&path) {
+                eprintln!("Couldn't write the results to {}: {e}", path.display());
+                return ExitCode::FAILURE;
+            }
+            println!(
+                "Wrote {} results to {}",
+                report.results.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", report.to_csv()),
+    }
+    ExitCode::SUCCESS
+}
+
+fn main() -> ExitCode {
+    match Cli::parse().command {
+        Command::Run(args) => run(args),
+        Command::Replay { file, play } => replay(file, play),
+        Command::Bench(args) => bench(args),
+        Command::ValidateConfig(args) => validate_config(args),
+        Command::Diff { before, after } => diff(before, after),
+        Command::Sweep(args) => sweep(args),
+    }
+}
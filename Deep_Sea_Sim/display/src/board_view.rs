@@ -0,0 +1,467 @@
+// Drawing the board as a grid, with a camera that can be zoomed and panned around.
+
+use std::collections::{HashMap, HashSet};
+
+use eframe::egui;
+use egui::{Align2, Color32, FontId, Pos2, Rect, Rounding, Sense, Shape, Stroke, Vec2};
+use game_data::debug_overlay::{AnimalDebug, DebugOverlay};
+use game_data::game_board::{Pos, Topology, EMPTY_GLYPH};
+use game_data::snapshot::BoardSnapshot;
+use game_data::spotlight::Spotlight;
+use game_data::EntityID;
+use instant::Instant;
+
+use crate::theme::LetterGlyphs;
+
+/// Size of a tile on screen, in points, at 1x zoom.
+const BASE_TILE_SIZE: f32 = 32.0;
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 4.0;
+/// The longest side of the minimap, in points.
+const MINIMAP_SIZE: f32 = 150.0;
+/// How far apart rows of hexagons are, as a fraction of a tile's width.
+const HEX_ROW_HEIGHT: f32 = 0.866;
+/// Polluted tiles get a wash of this drawn under them, more opaque the worse the pollution is.
+const POLLUTION_COLOR: Color32 = Color32::from_rgb(70, 60, 20);
+/// How opaque the wash over a tile is at the worst pollution there is.
+const MAX_POLLUTION_ALPHA: f32 = 0.6;
+/// Animals carrying a child get a dot of this in the corner of their tile.
+const PREGNANCY_COLOR: Color32 = Color32::from_rgb(240, 130, 190);
+/// The animal being followed gets ringed in this, along with the way it's headed and how far it can see.
+const SPOTLIGHT_COLOR: Color32 = Color32::from_rgb(255, 220, 90);
+/// The debug overlay's lines and filled in tiles are drawn in this.
+const DEBUG_COLOR: Color32 = Color32::from_rgb(90, 200, 255);
+
+/// The camera looking over the board.
+pub struct BoardView {
+    zoom: f32,
+    /// Where the top left corner of the board is, relative to the top left of the view.
+    offset: Vec2,
+    /// If false, the camera will be reset to fit the whole board the next time it's drawn.
+    fitted: bool,
+    /// If the camera has been zoomed or panned since it was last fit to the board.
+    moved: bool,
+    /// The size of the view the last time it was drawn, so we can tell if the window was resized.
+    last_view_size: Vec2,
+    /// Where every entity was as of the latest tick.
+    positions: HashMap<EntityID, Pos>,
+    /// For every entity that moved on the latest tick, how far away (in tiles) it started from where it is now.
+    moved_from: HashMap<Pos, Vec2>,
+    /// When the latest tick came in.
+    tick_start: Option<Instant>,
+    /// How the board's tiles are laid out.
+    topology: Topology,
+    /// How polluted every tile with any pollution on it is, as of the latest tick.
+    pollution: HashMap<Pos, f64>,
+    /// Where every animal carrying a child is, as of the latest tick.
+    pregnant: HashSet<Pos>,
+    /// The animal being followed, as of the latest tick.
+    spotlight: Option<Spotlight>,
+    /// If true, the camera will be moved over the animal being followed the next time it's drawn.
+    follow: bool,
+    /// What every animal's AI was up to as of the latest tick, if the debug overlay is on.
+    debug: Vec<AnimalDebug>,
+    /// Every tile the animal being followed searched through on the latest tick, if the debug overlay is on.
+    explored: HashSet<Pos>,
+}
+
+impl Default for BoardView {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            offset: Vec2::ZERO,
+            fitted: false,
+            moved: false,
+            last_view_size: Vec2::ZERO,
+            positions: HashMap::new(),
+            moved_from: HashMap::new(),
+            tick_start: None,
+            topology: Topology::Square,
+            pollution: HashMap::new(),
+            pregnant: HashSet::new(),
+            spotlight: None,
+            follow: false,
+            debug: Vec::new(),
+            explored: HashSet::new(),
+        }
+    }
+}
+
+impl BoardView {
+    fn tile_size(&self) -> f32 {
+        BASE_TILE_SIZE * self.zoom
+    }
+
+    /// Where the center of a tile is, in tiles from the top left corner of the board.
+    /// On a hex board every odd row is shifted half a tile right, and the rows are packed closer together.
+    fn tile_center(&self, x: usize, y: usize) -> Vec2 {
+        match self.topology {
+            Topology::Square => Vec2::new(x as f32 + 0.5, y as f32 + 0.5),
+            Topology::Hex => Vec2::new(
+                x as f32 + 0.5 + (y % 2) as f32 * 0.5,
+                y as f32 * HEX_ROW_HEIGHT + 0.5,
+            ),
+        }
+    }
+
+    /// How big the whole board is, in tiles.
+    fn board_size(&self, rows: usize, cols: usize) -> Vec2 {
+        match self.topology {
+            Topology::Square => Vec2::new(cols as f32, rows as f32),
+            Topology::Hex if rows > 1 => {
+                Vec2::new(cols as f32 + 0.5, (rows - 1) as f32 * HEX_ROW_HEIGHT + 1.0)
+            }
+            Topology::Hex => Vec2::new(cols as f32, rows as f32),
+        }
+    }
+
+    /// Find the tile under a point, given in tiles from the top left corner of the board.
+    fn tile_at(&self, point: Vec2, rows: usize, cols: usize) -> Option<Pos> {
+        if self.topology == Topology::Square {
+            if point.x < 0.0 || point.y < 0.0 || point.x >= cols as f32 || point.y >= rows as f32 {
+                return None;
+            }
+            return Some(Pos {
+                x: point.x as usize,
+                y: point.y as usize,
+            });
+        }
+        // hexagons don't line up into neat rows and columns, so just find whichever tile's center is closest
+        let row = ((point.y - 0.5) / HEX_ROW_HEIGHT).round().max(0.0) as usize;
+        let (x, y) = (row.saturating_sub(1)..(row + 2).min(rows))
+            .map(|y| {
+                let x = (point.x - (y % 2) as f32 * 0.5).floor().max(0.0) as usize;
+                (x.min(cols.saturating_sub(1)), y)
+            })
+            .min_by(|a, b| {
+                let dist = |(x, y): &(usize, usize)| (self.tile_center(*x, *y) - point).length();
+                dist(a).total_cmp(&dist(b))
+            })?;
+        // anything more than half a tile from the nearest center is off the edge of the board
+        ((self.tile_center(x, y) - point).length() <= 0.6).then_some(Pos { x, y })
+    }
+
+    /// Zoom out (or in) so the whole board fits in the view, and center it.
+    fn fit(&mut self, view: Rect, rows: usize, cols: usize) {
+        let size = self.board_size(rows, cols);
+        let zoom = (view.width() / size.x).min(view.height() / size.y) / BASE_TILE_SIZE;
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        let board_size = size * self.tile_size();
+        self.offset = (view.size() - board_size) / 2.0;
+        self.fitted = true;
+        self.moved = false;
+    }
+
+    /// Take in where everything is after a tick, so anything that moved can glide over to its new spot.
+    pub fn track_movement(&mut self, positions: &[(EntityID, Pos)]) {
+        let new_positions: HashMap<EntityID, Pos> = positions.iter().copied().collect();
+        self.moved_from = new_positions
+            .iter()
+            .filter_map(|(id, new)| {
+                let old = self.positions.get(id)?;
+                let delta = Vec2::new(old.x as f32 - new.x as f32, old.y as f32 - new.y as f32);
+                (delta != Vec2::ZERO).then_some((*new, delta))
+            })
+            .collect();
+        self.positions = new_positions;
+        self.tick_start = Some(Instant::now());
+    }
+
+    /// Take in how polluted the board is after a tick.
+    pub fn set_pollution(&mut self, pollution: &[(Pos, f64)]) {
+        self.pollution = pollution.iter().copied().collect();
+    }
+
+    /// Take in where every animal carrying a child is after a tick.
+    pub fn set_pregnant(&mut self, pregnant: &[Pos]) {
+        self.pregnant = pregnant.iter().copied().collect();
+    }
+
+    /// Take in the animal being followed after a tick. The camera catches up with it once a tick, so it can still be
+    /// dragged around in between.
+    pub fn set_spotlight(&mut self, spotlight: Option<Spotlight>) {
+        self.follow = spotlight.is_some();
+        self.spotlight = spotlight;
+    }
+
+    /// Take in what the AI was up to after a tick, or None to stop drawing the debug overlay.
+    pub fn set_debug(&mut self, debug: Option<DebugOverlay>) {
+        let debug = debug.unwrap_or_default();
+        self.debug = debug.animals;
+        self.explored = debug.explored.into_iter().collect();
+    }
+
+    /// Move the camera by some amount.
+    pub fn pan(&mut self, delta: Vec2) {
+        self.offset += delta;
+        self.moved = true;
+    }
+
+    /// Draw the board, handling any zooming and panning.
+    /// Hex boards get an outline around every tile, since the layout isn't obvious otherwise.
+    /// If given letter glyphs, entities are drawn as letters instead of emoji.
+    /// Anything that moved on the last tick is animated over `tick_length` seconds.
+    /// Returns the tile that was clicked on, if any.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        board: &BoardSnapshot,
+        topology: Topology,
+        text_color: Color32,
+        letters: Option<&LetterGlyphs>,
+        tick_length: f32,
+    ) -> Option<Pos> {
+        let tiles: Vec<&[char]> = board.rows().collect();
+        let (cols, rows) = board.dims();
+        let (response, painter) = ui.allocate_painter(ui.available_size(), Sense::click_and_drag());
+        let view = response.rect;
+        if rows == 0 || cols == 0 {
+            return None;
+        }
+        if topology != self.topology {
+            self.topology = topology;
+            self.fitted = false;
+        }
+        if view.size() != self.last_view_size {
+            if self.moved {
+                // keep looking at the same spot, just with more or less around it
+                self.offset += (view.size() - self.last_view_size) / 2.0;
+            } else {
+                self.fitted = false;
+            }
+            self.last_view_size = view.size();
+        }
+        if !self.fitted {
+            self.fit(view, rows, cols);
+        }
+        if let Some(spotlight) = self.spotlight.as_ref().filter(|_| self.follow) {
+            let target = self.tile_center(spotlight.pos.x, spotlight.pos.y) * self.tile_size();
+            self.offset = view.size() / 2.0 - target;
+            self.moved = true;
+            self.follow = false;
+        }
+
+        // drag to pan
+        if response.dragged() {
+            self.offset += response.drag_delta();
+            self.moved = true;
+        }
+        // scroll to zoom, keeping whatever's under the cursor in place
+        if let Some(hover) = response.hover_pos() {
+            let scroll = ui.input(|i| i.scroll_delta.y);
+            if scroll != 0.0 {
+                let new_zoom = (self.zoom * (scroll / 200.0).exp()).clamp(MIN_ZOOM, MAX_ZOOM);
+                let anchor = hover - view.min;
+                self.offset = anchor - (anchor - self.offset) * (new_zoom / self.zoom);
+                self.zoom = new_zoom;
+                self.moved = true;
+            }
+        }
+        if response.double_clicked() {
+            self.fitted = false;
+        }
+
+        let tile_size = self.tile_size();
+        let origin = view.min + self.offset;
+        // only bother drawing the tiles we can actually see
+        let row_height = match topology {
+            Topology::Square => tile_size,
+            Topology::Hex => tile_size * HEX_ROW_HEIGHT,
+        };
+        let first_col = ((view.min.x - origin.x) / tile_size).floor().max(0.0) as usize;
+        // the extra column catches the half tile that odd rows of hexagons stick out by
+        let last_col =
+            (((view.max.x - origin.x) / tile_size).ceil().max(0.0) as usize + 1).min(cols);
+        let first_row = ((view.min.y - origin.y) / row_height).floor().max(0.0) as usize;
+        let last_row =
+            (((view.max.y - origin.y) / row_height).ceil().max(0.0) as usize + 1).min(rows);
+        let outline = Stroke::new(1.0, text_color.linear_multiply(0.15));
+        let font = FontId::proportional(tile_size * 0.8);
+        // how much of the trip from the last tick's positions is left to go
+        let remaining = match self.tick_start {
+            Some(start) => (1.0 - start.elapsed().as_secs_f32() / tick_length).max(0.0),
+            None => 0.0,
+        };
+        if remaining > 0.0 {
+            ui.ctx().request_repaint();
+        }
+        for (y, row) in tiles.iter().enumerate().take(last_row).skip(first_row) {
+            for (x, tile) in row.iter().enumerate().take(last_col).skip(first_col) {
+                let moved_from = match self.moved_from.get(&Pos { x, y }) {
+                    Some(delta) => *delta * remaining,
+                    None => Vec2::ZERO,
+                };
+                let middle = origin + self.tile_center(x, y) * tile_size;
+                if let Some(level) = self.pollution.get(&Pos { x, y }) {
+                    let wash = POLLUTION_COLOR.linear_multiply(*level as f32 * MAX_POLLUTION_ALPHA);
+                    fill_tile(&painter, topology, middle, tile_size, wash);
+                }
+                if self.explored.contains(&Pos { x, y }) {
+                    let wash = DEBUG_COLOR.linear_multiply(0.2);
+                    fill_tile(&painter, topology, middle, tile_size, wash);
+                }
+                if topology == Topology::Hex {
+                    painter.add(Shape::closed_line(hexagon(middle, tile_size), outline));
+                }
+                let center = origin + (self.tile_center(x, y) + moved_from) * tile_size;
+                let (glyph, color) = match letters {
+                    None => (tile.to_string(), text_color),
+                    Some(letters) => match letters.get(*tile) {
+                        Some((letter, color)) => (letter.to_string(), color),
+                        None if *tile == EMPTY_GLYPH => (String::from("·"), text_color),
+                        None => (tile.to_string(), text_color),
+                    },
+                };
+                painter.text(center, Align2::CENTER_CENTER, glyph, font.clone(), color);
+                if self.pregnant.contains(&Pos { x, y }) {
+                    painter.circle_filled(
+                        center + Vec2::new(0.3, -0.3) * tile_size,
+                        tile_size * 0.1,
+                        PREGNANCY_COLOR,
+                    );
+                }
+            }
+        }
+
+        if let Some(spotlight) = &self.spotlight {
+            let stroke = Stroke::new(2.0, SPOTLIGHT_COLOR);
+            let center = |pos: &Pos| origin + self.tile_center(pos.x, pos.y) * tile_size;
+            painter.add(Shape::line(
+                spotlight.path.iter().map(center).collect(),
+                stroke,
+            ));
+            let moved_from = self.moved_from.get(&spotlight.pos).copied();
+            let here =
+                center(&spotlight.pos) + moved_from.unwrap_or(Vec2::ZERO) * remaining * tile_size;
+            painter.circle_stroke(here, tile_size * 0.6, stroke);
+            painter.circle_stroke(
+                here,
+                (spotlight.perception as f32 + 0.5) * tile_size,
+                Stroke::new(1.0, SPOTLIGHT_COLOR.linear_multiply(0.5)),
+            );
+        }
+
+        let small_font = FontId::proportional(tile_size * 0.35);
+        for animal in &self.debug {
+            let middle = origin + self.tile_center(animal.pos.x, animal.pos.y) * tile_size;
+            if let Some(target) = animal.target {
+                let target = origin + self.tile_center(target.x, target.y) * tile_size;
+                painter.line_segment([middle, target], Stroke::new(1.0, DEBUG_COLOR));
+            }
+            painter.text(
+                middle + Vec2::new(-0.3, -0.3) * tile_size,
+                Align2::CENTER_CENTER,
+                behavior_icon(&animal.behavior),
+                small_font.clone(),
+                text_color,
+            );
+        }
+
+        let minimap = self.show_minimap(&painter, view, rows, cols, &tiles);
+
+        if !response.clicked() {
+            return None;
+        }
+        let click = response.interact_pointer_pos()?;
+        if let Some(minimap) = minimap {
+            if minimap.contains(click) {
+                // jump the camera over to wherever was clicked
+                let scale = minimap.width() / self.board_size(rows, cols).x;
+                let target = (click - minimap.min) / scale * tile_size;
+                self.offset = view.size() / 2.0 - target;
+                self.moved = true;
+                return None;
+            }
+        }
+        self.tile_at((click - origin) / tile_size, rows, cols)
+    }
+
+    /// Draw an overview of the whole board in the corner, along with where the camera is looking.
+    /// Only shown if the board doesn't fit in the view. Returns where the minimap was drawn.
+    fn show_minimap(
+        &self,
+        painter: &egui::Painter,
+        view: Rect,
+        rows: usize,
+        cols: usize,
+        tiles: &[&[char]],
+    ) -> Option<Rect> {
+        let tile_size = self.tile_size();
+        let board_size = self.board_size(rows, cols);
+        let board = Rect::from_min_size(view.min + self.offset, board_size * tile_size);
+        if view.contains_rect(board) {
+            return None;
+        }
+        let scale = MINIMAP_SIZE / board_size.max_elem();
+        let size = board_size * scale;
+        let minimap = Rect::from_min_size(view.max - size - Vec2::splat(10.0), size);
+        painter.rect_filled(minimap, Rounding::none(), Color32::from_black_alpha(180));
+        for (y, row) in tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                if *tile != EMPTY_GLYPH {
+                    let pos = minimap.min + (self.tile_center(x, y) - Vec2::splat(0.5)) * scale;
+                    painter.rect_filled(
+                        Rect::from_min_size(pos, Vec2::splat(scale)),
+                        Rounding::none(),
+                        Color32::LIGHT_BLUE,
+                    );
+                }
+            }
+        }
+        // outline the part of the board we can see
+        let seen = Rect::from_min_max(
+            Pos2::new(0.0, 0.0) + (view.min - board.min) / tile_size * scale,
+            Pos2::new(0.0, 0.0) + (view.max - board.min) / tile_size * scale,
+        )
+        .translate(minimap.min.to_vec2())
+        .intersect(minimap);
+        painter.rect_stroke(seen, Rounding::none(), Stroke::new(1.0, Color32::WHITE));
+        Some(minimap)
+    }
+}
+
+/// Wash a whole tile over with a color.
+fn fill_tile(
+    painter: &egui::Painter,
+    topology: Topology,
+    middle: Pos2,
+    width: f32,
+    color: Color32,
+) {
+    match topology {
+        Topology::Square => painter.rect_filled(
+            Rect::from_center_size(middle, Vec2::splat(width)),
+            Rounding::none(),
+            color,
+        ),
+        Topology::Hex => {
+            painter.add(Shape::convex_polygon(
+                hexagon(middle, width),
+                color,
+                Stroke::NONE,
+            ));
+        }
+    }
+}
+
+/// A little icon for what an animal's busy doing, going by how its behavior describes itself.
+fn behavior_icon(behavior: &str) -> &'static str {
+    match behavior {
+        "idle" => "💤",
+        "eating" => "🍴",
+        "looking for a mate" => "❤",
+        "courting" => "💕",
+        _ => "❓",
+    }
+}
+
+/// The corners of a pointy-topped hexagon that's a tile wide, centered on a point.
+fn hexagon(center: Pos2, width: f32) -> Vec<Pos2> {
+    let radius = width / 3.0_f32.sqrt();
+    (0..6)
+        .map(|i| {
+            let angle = (60.0 * i as f32 - 30.0).to_radians();
+            center + Vec2::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
@@ -0,0 +1,90 @@
+// Keyboard shortcuts, turned into the same actions that the buttons perform.
+
+use eframe::egui;
+use egui::{Key, Vec2};
+
+/// How far the arrow keys move the camera per press, in points.
+const PAN_STEP: f32 = 40.0;
+
+/// Something the player asked the GUI to do, either with a button or a shortcut.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UiAction {
+    TogglePause,
+    SpeedUp,
+    SlowDown,
+    /// Go back a tick and pause there.
+    Rewind,
+    /// Save a picture of the board next to where the game is running.
+    Screenshot,
+    /// Start recording a timelapse, or stop and save the one being recorded.
+    ToggleTimelapse,
+    /// Move the camera over the board by this much.
+    Pan(Vec2),
+    /// Pick one of the two options for the current event. False is the first option, true is the second.
+    AnswerEvent(bool),
+    /// Close the current event once its outcome has been read.
+    DismissEvent,
+    ToggleMenu,
+    /// Take back the last change made in the editor.
+    Undo,
+    /// Make the last change that was taken back in the editor again.
+    Redo,
+}
+
+/// Every shortcut, and the key for what it does in the locale catalogs. Shown to the player in the menu.
+pub const SHORTCUTS: [(&str, &str); 12] = [
+    ("Space", "shortcut.pause"),
+    ("+ / -", "shortcut.speed"),
+    ("Backspace", "shortcut.rewind"),
+    ("F12", "shortcut.screenshot"),
+    ("F11", "shortcut.timelapse"),
+    ("Arrow keys", "shortcut.move"),
+    ("1 / 2", "shortcut.answer"),
+    ("Enter", "shortcut.continue"),
+    ("Esc", "shortcut.menu"),
+    ("Ctrl+Z / Ctrl+Y", "shortcut.undo"),
+    ("`", "shortcut.console"),
+    ("Scroll / drag", "shortcut.zoom"),
+];
+
+/// Check which shortcuts were pressed this frame.
+pub fn read_shortcuts(ctx: &egui::Context) -> Vec<UiAction> {
+    // don't steal keys from anyone typing into a text box
+    if ctx.wants_keyboard_input() {
+        return vec![];
+    }
+    ctx.input(|i| {
+        let bindings = [
+            (Key::Space, UiAction::TogglePause),
+            (Key::PlusEquals, UiAction::SpeedUp),
+            (Key::Minus, UiAction::SlowDown),
+            (Key::Backspace, UiAction::Rewind),
+            (Key::F12, UiAction::Screenshot),
+            (Key::F11, UiAction::ToggleTimelapse),
+            (Key::ArrowUp, UiAction::Pan(Vec2::new(0.0, PAN_STEP))),
+            (Key::ArrowDown, UiAction::Pan(Vec2::new(0.0, -PAN_STEP))),
+            (Key::ArrowLeft, UiAction::Pan(Vec2::new(PAN_STEP, 0.0))),
+            (Key::ArrowRight, UiAction::Pan(Vec2::new(-PAN_STEP, 0.0))),
+            (Key::Num1, UiAction::AnswerEvent(false)),
+            (Key::Num2, UiAction::AnswerEvent(true)),
+            (Key::Enter, UiAction::DismissEvent),
+            (Key::Escape, UiAction::ToggleMenu),
+        ];
+        let mut actions: Vec<UiAction> = bindings
+            .into_iter()
+            .filter(|(key, _)| i.key_pressed(*key))
+            .map(|(_, action)| action)
+            .collect();
+        // Ctrl+Shift+Z redoes too, like most editors
+        if i.modifiers.command && i.key_pressed(Key::Z) {
+            actions.push(match i.modifiers.shift {
+                true => UiAction::Redo,
+                false => UiAction::Undo,
+            });
+        }
+        if i.modifiers.command && i.key_pressed(Key::Y) {
+            actions.push(UiAction::Redo);
+        }
+        actions
+    })
+}
@@ -1,54 +1,260 @@
+mod board_view;
+mod input;
+mod theme;
+
+use std::fs;
 use std::io::Cursor;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::Receiver;
 use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::Duration;
 
+use board_view::BoardView;
 use eframe::egui;
-use egui::{TopBottomPanel, Vec2};
+use egui::TopBottomPanel;
 use egui_extras::RetainedImage;
+use game_data::colony_code::ColonyCode;
+use game_data::colony_health::{ColonyHealth, HealthFactor, Trend};
+use game_data::commands::SandboxCommand;
+use game_data::console::{parse_command, CONSOLE_VOTER};
+use game_data::crash::CrashReport;
+use game_data::entities::animals::HungerLevel;
+use game_data::entities::display_spec::GlyphStyle;
+use game_data::entities::Species;
+use game_data::equilibrium::{Outlook, SpeciesOutlook};
+use game_data::field_guide::GuidePage;
+use game_data::game_board::Topology;
+use game_data::game_events::{EventChoice, EventPrompt};
+use game_data::interaction_matrix::{Appetite, InteractionMatrix, SPECIES_FILE};
+use game_data::locale::{self, tr, tr_name, tr_with, Language};
+use game_data::profiling::{Phase, PhaseTimings};
+use game_data::scenario::AUTOSAVE_FILE;
+use game_data::settings::{
+    ColorTheme, Difficulty, EventFrequency, SimulationSettings, Verbosity, SETTINGS_FILE,
+};
+use game_data::setup::{parse_count, Preset, SetupConfig, SetupError, STARTING_SPECIES};
+use game_data::snapshot::BoardSnapshot;
+use game_data::statistics::{EntityStats, GenerationAverages, StatsOrder, Trait};
+use game_data::structures::{build_cost, BUILDABLE};
+use game_data::tile_report::TileReport;
+use game_data::tutorial::{Step, Tutorial};
+use game_data::voting::Tally;
+use game_data::weather::Weather;
+use game_data::EntityID;
+use game_data::{GameUpdate, Simulation};
+use input::{read_shortcuts, UiAction, SHORTCUTS};
+use instant::Instant;
+use theme::{event_icon, health_color, LetterGlyphs, Palette};
 
 // Include the background image in our compiled exe
 const BACKGROUND_IMAGE: &[u8] = include_bytes!("../../../UI_Graphics/underwater.jpg");
 
+// How fast the simulation can be made to run, in ticks per second
+const MIN_TICK_RATE: f64 = 0.5;
+const MAX_TICK_RATE: f64 = 30.0;
+
+/// How many ticks apart the frames of a timelapse are.
+const TIMELAPSE_EVERY: usize = 5;
+
+// Where to look for scenario files to offer on the start screen
+const SCENARIO_DIR: &str = "scenarios";
+
 pub struct SeaGui {
     first_input: String,
     second_input: String,
     third_input: String,
-    game_info: Vec<f32>,
+    /// The board being set up, filled in bit by bit over the setup screens.
+    setup: SetupConfig,
+    /// A colony code typed in on the setup screen, and whether the player's been warned that it was made with
+    /// different species rules.
+    code_input: String,
+    code_warned: bool,
+    /// The code the running game was set up from, for sharing. None for games loaded from a scenario.
+    colony_code: Option<ColonyCode>,
     start: bool,
     get_dim: bool,
     get_animals: bool,
     run_simulation: bool,
     pause: bool,
-    event_msg: Vec<String>,
+    /// The event the player's being asked about, if there is one.
+    event: Option<EventPrompt>,
     event_res: String,
-    previous_disp: String,
+    /// When the event being asked about turned up, to count down to its default answer from.
+    event_since: Option<Instant>,
+    /// The running tally on the event, if it's being voted on.
+    votes: Option<Tally>,
+    previous_disp: Arc<BoardSnapshot>,
     background_img: Option<RetainedImage>,
-    tx: Sender<(String, Vec<String>, String, Sender<bool>)>,
-    rx: Receiver<(String, Vec<String>, String, Sender<bool>)>,
+    tx: Sender<GameUpdate>,
+    rx: Receiver<GameUpdate>,
     loop_tx: Option<Sender<bool>>,
-    entities_info: Vec<String>,
+    cmd_tx: Option<Sender<SandboxCommand>>,
+    /// The game that's running, if one's been started.
+    simulation: Option<Simulation>,
+    /// How every animal in the colony is doing, oldest first.
+    stats: Vec<EntityStats>,
+    achievements: Vec<String>,
+    show_achievements: bool,
+    /// The mission the colony's working on, if any, followed by the last few it finished.
+    missions: Vec<String>,
+    show_missions: bool,
+    scenarios: Vec<PathBuf>,
+    edit_mode: bool,
+    /// What gets placed when a tile is clicked in edit mode. None means we're erasing instead.
+    palette: Option<Species>,
+    show_console: bool,
+    show_menu: bool,
+    show_settings: bool,
+    settings: SimulationSettings,
+    /// Shown in the settings window after trying to save.
+    settings_status: String,
+    tick_rate: f64,
+    board_view: BoardView,
+    letter_glyphs: LetterGlyphs,
+    console_input: String,
+    console_log: Vec<String>,
+    /// Set if the game loop has crashed, until the player picks what to do about it.
+    crash: Option<CrashReport>,
+    show_performance: bool,
+    timings: PhaseTimings,
+    /// How every species' traits have averaged out, generation by generation, and which one's being charted.
+    traits: Vec<GenerationAverages>,
+    /// The longest running families, longest first.
+    dynasties: Vec<String>,
+    show_traits: bool,
+    /// Whether the field guide's open, and which species it's showing.
+    show_guide: bool,
+    guide_species: Species,
+    trait_species: Species,
+    trait_shown: Trait,
+    /// How the board's tiles fit together. Picked on the setup screen, then kept up to date by the game loop.
+    topology: Topology,
+    /// The region being shown, and every one that can be switched over to.
+    region: String,
+    regions: Vec<String>,
+    /// Set after asking to look at another region, so the new board gets picked up even while paused.
+    switching_region: bool,
+    /// Set after asking to rewind, so the old board gets picked up even though we're paused.
+    rewinding: bool,
+    /// If a timelapse is being recorded.
+    recording: bool,
+    /// What the weather's doing over the region being shown.
+    weather: Weather,
+    /// What the colony has gathered to build with.
+    resources: usize,
+    /// What gets built when a tile is clicked outside of edit mode, if anything.
+    building: Option<Species>,
+    /// How the animals in the Colony Info panel are narrowed down and laid out. Only one species is shown if one's
+    /// picked, and only animals matching the search.
+    info_species: Option<Species>,
+    info_order: StatsOrder,
+    info_grouped: bool,
+    info_search: String,
+    /// The animal picked out in the Colony Info panel for the camera to follow, if any.
+    following: Option<EntityID>,
+    /// Everything going on at the tile last clicked on, if it's being looked at.
+    tile_report: Option<TileReport>,
+    /// Set after clicking on a tile, so the report on it gets picked up even while paused.
+    inspecting: bool,
+    /// Whether there's a change made in the editor that can be taken back, or made again.
+    can_undo: bool,
+    can_redo: bool,
+    show_debug: bool,
+    /// How well the colony's doing, and which way it's been going.
+    health: Option<ColonyHealth>,
+    health_trend: Trend,
+    /// Every species whose population looks to be heading for trouble.
+    outlook: Vec<SpeciesOutlook>,
+    /// How many of each animal there's been over the last few ticks, for the population graph.
+    populations: Vec<(Species, Vec<(usize, usize)>)>,
+    /// How far the player's got, if they're playing the tutorial.
+    tutorial: Option<Tutorial>,
+    /// The species rules, which colony codes are made and checked against and the field guide is put together from.
+    rules: InteractionMatrix,
 }
 impl Default for SeaGui {
     fn default() -> Self {
         let (tx, rx) = std::sync::mpsc::channel();
+        let settings = SimulationSettings::load(SETTINGS_FILE);
+        locale::set_language(settings.language);
         Self {
             first_input: String::new(),
             second_input: String::new(),
             third_input: String::new(),
-            game_info: Vec::new(),
+            setup: SetupConfig::new(0, 0),
+            code_input: String::new(),
+            code_warned: false,
+            colony_code: None,
             start: true,
             get_dim: false,
             get_animals: false,
             run_simulation: false,
             pause: false,
-            event_msg: Vec::new(),
+            event: None,
             event_res: String::new(),
-            previous_disp: String::new(),
+            event_since: None,
+            votes: None,
+            previous_disp: Arc::default(),
             background_img: None,
             tx,
             rx,
             loop_tx: None,
-            entities_info: Vec::new(),
+            cmd_tx: None,
+            simulation: None,
+            stats: Vec::new(),
+            achievements: Vec::new(),
+            show_achievements: false,
+            missions: Vec::new(),
+            show_missions: false,
+            scenarios: find_scenarios(),
+            edit_mode: false,
+            palette: Some(Species::Fish),
+            show_console: false,
+            show_menu: false,
+            show_settings: false,
+            tick_rate: settings.tick_rate,
+            settings,
+            settings_status: String::new(),
+            board_view: BoardView::default(),
+            letter_glyphs: LetterGlyphs::default(),
+            console_input: String::new(),
+            console_log: Vec::new(),
+            crash: None,
+            show_performance: false,
+            timings: PhaseTimings::default(),
+            traits: Vec::new(),
+            dynasties: Vec::new(),
+            show_traits: false,
+            show_guide: false,
+            guide_species: Species::Fish,
+            trait_species: Species::Fish,
+            trait_shown: Trait::Speed,
+            topology: Topology::Square,
+            region: String::new(),
+            regions: Vec::new(),
+            switching_region: false,
+            rewinding: false,
+            recording: false,
+            weather: Weather::Calm,
+            resources: 0,
+            building: None,
+            info_species: None,
+            info_order: StatsOrder::Id,
+            info_grouped: false,
+            info_search: String::new(),
+            following: None,
+            tile_report: None,
+            inspecting: false,
+            can_undo: false,
+            can_redo: false,
+            show_debug: false,
+            health: None,
+            health_trend: Trend::Steady,
+            outlook: Vec::new(),
+            populations: Vec::new(),
+            tutorial: None,
+            rules: InteractionMatrix::load(SPECIES_FILE),
         }
     }
 }
@@ -57,6 +263,1016 @@ impl SeaGui {
         Default::default()
     }
 
+    /// Set up and start a game from a colony code, leaving the setup screens behind if it works out.
+    fn start_game(&mut self, code: ColonyCode, ctx: &egui::Context) {
+        let cmd_rx = self.new_command_channel();
+        match game_data::initialize_board(
+            &code,
+            self.settings.clone(),
+            self.tx.clone(),
+            cmd_rx,
+            ctx.clone(),
+        ) {
+            Ok(simulation) => {
+                self.simulation = Some(simulation);
+                self.colony_code = Some(code);
+                self.tutorial = None;
+                self.event_res = String::new();
+                self.get_dim = false;
+                self.get_animals = false;
+                self.run_simulation = true;
+            }
+            Err(e) => self.event_res = e.describe(),
+        }
+    }
+
+    /// Start a game from the colony code typed in on the setup screen. A code made with different species rules than
+    /// the ones in use only starts once the player's been warned and imports it again anyway.
+    fn import_code(&mut self, ctx: &egui::Context) {
+        let code: ColonyCode = match self.code_input.parse() {
+            Ok(code) => code,
+            Err(e) => {
+                self.event_res = tr_with("setup.code_invalid", &[("error", &e)]);
+                return;
+            }
+        };
+        if !code.same_species(&self.rules) && !self.code_warned {
+            self.code_warned = true;
+            self.event_res = tr("setup.code_species");
+            return;
+        }
+        self.settings.difficulty = code.difficulty;
+        self.setup = code.setup.clone();
+        self.code_input = String::new();
+        self.start_game(code, ctx);
+    }
+
+    /// Set up a fresh channel for sending commands to the sandbox, returning the end the sandbox should listen on.
+    /// Whatever game was running before gets shut down, since it's about to be replaced.
+    fn new_command_channel(&mut self) -> Receiver<SandboxCommand> {
+        self.send_command(SandboxCommand::Shutdown);
+        let (cmd_tx, cmd_rx) = std::sync::mpsc::channel();
+        self.cmd_tx = Some(cmd_tx);
+        cmd_rx
+    }
+
+    fn send_command(&self, command: SandboxCommand) {
+        if let Some(cmd_tx) = &self.cmd_tx {
+            let _ = cmd_tx.send(command);
+        }
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.pause = paused;
+        self.send_command(SandboxCommand::SetPaused(paused));
+    }
+
+    /// How many seconds the player has left to answer the event, if it's on a timer and they haven't answered it yet.
+    fn event_time_left(&self) -> Option<u64> {
+        let since = self.event_since?;
+        if !self.event_res.is_empty() {
+            return None;
+        }
+        // nothing's timed while the tutorial's being played
+        let window = self
+            .settings
+            .event_window()
+            .filter(|_| self.tutorial.is_none())?;
+        Some(window.saturating_sub(since.elapsed().as_secs()))
+    }
+
+    /// The player took too long, so answer the event for them and carry on. If it was being voted on, whatever won
+    /// the vote gets picked. What happened goes in the console.
+    fn time_out_event(&mut self, event: &EventPrompt) {
+        let voted = self
+            .votes
+            .take()
+            .and_then(|votes| Some((votes.winner(event.default)?, votes.counts())));
+        let answer = voted.map_or(event.default, |(choice, _)| choice);
+        let choice = &event.choices[usize::from(answer)];
+        self.console_log.push(match voted {
+            Some((_, [first, second])) => tr_with(
+                "event.vote_won",
+                &[
+                    ("first", &first),
+                    ("second", &second),
+                    ("choice", &choice.label),
+                    ("outcome", &choice.outcome),
+                ],
+            ),
+            None => tr_with(
+                "event.timed_out",
+                &[("choice", &choice.label), ("outcome", &choice.outcome)],
+            ),
+        });
+        self.apply_action(UiAction::AnswerEvent(answer));
+        self.apply_action(UiAction::DismissEvent);
+    }
+
+    /// Carry out something the player asked for. Buttons and keyboard shortcuts both go through here,
+    /// so the simulation responds the same way no matter how it was asked.
+    fn apply_action(&mut self, action: UiAction) {
+        match action {
+            UiAction::TogglePause => {
+                self.set_paused(!self.pause);
+                if self.pause {
+                    self.complete_step(Step::Pause);
+                }
+                if !self.pause {
+                    self.edit_mode = false;
+                }
+            }
+            UiAction::SpeedUp | UiAction::SlowDown => {
+                self.tick_rate = if action == UiAction::SpeedUp {
+                    self.tick_rate * 1.5
+                } else {
+                    self.tick_rate / 1.5
+                }
+                .clamp(MIN_TICK_RATE, MAX_TICK_RATE);
+                self.send_command(SandboxCommand::SetTickRate(self.tick_rate));
+            }
+            UiAction::Rewind => {
+                // the sandbox pauses itself once it's rewound
+                self.pause = true;
+                self.rewinding = true;
+                self.send_command(SandboxCommand::Rewind(1));
+            }
+            UiAction::Screenshot => {
+                self.send_command(SandboxCommand::Screenshot(PathBuf::from(".")))
+            }
+            UiAction::ToggleTimelapse => {
+                self.recording = !self.recording;
+                self.send_command(match self.recording {
+                    true => SandboxCommand::StartTimelapse(TIMELAPSE_EVERY),
+                    false => SandboxCommand::StopTimelapse(PathBuf::from(".")),
+                });
+            }
+            UiAction::Pan(delta) => self.board_view.pan(delta),
+            UiAction::AnswerEvent(choice) => {
+                // when everyone's voting, the player only gets a vote like anyone else
+                if self.votes.is_some() {
+                    self.send_command(SandboxCommand::Vote(String::from(CONSOLE_VOTER), choice));
+                // only the first answer counts
+                } else if let (Some(event), true) = (&self.event, self.event_res.is_empty()) {
+                    self.event_res = event.choices[usize::from(choice)].outcome.clone();
+                    let _ = self.loop_tx.clone().unwrap().send(choice);
+                    self.complete_step(Step::Event);
+                }
+            }
+            UiAction::DismissEvent => {
+                if self.event.is_some() && !self.event_res.is_empty() {
+                    self.event = None;
+                    self.event_res = String::new();
+                    let _ = self.loop_tx.clone().unwrap().send(true);
+                }
+            }
+            UiAction::ToggleMenu => self.show_menu = !self.show_menu,
+            UiAction::Undo if self.edit_mode && self.pause => {
+                self.send_command(SandboxCommand::Undo)
+            }
+            UiAction::Redo if self.edit_mode && self.pause => {
+                self.send_command(SandboxCommand::Redo)
+            }
+            UiAction::Undo | UiAction::Redo => (),
+        }
+    }
+
+    /// Let the player change their preferences, saving them for next time.
+    fn render_settings(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_settings;
+        egui::Window::new(tr("settings.title"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("settings").num_columns(2).show(ui, |ui| {
+                    ui.label(tr("settings.speed"));
+                    ui.add(egui::Slider::new(
+                        &mut self.settings.tick_rate,
+                        MIN_TICK_RATE..=MAX_TICK_RATE,
+                    ));
+                    ui.end_row();
+
+                    ui.label(tr("settings.events"));
+                    egui::ComboBox::from_id_source("event_frequency")
+                        .selected_text(tr_name("frequency", self.settings.event_frequency.name()))
+                        .show_ui(ui, |ui| {
+                            for freq in EventFrequency::ALL {
+                                ui.selectable_value(
+                                    &mut self.settings.event_frequency,
+                                    freq,
+                                    tr_name("frequency", freq.name()),
+                                );
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label(tr("settings.autosave"))
+                        .on_hover_text(tr("settings.autosave_hint"));
+                    ui.add(
+                        egui::DragValue::new(&mut self.settings.autosave_interval)
+                            .suffix(format!(" {}", tr("settings.ticks"))),
+                    );
+                    ui.end_row();
+
+                    ui.label(tr("settings.event_timer"))
+                        .on_hover_text(tr("settings.event_timer_hint"));
+                    ui.add(
+                        egui::DragValue::new(&mut self.settings.event_timer)
+                            .suffix(format!(" {}", tr("settings.seconds"))),
+                    );
+                    ui.end_row();
+
+                    ui.label(tr("settings.event_voting"))
+                        .on_hover_text(tr("settings.event_voting_hint"));
+                    ui.checkbox(&mut self.settings.event_voting, "");
+                    ui.end_row();
+
+                    ui.label(tr("settings.serve_address"))
+                        .on_hover_text(tr("settings.serve_address_hint"));
+                    ui.text_edit_singleline(&mut self.settings.serve_address);
+                    ui.end_row();
+
+                    ui.label(tr("settings.notifications"));
+                    egui::ComboBox::from_id_source("verbosity")
+                        .selected_text(tr_name("verbosity", self.settings.verbosity.name()))
+                        .show_ui(ui, |ui| {
+                            for verbosity in Verbosity::ALL {
+                                ui.selectable_value(
+                                    &mut self.settings.verbosity,
+                                    verbosity,
+                                    tr_name("verbosity", verbosity.name()),
+                                );
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label(tr("settings.theme"));
+                    ui.horizontal(|ui| {
+                        for theme in ColorTheme::ALL {
+                            ui.radio_value(
+                                &mut self.settings.theme,
+                                theme,
+                                tr_name("theme", theme.name()),
+                            );
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label(tr("settings.letter_glyphs"))
+                        .on_hover_text(tr("settings.letter_glyphs_hint"));
+                    ui.checkbox(&mut self.settings.letter_glyphs, "");
+                    ui.end_row();
+
+                    ui.label(tr("settings.language"));
+                    egui::ComboBox::from_id_source("language")
+                        .selected_text(self.settings.language.native_name())
+                        .show_ui(ui, |ui| {
+                            for language in Language::ALL {
+                                ui.selectable_value(
+                                    &mut self.settings.language,
+                                    language,
+                                    language.native_name(),
+                                );
+                            }
+                        });
+                    ui.end_row();
+                });
+                ui.separator();
+                if ui.button(tr("settings.save")).clicked() {
+                    self.tick_rate = self.settings.tick_rate;
+                    locale::set_language(self.settings.language);
+                    self.send_command(SandboxCommand::ApplySettings(self.settings.clone()));
+                    self.settings_status = match self.settings.save(SETTINGS_FILE) {
+                        Ok(()) => tr("settings.saved"),
+                        Err(e) => tr_with("settings.save_failed", &[("error", &e)]),
+                    };
+                }
+                if !self.settings_status.is_empty() {
+                    ui.label(&self.settings_status);
+                }
+            });
+        self.show_settings = open;
+    }
+
+    /// The pause menu, listing every keyboard shortcut.
+    fn render_menu(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        egui::Window::new(tr("menu.title"))
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.vertical_centered_justified(|ui| {
+                    if ui.button(tr("menu.resume")).clicked() {
+                        self.show_menu = false;
+                        if self.pause {
+                            self.apply_action(UiAction::TogglePause);
+                        }
+                    }
+                    if ui.button(tr("menu.quit")).clicked() {
+                        frame.close();
+                    }
+                });
+                ui.separator();
+                egui::Grid::new("shortcuts").show(ui, |ui| {
+                    for (keys, action) in SHORTCUTS {
+                        ui.strong(keys);
+                        ui.label(tr(action));
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+
+    /// Developer console for poking at the running simulation. Toggled with the backtick key.
+    fn render_console(&mut self, ctx: &egui::Context) {
+        egui::Window::new(tr("console.title"))
+            .open(&mut self.show_console)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for line in &self.console_log {
+                            ui.monospace(line);
+                        }
+                    });
+                let input = ui.add(
+                    egui::TextEdit::singleline(&mut self.console_input)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(f32::INFINITY),
+                );
+                // the key that opens the console shouldn't end up in it
+                self.console_input.retain(|c| c != '`');
+                if input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    let line = std::mem::take(&mut self.console_input);
+                    self.console_log.push(format!("> {line}"));
+                    match parse_command(&line) {
+                        Ok(command) => {
+                            if let SandboxCommand::SetPaused(paused) = command {
+                                self.pause = paused;
+                            }
+                            if let Some(cmd_tx) = &self.cmd_tx {
+                                let _ = cmd_tx.send(command);
+                            }
+                        }
+                        Err(msg) => self.console_log.extend(msg.lines().map(String::from)),
+                    }
+                    input.request_focus();
+                }
+            });
+    }
+
+    /// Shown when the game loop has crashed. Lets the player pick up from the last autosave, if there is one.
+    fn render_crash(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let Some(report) = self.crash.clone() else {
+            return;
+        };
+        egui::Window::new(tr("crash.title"))
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(tr_with("crash.tick", &[("tick", &report.tick)]));
+                ui.monospace(&report.message);
+                ui.collapsing(tr("crash.backtrace"), |ui| {
+                    egui::ScrollArea::vertical()
+                        .max_height(300.0)
+                        .show(ui, |ui| ui.monospace(&report.backtrace));
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if Path::new(AUTOSAVE_FILE).exists() && ui.button(tr("crash.reload")).clicked()
+                    {
+                        let cmd_rx = self.new_command_channel();
+                        match game_data::initialize_from_scenario(
+                            AUTOSAVE_FILE,
+                            self.settings.clone(),
+                            self.tx.clone(),
+                            cmd_rx,
+                            ctx.clone(),
+                        ) {
+                            Ok(simulation) => {
+                                self.simulation = Some(simulation);
+                                self.tutorial = None;
+                                self.board_view = BoardView::default();
+                                self.event = None;
+                                self.event_res = String::new();
+                                self.pause = false;
+                                self.crash = None;
+                            }
+                            Err(e) => {
+                                self.event_res = tr_with("crash.reload_failed", &[("error", &e)])
+                            }
+                        }
+                    }
+                    if ui.button(tr("menu.quit")).clicked() {
+                        frame.close();
+                    }
+                });
+                if !self.event_res.is_empty() {
+                    ui.label(&self.event_res);
+                }
+            });
+    }
+
+    /// How long each part of the game loop has been taking, along with a histogram of the last few ticks.
+    fn render_performance(&mut self, ctx: &egui::Context) {
+        let to_ms = |d: std::time::Duration| format!("{:.2}", d.as_secs_f64() * 1000.0);
+        egui::Window::new(tr("performance.title"))
+            .open(&mut self.show_performance)
+            .show(ctx, |ui| {
+                ui.label(tr_with(
+                    "performance.last_tick",
+                    &[("ms", &to_ms(self.timings.last_total()))],
+                ));
+                egui::Grid::new("timings").striped(true).show(ui, |ui| {
+                    for heading in ["phase", "last", "mean", "p95", "max"] {
+                        ui.strong(tr(&format!("performance.{heading}")));
+                    }
+                    ui.strong("");
+                    ui.end_row();
+                    for phase in Phase::ALL {
+                        let Some(stats) = self.timings.stats(phase) else {
+                            continue;
+                        };
+                        ui.label(tr_name("phase", phase.name()));
+                        for time in [stats.last, stats.mean, stats.p95, stats.max] {
+                            ui.monospace(to_ms(time));
+                        }
+                        let histogram = self.timings.histogram(phase, 20);
+                        let tallest = histogram.iter().copied().max().unwrap_or(0).max(1);
+                        let (response, painter) =
+                            ui.allocate_painter(egui::vec2(100.0, 20.0), egui::Sense::hover());
+                        let bar_width = response.rect.width() / histogram.len() as f32;
+                        for (i, count) in histogram.into_iter().enumerate() {
+                            let height = response.rect.height() * count as f32 / tallest as f32;
+                            let left = response.rect.left() + i as f32 * bar_width;
+                            painter.rect_filled(
+                                egui::Rect::from_min_max(
+                                    egui::pos2(left, response.rect.bottom() - height),
+                                    egui::pos2(left + bar_width, response.rect.bottom()),
+                                ),
+                                egui::Rounding::none(),
+                                egui::Color32::LIGHT_BLUE,
+                            );
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+
+    /// Window charting how one of a species' traits has averaged out in each generation, one bar per generation, along
+    /// with the longest running families.
+    fn render_traits(&mut self, ctx: &egui::Context) {
+        egui::Window::new(tr("traits.title"))
+            .open(&mut self.show_traits)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("trait_species")
+                        .selected_text(tr_name("species", self.trait_species.name()))
+                        .show_ui(ui, |ui| {
+                            let mut seen: Vec<Species> = vec![];
+                            for averages in &self.traits {
+                                if !seen.contains(&averages.species) {
+                                    seen.push(averages.species);
+                                }
+                            }
+                            for species in seen {
+                                ui.selectable_value(
+                                    &mut self.trait_species,
+                                    species,
+                                    tr_name("species", species.name()),
+                                );
+                            }
+                        });
+                    egui::ComboBox::from_id_source("trait_shown")
+                        .selected_text(tr_name("trait", self.trait_shown.name()))
+                        .show_ui(ui, |ui| {
+                            for shown in Trait::ALL {
+                                ui.selectable_value(
+                                    &mut self.trait_shown,
+                                    shown,
+                                    tr_name("trait", shown.name()),
+                                );
+                            }
+                        });
+                });
+                ui.collapsing(tr("traits.dynasties"), |ui| {
+                    if self.dynasties.is_empty() {
+                        ui.label(tr("traits.no_children"));
+                    }
+                    for dynasty in &self.dynasties {
+                        ui.label(dynasty);
+                    }
+                });
+                let series: Vec<(usize, f64)> = self
+                    .traits
+                    .iter()
+                    .filter(|a| a.species == self.trait_species)
+                    .map(|a| (a.generation, a.mean(self.trait_shown)))
+                    .collect();
+                let (Some(first), Some(last)) = (series.first(), series.last()) else {
+                    ui.label(tr("traits.unseen"));
+                    return;
+                };
+                ui.label(tr_with(
+                    "traits.range",
+                    &[
+                        ("first", &first.0),
+                        ("last", &last.0),
+                        ("from", &format!("{:.2}", first.1)),
+                        ("to", &format!("{:.2}", last.1)),
+                    ],
+                ));
+                let tallest = series.iter().map(|(_, mean)| *mean).fold(0.0, f64::max);
+                let (response, painter) =
+                    ui.allocate_painter(egui::vec2(300.0, 120.0), egui::Sense::hover());
+                let bar_width = response.rect.width() / series.len() as f32;
+                for (i, (_, mean)) in series.iter().enumerate() {
+                    let height = match tallest > 0.0 {
+                        true => response.rect.height() * (*mean / tallest) as f32,
+                        false => 0.0,
+                    };
+                    let left = response.rect.left() + i as f32 * bar_width;
+                    painter.rect_filled(
+                        egui::Rect::from_min_max(
+                            egui::pos2(left, response.rect.bottom() - height),
+                            egui::pos2(left + bar_width * 0.9, response.rect.bottom()),
+                        ),
+                        egui::Rounding::none(),
+                        egui::Color32::LIGHT_GREEN,
+                    );
+                }
+            });
+    }
+
+    /// Everything going on at the tile that was clicked on, kept up to date every tick until it's closed.
+    fn render_tile_report(&mut self, ctx: &egui::Context) {
+        let Some(report) = &self.tile_report else {
+            return;
+        };
+        let style = self.glyph_style();
+        let mut open = true;
+        egui::Window::new(tr("tile.title"))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Grid::new("tile_report").show(ui, |ui| {
+                    let mut row = |name: &str, value: String| {
+                        ui.label(tr(name));
+                        ui.label(value);
+                        ui.end_row();
+                    };
+                    row(
+                        "tile.position",
+                        format!("{}, {}", report.pos.x, report.pos.y),
+                    );
+                    row(
+                        "tile.occupant",
+                        report.occupant.map_or(tr("tile.nothing"), |species| {
+                            let glyph = species.display_spec().glyph(style);
+                            format!("{glyph} {}", tr_name("species", species.name()))
+                        }),
+                    );
+                    row("tile.terrain", tr_name("terrain", report.terrain.name()));
+                    row("tile.light", format!("{:.2}", report.light));
+                    row("tile.nutrients", format!("{:.2}", report.nutrients));
+                    row("tile.scent", format!("{:.2}", report.scent));
+                    row("tile.pollution", format!("{:.2}", report.pollution));
+                    row(
+                        "tile.current",
+                        match report.downstream {
+                            Some(to) => tr_with(
+                                "tile.downstream",
+                                &[
+                                    ("current", &tr_name("current", report.current.name())),
+                                    ("x", &to.x),
+                                    ("y", &to.y),
+                                ],
+                            ),
+                            None => tr_name("current", report.current.name()),
+                        },
+                    );
+                });
+            });
+        if !open {
+            self.tile_report = None;
+            self.send_command(SandboxCommand::Inspect(None));
+        }
+    }
+
+    /// The field guide, with a page on whichever species is picked. Pages are put together from the species rules in
+    /// use every time they're shown, so they're never out of date.
+    fn render_guide(&mut self, ctx: &egui::Context) {
+        let (rows, cols) = self.previous_disp.dims();
+        let page = GuidePage::new(self.guide_species, &self.rules, rows * cols);
+        let glyph = page.species.display_spec().glyph(self.glyph_style());
+        egui::Window::new(tr("guide.title"))
+            .open(&mut self.show_guide)
+            .show(ctx, |ui| {
+                egui::ComboBox::from_id_source("guide_species")
+                    .selected_text(tr_name("species", self.guide_species.name()))
+                    .show_ui(ui, |ui| {
+                        for species in Species::all() {
+                            ui.selectable_value(
+                                &mut self.guide_species,
+                                species,
+                                tr_name("species", species.name()),
+                            );
+                        }
+                    });
+                ui.heading(format!(
+                    "{glyph} {}",
+                    tr_name("species", page.species.name())
+                ));
+                egui::Grid::new("guide_stats").show(ui, |ui| {
+                    for (name, value) in &page.stats {
+                        ui.label(tr_name("guide.stat", name));
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+                let diet = |entries: &[(Species, Appetite)]| {
+                    entries
+                        .iter()
+                        .map(|(s, a)| {
+                            let when = tr_name("appetite", a.name());
+                            format!("{} ({when})", tr_name("species", s.name()))
+                        })
+                        .collect()
+                };
+                render_guide_list(ui, tr("guide.eats"), diet(&page.eats));
+                render_guide_list(ui, tr("guide.eaten_by"), diet(&page.eaten_by));
+                let mates = page
+                    .mates_with
+                    .iter()
+                    .map(|(s, p)| {
+                        let who = tr_name("pairing", p.name());
+                        format!("{} ({who})", tr_name("species", s.name()))
+                    })
+                    .collect();
+                render_guide_list(ui, tr("guide.mates_with"), mates);
+                ui.label(tr_with("guide.food_value", &[("value", &page.food_value)]));
+                if let Some(cap) = page.population_cap {
+                    ui.label(tr_with("guide.cap", &[("cap", &cap)]));
+                }
+                ui.separator();
+                if let Some(wander) = page.wander {
+                    ui.label(tr_with(
+                        "guide.wander",
+                        &[("style", &tr_name("wander", wander.name()))],
+                    ));
+                }
+                let temperament: Vec<String> = page
+                    .temperament
+                    .iter()
+                    .map(|q| tr_name("personality", q))
+                    .collect();
+                if !temperament.is_empty() {
+                    ui.label(tr_with(
+                        "guide.temperament",
+                        &[("quirks", &temperament.join(", "))],
+                    ));
+                }
+                for behavior in &page.behaviors {
+                    ui.label(format!("• {}", tr_name("guide.behavior", behavior)));
+                }
+            });
+    }
+
+    /// Whether species are being drawn as emoji or letters.
+    fn glyph_style(&self) -> GlyphStyle {
+        match self.settings.letter_glyphs {
+            true => GlyphStyle::Ascii,
+            false => GlyphStyle::Emoji,
+        }
+    }
+
+    /// Let the tutorial know the player's done something it asks for, if it's being played.
+    fn complete_step(&mut self, step: Step) {
+        if let Some(tutorial) = &mut self.tutorial {
+            tutorial.complete(step);
+        }
+    }
+
+    /// Point out part of the screen to the player, with a highlight around it and a tooltip explaining it, if it's
+    /// what the tutorial's up to.
+    fn point_out(&self, ctx: &egui::Context, step: Step, rect: egui::Rect) {
+        let Some(tutorial) = &self.tutorial else {
+            return;
+        };
+        if tutorial.current(self.event.is_some()) != Some(step) {
+            return;
+        }
+        let color = ctx.style().visuals.warn_fg_color;
+        let layer = egui::LayerId::new(egui::Order::Tooltip, egui::Id::new("tutorial"));
+        ctx.layer_painter(layer).rect_stroke(
+            rect.expand(3.0),
+            egui::Rounding::same(4.0),
+            egui::Stroke::new(3.0, color),
+        );
+        let key = match (step, &self.event) {
+            (Step::Event, None) => String::from("tutorial.event_wait"),
+            _ => format!("tutorial.{}", step.name()),
+        };
+        let below = rect.left_bottom() + egui::vec2(0.0, 6.0);
+        egui::show_tooltip_at(ctx, egui::Id::new("tutorial_tip"), Some(below), |ui| {
+            ui.label(
+                egui::RichText::new(tr_with(
+                    "tutorial.step",
+                    &[
+                        ("step", &(tutorial.progress() + 1)),
+                        ("steps", &Step::ALL.len()),
+                    ],
+                ))
+                .color(color),
+            );
+            ui.label(tr(&key));
+        });
+        // the highlight's drawn on top of everything else, so it has to be redrawn every frame
+        ctx.request_repaint();
+    }
+
+    /// Let the player know they've made it through the tutorial.
+    fn render_tutorial_end(&mut self, ctx: &egui::Context) {
+        egui::Window::new(tr("tutorial.title"))
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(tr("tutorial.done"));
+                if ui.button(tr("tutorial.close")).clicked() {
+                    self.tutorial = None;
+                }
+            });
+    }
+
+    /// How many of each animal there's been over the last few ticks, with a line for each species. Hovering over it
+    /// shows the counts from that tick.
+    fn render_population_graph(&mut self, ui: &mut egui::Ui) {
+        let shown: Vec<&(Species, Vec<(usize, usize)>)> = self
+            .populations
+            .iter()
+            .filter(|(_, series)| series.iter().any(|(_, count)| *count > 0))
+            .collect();
+        let Some(ticks) = shown.iter().map(|(_, series)| series.len()).max() else {
+            return;
+        };
+        let tallest = shown
+            .iter()
+            .flat_map(|(_, series)| series.iter().map(|(_, count)| *count))
+            .max()
+            .unwrap_or(1);
+        ui.label(tr("graph.title"));
+        let (response, painter) =
+            ui.allocate_painter(egui::vec2(ui.available_width(), 80.0), egui::Sense::hover());
+        let rect = response.rect;
+        painter.rect_stroke(rect, egui::Rounding::none(), ui.visuals().window_stroke());
+        let step = rect.width() / ticks.saturating_sub(1).max(1) as f32;
+        let point = |i: usize, count: usize| {
+            egui::pos2(
+                rect.left() + i as f32 * step,
+                rect.bottom() - rect.height() * count as f32 / tallest as f32,
+            )
+        };
+        for (species, series) in &shown {
+            let [r, g, b] = species.display_spec().color;
+            let color = egui::Color32::from_rgb(r, g, b);
+            let points = series
+                .iter()
+                .enumerate()
+                .map(|(i, (_, count))| point(i, *count))
+                .collect();
+            painter.add(egui::Shape::line(points, egui::Stroke::new(2.0, color)));
+        }
+        self.point_out(ui.ctx(), Step::Graph, rect);
+        let Some(hovered) = response.hover_pos() else {
+            return;
+        };
+        let i = (((hovered.x - rect.left()) / step).round() as usize).min(ticks - 1);
+        painter.vline(
+            point(i, 0).x,
+            rect.y_range(),
+            egui::Stroke::new(1.0, ui.visuals().weak_text_color()),
+        );
+        let counts: Vec<String> = shown
+            .iter()
+            .filter_map(|(species, series)| {
+                let (_, count) = series.get(i)?;
+                Some(format!("{} {count}", tr_name("species", species.name())))
+            })
+            .collect();
+        let tick = shown
+            .iter()
+            .find_map(|(_, series)| series.get(i))
+            .map_or(0, |(tick, _)| *tick);
+        response.on_hover_text(tr_with(
+            "graph.hover",
+            &[("tick", &tick), ("counts", &counts.join(", "))],
+        ));
+        self.complete_step(Step::Graph);
+    }
+
+    /// Window for picking what to place on the board while editing.
+    fn render_palette(&mut self, ctx: &egui::Context) {
+        egui::Window::new(tr("editor.title"))
+            .default_pos(egui::Pos2::new(0.0, 40.0))
+            .show(ctx, |ui| {
+                ui.label(tr("editor.hint"));
+                for species in Species::all() {
+                    let glyph = species.display_spec().glyph(self.glyph_style());
+                    ui.selectable_value(
+                        &mut self.palette,
+                        Some(species),
+                        format!("{glyph} {}", tr_name("species", species.name())),
+                    );
+                }
+                ui.selectable_value(&mut self.palette, None, tr("editor.erase"));
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let undo = egui::Button::new(tr("editor.undo"));
+                    if ui.add_enabled(self.can_undo, undo).clicked() {
+                        self.send_command(SandboxCommand::Undo);
+                    }
+                    let redo = egui::Button::new(tr("editor.redo"));
+                    if ui.add_enabled(self.can_redo, redo).clicked() {
+                        self.send_command(SandboxCommand::Redo);
+                    }
+                });
+            });
+    }
+
+    /// Pick a structure to build with the colony's resources, shown under the colony info.
+    /// A big bar for the colony's health score, with an arrow for which way it's going and a breakdown of what went into
+    /// it underneath.
+    fn render_health_gauge(&self, ui: &mut egui::Ui) {
+        let Some(health) = &self.health else {
+            return;
+        };
+        let trend = tr_name("trend", self.health_trend.name());
+        let text = tr_with(
+            "health.score",
+            &[
+                ("score", &health.score),
+                ("arrow", &trend_arrow(self.health_trend)),
+            ],
+        );
+        ui.add(
+            egui::ProgressBar::new(health.score as f32 / 100.0)
+                .fill(health_color(health.score))
+                .desired_width(ui.available_width())
+                .text(egui::RichText::new(text).font(egui::FontId::proportional(20.0))),
+        )
+        .on_hover_text(trend);
+        ui.collapsing(tr("health.breakdown"), |ui| {
+            for factor in HealthFactor::ALL {
+                let points = health.factor(factor);
+                ui.add(
+                    egui::ProgressBar::new(points as f32 / 100.0)
+                        .fill(health_color(points))
+                        .text(format!("{} {points}", tr_name("health", factor.name()))),
+                );
+            }
+            ui.label(tr_with(
+                "health.recent_deaths",
+                &[("deaths", &health.recent_deaths)],
+            ));
+        });
+    }
+
+    /// A warning for every species whose population looks like it can't keep going the way it is.
+    fn render_outlook(&self, ui: &mut egui::Ui) {
+        let color = ui.visuals().warn_fg_color;
+        for outlook in &self.outlook {
+            let (key, ticks) = match outlook.outlook {
+                Outlook::Extinction(ticks) => ("outlook.extinction", ticks),
+                Outlook::Explosion(ticks) => ("outlook.explosion", ticks),
+                Outlook::Stable => continue,
+            };
+            let species = tr_name("species", outlook.species.name());
+            let growth = format!("{:+.1}", outlook.growth * 100.0);
+            ui.colored_label(
+                color,
+                tr_with(key, &[("species", &species), ("ticks", &ticks)]),
+            )
+            .on_hover_text(tr_with(
+                "outlook.growth",
+                &[("population", &outlook.population), ("growth", &growth)],
+            ));
+        }
+    }
+
+    fn render_build_menu(&mut self, ui: &mut egui::Ui) {
+        ui.label(tr_with(
+            "build.resources",
+            &[("resources", &self.resources)],
+        ));
+        ui.collapsing(tr("build.title"), |ui| {
+            ui.label(tr("build.hint"));
+            for species in BUILDABLE {
+                let glyph = species.display_spec().glyph(self.glyph_style());
+                // everything in BUILDABLE has a cost
+                let cost = build_cost(species).unwrap_or_default();
+                ui.add_enabled_ui(cost <= self.resources, |ui| {
+                    ui.selectable_value(
+                        &mut self.building,
+                        Some(species),
+                        format!("{glyph} {} ({cost})", tr_name("species", species.name())),
+                    );
+                });
+            }
+            ui.selectable_value(&mut self.building, None, tr("build.nothing"));
+        });
+    }
+
+    /// A few lines for every animal in the colony: how healthy and hungry it is, how far through its life it's got, and
+    /// what it's up to.
+    fn render_entity_stats(&mut self, ui: &mut egui::Ui) {
+        let mut seen: Vec<Species> = vec![];
+        for s in &self.stats {
+            if !seen.contains(&s.species) {
+                seen.push(s.species);
+            }
+        }
+        seen.sort_by_key(|species| species.name());
+        ui.add(egui::TextEdit::singleline(&mut self.info_search).hint_text(tr("info.search")));
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("info_species")
+                .selected_text(match self.info_species {
+                    Some(species) => tr_name("species", species.name()),
+                    None => tr("info.all"),
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.info_species, None, tr("info.all"));
+                    for species in &seen {
+                        ui.selectable_value(
+                            &mut self.info_species,
+                            Some(*species),
+                            tr_name("species", species.name()),
+                        );
+                    }
+                });
+            egui::ComboBox::from_id_source("info_order")
+                .selected_text(tr_name("order", self.info_order.name()))
+                .show_ui(ui, |ui| {
+                    for order in StatsOrder::ALL {
+                        ui.selectable_value(
+                            &mut self.info_order,
+                            order,
+                            tr_name("order", order.name()),
+                        );
+                    }
+                });
+            ui.checkbox(&mut self.info_grouped, tr("info.group"));
+        });
+
+        let mut shown: Vec<EntityStats> = self
+            .stats
+            .iter()
+            .filter(|s| self.info_species.is_none_or(|species| s.species == species))
+            .filter(|s| s.matches(&self.info_search))
+            .cloned()
+            .collect();
+        self.info_order.sort(&mut shown);
+        // clicking on an animal follows it around, and clicking on it again lets it go
+        let mut clicked = None;
+        let mut row = |ui: &mut egui::Ui, s: &EntityStats| {
+            if render_stat_row(ui, s, self.following == Some(s.id)) {
+                clicked = Some(s.id);
+            }
+        };
+        let list = egui::ScrollArea::vertical().show(ui, |ui| {
+            if !self.info_grouped {
+                shown.iter().for_each(|s| row(ui, s));
+                return;
+            }
+            for species in seen {
+                let members: Vec<&EntityStats> =
+                    shown.iter().filter(|s| s.species == species).collect();
+                if members.is_empty() {
+                    continue;
+                }
+                let title = format!(
+                    "{} {} ({})",
+                    members[0].glyph,
+                    tr_name("species", species.name()),
+                    members.len()
+                );
+                egui::CollapsingHeader::new(title)
+                    .id_source(species.name())
+                    .default_open(true)
+                    .show(ui, |ui| members.into_iter().for_each(|s| row(ui, s)));
+            }
+        });
+        self.point_out(ui.ctx(), Step::ColonyInfo, list.inner_rect);
+        if let Some(id) = clicked {
+            self.following = (self.following != Some(id)).then_some(id);
+            self.send_command(SandboxCommand::Spotlight(self.following));
+            if self.following.is_some() {
+                self.complete_step(Step::ColonyInfo);
+            }
+        }
+    }
+
     pub fn render_top_panel(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
@@ -70,17 +1286,110 @@ impl SeaGui {
                     if close_btn.clicked() {
                         frame.close();
                     }
-                    if !self.pause {
-                        let pause_btn = ui.add(egui::Button::new("⏸"));
-                        if pause_btn.clicked() {
-                            self.pause = true;
-                            ctx.request_repaint();
+                    let settings_btn = ui.add(egui::Button::new("⚙"));
+                    if settings_btn.clicked() {
+                        self.show_settings = !self.show_settings;
+                        self.settings_status = String::new();
+                    }
+                    let debug_btn = ui.add(egui::SelectableLabel::new(self.show_debug, "🐞"));
+                    if debug_btn.on_hover_text(tr("top.debug")).clicked() {
+                        self.show_debug = !self.show_debug;
+                        self.send_command(SandboxCommand::SetDebugOverlay(self.show_debug));
+                    }
+                    let guide_btn = ui.add(egui::SelectableLabel::new(self.show_guide, "📖"));
+                    if guide_btn.on_hover_text(tr("top.guide")).clicked() {
+                        self.show_guide = !self.show_guide;
+                    }
+                    let traits_btn = ui.add(egui::SelectableLabel::new(self.show_traits, "🧬"));
+                    if traits_btn.clicked() {
+                        self.show_traits = !self.show_traits;
+                    }
+                    let performance_btn =
+                        ui.add(egui::SelectableLabel::new(self.show_performance, "📊"));
+                    if performance_btn.clicked() {
+                        self.show_performance = !self.show_performance;
+                    }
+                    let achievements_btn = ui.add(egui::Button::new("🏆"));
+                    if achievements_btn.clicked() {
+                        self.show_achievements = !self.show_achievements;
+                    }
+                    let missions_btn = ui.add(egui::SelectableLabel::new(self.show_missions, "📜"));
+                    if missions_btn.clicked() {
+                        self.show_missions = !self.show_missions;
+                    }
+                    let edit_btn = ui.add(egui::SelectableLabel::new(self.edit_mode, "✏"));
+                    if edit_btn.clicked() {
+                        self.edit_mode = !self.edit_mode;
+                        // editing only makes sense while the board is holding still
+                        if self.edit_mode {
+                            self.set_paused(true);
                         }
+                        ctx.request_repaint();
+                    }
+                    let faster_btn = ui.add(egui::Button::new("⏩"));
+                    if faster_btn.clicked() {
+                        self.apply_action(UiAction::SpeedUp);
+                    }
+                    let pause_btn =
+                        ui.add(egui::Button::new(if self.pause { "▶" } else { "⏸" }));
+                    self.point_out(ctx, Step::Pause, pause_btn.rect);
+                    // until the event shows up, all there is to do is wait for it
+                    if self.event.is_none() {
+                        self.point_out(ctx, Step::Event, pause_btn.rect);
+                    }
+                    if pause_btn.clicked() {
+                        self.apply_action(UiAction::TogglePause);
+                        ctx.request_repaint();
+                    }
+                    let rewind_btn = ui.add(egui::Button::new("⏮"));
+                    if rewind_btn.on_hover_text(tr("top.rewind")).clicked() {
+                        self.apply_action(UiAction::Rewind);
+                        ctx.request_repaint();
+                    }
+                    let screenshot_btn = ui.add(egui::Button::new("📷"));
+                    if screenshot_btn.on_hover_text(tr("top.screenshot")).clicked() {
+                        self.apply_action(UiAction::Screenshot);
+                    }
+                    if let Some(code) = &self.colony_code {
+                        let share_btn = ui.add(egui::Button::new("🔗"));
+                        if share_btn.on_hover_text(tr("top.share_code")).clicked() {
+                            ui.output_mut(|o| o.copied_text = code.to_string());
+                        }
+                    }
+                    let timelapse_btn = ui.add(egui::Button::new(if self.recording {
+                        "⏹"
                     } else {
-                        let pause_btn = ui.add(egui::Button::new("▶"));
-                        if pause_btn.clicked() {
-                            self.pause = false;
-                            ctx.request_repaint();
+                        "🎞"
+                    }));
+                    let hover = match self.recording {
+                        true => tr("top.timelapse_stop"),
+                        false => tr("top.timelapse_start"),
+                    };
+                    if timelapse_btn.on_hover_text(hover).clicked() {
+                        self.apply_action(UiAction::ToggleTimelapse);
+                    }
+                    let slower_btn = ui.add(egui::Button::new("⏪"));
+                    if slower_btn.clicked() {
+                        self.apply_action(UiAction::SlowDown);
+                    }
+                    ui.label(tr_with(
+                        "top.tick_rate",
+                        &[("rate", &format!("{:.1}", self.tick_rate))],
+                    ));
+                    if !self.regions.is_empty() {
+                        let mut switch_to = None;
+                        egui::ComboBox::from_id_source("region_switcher")
+                            .selected_text(&self.region)
+                            .show_ui(ui, |ui| {
+                                for name in &self.regions {
+                                    if ui.selectable_label(*name == self.region, name).clicked() {
+                                        switch_to = Some(name.clone());
+                                    }
+                                }
+                            });
+                        if let Some(name) = switch_to.filter(|name| *name != self.region) {
+                            self.send_command(SandboxCommand::ViewRegion(name));
+                            self.switching_region = true;
                         }
                     }
                 })
@@ -90,12 +1399,60 @@ impl SeaGui {
 }
 impl eframe::App for SeaGui {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // in a browser the game runs on this thread, so it needs a chance to tick before we draw anything
+        if let Some(simulation) = &mut self.simulation {
+            simulation.pump();
+        }
+        ctx.set_visuals(theme::visuals(self.settings.theme));
+        let palette = Palette::for_theme(self.settings.theme);
         self.render_top_panel(ctx, frame);
+        if self.show_settings {
+            self.render_settings(ctx);
+        }
         let background = egui::containers::Frame {
-            fill: egui::Color32::from_rgb(97, 109, 128),
+            fill: palette.background,
             ..Default::default()
         };
         if self.run_simulation {
+            for action in read_shortcuts(ctx) {
+                self.apply_action(action);
+            }
+            if self.show_menu {
+                self.render_menu(ctx, frame);
+            }
+            if self.crash.is_some() {
+                self.render_crash(ctx, frame);
+            }
+            if self.show_performance {
+                self.render_performance(ctx);
+            }
+            if self.show_traits {
+                self.render_traits(ctx);
+            }
+            if self.show_guide {
+                self.render_guide(ctx);
+            }
+            if self.tile_report.is_some() {
+                self.render_tile_report(ctx);
+            }
+            if self.tutorial.as_ref().is_some_and(Tutorial::finished) {
+                self.render_tutorial_end(ctx);
+            }
+            // Panels need to be laid out before the central panel can take up the rest of the space
+            egui::SidePanel::right("colony_info")
+                .resizable(true)
+                .default_width(250.0)
+                .show(ctx, |ui| {
+                    ui.heading(tr("info.title"));
+                    self.render_health_gauge(ui);
+                    self.render_outlook(ui);
+                    self.render_population_graph(ui);
+                    if let Weather::Storm(ticks_left) = self.weather {
+                        ui.label(tr_with("info.storm", &[("ticks", &ticks_left)]));
+                    }
+                    self.render_build_menu(ui);
+                    self.render_entity_stats(ui);
+                });
             // Clear background frame
             let background = egui::containers::Frame {
                 fill: egui::Color32::from_rgba_premultiplied(0, 0, 0, 0),
@@ -119,80 +1476,187 @@ impl eframe::App for SeaGui {
                         col_img,
                     ));
                 }
-                ui.image(
+                // stretch the background over the whole panel, whatever size the window is
+                let backdrop = ui.image(
                     self.background_img.as_ref().unwrap().texture_id(ctx),
-                    self.background_img.as_ref().unwrap().size_vec2(),
+                    ui.available_size(),
                 );
+                if self.weather.is_stormy() {
+                    ui.painter()
+                        .rect_filled(backdrop.rect, egui::Rounding::none(), palette.storm);
+                }
                 // Render the actual game info
                 egui::CentralPanel::default()
                     .frame(background)
                     .show(ctx, |ui| {
-                        // If there is not an event, process the next game tick
-                        if self.event_msg.len() < 3 && !self.pause {
+                        // If there is not an event, process the next game tick.
+                        // While editing, the sandbox only sends us updates when the board was changed.
+                        if self.event.is_none()
+                            && (!self.pause
+                                || self.edit_mode
+                                || self.show_console
+                                || self.switching_region
+                                || self.rewinding
+                                || self.inspecting)
+                        {
                             if let Ok(result) = self.rx.try_recv() {
-                                self.previous_disp = result.0;
-                                self.entities_info = result.1;
-                                self.event_msg =
-                                    result.2.split('*').map(|s| s.to_string()).collect();
-                                self.loop_tx = Some(result.3);
+                                if result.crash.is_some() {
+                                    // keep the last good board up behind the crash report
+                                    self.crash = result.crash;
+                                } else {
+                                    self.previous_disp = result.board;
+                                    self.rewinding = false;
+                                    self.board_view.track_movement(&result.positions);
+                                    self.board_view.set_pollution(&result.pollution);
+                                    self.board_view.set_pregnant(&result.pregnant);
+                                    // whatever we were following could have died since
+                                    self.following = result.spotlight.as_ref().map(|s| s.id);
+                                    self.board_view.set_spotlight(result.spotlight);
+                                    self.tile_report = result.tile_report;
+                                    self.inspecting = false;
+                                    self.can_undo = result.can_undo;
+                                    self.can_redo = result.can_redo;
+                                    self.board_view.set_debug(result.debug);
+                                    self.stats = result.stats;
+                                    self.event_since =
+                                        result.event.as_ref().map(|_| Instant::now());
+                                    self.event = result.event;
+                                    self.votes = result.votes;
+                                    self.loop_tx = Some(result.loop_tx);
+                                    self.achievements = result.achievements;
+                                    self.console_log.extend(result.console);
+                                    self.timings = result.timings;
+                                    self.topology = result.topology;
+                                    if result.region != self.region {
+                                        // a whole new board, so start the camera over
+                                        self.board_view = BoardView::default();
+                                        self.region = result.region;
+                                        self.switching_region = false;
+                                    }
+                                    self.regions = result.regions;
+                                    self.weather = result.weather;
+                                    self.resources = result.resources;
+                                    self.missions = result.missions;
+                                    self.traits = result.traits;
+                                    self.dynasties = result.dynasties;
+                                    self.health = result.health;
+                                    self.health_trend = result.health_trend;
+                                    self.outlook = result.outlook;
+                                    self.populations = result.populations;
+                                }
                             }
                         }
+                        let toggle_console = ctx.input(|i| {
+                            i.events
+                                .iter()
+                                .any(|e| matches!(e, egui::Event::Text(t) if t == "`"))
+                        });
+                        if toggle_console {
+                            self.show_console = !self.show_console;
+                        }
+                        if self.show_console {
+                            self.render_console(ctx);
+                        }
                         // Display the board, either newly updated or the previous one
-                        ui.with_layout(
-                            egui::Layout::centered_and_justified(egui::Direction::LeftToRight),
-                            |ui| {
-                                ui.label(
-                                    egui::RichText::new(format!("\n{}", self.previous_disp))
-                                        .font(egui::FontId::proportional(110.0 * self.game_info[2]))
-                                        .color(egui::Color32::from_rgb(10, 10, 10)),
-                                );
-                            },
+                        let letters = self.settings.letter_glyphs.then_some(&self.letter_glyphs);
+                        let clicked = self.board_view.show(
+                            ui,
+                            &self.previous_disp,
+                            self.topology,
+                            palette.text,
+                            letters,
+                            // leave a little slack so everything has stopped before the next tick shows up
+                            0.8 / self.tick_rate as f32,
                         );
+                        if self.edit_mode && self.pause {
+                            self.render_palette(ctx);
+                            if let Some(pos) = clicked {
+                                self.send_command(match self.palette {
+                                    Some(species) => SandboxCommand::Spawn(pos, species),
+                                    None => SandboxCommand::Remove(pos),
+                                });
+                            }
+                        } else if let (Some(species), Some(pos)) = (self.building, clicked) {
+                            self.send_command(SandboxCommand::Build(pos, species));
+                        } else if let Some(pos) = clicked {
+                            self.inspecting = true;
+                            self.send_command(SandboxCommand::Inspect(Some(pos)));
+                        }
                         // If there is an event, display it in a new window, pausing the game execution
                         // until the event has been handled
-                        ui.with_layout(egui::Layout::top_down(egui::Align::Center), |_ui| {
-                            egui::Window::new("Colony Info")
-                                .vscroll(true)
-                                .default_pos(egui::Pos2::new(1410.0, 0.0))
+                        if self.show_achievements {
+                            egui::Window::new(tr("achievements.title"))
+                                .open(&mut self.show_achievements)
                                 .show(ctx, |ui| {
-                                    for i in self.entities_info.clone() {
+                                    if self.achievements.is_empty() {
+                                        ui.label(tr("achievements.none"));
+                                    }
+                                    for title in &self.achievements {
                                         ui.label(
-                                            egui::RichText::new(i)
+                                            egui::RichText::new(format!("🏆 {title}"))
                                                 .font(egui::FontId::proportional(20.0)),
                                         );
                                     }
                                 });
-                        });
-                        if self.event_msg.len() == 3 {
+                        }
+                        if self.show_missions {
+                            egui::Window::new(tr("missions.title"))
+                                .open(&mut self.show_missions)
+                                .show(ctx, |ui| {
+                                    if self.missions.is_empty() {
+                                        ui.label(tr("missions.none"));
+                                    }
+                                    for line in &self.missions {
+                                        ui.label(
+                                            egui::RichText::new(line)
+                                                .font(egui::FontId::proportional(20.0)),
+                                        );
+                                    }
+                                });
+                        }
+                        let time_left = self.event_time_left();
+                        if let (Some(event), Some(0)) = (&self.event, time_left) {
+                            self.time_out_event(&event.clone());
+                        } else if let Some(event) = self.event.clone() {
+                            if time_left.is_some() {
+                                ctx.request_repaint_after(Duration::from_secs(1));
+                            }
                             ui.with_layout(egui::Layout::top_down(egui::Align::Center), |_ui| {
-                                egui::Window::new("*EVENT*").show(ctx, |ui| {
-                                    ui.label(
-                                        egui::RichText::new(self.event_msg[0].clone())
-                                            .font(egui::FontId::proportional(20.0)),
-                                    );
-                                    // process the result and display the result
-                                    ui.with_layout(
-                                        egui::Layout::top_down(egui::Align::Center),
-                                        |ui| {
-                                            let left = ui.add(
-                                                egui::Button::new("1")
-                                                    .min_size(egui::vec2(100.0, 30.0)),
+                                let window = egui::Window::new(tr("event.title")).show(ctx, |ui| {
+                                    for text in [&event.intro, &event.prompt] {
+                                        if !text.is_empty() {
+                                            ui.label(
+                                                egui::RichText::new(text)
+                                                    .font(egui::FontId::proportional(20.0)),
                                             );
-                                            if left.clicked() {
-                                                self.event_res = self.event_msg[1].clone();
-                                                let _ = self.loop_tx.clone().unwrap().send(false);
-                                            }
-                                            ui.add_space(20.0);
-                                            let right = ui.add(
-                                                egui::Button::new("2")
-                                                    .min_size(egui::vec2(100.0, 30.0)),
-                                            );
-                                            if right.clicked() {
-                                                self.event_res = self.event_msg[2].clone();
-                                                let _ = self.loop_tx.clone().unwrap().send(true);
+                                        }
+                                    }
+                                    ui.add_space(10.0);
+                                    ui.horizontal(|ui| {
+                                        for (number, choice) in event.choices.iter().enumerate() {
+                                            if render_choice_card(ui, number + 1, choice) {
+                                                self.apply_action(UiAction::AnswerEvent(
+                                                    number == 1,
+                                                ));
                                             }
-                                        },
-                                    );
+                                        }
+                                    });
+                                    if let (Some(votes), Some(seconds)) = (&self.votes, time_left) {
+                                        let [first, second] = votes.counts();
+                                        ui.label(tr_with(
+                                            "event.votes",
+                                            &[("first", &first), ("second", &second)],
+                                        ));
+                                        ui.label(tr_with("event.voting", &[("seconds", &seconds)]));
+                                    } else if let Some(seconds) = time_left {
+                                        ui.label(tr_with(
+                                            "event.countdown",
+                                            &[
+                                                ("choice", &event.default_choice().label),
+                                                ("seconds", &seconds),
+                                            ],
+                                        ));
+                                    }
                                     if !self.event_res.is_empty() {
                                         ui.label(
                                             egui::RichText::new(self.event_res.clone())
@@ -203,123 +1667,280 @@ impl eframe::App for SeaGui {
                                             egui::Layout::top_down(egui::Align::Center),
                                             |ui| {
                                                 let done = ui.add(
-                                                    egui::Button::new("Proceed")
+                                                    egui::Button::new(tr("event.proceed"))
                                                         .min_size(egui::vec2(100.0, 30.0)),
                                                 );
                                                 if done.clicked() {
-                                                    self.event_msg = Vec::new();
-                                                    self.event_res = String::new();
-                                                    let _ =
-                                                        self.loop_tx.clone().unwrap().send(true);
+                                                    self.apply_action(UiAction::DismissEvent);
                                                 }
                                             },
                                         );
                                     }
                                 });
+                                if let Some(window) = window {
+                                    self.point_out(ctx, Step::Event, window.response.rect);
+                                }
                             });
                         }
                     });
             });
         } else if self.start {
-            egui::CentralPanel::default().frame(background).show(ctx, |ui| {
-                render_header(ui);
-                ui.label(egui::RichText::new("I see you have found yourself on the depths of the ocean. You must be here to manage the lawless lifeforms that call this place home. No doubt you posses the skills needed to make them thrive. When you are ready to begin, click play.").font(egui::FontId::proportional(20.0)).color(egui::Color32::from_rgb(10, 10, 10)));
-                ui.label("");
-                ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
-                    let enter = ui.add(egui::Button::new(egui::RichText::new("Play").font(egui::FontId::proportional(20.0))).min_size(egui::vec2(100.0, 30.0)).fill(egui::Color32::from_rgb(10,10,10)));
-                    if enter.clicked() {
-                        self.start = false;
-                        self.get_dim = true;
-                    }
+            egui::CentralPanel::default()
+                .frame(background)
+                .show(ctx, |ui| {
+                    render_header(ui, palette);
+                    ui.label(
+                        egui::RichText::new(tr("start.welcome"))
+                            .font(egui::FontId::proportional(20.0))
+                            .color(palette.text),
+                    );
+                    ui.label("");
+                    ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                        let enter = ui.add(
+                            egui::Button::new(
+                                egui::RichText::new(tr("start.play"))
+                                    .font(egui::FontId::proportional(20.0))
+                                    .color(palette.button_text),
+                            )
+                            .min_size(egui::vec2(100.0, 30.0))
+                            .fill(palette.button),
+                        );
+                        if enter.clicked() {
+                            self.start = false;
+                            self.get_dim = true;
+                        }
+                        ui.add_space(5.0);
+                        let tutorial = ui.add(
+                            egui::Button::new(
+                                egui::RichText::new(tr("start.tutorial"))
+                                    .font(egui::FontId::proportional(20.0))
+                                    .color(palette.button_text),
+                            )
+                            .min_size(egui::vec2(100.0, 30.0))
+                            .fill(palette.button),
+                        );
+                        if tutorial.clicked() {
+                            let cmd_rx = self.new_command_channel();
+                            self.simulation = Some(game_data::initialize_tutorial(
+                                self.settings.clone(),
+                                self.tx.clone(),
+                                cmd_rx,
+                                ctx.clone(),
+                            ));
+                            self.colony_code = None;
+                            self.tutorial = Some(Tutorial::default());
+                            self.event_res = String::new();
+                            self.start = false;
+                            self.run_simulation = true;
+                        }
+                        if !self.scenarios.is_empty() {
+                            ui.add_space(10.0);
+                            ui.label(
+                                egui::RichText::new(tr("start.scenario"))
+                                    .font(egui::FontId::proportional(20.0))
+                                    .color(palette.text),
+                            );
+                        }
+                        for path in self.scenarios.clone() {
+                            let name = path
+                                .file_stem()
+                                .unwrap_or_default()
+                                .to_string_lossy()
+                                .replace('_', " ");
+                            let scenario = ui.add(
+                                egui::Button::new(
+                                    egui::RichText::new(name)
+                                        .font(egui::FontId::proportional(20.0))
+                                        .color(palette.button_text),
+                                )
+                                .min_size(egui::vec2(100.0, 30.0))
+                                .fill(palette.button),
+                            );
+                            if scenario.clicked() {
+                                let cmd_rx = self.new_command_channel();
+                                match game_data::initialize_from_scenario(
+                                    &path.to_string_lossy(),
+                                    self.settings.clone(),
+                                    self.tx.clone(),
+                                    cmd_rx,
+                                    ctx.clone(),
+                                ) {
+                                    Ok(simulation) => {
+                                        self.simulation = Some(simulation);
+                                        self.colony_code = None;
+                                        self.tutorial = None;
+                                        self.event_res = String::new();
+                                        self.start = false;
+                                        self.run_simulation = true;
+                                    }
+                                    Err(e) => {
+                                        self.event_res = tr_with(
+                                            "start.load_failed",
+                                            &[("file", &path.display()), ("error", &e)],
+                                        )
+                                    }
+                                }
+                            }
+                        }
+                        if !self.event_res.is_empty() {
+                            ui.label(
+                                egui::RichText::new(self.event_res.clone())
+                                    .font(egui::FontId::proportional(20.0))
+                                    .color(palette.text),
+                            );
+                        }
+                    });
                 });
-            });
         } else if self.get_dim {
             egui::CentralPanel::default()
                 .frame(background)
                 .show(ctx, |ui| {
-                    let mut parse_err = false;
-                    render_header(ui);
+                    render_header(ui, palette);
                     ui.label(
-                        egui::RichText::new(
-                            "First, provide the desired dimensions of your colony.",
-                        )
-                        .font(egui::FontId::proportional(20.0))
-                        .color(egui::Color32::from_rgb(10, 10, 10)),
+                        egui::RichText::new(tr("setup.dimensions"))
+                            .font(egui::FontId::proportional(20.0))
+                            .color(palette.text),
                     );
                     ui.add_space(5.0);
                     ui.horizontal(|ui| {
                         let row_label = ui.label(
-                            egui::RichText::new("Rows: ")
+                            egui::RichText::new(format!("{}: ", tr("setup.rows")))
                                 .font(egui::FontId::proportional(20.0))
-                                .color(egui::Color32::from_rgb(10, 10, 10)),
+                                .color(palette.text),
                         );
                         ui.text_edit_singleline(&mut self.first_input)
                             .labelled_by(row_label.id);
                     });
                     ui.horizontal(|ui| {
                         let col_label = ui.label(
-                            egui::RichText::new("Columns: ")
+                            egui::RichText::new(format!("{}: ", tr("setup.columns")))
                                 .font(egui::FontId::proportional(20.0))
-                                .color(egui::Color32::from_rgb(10, 10, 10)),
+                                .color(palette.text),
                         );
                         ui.text_edit_singleline(&mut self.second_input)
                             .labelled_by(col_label.id);
                     });
+                    let mut hex = self.setup.topology == Topology::Hex;
+                    ui.checkbox(
+                        &mut hex,
+                        egui::RichText::new(tr("setup.hex"))
+                            .font(egui::FontId::proportional(20.0))
+                            .color(palette.text),
+                    );
+                    self.setup.topology = if hex { Topology::Hex } else { Topology::Square };
                     ui.label("");
                     ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
                         let enter = ui.add(
                             egui::Button::new(
-                                egui::RichText::new("Enter").font(egui::FontId::proportional(20.0)),
+                                egui::RichText::new(tr("setup.enter"))
+                                    .font(egui::FontId::proportional(20.0))
+                                    .color(palette.button_text),
                             )
                             .min_size(egui::vec2(100.0, 30.0))
-                            .fill(egui::Color32::from_rgb(10, 10, 10)),
+                            .fill(palette.button),
                         );
                         if enter.clicked() {
-                            match self.first_input.trim().parse::<f32>() {
-                                Ok(row) => match self.second_input.trim().parse::<f32>() {
-                                    Ok(col) => {
-                                        // We need to store or pass the data
-                                        self.game_info.push(row);
-                                        self.game_info.push(col);
-                                        self.first_input = String::new();
-                                        self.second_input = String::new();
-                                        self.get_dim = false;
-                                        self.get_animals = true;
-                                    }
-                                    Err(_) => parse_err = true,
-                                },
-                                Err(_) => parse_err = true,
+                            // nothing's been asked for yet, so only the size can be wrong here
+                            let setup = parse_count(&self.first_input)
+                                .and_then(|rows| Ok((rows, parse_count(&self.second_input)?)))
+                                .and_then(|(rows, cols)| SetupConfig::try_new(rows, cols, &[]));
+                            match setup {
+                                Ok(setup) => {
+                                    self.setup = SetupConfig {
+                                        topology: self.setup.topology,
+                                        ..setup
+                                    };
+                                    self.first_input = String::new();
+                                    self.second_input = String::new();
+                                    self.event_res = String::new();
+                                    self.get_dim = false;
+                                    self.get_animals = true;
+                                }
+                                Err(e) => self.event_res = e.describe(),
                             }
                         }
                     });
-                    if parse_err {
-                        ui.label("Dimensions must be positive integers.");
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        let code_label = ui.label(
+                            egui::RichText::new(format!("{}: ", tr("setup.code")))
+                                .font(egui::FontId::proportional(20.0))
+                                .color(palette.text),
+                        );
+                        let input = ui
+                            .text_edit_singleline(&mut self.code_input)
+                            .labelled_by(code_label.id);
+                        if input.changed() {
+                            self.code_warned = false;
+                        }
+                        let import = ui.button(tr("setup.code_import"));
+                        if import.on_hover_text(tr("setup.code_hint")).clicked() {
+                            self.import_code(ctx);
+                        }
+                    });
+                    if !self.event_res.is_empty() {
+                        ui.label(
+                            egui::RichText::new(self.event_res.clone())
+                                .font(egui::FontId::proportional(20.0))
+                                .color(palette.text),
+                        );
                     }
                 });
         } else if self.get_animals {
             egui::CentralPanel::default()
                 .frame(background)
                 .show(ctx, |ui| {
-                    let board_size = self.game_info[0] * self.game_info[1];
-                    // Scale display size based on the number of rows
-                    self.game_info.push(5.0 / self.game_info[0]);
-                    render_header(ui);
+                    render_header(ui, palette);
                     ui.label(
-                        egui::RichText::new(
-                            "Thank you...\nNow provide the starting animal populations",
-                        )
-                        .font(egui::FontId::proportional(20.0))
-                        .color(egui::Color32::from_rgb(10, 10, 10)),
+                        egui::RichText::new(tr("setup.populations"))
+                            .font(egui::FontId::proportional(20.0))
+                            .color(palette.text),
                     );
                     ui.add_space(5.0);
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            egui::RichText::new(format!("{}: ", tr("setup.start_from")))
+                                .font(egui::FontId::proportional(20.0))
+                                .color(palette.text),
+                        );
+                        for preset in Preset::ALL {
+                            if ui.button(tr_name("preset", preset.name())).clicked() {
+                                preset.apply(&mut self.setup);
+                                self.first_input = self.setup.fish.to_string();
+                                self.second_input = self.setup.crab.to_string();
+                                self.third_input = self.setup.shark.to_string();
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            egui::RichText::new(format!("{}: ", tr("setup.difficulty")))
+                                .font(egui::FontId::proportional(20.0))
+                                .color(palette.text),
+                        );
+                        for difficulty in Difficulty::ALL {
+                            ui.selectable_value(
+                                &mut self.settings.difficulty,
+                                difficulty,
+                                tr_name("difficulty", difficulty.name()),
+                            );
+                        }
+                    });
                     ui.horizontal(|ui| {
                         let row_label = ui.label(
                             egui::RichText::new(format!(
-                                "Fish 🐠 (limit {}): ",
-                                board_size as usize / 5
+                                "{}: ",
+                                tr_with(
+                                    "setup.limit",
+                                    &[
+                                        ("species", &tr_name("species", Species::Fish.name())),
+                                        ("glyph", &"🐠"),
+                                        ("limit", &self.setup.limit(Species::Fish)),
+                                    ],
+                                )
                             ))
                             .font(egui::FontId::proportional(20.0))
-                            .color(egui::Color32::from_rgb(10, 10, 10)),
+                            .color(palette.text),
                         );
                         ui.text_edit_singleline(&mut self.first_input)
                             .labelled_by(row_label.id);
@@ -327,11 +1948,18 @@ impl eframe::App for SeaGui {
                     ui.horizontal(|ui| {
                         let row_label = ui.label(
                             egui::RichText::new(format!(
-                                "Crab 🐚 (limit {}): ",
-                                board_size as usize / 7
+                                "{}: ",
+                                tr_with(
+                                    "setup.limit",
+                                    &[
+                                        ("species", &tr_name("species", Species::Crab.name())),
+                                        ("glyph", &"🐚"),
+                                        ("limit", &self.setup.limit(Species::Crab)),
+                                    ],
+                                )
                             ))
                             .font(egui::FontId::proportional(20.0))
-                            .color(egui::Color32::from_rgb(10, 10, 10)),
+                            .color(palette.text),
                         );
                         ui.text_edit_singleline(&mut self.second_input)
                             .labelled_by(row_label.id);
@@ -339,11 +1967,18 @@ impl eframe::App for SeaGui {
                     ui.horizontal(|ui| {
                         let row_label = ui.label(
                             egui::RichText::new(format!(
-                                "Shark 🐬 (limit {}): ",
-                                board_size as usize / 10
+                                "{}: ",
+                                tr_with(
+                                    "setup.limit",
+                                    &[
+                                        ("species", &tr_name("species", Species::Shark.name())),
+                                        ("glyph", &"🐬"),
+                                        ("limit", &self.setup.limit(Species::Shark)),
+                                    ],
+                                )
                             ))
                             .font(egui::FontId::proportional(20.0))
-                            .color(egui::Color32::from_rgb(10, 10, 10)),
+                            .color(palette.text),
                         );
                         ui.text_edit_singleline(&mut self.third_input)
                             .labelled_by(row_label.id);
@@ -352,51 +1987,39 @@ impl eframe::App for SeaGui {
                     ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
                         let enter = ui.add(
                             egui::Button::new(
-                                egui::RichText::new("Enter").font(egui::FontId::proportional(20.0)),
+                                egui::RichText::new(tr("setup.enter"))
+                                    .font(egui::FontId::proportional(20.0))
+                                    .color(palette.button_text),
                             )
                             .min_size(egui::vec2(100.0, 30.0))
-                            .fill(egui::Color32::from_rgb(10, 10, 10)),
+                            .fill(palette.button),
                         );
                         if enter.clicked() {
-                            match self.first_input.trim().parse::<usize>() {
-                                Ok(fish) => match self.second_input.trim().parse::<usize>() {
-                                    Ok(crab) => match self.third_input.trim().parse::<usize>() {
-                                        Ok(shark) => {
-                                            if fish > board_size as usize / 5
-                                                || crab > board_size as usize / 7
-                                                || shark > board_size as usize / 10
-                                            {
-                                                self.event_res = String::from(
-                                                    "Values must be less than the limit!",
-                                                );
-                                            } else {
-                                                game_data::initialize_board(
-                                                    self.game_info[0] as usize,
-                                                    self.game_info[1] as usize,
-                                                    fish,
-                                                    crab,
-                                                    shark,
-                                                    self.tx.clone(),
-                                                    ctx.clone(),
-                                                );
-                                                self.event_res = String::new();
-                                                self.get_animals = false;
-                                                self.run_simulation = true;
-                                            }
-                                        }
-                                        Err(_) => {
-                                            self.event_res =
-                                                String::from("Input must be positive number!")
-                                        }
-                                    },
-                                    Err(_) => {
-                                        self.event_res =
-                                            String::from("Input must be positive number!")
-                                    }
-                                },
-                                Err(_) => {
-                                    self.event_res = String::from("Input must be positive number!")
+                            let inputs = [&self.first_input, &self.second_input, &self.third_input];
+                            let populations = STARTING_SPECIES
+                                .into_iter()
+                                .zip(inputs)
+                                .map(|(species, text)| Ok((species, parse_count(text)?)))
+                                .collect::<Result<Vec<_>, SetupError>>();
+                            // the preset might have had a say in the kelp, so that's kept
+                            let setup = populations.and_then(|populations| {
+                                SetupConfig::try_new(self.setup.rows, self.setup.cols, &populations)
+                            });
+                            match setup {
+                                Ok(setup) => {
+                                    self.setup = SetupConfig {
+                                        topology: self.setup.topology,
+                                        kelp_chance: self.setup.kelp_chance,
+                                        ..setup
+                                    };
+                                    let code = ColonyCode::new(
+                                        self.setup.clone(),
+                                        self.settings.difficulty,
+                                        &self.rules,
+                                    );
+                                    self.start_game(code, ctx);
                                 }
+                                Err(e) => self.event_res = e.describe(),
                             }
                         }
                     });
@@ -404,7 +2027,7 @@ impl eframe::App for SeaGui {
                         ui.label(
                             egui::RichText::new(self.event_res.clone())
                                 .font(egui::FontId::proportional(20.0))
-                                .color(egui::Color32::from_rgb(10, 10, 10)),
+                                .color(palette.text),
                         );
                     }
                 });
@@ -412,11 +2035,120 @@ impl eframe::App for SeaGui {
     }
 }
 
-fn render_header(ui: &mut egui::Ui) {
+/// A heading in the field guide, with everything under it on one line, or a note that there's nothing.
+fn render_guide_list(ui: &mut egui::Ui, heading: String, entries: Vec<String>) {
+    ui.label(egui::RichText::new(heading).strong());
+    match entries.is_empty() {
+        true => ui.label(tr("guide.nothing")),
+        false => ui.label(entries.join(", ")),
+    };
+}
+
+/// One animal's line in the Colony Info panel, with bars for how it's holding up.
+/// Returns true if its name was clicked on.
+fn render_stat_row(ui: &mut egui::Ui, s: &EntityStats, followed: bool) -> bool {
+    let clicked = ui.horizontal(|ui| {
+        let name = format!("{}: {}", s.id.get_id_val(), s.glyph);
+        let name = egui::RichText::new(name).font(egui::FontId::proportional(20.0));
+        let clicked = ui
+            .selectable_label(followed, name)
+            .on_hover_text(tr("info.follow"))
+            .clicked();
+        ui.label(hunger_icon(s.hunger))
+            .on_hover_text(tr_name("hunger", s.hunger.name()));
+        if s.pregnant {
+            ui.label("🥚").on_hover_text(tr("info.expecting"));
+        }
+        ui.label(egui::RichText::new(tr_name("behavior", &s.behavior)).small());
+        let quirks: Vec<String> = s
+            .personality
+            .quirks()
+            .into_iter()
+            .map(|q| tr_name("personality", q))
+            .collect();
+        if !quirks.is_empty() {
+            ui.label(egui::RichText::new(quirks.join(", ")).small().italics());
+        }
+        clicked
+    });
+    ui.add(egui::ProgressBar::new(s.health as f32).text(tr_with(
+        "info.health",
+        &[("percent", &format!("{:.0}", s.health * 100.0))],
+    )));
+    if let Some(age) = s.age {
+        ui.add(egui::ProgressBar::new(age as f32).text(tr("info.age")));
+    }
+    ui.separator();
+    clicked.inner
+}
+
+/// An icon for how hungry an animal is.
+fn hunger_icon(hunger: HungerLevel) -> &'static str {
+    match hunger {
+        HungerLevel::Full => "😋",
+        HungerLevel::Hungry => "🍽",
+        HungerLevel::Starving => "⚠",
+        HungerLevel::Famished => "☠",
+    }
+}
+
+/// An arrow for which way the colony's health is going.
+fn trend_arrow(trend: Trend) -> &'static str {
+    match trend {
+        Trend::Rising => "⬆",
+        Trend::Steady => "➡",
+        Trend::Falling => "⬇",
+    }
+}
+
+/// One of an event's choices, as a card with its picture, its label and what it's expected to do.
+/// Returns true if it was picked.
+fn render_choice_card(ui: &mut egui::Ui, number: usize, choice: &EventChoice) -> bool {
+    egui::Frame::group(ui.style())
+        .show(ui, |ui| {
+            ui.set_width(240.0);
+            ui.vertical_centered(|ui| {
+                let label = format!("{} {number}. {}", event_icon(&choice.icon), choice.label);
+                let picked = ui
+                    .add(
+                        egui::Button::new(
+                            egui::RichText::new(label).font(egui::FontId::proportional(18.0)),
+                        )
+                        .wrap(true)
+                        .min_size(egui::vec2(220.0, 30.0)),
+                    )
+                    .clicked();
+                if choice.consequences.is_empty() {
+                    ui.label(egui::RichText::new(tr("event.no_consequences")).italics());
+                }
+                for consequence in &choice.consequences {
+                    ui.label(format!("• {}", consequence.describe()));
+                }
+                picked
+            })
+            .inner
+        })
+        .inner
+}
+
+/// Find every scenario file we can offer to the player, sorted by name.
+fn find_scenarios() -> Vec<PathBuf> {
+    let mut scenarios = match fs::read_dir(SCENARIO_DIR) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension() == Some("txt".as_ref()))
+            .collect(),
+        Err(_) => vec![],
+    };
+    scenarios.sort();
+    scenarios
+}
+
+fn render_header(ui: &mut egui::Ui, palette: Palette) {
     ui.vertical_centered(|ui| {
         ui.heading(
-            egui::RichText::new("Deep Sea Adventure")
-                .color(egui::Color32::from_rgb(10, 10, 10))
+            egui::RichText::new(tr("header.title"))
+                .color(palette.text)
                 .font(egui::FontId::proportional(20.0)),
         );
     });
@@ -425,9 +2157,13 @@ fn render_header(ui: &mut egui::Ui) {
     ui.add(sep);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn init() {
     let options = eframe::NativeOptions {
-        initial_window_size: Some(Vec2::new(1410.0, 810.0)),
+        // this is only the size for the first run, after that the window's size and position are remembered
+        initial_window_size: Some(egui::vec2(1410.0, 810.0)),
+        min_window_size: Some(egui::vec2(640.0, 400.0)),
+        resizable: true,
         ..Default::default()
     };
     let _ = eframe::run_native(
@@ -436,3 +2172,17 @@ pub fn init() {
         Box::new(|cc| Box::new(SeaGui::new(cc))),
     );
 }
+
+/// Start the GUI up in a browser, drawing onto the canvas with the given ID.
+#[cfg(target_arch = "wasm32")]
+pub fn init_web(canvas_id: &'static str) {
+    wasm_bindgen_futures::spawn_local(async move {
+        eframe::start_web(
+            canvas_id,
+            eframe::WebOptions::default(),
+            Box::new(|cc| Box::new(SeaGui::new(cc))),
+        )
+        .await
+        .expect("Failed to start the GUI");
+    });
+}
@@ -1,3 +1,10 @@
+#[cfg(not(target_arch = "wasm32"))]
 fn main() {
     display::init();
 }
+
+// built with `trunk build`, which drops us into index.html
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    display::init_web("the_canvas_id");
+}
@@ -0,0 +1,124 @@
+// Colors for the GUI, along with a letter-based glyph set for anyone who can't rely on emoji colors.
+
+use eframe::egui;
+use egui::{Color32, Stroke, Visuals};
+use game_data::entities::Species;
+use game_data::settings::ColorTheme;
+
+/// The colors used for everything we draw ourselves, rather than leaving it to egui.
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    /// Text drawn directly on the background.
+    pub text: Color32,
+    /// Background of the menus.
+    pub background: Color32,
+    /// Fill for the big menu buttons.
+    pub button: Color32,
+    /// Text on the big menu buttons.
+    pub button_text: Color32,
+    /// Laid over the background while a storm is blowing.
+    pub storm: Color32,
+}
+
+impl Palette {
+    pub fn for_theme(theme: ColorTheme) -> Self {
+        match theme {
+            ColorTheme::Dark => Self {
+                text: Color32::from_rgb(10, 10, 10),
+                background: Color32::from_rgb(97, 109, 128),
+                button: Color32::from_rgb(10, 10, 10),
+                button_text: Color32::from_rgb(220, 220, 220),
+                storm: Color32::from_black_alpha(120),
+            },
+            ColorTheme::Light => Self {
+                text: Color32::from_rgb(20, 20, 20),
+                background: Color32::from_rgb(200, 214, 230),
+                button: Color32::from_rgb(60, 84, 120),
+                button_text: Color32::WHITE,
+                storm: Color32::from_rgba_unmultiplied(40, 50, 70, 100),
+            },
+            ColorTheme::HighContrast => Self {
+                text: Color32::WHITE,
+                background: Color32::BLACK,
+                button: Color32::BLACK,
+                button_text: Color32::YELLOW,
+                storm: Color32::from_black_alpha(160),
+            },
+        }
+    }
+}
+
+/// Get the egui style to go along with a theme.
+pub fn visuals(theme: ColorTheme) -> Visuals {
+    match theme {
+        ColorTheme::Dark => Visuals::dark(),
+        ColorTheme::Light => Visuals::light(),
+        ColorTheme::HighContrast => {
+            let mut visuals = Visuals::dark();
+            visuals.override_text_color = Some(Color32::WHITE);
+            visuals.panel_fill = Color32::BLACK;
+            visuals.window_fill = Color32::BLACK;
+            visuals.window_stroke = Stroke::new(2.0, Color32::WHITE);
+            visuals.widgets.inactive.bg_stroke = Stroke::new(1.0, Color32::WHITE);
+            visuals.widgets.hovered.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+            visuals.selection.bg_fill = Color32::from_rgb(0, 90, 180);
+            visuals
+        }
+    }
+}
+
+/// The color for a score out of 100, going from red for a colony in trouble, through yellow, to green for one that's
+/// thriving.
+pub fn health_color(score: usize) -> Color32 {
+    let score = score.min(100) as f32 / 100.0;
+    let red = (2.0 * (1.0 - score)).min(1.0);
+    let green = (2.0 * score).min(1.0);
+    Color32::from_rgb((red * 200.0) as u8, (green * 170.0) as u8, 40)
+}
+
+/// The picture on an event choice's card, going by its icon ID. Anything we don't know about, like a script's, gets a scroll.
+pub fn event_icon(icon: &str) -> &'static str {
+    match icon {
+        "oil_spill.first" => "🌿",
+        "oil_spill.second" => "🛢",
+        "invasive_fish.first" => "🏃",
+        "invasive_fish.second" => "⚔",
+        "party.first" => "🎉",
+        "party.second" => "💤",
+        "vent_field.first" => "⛰",
+        "vent_field.second" => "🚧",
+        "cleanup.first" => "🦀",
+        "cleanup.second" => "🛋",
+        _ => "📜",
+    }
+}
+
+/// Swaps the emoji on the board out for each species' letter, in its color.
+pub struct LetterGlyphs {
+    /// Each species' emoji, and what to replace it with.
+    table: Vec<(char, char, Color32)>,
+}
+
+impl Default for LetterGlyphs {
+    fn default() -> Self {
+        let table = Species::all()
+            .into_iter()
+            .map(|species| {
+                let spec = species.display_spec();
+                let [r, g, b] = spec.color;
+                (spec.emoji, spec.ascii, Color32::from_rgb(r, g, b))
+            })
+            .collect();
+        Self { table }
+    }
+}
+
+impl LetterGlyphs {
+    /// Get the letter and color for a tile on the board. Empty tiles (and anything we don't recognize) are left blank.
+    pub fn get(&self, tile: char) -> Option<(char, Color32)> {
+        self.table
+            .iter()
+            .find(|(glyph, _, _)| *glyph == tile)
+            .map(|(_, letter, color)| (*letter, *color))
+    }
+}
@@ -0,0 +1,125 @@
+// Benchmarks for everything that runs on every tick, so we can tell if a change to the AI or the board slows the game down.
+// Run with `cargo bench -p game_data`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use futures::executor::block_on;
+use game_data::game_board::Pos;
+use game_data::save_file::write_save;
+use game_data::scenario::Scenario;
+use game_data::{find_path, Sandbox};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Board sizes to try, as (rows, cols).
+const SIZES: [(usize, usize); 3] = [(10, 10), (30, 30), (60, 60)];
+/// How much of the board should be covered by animals.
+const DENSITIES: [f64; 3] = [0.05, 0.15, 0.3];
+/// Every board is generated from the same seed, so runs can be compared against each other.
+const SEED: u64 = 0xDEE9;
+
+/// Make a new sandbox, splitting the animals up between fish, crabs, and sharks.
+fn make_sandbox(rows: usize, cols: usize, density: f64) -> Sandbox {
+    let animals = ((rows * cols) as f64 * density) as usize;
+    let fish = animals / 2;
+    let crab = animals * 3 / 10;
+    let shark = animals - fish - crab;
+    Sandbox::generate(
+        rows,
+        cols,
+        fish,
+        crab,
+        shark,
+        &mut StdRng::seed_from_u64(SEED),
+    )
+    .unwrap()
+}
+
+/// Benchmark one part of the tick against every board size and density.
+fn bench_phase(c: &mut Criterion, name: &str, phase: fn(&mut Sandbox)) {
+    let mut group = c.benchmark_group(name);
+    for (rows, cols) in SIZES {
+        for density in DENSITIES {
+            group.bench_with_input(
+                BenchmarkId::from_parameter(format!("{rows}x{cols}_{density}")),
+                &(rows, cols, density),
+                |b, &(rows, cols, density)| {
+                    b.iter_batched_ref(
+                        || make_sandbox(rows, cols, density),
+                        phase,
+                        BatchSize::LargeInput,
+                    )
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn bench_moves(c: &mut Criterion) {
+    bench_phase(c, "handle_moves", |sandbox| sandbox.handle_moves());
+}
+
+fn bench_processing(c: &mut Criterion) {
+    bench_phase(c, "handle_processing", |sandbox| {
+        sandbox.handle_processing()
+    });
+}
+
+fn bench_late_processing(c: &mut Criterion) {
+    bench_phase(c, "handle_late_processing", |sandbox| {
+        block_on(sandbox.handle_late_processing())
+    });
+}
+
+/// Search from the middle of the board to the far corner, which is about as long a path as an animal would ever look for.
+fn bench_pathfinding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("find_path");
+    for (rows, cols) in SIZES {
+        for density in DENSITIES {
+            let sandbox = make_sandbox(rows, cols, density);
+            let start = Pos {
+                x: cols / 2,
+                y: rows / 2,
+            };
+            let goal = Pos {
+                x: cols - 1,
+                y: rows - 1,
+            };
+            group.bench_function(
+                BenchmarkId::from_parameter(format!("{rows}x{cols}_{density}")),
+                |b| b.iter(|| find_path(start, sandbox.get_board(), |pos, _| pos == goal)),
+            );
+        }
+    }
+    group.finish();
+}
+
+/// Saving the biggest, busiest board. Capturing is the only part that holds up the game, with the rest done in the
+/// background, so they're timed separately.
+fn bench_saving(c: &mut Criterion) {
+    let mut group = c.benchmark_group("save");
+    let (rows, cols) = SIZES[SIZES.len() - 1];
+    let sandbox = make_sandbox(rows, cols, DENSITIES[DENSITIES.len() - 1]);
+    group.bench_function("capture", |b| {
+        b.iter(|| Scenario::capture(&sandbox, "Bench"))
+    });
+    let scenario = Scenario::capture(&sandbox, "Bench");
+    let path = std::env::temp_dir().join("deep_sea_sim_bench.sav");
+    group.bench_function("write_text", |b| {
+        b.iter(|| std::fs::write(&path, scenario.to_string()).unwrap())
+    });
+    group.bench_function("write_compressed", |b| {
+        b.iter(|| write_save(&path, &scenario).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_moves,
+    bench_processing,
+    bench_late_processing,
+    bench_pathfinding,
+    bench_saving
+);
+criterion_main!(benches);
@@ -0,0 +1,258 @@
+// Milestones for the player to work towards while the colony runs.
+
+use std::fs;
+use std::path::PathBuf;
+
+use log::{error, info};
+
+use crate::element_traits::Lives;
+use crate::entities::animals::Animals;
+use crate::entities::Species;
+use crate::game_board::Board;
+use crate::locale::tr;
+
+/// Where unlocked achievements are kept between runs.
+pub const ACHIEVEMENTS_FILE: &str = "achievements.txt";
+
+/// Number of ticks the colony has to survive for the first milestone.
+const SURVIVAL_TICKS: usize = 100;
+
+/// Generation a shark needs to reach to count as a dynasty.
+const DYNASTY_GENERATIONS: usize = 5;
+
+/// Fraction of the board that needs to be covered in plants to count as a forest.
+const FOREST_COVERAGE: f64 = 0.5;
+
+/// Every milestone the player can unlock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Achievement {
+    /// Make it through the first few ticks with something still alive.
+    FirstHundredTicks,
+    /// Raise a shark whose family goes back five generations.
+    SharkDynasty,
+    /// Cover half of the board with plants.
+    KelpForest,
+}
+
+impl Achievement {
+    /// All achievements, in the order they should be displayed.
+    pub const ALL: [Achievement; 3] = [
+        Achievement::FirstHundredTicks,
+        Achievement::SharkDynasty,
+        Achievement::KelpForest,
+    ];
+
+    /// Short name shown to the player.
+    pub fn title(&self) -> String {
+        tr(&format!("achievement.{}.title", self.key()))
+    }
+
+    /// What the player has to do to unlock this.
+    pub fn description(&self) -> String {
+        tr(&format!("achievement.{}.description", self.key()))
+    }
+
+    /// Stable name used when writing this to disk.
+    pub(crate) fn key(&self) -> &'static str {
+        match self {
+            Self::FirstHundredTicks => "first_hundred_ticks",
+            Self::SharkDynasty => "shark_dynasty",
+            Self::KelpForest => "kelp_forest",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.key() == key)
+    }
+
+    /// Check if the board currently meets the conditions for this achievement. These all go off of the counts the board
+    /// keeps as things are born and die, so checking them after every tick never means looking over the whole board.
+    /// The counts only hear about a death once the body's been cleared away, so anything that has to still be alive gets
+    /// looked for once the counts say it's there.
+    pub fn is_met(&self, clock: usize, board: &Board) -> bool {
+        match self {
+            Self::FirstHundredTicks => {
+                clock >= SURVIVAL_TICKS
+                    && board.head_count_of(Species::is_animal) > 0
+                    && living_animals(board).next().is_some()
+            }
+            Self::SharkDynasty => {
+                board.furthest_generation(Species::Shark) >= DYNASTY_GENERATIONS
+                    && living_animals(board).any(|a| {
+                        matches!(a, Animals::Shark(_)) && a.get_generation() >= DYNASTY_GENERATIONS
+                    })
+            }
+            Self::KelpForest => {
+                let (x, y) = board.dims();
+                board.head_count_of(Species::is_plant) as f64 >= (x * y) as f64 * FOREST_COVERAGE
+            }
+        }
+    }
+}
+
+/// Every animal on the board that's still alive.
+fn living_animals(board: &Board) -> impl Iterator<Item = &Animals> {
+    board
+        .arena()
+        .entities()
+        .filter_map(|entity| entity.animal())
+        .filter(|animal| !animal.is_dead())
+}
+
+/// Keeps track of which achievements have been unlocked, optionally saving them to disk as they come in.
+#[derive(Debug, Default)]
+pub struct AchievementTracker {
+    /// Achievements unlocked so far, in the order they were unlocked.
+    unlocked: Vec<Achievement>,
+    /// File to persist unlocked achievements to. If None, nothing is saved.
+    save_path: Option<PathBuf>,
+}
+
+impl AchievementTracker {
+    /// Load previously unlocked achievements from a file, saving any new ones back to it.
+    /// If the file doesn't exist yet, we just start fresh.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let unlocked = match fs::read_to_string(&path) {
+            Ok(contents) => contents
+                .lines()
+                .filter_map(|l| Achievement::from_key(l.trim()))
+                .collect(),
+            Err(_) => vec![],
+        };
+        Self {
+            unlocked,
+            save_path: Some(path),
+        }
+    }
+
+    /// Get every achievement unlocked so far.
+    pub fn get_unlocked(&self) -> &[Achievement] {
+        &self.unlocked
+    }
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    /// Check the board for anything newly unlocked, returning whatever was just unlocked.
+    pub fn check(&mut self, clock: usize, board: &Board) -> Vec<Achievement> {
+        let newly_unlocked = Achievement::ALL
+            .into_iter()
+            .filter(|a| !self.is_unlocked(*a) && a.is_met(clock, board))
+            .collect::<Vec<Achievement>>();
+
+        if !newly_unlocked.is_empty() {
+            for achievement in &newly_unlocked {
+                info!("Achievement unlocked: {}", achievement.title());
+            }
+            self.unlocked.extend(newly_unlocked.iter().copied());
+            self.save();
+        }
+
+        newly_unlocked
+    }
+
+    /// Write everything we've unlocked out to disk.
+    fn save(&self) {
+        if let Some(path) = &self.save_path {
+            let contents = self
+                .unlocked
+                .iter()
+                .map(|a| a.key())
+                .collect::<Vec<&str>>()
+                .join("\n");
+            if let Err(e) = fs::write(path, contents) {
+                error!("Failed to save achievements to {path:?}: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::animals::ConcreteAnimals;
+    use crate::entities::plants::ConcretePlants;
+    use crate::entities::NonAbstractTaxonomy;
+    use crate::game_board::Pos;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_kelp_forest() {
+        let mut testbed = TestBed::new_populated(
+            2,
+            2,
+            vec![
+                (Pos { x: 0, y: 0 }, ConcretePlants::Kelp),
+                (Pos { x: 1, y: 1 }, ConcretePlants::Kelp),
+            ],
+        );
+        let mut tracker = AchievementTracker::default();
+
+        let unlocked = tracker.check(0, &testbed.sandbox.board);
+        assert_eq!(unlocked, vec![Achievement::KelpForest]);
+
+        // shouldn't be unlocked twice
+        assert!(tracker.check(1, &testbed.sandbox.board).is_empty());
+
+        // and the count goes back down as the kelp's eaten away
+        let board = &mut testbed.sandbox.board;
+        board.remove_entity(Pos { x: 0, y: 0 });
+        assert!(!Achievement::KelpForest.is_met(2, board));
+    }
+
+    #[test]
+    fn test_survival_and_dynasty() {
+        let pos = Pos { x: 1, y: 1 };
+        let mut shark = ConcreteAnimals::Shark.create_new(None);
+        let mut testbed = TestBed::new_with_entities(3, 3, vec![(pos, shark.clone())]);
+        let mut tracker = AchievementTracker::default();
+
+        // a body that hasn't been cleared away yet doesn't count as surviving
+        let board = &mut testbed.sandbox.board;
+        let animal = board.entity_at_mut(pos).unwrap().animal_mut().unwrap();
+        animal.die("testing");
+        assert!(!Achievement::FirstHundredTicks.is_met(SURVIVAL_TICKS, board));
+        let testbed = TestBed::new_with_entities(3, 3, vec![(pos, shark.clone())]);
+        assert!(tracker
+            .check(SURVIVAL_TICKS - 1, &testbed.sandbox.board)
+            .is_empty());
+        assert_eq!(
+            tracker.check(SURVIVAL_TICKS, &testbed.sandbox.board),
+            vec![Achievement::FirstHundredTicks]
+        );
+
+        shark.reproduction_mut().unwrap().generation = DYNASTY_GENERATIONS;
+        let mut testbed = TestBed::new_with_entities(3, 3, vec![(pos, shark.clone())]);
+        // the dynasty has to still be going
+        let board = &mut testbed.sandbox.board;
+        let animal = board.entity_at_mut(pos).unwrap().animal_mut().unwrap();
+        animal.die("testing");
+        assert!(!Achievement::SharkDynasty.is_met(0, board));
+        board.remove_entity(pos);
+        assert_eq!(board.furthest_generation(Species::Shark), 0);
+
+        let testbed = TestBed::new_with_entities(3, 3, vec![(pos, shark)]);
+        assert_eq!(
+            tracker.check(0, &testbed.sandbox.board),
+            vec![Achievement::SharkDynasty]
+        );
+    }
+
+    #[test]
+    fn test_persistence() {
+        let path = std::env::temp_dir().join("deep_sea_sim_test_achievements.txt");
+        let _ = fs::remove_file(&path);
+
+        let testbed =
+            TestBed::new_populated(1, 1, vec![(Pos { x: 0, y: 0 }, ConcretePlants::Kelp)]);
+        let mut tracker = AchievementTracker::load(&path);
+        assert!(tracker.get_unlocked().is_empty());
+        tracker.check(0, &testbed.sandbox.board);
+
+        let reloaded = AchievementTracker::load(&path);
+        assert!(reloaded.is_unlocked(Achievement::KelpForest));
+        let _ = fs::remove_file(&path);
+    }
+}
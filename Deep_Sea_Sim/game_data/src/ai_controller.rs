@@ -6,17 +6,45 @@ use std::{
 };
 
 use log::{debug, info};
-use rand::{rngs::ThreadRng, Rng};
+use rand::{seq::IteratorRandom, Rng};
 
 use crate::{
     element_traits::{LifeStatus, Lives, Mobile, PostProcessResult, ProcessingContext},
     entities::{Entity, Living, PTUIDisplay},
+    entity_control::{EntityID, TrackedEntity},
+    interaction_matrix::{BehaviorWeights, WanderStyle},
     interactions::{EatsCreatures, Mates},
 };
 
-use crate::entities::animals::Animals;
+use crate::entities::animals::{Animals, FOREST_SPOTTING_DISTANCE};
+use crate::entities::components::Personality;
 
-use crate::game_board::{Board, Pos};
+use crate::game_board::{Board, Current, Pos};
+use crate::interaction_matrix::InteractionMatrix;
+
+/// How many ticks in a row a pair has to spend courting side by side before they'll mate.
+pub const COURTSHIP_TICKS: usize = 3;
+
+/// The odds of a roaming animal staying put for the tick instead.
+pub const IDLE_STAY_CHANCE: f64 = 0.3;
+
+/// How far out from where it started idling a patrol's corners are.
+pub const PATROL_RADIUS: usize = 2;
+
+/// How far off a plant can be for a hovering animal to settle on it as home.
+pub const HOVER_LOOKOUT: usize = 5;
+
+/// How far a hovering animal will stray from its home plant.
+pub const HOVER_RADIUS: usize = 2;
+
+/// Find a path from a starting position to the closest position that passes a check, stepping around anything in the way.
+/// This is the same search that animals use to chase down their targets.
+pub fn find_path<T>(start: Pos, board: &Board, check: T) -> Option<Vec<Pos>>
+where
+    T: Fn(Pos, &Board) -> bool,
+{
+    <EatAction as Pathfinder>::find_path_bfs(start, board, check)
+}
 
 /// Similar to other concrete implementations, this allows for easy categorization and initialization of different behaviors.
 #[derive(Debug, Clone, PartialEq)]
@@ -26,16 +54,117 @@ pub enum AIConcreteBehaviors {
     Mating(MateAction),
 }
 
+/// Doing nothing in particular. Only meant as a placeholder while an entity's real behavior is taken out to be ticked.
+impl Default for AIConcreteBehaviors {
+    fn default() -> Self {
+        Self::Idle(IdleAction::new(false))
+    }
+}
+
+/// Where an animal is headed and how it worked that out, for anyone curious about what it's thinking. This is the same
+/// search it moves by, so it's only as good as its next step: it can change completely by the next tick.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Plan {
+    /// The way there, starting from where the animal is and ending next to its target.
+    pub path: Vec<Pos>,
+    /// Whatever it's after, if it found anything.
+    pub target: Option<Pos>,
+    /// Every tile the search looked at along the way.
+    pub explored: Vec<Pos>,
+}
+
+impl AIConcreteBehaviors {
+    /// What we're after and how we mean to get there. Empty if we're not after anything in particular, like while
+    /// we're idling around or courting someone right next to us.
+    pub fn plan(
+        &self,
+        actor: &Animals,
+        position: Pos,
+        board: &Board,
+        rules: &InteractionMatrix,
+    ) -> Plan {
+        match self {
+            Self::Idle(_) => Plan::default(),
+            Self::Eating(e) => plan(
+                position,
+                actor.came_from(),
+                actor.max_speeds(),
+                board,
+                e.hunting(actor, position, board, rules),
+            ),
+            Self::Mating(m) if m.partner_adjacent(position, board) => Plan::default(),
+            Self::Mating(_) => plan(
+                position,
+                actor.came_from(),
+                actor.max_speeds(),
+                board,
+                |pos, board| MateAction::suitor_at(actor, pos, board, rules),
+            ),
+        }
+    }
+
+    /// Whether we're trying to get somewhere, so not getting there means something's wrong. Idling goes wherever, and
+    /// anything courting is meant to stay put.
+    pub fn wants_to_travel(&self) -> bool {
+        match self {
+            Self::Idle(_) => false,
+            Self::Eating(_) => true,
+            Self::Mating(m) => m.courtship == 0,
+        }
+    }
+
+    /// What kind of behavior this is, however far along it is.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Idle(_) => "idle",
+            Self::Eating(_) => "eating",
+            Self::Mating(_) => "mating",
+        }
+    }
+
+    /// Whether two behaviors are after the same thing, however far along either of them is.
+    pub fn same_pursuit(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Eating(a), Self::Eating(b)) => a.target() == b.target(),
+            _ => self.name() == other.name(),
+        }
+    }
+}
+
+/// Search the same way Pathfinder::find_smooth_path does, but keep hold of everything it turned up.
+fn plan<T>(
+    start: Pos,
+    came_from: Option<Pos>,
+    speeds: (usize, usize),
+    board: &Board,
+    check: T,
+) -> Plan
+where
+    T: Fn(Pos, &Board) -> bool,
+{
+    let (mut visited, mut goal) = explore(start, came_from, speeds, board, &check, true);
+    if goal.is_none() {
+        (visited, goal) = explore(start, came_from, speeds, board, &check, false);
+    }
+    Plan {
+        path: goal
+            .map(|goal| backtrack(&visited, goal))
+            .unwrap_or_default(),
+        target: goal,
+        explored: visited.into_keys().collect(),
+    }
+}
+
 // please look the other way for this impl
 // this was the nicest way I could string this together given the amount of time to implement it
 
 impl AIAction<Animals> for AIConcreteBehaviors {
-    fn priority(&self) -> usize {
+    fn urgency(&self, actor: &Animals, weights: &BehaviorWeights) -> f64 {
         match self {
             // TODO FIND A PRETTIER WAY TO DO THIS
-            Self::Eating(e) => e.priority(),
-            Self::Idle(i) => i.priority(),
-            Self::Mating(m) => m.priority(),
+            Self::Eating(e) => e.urgency(actor, weights),
+            Self::Idle(i) => i.urgency(actor, weights),
+            Self::Mating(m) => m.urgency(actor, weights),
         }
     }
 
@@ -132,7 +261,7 @@ pub trait AIControlled<T>: Lives + Debug {
 
     /// Get the action that we should perform during a tick.
     /// This will either return the behavior that we are currently performing,
-    /// or a new behavior that's of higher priority than our current behavior.
+    /// or a new behavior that's more urgent than our current behavior.
     /// Return None to keep using the existing behavior.
     fn get_next_action(
         &self,
@@ -170,8 +299,9 @@ where
     /// Create the action, and fill out any important fields.
     fn initialize(&mut self);
 
-    /// Get the priority of this action: the highest priority option at a given time will be selected.
-    fn priority(&self) -> usize;
+    /// How badly the actor wants to be doing this right now, going by how it's doing and what its species puts first.
+    /// Whichever option scores highest once it's been discounted for distance (see BehaviorWeights::score) is picked.
+    fn urgency(&self, actor: &T, weights: &BehaviorWeights) -> f64;
 
     /// Get a description of the action.
     fn get_action_desc(&self) -> String;
@@ -209,9 +339,7 @@ where
     where
         F: Fn(&T, &Entity, &ProcessingContext, &Board) -> bool,
     {
-        let ent_ctx = ctx.entity_context.read().unwrap();
-
-        for position in ent_ctx.get_active_positions() {
+        for position in board.tracked_near(ctx.position) {
             if position == ctx.position {
                 continue;
             }
@@ -234,8 +362,7 @@ where
     where
         F: Fn(&T, &Entity, &ProcessingContext, &Board) -> bool,
     {
-        let tile = board.get_tile_from_pos(position);
-        match tile.get_entity() {
+        match board.entity_at(position) {
             None => (),
             Some(e) => {
                 if check(actor, e, ctx, board) {
@@ -272,10 +399,17 @@ pub trait Pathfinder {
         let res = method(start, board, check);
         // dbg!(&res);
         if let Some(res) = res {
-            for path_pos in res {
-                if path_pos.x.abs_diff(start.x) <= max_x
-                    && path_pos.y.abs_diff(start.y) <= max_y
-                    && path_pos != start
+            // paths start from where we already are, which isn't anywhere to move to
+            for path_pos in res.into_iter().filter(|p| *p != start) {
+                let in_reach =
+                    path_pos.x.abs_diff(start.x) <= max_x && path_pos.y.abs_diff(start.y) <= max_y;
+                // moves go in a straight line, so cutting a corner the path went around would only get us stopped
+                // short (see Sandbox::try_move)
+                if in_reach
+                    && start
+                        .tiles_between(&path_pos)
+                        .iter()
+                        .all(|p| !board.get_tile_from_pos(*p).is_occupied())
                 {
                     last_good_pos = Some(path_pos)
                 } else {
@@ -286,120 +420,375 @@ pub trait Pathfinder {
         last_good_pos
     }
 
-    // a star would be really sweet but I can't really reason it out rn
+    /// Tiles next to hazards are treated as costly: they're only gone through if there's no other way to get there.
     fn find_path_bfs<T>(start: Pos, board: &Board, check: T) -> Option<Vec<Pos>>
     where
         T: Fn(Pos, &Board) -> bool,
     {
-        let (x, y) = board.dims();
-        let y_max = y - 1;
-        let x_max = x - 1;
+        Self::find_smooth_path(start, board, check, None, (1, 1))
+    }
 
-        let mut visited: HashMap<Pos, Option<Pos>> = HashMap::new();
-        let mut horizon: VecDeque<Pos> = VecDeque::new();
+    /// Same as find_path_bfs, but for something that moves at the given speeds along each axis, so whichever way
+    /// gets it there in the fewest ticks wins out over the fewest tiles. Whenever there's more than one quickest way
+    /// to go, the one that carries on the way we came wins out. Otherwise whichever way the search happens to try
+    /// first can flip from one tick to the next, and whatever's following it doubles back on itself.
+    fn find_smooth_path<T>(
+        start: Pos,
+        board: &Board,
+        check: T,
+        came_from: Option<Pos>,
+        speeds: (usize, usize),
+    ) -> Option<Vec<Pos>>
+    where
+        T: Fn(Pos, &Board) -> bool,
+    {
+        bfs(start, came_from, speeds, board, &check, true)
+            .or_else(|| bfs(start, came_from, speeds, board, &check, false))
+    }
+}
 
-        horizon.push_back(start);
-        visited.insert(start, None);
+// a star would be really sweet but I can't really reason it out rn
+fn bfs<T>(
+    start: Pos,
+    came_from: Option<Pos>,
+    speeds: (usize, usize),
+    board: &Board,
+    check: &T,
+    avoid_hazards: bool,
+) -> Option<Vec<Pos>>
+where
+    T: Fn(Pos, &Board) -> bool,
+{
+    let (visited, goal) = explore(start, came_from, speeds, board, check, avoid_hazards);
+    Some(backtrack(&visited, goal?))
+}
 
-        let mut next_to_visit = None;
-        let mut found_goal = false;
+/// Every tile next to a starting position, the ones carrying on straight from the way we came first and doubling back
+/// last. Otherwise they're left in the order the board gives them.
+fn first_steps(start: Pos, came_from: Option<Pos>, board: &Board) -> Vec<Pos> {
+    let mut steps: Vec<Pos> = board.neighbors(start).collect();
+    let ahead = came_from.and_then(|from| {
+        Some(Pos {
+            x: (2 * start.x).checked_sub(from.x)?,
+            y: (2 * start.y).checked_sub(from.y)?,
+        })
+    });
+    if let Some(ahead) = ahead {
+        steps.sort_by_key(|step| board.distance(*step, ahead));
+    }
+    steps
+}
 
-        info!("Starting bfs from {start:?}");
+/// How long a single step between two neighbouring tiles takes something that moves at a given speed along each
+/// axis, in fractions of a tick: anything that's quicker going one way than the other gets further in a tick going that
+/// way. Steps cost the same every which way for anything that's just as quick both ways, so searching by cost turns up
+/// the same paths a plain breadth-first search would.
+fn step_cost(from: Pos, to: Pos, (max_x, max_y): (usize, usize)) -> usize {
+    // scaled up by both speeds, so it all stays in whole numbers
+    let (max_x, max_y) = (max_x.max(1), max_y.max(1));
+    (from.x.abs_diff(to.x) * max_y).max(from.y.abs_diff(to.y) * max_x)
+}
 
-        while !horizon.is_empty() {
-            next_to_visit = horizon.pop_front(); // again, safe because we just verified it isn't empty
-            let cur_pos = next_to_visit.unwrap();
-            if check(cur_pos, board) && start != cur_pos {
-                info!("{cur_pos:?} is the goal!");
-                found_goal = true;
-                break;
-            }
+/// Tiles waiting to be looked at, bucketed by how long it takes to get to them. The cheapest always come out first,
+/// and anything that costs the same comes out in the order it went in. No step costs more than there are buckets less
+/// one, so nothing waiting at the same time ever ends up sharing a bucket with something that costs more.
+struct Frontier {
+    buckets: Vec<VecDeque<Pos>>,
+    /// What whatever comes out next costs, at least.
+    cost: usize,
+    len: usize,
+}
 
-            // it isn't our goal
-            let tile = board.get_tile_from_pos(cur_pos);
-            if tile.is_occupied() && cur_pos != start {
-                continue;
+impl Frontier {
+    fn new(max_step: usize) -> Self {
+        Self {
+            buckets: vec![VecDeque::new(); max_step + 1],
+            cost: 0,
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, pos: Pos, cost: usize) {
+        let buckets = self.buckets.len();
+        self.buckets[cost % buckets].push_back(pos);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<(Pos, usize)> {
+        if self.len == 0 {
+            return None;
+        }
+        let buckets = self.buckets.len();
+        loop {
+            if let Some(pos) = self.buckets[self.cost % buckets].pop_front() {
+                self.len -= 1;
+                return Some((pos, self.cost));
             }
+            self.cost += 1;
+        }
+    }
+}
 
-            // println!("Considering {cur_pos:?}");
+/// Every tile a search reached, along with the tile it was reached from and what it cost to get there.
+type Reached = HashMap<Pos, (Option<Pos>, usize)>;
+
+/// Search outwards from a starting position until something passes the check, cheapest tiles first for something that
+/// moves at the given speeds (see step_cost). Returns every tile that was reached, and the goal if one was found.
+/// Whichever way out of the start gets tried first wins any ties between equally quick paths, so that's decided by
+/// the way we came (see first_steps).
+fn explore<T>(
+    start: Pos,
+    came_from: Option<Pos>,
+    speeds: (usize, usize),
+    board: &Board,
+    check: &T,
+    avoid_hazards: bool,
+) -> (Reached, Option<Pos>)
+where
+    T: Fn(Pos, &Board) -> bool,
+{
+    let mut visited = Reached::new();
+    let mut horizon = Frontier::new(speeds.0.max(speeds.1).max(1));
 
-            for neighbor in Self::get_adjacent(cur_pos, x_max, y_max) {
-                // println!("Pushing back neighbor {neighbor:?}");
-                if visited.contains_key(&neighbor) {
-                    continue;
-                }
-                // If we haven't hit our goal and this tile is occupied, don't consider it.
+    horizon.push(start, 0);
+    visited.insert(start, (None, 0));
 
-                // let working_state = neighbor;
-                let resulting_entry = visited.entry(neighbor).or_insert(Some(cur_pos));
+    info!("Starting bfs from {start:?}");
 
-                if resulting_entry.is_some() {
-                    horizon.push_back(neighbor);
-                }
-            }
+    while let Some((cur_pos, cost)) = horizon.pop() {
+        // we've since found a quicker way here, and already looked around from there
+        if visited[&cur_pos].1 < cost {
+            continue;
         }
-
-        if !found_goal {
-            println!("Gave up in bfs");
-            return None;
-        } else {
-            println!("Found our entity at {next_to_visit:?}")
+        if check(cur_pos, board) && start != cur_pos {
+            info!("{cur_pos:?} is the goal!");
+            debug!("Found our entity at {cur_pos:?}");
+            return (visited, Some(cur_pos));
         }
 
-        let mut path: Vec<Pos> = Vec::new();
-        let original_end_node = next_to_visit.unwrap();
-        let mut parent = &original_end_node;
-        // backtrack
-        while let Some(Some(next_pos)) = visited.get(parent) {
-            path.push(*next_pos);
-            parent = next_pos;
+        // it isn't our goal
+        let tile = board.get_tile_from_pos(cur_pos);
+        if tile.is_occupied() && cur_pos != start {
+            continue;
         }
 
-        path.reverse();
+        // only the first step needs sorting, every tile found past that is queued up in the order it was reached
+        let mut steps = Vec::new();
+        if cur_pos == start {
+            steps = first_steps(start, came_from, board);
+        }
+        let onwards = board.neighbors(cur_pos).filter(|_| cur_pos != start);
+        for neighbor in steps.into_iter().chain(onwards) {
+            let reached = cost + step_cost(cur_pos, neighbor, speeds);
+            if visited
+                .get(&neighbor)
+                .is_some_and(|(_, quickest)| *quickest <= reached)
+            {
+                continue;
+            }
+            if avoid_hazards && board.near_hazard(neighbor) && !check(neighbor, board) {
+                continue;
+            }
+            visited.insert(neighbor, (Some(cur_pos), reached));
+            horizon.push(neighbor, reached);
+        }
+    }
 
-        // dbg!(path);
+    debug!("Gave up in bfs");
+    (visited, None)
+}
 
-        Some(path)
+/// Follow the trail a search left back from the goal to where it started. The goal itself is left off.
+fn backtrack(visited: &Reached, goal: Pos) -> Vec<Pos> {
+    let mut path: Vec<Pos> = Vec::new();
+    let mut parent = &goal;
+    // backtrack
+    while let Some((Some(next_pos), _)) = visited.get(parent) {
+        path.push(*next_pos);
+        parent = next_pos;
     }
 
-    // again, an iterator here would be lovely
-    // but that's a lot of work
-    fn get_adjacent(pos: Pos, x_max: usize, y_max: usize) -> Vec<Pos> {
-        let x_min = (pos.x as i64 - 1).max(0) as usize;
-        let y_min = (pos.y as i64 - 1).max(0) as usize;
-        let x_max = (pos.x + 1).min(x_max);
-        let y_max = (pos.y + 1).min(y_max);
+    path.reverse();
 
-        let mut ret = vec![];
+    // dbg!(path);
 
-        for x in x_min..=x_max {
-            for y in y_min..=y_max {
-                if x != pos.x && y != pos.y {
-                    ret.push(Pos { x, y })
-                }
-            }
-        }
-        // dbg!(&ret);
-        ret
-    }
+    path
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct IdleAction {
     // These vars are mostly just here so we can incorporate them in
-    /// If true, and we want to mate and have an adjacent mate, do the do.
-    mate_adjacent: bool,
-    /// Same for above, but for adjacent food.
+    /// If true, and we're hungry and have adjacent food, eat it.
+    /// Mating takes a courtship, so that's always left up to MateAction.
     feed_adjacent: bool,
+    /// What we're wandering around: the middle of our patrol, or the plant we're hovering near.
+    home: Option<Pos>,
+    /// Which corner of our patrol we're headed for next.
+    waypoint: usize,
 }
 
 impl IdleAction {
-    pub fn new(mate_adjacent: bool, feed_adjacent: bool) -> Self {
+    pub fn new(feed_adjacent: bool) -> Self {
         IdleAction {
-            mate_adjacent,
             feed_adjacent,
+            home: None,
+            waypoint: 0,
+        }
+    }
+
+    /// Where we're wandering around, if we've settled on somewhere yet.
+    pub fn home(&self) -> Option<Pos> {
+        self.home
+    }
+
+    /// Settle on somewhere to wander around if our style needs one and we don't have one yet, and move on to the next
+    /// corner of our patrol once we've reached this one or can't get any closer to it.
+    fn settle(&mut self, actor: &Animals, ctx: &ProcessingContext, board: &Board) {
+        let position = ctx.position;
+        match actor.wander_style(&ctx.rules) {
+            WanderStyle::Patrol => {
+                self.home.get_or_insert(position);
+                for _ in 0..4 {
+                    if self.patrol_step(actor, position, board).is_some() {
+                        break;
+                    }
+                    self.waypoint = (self.waypoint + 1) % 4;
+                }
+            }
+            WanderStyle::Hover => {
+                let still_there = self
+                    .home
+                    .and_then(|h| board.entity_at(h))
+                    .is_some_and(|e| e.plant().is_some());
+                if !still_there {
+                    self.home = board
+                        .positions_within(HOVER_LOOKOUT, position)
+                        .filter(|p| board.entity_at(*p).is_some_and(|e| e.plant().is_some()))
+                        .min_by_key(|p| board.distance(position, *p));
+                }
+            }
+            WanderStyle::Roam | WanderStyle::Drift | WanderStyle::Still => (),
+        }
+    }
+
+    /// The corner of our patrol we're headed for, going clockwise from the top left and kept on the board.
+    fn corner(&self, home: Pos, board: &Board) -> Pos {
+        let (cols, rows) = board.dims();
+        let (left, top) = (
+            home.x.saturating_sub(PATROL_RADIUS),
+            home.y.saturating_sub(PATROL_RADIUS),
+        );
+        let (right, bottom) = (
+            (home.x + PATROL_RADIUS).min(cols.saturating_sub(1)),
+            (home.y + PATROL_RADIUS).min(rows.saturating_sub(1)),
+        );
+        match self.waypoint {
+            0 => Pos { x: left, y: top },
+            1 => Pos { x: right, y: top },
+            2 => Pos {
+                x: right,
+                y: bottom,
+            },
+            _ => Pos { x: left, y: bottom },
+        }
+    }
+
+    /// Off to a random empty tile in reach that passes a check, or nowhere at all now and then.
+    fn roam(
+        actor: &Animals,
+        ctx: &ProcessingContext,
+        board: &Board,
+        keep: impl Fn(Pos) -> bool,
+    ) -> Option<Pos> {
+        let mut rng = ctx.rng.lock().unwrap();
+
+        // the lazier we are, the more often we can't be bothered
+        let stay = IDLE_STAY_CHANCE * Personality::sway(actor.personality(&ctx.rules).laziness);
+        if rng.gen_bool(stay.min(1.0)) {
+            return None;
+        }
+
+        let a = actor.body();
+        let (max_x, max_y) = a.get_max_movespeed();
+        for _ in 0..5 {
+            // start over from where we are on every try, same as Animals::random_walk
+            let mut new_pos = ctx.position;
+            let mut new_x_offset = rng.gen_range(-(max_x as i64)..=(max_x as i64));
+            let mut new_y_offset = rng.gen_range(-(max_y as i64)..=(max_y as i64));
+
+            debug!("moving to {new_x_offset}, {new_y_offset}");
+            // don't underflow bestie
+            if (new_pos.x as i64) + new_x_offset < 0 {
+                new_x_offset = 0;
+            }
+            if (new_pos.y as i64) + new_y_offset < 0 {
+                new_y_offset = 0;
+            }
+            new_pos.x = (new_pos.x as i64 + new_x_offset) as usize;
+            new_pos.y = (new_pos.y as i64 + new_y_offset) as usize;
+            info!("{a:?} moving to {new_pos:?}");
+            if board.is_valid_pos(new_pos) && keep(new_pos) {
+                let target_tile = board.get_tile_from_pos(new_pos);
+                if !target_tile.is_occupied() {
+                    return Some(new_pos);
+                }
+            }
+        }
+        None
+    }
+
+    /// A tile downstream, or any empty one next to us if the water's still, same as a drifting seed.
+    fn drift(ctx: &ProcessingContext, board: &Board) -> Option<Pos> {
+        let free = |p: &Pos| board.is_valid_pos(*p) && !board.get_tile_from_pos(*p).is_occupied();
+        match board.get_current() {
+            Current::Still => board
+                .neighbors(ctx.position)
+                .filter(free)
+                .choose(&mut *ctx.rng.lock().unwrap()),
+            current => current.downstream(ctx.position).filter(free),
         }
     }
+
+    /// Where to go next while wandering in a particular style, whatever our species' usual one is.
+    pub fn wander(
+        &self,
+        style: WanderStyle,
+        actor: &Animals,
+        ctx: &ProcessingContext,
+        board: &Board,
+    ) -> Option<Pos> {
+        match style {
+            WanderStyle::Roam => Self::roam(actor, ctx, board, |_| true),
+            WanderStyle::Drift => Self::drift(ctx, board),
+            WanderStyle::Patrol => self.patrol_step(actor, ctx.position, board),
+            WanderStyle::Hover => match self.home {
+                // anything that's strayed too far only heads back in
+                Some(home) => {
+                    let leash =
+                        HOVER_RADIUS.max(board.distance(ctx.position, home).saturating_sub(1));
+                    Self::roam(actor, ctx, board, |p| board.distance(p, home) <= leash)
+                }
+                // nowhere to settle, so we may as well look around
+                None => Self::roam(actor, ctx, board, |_| true),
+            },
+            WanderStyle::Still => None,
+        }
+    }
+
+    /// Whichever empty tile in reach and inside our patrol gets us closest to its next corner. None if nothing gets
+    /// us any closer, or if we haven't settled on where we're patrolling around yet.
+    fn patrol_step(&self, actor: &Animals, position: Pos, board: &Board) -> Option<Pos> {
+        let home = self.home?;
+        let corner = self.corner(home, board);
+        let (max_x, max_y) = actor.max_speeds();
+        let best = board
+            .positions_within(max_x.max(max_y), position)
+            .filter(|p| p.x.abs_diff(position.x) <= max_x && p.y.abs_diff(position.y) <= max_y)
+            .filter(|p| p.x.abs_diff(home.x).max(p.y.abs_diff(home.y)) <= PATROL_RADIUS)
+            .filter(|p| !board.get_tile_from_pos(*p).is_occupied())
+            .min_by_key(|p| board.distance(*p, corner))?;
+        (board.distance(best, corner) < board.distance(position, corner)).then_some(best)
+    }
 }
 
 impl AIAction<Animals> for IdleAction {
@@ -417,8 +806,8 @@ impl AIAction<Animals> for IdleAction {
         false // never finishes
     }
 
-    fn priority(&self) -> usize {
-        0 // should only be doing this if you have nothing better to
+    fn urgency(&self, _: &Animals, weights: &BehaviorWeights) -> f64 {
+        weights.idle // should only be doing this if you have nothing better to
     }
 
     fn get_action_desc(&self) -> String {
@@ -431,39 +820,33 @@ impl AIAction<Animals> for IdleAction {
         ctx: &ProcessingContext,
         board: &mut Board,
     ) -> Option<PostProcessResult> {
-        // TODO maybe random walk
+        self.settle(actor, ctx, board);
 
-        let mut can_mate = actor.can_mate();
-        let mut should_try_to_eat = actor.should_consider_eating();
+        // nothing on the run stops for a snack
+        let mut should_try_to_eat = actor.should_consider_eating() && !actor.fleeing();
 
-        if (can_mate && self.mate_adjacent) || (should_try_to_eat && self.feed_adjacent) {
+        if should_try_to_eat && self.feed_adjacent {
             // one loop immediately around us.
             // TODO SHOULD PROBABLY MAKE SOME KIND OF STATE MACHINE LIKE BEHAVIOR FOR CREATURES
-            for p in board.range(1, false, ctx.position) {
-                if !can_mate && !should_try_to_eat {
+            for p in board.neighbors(ctx.position) {
+                if !should_try_to_eat {
                     break;
                 }
                 // println!("{self:?} is looking to mate ({can_mate}) and eat ({should_try_to_eat})");
-                let tile = board.get_tile_mut_from_pos(p);
-                if let Some(e) = tile.get_entity_mut() {
+                if let Some(e) = board.entity_at_mut(p) {
                     match e {
                         Entity::NonLiving(_) => (),
                         Entity::Living(l) => match l {
                             Living::Animals(a) => {
-                                if should_try_to_eat && actor.can_eat(a) {
-                                    actor.eat(a);
+                                if should_try_to_eat && actor.can_eat(a, &ctx.rules) {
+                                    actor.hunt(a, &mut *ctx.rng.lock().unwrap(), &ctx.rules);
                                     should_try_to_eat = false;
                                 }
-                                if can_mate && actor.compatible_mate(a) {
-                                    info!("Trying to mate!");
-                                    actor.mate(a);
-                                    can_mate = false;
-                                }
                             }
                             Living::Plants(p) => {
-                                if should_try_to_eat && actor.can_eat(p) {
+                                if should_try_to_eat && actor.can_eat(p, &ctx.rules) {
                                     info!("{self:?} has eaten a tasty plant!");
-                                    actor.eat(p);
+                                    actor.eat(p, &ctx.rules);
                                     should_try_to_eat = false;
                                 }
                             }
@@ -483,60 +866,57 @@ impl AIAction<Animals> for IdleAction {
 
     fn get_movement(&self, actor: &Animals, ctx: &ProcessingContext, board: &Board) -> Option<Pos> {
         debug!("Idle moving!");
-        let mut new_pos = ctx.position;
 
         if matches!(actor.get_life_status(), LifeStatus::Dead) {
             return None; // don't dance if you're dead
         }
 
-        let mut rng: ThreadRng = rand::thread_rng();
-
-        if rng.gen_bool(0.3) {
-            // 50% chance they will just do nothing
-            return None;
-        }
-
-        for _ in 0..5 {
-            match actor {
-                Animals::Fish(a) | Animals::Crab(a) | Animals::Shark(a) => {
-                    let (max_x, max_y) = a.get_max_movespeed();
-                    let mut new_x_offset = rng.gen_range(-(max_x as i64)..=(max_x as i64));
-                    let mut new_y_offset = rng.gen_range(-(max_y as i64)..=(max_y as i64));
-
-                    debug!("moving to {new_x_offset}, {new_y_offset}");
-                    // don't underflow bestie
-                    if (new_pos.x as i64) + new_x_offset < 0 {
-                        new_x_offset = 0;
-                    }
-                    if (new_pos.y as i64) + new_y_offset < 0 {
-                        new_y_offset = 0;
-                    }
-                    new_pos.x = (new_pos.x as i64 + new_x_offset) as usize;
-                    new_pos.y = (new_pos.y as i64 + new_y_offset) as usize;
-                    info!("{a:?} moving to {new_pos:?}");
-                    if board.is_valid_pos(new_pos) {
-                        let target_tile = board.get_tile_from_pos(new_pos);
-                        if !target_tile.is_occupied() {
-                            return Some(new_pos);
-                        }
-                    }
-                }
-            }
-        }
-        None
+        self.wander(actor.wander_style(&ctx.rules), actor, ctx, board)
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct MateAction {
     done: bool,
+    /// Whoever we're courting, once we've found someone next to us.
+    partner: Option<EntityID>,
+    /// How many ticks in a row we've spent next to our partner. Anything coming between us starts it over.
+    courtship: usize,
 }
 
 impl Pathfinder for MateAction {}
 
 impl MateAction {
     pub fn new() -> Self {
-        Self { done: false }
+        Self {
+            done: false,
+            partner: None,
+            courtship: 0,
+        }
+    }
+
+    /// Whoever we're courting is still right next to us.
+    fn partner_adjacent(&self, position: Pos, board: &Board) -> bool {
+        self.courtship > 0
+            && board
+                .neighbors(position)
+                .any(|p| board.entity_at(p).and_then(|e| e.get_id()) == self.partner)
+    }
+
+    /// Whether there's something at a position that we could mate with.
+    fn suitor_at(actor: &Animals, pos: Pos, board: &Board, rules: &InteractionMatrix) -> bool {
+        match board.entity_at(pos) {
+            Some(Entity::Living(Living::Animals(a))) => {
+                actor.compatible_mate(a, rules) && actor.can_mate()
+            }
+            _ => false,
+        }
+    }
+
+    /// Give up on whoever we were courting.
+    fn break_off(&mut self) {
+        self.partner = None;
+        self.courtship = 0;
     }
 }
 
@@ -551,24 +931,36 @@ impl AIAction<Animals> for MateAction {
         todo!()
     }
 
-    fn priority(&self) -> usize {
-        1
+    fn urgency(&self, actor: &Animals, weights: &BehaviorWeights) -> f64 {
+        // nothing carrying a child goes looking for another, and anything hungry or hurt has better things to do
+        if actor.is_pregnant() {
+            return 0.0;
+        }
+        weights.mating * actor.hp_fraction() * (1.0 - actor.body().hunger.urgency())
     }
 
     fn untargeted(&self) -> bool {
         false
     }
 
-    fn is_valid_target(actor: &Animals, target: &Entity, _: &ProcessingContext, _: &Board) -> bool {
+    fn is_valid_target(
+        actor: &Animals,
+        target: &Entity,
+        ctx: &ProcessingContext,
+        _: &Board,
+    ) -> bool {
         if let Entity::Living(Living::Animals(a)) = target {
-            actor.compatible_mate(a) && a != actor
+            actor.compatible_mate(a, &ctx.rules) && a != actor
         } else {
             false
         }
     }
 
     fn get_action_desc(&self) -> String {
-        "looking for a mate".to_owned()
+        match self.courtship {
+            0 => "looking for a mate".to_owned(),
+            _ => "courting".to_owned(),
+        }
     }
 
     fn tick(
@@ -587,23 +979,54 @@ impl AIAction<Animals> for MateAction {
         // let lock = ctx.entity_context.lock().unwrap();
         // let entity_pos = lock.get_active_entries().get(&self.target);
 
-        // TODO AFTER DINNER
-        // CHASING DOWN ENTITIES FOR THIS IS DUMB AND STUPID
-        // JUST FIND THE CLOSEST ONE TO US AND EAT IT
-
-        for pos in board.range(1, false, ctx.position) {
-            if self.done {
-                return None;
-            }
-
-            let tile = board.get_tile_mut_from_pos(pos);
-            if let Some(Entity::Living(Living::Animals(a))) = tile.get_entity_mut() {
-                if actor.compatible_mate(a) && a != actor {
-                    println!("{self:?} has mated with {a:?}!");
-                    actor.mate(a);
-                    self.done = true;
+        // anyone next to us we could mate with
+        let suitors: Vec<(Pos, Option<EntityID>)> = board
+            .neighbors(ctx.position)
+            .filter_map(|pos| match board.entity_at(pos) {
+                Some(Entity::Living(Living::Animals(a)))
+                    if actor.compatible_mate(a, &ctx.rules) && a != actor =>
+                {
+                    Some((pos, a.get_id()))
                 }
+                _ => None,
+            })
+            .collect();
+        // nobody's in the mood with something hungry watching, though the odd cannibal we're courting doesn't count
+        let predators = actor.threats(ctx.position, board, &ctx.rules);
+        if predators
+            .iter()
+            .any(|p| suitors.iter().all(|(s, _)| s != p))
+        {
+            if self.courtship > 0 {
+                info!("{actor:?} was scared off from courting {:?}", self.partner);
             }
+            self.break_off();
+            return None;
+        }
+
+        // stick with whoever we've been courting as long as they've stuck around, otherwise start on someone new
+        let Some((pos, id)) = suitors
+            .iter()
+            .find(|(_, id)| self.courtship > 0 && *id == self.partner)
+            .or(suitors.first())
+            .copied()
+        else {
+            self.break_off();
+            return None;
+        };
+        if self.courtship == 0 || id != self.partner {
+            self.partner = id;
+            self.courtship = 0;
+        }
+        self.courtship += 1;
+        if self.courtship < COURTSHIP_TICKS {
+            return None;
+        }
+
+        if let Some(Entity::Living(Living::Animals(a))) = board.entity_at_mut(pos) {
+            info!("{self:?} has mated with {a:?}!");
+            actor.mate(a, &ctx.rules);
+            self.done = true;
         }
         None
     }
@@ -617,41 +1040,34 @@ impl AIAction<Animals> for MateAction {
     fn end(self, _: &mut Animals) {}
 
     fn get_movement(&self, actor: &Animals, ctx: &ProcessingContext, board: &Board) -> Option<Pos> {
-        let mut rng = rand::thread_rng();
+        // wandering off would ruin the moment
+        if self.partner_adjacent(ctx.position, board) {
+            return None;
+        }
+        let mut rng = ctx.rng.lock().unwrap();
 
         if !self.is_valid(actor, ctx, board) {
             // skip the expensive stuff
-            println!("We were trying to move as per our behavior, but it wasn't valid...");
-            return actor.random_walk(ctx.position, &mut rng, board);
+            debug!("We were trying to move as per our behavior, but it wasn't valid...");
+            return actor.random_walk(ctx.position, &mut *rng, board);
         }
         // if let Some(p) = entity_pos {
         let (x, y) = actor.max_speeds();
 
         let check = |pos: Pos, board: &Board| {
-            let tile = board.get_tile_from_pos(pos);
-            debug!("Checking tile at {pos:?}");
-            if let Some(ent) = tile.get_entity() {
-                debug!("Checking if we can mate with {ent:?} at {pos:?}");
-                if !actor.can_mate() {
-                    debug!("...but we aren't ready?")
-                }
-                match ent {
-                    Entity::NonLiving(_) => false,
-                    Entity::Living(l) => match l {
-                        Living::Animals(a) => actor.compatible_mate(a) && actor.can_mate(),
-                        _ => false,
-                    },
-                }
-            } else {
-                false
-            }
+            debug!("Checking if we can mate with anything at {pos:?}");
+            Self::suitor_at(actor, pos, board, &ctx.rules)
         };
 
-        let ret = Self::get_next_node(ctx.position, board, x, y, Self::find_path_bfs, check);
+        let came_from = actor.came_from();
+        let smooth_path = |start, board: &Board, check| {
+            Self::find_smooth_path(start, board, check, came_from, (x, y))
+        };
+        let ret = Self::get_next_node(ctx.position, board, x, y, smooth_path, check);
 
         if let Some(p) = ret {
-            if let Some(ent) = &board.get_tile_from_pos(p).get_entity() {
-                println!(
+            if let Some(ent) = board.entity_at(p) {
+                debug!(
                     "{} is trying to mate with {}!",
                     actor.get_display_char(),
                     ent.get_display_char()
@@ -659,7 +1075,7 @@ impl AIAction<Animals> for MateAction {
             }
         } else {
             // ugh, there's some weird bug
-            return actor.random_walk(ctx.position, &mut rng, board);
+            return actor.random_walk(ctx.position, &mut *rng, board);
         }
         ret
     }
@@ -669,22 +1085,147 @@ impl AIAction<Animals> for MateAction {
     }
 }
 
+/// How much more urgent food gets for anything eating for two.
+pub const PREGNANT_APPETITE: f64 = 1.5;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct EatAction {
-    very_hungry: bool,
     should_keep_chasing: bool,
+    /// Whatever we picked out to eat, once we've picked something.
+    target: Option<EntityID>,
 }
 
 impl Pathfinder for EatAction {}
 
 impl EatAction {
-    pub fn new(starving: bool) -> Self {
+    pub fn new() -> Self {
         Self {
-            // target,
-            very_hungry: starving,
             should_keep_chasing: true,
+            target: None,
+        }
+    }
+
+    /// Go after something in particular.
+    pub fn with_target(mut self, target: Option<EntityID>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Whatever we picked out to eat.
+    pub fn target(&self) -> Option<EntityID> {
+        self.target
+    }
+
+    /// Where whatever we picked out to eat is, as long as it's still on the board and we can still see it.
+    fn target_pos(
+        &self,
+        actor: &Animals,
+        from: Pos,
+        board: &Board,
+        rules: &InteractionMatrix,
+    ) -> Option<Pos> {
+        let pos = board.arena().position(self.target?)?;
+        Self::visible_prey(actor, from, pos, board, rules).then_some(pos)
+    }
+
+    /// Whether we'd picked something out and it's since been eaten, died or slipped out of sight.
+    pub fn lost_target(
+        &self,
+        actor: &Animals,
+        from: Pos,
+        board: &Board,
+        rules: &InteractionMatrix,
+    ) -> bool {
+        self.target.is_some() && self.target_pos(actor, from, board, rules).is_none()
+    }
+
+    /// What we're searching for on the way: whatever we picked out, or if that's gone, whatever's nearest, so we
+    /// don't carry on towards wherever it used to be. With nothing in sight at all, we follow our nose instead.
+    fn hunting<'a>(
+        &self,
+        actor: &'a Animals,
+        from: Pos,
+        board: &Board,
+        rules: &'a InteractionMatrix,
+    ) -> impl Fn(Pos, &Board) -> bool + 'a {
+        let target = self.target_pos(actor, from, board, rules);
+        let trail = if target.is_none() && !Self::prey_in_sight(actor, from, board, rules) {
+            Self::scent_trail(actor, from, board)
+        } else {
+            None
+        };
+        move |pos, board| match target {
+            Some(target) => pos == target,
+            None => Some(pos) == trail || Self::visible_prey(actor, from, pos, board, rules),
+        }
+    }
+
+    /// The freshest blood we can smell, if we've got a nose for it. Anything close enough to eat from is as far as the
+    /// trail can take us, so it's only somewhere further off than that.
+    pub fn scent_trail(actor: &Animals, from: Pos, board: &Board) -> Option<Pos> {
+        if actor.smell() == 0 {
+            return None;
+        }
+        // the strongest scent wins, then whichever's nearest, then whichever's nearest the top left
+        board
+            .positions_within(actor.smell(), from)
+            .filter(|p| board.distance(from, *p) > actor.reach())
+            .map(|p| (board.get_tile_from_pos(p).get_scent(), p))
+            .filter(|(scent, _)| *scent > 0.0)
+            .max_by(|(a, a_pos), (b, b_pos)| {
+                a.total_cmp(b).then_with(|| {
+                    let key = |p: &Pos| (board.distance(from, *p), p.y, p.x);
+                    key(b_pos).cmp(&key(a_pos))
+                })
+            })
+            .map(|(_, p)| p)
+    }
+
+    /// Whether there's something at a position that we'd eat, and can see. Plants can't hide, but animals can duck behind cover.
+    fn visible_prey(
+        actor: &Animals,
+        from: Pos,
+        pos: Pos,
+        board: &Board,
+        rules: &InteractionMatrix,
+    ) -> bool {
+        match board.entity_at(pos) {
+            Some(Entity::Living(Living::Animals(a))) => {
+                // anything hiding out in a kelp forest can only be spotted up close
+                let hidden = a.shelters_in_forest()
+                    && board.in_forest(pos)
+                    && board.distance(from, pos) > FOREST_SPOTTING_DISTANCE;
+                actor.can_eat(a, rules) && a != actor && !hidden && board.line_of_sight(from, pos)
+            }
+            Some(Entity::Living(Living::Plants(p))) => actor.can_eat(p, rules),
+            _ => false,
         }
     }
+
+    /// Whether there's anything on the board we'd eat and can see.
+    fn any_visible_prey(actor: &Animals, board: &Board, ctx: &ProcessingContext) -> bool {
+        Self::prey_in_sight(actor, ctx.position, board, &ctx.rules)
+    }
+
+    /// Whether there's anything on the board we'd eat and can see from a position.
+    fn prey_in_sight(actor: &Animals, from: Pos, board: &Board, rules: &InteractionMatrix) -> bool {
+        board
+            .tracked_near(from)
+            .into_iter()
+            .any(|pos| pos != from && Self::visible_prey(actor, from, pos, board, rules))
+    }
+
+    /// Whether there's anything to go after, whether we can see it or only smell it.
+    fn anything_to_hunt(actor: &Animals, board: &Board, ctx: &ProcessingContext) -> bool {
+        Self::any_visible_prey(actor, board, ctx)
+            || Self::scent_trail(actor, ctx.position, board).is_some()
+    }
+}
+
+impl Default for EatAction {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AIAction<Animals> for EatAction {
@@ -692,12 +1233,17 @@ impl AIAction<Animals> for EatAction {
         false
     }
 
-    fn is_valid_target(actor: &Animals, target: &Entity, _: &ProcessingContext, _: &Board) -> bool {
+    fn is_valid_target(
+        actor: &Animals,
+        target: &Entity,
+        ctx: &ProcessingContext,
+        _: &Board,
+    ) -> bool {
         match target {
             Entity::NonLiving(_) => false,
             Entity::Living(l) => match l {
-                Living::Plants(p) => actor.can_eat(p),
-                Living::Animals(a) => actor.can_eat(a),
+                Living::Plants(p) => actor.can_eat(p, &ctx.rules),
+                Living::Animals(a) => actor.can_eat(a, &ctx.rules),
             },
         }
     }
@@ -710,12 +1256,15 @@ impl AIAction<Animals> for EatAction {
         self.should_keep_chasing
     }
 
-    fn priority(&self) -> usize {
-        if self.very_hungry {
-            2
+    fn urgency(&self, actor: &Animals, weights: &BehaviorWeights) -> f64 {
+        // starving slowly eats away at us, so the worse off we are the less it can wait
+        let hurt = 1.0 - actor.hp_fraction();
+        let pregnant = if actor.is_pregnant() {
+            PREGNANT_APPETITE
         } else {
-            1
-        } // boost priority if we're starving
+            1.0
+        };
+        weights.hunger * actor.body().hunger.urgency() * (1.0 + hurt) * pregnant
     }
 
     fn get_action_desc(&self) -> String {
@@ -736,27 +1285,32 @@ impl AIAction<Animals> for EatAction {
             return None;
         }
 
-        for pos in board.range(1, false, ctx.position) {
+        // whatever we picked out gets first go, if it's in reach
+        let mut reachable: Vec<Pos> = actor.in_reach(ctx.position, board).collect();
+        reachable.sort_by_key(|p| board.entity_at(*p).and_then(|e| e.get_id()) != self.target);
+        for pos in reachable {
             if !self.should_keep_chasing {
                 return None;
             }
 
-            let tile = board.get_tile_mut_from_pos(pos);
-            if let Some(ent) = tile.get_entity_mut() {
+            if let Some(ent) = board.entity_at_mut(pos) {
                 match ent {
                     Entity::NonLiving(_) => (),
                     Entity::Living(l) => match l {
                         Living::Animals(a) => {
-                            if actor.can_eat(a) && a != actor {
-                                info!("{self:?} has eaten an animal!");
-                                actor.eat(a);
+                            if actor.can_eat(a, &ctx.rules) && a != actor {
+                                info!("{self:?} has gone after an animal!");
+                                if !actor.hunt(a, &mut *ctx.rng.lock().unwrap(), &ctx.rules) {
+                                    // it got away from us, so we'll have to go after it again
+                                    return None;
+                                }
                                 self.should_keep_chasing = false;
                             }
                         }
                         Living::Plants(p) => {
-                            if actor.can_eat(p) {
+                            if actor.can_eat(p, &ctx.rules) {
                                 info!("{self:?} has eaten a tasty plant!");
-                                actor.eat(p);
+                                actor.eat(p, &ctx.rules);
                                 self.should_keep_chasing = false;
                             }
                         }
@@ -769,8 +1323,10 @@ impl AIAction<Animals> for EatAction {
     }
 
     fn is_valid(&self, actor: &Animals, ctx: &ProcessingContext, board: &Board) -> bool {
+        // losing whatever we were after means picking something else out straight away
         actor.should_consider_eating()
-            && Self::any_available_matches(actor, board, ctx, Self::is_valid_target)
+            && !self.lost_target(actor, ctx.position, board, &ctx.rules)
+            && Self::anything_to_hunt(actor, board, ctx)
     }
 
     fn end(self, _: &mut Animals) {}
@@ -778,40 +1334,45 @@ impl AIAction<Animals> for EatAction {
     fn get_movement(&self, actor: &Animals, ctx: &ProcessingContext, board: &Board) -> Option<Pos> {
         // let lock = ctx.entity_context.write().unwrap();
 
-        let mut rng = rand::thread_rng();
+        let mut rng = ctx.rng.lock().unwrap();
 
-        if !self.is_valid(actor, ctx, board) {
+        // anything that's lost its target still goes after whatever's nearest this tick, and picks it out properly
+        // once it next decides what to do
+        if !actor.should_consider_eating() || !Self::anything_to_hunt(actor, board, ctx) {
             // skip the expensive stuff
-            return actor.random_walk(ctx.position, &mut rng, board);
+            return actor.random_walk(ctx.position, &mut *rng, board);
         }
         // if let Some(p) = entity_pos {
         let (x, y) = actor.max_speeds();
 
-        let check = |pos: Pos, board: &Board| {
-            let tile = board.get_tile_from_pos(pos);
-            debug!("Checking tile at {pos:?}");
-            if let Some(ent) = tile.get_entity() {
-                debug!("Checking if we can eat {ent:?} at {pos:?}");
-                if !actor.should_consider_eating() {
-                    debug!("...but we aren't even hungry?")
-                }
-                match ent {
-                    Entity::NonLiving(_) => false,
-                    Entity::Living(l) => match l {
-                        Living::Animals(a) => actor.can_eat(a) && a != actor,
-                        Living::Plants(p) => actor.can_eat(p),
-                    },
-                }
-            } else {
-                false
+        let check = self.hunting(actor, ctx.position, board, &ctx.rules);
+        // anything we can already get at gets eaten from right here
+        if actor.in_reach(ctx.position, board).any(|p| check(p, board)) {
+            return None;
+        }
+
+        let came_from = actor.came_from();
+        let is_prey = self.hunting(actor, ctx.position, board, &ctx.rules);
+        let smooth_path = |start, board: &Board, check| {
+            let mut path = Self::find_smooth_path(start, board, check, came_from, (x, y))?;
+            // paths end next to whatever they found, but there's no getting any closer than we can eat it from
+            let prey = path
+                .last()
+                .and_then(|last| board.neighbors(*last).find(|p| is_prey(*p, board)));
+            let close_enough = prey.and_then(|prey| {
+                path.iter()
+                    .position(|p| actor.within_reach(*p, prey, board))
+            });
+            if let Some(close_enough) = close_enough {
+                path.truncate(close_enough + 1);
             }
+            Some(path)
         };
-
-        let ret = Self::get_next_node(ctx.position, board, x, y, Self::find_path_bfs, check);
+        let ret = Self::get_next_node(ctx.position, board, x, y, smooth_path, check);
 
         if let Some(p) = ret {
-            if let Some(ent) = &board.get_tile_from_pos(p).get_entity() {
-                println!(
+            if let Some(ent) = board.entity_at(p) {
+                debug!(
                     "{} is trying to eat {}!",
                     actor.get_display_char(),
                     ent.get_display_char()
@@ -819,7 +1380,7 @@ impl AIAction<Animals> for EatAction {
             }
         } else {
             // if we didn't move anywhere, just try to go somewhere
-            return actor.random_walk(ctx.position, &mut rng, board);
+            return actor.random_walk(ctx.position, &mut *rng, board);
         }
         ret
     }
@@ -0,0 +1,148 @@
+// Family trees for every animal born on the board. Children know who their parents were from the moment they're born (see
+// Reproduction::parents), and the lineage here catches up with them after every tick, hooking each one up to its parents so
+// the whole tree can be walked from either end. Entity IDs are never handed out twice, even between regions, so everything
+// stays in the tree after it dies.
+
+use std::collections::HashMap;
+
+use crate::entities::{Entity, Living, Species};
+use crate::entity_control::{EntityID, TrackedEntity};
+use crate::game_board::Board;
+
+/// How many of the longest running families get shown to the player.
+pub const DYNASTIES_SHOWN: usize = 5;
+
+/// What's known about one animal's place in its family.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ancestor {
+    pub species: Species,
+    pub generation: usize,
+    pub parents: Vec<EntityID>,
+    /// Every child it's had, oldest first.
+    pub children: Vec<EntityID>,
+}
+
+/// Every animal that's been seen on the board, and how they're all related.
+#[derive(Debug, Default)]
+pub struct Lineage {
+    ancestors: HashMap<EntityID, Ancestor>,
+}
+
+impl Lineage {
+    /// Add any animals on the board we haven't seen before, and let their parents know about them.
+    pub fn record(&mut self, board: &Board) {
+        let mut newborns: Vec<EntityID> = vec![];
+        for (_, entity) in board.iter_occupied() {
+            let Entity::Living(Living::Animals(animal)) = entity else {
+                continue;
+            };
+            let Some(id) = animal.get_id() else {
+                continue;
+            };
+            if self.ancestors.contains_key(&id) {
+                continue;
+            }
+            let reproduction = &animal.body().reproduction;
+            self.ancestors.insert(
+                id,
+                Ancestor {
+                    species: Species::of(entity),
+                    generation: reproduction.generation,
+                    parents: reproduction.parents.clone(),
+                    children: vec![],
+                },
+            );
+            newborns.push(id);
+        }
+        // IDs go up as they're handed out, so children always end up in the order they were born
+        newborns.sort_unstable();
+        for child in newborns {
+            for parent in self.ancestors[&child].parents.clone() {
+                if let Some(parent) = self.ancestors.get_mut(&parent) {
+                    parent.children.push(child);
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, id: EntityID) -> Option<&Ancestor> {
+        self.ancestors.get(&id)
+    }
+
+    /// How many generations have come after an animal, counting down through its longest line of descendants.
+    /// Something with no children has a depth of 0.
+    pub fn depth(&self, id: EntityID) -> usize {
+        self.get(id)
+            .map(|a| &a.children)
+            .into_iter()
+            .flatten()
+            .map(|child| self.depth(*child) + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The animals with the most generations after them, deepest first, up to `count` of them.
+    /// Only founders (anything with no known parents) count, so a dynasty isn't counted again for every one of its members.
+    pub fn longest_dynasties(&self, count: usize) -> Vec<(EntityID, usize)> {
+        let mut dynasties: Vec<(EntityID, usize)> = self
+            .ancestors
+            .iter()
+            .filter(|(_, a)| a.parents.is_empty() && !a.children.is_empty())
+            .map(|(id, _)| (*id, self.depth(*id)))
+            .collect();
+        dynasties
+            .sort_unstable_by(|(a, a_depth), (b, b_depth)| b_depth.cmp(a_depth).then(a.cmp(b)));
+        dynasties.truncate(count);
+        dynasties
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::NonAbstractTaxonomy;
+    use crate::game_board::Pos;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_family_tree() {
+        let mut testbed = TestBed::builder(2, 2)
+            .with(Pos { x: 0, y: 0 }, Species::Fish)
+            .with(Pos { x: 1, y: 0 }, Species::Fish)
+            .build();
+        let (mother, father) = (
+            testbed.id_at(Pos { x: 0, y: 0 }),
+            testbed.id_at(Pos { x: 1, y: 0 }),
+        );
+        let mut lineage = Lineage::default();
+        lineage.record(&testbed.sandbox.board);
+
+        let mut child = Species::Fish.create_new(None);
+        child.reproduction_mut().unwrap().parents = vec![mother, father];
+        testbed
+            .sandbox
+            .board
+            .place_entity(Pos { x: 0, y: 1 }, child)
+            .unwrap();
+        let child = testbed.id_at(Pos { x: 0, y: 1 });
+        let mut grandchild = Species::Fish.create_new(None);
+        grandchild.reproduction_mut().unwrap().parents = vec![child];
+        testbed
+            .sandbox
+            .board
+            .place_entity(Pos { x: 1, y: 1 }, grandchild)
+            .unwrap();
+        lineage.record(&testbed.sandbox.board);
+
+        assert_eq!(lineage.get(child).unwrap().parents, vec![mother, father]);
+        assert_eq!(lineage.get(father).unwrap().children, vec![child]);
+        assert_eq!(lineage.depth(mother), 2);
+        assert_eq!(lineage.depth(child), 1);
+        assert_eq!(lineage.longest_dynasties(5), vec![(mother, 2), (father, 2)]);
+
+        // the family stays together after the first generation is gone
+        testbed.sandbox.board.remove_entity(Pos { x: 0, y: 0 });
+        lineage.record(&testbed.sandbox.board);
+        assert_eq!(lineage.longest_dynasties(1), vec![(mother, 2)]);
+    }
+}
@@ -0,0 +1,177 @@
+// Where every tracked entity on a board lives. Tiles only hold onto the ID of whatever's on them (and which slot it's in),
+// so moving something around is just a matter of moving its ID from one tile to another, and anything can be looked up
+// straight from its ID. The arena keeps track of where everything is, too, so finding every tracked entity on the board
+// never has to go through the entity manager.
+// While an entity's being worked on it gets lent out of the arena, but its tile keeps hold of its ID the whole time, so
+// nothing else can take its spot and there's always somewhere to give it back to. Nothing gets dropped on the floor.
+// Scenery without an ID of its own (rocks, shells and the like) never moves or gets processed, so it stays on its tile.
+
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+
+use crate::entities::Entity;
+use crate::entity_control::EntityID;
+use crate::game_board::Pos;
+
+/// Hashes IDs with a single multiply. Everything on the board gets looked up by ID over and over every tick, and IDs
+/// are handed out one after another by us, so there's nothing to gain from a slower, DoS-resistant hash.
+#[derive(Debug, Clone, Copy, Default)]
+struct IdHasher(u64);
+
+impl Hasher for IdHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.write_u64(*byte as u64);
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.0 = (self.0.rotate_left(5) ^ n).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.write_u64(n as u64);
+    }
+}
+
+/// Every tracked entity on a board, kept in slots that get reused as things come and go. Tiles hold onto the slot
+/// along with the ID, so looking up whatever's on a tile never has to go through the ID at all.
+#[derive(Debug, Clone, Default)]
+pub struct EntityArena {
+    /// Each slot's entity along with its ID, where the entity is None while it's been lent out.
+    slots: Vec<Option<(EntityID, Option<Entity>)>>,
+    /// Slots that are empty and ready to be reused.
+    free: Vec<usize>,
+    /// Which slot each ID is in.
+    index: HashMap<EntityID, usize, BuildHasherDefault<IdHasher>>,
+    /// Where each entity is. Anything looking around for something to eat or mate with runs down all of these and
+    /// usually stops at the first that'll do, so they're hashed the usual way to keep them well shuffled.
+    positions: HashMap<EntityID, Pos>,
+}
+
+impl EntityArena {
+    /// The entity with the given ID, unless it's not on the board or has been lent out.
+    pub fn get(&self, id: EntityID) -> Option<&Entity> {
+        self.at(*self.index.get(&id)?)
+    }
+
+    pub fn get_mut(&mut self, id: EntityID) -> Option<&mut Entity> {
+        self.at_mut(*self.index.get(&id)?)
+    }
+
+    /// How many entities there are, including any that are lent out.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Where the entity with the given ID is, even if it's been lent out.
+    pub fn position(&self, id: EntityID) -> Option<Pos> {
+        self.positions.get(&id).copied()
+    }
+
+    /// Where every entity is, including any that are lent out.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.positions.values().copied()
+    }
+
+    /// Every entity that hasn't been lent out, in no particular order.
+    pub fn entities(&self) -> impl Iterator<Item = &Entity> + '_ {
+        self.slots
+            .iter()
+            .filter_map(|slot| slot.as_ref()?.1.as_ref())
+    }
+
+    pub(crate) fn at(&self, slot: usize) -> Option<&Entity> {
+        self.slots.get(slot)?.as_ref()?.1.as_ref()
+    }
+
+    pub(crate) fn at_mut(&mut self, slot: usize) -> Option<&mut Entity> {
+        self.slots.get_mut(slot)?.as_mut()?.1.as_mut()
+    }
+
+    /// Put an entity in the arena at a position, returning the slot it ended up in.
+    pub(crate) fn insert(&mut self, id: EntityID, pos: Pos, entity: Entity) -> usize {
+        let slot = match self.index.get(&id) {
+            Some(slot) => *slot,
+            None => self.free.pop().unwrap_or_else(|| {
+                self.slots.push(None);
+                self.slots.len() - 1
+            }),
+        };
+        self.slots[slot] = Some((id, Some(entity)));
+        self.index.insert(id, slot);
+        self.positions.insert(id, pos);
+        slot
+    }
+
+    /// Note down that an entity has moved.
+    pub(crate) fn move_to(&mut self, id: EntityID, pos: Pos) {
+        if let Some(at) = self.positions.get_mut(&id) {
+            *at = pos;
+        }
+    }
+
+    /// Take an entity out of the arena for good. Anything that's been lent out has to be given back first.
+    pub(crate) fn remove(&mut self, slot: usize) -> Option<Entity> {
+        let (id, entity) = self.slots.get_mut(slot)?.as_mut()?;
+        let (id, entity) = (*id, entity.take()?);
+        self.slots[slot] = None;
+        self.free.push(slot);
+        self.index.remove(&id);
+        self.positions.remove(&id);
+        Some(entity)
+    }
+
+    /// Whether an entity's out being worked on.
+    pub fn is_lent(&self, id: EntityID) -> bool {
+        self.index
+            .get(&id)
+            .is_some_and(|slot| matches!(self.slots[*slot], Some((_, None))))
+    }
+
+    /// Every entity that's out being worked on.
+    pub fn lent_out(&self) -> impl Iterator<Item = EntityID> + '_ {
+        self.slots.iter().filter_map(|slot| match slot {
+            Some((id, None)) => Some(*id),
+            _ => None,
+        })
+    }
+
+    pub(crate) fn lend(&mut self, slot: usize) -> Option<Entity> {
+        self.slots.get_mut(slot)?.as_mut()?.1.take()
+    }
+
+    pub(crate) fn give_back(&mut self, slot: usize, entity: Entity) {
+        if let Some((_, place)) = &mut self.slots[slot] {
+            *place = Some(entity);
+        }
+    }
+
+    /// Stop keeping a place for something that was lent out and is never coming back.
+    pub(crate) fn forget(&mut self, id: EntityID) {
+        self.positions.remove(&id);
+        if let Some(slot) = self.index.remove(&id) {
+            self.slots[slot] = None;
+            self.free.push(slot);
+        }
+    }
+}
+
+/// An entity that's been lent out of a board's arena to be worked on, along with where it came from. It has to go back
+/// through Board::give_back, which always has room for it.
+#[derive(Debug)]
+#[must_use = "a lent entity has to be given back, or its tile stays taken for good"]
+pub struct Lent {
+    pub id: EntityID,
+    pub(crate) slot: usize,
+    pub pos: Pos,
+    pub entity: Entity,
+}
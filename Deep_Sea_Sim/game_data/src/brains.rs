@@ -0,0 +1,317 @@
+// Handing a species' decisions over to something outside the game, for trying out AIs of our own. Every tick, each
+// animal of a species with a brain hooked up gets an Observation of itself, everything it can see, and every option the
+// built in AI would have weighed up, and the brain picks one of those options for it. Anything the brain has no answer
+// for is left to the built in AI, so a brain only has to handle whatever it's interested in.
+//
+// A brain can be anything that implements Brain: a closure, for experimenting in process, or a SocketBrain, which sends
+// every observation off to a controller listening on a socket (like a reinforcement learning agent) and waits for it to
+// answer. Observations go out as plain text, one line per thing, ending with an empty line:
+//
+//     fish 12 at 3,4 hp=100 hunger=80 stamina=100
+//     sees shark at 5,4
+//     sees kelp at 3,5
+//     0: idle at 3,4
+//     1: eating at 3,5
+//
+// and the controller answers with the number of the option it picked on a line of its own, or anything else to leave it
+// to the built in AI.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+
+use log::warn;
+
+use crate::entities::Species;
+use crate::game_board::Pos;
+use crate::Sandbox;
+
+/// One of the options an animal has to pick from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    /// What it would be doing: idle, eating or mating.
+    pub action: &'static str,
+    /// Where it would be doing it.
+    pub target: Pos,
+}
+
+/// Everything an animal knows when it's deciding what to do next.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub id: Option<usize>,
+    pub species: Species,
+    pub position: Pos,
+    pub hp: i64,
+    pub hunger: i64,
+    pub stamina: usize,
+    /// Everything else it can see from where it is.
+    pub surroundings: Vec<(Pos, Species)>,
+    /// Every option it has, for the brain to pick from by number.
+    pub choices: Vec<Choice>,
+}
+
+impl fmt::Display for Observation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id = self.id.map_or(String::from("-"), |id| id.to_string());
+        let Pos { x, y } = self.position;
+        writeln!(
+            f,
+            "{} {id} at {x},{y} hp={} hunger={} stamina={}",
+            self.species.name(),
+            self.hp,
+            self.hunger,
+            self.stamina
+        )?;
+        for (Pos { x, y }, species) in &self.surroundings {
+            writeln!(f, "sees {} at {x},{y}", species.name())?;
+        }
+        for (i, choice) in self.choices.iter().enumerate() {
+            let Pos { x, y } = choice.target;
+            writeln!(f, "{i}: {} at {x},{y}", choice.action)?;
+        }
+        Ok(())
+    }
+}
+
+/// Something that decides what the animals of a species do, instead of the built in AI.
+pub trait Brain: Send {
+    /// Pick one of an animal's choices by its number, or None to let the built in AI decide this time.
+    fn choose(&mut self, observation: &Observation) -> Option<usize>;
+}
+
+impl<F> Brain for F
+where
+    F: FnMut(&Observation) -> Option<usize> + Send,
+{
+    fn choose(&mut self, observation: &Observation) -> Option<usize> {
+        self(observation)
+    }
+}
+
+/// A brain that lives on the other end of a socket.
+pub struct SocketBrain {
+    /// None once the connection's gone, after which the built in AI takes over for good.
+    stream: Option<(BufReader<TcpStream>, TcpStream)>,
+}
+
+impl SocketBrain {
+    /// Connect to a controller that's listening for observations.
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream: Some((BufReader::new(stream.try_clone()?), stream)),
+        })
+    }
+
+    fn ask(&mut self, observation: &Observation) -> io::Result<Option<usize>> {
+        let Some((reader, writer)) = &mut self.stream else {
+            return Ok(None);
+        };
+        writeln!(writer, "{observation}")?;
+        writer.flush()?;
+        let mut answer = String::new();
+        if reader.read_line(&mut answer)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(answer.trim().parse().ok())
+    }
+}
+
+impl Brain for SocketBrain {
+    fn choose(&mut self, observation: &Observation) -> Option<usize> {
+        self.ask(observation).unwrap_or_else(|e| {
+            warn!(
+                "Lost the connection to the controller ({e}), leaving the rest to the built in AI"
+            );
+            self.stream = None;
+            None
+        })
+    }
+}
+
+/// Every species that's been handed over to a brain. Shared between the sandbox and everything it processes.
+#[derive(Clone, Default)]
+pub struct Brains(Arc<Mutex<HashMap<Species, Box<dyn Brain>>>>);
+
+impl Brains {
+    /// Whether a species is being run by a brain.
+    pub fn controls(&self, species: Species) -> bool {
+        self.0.lock().unwrap().contains_key(&species)
+    }
+
+    /// Ask a species' brain which of an animal's choices it should go with. Anything that isn't one of them counts as
+    /// no answer.
+    pub fn choose(&self, observation: &Observation) -> Option<usize> {
+        let mut brains = self.0.lock().unwrap();
+        let brain = brains.get_mut(&observation.species)?;
+        brain
+            .choose(observation)
+            .filter(|i| *i < observation.choices.len())
+    }
+}
+
+impl fmt::Debug for Brains {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let brains = self.0.lock().unwrap();
+        f.debug_set()
+            .entries(brains.keys().map(|s| s.name()))
+            .finish()
+    }
+}
+
+impl Sandbox {
+    /// Hand a species' decisions over to a brain, replacing any it had already.
+    pub fn set_brain(&mut self, species: Species, brain: impl Brain + 'static) {
+        self.brains
+            .0
+            .lock()
+            .unwrap()
+            .insert(species, Box::new(brain));
+    }
+
+    /// Give a species back to the built in AI.
+    pub fn clear_brain(&mut self, species: Species) {
+        self.brains.0.lock().unwrap().remove(&species);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+    use crate::test_utils::TestBed;
+
+    /// A fish between two kelp leaves, hungry enough to want either.
+    fn hungry_fish() -> TestBed {
+        TestBed::builder(1, 5)
+            .with_stats(Pos { x: 2, y: 0 }, Species::Fish, &[("hunger", "10")])
+            .with(Pos { x: 0, y: 0 }, Species::KelpLeaf)
+            .with(Pos { x: 4, y: 0 }, Species::KelpLeaf)
+            .build()
+    }
+
+    #[test]
+    fn test_brains_pick_for_their_species() {
+        let fish = Pos { x: 2, y: 0 };
+        let mut testbed = hungry_fish();
+        let seen = Arc::new(Mutex::new(vec![]));
+        let log = Arc::clone(&seen);
+        testbed
+            .sandbox
+            .set_brain(Species::Fish, move |o: &Observation| {
+                log.lock().unwrap().push(o.clone());
+                // whichever leaf's further right, which the built in AI would never go for first, and nothing else
+                o.choices
+                    .iter()
+                    .position(|c| c.action == "eating" && c.target.x == 4)
+                    .or_else(|| o.choices.iter().position(|c| c.action == "idle"))
+            });
+        assert!(testbed.sandbox.brains.controls(Species::Fish));
+        assert!(!testbed.sandbox.brains.controls(Species::Shark));
+        // before it's had a chance to move
+        testbed.step_processing();
+        {
+            let seen = seen.lock().unwrap();
+            let first = &seen[0];
+            assert_eq!(first.species, Species::Fish);
+            assert_eq!(first.position, fish);
+            assert_eq!(first.hunger, 10);
+            assert!(first
+                .surroundings
+                .contains(&(Pos { x: 0, y: 0 }, Species::KelpLeaf)));
+            assert!(first.to_string().starts_with("fish "));
+            assert!(first.to_string().contains("sees kelp_leaf at 4,0"));
+        }
+
+        // off to the right, for the leaf it was told to go for
+        testbed.run_n_full_steps(1);
+        let right = Pos { x: 3, y: 0 };
+        assert_eq!(
+            testbed.get_entity_at_pos(right).map(Species::of),
+            Some(Species::Fish)
+        );
+        testbed.run_n_full_steps(2);
+        assert!(testbed.get_entity_at_pos(Pos { x: 4, y: 0 }).is_none());
+    }
+
+    #[test]
+    fn test_no_answer_leaves_it_to_the_ai() {
+        let mut testbed = hungry_fish();
+        // an answer that isn't one of the choices doesn't count either
+        testbed
+            .sandbox
+            .set_brain(Species::Fish, |o: &Observation| Some(o.choices.len()));
+        testbed.run_n_full_steps(3);
+        // the built in AI goes for the leaf nearest the top left
+        assert!(testbed.get_entity_at_pos(Pos { x: 0, y: 0 }).is_none());
+
+        let mut testbed = hungry_fish();
+        testbed
+            .sandbox
+            .set_brain(Species::Fish, |_: &Observation| Some(0));
+        testbed.sandbox.clear_brain(Species::Fish);
+        assert!(!testbed.sandbox.brains.controls(Species::Fish));
+    }
+
+    #[test]
+    fn test_socket_brains() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let controller = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut observation = vec![];
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                observation.push(line.trim().to_owned());
+            }
+            // go for the fish, then hang up
+            writeln!(writer, "1").unwrap();
+            observation
+        });
+
+        let mut brain = SocketBrain::connect(addr).unwrap();
+        let observation = Observation {
+            id: Some(3),
+            species: Species::Shark,
+            position: Pos { x: 1, y: 2 },
+            hp: 50,
+            hunger: 20,
+            stamina: 100,
+            surroundings: vec![(Pos { x: 2, y: 2 }, Species::Fish)],
+            choices: vec![
+                Choice {
+                    action: "idle",
+                    target: Pos { x: 1, y: 2 },
+                },
+                Choice {
+                    action: "eating",
+                    target: Pos { x: 2, y: 2 },
+                },
+            ],
+        };
+        assert_eq!(brain.choose(&observation), Some(1));
+        assert_eq!(
+            controller.join().unwrap(),
+            [
+                "shark 3 at 1,2 hp=50 hunger=20 stamina=100",
+                "sees fish at 2,2",
+                "0: idle at 1,2",
+                "1: eating at 2,2"
+            ]
+        );
+        // once the controller's gone, everything goes back to the built in AI
+        assert_eq!(brain.choose(&observation), None);
+        assert_eq!(brain.choose(&observation), None);
+    }
+}
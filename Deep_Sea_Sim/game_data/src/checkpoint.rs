@@ -0,0 +1,382 @@
+// Comparing two saved games, to see exactly what changed between them. Both sides are read in as scenarios (see
+// scenario.rs), so anything that can be saved can be compared: two points in the same game, to see what was born, what
+// died and what got hungrier along the way, or two games played out from the same start, to see which way of playing it
+// did better. Entities are matched up by their IDs, which saved games hang on to. Anything without one, like everything in
+// a hand written scenario, is matched up by where it is and what it is instead.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::entities::{Entity, Species};
+use crate::entity_control::{EntityID, TrackedEntity};
+use crate::game_board::Pos;
+use crate::region::DEFAULT_REGION;
+use crate::scenario::Scenario;
+
+/// One entity, as it was in one of the snapshots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityRecord {
+    pub id: Option<EntityID>,
+    pub species: Species,
+    pub region: String,
+    pub pos: Pos,
+    /// Every stat it has as (stat, value), in the same order they get saved in.
+    pub stats: Vec<(String, String)>,
+}
+
+impl EntityRecord {
+    fn new(region: &str, pos: Pos, entity: &Entity) -> Self {
+        let stats = entity.get_stats();
+        Self {
+            id: entity.get_id(),
+            species: Species::of(entity),
+            region: region.to_owned(),
+            pos,
+            stats: stats
+                .split_whitespace()
+                .filter_map(|s| s.split_once('='))
+                .map(|(stat, value)| (stat.to_owned(), value.to_owned()))
+                .collect(),
+        }
+    }
+
+    pub fn get_stat(&self, stat: &str) -> Option<&str> {
+        self.stats
+            .iter()
+            .find(|(s, _)| s == stat)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether this could be the same thing as `other`, going by where it is rather than by its ID.
+    /// Two things that both have IDs are only ever the same if the IDs are.
+    fn lines_up_with(&self, other: &Self) -> bool {
+        (self.id.is_none() || other.id.is_none())
+            && (&self.region, self.pos, self.species) == (&other.region, other.pos, other.species)
+    }
+}
+
+impl Display for EntityRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.species.name())?;
+        if let Some(id) = self.id {
+            write!(f, " #{}", id.get_id_val())?;
+        }
+        write!(f, " at ({}, {})", self.pos.x, self.pos.y)?;
+        if self.region != DEFAULT_REGION {
+            write!(f, " in {}", self.region)?;
+        }
+        Ok(())
+    }
+}
+
+/// Something that's in both snapshots, but isn't quite the same in both.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityChange {
+    pub before: EntityRecord,
+    pub after: EntityRecord,
+}
+
+impl EntityChange {
+    pub fn moved(&self) -> bool {
+        (&self.before.region, self.before.pos) != (&self.after.region, self.after.pos)
+    }
+
+    /// Every stat that's different, as (stat, before, after).
+    pub fn stat_changes(&self) -> Vec<(&str, &str, &str)> {
+        self.after
+            .stats
+            .iter()
+            .filter_map(|(stat, after)| {
+                let before = self.before.get_stat(stat)?;
+                (before != after).then_some((stat.as_str(), before, after.as_str()))
+            })
+            .collect()
+    }
+}
+
+/// How a whole species did between the snapshots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeciesDelta {
+    pub species: Species,
+    pub before: usize,
+    pub after: usize,
+    /// The average of every stat that's a number, as (stat, before, after).
+    /// Only stats that the species had in both snapshots are here.
+    pub averages: Vec<(String, f64, f64)>,
+}
+
+impl SpeciesDelta {
+    /// How many more of them there are now. Negative if there are fewer.
+    pub fn change(&self) -> isize {
+        self.after as isize - self.before as isize
+    }
+
+    fn is_unchanged(&self) -> bool {
+        self.before == self.after && self.averages.iter().all(|(_, b, a)| b == a)
+    }
+}
+
+/// Everything that's different between two snapshots of a game.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CheckpointDiff {
+    /// Everything that's only in the later snapshot.
+    pub added: Vec<EntityRecord>,
+    /// Everything that's only in the earlier snapshot.
+    pub removed: Vec<EntityRecord>,
+    /// Everything that's in both, but has moved or had its stats change.
+    pub changed: Vec<EntityChange>,
+    /// Every species that's in either snapshot, by name.
+    pub species: Vec<SpeciesDelta>,
+}
+
+impl CheckpointDiff {
+    /// Compare an earlier snapshot of a game with a later one.
+    pub fn new(before: &Scenario, after: &Scenario) -> Self {
+        let records = |scenario: &Scenario| -> Vec<EntityRecord> {
+            scenario
+                .get_regions()
+                .iter()
+                .flat_map(|r| {
+                    r.get_entities()
+                        .iter()
+                        .map(|(pos, e)| EntityRecord::new(r.get_name(), *pos, e))
+                })
+                .collect()
+        };
+        let (before, after) = (records(before), records(after));
+        let mut diff = Self {
+            species: species_deltas(&before, &after),
+            ..Self::default()
+        };
+
+        let mut unmatched: Vec<Option<EntityRecord>> = after.into_iter().map(Some).collect();
+        let by_id: HashMap<EntityID, usize> = unmatched
+            .iter()
+            .enumerate()
+            .filter_map(|(i, r)| Some((r.as_ref()?.id?, i)))
+            .collect();
+        let mut leftover = vec![];
+        for record in before {
+            let found = record.id.and_then(|id| by_id.get(&id)).copied();
+            match found.and_then(|i| unmatched[i].take()) {
+                Some(later) => diff.pair_up(record, later),
+                None => leftover.push(record),
+            }
+        }
+        // anything that couldn't be matched up by ID might still be sitting in the same spot
+        for record in leftover {
+            let found = unmatched
+                .iter_mut()
+                .find(|r| r.as_ref().is_some_and(|r| r.lines_up_with(&record)));
+            match found.and_then(Option::take) {
+                Some(later) => diff.pair_up(record, later),
+                None => diff.removed.push(record),
+            }
+        }
+        diff.added = unmatched.into_iter().flatten().collect();
+        diff
+    }
+
+    fn pair_up(&mut self, before: EntityRecord, after: EntityRecord) {
+        let change = EntityChange { before, after };
+        // something picking up an ID it didn't have before doesn't count as changing
+        if change.moved() || change.before.stats != change.after.stats {
+            self.changed.push(change);
+        }
+    }
+
+    /// Whether every entity is exactly where and how it was.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// How many of each species there are on either side, and what their stats average out to.
+fn species_deltas(before: &[EntityRecord], after: &[EntityRecord]) -> Vec<SpeciesDelta> {
+    let mut species: Vec<Species> = vec![];
+    for record in before.iter().chain(after) {
+        if !species.contains(&record.species) {
+            species.push(record.species);
+        }
+    }
+    species.sort_by_key(Species::name);
+    species
+        .into_iter()
+        .map(|s| {
+            let (before, after): (Vec<_>, Vec<_>) = (
+                before.iter().filter(|r| r.species == s).collect(),
+                after.iter().filter(|r| r.species == s).collect(),
+            );
+            let earlier = averages(&before);
+            let averages = averages(&after)
+                .into_iter()
+                .filter_map(|(stat, a)| {
+                    let b = earlier.iter().find(|(s, _)| *s == stat)?.1;
+                    Some((stat, b, a))
+                })
+                .collect();
+            SpeciesDelta {
+                species: s,
+                before: before.len(),
+                after: after.len(),
+                averages,
+            }
+        })
+        .collect()
+}
+
+/// The average of every stat that's a number, in the order they first show up.
+fn averages(records: &[&EntityRecord]) -> Vec<(String, f64)> {
+    let mut totals: Vec<(String, f64, usize)> = vec![];
+    for (stat, value) in records.iter().flat_map(|r| &r.stats) {
+        let Ok(value) = value.parse::<f64>() else {
+            continue;
+        };
+        match totals.iter_mut().find(|(s, _, _)| s == stat) {
+            Some((_, total, count)) => {
+                *total += value;
+                *count += 1;
+            }
+            None => totals.push((stat.clone(), value, 1)),
+        }
+    }
+    totals
+        .into_iter()
+        .map(|(stat, total, count)| (stat, total / count as f64))
+        .collect()
+}
+
+impl Display for CheckpointDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "Nothing changed");
+        }
+        writeln!(
+            f,
+            "{} added, {} removed, {} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )?;
+        for record in &self.added {
+            writeln!(f, "+ {record}")?;
+        }
+        for record in &self.removed {
+            writeln!(f, "- {record}")?;
+        }
+        for change in &self.changed {
+            write!(f, "~ {}", change.before)?;
+            if change.moved() {
+                write!(
+                    f,
+                    " moved to ({}, {})",
+                    change.after.pos.x, change.after.pos.y
+                )?;
+                if change.after.region != change.before.region {
+                    write!(f, " in {}", change.after.region)?;
+                }
+            }
+            for (stat, before, after) in change.stat_changes() {
+                write!(f, ", {stat} {before} -> {after}")?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "By species:")?;
+        for delta in self.species.iter().filter(|d| !d.is_unchanged()) {
+            write!(
+                f,
+                "  {} {} -> {} ({:+})",
+                delta.species.name(),
+                delta.before,
+                delta.after,
+                delta.change()
+            )?;
+            for (stat, before, after) in delta.averages.iter().filter(|(_, b, a)| b != a) {
+                write!(f, ", {stat} {before:.1} -> {after:.1}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::NonAbstractTaxonomy;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_checkpoint_diff() {
+        let (fish, crab, newborn) = (Pos { x: 0, y: 0 }, Pos { x: 2, y: 0 }, Pos { x: 4, y: 0 });
+        let mut testbed = TestBed::new_with_entities(
+            1,
+            5,
+            vec![
+                (fish, Species::Fish.create_new(None)),
+                (crab, Species::Crab.create_new(None)),
+            ],
+        );
+        let before = Scenario::capture(&testbed.sandbox, "Before");
+        assert!(CheckpointDiff::new(&before, &before).is_empty());
+
+        testbed.sandbox.board.remove_entity(crab);
+        testbed
+            .get_entity_at_pos_mut(fish)
+            .unwrap()
+            .set_stat("hp", "40")
+            .unwrap();
+        testbed
+            .sandbox
+            .board
+            .place_entity(newborn, Species::Fish.create_new(None))
+            .unwrap();
+        let after = Scenario::capture(&testbed.sandbox, "After");
+
+        let diff = CheckpointDiff::new(&before, &after);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(
+            (diff.added[0].species, diff.added[0].pos),
+            (Species::Fish, newborn)
+        );
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].species, Species::Crab);
+        assert_eq!(diff.changed.len(), 1);
+        assert!(!diff.changed[0].moved());
+        assert_eq!(diff.changed[0].stat_changes(), vec![("hp", "100", "40")]);
+
+        let fish_delta = diff
+            .species
+            .iter()
+            .find(|d| d.species == Species::Fish)
+            .unwrap();
+        assert_eq!((fish_delta.before, fish_delta.change()), (1, 1));
+        let hp = fish_delta
+            .averages
+            .iter()
+            .find(|(s, _, _)| s == "hp")
+            .unwrap();
+        assert_eq!((hp.1, hp.2), (100.0, 70.0));
+
+        let report = diff.to_string();
+        assert!(report.starts_with("1 added, 1 removed, 1 changed"));
+        assert!(report.contains("+ fish #"));
+        assert!(report.contains("- crab #"));
+        assert!(report.contains("hp 100 -> 40"));
+        assert!(report.contains("fish 1 -> 2 (+1), hp 100.0 -> 70.0"));
+    }
+
+    #[test]
+    /// A hand written scenario has no IDs, but whatever hasn't moved should still match up with the saved game
+    fn test_diff_without_ids() {
+        let scenario: Scenario = "size 1 3\nmap\nr..\nend\nentity fish 1 0\nentity crab 2 0"
+            .parse()
+            .unwrap();
+        let mut sandbox = scenario.build(1.0);
+        sandbox.board.remove_entity(Pos { x: 2, y: 0 });
+        let diff = CheckpointDiff::new(&scenario, &Scenario::capture(&sandbox, "Saved"));
+        assert!(diff.added.is_empty() && diff.changed.is_empty());
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].species, Species::Crab);
+    }
+}
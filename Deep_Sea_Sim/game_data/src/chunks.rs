@@ -0,0 +1,509 @@
+// Really big boards are split up into square chunks, and only the chunks where something's going on get simulated tick by
+// tick. A chunk wakes up whenever an animal or an egg is in it or right next door, and stays awake for a while after
+// they've gone. Everything else is dormant: the plants in it aren't processed one at a time, and instead every so often
+// (and whenever the chunk wakes back up) they're caught up in one go, growing, spreading and getting old by about as much
+// as they would have over the ticks they slept through. Seeds dropped while catching up don't drift anywhere, they just
+// root where they land. Animals on a chunked board only look around the chunks next to them for food and mates, too, so
+// nothing ends up running over the whole board.
+// Where everything in the awake chunks wants to move gets worked out a chunk at a time, spread out over as many threads as
+// the sandbox has been given, each chunk with its own randomness so it comes out the same however many threads there are.
+// The moves themselves are carried out afterwards on one thread: ones that stay inside their chunk go first, then the ones
+// heading over into another chunk, and anything that finds where it was going has been taken in the meantime has another
+// think given how the board looks now.
+// Boards small enough to simulate outright aren't chunked at all, so they play out exactly the same as they always have.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use log::debug;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::element_traits::{Growing, LifeStatus, Lives, ProcessingContext, Reproducing};
+use crate::entities::plants::Plants;
+use crate::entities::{Entity, Living};
+use crate::entity_control::EntityID;
+use crate::game_board::Pos;
+use crate::systems::desired_move;
+use crate::Sandbox;
+
+/// How many tiles across (and down) a chunk is.
+pub const CHUNK_SIZE: usize = 16;
+/// Boards with fewer tiles than this are simulated whole, tick by tick.
+pub const CHUNKED_TILES: usize = 128 * 128;
+/// How many ticks a chunk stays awake for after the last time anything was going on in or around it.
+const STAY_AWAKE_TICKS: usize = 20;
+/// How many ticks a dormant chunk goes between being caught up.
+const CATCH_UP_TICKS: usize = 50;
+
+/// Which chunks of a board are awake, and how far along each one's plants have been simulated.
+#[derive(Debug, Clone, Default)]
+pub struct Chunks {
+    /// How many chunks there are across the board.
+    across: usize,
+    /// For every chunk, the tick it stays awake until. Once that's come, it's dormant. Empty if the board isn't chunked.
+    awake_until: Vec<usize>,
+    /// For every chunk, the first tick its plants haven't been simulated through yet, or None if it's never been looked at.
+    settled: Vec<Option<usize>>,
+}
+
+impl Chunks {
+    /// Chunks for a board of the given size, all dormant to start with. Small boards don't get any.
+    pub fn for_board(rows: usize, cols: usize) -> Self {
+        if rows * cols < CHUNKED_TILES {
+            return Self::default();
+        }
+        let across = cols.div_ceil(CHUNK_SIZE);
+        let count = across * rows.div_ceil(CHUNK_SIZE);
+        Self {
+            across,
+            awake_until: vec![0; count],
+            settled: vec![None; count],
+        }
+    }
+
+    /// Whether the board's been split up at all.
+    pub fn is_chunked(&self) -> bool {
+        !self.awake_until.is_empty()
+    }
+
+    /// How many chunks there are.
+    pub fn len(&self) -> usize {
+        self.awake_until.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.awake_until.is_empty()
+    }
+
+    /// How many chunks are awake on the given tick.
+    pub fn awake(&self, tick: usize) -> usize {
+        self.awake_until
+            .iter()
+            .filter(|until| **until > tick)
+            .count()
+    }
+
+    /// Whether a tile gets simulated on the given tick. On a board that isn't chunked, they all do.
+    pub fn is_awake(&self, pos: Pos, tick: usize) -> bool {
+        !self.is_chunked() || self.awake_until[self.index(pos)] > tick
+    }
+
+    fn index(&self, pos: Pos) -> usize {
+        pos.y / CHUNK_SIZE * self.across + pos.x / CHUNK_SIZE
+    }
+
+    /// Keep the chunk a tile's in awake until the given tick, along with every chunk around it, so anything that wanders
+    /// over the edge still gets simulated.
+    fn wake_around(&mut self, pos: Pos, until: usize) {
+        let (x, y) = (pos.x / CHUNK_SIZE, pos.y / CHUNK_SIZE);
+        let down = self.awake_until.len() / self.across;
+        for row in y.saturating_sub(1)..=(y + 1).min(down - 1) {
+            for col in x.saturating_sub(1)..=(x + 1).min(self.across - 1) {
+                let chunk = &mut self.awake_until[row * self.across + col];
+                *chunk = (*chunk).max(until);
+            }
+        }
+    }
+
+    /// Every tile in the chunk a tile's in and the chunks around it, on a board with the given dimensions.
+    pub fn tiles_around(
+        &self,
+        pos: Pos,
+        (cols, rows): (usize, usize),
+    ) -> impl Iterator<Item = Pos> {
+        let (x, y) = (pos.x / CHUNK_SIZE, pos.y / CHUNK_SIZE);
+        let (left, top) = (
+            x.saturating_sub(1) * CHUNK_SIZE,
+            y.saturating_sub(1) * CHUNK_SIZE,
+        );
+        let (right, bottom) = (
+            ((x + 2) * CHUNK_SIZE).min(cols),
+            ((y + 2) * CHUNK_SIZE).min(rows),
+        );
+        (top..bottom).flat_map(move |y| (left..right).map(move |x| Pos { x, y }))
+    }
+
+    /// Every tile in a chunk, on a board with the given dimensions.
+    fn tiles(&self, chunk: usize, (cols, rows): (usize, usize)) -> impl Iterator<Item = Pos> {
+        let (left, top) = (
+            chunk % self.across * CHUNK_SIZE,
+            chunk / self.across * CHUNK_SIZE,
+        );
+        (top..(top + CHUNK_SIZE).min(rows))
+            .flat_map(move |y| (left..(left + CHUNK_SIZE).min(cols)).map(move |x| Pos { x, y }))
+    }
+}
+
+/// Somewhere an entity wants to move to, worked out on a worker thread.
+#[derive(Debug, Clone, Copy)]
+struct PlannedMove {
+    id: EntityID,
+    from: Pos,
+    to: Pos,
+    /// Whether there was nothing where it's going as of when the move was worked out.
+    was_free: bool,
+}
+
+/// Whether something needs to be simulated tick by tick, keeping the chunk it's in awake.
+fn is_busy(entity: &Entity) -> bool {
+    match entity {
+        Entity::Living(Living::Animals(_)) => true,
+        Entity::Living(Living::Plants(plant)) => matches!(plant, Plants::Egg(..)),
+        Entity::NonLiving(_) => false,
+    }
+}
+
+impl Sandbox {
+    /// Work out which chunks of the board are awake for this tick, and catch up any dormant ones that are due for it.
+    pub fn handle_chunks(&mut self) {
+        if !self.board.chunks().is_chunked() {
+            return;
+        }
+        let tick = self.clock;
+        let busy: Vec<Pos> = self
+            .entity_context
+            .read()
+            .unwrap()
+            .get_active_entries()
+            .values()
+            .filter(|pos| self.board.entity_at(**pos).is_some_and(is_busy))
+            .copied()
+            .collect();
+        let chunks = self.board.chunks_mut();
+        for pos in busy {
+            chunks.wake_around(pos, tick + STAY_AWAKE_TICKS);
+        }
+        // awake chunks get simulated as usual from here on, after catching up on whatever they slept through, and
+        // dormant ones only get caught up every so often
+        let mut due = vec![];
+        for (chunk, (until, settled)) in chunks
+            .awake_until
+            .iter()
+            .zip(chunks.settled.iter_mut())
+            .enumerate()
+        {
+            // anything that's never been looked at before starts from here
+            let since = settled.unwrap_or(tick);
+            let missed = tick.saturating_sub(since);
+            let awake = *until > tick;
+            if !awake && missed < CATCH_UP_TICKS {
+                *settled = Some(since);
+                continue;
+            }
+            *settled = Some(if awake { tick + 1 } else { tick });
+            if missed > 0 {
+                due.push((chunk, missed));
+            }
+        }
+        for (chunk, ticks) in due {
+            self.catch_up(chunk, ticks);
+        }
+    }
+
+    /// Every important entity that's in an awake chunk, oldest first.
+    pub fn get_awake_entities(&self) -> Vec<Pos> {
+        let chunks = self.board.chunks();
+        let em = self.entity_context.read().unwrap();
+        em.get_active_entries()
+            .values()
+            .filter(|pos| chunks.is_awake(**pos, self.clock))
+            .copied()
+            .collect()
+    }
+
+    fn awake_entries(&self) -> Vec<(EntityID, Pos)> {
+        let chunks = self.board.chunks();
+        let em = self.entity_context.read().unwrap();
+        em.get_active_entries()
+            .iter()
+            .filter(|(_, pos)| chunks.is_awake(**pos, self.clock))
+            .map(|(id, pos)| (*id, *pos))
+            .collect()
+    }
+
+    /// How many threads moves on a chunked board get worked out across.
+    pub fn worker_threads(&self) -> usize {
+        self.worker_threads
+    }
+
+    /// Work out moves on a chunked board across this many threads from now on. It's never less than one.
+    pub fn set_worker_threads(&mut self, threads: usize) {
+        self.worker_threads = threads.max(1);
+    }
+
+    /// Work out where everything in the awake chunks wants to go, a chunk per worker at a time, then carry it all out.
+    pub(crate) fn handle_chunked_moves(&mut self) {
+        let mut by_chunk: BTreeMap<usize, Vec<(EntityID, Pos)>> = BTreeMap::new();
+        for (id, pos) in self.awake_entries() {
+            let chunk = self.board.chunks().index(pos);
+            by_chunk.entry(chunk).or_default().push((id, pos));
+        }
+        let groups: Vec<(usize, Vec<(EntityID, Pos)>)> = by_chunk.into_iter().collect();
+        // every chunk gets its own randomness, handed out in order, so it doesn't matter which thread gets to it
+        let seeds: Vec<u64> = {
+            let mut rng = self.rng.lock().unwrap();
+            groups.iter().map(|_| rng.gen()).collect()
+        };
+        let (board, entity_context, brains, rules) =
+            (&self.board, &self.entity_context, &self.brains, &self.rules);
+        let plan = |index: usize| -> Vec<PlannedMove> {
+            let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(seeds[index])));
+            groups[index]
+                .1
+                .iter()
+                .filter_map(|(id, pos)| {
+                    let ctx = ProcessingContext {
+                        position: *pos,
+                        entity_context: Arc::clone(entity_context),
+                        rng: Arc::clone(&rng),
+                        brains: brains.clone(),
+                        rules: Arc::clone(rules),
+                    };
+                    let to = desired_move(board, &ctx)?;
+                    let was_free =
+                        board.is_valid_pos(to) && !board.get_tile_from_pos(to).is_occupied();
+                    Some(PlannedMove {
+                        id: *id,
+                        from: *pos,
+                        to,
+                        was_free,
+                    })
+                })
+                .collect()
+        };
+        let threads = self.worker_threads.min(groups.len()).max(1);
+        // there's no spawning threads in a browser, and no point to it with only the one
+        let planned: Vec<Vec<PlannedMove>> = if threads == 1 || cfg!(target_arch = "wasm32") {
+            (0..groups.len()).map(plan).collect()
+        } else {
+            let next = AtomicUsize::new(0);
+            let planned = Mutex::new(vec![vec![]; groups.len()]);
+            thread::scope(|scope| {
+                for _ in 0..threads {
+                    scope.spawn(|| loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        if index >= groups.len() {
+                            break;
+                        }
+                        let moves = plan(index);
+                        planned.lock().unwrap()[index] = moves;
+                    });
+                }
+            });
+            planned.into_inner().unwrap()
+        };
+
+        // moves inside a chunk can't get in the way of any other chunk's, so they go first
+        let mut crossing = vec![];
+        for ((chunk, _), moves) in groups.iter().zip(planned) {
+            for planned in moves {
+                if self.board.is_valid_pos(planned.to)
+                    && self.board.chunks().index(planned.to) != *chunk
+                {
+                    crossing.push(planned);
+                } else {
+                    self.carry_out_move(planned);
+                }
+            }
+        }
+        // then everything heading over a chunk's edge, in the order they'd have moved in on a smaller board
+        crossing.sort_unstable_by_key(|planned| planned.id);
+        for planned in crossing {
+            self.carry_out_move(planned);
+        }
+    }
+
+    /// Carry out a move that was worked out on a worker thread. If something else has taken the spot since, the entity
+    /// gets to pick somewhere else to go, now that it can see what's changed.
+    fn carry_out_move(&mut self, planned: PlannedMove) {
+        let PlannedMove {
+            from, to, was_free, ..
+        } = planned;
+        let taken = was_free && self.board.get_tile_from_pos(to).is_occupied();
+        if !taken {
+            self.try_move(from, to);
+            return;
+        }
+        debug!(target: self.log_target(), "{to:?} was taken before {from:?} could get there");
+        let ctx = self.processing_context(from);
+        if let Some(instead) = desired_move(&self.board, &ctx) {
+            self.try_move(from, instead);
+        }
+    }
+
+    /// Move the plants in a chunk along by a number of ticks all at once.
+    fn catch_up(&mut self, chunk: usize, ticks: usize) {
+        let rng = Arc::clone(&self.rng);
+        let mut rng = rng.lock().unwrap();
+        let plants: Vec<Pos> = self
+            .board
+            .chunks()
+            .tiles(chunk, self.board.dims())
+            .filter(|pos| {
+                let entity = self.board.entity_at(*pos);
+                entity.as_ref().is_some_and(|e| e.plant().is_some())
+            })
+            .collect();
+        debug!(target: self.log_target(), "Catching {} plants up by {ticks} ticks", plants.len());
+        for pos in plants {
+            let light = self.board.light_at(pos);
+            let Some(mut entity) = self.board.remove_entity(pos) else {
+                continue;
+            };
+            let Some(plant) = entity.plant_mut() else {
+                continue;
+            };
+            let body = plant.body_mut();
+            body.age.ticks += ticks;
+            let growing = ticks.saturating_sub(body.drift);
+            body.drift = body.drift.saturating_sub(ticks);
+            let growth = &mut body.growth;
+            let before = growth.level;
+            let grown = growth.progress + light * growing as f64;
+            growth.level += grown.floor() as usize;
+            growth.progress = grown.fract();
+            growth.light = light;
+            let (after, max) = (growth.level, growth.max);
+            let too_old = body.age.max.is_some_and(|most| most < body.age.ticks);
+            if too_old || plant.get_life_status() == LifeStatus::Dead {
+                continue;
+            }
+            if plant.ready_to_grow_into() {
+                if let Some(next) = plant.grow_into() {
+                    self.board.place_entity(pos, next).ok();
+                    continue;
+                }
+            }
+            // it would've spread every time its growth came round to another multiple of its max
+            if plant.get_offspring_data().is_some() && max > 0 {
+                for _ in 0..(after / max).saturating_sub(before / max) {
+                    plant.create_offspring(&mut self.board, pos, &mut *rng, &self.rules);
+                }
+            }
+            self.board.place_entity(pos, entity).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::Species;
+    use crate::entity_control::TrackedEntity;
+    use crate::settings::EventFrequency;
+    use crate::setup::SetupConfig;
+    use crate::test_utils::TestBed;
+
+    fn age_at(testbed: &TestBed, pos: Pos) -> usize {
+        let plant = testbed.get_entity_at_pos(pos).unwrap().plant().unwrap();
+        plant.body().age.ticks
+    }
+
+    #[test]
+    fn test_small_boards_arent_chunked() {
+        let chunks = Chunks::for_board(100, 100);
+        assert!(!chunks.is_chunked());
+        assert!(chunks.is_awake(Pos { x: 99, y: 99 }, 1000));
+
+        let chunks = Chunks::for_board(130, 200);
+        assert_eq!(chunks.len(), 9 * 13);
+        let last = chunks
+            .tiles(chunks.len() - 1, (200, 130))
+            .collect::<Vec<_>>();
+        assert_eq!(last.len(), 8 * 2);
+        assert_eq!(last[0], Pos { x: 192, y: 128 });
+    }
+
+    #[test]
+    fn test_dormant_chunks_catch_up() {
+        let (fish, near, far) = (
+            Pos { x: 1, y: 1 },
+            Pos { x: 10, y: 10 },
+            Pos { x: 100, y: 100 },
+        );
+        let mut testbed = TestBed::builder(128, 128)
+            .with(fish, Species::Fish)
+            .with(near, Species::Kelp)
+            .with(far, Species::Kelp)
+            .build();
+        testbed.sandbox.settings.event_frequency = EventFrequency::Never;
+        testbed.sandbox.tick();
+        let chunks = testbed.sandbox.board.chunks();
+        assert!(chunks.is_awake(near, 0));
+        assert!(!chunks.is_awake(far, 0));
+        assert_eq!(chunks.awake(0), 4);
+        // the kelp out on its own is left alone
+        assert_eq!(age_at(&testbed, near), 1);
+        assert_eq!(age_at(&testbed, far), 0);
+
+        for _ in 1..CATCH_UP_TICKS {
+            testbed.sandbox.tick();
+        }
+        assert_eq!(age_at(&testbed, far), 0);
+        testbed.sandbox.tick();
+        // until it's due to be caught up on everything it missed
+        assert_eq!(age_at(&testbed, far), CATCH_UP_TICKS);
+        assert!(
+            testbed
+                .get_entity_at_pos(far)
+                .unwrap()
+                .growth()
+                .unwrap()
+                .level
+                > 0
+        );
+    }
+
+    #[test]
+    fn test_moves_dont_depend_on_threads() {
+        let run = |threads| {
+            let setup = SetupConfig {
+                fish: 60,
+                crab: 40,
+                shark: 10,
+                ..SetupConfig::new(128, 128)
+            };
+            let mut sandbox = Sandbox::from_setup(&setup, &mut StdRng::seed_from_u64(5)).unwrap();
+            sandbox.reseed(5);
+            sandbox.settings.event_frequency = EventFrequency::Never;
+            sandbox.set_worker_threads(threads);
+            let before = sandbox.board.to_string();
+            for _ in 0..20 {
+                sandbox.tick();
+            }
+            assert_ne!(sandbox.board.to_string(), before);
+            sandbox.board.to_string()
+        };
+        assert_eq!(run(1), run(4));
+    }
+
+    #[test]
+    fn test_taken_spots_get_rethought() {
+        let (left, right, between) = (
+            Pos { x: 15, y: 5 },
+            Pos { x: 17, y: 5 },
+            Pos { x: 16, y: 5 },
+        );
+        let mut testbed = TestBed::builder(128, 128)
+            .with(left, Species::Fish)
+            .with(right, Species::Fish)
+            .build();
+        let id_at =
+            |testbed: &TestBed, pos| testbed.get_entity_at_pos(pos).unwrap().get_id().unwrap();
+        // both of them were counting on the spot in between, over on either side of a chunk's edge
+        for from in [left, right] {
+            let id = id_at(&testbed, from);
+            testbed.sandbox.carry_out_move(PlannedMove {
+                id,
+                from,
+                to: between,
+                was_free: true,
+            });
+        }
+        assert_eq!(testbed.sandbox.board.population(Species::Fish), 2);
+        assert!(testbed.get_entity_at_pos(left).is_none());
+        assert!(testbed.get_entity_at_pos(between).is_some());
+    }
+}
@@ -0,0 +1,288 @@
+// Colony codes, for sharing starting conditions. A code packs everything that goes into setting up a fresh board into
+// one short line that can be pasted into a forum post: the board's size and shape, its starting populations, the seed it
+// was filled in from, the difficulty, any events scheduled for a given tick and a fingerprint of the species config.
+// Two players starting from the same code get exactly the same board, and answering the same events the same way, see
+// exactly the same game play out.
+//
+// Underneath the base64, a code is a line of key=value pairs:
+//
+//     v=1 size=20x30 topology=square fish=40 crab=20 shark=5 kelp=0.2 seed=12345 difficulty=normal species=9c3e51a0d2b8f417 events=50:party,80:cleanup
+//
+// Only built in events can go in a code, since scripted ones can't go off without their script. A code made with a
+// different species config than the one in use still loads, but the game won't play out the same, so anything importing
+// one should check same_species first.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::game_board::Topology;
+use crate::game_events::EventTypes;
+use crate::interaction_matrix::InteractionMatrix;
+use crate::settings::Difficulty;
+use crate::setup::{SetupConfig, SetupError};
+use crate::Sandbox;
+
+/// The version of the format codes are written in. Codes from a newer version won't load.
+pub const CODE_VERSION: usize = 1;
+
+/// Everything needed to set up the same starting board as somebody else.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColonyCode {
+    pub setup: SetupConfig,
+    /// Where the randomness that fills in the board, and everything after, starts from.
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    /// Events that go off on a given tick, no matter what.
+    pub events: Vec<(usize, EventTypes)>,
+    /// A fingerprint of the species config the code was made with.
+    pub species: u64,
+}
+
+impl ColonyCode {
+    /// A code for a setup with a fresh seed, made with the species rules in use.
+    pub fn new(setup: SetupConfig, difficulty: Difficulty, rules: &InteractionMatrix) -> Self {
+        Self {
+            setup,
+            seed: rand::random(),
+            difficulty,
+            events: vec![],
+            species: rules.fingerprint(),
+        }
+    }
+
+    /// Whether the species rules in use are the ones the code was made with. If they aren't, the game won't play out
+    /// the same as it did for whoever shared it.
+    pub fn same_species(&self, rules: &InteractionMatrix) -> bool {
+        self.species == rules.fingerprint()
+    }
+
+    /// Set up the board the code describes. The difficulty's left for the settings to pass on.
+    pub fn build(&self) -> Result<Sandbox, SetupError> {
+        self.setup.validate()?;
+        let mut sandbox = Sandbox::from_setup(&self.setup, &mut StdRng::seed_from_u64(self.seed))?;
+        sandbox.scripted_events = self.events.clone();
+        Ok(sandbox)
+    }
+}
+
+impl Display for ColonyCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let setup = &self.setup;
+        let mut text = format!(
+            "v={CODE_VERSION} size={}x{} topology={} fish={} crab={} shark={} kelp={} seed={} difficulty={} species={:016x}",
+            setup.rows,
+            setup.cols,
+            setup.topology.name(),
+            setup.fish,
+            setup.crab,
+            setup.shark,
+            setup.kelp_chance,
+            self.seed,
+            self.difficulty.name(),
+            self.species,
+        );
+        if !self.events.is_empty() {
+            let events: Vec<String> = self
+                .events
+                .iter()
+                .map(|(tick, event)| format!("{tick}:{}", event.name()))
+                .collect();
+            text += &format!(" events={}", events.join(","));
+        }
+        f.write_str(&URL_SAFE_NO_PAD.encode(text))
+    }
+}
+
+impl FromStr for ColonyCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let not_a_code = || String::from("that isn't a colony code");
+        let bytes = URL_SAFE_NO_PAD.decode(s.trim()).map_err(|_| not_a_code())?;
+        let text = String::from_utf8(bytes).map_err(|_| not_a_code())?;
+        let number = |key: &str, value: &str| {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("'{value}' isn't a valid {key}"))
+        };
+
+        let mut code = Self {
+            setup: SetupConfig::new(0, 0),
+            seed: 0,
+            difficulty: Difficulty::Normal,
+            events: vec![],
+            species: 0,
+        };
+        let mut version = None;
+        let mut seeded = false;
+        for pair in text.split_whitespace() {
+            let (key, value) = pair.split_once('=').ok_or_else(not_a_code)?;
+            match key {
+                "v" => version = Some(number(key, value)? as usize),
+                "size" => {
+                    let (rows, cols) = value.split_once('x').ok_or_else(not_a_code)?;
+                    code.setup.rows = number(key, rows)? as usize;
+                    code.setup.cols = number(key, cols)? as usize;
+                }
+                "topology" => {
+                    code.setup.topology = Topology::ALL
+                        .into_iter()
+                        .find(|t| t.name() == value)
+                        .ok_or_else(|| format!("'{value}' isn't a known topology"))?
+                }
+                "fish" => code.setup.fish = number(key, value)? as usize,
+                "crab" => code.setup.crab = number(key, value)? as usize,
+                "shark" => code.setup.shark = number(key, value)? as usize,
+                "kelp" => {
+                    code.setup.kelp_chance = value
+                        .parse()
+                        .map_err(|_| format!("'{value}' isn't a valid kelp chance"))?
+                }
+                "seed" => {
+                    code.seed = number(key, value)?;
+                    seeded = true;
+                }
+                "difficulty" => {
+                    code.difficulty = Difficulty::ALL
+                        .into_iter()
+                        .find(|d| d.name() == value)
+                        .ok_or_else(|| format!("there's no difficulty called '{value}'"))?
+                }
+                "species" => {
+                    code.species = u64::from_str_radix(value, 16)
+                        .map_err(|_| format!("'{value}' isn't a valid species fingerprint"))?
+                }
+                "events" => {
+                    for event in value.split(',') {
+                        let (tick, name) = event.split_once(':').ok_or_else(not_a_code)?;
+                        let kind = EventTypes::from_name(name)
+                            .ok_or_else(|| format!("'{name}' isn't a known event"))?;
+                        code.events.push((number("tick", tick)? as usize, kind));
+                    }
+                }
+                _ => return Err(not_a_code()),
+            }
+        }
+        match version {
+            None => return Err(not_a_code()),
+            Some(version) if version > CODE_VERSION => {
+                return Err(format!(
+                    "this code was made by a newer version of the game (version {version})"
+                ))
+            }
+            Some(_) => {}
+        }
+        if !seeded {
+            return Err(String::from("the code is missing its seed"));
+        }
+        code.setup.validate().map_err(|e| e.to_string())?;
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::Species;
+
+    fn example() -> ColonyCode {
+        ColonyCode {
+            setup: SetupConfig {
+                fish: 8,
+                crab: 4,
+                shark: 2,
+                kelp_chance: 0.15,
+                topology: Topology::Hex,
+                ..SetupConfig::new(12, 9)
+            },
+            seed: 0xDEE9,
+            difficulty: Difficulty::Hard,
+            events: vec![(5, EventTypes::Party), (20, EventTypes::OilSpill)],
+            species: InteractionMatrix::default().fingerprint(),
+        }
+    }
+
+    #[test]
+    fn test_codes_round_trip() {
+        let code = example();
+        let shared = code.to_string();
+        // short enough to paste anywhere, and nothing in it that'd need escaping in a link
+        assert!(shared.len() < 200, "{shared}");
+        assert!(shared
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        let imported: ColonyCode = format!("  {shared}\n").parse().unwrap();
+        assert_eq!(imported, code);
+        assert!(imported.same_species(&InteractionMatrix::default()));
+
+        let plain = ColonyCode::new(
+            SetupConfig::new(3, 3),
+            Difficulty::Easy,
+            &InteractionMatrix::default(),
+        );
+        assert_eq!(plain.to_string().parse::<ColonyCode>().unwrap(), plain);
+    }
+
+    #[test]
+    fn test_codes_set_up_the_same_game() {
+        let code = example();
+        let mut first = code.build().unwrap();
+        let mut second = code
+            .to_string()
+            .parse::<ColonyCode>()
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(first.digest(), second.digest());
+        assert_eq!(first.census().count(Species::Fish), 8);
+        assert_eq!(first.scripted_events, code.events);
+        for _ in 0..10 {
+            first.tick();
+            second.tick();
+        }
+        assert_eq!(first.digest(), second.digest());
+
+        let reseeded = ColonyCode { seed: 1, ..code };
+        assert_ne!(reseeded.build().unwrap().digest().hash, first.digest().hash);
+    }
+
+    #[test]
+    fn test_bad_codes() {
+        let encode = |text: &str| URL_SAFE_NO_PAD.encode(text);
+        for bad in [
+            String::from("not even base64!"),
+            encode("hello there"),
+            encode("size=3x3 seed=1"),
+            encode("v=1 size=3x3"),
+            encode("v=1 size=0x3 seed=1"),
+            encode("v=1 size=3x3 seed=1 events=4:meteor"),
+            encode("v=1 size=3x3 seed=1 fish=50"),
+        ] {
+            assert!(bad.parse::<ColonyCode>().is_err(), "{bad}");
+        }
+        let newer = encode(&format!("v={} size=3x3 seed=1", CODE_VERSION + 1));
+        assert!(newer.parse::<ColonyCode>().unwrap_err().contains("newer"));
+    }
+
+    #[test]
+    fn test_species_fingerprints() {
+        let default = InteractionMatrix::default();
+        assert_eq!(
+            default.fingerprint(),
+            InteractionMatrix::default().fingerprint()
+        );
+        let (changed, ignored) = InteractionMatrix::parse("eats shark fish never");
+        assert!(ignored.is_empty());
+        assert_ne!(changed.fingerprint(), default.fingerprint());
+        let code = ColonyCode {
+            species: changed.fingerprint(),
+            ..example()
+        };
+        assert!(!code.same_species(&default));
+    }
+}
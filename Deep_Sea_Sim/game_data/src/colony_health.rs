@@ -0,0 +1,320 @@
+// One number for how well the colony's doing, worked out after every tick from a few things that tend to go wrong: how
+// evenly the animals are spread across species, how well fed they are, how much of the sea floor is growing plants, and
+// how many animals have died lately. Each of those is scored out of 100 and the score is their average, so a colony that
+// only has one thing going wrong can still do alright. The last few scores are kept around to tell whether things are
+// picking up or going downhill. Every region counts, the same as in a census.
+// Deaths are found by keeping track of which animals were around after the last tick, so anything that goes missing
+// counts, whether it starved, got eaten or got picked off by an event.
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::entities::animals::HungerLevel;
+use crate::entities::{Entity, Living, Species};
+use crate::entity_control::{EntityID, TrackedEntity};
+use crate::game_board::Board;
+
+/// How many of the last scores are kept around.
+const SCORES_KEPT: usize = 100;
+/// How many ticks back deaths count against the colony.
+const DEATH_WINDOW: usize = 20;
+/// How many ticks back a score is compared against to tell which way things are going.
+const TREND_WINDOW: usize = 10;
+/// How many points a score needs to have moved by over the trend window before it counts as going anywhere.
+const TREND_THRESHOLD: usize = 3;
+/// How much of the sea floor needs to be growing plants for them to score full marks.
+const PLANT_TARGET: f64 = 0.25;
+
+/// Something that goes into a colony's health score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthFactor {
+    /// How evenly the animals are spread out over every species there is.
+    Diversity,
+    /// How well fed the animals are, on average.
+    Hunger,
+    /// How much of the sea floor is growing plants.
+    Plants,
+    /// How many of the animals around lately are still alive.
+    Deaths,
+}
+
+impl HealthFactor {
+    pub const ALL: [HealthFactor; 4] = [
+        HealthFactor::Diversity,
+        HealthFactor::Hunger,
+        HealthFactor::Plants,
+        HealthFactor::Deaths,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Diversity => "diversity",
+            Self::Hunger => "hunger",
+            Self::Plants => "plants",
+            Self::Deaths => "deaths",
+        }
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|f| f == self).unwrap()
+    }
+}
+
+/// Which way a colony's health has been going lately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Trend {
+    Rising,
+    #[default]
+    Steady,
+    Falling,
+}
+
+impl Trend {
+    pub const ALL: [Trend; 3] = [Trend::Rising, Trend::Steady, Trend::Falling];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rising => "rising",
+            Self::Steady => "steady",
+            Self::Falling => "falling",
+        }
+    }
+}
+
+/// How well the colony was doing as of one tick, out of 100.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColonyHealth {
+    pub tick: usize,
+    /// The average of every factor.
+    pub score: usize,
+    /// How many animals have died over the last few ticks.
+    pub recent_deaths: usize,
+    factors: [usize; 4],
+}
+
+impl ColonyHealth {
+    /// How the colony scored on one factor, out of 100.
+    pub fn factor(&self, factor: HealthFactor) -> usize {
+        self.factors[factor.index()]
+    }
+}
+
+/// How many points out of 100 a fraction is worth.
+fn points(fraction: f64) -> usize {
+    (fraction.clamp(0.0, 1.0) * 100.0).round() as usize
+}
+
+/// How well fed an animal at some hunger level is, from 0 to 1.
+fn fullness(hunger: HungerLevel) -> f64 {
+    match hunger {
+        HungerLevel::Full => 1.0,
+        HungerLevel::Hungry => 2.0 / 3.0,
+        HungerLevel::Starving => 1.0 / 3.0,
+        HungerLevel::Famished => 0.0,
+    }
+}
+
+/// How evenly some populations are spread out over every species of animal there is, from 0 (only one species, or none at
+/// all) to 1 (the same number of every species).
+fn evenness(populations: &[usize]) -> f64 {
+    let total: usize = populations.iter().sum();
+    let species = Species::all()
+        .into_iter()
+        .filter(Species::is_animal)
+        .count();
+    if total == 0 || species < 2 {
+        return 0.0;
+    }
+    let shannon: f64 = populations
+        .iter()
+        .filter(|n| **n > 0)
+        .map(|n| {
+            let share = *n as f64 / total as f64;
+            -share * share.ln()
+        })
+        .sum();
+    shannon / (species as f64).ln()
+}
+
+/// The colony's health over the last few ticks.
+#[derive(Debug, Default)]
+pub struct HealthTracker {
+    /// The last few scores, oldest first.
+    scores: VecDeque<ColonyHealth>,
+    /// How many animals died on each of the last few ticks, oldest first.
+    deaths: VecDeque<(usize, usize)>,
+    /// Every animal that was around as of the last tick, or None if we've not looked yet.
+    alive: Option<HashSet<EntityID>>,
+}
+
+impl HealthTracker {
+    /// Score the colony as it is on the given tick, across all of its boards.
+    pub fn record<'a>(&mut self, tick: usize, boards: impl Iterator<Item = &'a Board>) {
+        let mut alive = HashSet::new();
+        let mut populations = vec![];
+        let mut fed = 0.0;
+        let mut plants = 0;
+        let mut tiles = 0;
+        for board in boards {
+            let (cols, rows) = board.dims();
+            tiles += cols * rows;
+            for (_, entity) in board.iter_occupied() {
+                let species = Species::of(entity);
+                if species.is_plant() {
+                    plants += 1;
+                }
+                let Entity::Living(Living::Animals(animal)) = entity else {
+                    continue;
+                };
+                match populations.iter_mut().find(|(s, _)| *s == species) {
+                    Some((_, count)) => *count += 1,
+                    None => populations.push((species, 1)),
+                }
+                fed += fullness(animal.body().hunger.state);
+                alive.extend(animal.get_id());
+            }
+        }
+
+        let died = self
+            .alive
+            .as_ref()
+            .map_or(0, |before| before.difference(&alive).count());
+        self.deaths.push_back((tick, died));
+        while self
+            .deaths
+            .front()
+            .is_some_and(|(t, _)| t + DEATH_WINDOW <= tick)
+        {
+            self.deaths.pop_front();
+        }
+        let recent_deaths: usize = self.deaths.iter().map(|(_, n)| n).sum();
+
+        let populations: Vec<usize> = populations.into_iter().map(|(_, n)| n).collect();
+        let animals = alive.len();
+        let mut factors = [0; 4];
+        factors[HealthFactor::Diversity.index()] = points(evenness(&populations));
+        factors[HealthFactor::Hunger.index()] = points(fed / animals.max(1) as f64);
+        factors[HealthFactor::Plants.index()] =
+            points(plants as f64 / tiles.max(1) as f64 / PLANT_TARGET);
+        factors[HealthFactor::Deaths.index()] =
+            points(animals as f64 / (animals + recent_deaths).max(1) as f64);
+        self.scores.push_back(ColonyHealth {
+            tick,
+            score: factors.iter().sum::<usize>() / factors.len(),
+            recent_deaths,
+            factors,
+        });
+        if self.scores.len() > SCORES_KEPT {
+            self.scores.pop_front();
+        }
+        self.alive = Some(alive);
+    }
+
+    /// The most recent score, if there's been one yet.
+    pub fn latest(&self) -> Option<&ColonyHealth> {
+        self.scores.back()
+    }
+
+    /// The last few scores, oldest first.
+    pub fn scores(&self) -> impl Iterator<Item = &ColonyHealth> {
+        self.scores.iter()
+    }
+
+    /// Which way the score's gone over the last few ticks. Until there's been a few ticks to go on, it's steady.
+    pub fn trend(&self) -> Trend {
+        let Some(latest) = self.latest() else {
+            return Trend::Steady;
+        };
+        let Some(before) = self
+            .scores
+            .iter()
+            .rev()
+            .find(|h| h.tick + TREND_WINDOW <= latest.tick)
+        else {
+            return Trend::Steady;
+        };
+        if latest.score >= before.score + TREND_THRESHOLD {
+            Trend::Rising
+        } else if latest.score + TREND_THRESHOLD <= before.score {
+            Trend::Falling
+        } else {
+            Trend::Steady
+        }
+    }
+
+    /// Forget every score after the given tick, for when the game's been rewound to it. Whatever's on the board now
+    /// can't be compared with what was there before the rewind, so nobody counts as having died until the next tick.
+    pub fn rewind(&mut self, tick: usize) {
+        self.scores.retain(|h| h.tick <= tick);
+        self.deaths.retain(|(t, _)| *t <= tick);
+        self.alive = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_board::Pos;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_score() {
+        let mut testbed = TestBed::builder(2, 2)
+            .with(Pos { x: 0, y: 0 }, Species::Fish)
+            .with(Pos { x: 1, y: 0 }, Species::Shark)
+            .with(Pos { x: 0, y: 1 }, Species::Kelp)
+            .build();
+        let mut tracker = HealthTracker::default();
+        tracker.record(0, std::iter::once(&testbed.sandbox.board));
+        let health = tracker.latest().unwrap().clone();
+        // everything's just been born, so nobody's hungry and nobody's died
+        assert_eq!(health.factor(HealthFactor::Hunger), 100);
+        assert_eq!(health.factor(HealthFactor::Deaths), 100);
+        assert_eq!(health.factor(HealthFactor::Plants), 100);
+        // one each of two species, out of however many there are
+        let species = Species::all()
+            .into_iter()
+            .filter(Species::is_animal)
+            .count();
+        let diversity = points(2f64.ln() / (species as f64).ln());
+        assert_eq!(health.factor(HealthFactor::Diversity), diversity);
+        assert_eq!(health.recent_deaths, 0);
+
+        testbed.sandbox.board.remove_entity(Pos { x: 1, y: 0 });
+        tracker.record(1, std::iter::once(&testbed.sandbox.board));
+        let health = tracker.latest().unwrap();
+        assert_eq!(health.recent_deaths, 1);
+        assert_eq!(health.factor(HealthFactor::Deaths), 50);
+        assert_eq!(health.factor(HealthFactor::Diversity), 0);
+        assert!(health.score < tracker.scores().next().unwrap().score);
+    }
+
+    #[test]
+    fn test_trend() {
+        let mut testbed = TestBed::builder(2, 2)
+            .with(Pos { x: 0, y: 0 }, Species::Fish)
+            .with(Pos { x: 1, y: 0 }, Species::Crab)
+            .build();
+        let mut tracker = HealthTracker::default();
+        for tick in 0..TREND_WINDOW {
+            tracker.record(tick, std::iter::once(&testbed.sandbox.board));
+        }
+        assert_eq!(tracker.trend(), Trend::Steady);
+        testbed.sandbox.board.remove_entity(Pos { x: 1, y: 0 });
+        tracker.record(TREND_WINDOW, std::iter::once(&testbed.sandbox.board));
+        assert_eq!(tracker.trend(), Trend::Falling);
+
+        tracker.rewind(TREND_WINDOW - 1);
+        assert_eq!(tracker.latest().unwrap().tick, TREND_WINDOW - 1);
+        assert_eq!(tracker.trend(), Trend::Steady);
+    }
+
+    #[test]
+    fn test_sandbox_keeps_score() {
+        let mut sandbox = TestBed::new_default(6, 6, 4, 2, 0).sandbox;
+        assert!(sandbox.census().health.is_none());
+        sandbox.run_headless(3, |_, _| ());
+        let census = sandbox.census();
+        assert_eq!(census.health.unwrap().tick, 3);
+        assert_eq!(sandbox.get_colony_health().scores().count(), 3);
+    }
+}
@@ -0,0 +1,399 @@
+// Commands sent in from outside the game loop, letting the player poke at a running sandbox.
+
+use std::path::PathBuf;
+
+use crate::editor::Edit;
+use crate::element_traits::Lives;
+use crate::entities::{Entity, Living, NonAbstractTaxonomy, Species};
+use crate::entity_control::EntityID;
+use crate::error::Error;
+use crate::game_board::Pos;
+use crate::game_events::EventTypes;
+use crate::settings::SimulationSettings;
+use crate::Sandbox;
+
+/// Something the outside world wants the sandbox to do in between ticks.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SandboxCommand {
+    /// Place a new entity of the given species on an empty tile.
+    Spawn(Pos, Species),
+    /// Build a structure on an empty tile, paying for it out of the colony's resources.
+    Build(Pos, Species),
+    /// Remove whatever is on a tile.
+    Remove(Pos),
+    /// Take back the last spawn or removal made since the last tick.
+    Undo,
+    /// Make the last spawn or removal that was taken back again.
+    Redo,
+    /// Kill a living entity outright. It'll be cleaned up at the end of the next tick, like anything else that dies.
+    Kill(EntityID),
+    /// Make an event go off on the next tick, skipping the usual odds.
+    TriggerEvent(EventTypes),
+    /// Set how full an animal is.
+    SetHunger(EntityID, i64),
+    /// Override any of an entity's stats by name, like in a scenario file.
+    SetStat(EntityID, String, String),
+    /// Look up an entity's current stats.
+    Stats(EntityID),
+    /// Look up an animal's parents and children. Works for anything that's ever been on the board, dead or alive.
+    Family(EntityID),
+    /// Follow an animal around, explaining everything it decides to do, or stop following whichever one was.
+    Spotlight(Option<EntityID>),
+    /// Look at everything going on at a tile, from then on, or stop looking at whichever one was.
+    Inspect(Option<Pos>),
+    /// Switch the AI debug overlay on or off.
+    SetDebugOverlay(bool),
+    /// Save a picture of the board into this folder, named after the current tick.
+    Screenshot(PathBuf),
+    /// Start recording a timelapse, taking a screenshot every this many ticks.
+    StartTimelapse(usize),
+    /// Stop recording the timelapse and save it into this folder.
+    StopTimelapse(PathBuf),
+    /// Go back this many ticks and pause there. Carrying on from there plays out differently than it did the first time.
+    Rewind(usize),
+    /// Run this many ticks, then pause.
+    Step(usize),
+    /// Change how many ticks we try to run per second.
+    SetTickRate(f64),
+    /// Switch over to a new set of preferences.
+    ApplySettings(SimulationSettings),
+    /// Stop or start ticking. Commands will still be handled while the sandbox is paused.
+    SetPaused(bool),
+    /// Switch over to looking at (and poking at) another region.
+    ViewRegion(String),
+    /// Vote for the first choice (false) or the second (true) on the event being voted on, by whoever's voting.
+    Vote(String, bool),
+    /// Stop the game for good. It's up to whatever's driving the sandbox to actually stop ticking it.
+    Shutdown,
+}
+
+impl Sandbox {
+    /// Find where an entity currently is on the board.
+    fn find_entity(&self, id: EntityID) -> Result<Pos, Error> {
+        self.entity_context
+            .read()
+            .unwrap()
+            .get_active_entries()
+            .get(&id)
+            .copied()
+            .ok_or(Error::StaleEntityId(id))
+    }
+
+    /// Carry out a single command, returning a short description of what happened.
+    /// If the command couldn't be carried out, the board is left untouched.
+    pub fn apply_command(&mut self, command: SandboxCommand) -> Result<String, String> {
+        match command {
+            SandboxCommand::Spawn(pos, species) => {
+                self.board
+                    .place_entity(pos, species.create_new(None))
+                    .map_err(|e| e.to_string())?;
+                self.edits.record(Edit::Remove(pos));
+                Ok(format!("Spawned a {} at {pos:?}", species.name()))
+            }
+            SandboxCommand::Build(pos, species) => {
+                let left = self.build(pos, species).map_err(|e| e.to_string())?;
+                Ok(format!(
+                    "Built a {} at {pos:?}, leaving {left} resources",
+                    species.name()
+                ))
+            }
+            SandboxCommand::Remove(pos) => {
+                if !self.board.is_valid_pos(pos) {
+                    return Err(format!("{pos:?} is off the board"));
+                }
+                match self.board.remove_entity(pos) {
+                    Some(entity) => {
+                        self.edits.record(Edit::Place(pos, Box::new(entity)));
+                        Ok(format!("Removed the entity at {pos:?}"))
+                    }
+                    None => Err(format!("There's nothing at {pos:?} to remove")),
+                }
+            }
+            SandboxCommand::Undo => self.undo_edit().map_err(|e| e.to_string()),
+            SandboxCommand::Redo => self.redo_edit().map_err(|e| e.to_string()),
+            SandboxCommand::Kill(id) => {
+                let pos = self.find_entity(id).map_err(|e| e.to_string())?;
+                match self.board.entity_at_mut(pos) {
+                    Some(Entity::Living(Living::Animals(a))) => a.die("divine intervention"),
+                    Some(Entity::Living(Living::Plants(p))) => p.die("divine intervention"),
+                    _ => return Err(format!("The entity at {pos:?} can't be killed")),
+                }
+                Ok(format!("Killed entity {} at {pos:?}", id.get_id_val()))
+            }
+            SandboxCommand::TriggerEvent(kind) => {
+                // piggyback off of scripted events, so the event goes through the usual flow
+                let msg = format!("Triggered {}", kind.name());
+                self.scripted_events.push((self.clock, kind));
+                Ok(msg)
+            }
+            SandboxCommand::SetHunger(id, hunger) => {
+                let pos = self.find_entity(id).map_err(|e| e.to_string())?;
+                match self.board.entity_at_mut(pos) {
+                    Some(ent @ Entity::Living(Living::Animals(_))) => {
+                        ent.set_stat("hunger", &hunger.to_string())?
+                    }
+                    _ => return Err(format!("The entity at {pos:?} doesn't get hungry")),
+                }
+                Ok(format!(
+                    "Set the hunger of entity {} to {hunger}",
+                    id.get_id_val()
+                ))
+            }
+            SandboxCommand::SetStat(id, stat, value) => {
+                let pos = self.find_entity(id).map_err(|e| e.to_string())?;
+                match self.board.entity_at_mut(pos) {
+                    Some(ent) => ent.set_stat(&stat, &value)?,
+                    None => return Err(format!("There's nothing at {pos:?}")),
+                }
+                Ok(format!(
+                    "Set {stat} of entity {} to {value}",
+                    id.get_id_val()
+                ))
+            }
+            SandboxCommand::Stats(id) => {
+                let pos = self.find_entity(id).map_err(|e| e.to_string())?;
+                match self.board.entity_at(pos) {
+                    Some(ent) => Ok(format!(
+                        "{} #{} at {pos:?}: {}",
+                        Species::of(ent).name(),
+                        id.get_id_val(),
+                        ent.get_stats()
+                    )),
+                    None => Err(format!("There's nothing at {pos:?}")),
+                }
+            }
+            SandboxCommand::Family(id) => {
+                let ancestor = self
+                    .lineage
+                    .get(id)
+                    .ok_or_else(|| Error::StaleEntityId(id).to_string())?;
+                let list = |ids: &[EntityID]| match ids.is_empty() {
+                    true => String::from("none"),
+                    false => ids
+                        .iter()
+                        .map(|id| format!("#{}", id.get_id_val()))
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                };
+                Ok(format!(
+                    "{} #{} (generation {}): parents {}; children {}",
+                    ancestor.species.name(),
+                    id.get_id_val(),
+                    ancestor.generation,
+                    list(&ancestor.parents),
+                    list(&ancestor.children)
+                ))
+            }
+            SandboxCommand::Spotlight(id) => {
+                self.set_spotlight(id).map_err(|e| e.to_string())?;
+                Ok(match id {
+                    Some(id) => format!("Following #{}", id.get_id_val()),
+                    None => String::from("Stopped following"),
+                })
+            }
+            SandboxCommand::Inspect(pos) => {
+                let Some(pos) = pos else {
+                    self.inspected = None;
+                    return Ok(String::from("Stopped looking at the tile"));
+                };
+                let report = self.board.tile_report(pos).map_err(|e| e.to_string())?;
+                self.inspected = Some(pos);
+                Ok(report.to_string())
+            }
+            SandboxCommand::SetDebugOverlay(on) => {
+                self.set_debug_overlay(on);
+                Ok(format!(
+                    "Switched the debug overlay {}",
+                    if on { "on" } else { "off" }
+                ))
+            }
+            SandboxCommand::Screenshot(dir) => {
+                let path = self.save_screenshot(&dir).map_err(|e| e.to_string())?;
+                Ok(format!("Saved a screenshot to {}", path.display()))
+            }
+            SandboxCommand::StartTimelapse(every) => {
+                self.start_timelapse(every);
+                Ok(format!(
+                    "Recording a timelapse, a frame every {every} ticks"
+                ))
+            }
+            SandboxCommand::StopTimelapse(dir) => {
+                let timelapse = self
+                    .stop_timelapse()
+                    .ok_or("There's no timelapse being recorded")?;
+                let path = timelapse.save(&dir).map_err(|e| e.to_string())?;
+                Ok(format!(
+                    "Saved a timelapse of {} frames to {}",
+                    timelapse.frames().len(),
+                    path.display()
+                ))
+            }
+            SandboxCommand::Rewind(ticks) => {
+                let tick = self.rewind(ticks).map_err(|e| e.to_string())?;
+                self.edits.clear();
+                self.paused = true;
+                self.pending_steps = 0;
+                Ok(format!("Rewound to tick {tick}"))
+            }
+            SandboxCommand::Step(ticks) => {
+                self.paused = true;
+                self.pending_steps = ticks;
+                Ok(format!("Running {ticks} ticks"))
+            }
+            SandboxCommand::SetTickRate(tick_rate) => {
+                if !(tick_rate > 0.0 && tick_rate.is_finite()) {
+                    return Err(format!("{tick_rate} isn't a usable tick rate"));
+                }
+                self.tick_rate = tick_rate;
+                Ok(format!("Running at {tick_rate} ticks per second"))
+            }
+            SandboxCommand::ApplySettings(settings) => {
+                self.apply_settings(settings);
+                Ok(String::from("Applied new settings"))
+            }
+            SandboxCommand::SetPaused(paused) => {
+                self.paused = paused;
+                self.pending_steps = 0;
+                Ok(String::from(if paused { "Paused" } else { "Resumed" }))
+            }
+            SandboxCommand::ViewRegion(name) => {
+                self.view_region(&name).map_err(|e| e.to_string())?;
+                self.edits.clear();
+                Ok(format!("Now looking at {name}"))
+            }
+            SandboxCommand::Vote(voter, choice) => self.vote(&voter, choice),
+            SandboxCommand::Shutdown => Ok(String::from("Shutting down")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::element_traits::Lives;
+    use crate::entities::animals::{Animals, ConcreteAnimals};
+    use crate::entity_control::TrackedEntity;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_spawn_and_remove() {
+        let mut testbed =
+            TestBed::new_populated(3, 3, vec![(Pos { x: 0, y: 0 }, ConcreteAnimals::Crab)]);
+        let pos = Pos { x: 2, y: 1 };
+
+        assert!(testbed
+            .sandbox
+            .apply_command(SandboxCommand::Spawn(pos, Species::Shark))
+            .is_ok());
+        assert_eq!(
+            testbed.get_entity_at_pos(pos).map(Species::of),
+            Some(Species::Shark)
+        );
+        // new living things need to be tracked like anything else
+        assert!(testbed.sandbox.get_important_entities().contains(&pos));
+
+        // can't stack things on top of each other, or place them off the board
+        assert!(testbed
+            .sandbox
+            .apply_command(SandboxCommand::Spawn(pos, Species::Fish))
+            .is_err());
+        assert!(testbed
+            .sandbox
+            .apply_command(SandboxCommand::Spawn(Pos { x: 3, y: 0 }, Species::Fish))
+            .is_err());
+
+        assert!(testbed
+            .sandbox
+            .apply_command(SandboxCommand::Remove(pos))
+            .is_ok());
+        assert!(testbed.get_entity_at_pos(pos).is_none());
+        assert!(!testbed.sandbox.get_important_entities().contains(&pos));
+        assert!(testbed
+            .sandbox
+            .apply_command(SandboxCommand::Remove(pos))
+            .is_err());
+    }
+
+    #[test]
+    /// Starve and kill something on demand, then make sure it gets cleaned up like anything else
+    fn test_kill_and_set_hunger() {
+        let pos = Pos { x: 1, y: 1 };
+        let mut testbed = TestBed::new_populated(3, 3, vec![(pos, ConcreteAnimals::Fish)]);
+        let id = testbed.get_entity_at_pos(pos).unwrap().get_id().unwrap();
+
+        testbed
+            .sandbox
+            .apply_command(SandboxCommand::SetHunger(id, -20))
+            .unwrap();
+        match testbed.get_entity_at_pos(pos) {
+            Some(Entity::Living(Living::Animals(a @ Animals::Fish(f)))) => {
+                assert!(a.starving());
+                assert_eq!(f.hunger.level, -20);
+            }
+            e => panic!("Expected a fish, found {e:?}"),
+        }
+
+        testbed
+            .sandbox
+            .apply_command(SandboxCommand::Kill(id))
+            .unwrap();
+        match testbed.get_entity_at_pos(pos) {
+            Some(Entity::Living(Living::Animals(a))) => assert!(a.is_dead()),
+            e => panic!("Expected a fish, found {e:?}"),
+        }
+        testbed.run_n_full_steps(1);
+        assert!(testbed.sandbox.get_important_entities().is_empty());
+
+        // it's gone, so there's nothing left to kill
+        assert!(testbed
+            .sandbox
+            .apply_command(SandboxCommand::Kill(id))
+            .is_err());
+        assert!(testbed
+            .sandbox
+            .apply_command(SandboxCommand::SetHunger(EntityID::from(1000), 0))
+            .is_err());
+    }
+
+    #[test]
+    fn test_set_and_get_stats() {
+        let pos = Pos { x: 0, y: 0 };
+        let mut testbed = TestBed::new_populated(2, 2, vec![(pos, ConcreteAnimals::Crab)]);
+        let id = testbed.get_entity_at_pos(pos).unwrap().get_id().unwrap();
+
+        testbed
+            .sandbox
+            .apply_command(SandboxCommand::SetStat(
+                id,
+                String::from("age"),
+                String::from("12"),
+            ))
+            .unwrap();
+        let stats = testbed
+            .sandbox
+            .apply_command(SandboxCommand::Stats(id))
+            .unwrap();
+        assert!(stats.starts_with("crab"));
+        assert!(stats.contains("age=12"));
+
+        assert!(testbed
+            .sandbox
+            .apply_command(SandboxCommand::SetStat(
+                id,
+                String::from("wings"),
+                String::from("2"),
+            ))
+            .is_err());
+    }
+
+    #[test]
+    fn test_trigger_event() {
+        let mut testbed = TestBed::new_default(5, 5, 2, 0, 0);
+        testbed
+            .sandbox
+            .apply_command(SandboxCommand::TriggerEvent(EventTypes::Party))
+            .unwrap();
+        let event = testbed.sandbox.handle_events().unwrap();
+        assert_eq!(event.kind, EventTypes::Party);
+    }
+}
@@ -0,0 +1,210 @@
+// Text commands for the developer console, turned into commands for the sandbox.
+
+use std::path::PathBuf;
+
+use crate::commands::SandboxCommand;
+use crate::entities::Species;
+use crate::entity_control::EntityID;
+use crate::game_board::Pos;
+use crate::game_events::EventTypes;
+
+/// Everything the console understands.
+pub const CONSOLE_HELP: &str = "\
+spawn <species> <x> <y>        place a new entity on an empty tile
+build <structure> <x> <y>      build a structure with the colony's resources
+remove <x> <y>                 remove whatever is on a tile
+undo / redo                    take back the last spawn or removal since the last tick, or make it again
+kill <id>                      kill a living entity
+stats <id>                     show an entity's stats
+family <id>                    show an animal's parents and children
+follow <id> / unfollow         follow an animal around and explain what it's doing
+tile <x> <y>                   show the terrain, light, nutrients, scent, pollution and current on a tile
+seteffect <id> <stat> <value>  override one of an entity's stats
+hunger <id> <value>            set how full an animal is
+event <name>                   trigger an event on the next tick
+tick <n>                       run n ticks, then pause
+rewind <n>                     go back n ticks, then pause
+screenshot                     save a picture of the board as a PNG
+timelapse <n> / timelapse stop record a frame every n ticks, then save them as an animated PNG
+speed <ticks per second>       change how fast the simulation runs
+pause / resume                 stop or start the simulation
+region <name>                  switch over to another region
+vote <1|2>                     vote for one of the choices on an event that's being voted on";
+
+/// Who votes from the console are cast by. Anyone voting from somewhere else gets a name of their own.
+pub const CONSOLE_VOTER: &str = "console";
+
+fn parse_arg<T: std::str::FromStr>(arg: Option<&str>, what: &str) -> Result<T, String> {
+    let arg = arg.ok_or_else(|| format!("missing {what}"))?;
+    arg.parse()
+        .map_err(|_| format!("'{arg}' isn't a valid {what}"))
+}
+
+/// Turn a line typed into the console into something the sandbox can run.
+/// If the line doesn't make sense, the error explains why. Asking for "help" also gets you an "error", listing every command.
+pub fn parse_command(line: &str) -> Result<SandboxCommand, String> {
+    let mut words = line.split_whitespace();
+    let command = match words.next() {
+        None => return Err(String::from("type 'help' for a list of commands")),
+        Some("help") => return Err(String::from(CONSOLE_HELP)),
+        Some("spawn") => {
+            let name = words.next().ok_or("missing species")?;
+            let species = Species::from_name(name)
+                .ok_or_else(|| format!("'{name}' isn't a known species"))?;
+            let x = parse_arg(words.next(), "x position")?;
+            let y = parse_arg(words.next(), "y position")?;
+            SandboxCommand::Spawn(Pos { x, y }, species)
+        }
+        Some("build") => {
+            let name = words.next().ok_or("missing structure")?;
+            let species = Species::from_name(name)
+                .ok_or_else(|| format!("'{name}' isn't a known structure"))?;
+            let x = parse_arg(words.next(), "x position")?;
+            let y = parse_arg(words.next(), "y position")?;
+            SandboxCommand::Build(Pos { x, y }, species)
+        }
+        Some("remove") => {
+            let x = parse_arg(words.next(), "x position")?;
+            let y = parse_arg(words.next(), "y position")?;
+            SandboxCommand::Remove(Pos { x, y })
+        }
+        Some("undo") => SandboxCommand::Undo,
+        Some("redo") => SandboxCommand::Redo,
+        Some("kill") => {
+            SandboxCommand::Kill(EntityID::from(parse_arg::<usize>(words.next(), "id")?))
+        }
+        Some("stats") => {
+            SandboxCommand::Stats(EntityID::from(parse_arg::<usize>(words.next(), "id")?))
+        }
+        Some("family") => {
+            SandboxCommand::Family(EntityID::from(parse_arg::<usize>(words.next(), "id")?))
+        }
+        Some("follow") => SandboxCommand::Spotlight(Some(EntityID::from(parse_arg::<usize>(
+            words.next(),
+            "id",
+        )?))),
+        Some("unfollow") => SandboxCommand::Spotlight(None),
+        Some("tile") => {
+            let x = parse_arg(words.next(), "x position")?;
+            let y = parse_arg(words.next(), "y position")?;
+            SandboxCommand::Inspect(Some(Pos { x, y }))
+        }
+        Some("seteffect") => {
+            let id = EntityID::from(parse_arg::<usize>(words.next(), "id")?);
+            let stat = words.next().ok_or("missing stat")?;
+            let value = words.next().ok_or("missing value")?;
+            SandboxCommand::SetStat(id, stat.to_owned(), value.to_owned())
+        }
+        Some("hunger") => {
+            let id = EntityID::from(parse_arg::<usize>(words.next(), "id")?);
+            SandboxCommand::SetHunger(id, parse_arg(words.next(), "hunger")?)
+        }
+        Some("event") => {
+            let name = words.next().ok_or("missing event name")?;
+            let kind = EventTypes::from_name(name)
+                .ok_or_else(|| format!("'{name}' isn't a known event"))?;
+            SandboxCommand::TriggerEvent(kind)
+        }
+        Some("tick") => SandboxCommand::Step(parse_arg(words.next(), "tick count")?),
+        Some("rewind") => SandboxCommand::Rewind(parse_arg(words.next(), "tick count")?),
+        Some("screenshot") => SandboxCommand::Screenshot(PathBuf::from(".")),
+        Some("timelapse") => match words.next() {
+            Some("stop") => SandboxCommand::StopTimelapse(PathBuf::from(".")),
+            every => SandboxCommand::StartTimelapse(parse_arg(every, "tick count")?),
+        },
+        Some("speed") => SandboxCommand::SetTickRate(parse_arg(words.next(), "tick rate")?),
+        Some("pause") => SandboxCommand::SetPaused(true),
+        Some("resume") => SandboxCommand::SetPaused(false),
+        Some("region") => {
+            // region names can have spaces in them
+            let name = words.by_ref().collect::<Vec<&str>>().join(" ");
+            if name.is_empty() {
+                return Err(String::from("missing region name"));
+            }
+            SandboxCommand::ViewRegion(name)
+        }
+        Some("vote") => match words.next() {
+            Some("1") => SandboxCommand::Vote(String::from(CONSOLE_VOTER), false),
+            Some("2") => SandboxCommand::Vote(String::from(CONSOLE_VOTER), true),
+            Some(other) => return Err(format!("'{other}' isn't one of the choices, pick 1 or 2")),
+            None => return Err(String::from("missing choice")),
+        },
+        Some(other) => return Err(format!("unknown command '{other}', try 'help'")),
+    };
+    if let Some(extra) = words.next() {
+        return Err(format!("didn't expect '{extra}'"));
+    }
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            parse_command("spawn shark 3 4"),
+            Ok(SandboxCommand::Spawn(Pos { x: 3, y: 4 }, Species::Shark))
+        );
+        assert_eq!(
+            parse_command("build barrier 0 2"),
+            Ok(SandboxCommand::Build(Pos { x: 0, y: 2 }, Species::Barrier))
+        );
+        assert_eq!(
+            parse_command("  seteffect 12 hp 40 "),
+            Ok(SandboxCommand::SetStat(
+                EntityID::from(12),
+                String::from("hp"),
+                String::from("40")
+            ))
+        );
+        assert_eq!(
+            parse_command("event oil_spill"),
+            Ok(SandboxCommand::TriggerEvent(EventTypes::OilSpill))
+        );
+        assert_eq!(parse_command("tick 10"), Ok(SandboxCommand::Step(10)));
+        assert_eq!(parse_command("rewind 3"), Ok(SandboxCommand::Rewind(3)));
+        assert_eq!(
+            parse_command("screenshot"),
+            Ok(SandboxCommand::Screenshot(PathBuf::from(".")))
+        );
+        assert_eq!(
+            parse_command("timelapse 5"),
+            Ok(SandboxCommand::StartTimelapse(5))
+        );
+        assert_eq!(
+            parse_command("timelapse stop"),
+            Ok(SandboxCommand::StopTimelapse(PathBuf::from(".")))
+        );
+        assert_eq!(
+            parse_command("family 7"),
+            Ok(SandboxCommand::Family(EntityID::from(7)))
+        );
+        assert_eq!(
+            parse_command("follow 3"),
+            Ok(SandboxCommand::Spotlight(Some(EntityID::from(3))))
+        );
+        assert_eq!(parse_command("undo"), Ok(SandboxCommand::Undo));
+        assert_eq!(
+            parse_command("tile 2 5"),
+            Ok(SandboxCommand::Inspect(Some(Pos { x: 2, y: 5 })))
+        );
+        assert_eq!(
+            parse_command("region open ocean"),
+            Ok(SandboxCommand::ViewRegion(String::from("open ocean")))
+        );
+
+        assert_eq!(
+            parse_command("vote 2"),
+            Ok(SandboxCommand::Vote(String::from(CONSOLE_VOTER), true))
+        );
+
+        assert!(parse_command("spawn dolphin 0 0").is_err());
+        assert!(parse_command("vote 3").is_err());
+        assert!(parse_command("kill fish").is_err());
+        assert!(parse_command("tick 1 2").is_err());
+        assert!(parse_command("region").is_err());
+        assert_eq!(parse_command("help"), Err(String::from(CONSOLE_HELP)));
+    }
+}
@@ -0,0 +1,94 @@
+// Keeping a panic in the game loop from taking the rest of the program down with it.
+
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+/// Everything we know about why the game loop died.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrashReport {
+    /// The tick the game was on when it crashed.
+    pub tick: usize,
+    /// What the panic said.
+    pub message: String,
+    pub backtrace: String,
+}
+
+thread_local! {
+    /// The message and backtrace of the last panic on this thread, saved by our panic hook.
+    static LAST_PANIC: RefCell<Option<(String, String)>> = const { RefCell::new(None) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+/// Install a panic hook that squirrels away the message and backtrace of every panic, so they can be reported later.
+/// The old hook still gets run, so panics get printed like normal.
+fn install_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let message = match info.payload().downcast_ref::<&str>() {
+                Some(s) => s.to_string(),
+                None => match info.payload().downcast_ref::<String>() {
+                    Some(s) => s.clone(),
+                    None => String::from("unknown panic"),
+                },
+            };
+            let message = match info.location() {
+                Some(location) => format!("{message} (at {location})"),
+                None => message,
+            };
+            let backtrace = Backtrace::force_capture().to_string();
+            LAST_PANIC.with(|last| *last.borrow_mut() = Some((message, backtrace)));
+            previous_hook(info);
+        }));
+    });
+}
+
+/// Run something that might panic, catching the panic and turning it into a report instead.
+/// The tick is read after the panic is caught, so it reflects wherever things went wrong.
+pub fn run_contained<T>(
+    f: impl FnOnce(&mut T),
+    state: &mut T,
+    get_tick: impl Fn(&T) -> usize,
+) -> Result<(), CrashReport> {
+    install_hook();
+    match panic::catch_unwind(AssertUnwindSafe(|| f(state))) {
+        Ok(()) => Ok(()),
+        Err(_) => {
+            let (message, backtrace) = LAST_PANIC
+                .with(|last| last.borrow_mut().take())
+                .unwrap_or_else(|| (String::from("unknown panic"), String::new()));
+            Err(CrashReport {
+                tick: get_tick(state),
+                message,
+                backtrace,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panics_are_contained() {
+        let mut clock = 0;
+        let report = run_contained(
+            |clock: &mut usize| {
+                *clock = 42;
+                panic!("the fish have unionized");
+            },
+            &mut clock,
+            |clock| *clock,
+        )
+        .unwrap_err();
+        assert_eq!(report.tick, 42);
+        assert!(report.message.starts_with("the fish have unionized"));
+
+        assert!(run_contained(|clock: &mut usize| *clock += 1, &mut clock, |c| *c).is_ok());
+        assert_eq!(clock, 43);
+    }
+}
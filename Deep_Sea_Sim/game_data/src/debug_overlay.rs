@@ -0,0 +1,95 @@
+// A look under the hood at what the AI is thinking, for working out why the animals do what they do. It's only put
+// together while it's switched on, since working out what everything's after means running every animal's search over
+// again once the tick is done. Every animal gets marked with what it's busy doing and where it's headed, and the one
+// being followed around (see spotlight.rs) gets every tile its search looked at filled in as well.
+
+use crate::ai_controller::{AIAction, AIControlled};
+use crate::game_board::Pos;
+use crate::Sandbox;
+
+/// What one animal is up to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimalDebug {
+    pub pos: Pos,
+    /// What it's busy doing, like "idle" or "courting".
+    pub behavior: String,
+    /// Whatever it's after, if it's after anything.
+    pub target: Option<Pos>,
+}
+
+/// What every animal on the board is up to.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DebugOverlay {
+    pub animals: Vec<AnimalDebug>,
+    /// Every tile the animal being followed looked at while it was working out where to go.
+    pub explored: Vec<Pos>,
+}
+
+impl Sandbox {
+    /// Switch the overlay on or off.
+    pub fn set_debug_overlay(&mut self, on: bool) {
+        self.debug_overlay = on;
+    }
+
+    /// What every animal on the board is up to, or None if the overlay's switched off.
+    pub fn get_debug_overlay(&self) -> Option<DebugOverlay> {
+        if !self.debug_overlay {
+            return None;
+        }
+        let followed = self.get_spotlight().map(|s| s.pos);
+        let mut overlay = DebugOverlay::default();
+        for (pos, entity) in self.board.iter_occupied() {
+            let Some(animal) = entity.animal() else {
+                continue;
+            };
+            let behavior = animal.get_current_behavior();
+            let plan = behavior.plan(animal, pos, &self.board, &self.rules);
+            overlay.animals.push(AnimalDebug {
+                pos,
+                behavior: behavior.get_action_desc(),
+                target: plan.target,
+            });
+            if followed == Some(pos) {
+                overlay.explored = plan.explored;
+            }
+        }
+        Some(overlay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_controller::{AIConcreteBehaviors, EatAction};
+    use crate::entities::Species;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_debug_overlay() {
+        let (shark, fish) = (Pos { x: 0, y: 0 }, Pos { x: 4, y: 0 });
+        let mut testbed = TestBed::builder(1, 5)
+            .with(shark, Species::Shark)
+            .with(fish, Species::Fish)
+            .build();
+        testbed
+            .animal_at_mut(shark)
+            .set_current_behavior(AIConcreteBehaviors::Eating(EatAction::new()));
+        let id = testbed.id_at(shark);
+        let mut sandbox = testbed.sandbox;
+        assert!(sandbox.get_debug_overlay().is_none());
+        sandbox.set_debug_overlay(true);
+
+        let overlay = sandbox.get_debug_overlay().unwrap();
+        let hunter = overlay.animals.iter().find(|a| a.pos == shark).unwrap();
+        assert_eq!(hunter.behavior, "eating");
+        assert_eq!(hunter.target, Some(fish));
+        let idler = overlay.animals.iter().find(|a| a.pos == fish).unwrap();
+        assert_eq!((idler.behavior.as_str(), idler.target), ("idle", None));
+        // nobody's being followed, so there's no search to show
+        assert!(overlay.explored.is_empty());
+
+        sandbox.set_spotlight(Some(id)).unwrap();
+        let explored = sandbox.get_debug_overlay().unwrap().explored;
+        assert!(explored.contains(&shark) && explored.contains(&fish));
+    }
+}
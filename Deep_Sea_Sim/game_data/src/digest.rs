@@ -0,0 +1,78 @@
+// A short fingerprint of a whole game, for telling at a glance whether two games ended up in exactly the same place. It
+// counts up every species and hashes the game as it would be saved, so every entity, stat and scheduled event goes into it.
+// The golden runs (see tests/test_golden.rs) lean on this to catch changes to how the game plays out.
+
+use std::fmt::Display;
+
+use crate::entities::Species;
+use crate::scenario::Scenario;
+use crate::Sandbox;
+
+/// Everything that goes into a digest, boiled down.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Digest {
+    pub tick: usize,
+    /// How many of each species there are in every region, by name. Anything there's none of is left out.
+    pub census: Vec<(Species, usize)>,
+    /// A hash of the whole game, as it would be saved.
+    pub hash: u64,
+}
+
+impl Display for Digest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "tick={}", self.tick)?;
+        for (species, count) in &self.census {
+            write!(f, " {}={count}", species.name())?;
+        }
+        write!(f, " hash={:016x}", self.hash)
+    }
+}
+
+/// FNV-1a, which unlike the standard library's hasher gives the same answer on every machine and every version of Rust.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl Sandbox {
+    pub fn digest(&self) -> Digest {
+        let mut census: Vec<(Species, usize)> = self.census().populations.into_iter().collect();
+        census.sort_by_key(|(species, _)| species.name());
+        let saved = Scenario::capture(self, "Digest").to_string();
+        Digest {
+            tick: self.clock,
+            census,
+            hash: fnv1a(saved.as_bytes()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_board::Pos;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_digest() {
+        let mut sandbox = TestBed::new_populated(
+            1,
+            5,
+            vec![
+                (Pos { x: 0, y: 0 }, Species::Fish),
+                (Pos { x: 2, y: 0 }, Species::Fish),
+                (Pos { x: 4, y: 0 }, Species::Crab),
+            ],
+        )
+        .sandbox;
+        let digest = sandbox.digest();
+        assert_eq!(digest.census, vec![(Species::Crab, 1), (Species::Fish, 2)]);
+        assert!(digest.to_string().starts_with("tick=0 crab=1 fish=2 hash="));
+        assert_eq!(sandbox.digest(), digest);
+
+        sandbox.tick();
+        assert_ne!(sandbox.digest(), digest);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+    }
+}
@@ -0,0 +1,199 @@
+// Taking back changes made to the board by hand. Every spawn and removal that comes in as a command is remembered as
+// the edit that'd undo it: undoing a spawn takes whatever was spawned back off the board, and undoing a removal puts the
+// very same entity back where it was, ID and all, so the entity manager ends up tracking it just as it was before. Undoing
+// an edit remembers what it'd take to redo it, and making a fresh edit forgets anything that could've been redone.
+//
+// Edits only make sense on the board they were made to, so they're all forgotten as soon as the game ticks on, rewinds
+// or switches over to another region.
+
+use crate::entities::{Entity, Species};
+use crate::error::Error;
+use crate::game_board::{Board, Pos};
+use crate::Sandbox;
+
+/// How many edits can be undone. The oldest ones are forgotten past this.
+pub const MAX_EDITS: usize = 100;
+
+/// A change to make to the board, to undo (or redo) one that was made before.
+#[derive(Debug, Clone)]
+pub enum Edit {
+    /// Put this entity back on the tile.
+    Place(Pos, Box<Entity>),
+    /// Take whatever's on the tile back off it.
+    Remove(Pos),
+}
+
+impl Edit {
+    fn pos(&self) -> Pos {
+        match self {
+            Self::Place(pos, _) | Self::Remove(pos) => *pos,
+        }
+    }
+
+    /// Whether the board's changed since, so that this can't be done any more.
+    fn blocked(&self, board: &Board) -> bool {
+        let occupied = board.get_tile_from_pos(self.pos()).is_occupied();
+        match self {
+            Self::Place(..) => occupied,
+            Self::Remove(_) => !occupied,
+        }
+    }
+
+    /// Make the change, returning the edit that'd change it back and a description of what was done.
+    fn apply(self, board: &mut Board) -> Result<(Edit, String), Error> {
+        match self {
+            Self::Place(pos, entity) => {
+                let species = Species::of(&entity);
+                board.place_entity(pos, *entity)?;
+                Ok((
+                    Self::Remove(pos),
+                    format!("Put the {} back at {pos:?}", species.name()),
+                ))
+            }
+            Self::Remove(pos) => {
+                let entity = board.remove_entity(pos).ok_or(Error::EditBlocked(pos))?;
+                let msg = format!("Took the {} back off {pos:?}", Species::of(&entity).name());
+                Ok((Self::Place(pos, Box::new(entity)), msg))
+            }
+        }
+    }
+}
+
+/// Every edit that can be undone, and every undone one that can be redone, most recent last.
+#[derive(Debug, Default)]
+pub struct EditHistory {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+}
+
+impl EditHistory {
+    /// Remember an edit that was just made, by what it'd take to undo it.
+    pub fn record(&mut self, undo: Edit) {
+        self.redo.clear();
+        self.undo.push(undo);
+        if self.undo.len() > MAX_EDITS {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Forget every edit, once they no longer fit the board.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+impl Sandbox {
+    /// Take back the last edit made to the board. Returns what was done to take it back.
+    pub fn undo_edit(&mut self) -> Result<String, Error> {
+        let edit = self.edits.undo.pop().ok_or(Error::NothingToUndo)?;
+        if edit.blocked(&self.board) {
+            let pos = edit.pos();
+            self.edits.undo.push(edit);
+            return Err(Error::EditBlocked(pos));
+        }
+        let (redo, msg) = edit.apply(&mut self.board)?;
+        self.edits.redo.push(redo);
+        Ok(msg)
+    }
+
+    /// Make the last edit that was undone again. Returns what was done to make it.
+    pub fn redo_edit(&mut self) -> Result<String, Error> {
+        let edit = self.edits.redo.pop().ok_or(Error::NothingToRedo)?;
+        if edit.blocked(&self.board) {
+            let pos = edit.pos();
+            self.edits.redo.push(edit);
+            return Err(Error::EditBlocked(pos));
+        }
+        let (undo, msg) = edit.apply(&mut self.board)?;
+        self.edits.undo.push(undo);
+        Ok(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::SandboxCommand;
+    use crate::entities::NonAbstractTaxonomy;
+    use crate::test_utils::TestBed;
+
+    /// The ID of whatever's at a position, as far as the entity manager knows.
+    fn registered_at(testbed: &TestBed, pos: Pos) -> Option<usize> {
+        let em = testbed.sandbox.entity_context.read().unwrap();
+        em.get_active_entries()
+            .iter()
+            .find(|(_, p)| **p == pos)
+            .map(|(id, _)| id.get_id_val())
+    }
+
+    #[test]
+    fn test_undo_spawn_and_remove() {
+        let crab = Pos { x: 0, y: 0 };
+        let fish = Pos { x: 2, y: 1 };
+        let mut testbed = TestBed::builder(3, 3).with(crab, Species::Crab).build();
+        let crab_id = registered_at(&testbed, crab).unwrap();
+        assert!(testbed.sandbox.undo_edit().is_err());
+
+        let commands = [
+            SandboxCommand::Spawn(fish, Species::Fish),
+            SandboxCommand::Remove(crab),
+        ];
+        for command in commands {
+            testbed.sandbox.apply_command(command).unwrap();
+        }
+        let fish_id = registered_at(&testbed, fish).unwrap();
+        assert_eq!(registered_at(&testbed, crab), None);
+
+        // the crab comes back as itself, and the fish is forgotten about entirely
+        testbed.sandbox.undo_edit().unwrap();
+        assert_eq!(registered_at(&testbed, crab), Some(crab_id));
+        testbed.sandbox.undo_edit().unwrap();
+        assert!(testbed.sandbox.board.entity_at(fish).is_none());
+        assert_eq!(registered_at(&testbed, fish), None);
+        assert!(!testbed.sandbox.edits.can_undo());
+
+        testbed.sandbox.redo_edit().unwrap();
+        assert_eq!(registered_at(&testbed, fish), Some(fish_id));
+        assert!(testbed.sandbox.edits.can_undo());
+
+        // a fresh edit means there's nothing left to redo
+        testbed
+            .sandbox
+            .apply_command(SandboxCommand::Spawn(Pos { x: 1, y: 1 }, Species::Kelp))
+            .unwrap();
+        assert!(!testbed.sandbox.edits.can_redo());
+        assert!(testbed.sandbox.redo_edit().is_err());
+    }
+
+    #[test]
+    fn test_edits_left_alone_when_blocked() {
+        let pos = Pos { x: 1, y: 1 };
+        let mut testbed = TestBed::builder(3, 3).build();
+        let sandbox = &mut testbed.sandbox;
+        sandbox
+            .apply_command(SandboxCommand::Spawn(pos, Species::Fish))
+            .unwrap();
+        sandbox.undo_edit().unwrap();
+        // something else took the spot in the meantime
+        sandbox
+            .board
+            .place_entity(pos, Species::Rock.create_new(None))
+            .unwrap();
+        assert!(matches!(sandbox.redo_edit(), Err(Error::EditBlocked(p)) if p == pos));
+        assert!(sandbox.edits.can_redo());
+
+        // and the game moving on forgets every edit
+        sandbox.board.remove_entity(pos);
+        sandbox.tick();
+        assert!(!sandbox.edits.can_redo());
+    }
+}
@@ -1,23 +1,31 @@
 use std::{
     fmt::Debug,
-    sync::{Arc, RwLock},
+    sync::{Arc, Mutex, RwLock},
 };
 
-use rand::Rng;
+use rand::{rngs::StdRng, Rng};
 
 use async_trait::async_trait;
 
+use crate::brains::Brains;
+use crate::error::Error;
 use crate::game_board::Board;
-use crate::{entities::Entity, entity_control::EntityManager, Pos, Tile};
+use crate::interaction_matrix::InteractionMatrix;
+use crate::settings::Difficulty;
+use crate::{
+    entities::{Entity, Species},
+    entity_control::EntityManager,
+    Pos,
+};
 
-use log::info;
+use log::{info, warn};
 
 /// Provide some sort of hint to the game controller about any special kind of behavior after processing.
 pub enum PostProcessResult {
     /// If returned, remove the entity from the processing list, with nothing else in its place.
     Delete,
     /// If returned, drop the current entity, and leave this in its place.
-    ReplaceMeWith(Entity),
+    ReplaceMeWith(Box<Entity>),
     /// If returned, try to add these e ntities around us. If we fail to add any, it's okay, we'll just drop them.
     TryToAddEntities(Vec<(Pos, Entity)>),
     /// If returned, try to add these entities around us. If we fail to add any, it's okay, we'll just drop them.
@@ -50,13 +58,22 @@ pub trait Processing {
     fn process(&mut self, board: &mut Board, ctx: ProcessingContext) -> Option<PostProcessResult>;
 
     /// Perform some late processing. Anything performed here should be atomic and thread-safe.
-    async fn late_process(&mut self) -> Option<PostProcessResult>;
+    async fn late_process(&mut self, difficulty: Difficulty) -> Option<PostProcessResult>;
 }
 
+/// A sandbox's own source of randomness, shared with everything that gets processed in it.
+/// Seeding it is enough to make a run repeatable, no matter what else is running in the process.
+pub type SimRng = Arc<Mutex<StdRng>>;
+
 /// A helper data structure passed into processing elements.
 pub struct ProcessingContext {
     pub position: Pos,
     pub entity_context: Arc<RwLock<EntityManager>>,
+    pub rng: SimRng,
+    /// Whatever's deciding for any species the built in AI has been taken off of.
+    pub brains: Brains,
+    /// The species rules the game's played by.
+    pub rules: Arc<InteractionMatrix>,
 }
 
 /// Defines your life status.
@@ -74,13 +91,13 @@ pub trait Lives: Processing {
     fn will_ever_live(&self) -> bool;
 
     /// Run the life loop.
-    fn life(&mut self) {
+    fn life(&mut self, difficulty: Difficulty) {
         if !self.will_ever_live() {
             return;
         }
 
         self.process_health();
-        self.process_hunger();
+        self.process_hunger(difficulty);
         self.process_age();
         self.process_life_misc();
     }
@@ -97,8 +114,8 @@ pub trait Lives: Processing {
     /// Process our health.
     fn process_health(&mut self);
 
-    /// Process our hunger
-    fn process_hunger(&mut self);
+    /// Process our hunger, which drains faster the harder the game is.
+    fn process_hunger(&mut self, difficulty: Difficulty);
 
     /// Handle anything related to us aging.
     fn process_age(&mut self);
@@ -114,6 +131,11 @@ pub trait Lives: Processing {
         true
     }
 
+    /// Whatever's left on our tile once we've died and been cleared away, like a crab's shell. Takes our place instead of a corpse.
+    fn remains(&self) -> Option<Entity> {
+        None
+    }
+
     // Little helper function
     fn is_dead(&self) -> bool {
         matches!(self.get_life_status(), LifeStatus::Dead)
@@ -121,6 +143,7 @@ pub trait Lives: Processing {
 }
 
 /// Little informative struct created by things that can reproduce to help inform the offspring finder
+#[derive(Debug, Clone)]
 pub struct OffspringData {
     /// Minimum allowable offspring, assuming we can fill all the spaces around us (which we may not be able to).
     pub min_offspring: usize,
@@ -161,10 +184,32 @@ where
     /// Should be called when offspring are actually born.
     fn on_offspring_created(&mut self);
 
-    /// Create some children out of ourselves, optionally destroying ourselves in the process.
-    fn create_offspring(&mut self, board: &mut Board, pos: Pos) -> Vec<Pos> {
-        let children_so_far = 0;
-        let mut rng = rand::thread_rng();
+    /// What our children grow up into, which is what they count towards when checking the population cap.
+    fn offspring_species(&self) -> Species;
+
+    /// Whether our children can take root on top of scenery that's small enough to be swallowed up, like shells.
+    fn grows_over_scenery(&self) -> bool {
+        false
+    }
+
+    /// Whether a child of ours could end up on a tile with this on it.
+    fn room_for_child(&self, occupant: Option<&Entity>) -> bool {
+        match occupant {
+            None => true,
+            Some(Entity::NonLiving(n)) => self.grows_over_scenery() && n.can_be_overgrown(),
+            Some(Entity::Living(_)) => false,
+        }
+    }
+
+    /// Pick out the tiles around us our children should go on: as many as we need to have, if there's room for them,
+    /// and maybe a few more besides.
+    fn nursery(
+        &self,
+        board: &Board,
+        pos: Pos,
+        rng: &mut impl Rng,
+        rules: &InteractionMatrix,
+    ) -> Vec<Pos> {
         let offspring_data = self.get_offspring_data();
         if offspring_data.is_none() {
             // error!("Offspring data was none for something that tried to reproduce!");
@@ -172,11 +217,19 @@ where
         }
         let offspring_data = offspring_data.unwrap();
         let mut positions_spread = Vec::new();
-        let all_valid_tiles = board.range(1, false, pos);
+        if let Some(cap) = board.at_population_cap(self.offspring_species(), rules) {
+            info!("No children for {self:?}, there are already {cap} of its kind, which is as many as there can be");
+            return positions_spread;
+        }
         let mut necessary_children = offspring_data.min_offspring;
-        let empty_tiles = all_valid_tiles
-            .into_iter()
-            .filter(|p| !board.get_tile_from_pos(*p).is_occupied())
+        // polluted water is less likely to take, the worse it is
+        let empty_tiles = board
+            .neighbors(pos)
+            .filter(|p| self.room_for_child(board.entity_at(*p)))
+            .filter(|p| {
+                let pollution = board.get_tile_from_pos(*p).get_pollution();
+                pollution == 0.0 || !rng.gen_bool(pollution)
+            })
             .collect::<Vec<Pos>>();
         if empty_tiles.is_empty() {
             info!("There were no valid tiles for reproduction around {self:?}");
@@ -194,12 +247,6 @@ where
             if positions_spread.contains(&selected) {
                 continue;
             }
-            let new_tile = board.get_tile_mut_from_pos(selected);
-            if new_tile.is_occupied() {
-                continue;
-            }
-            // have that child
-            self.have_child(new_tile, pos, children_so_far);
             necessary_children -= 1;
             positions_spread.push(selected);
         }
@@ -208,7 +255,7 @@ where
 
         let still_empty_pos = empty_tiles
             .into_iter()
-            .filter(|p| !board.get_tile_from_pos(*p).is_occupied())
+            .filter(|p| !positions_spread.contains(p))
             .collect::<Vec<Pos>>();
 
         for pos in still_empty_pos {
@@ -216,20 +263,49 @@ where
                 break;
             }
             if rng.gen_bool(offspring_data.percent_chance_per_tile) {
-                let new_tile = board.get_tile_mut_from_pos(pos);
-                self.have_child(new_tile, pos, children_so_far);
                 positions_spread.push(pos);
             }
         }
 
+        positions_spread
+    }
+
+    /// Create some children out of ourselves, optionally destroying ourselves in the process. Returns where they all
+    /// ended up.
+    fn create_offspring(
+        &mut self,
+        board: &mut Board,
+        pos: Pos,
+        rng: &mut impl Rng,
+        rules: &InteractionMatrix,
+    ) -> Vec<Pos> {
+        let positions_spread = self.nursery(board, pos, rng, rules);
+        let mut born = vec![];
+        for (children_so_far, child_pos) in positions_spread.iter().enumerate() {
+            // anything still in the way gets swallowed up
+            board.remove_entity(*child_pos);
+            // have that child
+            match self.have_child(board, *child_pos, children_so_far, rng) {
+                Ok(placed) => born.extend(placed),
+                Err(e) => warn!("Nowhere on the board for a child to go: {e}"),
+            }
+        }
+
         self.on_offspring_created();
 
-        positions_spread
+        born
     }
 
     /// Get data on how new offspring should be created.
     fn get_offspring_data(&self) -> Option<OffspringData>;
 
-    /// Create a child on a given tile.
-    fn have_child(&mut self, tile: &mut Tile, pos: Pos, children_so_far: usize);
+    /// Create a child on a given tile, or the closest empty one to it if something that's being worked on is still
+    /// holding onto it. Returns where the child ended up, if we had one at all. Only fails if the board's full.
+    fn have_child(
+        &mut self,
+        board: &mut Board,
+        pos: Pos,
+        children_so_far: usize,
+        rng: &mut impl Rng,
+    ) -> Result<Option<Pos>, Error>;
 }
@@ -1,42 +1,114 @@
-use std::cmp::{max, min};
+use std::cmp::{min, Reverse};
 
 use async_trait::async_trait;
-use log::info;
-use rand::rngs::ThreadRng;
+use log::{debug, info};
 use rand::Rng;
 
 use crate::ai_controller::{
     AIAction, AIConcreteBehaviors, AIControlled, EatAction, IdleAction, MateAction,
 };
+use crate::brains::{Choice, Observation};
 use crate::element_traits::{
     LifeStatus, Lives, Mobile, OffspringData, PostProcessResult, Processing, ProcessingContext,
     Reproducing,
 };
 use crate::entity_control::{EntityID, TrackedEntity};
-use crate::game_board::Board;
-use crate::interactions::{EatResult, Eaten, EatsCreatures, Mates};
+use crate::error::Error;
+use crate::game_board::{Board, Edge};
+use crate::interaction_matrix::{Appetite, BehaviorWeights, InteractionMatrix, WanderStyle};
+use crate::interactions::{
+    EatResult, Eaten, EatsCreatures, FightOutcome, Fights, Mates, FLEE_THRESHOLD, FLEE_TICKS,
+    MAX_STAMINA, STAMINA_RECOVERY,
+};
+use crate::scent::{BLOOD_LURE, WOUNDED_THRESHOLD};
+use crate::settings::Difficulty;
+use crate::weather::STORM_GUST;
 use crate::Pos;
 
+pub use super::components::HungerLevel;
+use super::components::{
+    Age, Combat, Health, Hunger, Mobility, Personality, Progress, Reproduction,
+};
+use super::custom::{AnimalStats, CustomSpecies};
+use super::nonliving::{ConcreteDecorations, SHELL_DEFENSE_BONUS};
 use super::NonAbstractTaxonomy;
 use super::{
-    plants::Plants, Entity, Living, PTUIDisplay, Sex, MAXIMUM_ACTIONS_TO_CONSIDER,
-    MAX_PREGNANCY_LEVEL,
+    plants::{egg, Plants},
+    Entity, Living, PTUIDisplay, Sex, Species, MAXIMUM_ACTIONS_TO_CONSIDER, MAX_PREGNANCY_LEVEL,
 };
 
+/// The odds of a shark picking a fight with a rival next to it, each tick.
+const TERRITORY_FIGHT_CHANCE: f64 = 0.25;
+
+/// How far something on the run keeps an eye out for anything that might eat it.
+pub const LOOKOUT_RADIUS: usize = 5;
+
+/// How close to food anything that might eat us can be before an average animal thinks twice about going for it. The
+/// boldest don't mind at all, and the most timid want them twice as far off.
+pub const WARINESS: f64 = 1.0;
+
+/// How much less a meal is worth when something that might eat us is closer to it than we'd like.
+pub const RISKY_MEAL: f64 = 0.5;
+
+/// How close a predator has to get to spot a fish hiding in a kelp forest.
+pub const FOREST_SPOTTING_DISTANCE: usize = 1;
+
+/// How much health fish in a kelp forest get back every tick, on top of what they'd heal anyway.
+pub const FOREST_HEALING: i64 = 5;
+
+/// How much slower an animal carrying a child gets, in tiles per tick. Nothing slows all the way down to a standstill.
+pub const PREGNANT_SLOWDOWN: usize = 1;
+
+/// How much more hunger an animal carrying a child burns through every tick, before difficulty.
+pub const PREGNANT_HUNGER_DRAIN: i64 = 1;
+
+/// How many of its own kind an animal can have right around it before it starts getting sick.
+pub const CROWDING_LIMIT: usize = 4;
+
+/// How much hp an animal loses to disease every tick, for every neighbour of its own kind past CROWDING_LIMIT.
+pub const DISEASE_DAMAGE: i64 = 3;
+
+/// How many ticks an animal spends going after something without getting anywhere before it gives up for a bit.
+/// Longer than COURTSHIP_TICKS, though courting doesn't count anyway.
+pub const STUCK_TICKS: usize = 5;
+
+/// How many ticks an animal that got stuck wanders about for before trying again, hopefully from somewhere better.
+pub const WANDER_TICKS: usize = 3;
+
+/// Give an animal made without the game's own randomness its species' temperament and nothing more, so anything put
+/// down by hand, like in a scenario, plays out the same every time.
+pub(crate) fn average(mut entity: Entity) -> Entity {
+    if let Some(animal) = entity.animal_mut() {
+        animal.body_mut().personality = Personality::default();
+    }
+    entity
+}
+
 pub enum ConcreteAnimals {
     Fish,
     Crab,
     Shark,
 }
 
-impl NonAbstractTaxonomy for ConcreteAnimals {
-    fn create_new(&self, entity_id: Option<EntityID>) -> Entity {
-        let new_animal = match self {
+impl ConcreteAnimals {
+    /// Same as create_new, but using a given source of randomness to pick the animal's sex.
+    pub fn create_new_with_rng(&self, entity_id: Option<EntityID>, rng: &mut impl Rng) -> Entity {
+        let sex = if rng.gen_bool(0.5) {
+            Sex::Male
+        } else {
+            Sex::Female
+        };
+        let mut new_animal = match self {
             Self::Fish => {
-                let new_animal = AnimalType::new("fish", 100, 300, 5, 100, entity_id, 1, 1, None);
+                // not much of a fight in them, but they're slippery
+                let combat = Combat::new(25, 5, false).with_evasion(0.6);
+                let new_animal =
+                    AnimalType::new("fish", 100, 300, 5, 100, entity_id, 1, 1, sex, combat);
                 Animals::Fish(new_animal)
             }
             Self::Crab => {
+                // crabs won't go down without a fight, and their shells take the edge off
+                let combat = Combat::new(50, 30, true).with_evasion(0.1);
                 let new_animal = AnimalType::new(
                     "crab",
                     150,
@@ -46,19 +118,32 @@ impl NonAbstractTaxonomy for ConcreteAnimals {
                     entity_id,
                     3,
                     1,
-                    Some(Sex::Neutral),
+                    Sex::Neutral,
+                    combat,
                 );
                 Animals::Crab(new_animal)
             }
             Self::Shark => {
-                // live fast die young
-                let new_animal = AnimalType::new("shark", 200, 125, 10, 50, entity_id, 3, 3, None);
+                // live fast die young, and lunge at anything that strays too close
+                let combat = Combat::new(100, 20, false)
+                    .with_reach(2)
+                    .with_evasion(0.2)
+                    .with_smell(8);
+                let new_animal =
+                    AnimalType::new("shark", 200, 125, 10, 50, entity_id, 3, 3, sex, combat);
                 Animals::Shark(new_animal)
             }
         };
+        new_animal.roll_personality(rng);
 
         Entity::Living(Living::Animals(new_animal))
     }
+}
+
+impl NonAbstractTaxonomy for ConcreteAnimals {
+    fn create_new(&self, entity_id: Option<EntityID>) -> Entity {
+        average(self.create_new_with_rng(entity_id, &mut rand::thread_rng()))
+    }
 
     /// Get whether this specific type matches the passed-in entity.
     fn same_kind(&self, entity: &Entity) -> bool {
@@ -69,6 +154,7 @@ impl NonAbstractTaxonomy for ConcreteAnimals {
                     Animals::Crab(_) => matches!(self, ConcreteAnimals::Crab),
                     Animals::Fish(_) => matches!(self, ConcreteAnimals::Fish),
                     Animals::Shark(_) => matches!(self, ConcreteAnimals::Shark),
+                    Animals::Custom(..) => false,
                 },
                 _ => false,
             },
@@ -81,57 +167,404 @@ pub enum Animals {
     Fish(AnimalType),
     Crab(AnimalType),
     Shark(AnimalType),
+    /// An animal added by a plugin.
+    Custom(CustomSpecies, AnimalType),
 }
 
 impl Animals {
-    pub fn should_consider_eating(&self) -> bool {
+    pub fn species(&self) -> Species {
+        match self {
+            Self::Fish(_) => Species::Fish,
+            Self::Crab(_) => Species::Crab,
+            Self::Shark(_) => Species::Shark,
+            Self::Custom(c, _) => Species::Custom(*c),
+        }
+    }
+
+    /// Get at the components every animal is made of, whatever species it is.
+    pub fn body(&self) -> &AnimalType {
+        match self {
+            Self::Fish(a) | Self::Crab(a) | Self::Shark(a) | Self::Custom(_, a) => a,
+        }
+    }
+
+    pub fn body_mut(&mut self) -> &mut AnimalType {
         match self {
-            Self::Shark(a) | Self::Crab(a) | Self::Fish(a) => {
-                matches!(a.hunger, HungerLevel::Hungry | HungerLevel::Starving)
+            Self::Fish(a) | Self::Crab(a) | Self::Shark(a) | Self::Custom(_, a) => a,
+        }
+    }
+
+    pub fn should_consider_eating(&self) -> bool {
+        self.body().hunger.is_hungry()
+    }
+
+    /// Get which generation this animal belongs to. Animals placed on the board at the start are generation 1.
+    pub fn get_generation(&self) -> usize {
+        self.body().reproduction.generation
+    }
+
+    pub fn is_pregnant(&self) -> bool {
+        self.body().reproduction.pregnant
+    }
+
+    /// How much of our health we've got left, from 0 to 1.
+    pub fn hp_fraction(&self) -> f64 {
+        let health = &self.body().health;
+        health.hp as f64 / health.hp_max.max(1) as f64
+    }
+
+    /// How many tiles away we can get at something to eat it.
+    pub fn reach(&self) -> usize {
+        self.body().combat.reach
+    }
+
+    /// Whether we could eat something at a position from another: close enough, with nothing in the way of a lunge.
+    /// Chasing and eating both go by this, so wherever a chase leaves us is somewhere we can eat from.
+    pub fn within_reach(&self, from: Pos, pos: Pos, board: &Board) -> bool {
+        from != pos && board.distance(from, pos) <= self.reach() && board.line_of_sight(from, pos)
+    }
+
+    /// Everywhere we could eat something from a position.
+    pub fn in_reach<'a>(&'a self, from: Pos, board: &'a Board) -> impl Iterator<Item = Pos> + 'a {
+        board
+            .positions_within(self.reach(), from)
+            .filter(move |p| self.within_reach(from, *p, board))
+    }
+
+    /// What our species puts first when deciding what to do next.
+    pub fn behavior_weights(&self, rules: &InteractionMatrix) -> BehaviorWeights {
+        let mut weights = rules.weights(self.species());
+        let personality = self.personality(rules);
+        weights.idle *= Personality::sway(personality.laziness);
+        weights.mating *= Personality::sway(personality.sociability);
+        weights
+    }
+
+    /// What we're like: our species' temperament, leaned one way or another by our own quirks.
+    pub fn personality(&self, rules: &InteractionMatrix) -> Personality {
+        rules
+            .temperament(self.species())
+            .leaning(self.body().personality)
+    }
+
+    /// Be born leaning one way or another from our species' temperament.
+    pub(crate) fn roll_personality(&mut self, rng: &mut impl Rng) {
+        self.body_mut().personality = Personality::around(Personality::default(), rng);
+    }
+
+    /// How close to a meal anything that might eat us can be before we think twice about going for it.
+    pub fn wariness(&self, rules: &InteractionMatrix) -> usize {
+        (WARINESS * (1.0 - self.personality(rules).boldness)).round() as usize
+    }
+
+    /// How our species gets about while we're idle.
+    pub fn wander_style(&self, rules: &InteractionMatrix) -> WanderStyle {
+        rules.wander_style(self.species())
+    }
+
+    /// Whether whatever's at a position is something a behavior could actually be carried out on. Idling is
+    /// always fine.
+    fn would_go_after(
+        &self,
+        behavior: &AIConcreteBehaviors,
+        pos: Pos,
+        ctx: &ProcessingContext,
+        board: &Board,
+    ) -> bool {
+        let target = board.entity_at(pos);
+        match behavior {
+            AIConcreteBehaviors::Idle(_) => true,
+            // following a trail of blood, with nothing at the end of it that we can see yet
+            AIConcreteBehaviors::Eating(e) if e.target().is_none() => {
+                board.get_tile_from_pos(pos).get_scent() > 0.0
+            }
+            AIConcreteBehaviors::Eating(_) => {
+                target.is_some_and(|t| EatAction::is_valid_target(self, t, ctx, board))
+            }
+            AIConcreteBehaviors::Mating(_) => {
+                target.is_some_and(|t| MateAction::is_valid_target(self, t, ctx, board))
             }
         }
     }
 
+    /// The child we're carrying, one generation past us, that knows who its parents were.
+    fn conceive(&self, rng: &mut impl Rng) -> Animals {
+        let mut new_child = match self {
+            Self::Crab(_) => ConcreteAnimals::Crab.create_new_with_rng(None, rng),
+            Self::Shark(_) => ConcreteAnimals::Shark.create_new_with_rng(None, rng),
+            Self::Fish(_) => ConcreteAnimals::Fish.create_new_with_rng(None, rng),
+            Self::Custom(c, _) => c.create_new_with_rng(None, rng),
+        };
+        // children are always one generation past their parent, and remember who had them
+        let parents = [self.get_id(), self.body().reproduction.mate]
+            .into_iter()
+            .flatten()
+            .collect();
+        if let Some(r) = new_child.reproduction_mut() {
+            r.generation = self.get_generation() + 1;
+            r.parents = parents;
+        }
+        match new_child {
+            Entity::Living(Living::Animals(child)) => child,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Lay a clutch of eggs on the tiles around us, to be put down once we're done processing.
+    pub fn lay_eggs(
+        &mut self,
+        board: &Board,
+        pos: Pos,
+        rng: &mut impl Rng,
+        rules: &InteractionMatrix,
+    ) -> Vec<(Pos, Entity)> {
+        let eggs = self
+            .nursery(board, pos, rng, rules)
+            .into_iter()
+            .map(|p| (p, egg(self.conceive(rng), None)))
+            .collect();
+        self.on_offspring_created();
+        eggs
+    }
+
     pub fn starving(&self) -> bool {
-        match self {
-            Self::Shark(a) | Self::Crab(a) | Self::Fish(a) => {
-                matches!(a.hunger, HungerLevel::Starving)
+        matches!(self.body().hunger.state, HungerLevel::Starving)
+    }
+
+    /// How many tiles away we can smell blood from.
+    pub fn smell(&self) -> usize {
+        self.body().combat.smell
+    }
+
+    /// Whether we came out of a fight badly hurt, and are leaving a trail of blood behind us.
+    pub fn wounded(&self) -> bool {
+        self.body().combat.wounded
+    }
+
+    /// Whether we get the run of kelp forests: harder to spot, and quicker to heal. Only fish do.
+    pub fn shelters_in_forest(&self) -> bool {
+        matches!(self, Self::Fish(_))
+    }
+
+    /// Whether we're still running from a fight.
+    pub fn fleeing(&self) -> bool {
+        self.body().combat.fleeing > 0
+    }
+
+    /// Go after another animal for a meal. Anything that fights back has to be beaten first, and only then does it get eaten.
+    /// Returns false if it got out of the way before we could get hold of it.
+    pub fn hunt(
+        &mut self,
+        target: &mut Animals,
+        rng: &mut impl Rng,
+        rules: &InteractionMatrix,
+    ) -> bool {
+        if target.dodge(self, rng) {
+            info!("{target:?} got out of the way of {self:?}");
+            return false;
+        }
+        if !target.body().combat.fights_back {
+            self.eat(target, rules);
+            return true;
+        }
+        let outcome = self.fight(target, rng);
+        info!("{self:?} went after {target:?} and it fought back: {outcome:?}");
+        if outcome == FightOutcome::AttackerWon {
+            self.restore_hunger(target, rules);
+        }
+        true
+    }
+
+    /// Sharks won't share their waters with another shark they can't mate with, and will pick a fight over it.
+    /// Whether it fights off any of its own kind that come too close.
+    pub fn territorial(&self) -> bool {
+        matches!(self, Self::Shark(_))
+    }
+
+    /// Whether it picks up empty shells to wear.
+    pub fn wears_shells(&self) -> bool {
+        matches!(self, Self::Crab(_))
+    }
+
+    fn defend_territory(&mut self, board: &mut Board, ctx: &ProcessingContext) {
+        if !self.territorial() || self.fleeing() {
+            return;
+        }
+        let mut rng = ctx.rng.lock().unwrap();
+        for pos in board.neighbors(ctx.position) {
+            let Some(rival) = board.entity_at_mut(pos) else {
+                continue;
+            };
+            let Some(rival @ Animals::Shark(_)) = rival.animal_mut() else {
+                continue;
+            };
+            let same_sex = rival.body().reproduction.sex == self.body().reproduction.sex;
+            if rival.is_dead() || rival.fleeing() || !same_sex {
+                continue;
             }
+            if rng.gen_bool(TERRITORY_FIGHT_CHANCE) {
+                let outcome = self.fight(rival, &mut *rng);
+                info!(
+                    "Sharks at {:?} and {pos:?} fought over territory: {outcome:?}",
+                    ctx.position
+                );
+                return;
+            }
+        }
+    }
+
+    /// Crabs without a shell will take the first one they find lying next to them, and wear it from then on.
+    fn pick_up_shell(&mut self, board: &mut Board, position: Pos) {
+        if !self.wears_shells() || self.body().combat.shell > 0 {
+            return;
+        }
+        let shell = board.neighbors(position).find(
+            |pos| matches!(board.entity_at(*pos), Some(Entity::NonLiving(n)) if n.can_be_carried()),
+        );
+        if let Some(pos) = shell {
+            board.remove_entity(pos);
+            self.body_mut().combat.shell = SHELL_DEFENSE_BONUS;
+            info!("{self:?} picked up a shell at {pos:?}");
+        }
+    }
+
+    /// Where everything close enough to worry about that might eat us is.
+    pub fn threats(&self, position: Pos, board: &Board, rules: &InteractionMatrix) -> Vec<Pos> {
+        board
+            .positions_within(LOOKOUT_RADIUS, position)
+            .filter(|pos| match board.entity_at(*pos) {
+                Some(Entity::Living(Living::Animals(a))) => {
+                    a != self
+                        && !a.is_dead()
+                        && rules.appetite(a.species(), self.species()) != Appetite::Never
+                }
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// Somewhere we can get to this tick that's out of sight of anything around that might eat us, or failing that,
+    /// as far from them as we can get. None if there's nothing to hide from, or if we're best off staying put.
+    fn hiding_spot(&self, position: Pos, board: &Board, rules: &InteractionMatrix) -> Option<Pos> {
+        let threats = self.threats(position, board, rules);
+        if threats.is_empty() {
+            return None;
+        }
+        let (max_x, max_y) = self.max_speeds();
+        let reachable = board
+            .positions_within(max_x.max(max_y), position)
+            .filter(|p| p.x.abs_diff(position.x) <= max_x && p.y.abs_diff(position.y) <= max_y)
+            .filter(|p| *p == position || !board.get_tile_from_pos(*p).is_occupied());
+        let seen_by = |p: Pos| {
+            threats
+                .iter()
+                .filter(|t| board.line_of_sight(**t, p))
+                .count()
+        };
+        let closest = |p: Pos| {
+            threats
+                .iter()
+                .map(|t| board.distance(p, *t))
+                .min()
+                .unwrap_or(0)
+        };
+        let best = reachable.min_by_key(|p| (seen_by(*p), Reverse(closest(*p))))?;
+        (best != position).then_some(best)
+    }
+
+    /// Where to go next on a migration: whichever empty tile in reach gets us closest to the edge we're headed for,
+    /// steering clear of hazards. None if nothing in reach gets us any closer.
+    fn migration_step(&self, position: Pos, edge: Edge, board: &Board) -> Option<Pos> {
+        let (max_x, max_y) = self.max_speeds();
+        let best = board
+            .positions_within(max_x.max(max_y), position)
+            .filter(|p| p.x.abs_diff(position.x) <= max_x && p.y.abs_diff(position.y) <= max_y)
+            .filter(|p| !board.get_tile_from_pos(*p).is_occupied() && !board.near_hazard(*p))
+            .min_by_key(|p| board.distance_to_edge(*p, edge))?;
+        (board.distance_to_edge(best, edge) < board.distance_to_edge(position, edge))
+            .then_some(best)
+    }
+
+    /// The last tile we moved off of, for carrying on the same way.
+    pub fn came_from(&self) -> Option<Pos> {
+        self.body().mobility.progress.came_from
+    }
+
+    /// Catch up on where we've got to since last tick. Anything that's spent STUCK_TICKS going after the same thing
+    /// without getting anywhere, whether by staying put or bouncing back and forth between two tiles, wanders off
+    /// for WANDER_TICKS and then tries again from wherever it ends up.
+    fn track_progress(&mut self, position: Pos, same_behavior: bool) {
+        // running away and migrating have their own ideas about where to go
+        let travelling = self.get_current_behavior().wants_to_travel()
+            && !self.fleeing()
+            && self.body().mobility.heading.is_none();
+        let progress = &mut self.body_mut().mobility.progress;
+        let last = progress.last_seen.replace(position);
+        // ending up back where we just moved off of gets us about as far as staying put
+        let getting_nowhere = last == Some(position) || progress.came_from == Some(position);
+        if last.is_some_and(|last| last != position) {
+            progress.came_from = last;
+        }
+        if progress.wander_ticks > 0 {
+            progress.wander_ticks -= 1;
+            return;
+        }
+        if !(travelling && same_behavior && getting_nowhere) {
+            progress.stuck_ticks = 0;
+            return;
+        }
+        progress.stuck_ticks += 1;
+        if progress.stuck_ticks >= STUCK_TICKS {
+            progress.stuck_ticks = 0;
+            progress.wander_ticks = WANDER_TICKS;
+            // whichever way we were going didn't work out
+            progress.came_from = None;
+            info!("{:?} got stuck at {position:?}, and is wandering off", self);
         }
     }
 
     /// Get a position that's a random walk from our current step.
     pub fn random_walk<T: Rng>(&self, start: Pos, rng: &mut T, board: &Board) -> Option<Pos> {
-        let mut new_pos = start;
+        let a = self.body();
+        // storms throw everything about, further than it meant to go
+        let gust = if board.get_weather().is_stormy() {
+            rng.gen_range(0..=STORM_GUST)
+        } else {
+            0
+        };
+        let (speed_x, speed_y) = a.get_max_movespeed();
+        let (max_x, max_y) = ((speed_x + gust) as i64, (speed_y + gust) as i64);
+        // expecting mothers would rather wander somewhere with a bit of cover, if they can find it
+        let wants_cover = a.reproduction.pregnant;
+        let mut uncovered = None;
         for _ in 0..5 {
-            match self {
-                Animals::Fish(a) | Animals::Crab(a) | Animals::Shark(a) => {
-                    let mut new_x_offset =
-                        rng.gen_range(-(a.max_x_movespeed as i64)..=(a.max_x_movespeed as i64));
-                    let mut new_y_offset =
-                        rng.gen_range(-(a.max_y_movespeed as i64)..=(a.max_y_movespeed as i64));
-
-                    // println!("moving to {new_x_offset}, {new_y_offset}");
-                    // don't underflow bestie
-                    if (new_pos.x as i64) + new_x_offset < 0 {
-                        new_x_offset = 0;
-                    }
-                    if (new_pos.y as i64) + new_y_offset < 0 {
-                        new_y_offset = 0;
-                    }
-                    new_pos.x = (new_pos.x as i64 + new_x_offset) as usize;
-                    new_pos.y = (new_pos.y as i64 + new_y_offset) as usize;
-                    info!("{a:?} moving to {new_pos:?}");
-                    if board.is_valid_pos(new_pos) {
-                        let target_tile = board.get_tile_from_pos(new_pos);
-                        if !target_tile.is_occupied() {
-                            return Some(new_pos);
-                        }
+            // every try starts over from where we are, or a few misses in a row would carry us further than we can move
+            let mut new_pos = start;
+            let mut new_x_offset = rng.gen_range(-max_x..=max_x);
+            let mut new_y_offset = rng.gen_range(-max_y..=max_y);
+
+            // println!("moving to {new_x_offset}, {new_y_offset}");
+            // don't underflow bestie
+            if (new_pos.x as i64) + new_x_offset < 0 {
+                new_x_offset = 0;
+            }
+            if (new_pos.y as i64) + new_y_offset < 0 {
+                new_y_offset = 0;
+            }
+            new_pos.x = (new_pos.x as i64 + new_x_offset) as usize;
+            new_pos.y = (new_pos.y as i64 + new_y_offset) as usize;
+            info!("{a:?} moving to {new_pos:?}");
+            if board.is_valid_pos(new_pos) {
+                let target_tile = board.get_tile_from_pos(new_pos);
+                if !target_tile.is_occupied() {
+                    if !wants_cover || board.sheltered(new_pos) {
+                        return Some(new_pos);
                     }
+                    uncovered.get_or_insert(new_pos);
                 }
             }
         }
-        None
+        uncovered
     }
 
     pub fn get_all_possible_actions(
@@ -140,118 +573,203 @@ impl Animals {
         ctx: &ProcessingContext,
     ) -> Vec<(Pos, AIConcreteBehaviors)> {
         let our_position = ctx.position;
+        // anything on the run just wants to get away
+        if self.fleeing() {
+            return vec![(our_position, AIConcreteBehaviors::default())];
+        }
 
         let mut concrete_behaviors = vec![];
 
         concrete_behaviors.push((
             our_position,
-            AIConcreteBehaviors::Idle(IdleAction::new(true, true)),
+            AIConcreteBehaviors::Idle(IdleAction::new(true)),
         ));
+        // whether either is worth doing doesn't depend on where we'd be doing it, and it means scanning the whole board
+        let eat_behavior = AIConcreteBehaviors::Eating(EatAction::new());
+        let should_eat = self.should_consider_eating() && eat_behavior.is_valid(self, ctx, board);
+        let mate_behavior = AIConcreteBehaviors::Mating(MateAction::new());
+        let should_mate = self.can_mate() && mate_behavior.is_valid(self, ctx, board);
         // that's a mouthful
         // run over all our active entities and see if there are any actions that we might want to perform on them
-        for (_, pos) in ctx
-            .entity_context
-            .read()
-            .unwrap()
-            .get_active_entries()
-            .iter()
-        {
+        for pos in &board.tracked_near(our_position) {
             // don't go looking forever
             if concrete_behaviors.len() > MAXIMUM_ACTIONS_TO_CONSIDER {
                 break;
             }
-            let tile = board.get_tile_from_pos(*pos);
-
-            // ignore dead stuff
-            if !tile.is_occupied() || matches!(tile.get_entity(), Some(Entity::NonLiving(_))) {
+            // ignore dead stuff, and ourselves
+            if *pos == our_position
+                || matches!(board.entity_at(*pos), None | Some(Entity::NonLiving(_)))
+            {
                 continue;
             }
 
-            if self.should_consider_eating() {
-                let eat_behavior = AIConcreteBehaviors::Eating(EatAction::new(self.starving()));
-                if eat_behavior.is_valid(self, ctx, board) {
-                    // println!("Gonna eat");
-                    concrete_behaviors.push((*pos, eat_behavior))
-                }
+            if should_eat {
+                // println!("Gonna eat");
+                let target = board.entity_at(*pos).and_then(|e| e.get_id());
+                concrete_behaviors.push((
+                    *pos,
+                    AIConcreteBehaviors::Eating(EatAction::new().with_target(target)),
+                ))
             }
 
-            if self.can_mate() {
-                let mate_behavior = AIConcreteBehaviors::Mating(MateAction::new());
-                if mate_behavior.is_valid(self, ctx, board) {
-                    concrete_behaviors.push((*pos, mate_behavior))
-                }
+            if should_mate {
+                concrete_behaviors.push((*pos, mate_behavior.clone()))
             }
         }
+        // whatever we can smell might be worth going after too, even if we can't see it yet
+        if let Some(trail) =
+            EatAction::scent_trail(self, our_position, board).filter(|_| should_eat)
+        {
+            concrete_behaviors.push((trail, AIConcreteBehaviors::Eating(EatAction::new())));
+        }
 
         concrete_behaviors
     }
 
+    /// How much less worth going after a meal is for anything that could really hurt us being too close to it for our
+    /// liking. Our own kind doesn't count.
+    fn risk(
+        &self,
+        behavior: &AIConcreteBehaviors,
+        pos: Pos,
+        threats: &[Pos],
+        board: &Board,
+        rules: &InteractionMatrix,
+    ) -> f64 {
+        let wariness = self.wariness(rules);
+        let risky = matches!(behavior, AIConcreteBehaviors::Eating(_))
+            && threats.iter().any(|t| {
+                board.distance(*t, pos) <= wariness
+                    && board
+                        .entity_at(*t)
+                        .and_then(Entity::animal)
+                        .is_some_and(|a| {
+                            a.species() != self.species() && a.attack_power() > self.defense()
+                        })
+            });
+        if risky {
+            RISKY_MEAL
+        } else {
+            1.0
+        }
+    }
+
+    /// How much more worth going after a meal is for the blood in the water around it, if we can smell it from here.
+    fn blood_lure(
+        &self,
+        behavior: &AIConcreteBehaviors,
+        pos: Pos,
+        ctx: &ProcessingContext,
+        board: &Board,
+    ) -> f64 {
+        if !matches!(behavior, AIConcreteBehaviors::Eating(_))
+            || board.distance(ctx.position, pos) > self.smell()
+        {
+            return 1.0;
+        }
+        1.0 + BLOOD_LURE * board.get_tile_from_pos(pos).get_scent()
+    }
+
+    /// Whatever our species' brain would have us do instead of what we'd pick for ourselves, if it's been handed one
+    /// and it has an answer. It only gets to pick from what we'd actually go after.
+    fn ask_brain(
+        &self,
+        all_behaviors: &[(Pos, AIConcreteBehaviors)],
+        ctx: &ProcessingContext,
+        board: &Board,
+    ) -> Option<AIConcreteBehaviors> {
+        let species = self.species();
+        if !ctx.brains.controls(species) {
+            return None;
+        }
+        let options: Vec<&(Pos, AIConcreteBehaviors)> = all_behaviors
+            .iter()
+            .filter(|(pos, behavior)| self.would_go_after(behavior, *pos, ctx, board))
+            .collect();
+        let observation = Observation {
+            id: self.get_id().map(|id| id.get_id_val()),
+            species,
+            position: ctx.position,
+            hp: self.get_health(),
+            hunger: self.body().hunger.level,
+            stamina: self.body().combat.stamina,
+            surroundings: board
+                .positions_within(LOOKOUT_RADIUS, ctx.position)
+                .filter(|pos| *pos != ctx.position)
+                .filter_map(|pos| board.entity_at(pos).map(|e| (pos, Species::of(e))))
+                .collect(),
+            choices: options
+                .iter()
+                .map(|(pos, behavior)| Choice {
+                    action: behavior.name(),
+                    target: *pos,
+                })
+                .collect(),
+        };
+        let chosen = ctx.brains.choose(&observation)?;
+        Some(options[chosen].1.clone())
+    }
+
     /// Get the best possible action for us at this moment.
     fn get_best_possible_behavior(
         &self,
         all_behaviors: Vec<(Pos, AIConcreteBehaviors)>,
         ctx: &ProcessingContext,
+        board: &Board,
     ) -> Option<(Pos, AIConcreteBehaviors)> {
-        // first, filter out the highest priority event
-        if all_behaviors.is_empty() {
-            return None;
-        }
-        let highest_priority = all_behaviors
+        // how badly we want to do something, less however far we'd have to go to do it.
+        // if a few score the same, whichever's nearest the top left wins out, since what we're after now decides which
+        // way we go, and the order we came across them in is anyone's guess
+        let weights = self.behavior_weights(&ctx.rules);
+        let threats = if all_behaviors
             .iter()
-            .map(|(_, b)| b.priority())
-            .max()
-            .unwrap();
-        let highest_priority_elements = all_behaviors
-            .iter()
-            .filter(|(_, b)| b.priority() == highest_priority)
-            .collect::<Vec<&(Pos, AIConcreteBehaviors)>>();
-
-        if highest_priority_elements.len() == 1 {
-            return Some(highest_priority_elements[0].to_owned());
-        }
-        // we have a few high-priority tasks
-        // let's just pick the first closest one we find
-        // yes this will have a preferential orrder, but it'd be a coin flip otherwise
-        if let Some(res) = highest_priority_elements
-            .into_iter()
-            .min_by_key(|(p1, _)| ctx.position.dist_to(p1))
+            .any(|(_, b)| matches!(b, AIConcreteBehaviors::Eating(_)))
         {
-            let actual_result = res.to_owned(); // TODO we can do better than cloning heres
-            Some(actual_result)
+            self.threats(ctx.position, board, &ctx.rules)
         } else {
-            None
+            vec![]
+        };
+        let mut best: Option<(f64, Pos, AIConcreteBehaviors)> = None;
+        for (pos, behavior) in all_behaviors {
+            let score = weights.score(
+                behavior.urgency(self, &weights)
+                    * self.blood_lure(&behavior, pos, ctx, board)
+                    * self.risk(&behavior, pos, &threats, board, &ctx.rules),
+                board.distance(ctx.position, pos),
+            );
+            // checking whether something's really there for the taking is the slow part, so it's only done for
+            // whatever would beat the best we've found so far
+            let beaten = |(best, best_pos, _): &(f64, Pos, AIConcreteBehaviors)| {
+                *best > score || (*best == score && (best_pos.y, best_pos.x) <= (pos.y, pos.x))
+            };
+            if best.as_ref().is_some_and(beaten) || !self.would_go_after(&behavior, pos, ctx, board)
+            {
+                continue;
+            }
+            best = Some((score, pos, behavior));
         }
+        best.map(|(_, pos, behavior)| (pos, behavior))
     }
 }
 
 impl PTUIDisplay for Animals {
     fn get_display_char(&self) -> char {
-        match &self {
-            Self::Fish(_) => '🐠',
-            Self::Shark(_) => '🐬',
-            Self::Crab(_) => '🐚',
-        }
+        self.species().display_spec().emoji
     }
 }
 
 #[async_trait]
 impl Processing for Animals {
     fn will_process(&self) -> bool {
-        match self {
-            Self::Fish(_) | Self::Crab(_) | Self::Shark(_) => true,
-        }
+        true
     }
 
     fn will_process_late(&self) -> bool {
-        match self {
-            Self::Fish(_) | Self::Crab(_) | Self::Shark(_) => true,
-        }
+        true
     }
 
     fn will_ever_process(&self) -> bool {
-        match self {
-            Self::Fish(_) | Self::Crab(_) | Self::Shark(_) => self.will_ever_live(),
-        }
+        self.will_ever_live()
     }
 
     fn process(&mut self, board: &mut Board, ctx: ProcessingContext) -> Option<PostProcessResult> {
@@ -259,30 +777,34 @@ impl Processing for Animals {
             return None; // cleanup after the tick
         }
         if self.ready_to_reproduce() {
-            let new_important_positions = self.create_offspring(board, ctx.position);
-            println!(
-                "{:?} has given birth to {} new creatures!",
-                &self,
-                new_important_positions.len()
+            let eggs = self.lay_eggs(
+                board,
+                ctx.position,
+                &mut *ctx.rng.lock().unwrap(),
+                &ctx.rules,
             );
-            // new_important_positions.push(position);  // make sure our current position stays important
+            info!("{:?} has laid {} eggs!", &self, eggs.len());
+            return Some(PostProcessResult::TryToAddEntities(eggs));
+        }
+
+        self.defend_territory(board, &ctx);
+        if self.is_dead() {
             return None;
         }
+        self.pick_up_shell(board, ctx.position);
 
+        let was_doing = std::mem::discriminant(self.get_current_behavior());
         self.update_behavior(&ctx, board);
+        let same_behavior = was_doing == std::mem::discriminant(self.get_current_behavior());
+        self.track_progress(ctx.position, same_behavior);
 
-        // augh I hate this pattern and I know it's an antipattern but I really can't think of anything better
-        // in a perfect world with more time this would be better thought out
-
-        // Pull out our current behavior, and clone it.
+        // Pull out our current behavior, leaving an idle one in its place until we put it back.
         // We do this, similar to how we handle normal processing, because tick() requires a mutable reference to self,
         // but of course current behavior is also owned by self.
-        // At the very least they're (generally) pretty cheap.
-
-        let mut current_bhvr = self.get_current_behavior_mut().clone();
+        let mut current_bhvr = std::mem::take(self.get_current_behavior_mut());
         // just so we aren't spammed
         if !cfg!(test) {
-            println!(
+            debug!(
                 "{} ({:?}) is {}.",
                 self.get_display_char(),
                 ctx.position,
@@ -297,14 +819,20 @@ impl Processing for Animals {
     }
 
     /// Take ourselves in, owned (so we can async process), then return ourselves again
-    async fn late_process(&mut self) -> Option<PostProcessResult> {
-        self.life(); // run this stuff late
-        match self {
-            Self::Fish(_) | Self::Crab(_) | Self::Shark(_) => (),
+    async fn late_process(&mut self, difficulty: Difficulty) -> Option<PostProcessResult> {
+        self.life(difficulty); // run this stuff late
+        if let Self::Custom(c, _) = *self {
+            if !self.is_dead() {
+                c.plugin().on_tick(self);
+            }
         }
-        if self.delete_on_death() && matches!(self.get_life_status(), LifeStatus::Dead) {
+        if !matches!(self.get_life_status(), LifeStatus::Dead) {
+            return None;
+        }
+        if let Some(remains) = self.remains() {
+            Some(PostProcessResult::ReplaceMeWith(Box::new(remains)))
+        } else if self.delete_on_death() {
             Some(PostProcessResult::Delete)
-            // return Some(PostProcessResult::Delete);
         } else {
             None
         }
@@ -315,136 +843,179 @@ impl Processing for Animals {
 
 impl Lives for Animals {
     fn will_ever_live(&self) -> bool {
-        match self {
-            Self::Fish(a) | Self::Crab(a) | Self::Shark(a) => !a.has_died,
-        }
+        !self.body().health.has_died
     }
 
     fn get_health(&self) -> i64 {
-        match self {
-            Self::Fish(a) | Self::Crab(a) | Self::Shark(a) => a.hp,
-        }
+        self.body().health.hp
     }
 
     fn get_life_status(&self) -> crate::element_traits::LifeStatus {
-        match self {
-            Self::Fish(a) | Self::Crab(a) | Self::Shark(a) => {
-                if !a.has_died {
-                    LifeStatus::Alive
-                } else {
-                    LifeStatus::Dead
-                }
-            }
+        if !self.body().health.has_died {
+            LifeStatus::Alive
+        } else {
+            LifeStatus::Dead
         }
     }
 
     fn process_health(&mut self) {
-        match self {
-            Self::Fish(a) | Self::Crab(a) | Self::Shark(a) => {
-                let heal_rate = match a.hunger {
-                    HungerLevel::Full => 2,
-                    HungerLevel::Hungry => 1,
-                    HungerLevel::Starving => 0, // todo things don't die yet
-                    HungerLevel::Famished => -2,
-                };
-                self.modify_health(heal_rate, "hunger");
-            }
+        let heal_rate = match self.body().hunger.state {
+            HungerLevel::Full => 2,
+            HungerLevel::Hungry => 1,
+            HungerLevel::Starving => 0, // todo things don't die yet
+            HungerLevel::Famished => -2,
+        };
+        self.modify_health(heal_rate, "hunger");
+        // whatever's going around spreads quicker the more of us there are packed in together
+        let crowding = self.body().health.crowding;
+        if crowding > CROWDING_LIMIT && !self.is_dead() {
+            let sickness = (crowding - CROWDING_LIMIT) as i64 * DISEASE_DAMAGE;
+            self.modify_health(-sickness, "disease");
         }
     }
 
     fn modify_health(&mut self, delta: i64, cause: &str) {
-        match self {
-            Self::Fish(a) | Self::Crab(a) | Self::Shark(a) => {
-                a.hp = min(max(a.hp + delta, 0), a.hp_max);
-
-                if a.hp == 0 {
-                    self.die(cause);
-                }
-            }
+        if self.body_mut().health.change(delta) {
+            self.die(cause);
         }
     }
 
     fn die(&mut self, cause: &str) {
+        let a = self.body_mut();
+        a.health.has_died = true;
+        info!("{:?} has died of {cause}!", a)
+    }
+
+    /// Crabs leave their shells behind, and sharks are big enough to leave their bones.
+    fn remains(&self) -> Option<Entity> {
         match self {
-            Self::Fish(a) | Self::Crab(a) | Self::Shark(a) => {
-                a.has_died = true;
-                println!("{:?} has died of {cause}!", a)
-            }
+            Self::Crab(_) => Some(ConcreteDecorations::Shell.create_new(None)),
+            Self::Shark(_) => Some(ConcreteDecorations::Bones.create_new(None)),
+            Self::Fish(_) | Self::Custom(..) => None,
         }
     }
 
-    fn process_hunger(&mut self) {
+    fn process_hunger(&mut self, difficulty: Difficulty) {
         // println!("Hunger processed");
-        match self {
-            Self::Fish(a) => {
-                a.hunger_level -= 2;
-                a.hunger = HungerLevel::from(a.hunger_level);
-            }
-            Self::Shark(a) => {
-                a.hunger_level -= 3;
-                a.hunger = HungerLevel::from(a.hunger_level);
-            }
-            Self::Crab(a) => {
-                a.hunger_level -= 1;
-                a.hunger = HungerLevel::from(a.hunger_level);
-            }
-        }
+        let drain = match self {
+            Self::Fish(_) => 2,
+            Self::Shark(_) => 3,
+            Self::Crab(_) => 1,
+            Self::Custom(c, _) => c.animal_stats().hunger_drain,
+        };
+        // eating for two
+        let drain = match self.is_pregnant() {
+            true => drain + PREGNANT_HUNGER_DRAIN,
+            false => drain,
+        };
+        let hunger = &mut self.body_mut().hunger;
+        hunger.set(hunger.level - difficulty.hunger_drain(drain));
     }
 
     fn process_age(&mut self) {
-        match self {
-            Self::Fish(a) | Self::Shark(a) | Self::Crab(a) => {
-                a.age += 1;
-                if a.age >= a.max_age {
-                    self.die("old age");
-                }
-            }
+        let age = &mut self.body_mut().age;
+        age.ticks += 1;
+        if age.max.is_some_and(|max_age| age.ticks >= max_age) {
+            self.die("old age");
         }
         // TODO
     }
 
     fn process_life_misc(&mut self) {
+        let combat = &mut self.body_mut().combat;
+        combat.fleeing = combat.fleeing.saturating_sub(1);
+        combat.stamina = (combat.stamina + STAMINA_RECOVERY).min(MAX_STAMINA);
+        // anything that's healed back up has stopped bleeding
+        if self.hp_fraction() >= WOUNDED_THRESHOLD {
+            self.body_mut().combat.wounded = false;
+        }
+        let mobility = &mut self.body_mut().mobility;
+        if let Some(heading) = &mut mobility.heading {
+            heading.ticks_left = heading.ticks_left.saturating_sub(1);
+            if heading.ticks_left == 0 {
+                // we've given up on getting across, and this is home now
+                mobility.heading = None;
+            }
+        }
         self.process_mating()
     }
 }
 
+impl Fights for Animals {
+    fn attack_power(&self) -> usize {
+        self.body().combat.attack
+    }
+
+    fn defense(&self) -> usize {
+        let combat = &self.body().combat;
+        combat.defense + combat.shell
+    }
+
+    fn should_flee(&self) -> bool {
+        let health = &self.body().health;
+        (health.hp as f64) < health.hp_max as f64 * FLEE_THRESHOLD
+    }
+
+    fn flee(&mut self) {
+        self.body_mut().combat.fleeing = FLEE_TICKS;
+        // whatever we were up to can wait
+        self.set_current_behavior(AIConcreteBehaviors::default());
+    }
+
+    fn evasion(&self) -> f64 {
+        self.body().combat.evasion
+    }
+
+    fn quickness(&self) -> f64 {
+        let (x, y) = self.body().get_max_movespeed();
+        (x + y) as f64 / 2.0
+    }
+
+    fn stamina(&self) -> usize {
+        self.body().combat.stamina
+    }
+
+    fn tire(&mut self, by: usize) {
+        let combat = &mut self.body_mut().combat;
+        combat.stamina = combat.stamina.saturating_sub(by);
+    }
+
+    fn check_wounds(&mut self) {
+        if !self.is_dead() && self.hp_fraction() < WOUNDED_THRESHOLD {
+            self.body_mut().combat.wounded = true;
+        }
+    }
+}
+
 impl Mobile for Animals {
     fn max_speeds(&self) -> (usize, usize) {
-        match self {
-            Self::Fish(a) | Self::Shark(a) | Self::Crab(a) => {
-                (a.max_x_movespeed, a.max_y_movespeed)
-            }
-        }
+        self.body().get_max_movespeed()
     }
 }
 
 impl EatsCreatures<Plants> for Animals {
-    fn restore_hunger(&mut self, target: &Plants) {
-        let hunger_restored = self.hunger_restored(target);
-        match self {
-            Self::Crab(a) | Self::Fish(a) | Self::Shark(a) => {
-                a.hunger_level += hunger_restored as i64
-            }
-        };
+    fn restore_hunger(&mut self, target: &Plants, rules: &InteractionMatrix) {
+        let hunger_restored = self.hunger_restored(target, rules);
+        self.body_mut().hunger.level += hunger_restored as i64;
+        if let Self::Custom(c, _) = *self {
+            c.plugin().on_eat(self, target.species());
+        }
     }
 
-    fn hunger_restored(&self, target: &Plants) -> usize {
-        match target {
-            Plants::Kelp(_) => 100, // full kelp is very fulfilling
-            Plants::KelpLeaf(_) => 25,
-            Plants::KelpSeed(_) => 10, // barely worth it
-        }
+    fn hunger_restored(&self, target: &Plants, rules: &InteractionMatrix) -> usize {
+        rules.food_value(target.species())
     }
 
-    fn can_eat(&self, target: &Plants) -> bool {
+    fn can_eat(&self, target: &Plants, rules: &InteractionMatrix) -> bool {
         if target.is_dead() {
             return false;
         }
-        match self {
-            Self::Shark(_) => false, // sharks never eat plants, they're carnivores
-            Self::Crab(a) => matches!(a.hunger, HungerLevel::Hungry | HungerLevel::Starving),
-            Self::Fish(a) => matches!(a.hunger, HungerLevel::Starving | HungerLevel::Hungry), // save it for the crabs
-        }
+        let a = self.body();
+        rules.appetite(self.species(), target.species()).allows(
+            &a.hunger,
+            a.health.hp,
+            target.get_retaliation_damage(),
+        )
     }
 
     fn get_attack(&self, _: &Plants) -> usize {
@@ -472,162 +1043,128 @@ impl Eaten for Animals {
             Self::Shark(_) => 100,
             Self::Crab(_) => 50,
             Self::Fish(_) => 25,
+            Self::Custom(c, _) => c.animal_stats().retaliation,
         }
     }
 }
 
 impl EatsCreatures<Animals> for Animals {
-    fn can_eat(&self, target: &Animals) -> bool {
+    fn can_eat(&self, target: &Animals, rules: &InteractionMatrix) -> bool {
         if target.is_dead() {
             return false;
         }
         if *self == *target {
             return false;
         }
-        match (self, target) {
-            (Self::Shark(_), Self::Shark(_)) => false,
-            (Self::Shark(_), _) => true, // sharks can eat anything that isn't themselves
-            (Self::Fish(_), Self::Crab(_)) => true, // fish can eat crabs, but they might be killed by them in the process!
-            // fish are a bit more careful about trying to eat other fish, and will only do it if absolutely necessary!
-            (Self::Fish(a), Self::Fish(_)) => match a.hunger {
-                HungerLevel::Famished => true,
-                HungerLevel::Starving => a.hp > target.get_retaliation_damage() as i64,
-                HungerLevel::Hungry => false,
-                HungerLevel::Full => false,
-            },
-            // matches!(a.hunger, HungerLevel::Starving) && a.hp > target.get_retaliation_damage() as i64,
-            _ => false, // anything else is a crabshoot
-        }
+        // anything without a row is a crabshoot
+        let a = self.body();
+        rules.appetite(self.species(), target.species()).allows(
+            &a.hunger,
+            a.health.hp,
+            target.get_retaliation_damage(),
+        )
     }
 
-    fn restore_hunger(&mut self, target: &Animals) {
-        let hunger_restored = self.hunger_restored(target);
-        match self {
-            Self::Crab(a) | Self::Fish(a) | Self::Shark(a) => {
-                a.hunger_level += hunger_restored as i64
-            }
-        };
+    fn restore_hunger(&mut self, target: &Animals, rules: &InteractionMatrix) {
+        let hunger_restored = self.hunger_restored(target, rules);
+        self.body_mut().hunger.level += hunger_restored as i64;
+        if let Self::Custom(c, _) = *self {
+            c.plugin().on_eat(self, target.species());
+        }
     }
 
-    fn hunger_restored(&self, target: &Animals) -> usize {
-        match target {
-            Animals::Crab(_) => 50,
-            Animals::Fish(_) => 100,
-            Animals::Shark(_) => 500,
-        }
+    fn hunger_restored(&self, target: &Animals, rules: &InteractionMatrix) -> usize {
+        rules.food_value(target.species())
     }
 
     fn get_attack(&self, _: &Animals) -> usize {
-        match self {
-            Self::Shark(_) => 100,
-            Self::Crab(_) => 50,
-            Self::Fish(_) => 25,
-        }
+        self.body().combat.attack
     }
 }
 
 impl Reproducing for Animals {
     fn ready_to_reproduce(&self) -> bool {
-        match self {
-            Self::Crab(a) | Self::Fish(a) | Self::Shark(a) => {
-                matches!(a.sex, Sex::Female | Sex::Neutral)
-                    && a.pregnancy_level >= MAX_PREGNANCY_LEVEL
-            }
-        }
+        let r = &self.body().reproduction;
+        matches!(r.sex, Sex::Female | Sex::Neutral) && r.pregnancy_level >= MAX_PREGNANCY_LEVEL
     }
 
     fn get_offspring_data(&self) -> Option<OffspringData> {
-        match self {
-            // For now, just a single
-            Self::Crab(_) | Self::Fish(_) | Self::Shark(_) => Some(OffspringData {
-                min_offspring: 1,
-                max_offspring: 1,
-                percent_chance_per_tile: 0.0,
-            }),
-        }
+        // For now, just a single
+        Some(OffspringData {
+            min_offspring: 1,
+            max_offspring: 1,
+            percent_chance_per_tile: 0.0,
+        })
     }
 
-    fn have_child(&mut self, tile: &mut crate::Tile, _: Pos, _: usize) {
-        let new_child = match self {
-            Self::Crab(_) => ConcreteAnimals::Crab.create_new(None),
-            Self::Shark(_) => ConcreteAnimals::Shark.create_new(None),
-            Self::Fish(_) => ConcreteAnimals::Fish.create_new(None),
-        };
-        // ids populated by tile
-        let _ = tile.add_entity(new_child);
+    /// Animals lay eggs rather than giving birth, which hatch into the child later on.
+    fn have_child(
+        &mut self,
+        board: &mut Board,
+        pos: Pos,
+        _: usize,
+        rng: &mut impl Rng,
+    ) -> Result<Option<Pos>, Error> {
+        // ids populated by the board
+        board
+            .place_nearby(pos, egg(self.conceive(rng), None))
+            .map(Some)
+    }
+
+    fn offspring_species(&self) -> Species {
+        self.species()
     }
 
     fn on_offspring_created(&mut self) {
-        match self {
-            // For now, just a single
-            Self::Crab(a) | Self::Fish(a) | Self::Shark(a) => {
-                a.pregnant = false;
-                a.pregnancy_level = 0;
-                a.ticks_since_last_mating = 0; // we'll just set this here so there's a bit of a cooldown between having a child and trying to make more
-            }
-        }
+        let r = &mut self.body_mut().reproduction;
+        r.pregnant = false;
+        r.mate = None;
+        r.pregnancy_level = 0;
+        r.ticks_since_last_mating = 0; // we'll just set this here so there's a bit of a cooldown between having a child and trying to make more
     }
 }
 
 impl Mates for Animals {
-    fn compatible_mate(&self, target: &Self) -> bool {
+    fn compatible_mate(&self, target: &Self, rules: &InteractionMatrix) -> bool {
         let able_to_mate = self.can_mate() && target.can_mate();
-        let compatible = match (self, target) {
-            (Animals::Shark(a), Animals::Shark(b)) | (Animals::Fish(a), Animals::Fish(b)) => {
-                a.sex != b.sex
-            }
-            (Animals::Crab(_), Animals::Crab(_)) => true, // crabs don't need gender
-            _ => false,
-        };
+        let compatible = rules
+            .pairing(self.species(), target.species())
+            .allows(self.body().reproduction.sex, target.body().reproduction.sex);
 
         able_to_mate && compatible
     }
 
     fn can_mate(&self) -> bool {
-        match self {
-            // For now, just a single
-            Self::Crab(a) | Self::Fish(a) | Self::Shark(a) => {
-                !a.pregnant && a.ticks_since_last_mating >= a.mating_cooldown
-            }
-        }
+        let r = &self.body().reproduction;
+        !r.pregnant && r.ticks_since_last_mating >= r.mating_cooldown
     }
 
     fn slow_mate(&mut self, factor: f64) {
-        match self {
-            Self::Crab(a) | Self::Fish(a) | Self::Shark(a) => {
-                let less_growth = a.ticks_since_last_mating as f64 / factor;
-                if factor < 1.0 {
-                    a.ticks_since_last_mating = less_growth.ceil() as usize;
-                } else {
-                    a.ticks_since_last_mating -= less_growth.ceil() as usize;
-                }
-            }
+        let r = &mut self.body_mut().reproduction;
+        let less_growth = r.ticks_since_last_mating as f64 / factor;
+        if factor < 1.0 {
+            r.ticks_since_last_mating = less_growth.ceil() as usize;
+        } else {
+            r.ticks_since_last_mating -= less_growth.ceil() as usize;
         }
     }
 
-    fn on_successful_mate(&mut self) {
-        match self {
-            // For now, just a single
-            Self::Crab(a) | Self::Fish(a) | Self::Shark(a) => {
-                if matches!(a.sex, Sex::Female | Sex::Neutral) {
-                    a.pregnant = true;
-                }
-                a.ticks_since_last_mating = 0;
-            }
+    fn on_successful_mate(&mut self, partner: Option<EntityID>) {
+        let r = &mut self.body_mut().reproduction;
+        if matches!(r.sex, Sex::Female | Sex::Neutral) {
+            r.pregnant = true;
+            r.mate = partner;
         }
+        r.ticks_since_last_mating = 0;
     }
 
     fn process_mating(&mut self) {
-        match self {
-            // For now, just a single
-            Self::Crab(a) | Self::Fish(a) | Self::Shark(a) => {
-                a.ticks_since_last_mating += 1;
-                if !a.pregnant {
-                    return;
-                }
-                a.pregnancy_level += a.pregnancy_step;
-            }
+        let r = &mut self.body_mut().reproduction;
+        r.ticks_since_last_mating += 1;
+        if !r.pregnant {
+            return;
         }
+        r.pregnancy_level += r.pregnancy_step;
     }
 }
 
@@ -637,18 +1174,12 @@ impl TrackedEntity for Animals {
     }
 
     fn register(&mut self, id: EntityID) -> Result<(), EntityID> {
-        match self {
-            // For now, just a single
-            Self::Crab(a) | Self::Fish(a) | Self::Shark(a) => a.id = Some(id),
-        }
+        self.body_mut().id = Some(id);
         Ok(())
     }
 
     fn get_id(&self) -> Option<EntityID> {
-        match self {
-            // For now, just a single
-            Self::Crab(a) | Self::Fish(a) | Self::Shark(a) => a.id,
-        }
+        self.body().id
     }
 }
 
@@ -674,7 +1205,16 @@ impl AIControlled<Animals> for Animals {
         board: &Board,
     ) -> Option<AIConcreteBehaviors> {
         let all_possible_actions = self.get_all_possible_actions(board, ctx);
-        let best_possible_action = self.get_best_possible_behavior(all_possible_actions, ctx);
+        if let Some(chosen) = self.ask_brain(&all_possible_actions, ctx, board) {
+            // being told to keep doing what we're already doing shouldn't start it all over again
+            let cur_behavior = self.get_current_behavior();
+            if cur_behavior.is_valid(self, ctx, board) && cur_behavior.same_pursuit(&chosen) {
+                return None;
+            }
+            return Some(chosen);
+        }
+        let best_possible_action =
+            self.get_best_possible_behavior(all_possible_actions, ctx, board);
 
         // if let Some((p, act)) = &best_possible_action {
         //     // println!("Best possible action for {:?} at {:?} is \n {} at {p:?}", self.get_id(), ctx.position, act.get_action_desc());
@@ -687,38 +1227,46 @@ impl AIControlled<Animals> for Animals {
                 info!("New action was completed!");
                 return Some(new_action);
 
-            // } else if cur_behavior. {
+                // } else if cur_behavior. {
 
-            // }
-            } else if cur_behavior.priority() >= new_action.priority() {
-                // println!("New action {new_action:?} was of too low priority vs {cur_behavior:?}!");
+                // }
+            }
+            let weights = self.behavior_weights(&ctx.rules);
+            if cur_behavior.urgency(self, &weights) >= new_action.urgency(self, &weights) {
+                // println!("New action {new_action:?} was less urgent than {cur_behavior:?}!");
                 return None;
-            } else {
-                return Some(new_action);
             }
+            return Some(new_action);
         }
         None
     }
 
     fn get_current_behavior_mut(&mut self) -> &mut AIConcreteBehaviors {
-        match self {
-            Self::Crab(a) | Self::Fish(a) | Self::Shark(a) => &mut a.current_behavior,
-        }
+        &mut self.body_mut().current_behavior
     }
 
     fn get_current_behavior(&self) -> &AIConcreteBehaviors {
-        match self {
-            Self::Crab(a) | Self::Fish(a) | Self::Shark(a) => &a.current_behavior,
-        }
+        &self.body().current_behavior
     }
 
     fn set_current_behavior(&mut self, behavior: AIConcreteBehaviors) {
-        match self {
-            Self::Crab(a) | Self::Fish(a) | Self::Shark(a) => a.current_behavior = behavior,
-        }
+        self.body_mut().current_behavior = behavior
     }
 
     fn get_desired_move(&self, ctx: &ProcessingContext, board: &Board) -> Option<Pos> {
+        if self.fleeing() {
+            return self
+                .hiding_spot(ctx.position, board, &ctx.rules)
+                .or_else(|| self.random_walk(ctx.position, &mut *ctx.rng.lock().unwrap(), board));
+        }
+        if let Some(heading) = &self.body().mobility.heading {
+            return self
+                .migration_step(ctx.position, heading.to, board)
+                .or_else(|| self.random_walk(ctx.position, &mut *ctx.rng.lock().unwrap(), board));
+        }
+        if self.body().mobility.progress.wander_ticks > 0 {
+            return self.random_walk(ctx.position, &mut *ctx.rng.lock().unwrap(), board);
+        }
         let bhvr = self.get_current_behavior();
         match bhvr {
             // todo this could probably be better placed in AIConcreteBehaviors itself
@@ -729,54 +1277,20 @@ impl AIControlled<Animals> for Animals {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum HungerLevel {
-    Full,
-    Hungry,
-    Starving,
-    Famished,
-}
-
-impl From<i64> for HungerLevel {
-    fn from(value: i64) -> Self {
-        match value {
-            51..=i64::MAX => HungerLevel::Full,
-            1..=50 => HungerLevel::Hungry,
-            -24..=0 => HungerLevel::Starving,
-            i64::MIN..=-25 => HungerLevel::Famished,
-        }
-    }
-}
-
 /// The raw definition of an animal. One of the possibilities for the bottom of the enum tree.
+/// Everything it's made of is kept in components, which look the same for every animal.
 #[derive(Debug, Clone, PartialEq)]
 pub struct AnimalType {
     /// The name of the animal.
     name: String,
-    /// Current hitpoints.
-    hp: i64,
-    /// Maximum number of hit points.
-    hp_max: i64,
-    /// How many tiles this creature can move per turn in the x direction
-    max_x_movespeed: usize,
-    /// How many tiles this creature can move per turn in the y direction
-    max_y_movespeed: usize,
-    hunger: HungerLevel,
-    pub hunger_level: i64,
-    has_died: bool,
-    age: usize,
-    max_age: usize, // animals don't live forever,
-    sex: Sex,
-    // Reproduction stuff
-    pregnant: bool,
-    /// Pregnancy increases by a given step
-    pregnancy_level: usize,
-    /// Step by which this creature progresses towards having a child
-    pregnancy_step: usize,
-    /// The number of ticks since we last had a child.
-    pub ticks_since_last_mating: usize,
-    /// How long this species takes between mating
-    mating_cooldown: usize,
+    pub health: Health,
+    pub hunger: Hunger,
+    pub mobility: Mobility,
+    pub age: Age, // animals don't live forever
+    pub reproduction: Reproduction,
+    pub combat: Combat,
+    /// How far we lean away from our species' temperament, one way or the other.
+    pub personality: Personality,
     /// Our working entity ID
     id: Option<EntityID>,
     /// Our possible behaviors
@@ -794,61 +1308,160 @@ impl AnimalType {
         id: Option<EntityID>,
         max_movespeed_x: usize,
         max_movespeed_y: usize,
-        sex_override: Option<Sex>,
+        sex: Sex,
+        combat: Combat,
     ) -> Self {
-        let mut rng: ThreadRng = rand::thread_rng();
-        let chosen_sex = if let Some(sex) = sex_override {
-            sex
-        } else if rng.gen_bool(0.5) {
-            Sex::Male
-        } else {
-            Sex::Female
-        };
         Self {
             name: String::from(name),
-            hp_max: hp,
-            hp,
-            hunger: HungerLevel::Full,
-            hunger_level: 100,
-            has_died: false,
-            age: 0,
-            max_age,
-            sex: chosen_sex,
-            pregnancy_level: 0,
-            pregnant: false,
-            pregnancy_step,
-            mating_cooldown,
-            ticks_since_last_mating: 0,
+            health: Health::new(hp),
+            hunger: Hunger::new(100),
+            mobility: Mobility {
+                max_x: max_movespeed_x,
+                max_y: max_movespeed_y,
+                heading: None,
+                progress: Progress::default(),
+            },
+            age: Age::new(Some(max_age)),
+            reproduction: Reproduction::new(sex, pregnancy_step, mating_cooldown),
+            combat,
+            personality: Personality::default(),
             id,
-            max_x_movespeed: max_movespeed_x,
-            max_y_movespeed: max_movespeed_y,
-            current_behavior: AIConcreteBehaviors::Idle(IdleAction::new(true, true)),
+            current_behavior: AIConcreteBehaviors::Idle(IdleAction::new(true)),
         }
     }
 
-    /// Get the maximum movespeeds in the (x, y) directions.
+    /// Set up an animal from a plugin's stats.
+    pub(super) fn from_stats(
+        name: &str,
+        stats: &AnimalStats,
+        id: Option<EntityID>,
+        sex: Sex,
+    ) -> Self {
+        let (speed_x, speed_y) = stats.speed;
+        Self::new(
+            name,
+            stats.hp,
+            stats.max_age,
+            stats.pregnancy_step,
+            stats.mating_cooldown,
+            id,
+            speed_x,
+            speed_y,
+            sex,
+            Combat::new(stats.attack, stats.defense, stats.fights_back)
+                .with_reach(stats.reach)
+                .with_evasion(stats.evasion)
+                .with_smell(stats.smell),
+        )
+    }
+
+    /// Get the maximum movespeeds in the (x, y) directions. Carrying a child slows us down a little.
     pub fn get_max_movespeed(&self) -> (usize, usize) {
-        (self.max_x_movespeed, self.max_y_movespeed)
+        let (x, y) = (self.mobility.max_x, self.mobility.max_y);
+        match self.reproduction.pregnant {
+            true => (
+                x.saturating_sub(PREGNANT_SLOWDOWN).max(x.min(1)),
+                y.saturating_sub(PREGNANT_SLOWDOWN).max(y.min(1)),
+            ),
+            false => (x, y),
+        }
+    }
+
+    /// Override one of our stats by name, parsing the value from a string.
+    /// Used to set up animals from files, so the error is meant to be read by a person.
+    pub fn set_stat(&mut self, stat: &str, value: &str) -> Result<(), String> {
+        let parse_err = || format!("'{value}' is not a valid value for {stat}");
+        match stat {
+            "hp" => {
+                let hp = value.parse().map_err(|_| parse_err())?;
+                self.health.hp = min(hp, self.health.hp_max);
+            }
+            "hunger" => self.hunger.set(value.parse().map_err(|_| parse_err())?),
+            "age" => self.age.ticks = value.parse().map_err(|_| parse_err())?,
+            "generation" => {
+                self.reproduction.generation = value.parse().map_err(|_| parse_err())?
+            }
+            "pregnant" => self.reproduction.pregnant = value.parse().map_err(|_| parse_err())?,
+            "sex" => {
+                self.reproduction.sex = match value {
+                    "male" => Sex::Male,
+                    "female" => Sex::Female,
+                    "neutral" => Sex::Neutral,
+                    _ => return Err(parse_err()),
+                }
+            }
+            "stamina" => {
+                let stamina: usize = value.parse().map_err(|_| parse_err())?;
+                self.combat.stamina = stamina.min(MAX_STAMINA);
+            }
+            "wounded" => self.combat.wounded = value.parse().map_err(|_| parse_err())?,
+            "boldness" | "laziness" | "sociability" => {
+                let value: f64 = value
+                    .parse()
+                    .ok()
+                    .filter(|v| (-1.0..=1.0).contains(v))
+                    .ok_or_else(parse_err)?;
+                let personality = &mut self.personality;
+                match stat {
+                    "boldness" => personality.boldness = value,
+                    "laziness" => personality.laziness = value,
+                    _ => personality.sociability = value,
+                }
+            }
+            "shell" => self.combat.shell = value.parse().map_err(|_| parse_err())?,
+            _ => return Err(format!("{} has no stat called '{stat}'", self.name)),
+        }
+        Ok(())
+    }
+
+    /// Get all of the stats that can be set through set_stat, written out as stat=value.
+    pub fn get_stats(&self) -> String {
+        format!(
+            "hp={} hunger={} age={} generation={} pregnant={} sex={} stamina={} wounded={} boldness={} laziness={} sociability={} shell={}",
+            self.health.hp,
+            self.hunger.level,
+            self.age.ticks,
+            self.reproduction.generation,
+            self.reproduction.pregnant,
+            format!("{:?}", self.reproduction.sex).to_lowercase(),
+            self.combat.stamina,
+            self.combat.wounded,
+            self.personality.boldness,
+            self.personality.laziness,
+            self.personality.sociability,
+            self.combat.shell
+        )
     }
 }
 
 impl PTUIDisplay for AnimalType {
+    /// Drawn like the species it's named after, or as the first letter of its name if there isn't one.
     fn get_display_char(&self) -> char {
-        return self.name.chars().next().unwrap();
+        Species::from_name(&self.name).map_or_else(
+            || self.name.chars().next().unwrap(),
+            |species| species.display_spec().emoji,
+        )
     }
 }
 
 #[cfg(test)]
 pub mod tests {
-    use std::sync::Arc;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
 
-    use super::{Animals, ConcreteAnimals, HungerLevel};
+    use super::{
+        Animals, ConcreteAnimals, CROWDING_LIMIT, DISEASE_DAMAGE, PREGNANT_HUNGER_DRAIN,
+        PREGNANT_SLOWDOWN,
+    };
     use crate::{
         ai_controller::{AIConcreteBehaviors, AIControlled},
-        element_traits::{Lives, ProcessingContext},
-        entities::{plants::ConcretePlants, Entity, Living, NonAbstractTaxonomy, Sex},
+        element_traits::{Lives, Mobile},
+        entities::{plants::ConcretePlants, Entity, Living, NonAbstractTaxonomy, Sex, Species},
         entity_control::{EntityID, TrackedEntity},
         game_board::Pos,
+        interaction_matrix::InteractionMatrix,
+        interactions::EatsCreatures,
+        settings::Difficulty,
         test_utils::TestBed,
     };
 
@@ -861,30 +1474,48 @@ pub mod tests {
 
         let mut testbed = TestBed::new_with_entities(3, 3, vec![(Pos { x: 1, y: 1 }, creature)]);
 
-        let tile = testbed.sandbox.board.get_tile(1, 1);
+        let tile = testbed.sandbox.board.entity_at(Pos { x: 1, y: 1 });
 
-        if let Some(Entity::Living(Living::Animals(a))) = &tile.get_entity() {
+        if let Some(Entity::Living(Living::Animals(a))) = tile {
             health = a.get_health();
             if let Animals::Crab(c) = &a {
-                hunger = c.hunger_level;
+                hunger = c.hunger.level;
             }
         }
 
         testbed.run_n_full_steps(2);
 
-        let tile = testbed.sandbox.board.get_tile(1, 1);
+        let tile = testbed.sandbox.board.entity_at(Pos { x: 1, y: 1 });
 
-        if let Some(Entity::Living(Living::Animals(a))) = &tile.get_entity() {
+        if let Some(Entity::Living(Living::Animals(a))) = tile {
             assert!(!a.is_dead());
             if let Animals::Crab(c) = a {
                 assert_eq!(a.get_health(), health);
-                dbg!(c.hunger_level);
+                dbg!(c.hunger.level);
                 dbg!(hunger);
-                assert!(c.hunger_level < hunger);
+                assert!(c.hunger.level < hunger);
             }
         }
     }
 
+    #[test]
+    fn verify_hunger_scales_with_difficulty() {
+        let hunger_after = |difficulty: Difficulty| {
+            let mut shark = ConcreteAnimals::Shark.create_new(None);
+            let Entity::Living(Living::Animals(a)) = &mut shark else {
+                unreachable!()
+            };
+            a.process_hunger(difficulty);
+            let Animals::Shark(s) = a else { unreachable!() };
+            s.hunger.level
+        };
+        let easy = hunger_after(Difficulty::Easy);
+        let normal = hunger_after(Difficulty::Normal);
+        let hard = hunger_after(Difficulty::Hard);
+        assert_eq!(normal, 100 - 3);
+        assert!(easy > normal && normal > hard);
+    }
+
     #[test]
     fn verify_death() {
         let pre_verify = |a: &mut Animals| {
@@ -907,31 +1538,23 @@ pub mod tests {
         F: FnOnce(&mut Animals),
         G: FnOnce(Option<&mut Animals>),
     {
-        let creature = ConcreteAnimals::Crab.create_new(None);
-        let mut testbed = TestBed::new_with_entities(3, 3, vec![(Pos { x: 1, y: 1 }, creature)]);
-        let tile = testbed.sandbox.board.get_tile_mut(1, 1);
-
-        if let Some(Entity::Living(Living::Animals(a))) = tile.get_entity_mut() {
-            pre_check(a);
-        };
+        let pos = Pos { x: 1, y: 1 };
+        let mut testbed = TestBed::builder(3, 3).with(pos, Species::Crab).build();
+        pre_check(testbed.animal_at_mut(pos));
 
         testbed.run_n_steps_no_checks(n_steps, false, process, late_process, false);
 
-        let tile = testbed.sandbox.board.get_tile_mut(1, 1);
-
-        if let Some(Entity::Living(Living::Animals(a))) = tile.get_entity_mut() {
-            post_check(Some(a));
-        } else {
-            post_check(None)
-        }
+        post_check(
+            testbed
+                .get_entity_at_pos_mut(pos)
+                .and_then(Entity::animal_mut),
+        );
     }
 
     #[test]
     fn verify_starvation() {
         let pre_verify = |a: &mut Animals| {
-            let (Animals::Crab(c) | Animals::Fish(c) | Animals::Shark(c)) = a;
-            c.hunger_level = -5;
-            c.hunger = HungerLevel::Starving;
+            a.body_mut().hunger.set(-5);
         };
         let post_verify = |a: Option<&mut Animals>| assert!(a.is_none());
 
@@ -942,7 +1565,7 @@ pub mod tests {
     fn verify_old_age() {
         let pre_verify = |a: &mut Animals| {
             if let Animals::Crab(c) = a {
-                c.age = 5000;
+                c.age.ticks = 5000;
             };
         };
         let post_verify = |a: Option<&mut Animals>| assert!(a.is_none());
@@ -961,21 +1584,18 @@ pub mod tests {
         );
 
         // make em hungry
-        let tile = testbed.sandbox.board.get_tile_mut(1, 1);
-        if let Some(Entity::Living(Living::Animals(Animals::Crab(a)))) = tile.get_entity_mut() {
-            a.hunger_level = -1;
-            a.hunger = HungerLevel::Starving;
+        if let Some(Entity::Living(Living::Animals(Animals::Crab(a)))) =
+            testbed.sandbox.board.entity_at_mut(Pos { x: 1, y: 1 })
+        {
+            a.hunger.set(-1);
         }
 
-        let tile = testbed.sandbox.board.get_tile(1, 1);
+        let tile = testbed.sandbox.board.entity_at(Pos { x: 1, y: 1 });
 
-        let ctx = ProcessingContext {
-            entity_context: Arc::clone(&testbed.sandbox.entity_context),
-            position: Pos { x: 1, y: 1 },
-        };
+        let ctx = testbed.sandbox.processing_context(Pos { x: 1, y: 1 });
 
         // everything should start out idling
-        if let Some(Entity::Living(Living::Animals(a))) = tile.get_entity() {
+        if let Some(Entity::Living(Living::Animals(a))) = tile {
             assert!(matches!(
                 a.get_current_behavior(),
                 AIConcreteBehaviors::Idle(_)
@@ -997,16 +1617,16 @@ pub mod tests {
         // ensure it isn't sticky
 
         // make em
-        let tile = testbed.sandbox.board.get_tile_mut(1, 1);
-        if let Some(Entity::Living(Living::Animals(Animals::Crab(a)))) = tile.get_entity_mut() {
-            a.hunger_level = 100;
-            a.hunger = HungerLevel::Full;
+        if let Some(Entity::Living(Living::Animals(Animals::Crab(a)))) =
+            testbed.sandbox.board.entity_at_mut(Pos { x: 1, y: 1 })
+        {
+            a.hunger.set(100);
         }
 
-        let tile = testbed.sandbox.board.get_tile(1, 1);
+        let tile = testbed.sandbox.board.entity_at(Pos { x: 1, y: 1 });
 
         // everything should start out idling
-        if let Some(Entity::Living(Living::Animals(a))) = tile.get_entity() {
+        if let Some(Entity::Living(Living::Animals(a))) = tile {
             let behaviors = a.get_all_possible_actions(&testbed.sandbox.board, &ctx);
             // dbg!(&behaviors);
             assert_eq!(behaviors.len(), 1);
@@ -1031,10 +1651,7 @@ pub mod tests {
         let plant = ConcretePlants::Kelp.create_new(None);
         let mut testbed = TestBed::new_with_entities(5, 8, vec![(Pos { x: 1, y: 1 }, creature)]);
 
-        let ctx = ProcessingContext {
-            entity_context: Arc::clone(&testbed.sandbox.entity_context),
-            position: Pos { x: 1, y: 1 },
-        };
+        let ctx = testbed.sandbox.processing_context(Pos { x: 1, y: 1 });
 
         let mut entity_id: Option<EntityID> = None;
 
@@ -1061,15 +1678,18 @@ pub mod tests {
         }
 
         // make hungry
-        let tile = testbed.sandbox.board.get_tile_mut(1, 1);
-        if let Some(Entity::Living(Living::Animals(Animals::Crab(a)))) = tile.get_entity_mut() {
-            a.hunger_level = 0;
-            a.hunger = HungerLevel::Starving;
+        if let Some(Entity::Living(Living::Animals(Animals::Crab(a)))) =
+            testbed.sandbox.board.entity_at_mut(Pos { x: 1, y: 1 })
+        {
+            a.hunger.set(0);
         }
 
         // insert plant
-        let tile = testbed.sandbox.board.get_tile_mut(4, 6);
-        tile.add_entity(plant).unwrap();
+        testbed
+            .sandbox
+            .board
+            .place_entity(Pos { x: 6, y: 4 }, plant)
+            .unwrap();
 
         testbed.run_n_steps_no_checks(1, false, true, true, false);
 
@@ -1100,13 +1720,13 @@ pub mod tests {
     fn verify_mating() {
         let mut creature = ConcreteAnimals::Crab.create_new(None);
         if let Entity::Living(Living::Animals(Animals::Crab(c))) = &mut creature {
-            c.sex = Sex::Male;
-            c.ticks_since_last_mating = 1000;
+            c.reproduction.sex = Sex::Male;
+            c.reproduction.ticks_since_last_mating = 1000;
         }
         let mut creature_2 = creature.clone();
 
         if let Entity::Living(Living::Animals(Animals::Crab(c))) = &mut creature_2 {
-            c.sex = Sex::Female;
+            c.reproduction.sex = Sex::Female;
         }
 
         let mut testbed = TestBed::new_with_entities(
@@ -1118,13 +1738,10 @@ pub mod tests {
             ],
         );
 
-        let ctx = ProcessingContext {
-            entity_context: Arc::clone(&testbed.sandbox.entity_context),
-            position: Pos { x: 1, y: 1 },
-        };
+        let ctx = testbed.sandbox.processing_context(Pos { x: 1, y: 1 });
 
-        let tile = testbed.sandbox.board.get_tile(1, 1);
-        if let Some(Entity::Living(Living::Animals(a))) = tile.get_entity() {
+        let tile = testbed.sandbox.board.entity_at(Pos { x: 1, y: 1 });
+        if let Some(Entity::Living(Living::Animals(a))) = tile {
             assert!(
                 a.get_all_possible_actions(&testbed.sandbox.board, &ctx)
                     .len()
@@ -1134,8 +1751,8 @@ pub mod tests {
 
         testbed.run_n_steps_no_checks(10, false, true, true, false);
 
-        let tile = testbed.sandbox.board.get_tile(1, 1);
-        if let Some(Entity::Living(Living::Animals(a))) = tile.get_entity() {
+        let tile = testbed.sandbox.board.entity_at(Pos { x: 1, y: 1 });
+        if let Some(Entity::Living(Living::Animals(a))) = tile {
             assert!(
                 a.get_all_possible_actions(&testbed.sandbox.board, &ctx)
                     .len()
@@ -1153,4 +1770,76 @@ pub mod tests {
         let entities = testbed.sandbox.get_important_entities();
         assert!(entities.len() > 2);
     }
+
+    #[test]
+    /// Carrying a child slows an animal down and makes it hungrier, and it'd rather wander somewhere sheltered
+    fn verify_pregnancy_modifiers() {
+        let mut shark = ConcreteAnimals::Shark.create_new(None);
+        let Entity::Living(Living::Animals(a)) = &mut shark else {
+            unreachable!()
+        };
+        a.body_mut().reproduction.pregnant = true;
+        assert_eq!(
+            a.max_speeds(),
+            (3 - PREGNANT_SLOWDOWN, 3 - PREGNANT_SLOWDOWN)
+        );
+        a.process_hunger(Difficulty::Normal);
+        assert_eq!(a.body().hunger.level, 100 - 3 - PREGNANT_HUNGER_DRAIN);
+
+        // fish can't get any slower than they already are
+        let mut fish = ConcreteAnimals::Fish.create_new(None);
+        fish.reproduction_mut().unwrap().pregnant = true;
+        assert_eq!(fish.animal().unwrap().max_speeds(), (1, 1));
+
+        // a rock at one end of the row is the only cover around, a step away from where the fish starts
+        let start = Pos { x: 2, y: 0 };
+        let testbed = TestBed::builder(1, 7).terrain(&["r......"]).build();
+        let board = &testbed.sandbox.board;
+        let sheltered = |pregnant: bool| {
+            let mut fish = ConcreteAnimals::Fish.create_new(None);
+            fish.reproduction_mut().unwrap().pregnant = pregnant;
+            let fish = fish.animal().unwrap().clone();
+            (0..50)
+                .filter_map(|seed| fish.random_walk(start, &mut StdRng::seed_from_u64(seed), board))
+                .filter(|pos| board.sheltered(*pos))
+                .count()
+        };
+        assert!(sheltered(true) > sheltered(false));
+    }
+
+    #[test]
+    /// Famished crabs turn on seeds and each other, and animals packed in too tightly with their own kind get sick
+    fn verify_population_pressure() {
+        let crab = |hunger: &str| {
+            let mut crab = Species::Crab.create_new(None);
+            crab.set_stat("hunger", hunger).unwrap();
+            crab.animal().unwrap().clone()
+        };
+        let plant = |species: Species| species.create_new(None).plant().unwrap().clone();
+        let (seed, leaf) = (plant(Species::KelpSeed), plant(Species::KelpLeaf));
+        let (hungry, famished) = (crab("20"), crab("-30"));
+        let rules = InteractionMatrix::default();
+        assert!(hungry.can_eat(&leaf, &rules) && !hungry.can_eat(&seed, &rules));
+        assert!(!hungry.can_eat(&crab("100"), &rules));
+        assert!(famished.can_eat(&seed, &rules) && famished.can_eat(&crab("100"), &rules));
+
+        // a fish with a school all the way around it
+        let center = Pos { x: 1, y: 1 };
+        let school = (0..3)
+            .flat_map(|x| (0..3).map(move |y| (Pos { x, y }, Species::Fish.create_new(None))))
+            .collect();
+        let testbed = TestBed::new_with_entities(3, 3, school);
+        assert_eq!(testbed.sandbox.board.crowding_at(center), 8);
+        assert_eq!(testbed.sandbox.board.crowding_at(Pos { x: 0, y: 0 }), 3);
+
+        let mut fish = testbed.get_entity_at_pos(center).unwrap().clone();
+        let fish = fish.animal_mut().unwrap();
+        let hp_max = fish.body().health.hp_max;
+        fish.body_mut().health.crowding = CROWDING_LIMIT;
+        fish.process_health();
+        assert_eq!(fish.get_health(), hp_max);
+        fish.body_mut().health.crowding = CROWDING_LIMIT + 2;
+        fish.process_health();
+        assert_eq!(fish.get_health(), hp_max - 2 * DISEASE_DAMAGE);
+    }
 }
@@ -0,0 +1,463 @@
+// The pieces that living things are put together from. Each one only covers a single part of being alive, and is laid out
+// the same no matter the species, so anything that only cares about (say) health can get at it through Entity::health
+// without matching its way down through every animal and plant there is.
+// For now they still travel around inside the entity they belong to, since the board hands whole entities out wherever
+// something's looked up. Giving each component a store of its own keyed by EntityID is left for its own change.
+
+use std::cmp::{max, min};
+
+use rand::Rng;
+
+use super::Sex;
+use crate::entity_control::EntityID;
+use crate::game_board::{Edge, Pos};
+use crate::interactions::MAX_STAMINA;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HungerLevel {
+    Full,
+    Hungry,
+    Starving,
+    Famished,
+}
+
+impl HungerLevel {
+    pub const ALL: [HungerLevel; 4] = [Self::Full, Self::Hungry, Self::Starving, Self::Famished];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Hungry => "hungry",
+            Self::Starving => "starving",
+            Self::Famished => "famished",
+        }
+    }
+}
+
+impl From<i64> for HungerLevel {
+    fn from(value: i64) -> Self {
+        match value {
+            51..=i64::MAX => HungerLevel::Full,
+            1..=50 => HungerLevel::Hungry,
+            -24..=0 => HungerLevel::Starving,
+            i64::MIN..=-25 => HungerLevel::Famished,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Health {
+    /// Current hitpoints. For plants, this is how many bites are left.
+    pub hp: i64,
+    /// Maximum number of hit points.
+    pub hp_max: i64,
+    /// If we're irrevocably dead
+    pub has_died: bool,
+    /// How many of our own kind were packed in right around us last tick. Set by the board before we're processed,
+    /// the same way a plant's light is. Only animals get crowded.
+    pub crowding: usize,
+}
+
+impl Health {
+    pub fn new(hp: i64) -> Self {
+        Self {
+            hp,
+            hp_max: hp,
+            has_died: false,
+            crowding: 0,
+        }
+    }
+
+    /// Change our hp, without going below 0 or above the max. Returns true if that left us with nothing.
+    pub fn change(&mut self, delta: i64) -> bool {
+        self.hp = min(max(self.hp + delta, 0), self.hp_max);
+        self.hp == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunger {
+    /// How full we are. Goes down every tick, and back up whenever we eat.
+    pub level: i64,
+    /// How we feel about it. Only caught up with the level once a tick, when hunger is processed.
+    pub state: HungerLevel,
+}
+
+impl Hunger {
+    pub fn new(level: i64) -> Self {
+        Self {
+            level,
+            state: HungerLevel::from(level),
+        }
+    }
+
+    /// Set the level, and how we feel about it, straight away.
+    pub fn set(&mut self, level: i64) {
+        *self = Self::new(level);
+    }
+
+    /// Whether we'd eat something if it came along.
+    pub fn is_hungry(&self) -> bool {
+        matches!(self.state, HungerLevel::Hungry | HungerLevel::Starving)
+    }
+
+    /// How badly we need to eat, from 0 while we're full, a quarter as soon as we're hungry, up to 1 once we're
+    /// famished.
+    pub fn urgency(&self) -> f64 {
+        match self.state {
+            HungerLevel::Full => 0.0,
+            _ => ((75 - self.level) as f64 / 100.0).clamp(0.0, 1.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mobility {
+    /// How many tiles this creature can move per turn in the x direction
+    pub max_x: usize,
+    /// How many tiles this creature can move per turn in the y direction
+    pub max_y: usize,
+    /// Where we're headed, if we're out on a migration.
+    pub heading: Option<Heading>,
+    /// How we've been getting on at getting wherever we're going.
+    pub progress: Progress,
+}
+
+/// Where an animal's been the last couple of ticks, for keeping it headed the same way and telling when it's stuck.
+/// Caught up once a tick, while the animal's processed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Progress {
+    /// The last tile we moved off of.
+    pub came_from: Option<Pos>,
+    /// Where we were as of last tick.
+    pub last_seen: Option<Pos>,
+    /// How many ticks in a row we've been after something without getting any closer.
+    pub stuck_ticks: usize,
+    /// How many more ticks we'll wander about for before going after anything again.
+    pub wander_ticks: usize,
+}
+
+/// The edge of the board a migrating animal is trying to reach, and how much longer it'll try for before settling down.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+    pub to: Edge,
+    pub ticks_left: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Age {
+    /// Age, in ticks.
+    pub ticks: usize,
+    /// How old we can possibly be (in ticks) before dying. If None, can't die of old age.
+    pub max: Option<usize>,
+}
+
+impl Age {
+    pub fn new(max: Option<usize>) -> Self {
+        Self { ticks: 0, max }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reproduction {
+    pub sex: Sex,
+    pub pregnant: bool,
+    /// Pregnancy increases by a given step
+    pub pregnancy_level: usize,
+    /// Step by which this creature progresses towards having a child
+    pub pregnancy_step: usize,
+    /// The number of ticks since we last had a child.
+    pub ticks_since_last_mating: usize,
+    /// How long this species takes between mating
+    pub mating_cooldown: usize,
+    /// How many generations removed we are from the animals the board started with.
+    pub generation: usize,
+    /// Whoever we're carrying a child of, if they were being tracked.
+    pub mate: Option<EntityID>,
+    /// Whoever had us. Empty for anything that wasn't born on the board.
+    pub parents: Vec<EntityID>,
+}
+
+impl Reproduction {
+    pub fn new(sex: Sex, pregnancy_step: usize, mating_cooldown: usize) -> Self {
+        Self {
+            sex,
+            pregnant: false,
+            pregnancy_level: 0,
+            pregnancy_step,
+            ticks_since_last_mating: 0,
+            mating_cooldown,
+            generation: 1,
+            mate: None,
+            parents: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Combat {
+    /// How hard a blow lands, before the roll.
+    pub attack: usize,
+    /// How much of every blow gets shrugged off.
+    pub defense: usize,
+    /// If true, it'd rather fight than get eaten, like crabs.
+    pub fights_back: bool,
+    /// How many more ticks it'll keep running from a fight for. It won't go looking for trouble until it's done.
+    pub fleeing: usize,
+    /// Extra defense from a shell it's picked up and is wearing. Only crabs wear shells.
+    pub shell: usize,
+    /// How many tiles away it can get at something to eat it. Most things have to be right next to it.
+    pub reach: usize,
+    /// The odds of getting out of the way of something trying to eat it, when it's fresh and just as quick.
+    pub evasion: f64,
+    /// How much it's got left in it for dodging, out of MAX_STAMINA. Every dodge and every miss wears it down, and it
+    /// comes back a little every tick.
+    pub stamina: usize,
+    /// How many tiles away it can smell blood from. Most things can't at all.
+    pub smell: usize,
+    /// Whether it came out of a fight badly hurt and is bleeding. It stops once it's healed back up.
+    pub wounded: bool,
+}
+
+impl Combat {
+    pub fn new(attack: usize, defense: usize, fights_back: bool) -> Self {
+        Self {
+            attack,
+            defense,
+            fights_back,
+            fleeing: 0,
+            shell: 0,
+            reach: 1,
+            evasion: 0.0,
+            stamina: MAX_STAMINA,
+            smell: 0,
+            wounded: false,
+        }
+    }
+
+    /// Able to eat things further away than right next to it.
+    pub fn with_reach(mut self, reach: usize) -> Self {
+        self.reach = reach;
+        self
+    }
+
+    /// Able to get out of the way of whatever's trying to eat it, some of the time.
+    pub fn with_evasion(mut self, evasion: f64) -> Self {
+        self.evasion = evasion;
+        self
+    }
+
+    /// Able to smell blood in the water from a way off.
+    pub fn with_smell(mut self, smell: usize) -> Self {
+        self.smell = smell;
+        self
+    }
+}
+
+/// What sets one animal apart from the rest of its kind, rolled when it's born. Every trait runs from -1 to 1, with 0
+/// as average as it gets, and nudges how much the animal cares about what it could be doing by up to PERSONALITY_SWAY
+/// either way.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Personality {
+    /// How close to anything that might eat it it'll go for a meal. Timid ones (below 0) keep well clear.
+    pub boldness: f64,
+    /// How much it'd rather potter about than get on with anything.
+    pub laziness: f64,
+    /// How keen it is on finding a mate.
+    pub sociability: f64,
+}
+
+/// How far either way of its species' temperament an animal's personality can land.
+pub const PERSONALITY_SPREAD: f64 = 0.5;
+
+/// How much a trait at either extreme scales whatever it has a say in.
+pub const PERSONALITY_SWAY: f64 = 0.5;
+
+/// How far from average a trait has to be before it's worth mentioning.
+pub const QUIRK_THRESHOLD: f64 = 0.4;
+
+impl Personality {
+    /// Somewhere within PERSONALITY_SPREAD of a species' temperament.
+    pub fn around(temperament: Personality, rng: &mut impl Rng) -> Self {
+        let mut roll = |average: f64| {
+            (average + rng.gen_range(-PERSONALITY_SPREAD..=PERSONALITY_SPREAD)).clamp(-1.0, 1.0)
+        };
+        Self {
+            boldness: roll(temperament.boldness),
+            laziness: roll(temperament.laziness),
+            sociability: roll(temperament.sociability),
+        }
+    }
+
+    /// This temperament, leaned one way or another by an animal's own quirks.
+    pub fn leaning(self, lean: Personality) -> Self {
+        Self {
+            boldness: (self.boldness + lean.boldness).clamp(-1.0, 1.0),
+            laziness: (self.laziness + lean.laziness).clamp(-1.0, 1.0),
+            sociability: (self.sociability + lean.sociability).clamp(-1.0, 1.0),
+        }
+    }
+
+    /// Read a temperament row's numbers in, all of which have to be between -1 and 1.
+    pub fn parse(words: [&str; 3]) -> Option<Self> {
+        let mut numbers = words
+            .into_iter()
+            .map(|w| w.parse::<f64>().ok().filter(|n| (-1.0..=1.0).contains(n)));
+        Some(Self {
+            boldness: numbers.next()??,
+            laziness: numbers.next()??,
+            sociability: numbers.next()??,
+        })
+    }
+
+    /// How much a trait scales whatever it has a say in.
+    pub fn sway(trait_: f64) -> f64 {
+        1.0 + PERSONALITY_SWAY * trait_
+    }
+
+    /// A word for every trait that stands out, like "bold" or "lazy".
+    pub fn quirks(&self) -> Vec<&'static str> {
+        [
+            (self.boldness, "bold", "timid"),
+            (self.laziness, "lazy", "restless"),
+            (self.sociability, "sociable", "aloof"),
+        ]
+        .into_iter()
+        .filter_map(|(value, high, low)| {
+            if value >= QUIRK_THRESHOLD {
+                Some(high)
+            } else if value <= -QUIRK_THRESHOLD {
+                Some(low)
+            } else {
+                None
+            }
+        })
+        .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Growth {
+    /// Amount that the plant has currently grown
+    pub level: usize,
+    /// Point at which the plant should consider changing into another species
+    pub max: usize,
+    /// How much light reached the plant last tick, from the board's light_at. It grows by this much every tick.
+    pub light: f64,
+    /// Growth that's built up but hasn't made it to a whole level yet.
+    pub progress: f64,
+}
+
+impl Growth {
+    pub fn new(max: usize) -> Self {
+        Self {
+            level: 0,
+            max,
+            light: 1.0,
+            progress: 0.0,
+        }
+    }
+
+    /// Soak up nutrients, which get grown through a level a tick (at most) on top of whatever light we're getting.
+    pub fn fertilize(&mut self, nutrients: f64) {
+        self.progress += nutrients;
+    }
+
+    /// Grow by however much light we got. Light never goes over 1, so this never grows more than a level at a time,
+    /// and anything waiting on a particular level won't miss it.
+    pub fn photosynthesize(&mut self) {
+        self.progress += self.light;
+        if self.progress >= 1.0 {
+            self.progress -= 1.0;
+            self.level += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{NonAbstractTaxonomy, Species};
+
+    #[test]
+    fn test_health_stays_in_bounds() {
+        let mut health = Health::new(50);
+        assert!(!health.change(20));
+        assert_eq!(health.hp, 50);
+        assert!(!health.change(-30));
+        assert_eq!(health.hp, 20);
+        assert!(health.change(-100));
+        assert_eq!(health.hp, 0);
+    }
+
+    #[test]
+    fn test_hunger_state_lags_behind() {
+        let mut hunger = Hunger::new(100);
+        assert_eq!(hunger.state, HungerLevel::Full);
+        hunger.level = 10;
+        assert!(!hunger.is_hungry());
+        hunger.set(10);
+        assert!(hunger.is_hungry());
+        hunger.set(-50);
+        assert_eq!(hunger.state, HungerLevel::Famished);
+        assert!(!hunger.is_hungry());
+    }
+
+    #[test]
+    fn test_entities_only_have_their_own_components() {
+        let mut fish = Species::Fish.create_new(None);
+        let kelp = Species::Kelp.create_new(None);
+        let rock = Species::Rock.create_new(None);
+
+        assert!(fish.hunger().is_some() && fish.mobility().is_some());
+        assert!(fish.growth().is_none());
+        assert!(kelp.growth().is_some() && kelp.health().is_some());
+        assert!(kelp.hunger().is_none() && kelp.reproduction().is_none());
+        assert!(rock.health().is_none() && rock.age().is_none());
+
+        fish.hunger_mut().unwrap().set(-5);
+        fish.age_mut().unwrap().ticks = 7;
+        assert!(fish.get_stats().starts_with("hp=100 hunger=-5 age=7"));
+    }
+
+    #[test]
+    fn test_personalities_land_around_their_temperament() {
+        let temperament = Personality {
+            boldness: 0.9,
+            laziness: -0.2,
+            sociability: 0.0,
+        };
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let p = Personality::around(temperament, &mut rng);
+            assert!(p.boldness >= temperament.boldness - PERSONALITY_SPREAD && p.boldness <= 1.0);
+            assert!((p.laziness - temperament.laziness).abs() <= PERSONALITY_SPREAD);
+        }
+
+        let odd = Personality {
+            boldness: 1.0,
+            laziness: -QUIRK_THRESHOLD,
+            sociability: 0.1,
+        };
+        assert_eq!(odd.quirks(), vec!["bold", "restless"]);
+        assert!(Personality::default().quirks().is_empty());
+        assert_eq!(Personality::sway(0.0), 1.0);
+        assert_eq!(
+            Personality::parse(["1", "-1", "0"]),
+            Some(Personality {
+                boldness: 1.0,
+                laziness: -1.0,
+                sociability: 0.0
+            })
+        );
+        assert_eq!(Personality::parse(["1", "-2", "0"]), None);
+
+        // anything put down by hand is as average as its species
+        let fish = Species::Fish.create_new(None);
+        let rules = crate::interaction_matrix::InteractionMatrix::default();
+        assert_eq!(
+            fish.animal().unwrap().personality(&rules),
+            rules.temperament(Species::Fish)
+        );
+    }
+}
@@ -0,0 +1,362 @@
+// Species added from outside this crate, without having to touch the Animals or Plants enums.
+// A downstream crate implements SpeciesPlugin and registers it before setting up a game. From then on the species can be used
+// anywhere a built in one can: scenario files, the console, the spawn palette, and scripts.
+
+use std::sync::RwLock;
+
+use rand::Rng;
+
+use crate::element_traits::{Lives, OffspringData};
+use crate::entity_control::EntityID;
+use crate::error::Error;
+
+use super::animals::{average, AnimalType, Animals};
+use super::plants::{Plant, Plants};
+use super::{Entity, Living, NonAbstractTaxonomy, Sex, Species};
+
+/// Everything a plugin animal needs to get started, which would otherwise be hardcoded for each built in animal.
+#[derive(Debug, Clone)]
+pub struct AnimalStats {
+    pub hp: i64,
+    /// How many ticks it can live for.
+    pub max_age: usize,
+    /// How much closer to giving birth it gets each tick once it's pregnant. Births happen at 100.
+    pub pregnancy_step: usize,
+    /// How many ticks it needs between matings.
+    pub mating_cooldown: usize,
+    /// How many tiles it can move in a tick, in the (x, y) directions.
+    pub speed: (usize, usize),
+    /// How much hungrier it gets every tick, before the difficulty is taken into account. Fish get 2, sharks get 3.
+    pub hunger_drain: i64,
+    /// If true, it needs a partner of the opposite sex to mate like fish and sharks do. Otherwise, it mates like a crab.
+    pub sexed: bool,
+    /// How much damage it does to whatever it's trying to eat, or fighting.
+    pub attack: usize,
+    /// How much of every blow it shrugs off in a fight.
+    pub defense: usize,
+    /// If true, it fights anything that tries to eat it, like crabs do.
+    pub fights_back: bool,
+    /// How much damage it does back to whatever's trying to eat it.
+    pub retaliation: usize,
+    /// How much hunger it restores when it gets eaten.
+    pub food_value: usize,
+    /// How many tiles away it can eat something from. 1 means it has to be right next to it.
+    pub reach: usize,
+    /// The odds of it dodging whatever's trying to eat it, from 0 to 1, before speed and stamina come into it.
+    pub evasion: f64,
+    /// How many tiles away it can smell blood from. 0 means it can't.
+    pub smell: usize,
+}
+
+/// Everything a plugin plant needs to get started.
+#[derive(Debug, Clone)]
+pub struct PlantStats {
+    /// How many bites it takes to finish off.
+    pub hp: i64,
+    /// How long it takes to grow enough to spread.
+    pub max_growth: usize,
+    /// How many ticks it can live for. If None, it never dies of old age.
+    pub max_age: Option<usize>,
+    /// How much hunger it restores when it gets eaten.
+    pub food_value: usize,
+    /// How it spreads once it's grown, like kelp spreading seeds. If None, it never spreads.
+    pub offspring: Option<OffspringData>,
+}
+
+/// What kind of thing a plugin species is, along with its starting stats.
+#[derive(Debug, Clone)]
+pub enum Kingdom {
+    Animal(AnimalStats),
+    Plant(PlantStats),
+}
+
+/// A species that lives outside this crate. Only the name, glyph and stats are required, everything else has a default.
+pub trait SpeciesPlugin: Send + Sync {
+    /// The name used for this species in files and commands. It can't be the same as any other species'.
+    fn name(&self) -> &'static str;
+
+    /// What it looks like on the board.
+    fn glyph(&self) -> char;
+
+    /// What it stands in as when the board is drawn with letters instead.
+    fn letter(&self) -> char {
+        self.name()
+            .chars()
+            .next()
+            .unwrap_or('?')
+            .to_ascii_uppercase()
+    }
+
+    /// The color it's drawn in as a letter, or in screenshots, as RGB. Left out, it gets the same color as anything
+    /// else in its kingdom.
+    fn color(&self) -> Option<[u8; 3]> {
+        None
+    }
+
+    /// Whether it's an animal or a plant, and how it starts out.
+    fn kingdom(&self) -> Kingdom;
+
+    /// Its row of the diet matrix: whether it can eat the given species. Only asked once it's hungry,
+    /// and only about prey that the species config doesn't already have a row for.
+    fn eats(&self, _prey: Species) -> bool {
+        false
+    }
+
+    /// Its column of the diet matrix: whether the given species can eat it, for predators that don't know about it.
+    /// Rows in the species config win out over this, so by default sharks will eat any animal that isn't a shark,
+    /// and fish and crabs will eat any plant, no matter what this says.
+    fn eaten_by(&self, _predator: Species) -> bool {
+        false
+    }
+
+    /// Called every tick while it's alive, after its age and hunger have been taken care of.
+    fn on_tick(&self, _creature: &mut dyn Lives) {}
+
+    /// Called whenever it eats something.
+    fn on_eat(&self, _creature: &mut dyn Lives, _prey: Species) {}
+}
+
+struct Registered {
+    plugin: Box<dyn SpeciesPlugin>,
+    kingdom: Kingdom,
+}
+
+/// Species are passed around by value all over the place, so plugins live here for the rest of the program once registered.
+static REGISTRY: RwLock<Vec<&'static Registered>> = RwLock::new(Vec::new());
+
+/// Add a new species to the game, returning the species to refer to it by.
+pub fn register_species(plugin: impl SpeciesPlugin + 'static) -> Result<Species, Error> {
+    let name = plugin.name();
+    if Species::from_name(name).is_some() {
+        return Err(Error::SpeciesTaken(name.to_string()));
+    }
+    let mut registry = REGISTRY.write().unwrap();
+    // someone else might have snuck in with the same name since we checked
+    if registry.iter().any(|r| r.plugin.name() == name) {
+        return Err(Error::SpeciesTaken(name.to_string()));
+    }
+    let kingdom = plugin.kingdom();
+    registry.push(Box::leak(Box::new(Registered {
+        plugin: Box::new(plugin),
+        kingdom,
+    })));
+    Ok(Species::Custom(CustomSpecies(registry.len() - 1)))
+}
+
+/// Every species that's been registered so far, in the order they were registered.
+pub fn registered_species() -> Vec<Species> {
+    let registry = REGISTRY.read().unwrap();
+    (0..registry.len())
+        .map(|i| Species::Custom(CustomSpecies(i)))
+        .collect()
+}
+
+/// Whether a predator can eat its prey, when at least one of them comes from a plugin. Either side saying yes is enough.
+pub(crate) fn plugin_diet(predator: Species, prey: Species) -> bool {
+    let eats = matches!(predator, Species::Custom(c) if c.plugin().eats(prey));
+    let eaten = matches!(prey, Species::Custom(c) if c.plugin().eaten_by(predator));
+    eats || eaten
+}
+
+/// A species that was added through register_species.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CustomSpecies(usize);
+
+impl CustomSpecies {
+    fn registered(&self) -> &'static Registered {
+        REGISTRY.read().unwrap()[self.0]
+    }
+
+    pub fn plugin(&self) -> &'static dyn SpeciesPlugin {
+        self.registered().plugin.as_ref()
+    }
+
+    pub fn kingdom(&self) -> &'static Kingdom {
+        &self.registered().kingdom
+    }
+
+    pub fn is_animal(&self) -> bool {
+        matches!(self.kingdom(), Kingdom::Animal(_))
+    }
+
+    /// The stats of a species that's known to be an animal, since only animals get made into Animals::Custom.
+    pub(crate) fn animal_stats(&self) -> &'static AnimalStats {
+        match self.kingdom() {
+            Kingdom::Animal(stats) => stats,
+            Kingdom::Plant(_) => unreachable!("{} isn't an animal", self.plugin().name()),
+        }
+    }
+
+    /// How much hunger it restores when it gets eaten, whichever kind of thing it is.
+    pub fn food_value(&self) -> usize {
+        match self.kingdom() {
+            Kingdom::Animal(stats) => stats.food_value,
+            Kingdom::Plant(stats) => stats.food_value,
+        }
+    }
+
+    /// Same as animal_stats, but for plants.
+    pub(crate) fn plant_stats(&self) -> &'static PlantStats {
+        match self.kingdom() {
+            Kingdom::Plant(stats) => stats,
+            Kingdom::Animal(_) => unreachable!("{} isn't a plant", self.plugin().name()),
+        }
+    }
+
+    /// Same as create_new, but using a given source of randomness to pick an animal's sex.
+    pub fn create_new_with_rng(&self, id: Option<EntityID>, rng: &mut impl Rng) -> Entity {
+        let name = self.plugin().name();
+        match self.kingdom() {
+            Kingdom::Animal(stats) => {
+                let sex = match (stats.sexed, rng.gen_bool(0.5)) {
+                    (false, _) => Sex::Neutral,
+                    (true, true) => Sex::Male,
+                    (true, false) => Sex::Female,
+                };
+                let animal = AnimalType::from_stats(name, stats, id, sex);
+                let mut animal = Animals::Custom(*self, animal);
+                animal.roll_personality(rng);
+                Entity::Living(Living::Animals(animal))
+            }
+            Kingdom::Plant(stats) => {
+                let plant = Plant::new(
+                    name.to_owned(),
+                    stats.max_growth,
+                    stats.hp,
+                    stats.max_age,
+                    id,
+                );
+                Entity::Living(Living::Plants(Plants::Custom(*self, plant)))
+            }
+        }
+    }
+}
+
+impl NonAbstractTaxonomy for CustomSpecies {
+    fn create_new(&self, id: Option<EntityID>) -> Entity {
+        average(self.create_new_with_rng(id, &mut rand::thread_rng()))
+    }
+
+    fn same_kind(&self, entity: &Entity) -> bool {
+        Species::of(entity) == Species::Custom(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::OnceLock;
+
+    use super::*;
+    use crate::entities::PTUIDisplay;
+    use crate::game_board::Pos;
+    use crate::interaction_matrix::InteractionMatrix;
+    use crate::interactions::{EatsCreatures, Mates};
+    use crate::test_utils::TestBed;
+
+    /// Eats fish, and gets eaten by nothing but sharks. Burns out after a single tick.
+    struct Eel;
+
+    impl SpeciesPlugin for Eel {
+        fn name(&self) -> &'static str {
+            "eel"
+        }
+
+        fn glyph(&self) -> char {
+            '🐍'
+        }
+
+        fn kingdom(&self) -> Kingdom {
+            Kingdom::Animal(AnimalStats {
+                hp: 80,
+                max_age: 400,
+                pregnancy_step: 5,
+                mating_cooldown: 100,
+                speed: (2, 2),
+                hunger_drain: 2,
+                sexed: false,
+                attack: 40,
+                defense: 10,
+                fights_back: false,
+                retaliation: 60,
+                food_value: 75,
+                reach: 1,
+                evasion: 0.3,
+                smell: 0,
+            })
+        }
+
+        fn eats(&self, prey: Species) -> bool {
+            prey == Species::Fish
+        }
+
+        fn on_tick(&self, creature: &mut dyn Lives) {
+            creature.die("burning out");
+        }
+    }
+
+    /// Registered plugins stick around for the whole test run, so every test has to share the one eel.
+    fn eel() -> Species {
+        static EEL: OnceLock<Species> = OnceLock::new();
+        *EEL.get_or_init(|| register_species(Eel).unwrap())
+    }
+
+    fn animal(entity: Entity) -> Animals {
+        match entity {
+            Entity::Living(Living::Animals(a)) => a,
+            _ => panic!("{entity:?} isn't an animal"),
+        }
+    }
+
+    #[test]
+    fn test_registering() {
+        let eel = eel();
+        assert_eq!(Species::from_name("eel"), Some(eel));
+        assert_eq!(eel.name(), "eel");
+        assert!(Species::all().contains(&eel));
+        assert!(!Species::ALL.contains(&eel));
+        assert!(matches!(
+            register_species(Eel),
+            Err(Error::SpeciesTaken(name)) if name == "eel"
+        ));
+
+        let created = eel.create_new(None);
+        assert_eq!(Species::of(&created), eel);
+        assert!(eel.same_kind(&created));
+        assert_eq!(created.get_display_char(), '🐍');
+    }
+
+    #[test]
+    fn test_diet_and_mating() {
+        let eel = animal(eel().create_new(None));
+        let fish = animal(Species::Fish.create_new(None));
+        let shark = animal(Species::Shark.create_new(None));
+        let rules = InteractionMatrix::default();
+        assert!(eel.can_eat(&fish, &rules));
+        assert!(!eel.can_eat(&shark, &rules));
+        assert!(!fish.can_eat(&eel, &rules));
+        assert!(shark.can_eat(&eel, &rules));
+        assert_eq!(shark.hunger_restored(&eel, &rules), 75);
+
+        // eels mate like crabs, with any other eel, once they've waited long enough
+        let mut other = animal(Species::from_name("eel").unwrap().create_new(None));
+        assert!(!eel.compatible_mate(&other, &rules));
+        let mut eel = eel;
+        for animal in [&mut eel, &mut other] {
+            animal.body_mut().reproduction.ticks_since_last_mating = 100;
+        }
+        assert!(eel.compatible_mate(&other, &rules));
+        assert!(!eel.compatible_mate(&fish, &rules));
+    }
+
+    #[test]
+    fn test_plugin_hooks() {
+        let mut eel = eel().create_new(None);
+        eel.set_stat("hunger", "10").unwrap();
+        assert!(eel.get_stats().contains("hunger=10"));
+
+        let pos = Pos { x: 1, y: 1 };
+        let mut testbed = TestBed::new_with_entities(3, 3, vec![(pos, eel)]);
+        testbed.sandbox.tick();
+        assert!(testbed.sandbox.board.entity_at(pos).is_none());
+    }
+}
@@ -0,0 +1,121 @@
+// How every species looks, in one place. Each one has an emoji for the GUI and any terminal that can show them, a plain
+// character for anywhere that can't (or for players who'd rather not rely on telling emoji apart), the name of its picture
+// for anything drawing sprites, and a color for anything drawing letters or blocks of color, like screenshots.
+// Entities only ever hand out their emoji (see PTUIDisplay), which is what goes into board snapshots and over the
+// network, and it's up to whatever's showing them to look the species back up and pick how to draw it.
+
+use super::Species;
+
+// Colors from the Okabe-Ito palette, which stay distinguishable with most kinds of colorblindness
+const PREY_COLOR: [u8; 3] = [230, 159, 0];
+const PREDATOR_COLOR: [u8; 3] = [213, 94, 0];
+const PLANT_COLOR: [u8; 3] = [0, 158, 115];
+const DECORATION_COLOR: [u8; 3] = [153, 153, 153];
+const HAZARD_COLOR: [u8; 3] = [204, 121, 167];
+
+/// Every way a species can be drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplaySpec {
+    pub emoji: char,
+    /// A plain ASCII character to stand in for it, different from every other species' one.
+    pub ascii: char,
+    /// The name of its picture, for anything drawing sprites.
+    pub sprite: &'static str,
+    /// As RGB.
+    pub color: [u8; 3],
+}
+
+/// Which of a species' glyphs to draw it with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GlyphStyle {
+    #[default]
+    Emoji,
+    Ascii,
+}
+
+impl DisplaySpec {
+    pub fn glyph(&self, style: GlyphStyle) -> char {
+        match style {
+            GlyphStyle::Emoji => self.emoji,
+            GlyphStyle::Ascii => self.ascii,
+        }
+    }
+}
+
+impl Species {
+    /// How this species looks.
+    pub fn display_spec(&self) -> DisplaySpec {
+        let (emoji, ascii, color) = match self {
+            Self::Fish => ('🐠', 'F', PREY_COLOR),
+            Self::Crab => ('🐚', 'C', PREY_COLOR),
+            Self::Shark => ('🐬', 'S', PREDATOR_COLOR),
+            Self::Kelp => ('🌳', 'k', PLANT_COLOR),
+            Self::KelpSeed => ('🌱', ',', PLANT_COLOR),
+            Self::KelpLeaf => ('🌿', 'L', PLANT_COLOR),
+            Self::Egg => ('🥚', 'E', PREY_COLOR),
+            Self::Rock => ('🗿', '#', DECORATION_COLOR),
+            Self::Shell => ('🔲', 'O', DECORATION_COLOR),
+            Self::Rubble => ('🪨', 'b', DECORATION_COLOR),
+            Self::Bones => ('🦴', 'X', DECORATION_COLOR),
+            Self::Urchin => ('🌰', 'U', HAZARD_COLOR),
+            Self::Vent => ('🌋', 'V', HAZARD_COLOR),
+            Self::Reef => ('🪸', 'A', DECORATION_COLOR),
+            Self::Barrier => ('🚧', 'W', DECORATION_COLOR),
+            Self::Feeder => ('🧺', 'D', DECORATION_COLOR),
+            Self::Custom(c) => {
+                let plugin = c.plugin();
+                let kingdom = match c.is_animal() {
+                    true => PREY_COLOR,
+                    false => PLANT_COLOR,
+                };
+                (
+                    plugin.glyph(),
+                    plugin.letter(),
+                    plugin.color().unwrap_or(kingdom),
+                )
+            }
+        };
+        DisplaySpec {
+            emoji,
+            ascii,
+            sprite: self.name(),
+            color,
+        }
+    }
+
+    /// Look up whichever species is drawn with this emoji, like one from a board snapshot.
+    pub fn from_glyph(glyph: char) -> Option<Self> {
+        Self::all()
+            .into_iter()
+            .find(|species| species.display_spec().emoji == glyph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{NonAbstractTaxonomy, PTUIDisplay};
+    use crate::game_board::EMPTY_ASCII;
+
+    #[test]
+    fn test_display_specs() {
+        let specs: Vec<DisplaySpec> = Species::ALL.iter().map(Species::display_spec).collect();
+        for (i, spec) in specs.iter().enumerate() {
+            let species = Species::ALL[i];
+            // whatever's on the board is drawn the way its species says, and can be looked back up from it
+            assert_eq!(species.create_new(None).get_display_char(), spec.emoji);
+            assert_eq!(Species::from_glyph(spec.emoji), Some(species));
+            assert!(spec.ascii.is_ascii_graphic(), "{species:?}");
+            assert_ne!(spec.ascii, EMPTY_ASCII);
+            assert_eq!(spec.sprite, species.name());
+            // no two species can be mistaken for each other, however they're drawn
+            for other in &specs[i + 1..] {
+                assert_ne!(spec.emoji, other.emoji);
+                assert_ne!(spec.ascii, other.ascii);
+            }
+        }
+        assert_eq!(specs[0].glyph(GlyphStyle::Ascii), 'F');
+        assert_eq!(specs[0].glyph(GlyphStyle::default()), '🐠');
+        assert_eq!(Species::from_glyph('?'), None);
+    }
+}
@@ -1,13 +1,29 @@
 pub mod animals;
+pub mod components;
+pub mod custom;
+pub mod display_spec;
 pub mod nonliving;
 pub mod plants;
 
+use async_trait::async_trait;
+
+use crate::element_traits::{PostProcessResult, Processing, ProcessingContext};
 use crate::entity_control::{EntityID, TrackedEntity};
+use crate::game_board::Board;
+use crate::settings::Difficulty;
 
-use self::{animals::Animals, nonliving::Decoration, plants::Plants};
+use self::{
+    animals::{Animals, ConcreteAnimals},
+    components::{Age, Growth, Health, Hunger, Mobility, Reproduction},
+    custom::CustomSpecies,
+    nonliving::{
+        ConcreteDecorations, ConcreteHazards, ConcreteStructures, Decoration, Hazard, Structure,
+    },
+    plants::{ConcretePlants, Plants},
+};
 
 /// Once something reaches this pregancy level, they will start trying to have a child if they can.
-const MAX_PREGNANCY_LEVEL: usize = 100;
+pub(crate) const MAX_PREGNANCY_LEVEL: usize = 100;
 
 /// The maximum number of actions to consider per turn
 const MAXIMUM_ACTIONS_TO_CONSIDER: usize = 5000;
@@ -34,11 +50,164 @@ pub trait PTUIDisplay {
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)] // most of what's on the board is alive, so boxing it would only add a hop to every lookup
 pub enum Entity {
     Living(Living),
     NonLiving(NonLiving),
 }
 
+impl Entity {
+    /// Override one of this entity's stats by name, like "hunger" or "hp".
+    pub fn set_stat(&mut self, stat: &str, value: &str) -> Result<(), String> {
+        match self {
+            Self::Living(Living::Animals(a)) => a.body_mut().set_stat(stat, value),
+            Self::Living(Living::Plants(p)) => p.set_stat(stat, value),
+            Self::NonLiving(_) => Err(format!("decorations don't have a stat called '{stat}'")),
+        }
+    }
+
+    /// Get every stat that can be set on this entity, written out as stat=value.
+    pub fn get_stats(&self) -> String {
+        match self {
+            Self::Living(Living::Animals(a)) => a.body().get_stats(),
+            Self::Living(Living::Plants(p)) => p.get_stats(),
+            Self::NonLiving(_) => String::new(),
+        }
+    }
+
+    pub fn living(&self) -> Option<&Living> {
+        match self {
+            Self::Living(l) => Some(l),
+            Self::NonLiving(_) => None,
+        }
+    }
+
+    pub fn living_mut(&mut self) -> Option<&mut Living> {
+        match self {
+            Self::Living(l) => Some(l),
+            Self::NonLiving(_) => None,
+        }
+    }
+
+    pub fn animal(&self) -> Option<&Animals> {
+        match self {
+            Self::Living(Living::Animals(a)) => Some(a),
+            _ => None,
+        }
+    }
+
+    pub fn animal_mut(&mut self) -> Option<&mut Animals> {
+        match self {
+            Self::Living(Living::Animals(a)) => Some(a),
+            _ => None,
+        }
+    }
+
+    pub fn plant(&self) -> Option<&Plants> {
+        match self {
+            Self::Living(Living::Plants(p)) => Some(p),
+            _ => None,
+        }
+    }
+
+    pub fn plant_mut(&mut self) -> Option<&mut Plants> {
+        match self {
+            Self::Living(Living::Plants(p)) => Some(p),
+            _ => None,
+        }
+    }
+
+    // Each component lives on whichever kinds of entity have it. Decorations don't have any, other than a hazard's sting.
+
+    pub fn health(&self) -> Option<&Health> {
+        match self.living()? {
+            Living::Animals(a) => Some(&a.body().health),
+            Living::Plants(p) => Some(&p.body().health),
+        }
+    }
+
+    pub fn health_mut(&mut self) -> Option<&mut Health> {
+        match self.living_mut()? {
+            Living::Animals(a) => Some(&mut a.body_mut().health),
+            Living::Plants(p) => Some(&mut p.body_mut().health),
+        }
+    }
+
+    pub fn age(&self) -> Option<&Age> {
+        match self.living()? {
+            Living::Animals(a) => Some(&a.body().age),
+            Living::Plants(p) => Some(&p.body().age),
+        }
+    }
+
+    pub fn age_mut(&mut self) -> Option<&mut Age> {
+        match self.living_mut()? {
+            Living::Animals(a) => Some(&mut a.body_mut().age),
+            Living::Plants(p) => Some(&mut p.body_mut().age),
+        }
+    }
+
+    /// Only animals get hungry.
+    pub fn hunger(&self) -> Option<&Hunger> {
+        self.animal().map(|a| &a.body().hunger)
+    }
+
+    pub fn hunger_mut(&mut self) -> Option<&mut Hunger> {
+        self.animal_mut().map(|a| &mut a.body_mut().hunger)
+    }
+
+    /// Only animals move around.
+    pub fn mobility(&self) -> Option<&Mobility> {
+        self.animal().map(|a| &a.body().mobility)
+    }
+
+    pub fn mobility_mut(&mut self) -> Option<&mut Mobility> {
+        self.animal_mut().map(|a| &mut a.body_mut().mobility)
+    }
+
+    /// Only animals mate. Plants spread on their own once they've grown, see growth.
+    pub fn reproduction(&self) -> Option<&Reproduction> {
+        self.animal().map(|a| &a.body().reproduction)
+    }
+
+    pub fn reproduction_mut(&mut self) -> Option<&mut Reproduction> {
+        self.animal_mut().map(|a| &mut a.body_mut().reproduction)
+    }
+
+    /// Only plants grow.
+    pub fn growth(&self) -> Option<&Growth> {
+        self.plant().map(|p| &p.body().growth)
+    }
+
+    pub fn growth_mut(&mut self) -> Option<&mut Growth> {
+        self.plant_mut().map(|p| &mut p.body_mut().growth)
+    }
+
+    /// Whether it's big enough to hide behind. Rocks and full grown kelp are, and nothing else is.
+    pub fn gives_cover(&self) -> bool {
+        matches!(
+            self,
+            Self::NonLiving(NonLiving::Rock(_)) | Self::Living(Living::Plants(Plants::Kelp(_)))
+        )
+    }
+
+    /// Only hazards hurt whatever's around them.
+    pub fn hazard(&self) -> Option<&Hazard> {
+        match self {
+            Self::NonLiving(n) => n.hazard(),
+            Self::Living(_) => None,
+        }
+    }
+
+    /// Only things the player built are structures.
+    pub fn structure_mut(&mut self) -> Option<&mut Structure> {
+        match self {
+            Self::NonLiving(n) => n.structure_mut(),
+            Self::Living(_) => None,
+        }
+    }
+}
+
 impl PTUIDisplay for Entity {
     fn get_display_char(&self) -> char {
         match &self {
@@ -81,23 +250,122 @@ impl TrackedEntity for Entity {
 pub enum NonLiving {
     Rock(Decoration),
     Shell(Decoration),
+    /// What's left of a rock once it's been shattered. It washes away after a while.
+    Rubble(Decoration),
+    /// What's left of something big once it's died. It crumbles away after a while, feeding the plants around it.
+    Bones(Decoration),
+    Urchin(Hazard),
+    Vent(Hazard),
+    /// Built by the player, feeding the plants around it.
+    Reef(Structure),
+    /// Built by the player, keeping sharks away from it.
+    Barrier(Structure),
+    /// Built by the player, putting out food every so often.
+    Feeder(Structure),
+}
+
+impl NonLiving {
+    /// What makes this dangerous to be around, if anything.
+    pub fn hazard(&self) -> Option<&Hazard> {
+        match self {
+            Self::Urchin(h) | Self::Vent(h) => Some(h),
+            _ => None,
+        }
+    }
+
+    /// The state of plain scenery, which hazards don't have.
+    pub fn decoration_mut(&mut self) -> Option<&mut Decoration> {
+        match self {
+            Self::Rock(d) | Self::Shell(d) | Self::Rubble(d) | Self::Bones(d) => Some(d),
+            _ => None,
+        }
+    }
+
+    /// The state of something the player built.
+    pub fn structure_mut(&mut self) -> Option<&mut Structure> {
+        match self {
+            Self::Reef(s) | Self::Barrier(s) | Self::Feeder(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Whether a crab could pick it up and wear it.
+    pub fn can_be_carried(&self) -> bool {
+        matches!(self, Self::Shell(_))
+    }
+
+    /// Whether spreading plants can grow right over it, swallowing it up.
+    pub fn can_be_overgrown(&self) -> bool {
+        matches!(self, Self::Shell(_) | Self::Rubble(_) | Self::Bones(_))
+    }
+
+    pub fn species(&self) -> Species {
+        match self {
+            Self::Rock(_) => Species::Rock,
+            Self::Shell(_) => Species::Shell,
+            Self::Rubble(_) => Species::Rubble,
+            Self::Bones(_) => Species::Bones,
+            Self::Urchin(_) => Species::Urchin,
+            Self::Vent(_) => Species::Vent,
+            Self::Reef(_) => Species::Reef,
+            Self::Barrier(_) => Species::Barrier,
+            Self::Feeder(_) => Species::Feeder,
+        }
+    }
 }
 
 impl PTUIDisplay for NonLiving {
     fn get_display_char(&self) -> char {
-        match &self {
-            Self::Rock(_) => '🗿',
-            Self::Shell(_) => '🔲',
-        }
+        self.species().display_spec().emoji
     }
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)] // same as Entity: there are plenty of animals about, and they'd all pay for the hop
 pub enum Living {
     Plants(Plants),
     Animals(Animals),
 }
 
+/// Anything living processes like the animal or plant it is, so the systems don't have to care which.
+#[async_trait]
+impl Processing for Living {
+    fn will_process(&self) -> bool {
+        match self {
+            Self::Plants(p) => p.will_process(),
+            Self::Animals(a) => a.will_process(),
+        }
+    }
+
+    fn will_process_late(&self) -> bool {
+        match self {
+            Self::Plants(p) => p.will_process_late(),
+            Self::Animals(a) => a.will_process_late(),
+        }
+    }
+
+    fn will_ever_process(&self) -> bool {
+        match self {
+            Self::Plants(p) => p.will_ever_process(),
+            Self::Animals(a) => a.will_ever_process(),
+        }
+    }
+
+    fn process(&mut self, board: &mut Board, ctx: ProcessingContext) -> Option<PostProcessResult> {
+        match self {
+            Self::Plants(p) => p.process(board, ctx),
+            Self::Animals(a) => a.process(board, ctx),
+        }
+    }
+
+    async fn late_process(&mut self, difficulty: Difficulty) -> Option<PostProcessResult> {
+        match self {
+            Self::Plants(p) => p.late_process(difficulty).await,
+            Self::Animals(a) => a.late_process(difficulty).await,
+        }
+    }
+}
+
 impl PTUIDisplay for Living {
     fn get_display_char(&self) -> char {
         match &self {
@@ -108,6 +376,144 @@ impl PTUIDisplay for Living {
     }
 }
 
+/// Every concrete kind of entity that can end up on the board.
+/// Handy whenever we need to refer to an entity by name, like when reading one in from a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Species {
+    Fish,
+    Crab,
+    Shark,
+    Kelp,
+    KelpSeed,
+    KelpLeaf,
+    /// Laid by animals instead of giving birth, and hatches into one of them. See plants::egg.
+    Egg,
+    Rock,
+    Shell,
+    Rubble,
+    Bones,
+    Urchin,
+    Vent,
+    Reef,
+    Barrier,
+    Feeder,
+    /// A species added by a plugin, through custom::register_species.
+    Custom(CustomSpecies),
+}
+
+impl Species {
+    pub const ALL: [Species; 16] = [
+        Species::Fish,
+        Species::Crab,
+        Species::Shark,
+        Species::Kelp,
+        Species::KelpSeed,
+        Species::KelpLeaf,
+        Species::Egg,
+        Species::Rock,
+        Species::Shell,
+        Species::Rubble,
+        Species::Bones,
+        Species::Urchin,
+        Species::Vent,
+        Species::Reef,
+        Species::Barrier,
+        Species::Feeder,
+    ];
+
+    /// Every species there is, including ones added by plugins. ALL only has the built in ones.
+    pub fn all() -> Vec<Species> {
+        Self::ALL
+            .into_iter()
+            .chain(custom::registered_species())
+            .collect()
+    }
+
+    /// The name we use for this species in files and commands.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Fish => "fish",
+            Self::Crab => "crab",
+            Self::Shark => "shark",
+            Self::Kelp => "kelp",
+            Self::KelpSeed => "kelp_seed",
+            Self::KelpLeaf => "kelp_leaf",
+            Self::Egg => "egg",
+            Self::Rock => "rock",
+            Self::Shell => "shell",
+            Self::Rubble => "rubble",
+            Self::Bones => "bones",
+            Self::Urchin => "urchin",
+            Self::Vent => "vent",
+            Self::Reef => "reef",
+            Self::Barrier => "barrier",
+            Self::Feeder => "feeder",
+            Self::Custom(c) => c.plugin().name(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().into_iter().find(|s| s.name() == name)
+    }
+
+    pub fn is_animal(&self) -> bool {
+        match self {
+            Self::Fish | Self::Crab | Self::Shark => true,
+            Self::Custom(c) => c.is_animal(),
+            _ => false,
+        }
+    }
+
+    pub fn is_plant(&self) -> bool {
+        match self {
+            Self::Kelp | Self::KelpSeed | Self::KelpLeaf => true,
+            Self::Custom(c) => !c.is_animal(),
+            _ => false,
+        }
+    }
+
+    /// Get the species of an existing entity.
+    pub fn of(entity: &Entity) -> Self {
+        match entity {
+            Entity::Living(Living::Animals(a)) => a.species(),
+            Entity::Living(Living::Plants(p)) => p.species(),
+            Entity::NonLiving(n) => n.species(),
+        }
+    }
+}
+
+impl NonAbstractTaxonomy for Species {
+    fn create_new(&self, id: Option<EntityID>) -> Entity {
+        match self {
+            Self::Fish => ConcreteAnimals::Fish.create_new(id),
+            Self::Crab => ConcreteAnimals::Crab.create_new(id),
+            Self::Shark => ConcreteAnimals::Shark.create_new(id),
+            Self::Kelp => ConcretePlants::Kelp.create_new(id),
+            Self::KelpSeed => ConcretePlants::KelpSeed.create_new(id),
+            Self::KelpLeaf => ConcretePlants::KelpLeaf.create_new(id),
+            // an egg that wasn't laid by anything has a fish in it
+            Self::Egg => match ConcreteAnimals::Fish.create_new(None) {
+                Entity::Living(Living::Animals(fish)) => plants::egg(fish, id),
+                _ => unreachable!(),
+            },
+            Self::Rock => ConcreteDecorations::Rock.create_new(id),
+            Self::Shell => ConcreteDecorations::Shell.create_new(id),
+            Self::Rubble => ConcreteDecorations::Rubble.create_new(id),
+            Self::Bones => ConcreteDecorations::Bones.create_new(id),
+            Self::Urchin => ConcreteHazards::Urchin.create_new(id),
+            Self::Vent => ConcreteHazards::Vent.create_new(id),
+            Self::Reef => ConcreteStructures::Reef.create_new(id),
+            Self::Barrier => ConcreteStructures::Barrier.create_new(id),
+            Self::Feeder => ConcreteStructures::Feeder.create_new(id),
+            Self::Custom(c) => c.create_new(id),
+        }
+    }
+
+    fn same_kind(&self, entity: &Entity) -> bool {
+        Self::of(entity) == *self
+    }
+}
+
 pub fn generate_creatures<T>(number_to_gen: usize, class: T) -> Vec<Entity>
 where
     T: NonAbstractTaxonomy,
@@ -1,21 +1,36 @@
 use crate::entity_control::EntityID;
 
-use super::{Entity, NonAbstractTaxonomy, NonLiving, PTUIDisplay};
+use super::{Entity, NonAbstractTaxonomy, NonLiving, PTUIDisplay, Species};
+
+/// How many hard knocks a rock can take before it shatters.
+pub const ROCK_DURABILITY: usize = 3;
+
+/// How much harder a crab is to hurt while it's wearing a shell.
+pub const SHELL_DEFENSE_BONUS: usize = 15;
+
+/// The odds of a bit of rubble washing away on any given tick.
+pub const RUBBLE_WASH_AWAY_CHANCE: f64 = 0.01;
+
+/// The odds of a pile of bones crumbling away on any given tick.
+pub const BONES_CRUMBLE_CHANCE: f64 = 0.02;
+
+/// How many ticks of growth at full light the plants around a pile of bones get once it's crumbled into them.
+pub const BONE_NUTRIENTS: f64 = 10.0;
 
 pub enum ConcreteDecorations {
     Rock,
     Shell,
+    Rubble,
+    Bones,
 }
 
 impl NonAbstractTaxonomy for ConcreteDecorations {
     fn create_new(&self, _: Option<EntityID>) -> Entity {
         let new_creature = match self {
-            Self::Rock => NonLiving::Rock(Decoration {
-                name: "rock".to_owned(),
-            }),
-            Self::Shell => NonLiving::Shell(Decoration {
-                name: "shell".to_owned(),
-            }),
+            Self::Rock => NonLiving::Rock(Decoration::new("rock", ROCK_DURABILITY)),
+            Self::Shell => NonLiving::Shell(Decoration::new("shell", 1)),
+            Self::Rubble => NonLiving::Rubble(Decoration::new("rubble", 1)),
+            Self::Bones => NonLiving::Bones(Decoration::new("bones", 1)),
         };
 
         Entity::NonLiving(new_creature)
@@ -26,6 +41,13 @@ impl NonAbstractTaxonomy for ConcreteDecorations {
             Entity::NonLiving(nl) => match nl {
                 NonLiving::Rock(_) => matches!(self, ConcreteDecorations::Rock),
                 NonLiving::Shell(_) => matches!(self, ConcreteDecorations::Shell),
+                NonLiving::Rubble(_) => matches!(self, ConcreteDecorations::Rubble),
+                NonLiving::Bones(_) => matches!(self, ConcreteDecorations::Bones),
+                NonLiving::Urchin(_)
+                | NonLiving::Vent(_)
+                | NonLiving::Reef(_)
+                | NonLiving::Barrier(_)
+                | NonLiving::Feeder(_) => false,
             },
             _ => false,
         }
@@ -35,10 +57,131 @@ impl NonAbstractTaxonomy for ConcreteDecorations {
 #[derive(Debug, Clone)]
 pub struct Decoration {
     pub name: String,
+    /// How many more knocks it can take before it breaks.
+    pub durability: usize,
+}
+
+impl Decoration {
+    pub fn new(name: &str, durability: usize) -> Self {
+        Self {
+            name: name.to_owned(),
+            durability,
+        }
+    }
+
+    /// Knock it about a bit. Returns true once it's taken all it can.
+    pub fn wear(&mut self, knocks: usize) -> bool {
+        self.durability = self.durability.saturating_sub(knocks);
+        self.durability == 0
+    }
 }
 
 impl PTUIDisplay for Decoration {
+    /// Drawn like the species it's named after, or as the first letter of its name if there isn't one.
     fn get_display_char(&self) -> char {
-        return self.name.chars().next().unwrap();
+        Species::from_name(&self.name).map_or_else(
+            || self.name.chars().next().unwrap(),
+            |species| species.display_spec().emoji,
+        )
+    }
+}
+
+/// How much an urchin's spines hurt anything that gets too close.
+pub const URCHIN_DAMAGE: i64 = 10;
+
+/// Vents scald anything that gets too close, which hurts a lot more than spines.
+pub const VENT_DAMAGE: i64 = 25;
+
+/// Things that don't live, but still hurt whatever swims up next to them or tries to go through them.
+pub enum ConcreteHazards {
+    Urchin,
+    Vent,
+}
+
+impl NonAbstractTaxonomy for ConcreteHazards {
+    fn create_new(&self, _: Option<EntityID>) -> Entity {
+        let new_hazard = match self {
+            Self::Urchin => NonLiving::Urchin(Hazard {
+                name: "urchin".to_owned(),
+                damage: URCHIN_DAMAGE,
+            }),
+            Self::Vent => NonLiving::Vent(Hazard {
+                name: "vent".to_owned(),
+                damage: VENT_DAMAGE,
+            }),
+        };
+
+        Entity::NonLiving(new_hazard)
+    }
+
+    fn same_kind(&self, entity: &Entity) -> bool {
+        match entity {
+            Entity::NonLiving(NonLiving::Urchin(_)) => matches!(self, ConcreteHazards::Urchin),
+            Entity::NonLiving(NonLiving::Vent(_)) => matches!(self, ConcreteHazards::Vent),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Hazard {
+    pub name: String,
+    /// How much it hurts every time something moves up next to it.
+    pub damage: i64,
+}
+
+/// How many ticks a feeder takes to put out each bit of food.
+pub const FEEDER_INTERVAL: usize = 20;
+
+/// Things the player builds for the colony, which each do something to the tiles around them every tick.
+pub enum ConcreteStructures {
+    /// Feeds the plants growing around it.
+    Reef,
+    /// Keeps sharks from coming near it.
+    Barrier,
+    /// Puts out a bit of food every so often.
+    Feeder,
+}
+
+impl NonAbstractTaxonomy for ConcreteStructures {
+    fn create_new(&self, _: Option<EntityID>) -> Entity {
+        let new_structure = match self {
+            Self::Reef => NonLiving::Reef(Structure::new("reef", 0)),
+            Self::Barrier => NonLiving::Barrier(Structure::new("barrier", 0)),
+            Self::Feeder => NonLiving::Feeder(Structure::new("feeder", FEEDER_INTERVAL)),
+        };
+
+        Entity::NonLiving(new_structure)
+    }
+
+    fn same_kind(&self, entity: &Entity) -> bool {
+        match entity {
+            Entity::NonLiving(NonLiving::Reef(_)) => matches!(self, ConcreteStructures::Reef),
+            Entity::NonLiving(NonLiving::Barrier(_)) => matches!(self, ConcreteStructures::Barrier),
+            Entity::NonLiving(NonLiving::Feeder(_)) => matches!(self, ConcreteStructures::Feeder),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Structure {
+    pub name: String,
+    /// How many more ticks until it does its thing again, for anything that doesn't do it every tick.
+    pub cooldown: usize,
+}
+
+impl Structure {
+    pub fn new(name: &str, cooldown: usize) -> Self {
+        Self {
+            name: name.to_owned(),
+            cooldown,
+        }
+    }
+
+    /// Count down a tick. Returns true once it's ready to do its thing again, and stays ready until it's wound back up.
+    pub fn count_down(&mut self) -> bool {
+        self.cooldown = self.cooldown.saturating_sub(1);
+        self.cooldown == 0
     }
 }
@@ -1,20 +1,37 @@
-use std::cmp::{max, min};
-
 use async_trait::async_trait;
 use log::info;
+use rand::seq::IteratorRandom;
+use rand::Rng;
 
 use crate::{
     element_traits::{
-        Growing, LifeStatus, Lives, OffspringData, PostProcessResult, Processing,
+        Growing, LifeStatus, Lives, Mobile, OffspringData, PostProcessResult, Processing,
         ProcessingContext, Reproducing,
     },
     entity_control::{EntityID, TrackedEntity},
-    game_board::Board,
+    error::Error,
+    game_board::{Board, Current},
     interactions::{EatResult, Eaten},
+    settings::Difficulty,
     Pos,
 };
 
-use super::{Entity, Living, NonAbstractTaxonomy, PTUIDisplay};
+use super::animals::Animals;
+use super::components::{Age, Growth, Health};
+use super::custom::CustomSpecies;
+use super::{Entity, Living, NonAbstractTaxonomy, PTUIDisplay, Species};
+
+/// How many ticks a new kelp seed drifts along with the current before it roots and starts to grow.
+pub const SEED_DRIFT_TICKS: usize = 5;
+
+/// How many ticks an egg takes to hatch once it's been laid.
+pub const INCUBATION_TICKS: usize = 30;
+
+/// Lay an egg with `hatchling` waiting inside it.
+pub fn egg(hatchling: Animals, id: Option<EntityID>) -> Entity {
+    let shell = Plant::new("egg".to_owned(), INCUBATION_TICKS, 1, None, id);
+    Entity::Living(Living::Plants(Plants::Egg(Box::new(hatchling), shell)))
+}
 
 // only add the plants we'll see on spawn here
 pub enum ConcretePlants {
@@ -31,7 +48,11 @@ impl NonAbstractTaxonomy for ConcretePlants {
                 // kelp will last a long time on its own
             }
             Self::KelpLeaf => Plants::KelpLeaf(Plant::new("kelp_leaf".to_owned(), 15, 1, None, id)),
-            Self::KelpSeed => Plants::KelpSeed(Plant::new("kelp_seed".to_owned(), 50, 1, None, id)),
+            Self::KelpSeed => {
+                let mut seed = Plant::new("kelp_seed".to_owned(), 50, 1, None, id);
+                seed.drift = SEED_DRIFT_TICKS;
+                Plants::KelpSeed(seed)
+            }
         };
 
         Entity::Living(Living::Plants(new_plant))
@@ -43,6 +64,7 @@ impl NonAbstractTaxonomy for ConcretePlants {
                 Plants::KelpSeed(_) => matches!(self, Self::KelpSeed),
                 Plants::KelpLeaf(_) => matches!(self, Self::KelpLeaf),
                 Plants::Kelp(_) => matches!(self, Self::Kelp),
+                Plants::Egg(..) | Plants::Custom(..) => false,
             },
             _ => false,
         }
@@ -54,19 +76,119 @@ pub enum Plants {
     Kelp(Plant),
     KelpSeed(Plant),
     KelpLeaf(Plant),
+    /// An animal that hasn't hatched yet. Eggs aren't plants, but they sit still, grow and get eaten just like them,
+    /// so they're kept here. They grow by a level every tick, whatever the light, and hatch once they're fully grown.
+    Egg(Box<Animals>, Plant),
+    /// A plant added by a plugin.
+    Custom(CustomSpecies, Plant),
+}
+
+impl Plants {
+    pub fn species(&self) -> Species {
+        match self {
+            Self::Kelp(_) => Species::Kelp,
+            Self::KelpSeed(_) => Species::KelpSeed,
+            Self::KelpLeaf(_) => Species::KelpLeaf,
+            Self::Egg(..) => Species::Egg,
+            Self::Custom(c, _) => Species::Custom(*c),
+        }
+    }
+
+    /// Get at the components every plant is made of, whatever species it is.
+    pub fn body(&self) -> &Plant {
+        match self {
+            Self::Kelp(p)
+            | Self::KelpSeed(p)
+            | Self::KelpLeaf(p)
+            | Self::Egg(_, p)
+            | Self::Custom(_, p) => p,
+        }
+    }
+
+    pub fn body_mut(&mut self) -> &mut Plant {
+        match self {
+            Self::Kelp(p)
+            | Self::KelpSeed(p)
+            | Self::KelpLeaf(p)
+            | Self::Egg(_, p)
+            | Self::Custom(_, p) => p,
+        }
+    }
+
+    /// Override one of our stats by name. On top of everything a plant has, eggs know what's going to hatch out of them,
+    /// as `hatches`, though anything hatched that way is a first generation with no parents.
+    pub fn set_stat(&mut self, stat: &str, value: &str) -> Result<(), String> {
+        match (self, stat) {
+            (Self::Egg(hatchling, _), "hatches") => {
+                let species = Species::from_name(value).filter(Species::is_animal);
+                let Some(Entity::Living(Living::Animals(a))) = species.map(|s| s.create_new(None))
+                else {
+                    return Err(format!("'{value}' can't hatch out of an egg"));
+                };
+                **hatchling = a;
+                Ok(())
+            }
+            (plant, _) => plant.body_mut().set_stat(stat, value),
+        }
+    }
+
+    /// Get all of the stats that can be set through set_stat, written out as stat=value.
+    pub fn get_stats(&self) -> String {
+        match self {
+            Self::Egg(hatchling, p) => {
+                format!("{} hatches={}", p.get_stats(), hatchling.species().name())
+            }
+            plant => plant.body().get_stats(),
+        }
+    }
+
+    /// What's going to hatch out of us, if we're an egg.
+    pub fn hatches_into(&self) -> Option<Species> {
+        match self {
+            Self::Egg(hatchling, _) => Some(hatchling.species()),
+            _ => None,
+        }
+    }
+
+    /// Whether we're still a seed floating about, and haven't rooted yet.
+    pub fn drifting(&self) -> bool {
+        self.body().drift > 0
+    }
+
+    /// Where we'd like to drift to this tick: one tile downstream, or any empty tile nearby if the water's still.
+    pub fn drift_move(&self, pos: Pos, board: &Board, rng: &mut impl Rng) -> Option<Pos> {
+        if !self.drifting() {
+            return None;
+        }
+        match board.get_current() {
+            Current::Still => board
+                .neighbors(pos)
+                .filter(|p| !board.get_tile_from_pos(*p).is_occupied())
+                .choose(rng),
+            current => current.downstream(pos).filter(|p| board.is_valid_pos(*p)),
+        }
+    }
+}
+
+impl Mobile for Plants {
+    /// Seeds get carried a tile at a time while they drift, and everything else stays put.
+    fn max_speeds(&self) -> (usize, usize) {
+        if self.drifting() {
+            (1, 1)
+        } else {
+            (0, 0)
+        }
+    }
 }
 
 impl Eaten for Plants {
     fn on_eat(&mut self, _: usize) -> Option<Vec<EatResult>> {
         // regardless of attack damage,
-        match self {
-            Self::Kelp(p) | Self::KelpLeaf(p) | Self::KelpSeed(p) => {
-                println!("{p:?} was eaten!");
-                p.hp -= 1;
-                if p.hp == 0 {
-                    self.die("eaten")
-                }
-            }
+        let p = self.body_mut();
+        info!("{p:?} was eaten!");
+        p.health.hp -= 1;
+        if p.health.hp == 0 {
+            self.die("eaten")
         }
         Some(vec![EatResult::Eaten])
     }
@@ -86,28 +208,39 @@ impl Growing for Plants {
             Self::Kelp(_) => None,
             Self::KelpLeaf(_) => Some(ConcretePlants::Kelp.create_new(self.get_id())),
             Self::KelpSeed(_) => Some(ConcretePlants::KelpLeaf.create_new(self.get_id())),
+            // hatchlings keep the egg's ID, the same way growing plants do
+            Self::Egg(hatchling, _) => {
+                let mut hatchling = (**hatchling).clone();
+                if let Some(id) = self.get_id() {
+                    hatchling.register(id).ok();
+                }
+                Some(Entity::Living(Living::Animals(hatchling)))
+            }
+            // plugin plants stay the same thing their whole lives
+            Self::Custom(..) => None,
         }
     }
 
-    /// Increase our growth level
+    /// Increase our growth level, by more the more light we're getting
     fn grow_step(&mut self) {
         match self {
-            Self::Kelp(p) | Self::KelpLeaf(p) | Self::KelpSeed(p) => p.growth_level += 1,
+            // eggs are kept warm whether the sun's out or not
+            Self::Egg(_, p) => p.growth.level += 1,
+            _ => self.body_mut().growth.photosynthesize(),
         }
     }
 
     fn slow_growth(&mut self, factor: usize) {
-        match self {
-            Self::Kelp(p) | Self::KelpLeaf(p) | Self::KelpSeed(p) => {
-                let less_growth = p.growth_level as f64 / factor as f64;
-                p.growth_level -= less_growth.ceil() as usize;
-            }
-        }
+        let growth = &mut self.body_mut().growth;
+        let less_growth = growth.level as f64 / factor as f64;
+        growth.level -= less_growth.ceil() as usize;
     }
 
     fn ready_to_grow_into(&self) -> bool {
         match self {
-            Self::KelpLeaf(p) | Self::KelpSeed(p) => p.growth_level >= p.max_growth,
+            Self::KelpLeaf(p) | Self::KelpSeed(p) | Self::Egg(_, p) => {
+                p.growth.level >= p.growth.max
+            }
             _ => false, // don't let kelp "grow", though TODO it eventually should
         }
     }
@@ -116,13 +249,28 @@ impl Growing for Plants {
 impl Reproducing for Plants {
     fn ready_to_reproduce(&self) -> bool {
         match self {
-            Self::Kelp(p) => p.growth_level % p.max_growth == 0 && p.growth_level > 0,
+            Self::Kelp(p) => p.growth.level % p.growth.max == 0 && p.growth.level > 0,
+            Self::Custom(c, p) => {
+                c.plant_stats().offspring.is_some()
+                    && p.growth.level % p.growth.max == 0
+                    && p.growth.level > 0
+            }
             _ => false,
         }
     }
 
     fn on_offspring_created(&mut self) {}
 
+    /// Seeds grow up into whatever dropped them.
+    fn offspring_species(&self) -> Species {
+        self.species()
+    }
+
+    /// Kelp takes root right on top of shells and rubble, swallowing them up.
+    fn grows_over_scenery(&self) -> bool {
+        true
+    }
+
     fn get_offspring_data(&self) -> Option<OffspringData> {
         match self {
             Self::Kelp(_) => {
@@ -133,29 +281,32 @@ impl Reproducing for Plants {
                     percent_chance_per_tile: 0.1,
                 })
             }
+            Self::Custom(c, _) => c.plant_stats().offspring.clone(),
             _ => None,
         }
     }
 
-    fn have_child(&mut self, tile: &mut crate::Tile, _pos: Pos, _children_so_far: usize) {
+    fn have_child(
+        &mut self,
+        board: &mut Board,
+        pos: Pos,
+        _children_so_far: usize,
+        _rng: &mut impl Rng,
+    ) -> Result<Option<Pos>, Error> {
         let seed = match self {
             // it'll be assigned its ID when added
             Plants::Kelp(_) => Some(ConcretePlants::KelpSeed.create_new(None)),
+            // plugin plants spread copies of themselves
+            Plants::Custom(c, _) => Some(c.create_new(None)),
             _ => None,
         };
-        if let Some(s) = seed {
-            tile.add_entity(s).unwrap()
-        }
+        seed.map(|s| board.place_nearby(pos, s)).transpose()
     }
 }
 
 impl PTUIDisplay for Plants {
     fn get_display_char(&self) -> char {
-        match &self {
-            Self::Kelp(_) => '🌳',
-            Self::KelpSeed(_) => '🌱',
-            Self::KelpLeaf(_) => '🌿',
-        }
+        self.species().display_spec().emoji
     }
 }
 
@@ -165,65 +316,53 @@ impl Lives for Plants {
     }
 
     fn modify_health(&mut self, delta: i64, cause: &str) {
-        match self {
-            Self::Kelp(p) | Self::KelpSeed(p) | Self::KelpLeaf(p) => {
-                p.hp = max(0, min(p.hp_max, p.hp + delta));
-                if p.hp == 0 {
-                    self.die(cause);
-                }
-            }
+        if self.body_mut().health.change(delta) {
+            self.die(cause);
         }
     }
 
     fn get_health(&self) -> i64 {
-        match &self {
-            Self::Kelp(p) | Self::KelpSeed(p) | Self::KelpLeaf(p) => p.hp,
-        }
+        self.body().health.hp
     }
 
     fn get_life_status(&self) -> LifeStatus {
-        match &self {
-            Self::Kelp(p) | Self::KelpSeed(p) | Self::KelpLeaf(p) => {
-                if p.has_died {
-                    LifeStatus::Dead
-                } else {
-                    LifeStatus::Alive
-                }
-            }
+        if self.body().health.has_died {
+            LifeStatus::Dead
+        } else {
+            LifeStatus::Alive
         }
     }
 
     fn process_life_misc(&mut self) {
-        self.grow_step()
+        // seeds don't start growing until they've rooted
+        let body = self.body_mut();
+        if body.drift > 0 {
+            body.drift -= 1;
+        } else {
+            self.grow_step()
+        }
     }
 
     fn process_health(&mut self) {
         // we don't do anything
     }
 
-    fn process_hunger(&mut self) {
+    fn process_hunger(&mut self, _difficulty: Difficulty) {
         // no hunger
     }
 
     fn process_age(&mut self) {
-        match self {
-            Self::Kelp(p) | Self::KelpSeed(p) | Self::KelpLeaf(p) => {
-                p.age += 1;
-                if let Some(max_age) = p.max_age {
-                    if max_age < p.age {
-                        self.die("old age")
-                    }
-                }
+        let age = &mut self.body_mut().age;
+        age.ticks += 1;
+        if let Some(max_age) = age.max {
+            if max_age < age.ticks {
+                self.die("old age")
             }
         }
     }
 
     fn die(&mut self, cause: &str) {
-        match self {
-            Self::Kelp(p) | Self::KelpSeed(p) | Self::KelpLeaf(p) => {
-                p.has_died = true;
-            }
-        }
+        self.body_mut().die();
 
         info!("{self:?} has died of {cause}!")
 
@@ -240,13 +379,18 @@ impl Processing for Plants {
     fn will_process(&self) -> bool {
         match self {
             &Plants::Kelp(_) => true, // only kelp processes
+            Plants::Custom(c, _) => c.plant_stats().offspring.is_some(), // and anything else that spreads
             _ => false,
         }
     }
 
     fn will_process_late(&self) -> bool {
         match self {
-            Plants::KelpLeaf(_) | Plants::KelpSeed(_) | Plants::Kelp(_) => true, // these baddies need to grow (also kelp needs to die lol)
+            Plants::KelpLeaf(_)
+            | Plants::KelpSeed(_)
+            | Plants::Kelp(_)
+            | Plants::Egg(..)
+            | Plants::Custom(..) => true, // these baddies need to grow (also kelp needs to die lol)
         }
     }
 
@@ -256,7 +400,12 @@ impl Processing for Plants {
         }
 
         if self.ready_to_reproduce() {
-            let new_important_positions = self.create_offspring(board, ctx.position);
+            let new_important_positions = self.create_offspring(
+                board,
+                ctx.position,
+                &mut *ctx.rng.lock().unwrap(),
+                &ctx.rules,
+            );
             // new_important_positions.push(position);  // make sure our current position stays important
             return Some(PostProcessResult::MarkTheseAsInteresting(
                 new_important_positions,
@@ -264,7 +413,7 @@ impl Processing for Plants {
         }
 
         match self {
-            &mut Plants::Kelp(_) => {
+            &mut Plants::Kelp(_) | &mut Plants::Custom(..) => {
                 if matches!(self.get_life_status(), LifeStatus::Dead) {
                     // let new_important_positions = self.create_offspring(board, position, false);
                     return Some(PostProcessResult::Delete);
@@ -275,13 +424,18 @@ impl Processing for Plants {
         }
     }
 
-    async fn late_process(&mut self) -> Option<PostProcessResult> {
+    async fn late_process(&mut self, difficulty: Difficulty) -> Option<PostProcessResult> {
         match self.get_life_status() {
             LifeStatus::Alive => {
-                self.life();
+                self.life(difficulty);
+                if let Self::Custom(c, _) = *self {
+                    c.plugin().on_tick(self);
+                }
                 if self.ready_to_grow_into() {
                     if let Some(the_next_generation) = self.grow_into() {
-                        return Some(PostProcessResult::ReplaceMeWith(the_next_generation));
+                        return Some(PostProcessResult::ReplaceMeWith(Box::new(
+                            the_next_generation,
+                        )));
                     }
                 };
                 None
@@ -300,36 +454,24 @@ impl TrackedEntity for Plants {
     }
 
     fn register(&mut self, id: EntityID) -> Result<(), EntityID> {
-        match self {
-            Self::Kelp(p) | Self::KelpLeaf(p) | Self::KelpSeed(p) => p.entity_id = Some(id),
-        }
+        self.body_mut().entity_id = Some(id);
         Ok(())
     }
 
     fn get_id(&self) -> Option<EntityID> {
-        match self {
-            Self::Kelp(p) | Self::KelpLeaf(p) | Self::KelpSeed(p) => p.entity_id,
-        }
+        self.body().entity_id
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Plant {
     pub name: String,
-    /// Amount that the plant has currently grown
-    pub growth_level: usize,
-    /// Point at which the plant should consider changing into another species
-    max_growth: usize,
-    /// Current bites left
-    hp: i64,
     /// Number of "HP", or basically the number of times this can be eaten.
-    hp_max: i64,
-    /// Age, in ticks.
-    age: usize,
-    /// How old we can possibly be (in ticks) before dying. If None, can't die of old age.
-    max_age: Option<usize>,
-    /// If we're irrevocably dead
-    has_died: bool,
+    pub health: Health,
+    pub growth: Growth,
+    pub age: Age,
+    /// How many more ticks we'll drift with the current before rooting. Only seeds drift.
+    pub drift: usize,
     /// Our ID as a tracked entity.
     entity_id: Option<EntityID>,
 }
@@ -344,24 +486,46 @@ impl Plant {
     ) -> Self {
         Plant {
             name,
-            growth_level: 0,
-            max_growth,
-            hp_max: 0,
-            hp,
-            age: 0,
-            max_age,
-            has_died: false,
+            health: Health::new(hp),
+            growth: Growth::new(max_growth),
+            age: Age::new(max_age),
+            drift: 0,
             entity_id,
         }
     }
 
     pub fn die(&mut self) {
-        self.has_died = true;
+        self.health.has_died = true;
+    }
+
+    /// Override one of our stats by name, parsing the value from a string.
+    pub fn set_stat(&mut self, stat: &str, value: &str) -> Result<(), String> {
+        let parse_err = || format!("'{value}' is not a valid value for {stat}");
+        match stat {
+            "hp" => self.health.hp = value.parse().map_err(|_| parse_err())?,
+            "growth" => self.growth.level = value.parse().map_err(|_| parse_err())?,
+            "age" => self.age.ticks = value.parse().map_err(|_| parse_err())?,
+            "drift" => self.drift = value.parse().map_err(|_| parse_err())?,
+            _ => return Err(format!("{} has no stat called '{stat}'", self.name)),
+        }
+        Ok(())
+    }
+
+    /// Get all of the stats that can be set through set_stat, written out as stat=value.
+    pub fn get_stats(&self) -> String {
+        format!(
+            "hp={} growth={} age={} drift={}",
+            self.health.hp, self.growth.level, self.age.ticks, self.drift
+        )
     }
 }
 
 impl PTUIDisplay for Plant {
+    /// Drawn like the species it's named after, or as the first letter of its name if there isn't one.
     fn get_display_char(&self) -> char {
-        return self.name.chars().next().unwrap();
+        Species::from_name(&self.name).map_or_else(
+            || self.name.chars().next().unwrap(),
+            |species| species.display_spec().emoji,
+        )
     }
 }
@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 
 use log::warn;
@@ -24,7 +25,7 @@ pub trait TrackedEntity {
 
 /// An ID tracking an entity.
 /// Essentially just a usize, but we're wrapping it in a struct for typing's sake.
-#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct EntityID {
     id: usize,
 }
@@ -34,6 +35,13 @@ impl EntityID {
     }
 }
 
+/// Mostly for referring to entities by the number shown to the player.
+impl From<usize> for EntityID {
+    fn from(id: usize) -> Self {
+        Self { id }
+    }
+}
+
 /// A struct that's designed to be passed around in an Arc<Mutex>.
 /// This keeps track of all the living entities, and the tiles that they're on.
 /// These entity IDs are essentially weak references to the entities themselves. This will provide access to their position, but it may become invalid.
@@ -41,27 +49,56 @@ impl EntityID {
 #[derive(Debug, Clone)]
 pub struct EntityManager {
     /// The current largest entity ID. The next entity ID that will be handed out will be this + 1
-    current_largest_entity_id: usize,
-    /// Map of current entity IDs to their position.
-    active_entities: HashMap<EntityID, Pos>,
+    /// Shared between the managers of every region, so an entity can wander between them without its ID clashing with anyone else's.
+    current_largest_entity_id: Arc<AtomicUsize>,
+    /// Map of current entity IDs to their position. Kept in ID order, so everything can be run over oldest first
+    /// without sorting the whole list again every phase.
+    active_entities: BTreeMap<EntityID, Pos>,
+    /// Every tile with full grown kelp on it, kept up to date by the tiles so forests can be found without looking over the whole board.
+    mature_kelp: HashSet<Pos>,
 }
 
 impl EntityManager {
     /// If you want to make a new one, you'll be creating it as an arc<mutex>>. This shouldn't really exist in any other context.
     pub fn new() -> Arc<RwLock<Self>> {
         Arc::new(RwLock::new(Self {
-            current_largest_entity_id: 0,
-            active_entities: HashMap::new(),
+            current_largest_entity_id: Arc::new(AtomicUsize::new(0)),
+            active_entities: BTreeMap::new(),
+            mature_kelp: HashSet::new(),
         }))
     }
 
+    /// Make a manager for another region, handing out IDs from the same pool as this one.
+    pub fn new_sharing_ids(other: &Arc<RwLock<Self>>) -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self {
+            current_largest_entity_id: Arc::clone(&other.read().unwrap().current_largest_entity_id),
+            active_entities: BTreeMap::new(),
+            mature_kelp: HashSet::new(),
+        }))
+    }
+
+    /// Make sure an ID that's already been handed out somewhere else, like in a saved game, never gets handed out again.
+    pub fn skip_past(&mut self, id: EntityID) {
+        self.current_largest_entity_id
+            .fetch_max(id.id, Ordering::Relaxed);
+    }
+
     /// Add a new entity to the global list.
     pub fn register_new_entity(&mut self, new_position: Pos, entity: &mut Entity) -> EntityID {
-        self.current_largest_entity_id += 1;
+        let new_ent_id = self.hand_out_id(entity);
+        self.active_entities.insert(new_ent_id, new_position);
+        new_ent_id
+    }
+
+    /// Give an entity the next ID, without adding it to the list yet. IDs are handed out from a shared count, so this
+    /// only needs the manager to be read from.
+    pub fn hand_out_id(&self, entity: &mut Entity) -> EntityID {
         let new_ent_id = EntityID {
-            id: self.current_largest_entity_id,
+            id: self
+                .current_largest_entity_id
+                .fetch_add(1, Ordering::Relaxed)
+                + 1,
         };
-        self.active_entities.insert(new_ent_id, new_position);
         if let Err(id) = entity.register(new_ent_id) {
             warn!("Entity {entity:?} was to be given ID {id:?}, but registration failed!")
         }
@@ -77,14 +114,61 @@ impl EntityManager {
         };
     }
 
-    /// Public accessor for getting the entries in the map, but only as an immutable reference
-    pub fn get_active_entries(&self) -> &HashMap<EntityID, Pos> {
+    /// Public accessor for getting the entries in the map, but only as an immutable reference. They come oldest first.
+    pub fn get_active_entries(&self) -> &BTreeMap<EntityID, Pos> {
         &self.active_entities
     }
 
     pub fn get_active_positions(&self) -> Vec<Pos> {
         self.active_entities.values().copied().collect()
     }
+
+    /// Note down whether there's full grown kelp at a position.
+    /// This should probably be called within a tile, too.
+    pub fn update_vegetation(&mut self, pos: Pos, mature_kelp: bool) {
+        if mature_kelp {
+            self.mature_kelp.insert(pos);
+        } else {
+            self.mature_kelp.remove(&pos);
+        }
+    }
+
+    pub fn get_mature_kelp(&self) -> &HashSet<Pos> {
+        &self.mature_kelp
+    }
+
+    /// Make every change that was saved up in a batch, in one go.
+    pub fn apply(&mut self, updates: EntityUpdates) {
+        for (entity, new_position) in updates.positions {
+            self.update_position(entity, new_position);
+        }
+        for (pos, mature_kelp) in updates.vegetation {
+            self.update_vegetation(pos, mature_kelp);
+        }
+    }
+}
+
+/// Changes to the entity manager saved up over a phase, so they can all be made under one lock at the end instead of
+/// taking it for every single one.
+#[derive(Debug, Clone, Default)]
+pub struct EntityUpdates {
+    /// Every entity that's been moved, added or removed and where it went, or None if it's gone, in the order they
+    /// happened.
+    positions: Vec<(EntityID, Option<Pos>)>,
+    /// Every change to the full grown kelp, in the order they happened.
+    vegetation: Vec<(Pos, bool)>,
+}
+
+impl EntityUpdates {
+    /// Note down an entity's new position, to be made along with everything else.
+    pub fn update_position(&mut self, entity: EntityID, new_position: Option<Pos>) {
+        self.positions.push((entity, new_position));
+    }
+
+    /// Note down whether there's full grown kelp at a position, to be made along with everything else.
+    pub fn update_vegetation(&mut self, pos: Pos, mature_kelp: bool) {
+        self.vegetation.push((pos, mature_kelp));
+    }
 }
 #[cfg(test)]
 mod test {
@@ -105,14 +189,17 @@ mod test {
 
         let em = Arc::clone(&testbed.sandbox.entity_context);
 
-        let kelp_tile = testbed.sandbox.board.get_tile(1, 1);
-        assert!(&kelp_tile.is_occupied());
+        assert!(testbed
+            .sandbox
+            .board
+            .get_tile_from_pos(kelp_position)
+            .is_occupied());
 
         // verify that everything stays connected when we insert the entity
 
         let mut ent_id = None;
 
-        if let Some(ent) = &kelp_tile.get_entity() {
+        if let Some(ent) = testbed.sandbox.board.entity_at(kelp_position) {
             ent_id = ent.get_id();
             // verify that an ID was registered
             assert!(ent_id.is_some());
@@ -128,11 +215,29 @@ mod test {
         }
 
         // now, verify that removing the entry from the tile also removes it from the active entries list
-        let kelp_tile = testbed.sandbox.board.get_tile_mut(1, 1);
-        let ent = kelp_tile.remove_entity().unwrap();
+        let ent = testbed.sandbox.board.remove_entity(kelp_position).unwrap();
         assert_eq!(ent.get_id(), ent_id);
         let em_guard = em.read().unwrap();
         let kelp_pos = em_guard.get_active_entries().get(&ent.get_id().unwrap());
         assert!(kelp_pos.is_none())
     }
+
+    #[test]
+    fn test_entries_oldest_first() {
+        let em = EntityManager::new();
+        let mut em = em.write().unwrap();
+        let positions = [Pos { x: 2, y: 2 }, Pos { x: 0, y: 0 }, Pos { x: 1, y: 1 }];
+        let ids: Vec<EntityID> = positions
+            .iter()
+            .map(|pos| em.register_new_entity(*pos, &mut ConcretePlants::Kelp.create_new(None)))
+            .collect();
+        // moving around doesn't change where something comes in the list
+        em.update_position(ids[0], Some(Pos { x: 0, y: 1 }));
+        em.update_position(ids[1], None);
+        assert_eq!(
+            em.get_active_positions(),
+            vec![Pos { x: 0, y: 1 }, Pos { x: 1, y: 1 }]
+        );
+        assert!(em.get_active_entries().keys().eq(&[ids[0], ids[2]]));
+    }
 }
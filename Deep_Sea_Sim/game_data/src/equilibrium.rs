@@ -0,0 +1,356 @@
+// Whether the colony's populations look like they can keep going the way they are. Every tick, how many of each species
+// there are gets written down, and from the last stretch of those we fit a Lotka–Volterra style estimate for each animal:
+// how fast it grows per head, going up with how much of its food is around and down with how many of whatever eats it
+// there are. Plugging in what's around right now gives how fast it's growing (or shrinking) at the moment, and following
+// that out says whether it's on course to die out, or to overrun the sea, any time soon.
+// It's only a rough guess from a short, noisy stretch of history, so it's shown to the player as advice rather than
+// driving anything.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::entities::Species;
+use crate::game_board::Board;
+use crate::interaction_matrix::{Appetite, InteractionMatrix};
+
+/// How many ticks of populations are kept around to fit estimates to.
+const SAMPLES_KEPT: usize = 50;
+/// How many ticks need to have been seen before anything gets said about them.
+const MIN_SAMPLES: usize = 20;
+/// How many ticks ahead we're willing to warn about.
+const HORIZON: f64 = 100.0;
+/// How much of the sea a single species needs to be covering before it counts as having overrun it.
+const OVERRUN_SHARE: f64 = 0.5;
+/// Growth slower than this, either way, is as good as holding steady.
+const STEADY_RATE: f64 = 0.002;
+/// Anything smaller than this on the diagonal while solving for a fit means the fit can't be pinned down.
+const SINGULAR: f64 = 1e-9;
+
+/// Where a species looks to be heading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outlook {
+    /// On course to die out in about this many ticks.
+    Extinction(usize),
+    /// On course to overrun the sea in about this many ticks.
+    Explosion(usize),
+    /// Nothing to worry about any time soon.
+    Stable,
+}
+
+impl Outlook {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Extinction(_) => "extinction",
+            Self::Explosion(_) => "explosion",
+            Self::Stable => "stable",
+        }
+    }
+}
+
+/// How one species' population looks to be going.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeciesOutlook {
+    pub species: Species,
+    /// How many there are now.
+    pub population: usize,
+    /// How much the population's growing by per tick right now, going by the fit. Negative if it's shrinking.
+    pub growth: f64,
+    pub outlook: Outlook,
+}
+
+impl SpeciesOutlook {
+    /// Whether the player ought to hear about this.
+    pub fn is_warning(&self) -> bool {
+        self.outlook != Outlook::Stable
+    }
+}
+
+/// How many of everything there were on one tick.
+#[derive(Debug, Clone)]
+struct Sample {
+    tick: usize,
+    populations: HashMap<Species, usize>,
+}
+
+impl Sample {
+    fn count(&self, species: Species) -> usize {
+        self.populations.get(&species).copied().unwrap_or(0)
+    }
+
+    /// How many there were of everything in a group, all together.
+    fn total(&self, group: &[Species]) -> f64 {
+        group.iter().map(|s| self.count(*s) as f64).sum()
+    }
+}
+
+/// How many of every living species there were over the last few ticks.
+#[derive(Debug, Default)]
+pub struct PopulationHistory {
+    samples: VecDeque<Sample>,
+    /// How many tiles there are to go around, across every board.
+    tiles: usize,
+}
+
+impl PopulationHistory {
+    /// Count up everything living on the boards, as of the given tick.
+    pub fn record<'a>(&mut self, tick: usize, boards: impl Iterator<Item = &'a Board>) {
+        let mut populations = HashMap::new();
+        let mut tiles = 0;
+        for board in boards {
+            let (cols, rows) = board.dims();
+            tiles += cols * rows;
+            for (_, entity) in board.iter_occupied() {
+                let species = Species::of(entity);
+                if species.is_animal() || species.is_plant() {
+                    *populations.entry(species).or_default() += 1;
+                }
+            }
+        }
+        self.tiles = tiles;
+        self.push(tick, populations);
+    }
+
+    fn push(&mut self, tick: usize, populations: HashMap<Species, usize>) {
+        self.samples.push_back(Sample { tick, populations });
+        if self.samples.len() > SAMPLES_KEPT {
+            self.samples.pop_front();
+        }
+    }
+
+    /// How many of a species there were on each tick we've still got, oldest first.
+    pub fn series(&self, species: Species) -> Vec<(usize, usize)> {
+        self.samples
+            .iter()
+            .map(|s| (s.tick, s.count(species)))
+            .collect()
+    }
+
+    /// Where every animal species that's still around looks to be heading, ordered by name. Nothing's said until
+    /// there's been a few ticks to go on.
+    pub fn outlook(&self, rules: &InteractionMatrix) -> Vec<SpeciesOutlook> {
+        if self.samples.len() < MIN_SAMPLES {
+            return vec![];
+        }
+        let mut seen: Vec<Species> = self
+            .samples
+            .iter()
+            .flat_map(|s| s.populations.keys().copied())
+            .collect();
+        seen.sort_by_key(Species::name);
+        seen.dedup();
+        seen.iter()
+            .filter(|s| s.is_animal())
+            .filter_map(|species| {
+                let others = seen.iter().copied().filter(|s| s != species);
+                let prey: Vec<Species> = others
+                    .clone()
+                    .filter(|s| rules.appetite(*species, *s) != Appetite::Never)
+                    .collect();
+                let predators: Vec<Species> = others
+                    .filter(|s| rules.appetite(*s, *species) != Appetite::Never)
+                    .collect();
+                self.species_outlook(*species, &prey, &predators)
+            })
+            .collect()
+    }
+
+    /// Fit how fast a species grows to how much of its prey and how many of its predators are around, and follow
+    /// where that's taking it. None if it's already gone.
+    fn species_outlook(
+        &self,
+        species: Species,
+        prey: &[Species],
+        predators: &[Species],
+    ) -> Option<SpeciesOutlook> {
+        let latest = self.samples.back()?;
+        let population = latest.count(species);
+        if population == 0 {
+            return None;
+        }
+        // densities rather than head counts, so the fit comes out the same whatever size the sea is
+        let tiles = self.tiles.max(1) as f64;
+        let features = |s: &Sample| [s.total(prey) / tiles, s.total(predators) / tiles];
+        let (rows, growth): (Vec<[f64; 2]>, Vec<f64>) = self
+            .samples
+            .iter()
+            .zip(self.samples.iter().skip(1))
+            .map(|(before, after)| {
+                // one extra head all round, so dying out doesn't send anything off to infinity
+                let rate =
+                    ((after.count(species) + 1) as f64 / (before.count(species) + 1) as f64).ln();
+                (features(before), rate)
+            })
+            .unzip();
+        let rate = fit_growth(&rows, &growth, features(latest));
+
+        let headcount = (population + 1) as f64;
+        let overrun = tiles * OVERRUN_SHARE;
+        // how long until (population + 1) * e^(rate * ticks) comes down to 1, or up to overrunning the sea
+        let outlook = if rate < -STEADY_RATE {
+            let ticks = headcount.ln() / -rate;
+            if ticks <= HORIZON {
+                Outlook::Extinction(ticks.round() as usize)
+            } else {
+                Outlook::Stable
+            }
+        } else if rate > STEADY_RATE {
+            let ticks = (overrun / headcount).ln().max(0.0) / rate;
+            if ticks <= HORIZON {
+                Outlook::Explosion(ticks.round() as usize)
+            } else {
+                Outlook::Stable
+            }
+        } else {
+            Outlook::Stable
+        };
+        Some(SpeciesOutlook {
+            species,
+            population,
+            growth: rate,
+            outlook,
+        })
+    }
+
+    /// Forget every tick after the given one, for when the game's been rewound to it.
+    pub fn rewind(&mut self, tick: usize) {
+        self.samples.retain(|s| s.tick <= tick);
+    }
+}
+
+/// Fit per head growth to `rate = a + b * prey - c * predators` by least squares, and work out the rate at `now`.
+/// Prey and predators that never changed over the samples can't say anything about growth, so they're left out, and if
+/// the fit still can't be pinned down, it's just the average growth over the samples.
+fn fit_growth(rows: &[[f64; 2]], growth: &[f64], now: [f64; 2]) -> f64 {
+    let mean = growth.iter().sum::<f64>() / growth.len().max(1) as f64;
+    let varies: Vec<usize> = (0..2)
+        .filter(|i| {
+            rows.iter()
+                .any(|r| (r[*i] - rows[0][*i]).abs() > f64::EPSILON)
+        })
+        .collect();
+    // the normal equations, with a column of ones for the intercept
+    let width = varies.len() + 1;
+    let terms = |r: &[f64; 2]| -> Vec<f64> {
+        std::iter::once(1.0)
+            .chain(varies.iter().map(|i| r[*i]))
+            .collect()
+    };
+    let mut system = vec![vec![0.0; width + 1]; width];
+    for (row, y) in rows.iter().zip(growth) {
+        let x = terms(row);
+        for i in 0..width {
+            for j in 0..width {
+                system[i][j] += x[i] * x[j];
+            }
+            system[i][width] += x[i] * y;
+        }
+    }
+    match solve(system) {
+        Some(coefficients) => terms(&now)
+            .iter()
+            .zip(coefficients)
+            .map(|(x, c)| x * c)
+            .sum(),
+        None => mean,
+    }
+}
+
+/// Solve a system of linear equations, written as rows of coefficients with the constant on the end, by Gaussian
+/// elimination. None if there isn't exactly one answer.
+fn solve(mut system: Vec<Vec<f64>>) -> Option<Vec<f64>> {
+    let n = system.len();
+    for col in 0..n {
+        let pivot =
+            (col..n).max_by(|a, b| system[*a][col].abs().total_cmp(&system[*b][col].abs()))?;
+        if system[pivot][col].abs() < SINGULAR {
+            return None;
+        }
+        system.swap(col, pivot);
+        let pivot = system[col].clone();
+        for (r, row) in system.iter_mut().enumerate() {
+            if r != col {
+                let factor = row[col] / pivot[col];
+                for (x, p) in row.iter_mut().zip(&pivot).skip(col) {
+                    *x -= factor * p;
+                }
+            }
+        }
+    }
+    Some((0..n).map(|i| system[i][n] / system[i][i]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(series: impl Fn(usize) -> Vec<(Species, usize)>) -> PopulationHistory {
+        let mut history = PopulationHistory {
+            tiles: 400,
+            ..Default::default()
+        };
+        for tick in 0..SAMPLES_KEPT {
+            history.push(tick, series(tick).into_iter().collect());
+        }
+        history
+    }
+
+    fn outlook_for(history: &PopulationHistory, species: Species) -> Outlook {
+        history
+            .outlook(&InteractionMatrix::default())
+            .into_iter()
+            .find(|o| o.species == species)
+            .unwrap()
+            .outlook
+    }
+
+    #[test]
+    fn test_fit() {
+        // rate = 0.1 + 2 * prey - 3 * predators, exactly
+        let rows: Vec<[f64; 2]> = (0..10)
+            .map(|i| [i as f64 * 0.01, (i * i % 7) as f64 * 0.01])
+            .collect();
+        let growth: Vec<f64> = rows.iter().map(|r| 0.1 + 2.0 * r[0] - 3.0 * r[1]).collect();
+        let rate = fit_growth(&rows, &growth, [0.05, 0.02]);
+        assert!((rate - (0.1 + 0.1 - 0.06)).abs() < 1e-9);
+        // with nothing changing there's nothing to fit to but the average
+        let flat = vec![[0.1, 0.2]; 4];
+        assert!((fit_growth(&flat, &[0.1, 0.2, 0.3, 0.4], [0.5, 0.5]) - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_outlook() {
+        // the sharks are dwindling on a steady supply of fish, and the crabs are taking off
+        let history = history(|tick| {
+            vec![
+                (
+                    Species::Shark,
+                    (200.0 * 0.95f64.powi(tick as i32)).round() as usize,
+                ),
+                (Species::Fish, 30),
+                (
+                    Species::Crab,
+                    (2.0 * 1.05f64.powi(tick as i32)).round() as usize,
+                ),
+            ]
+        });
+        assert!(matches!(
+            outlook_for(&history, Species::Shark),
+            Outlook::Extinction(_)
+        ));
+        assert!(matches!(
+            outlook_for(&history, Species::Crab),
+            Outlook::Explosion(_)
+        ));
+        assert_eq!(outlook_for(&history, Species::Fish), Outlook::Stable);
+
+        let too_soon = PopulationHistory::default();
+        assert!(too_soon.outlook(&InteractionMatrix::default()).is_empty());
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut history = history(|_| vec![(Species::Fish, 3)]);
+        history.rewind(9);
+        assert_eq!(history.series(Species::Fish).len(), 10);
+        assert!(history.outlook(&InteractionMatrix::default()).is_empty());
+    }
+}
@@ -0,0 +1,80 @@
+// Everything that can go wrong while poking at the board or running the game loop.
+
+use std::fmt::Display;
+
+use crate::entities::Entity;
+use crate::entity_control::EntityID;
+use crate::game_board::Pos;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Tried to put an entity on a tile that already had one.
+    /// The entity that didn't fit is handed back, so it doesn't just disappear.
+    TileOccupied { pos: Pos, entity: Box<Entity> },
+    /// Tried to do something at a position that isn't on the board.
+    OutOfBounds(Pos),
+    /// Tried to move something from a tile with nothing there to move, or onto one that's already taken.
+    /// Whatever was there stays where it was.
+    CantMove { from: Pos, to: Pos },
+    /// An entity ID that doesn't (or no longer) belongs to anything on the board.
+    StaleEntityId(EntityID),
+    /// The other end of a channel hung up, so there's nobody left to talk to.
+    ChannelClosed,
+    /// There's no region by this name.
+    UnknownRegion(String),
+    /// Tried to register a species under a name that's already in use.
+    SpeciesTaken(String),
+    /// Tried to build something that isn't a structure.
+    NotBuildable(String),
+    /// The colony doesn't have enough resources to build something.
+    CantAfford { cost: usize, resources: usize },
+    /// Tried to rewind further back than the game's been keeping track of.
+    CantRewind { ticks: usize, kept: usize },
+    /// There's no edit to the board left to take back.
+    NothingToUndo,
+    /// No edit's been taken back since the last one was made, so there's nothing to make again.
+    NothingToRedo,
+    /// The tile an edit was made to has changed since, so it can't be taken back (or made again).
+    EditBlocked(Pos),
+}
+
+impl Error {
+    /// Get back the entity that couldn't be placed, if that's what went wrong.
+    pub fn into_entity(self) -> Option<Entity> {
+        match self {
+            Self::TileOccupied { entity, .. } => Some(*entity),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TileOccupied { pos, .. } => write!(f, "{pos:?} is already occupied"),
+            Self::OutOfBounds(pos) => write!(f, "{pos:?} is off the board"),
+            Self::CantMove { from, to } => {
+                write!(f, "Couldn't move anything from {from:?} to {to:?}")
+            }
+            Self::StaleEntityId(id) => write!(f, "There's no entity with id {}", id.get_id_val()),
+            Self::ChannelClosed => write!(f, "The other end of the channel was closed"),
+            Self::UnknownRegion(name) => write!(f, "There's no region called '{name}'"),
+            Self::SpeciesTaken(name) => write!(f, "There's already a species called '{name}'"),
+            Self::NotBuildable(name) => write!(f, "A {name} isn't something that can be built"),
+            Self::CantAfford { cost, resources } => {
+                write!(f, "That costs {cost}, but the colony only has {resources}")
+            }
+            Self::CantRewind { ticks, kept } => {
+                write!(
+                    f,
+                    "Can't rewind {ticks} ticks, only the last {kept} were kept"
+                )
+            }
+            Self::NothingToUndo => write!(f, "There's nothing to undo"),
+            Self::NothingToRedo => write!(f, "There's nothing to redo"),
+            Self::EditBlocked(pos) => write!(f, "{pos:?} has changed since that edit was made"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
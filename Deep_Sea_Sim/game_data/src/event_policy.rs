@@ -0,0 +1,272 @@
+// How events get answered when nobody's around to answer them, like in headless runs, sweeps and benchmarks.
+// A policy looks at what an event is asking and picks one of its two answers, so a long experiment never hangs waiting
+// on a player. Every answer, whoever gave it, goes into the sandbox's event log along with the policy that picked it,
+// so a run can be looked back over (or played again) knowing which choices were made on purpose.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use rand::Rng;
+
+use crate::game_events::{Consequence, EventPrompt, Stat};
+use crate::{PendingEvent, Sandbox};
+
+/// How much worse pollution is for every hundred ticks it hangs around for.
+const POLLUTION_PER_HUNDRED_TICKS: f64 = 1.0;
+/// How much a tick's worth of hunger counts against an answer, next to a percentage point of anything else.
+const HUNGER_WEIGHT: f64 = 10.0;
+/// How much every tile across a vent field counts against an answer.
+const VENT_WEIGHT: f64 = 10.0;
+
+/// A way of answering events without asking anyone.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventPolicy {
+    /// Flip a coin, using the sandbox's own randomness.
+    Random,
+    /// Go with each event's default answer, the same one a timed out player gets.
+    Default,
+    /// Always go with the first answer.
+    First,
+    /// Answers picked ahead of time for events on particular ticks. Anything else gets its default answer.
+    ByTick(BTreeMap<usize, bool>),
+    /// Weigh up what each answer is expected to do, and go with whichever does the least damage.
+    /// Ties, like for scripted events that don't say what they'll do, go to the default answer.
+    LeastDamaging,
+}
+
+impl EventPolicy {
+    /// Every policy that doesn't need anything else to go on.
+    pub const ALL: [EventPolicy; 4] = [
+        Self::Random,
+        Self::Default,
+        Self::First,
+        Self::LeastDamaging,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Random => "random",
+            Self::Default => "default",
+            Self::First => "first",
+            Self::ByTick(_) => "by_tick",
+            Self::LeastDamaging => "least_damaging",
+        }
+    }
+
+    /// Read a policy in by name. Answers by tick are written `by_tick:12=1,40=2`, numbering the answers the same
+    /// way they are on the player's buttons.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if let Some(ticks) = text.strip_prefix("by_tick:") {
+            let mut answers = BTreeMap::new();
+            for entry in ticks.split(',').filter(|entry| !entry.is_empty()) {
+                let answer = entry.split_once('=').and_then(|(tick, answer)| {
+                    let tick = tick.trim().parse().ok()?;
+                    match answer.trim() {
+                        "1" => Some((tick, false)),
+                        "2" => Some((tick, true)),
+                        _ => None,
+                    }
+                });
+                let (tick, answer) =
+                    answer.ok_or_else(|| format!("'{entry}' should look like <tick>=<1 or 2>"))?;
+                answers.insert(tick, answer);
+            }
+            return Ok(Self::ByTick(answers));
+        }
+        Self::ALL
+            .into_iter()
+            .find(|policy| policy.name() == text)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|p| p.name()).collect();
+                format!(
+                    "there's no event policy called '{text}', try one of {} or by_tick:<tick>=<1 or 2>,...",
+                    names.join(", ")
+                )
+            })
+    }
+
+    /// Answer every event the same way it was answered in a log, so a run can be played over again.
+    pub fn replaying(log: &[AnsweredEvent]) -> Self {
+        Self::ByTick(log.iter().map(|a| (a.tick, a.answer)).collect())
+    }
+
+    /// Pick an answer to an event that went off on the given tick.
+    pub fn choose(&self, tick: usize, prompt: &EventPrompt, rng: &mut impl Rng) -> bool {
+        match self {
+            Self::Random => rng.gen_bool(0.5),
+            Self::Default => prompt.default,
+            Self::First => false,
+            Self::ByTick(answers) => answers.get(&tick).copied().unwrap_or(prompt.default),
+            Self::LeastDamaging => {
+                let [first, second] = prompt.choices.each_ref().map(|c| harm(&c.consequences));
+                match first.total_cmp(&second) {
+                    std::cmp::Ordering::Less => false,
+                    std::cmp::Ordering::Greater => true,
+                    std::cmp::Ordering::Equal => prompt.default,
+                }
+            }
+        }
+    }
+}
+
+/// Written out the same way parse reads it back in.
+impl fmt::Display for EventPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self::ByTick(answers) = self else {
+            return f.write_str(self.name());
+        };
+        let answers: Vec<String> = answers
+            .iter()
+            .map(|(tick, answer)| format!("{tick}={}", usize::from(*answer) + 1))
+            .collect();
+        write!(f, "by_tick:{}", answers.join(","))
+    }
+}
+
+/// How bad a set of consequences is for the colony, with anything that helps it counting against the rest.
+fn harm(consequences: &[Consequence]) -> f64 {
+    consequences
+        .iter()
+        .map(|c| {
+            let change = c.change as f64;
+            match c.stat {
+                Stat::Plants | Stat::Health | Stat::Breeding => -change,
+                Stat::Fights => change,
+                Stat::Hunger => change * HUNGER_WEIGHT,
+                Stat::Vents => change * VENT_WEIGHT,
+                Stat::Pollution => {
+                    let ticks = c.ticks.unwrap_or(0) as f64;
+                    change * (1.0 + ticks / 100.0 * POLLUTION_PER_HUNDRED_TICKS)
+                }
+            }
+        })
+        .sum()
+}
+
+/// An event that got answered, and how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnsweredEvent {
+    /// The tick the event went off on.
+    pub tick: usize,
+    pub region: String,
+    /// The event's name.
+    pub event: String,
+    pub answer: bool,
+    /// The name of the policy that picked the answer, or None if the player picked it.
+    pub policy: Option<&'static str>,
+}
+
+impl AnsweredEvent {
+    /// The answer as the player would've seen it, 1 or 2.
+    pub fn answer_number(&self) -> usize {
+        usize::from(self.answer) + 1
+    }
+}
+
+impl Sandbox {
+    /// Answer events with a policy from now on, whenever nobody else answers them.
+    pub fn set_event_policy(&mut self, policy: EventPolicy) {
+        self.event_policy = Some(policy);
+    }
+
+    /// The policy events get answered with when nobody else answers them. Unless one's been set, they're answered
+    /// at random, or with their default answer if they're on a timer.
+    pub fn event_policy(&self) -> EventPolicy {
+        match (&self.event_policy, self.settings.event_window()) {
+            (Some(policy), _) => policy.clone(),
+            (None, None) => EventPolicy::Random,
+            (None, Some(_)) => EventPolicy::Default,
+        }
+    }
+
+    /// Every event that's been answered so far, oldest first.
+    pub fn event_log(&self) -> &[AnsweredEvent] {
+        &self.event_log
+    }
+
+    /// Answer an event with the event policy, as if the player had. If it's been voted on, whatever won the vote
+    /// gets picked instead.
+    pub fn answer_with_policy(&mut self, pending: PendingEvent) {
+        let prompt = self.describe_event(&pending);
+        let voted = self.poll.as_ref().and_then(|t| t.winner(prompt.default));
+        let (choice, name) = match voted {
+            Some(choice) => (choice, "votes"),
+            None => {
+                let policy = self.event_policy();
+                let choice = policy.choose(self.clock, &prompt, &mut *self.rng.lock().unwrap());
+                (choice, policy.name())
+            }
+        };
+        self.answer_event(pending, choice);
+        if let Some(answered) = self.event_log.last_mut() {
+            answered.policy = Some(name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_events::{get_rand_event, Event, EventTypes};
+    use crate::settings::{Difficulty, EventFrequency};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_parse() {
+        for policy in EventPolicy::ALL {
+            assert_eq!(EventPolicy::parse(policy.name()), Ok(policy));
+        }
+        let by_tick = EventPolicy::parse("by_tick:12=1,40=2").unwrap();
+        assert_eq!(
+            by_tick,
+            EventPolicy::ByTick(BTreeMap::from([(12, false), (40, true)]))
+        );
+        assert_eq!(by_tick.to_string(), "by_tick:12=1,40=2");
+        assert!(EventPolicy::parse("by_tick:12=3").is_err());
+        assert!(EventPolicy::parse("cautious").is_err());
+    }
+
+    #[test]
+    fn test_choose() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let prompt = |i| get_rand_event(i).get_prompt(Difficulty::Normal);
+        let least = |i| EventPolicy::LeastDamaging.choose(0, &prompt(i), &mut rng.clone());
+        // hiding from the oil, capping the vents and cleaning up are all better than the alternative
+        assert!(!least(0));
+        assert!(!least(3));
+        assert!(!least(4));
+        // and a party's worth the hunger
+        assert!(!least(2));
+
+        let by_tick = EventPolicy::ByTick(BTreeMap::from([(5, false)]));
+        let party = prompt(2);
+        assert!(!by_tick.choose(5, &party, &mut rng));
+        assert_eq!(by_tick.choose(6, &party, &mut rng), party.default);
+        assert!(!EventPolicy::First.choose(0, &party, &mut rng));
+        assert!(EventPolicy::Default.choose(0, &party, &mut rng));
+    }
+
+    #[test]
+    fn test_event_log() {
+        let mut sandbox = crate::test_utils::TestBed::new_default(6, 6, 4, 0, 0).sandbox;
+        // only the scripted events should go off
+        sandbox.settings.event_frequency = EventFrequency::Never;
+        sandbox.set_event_policy(EventPolicy::First);
+        sandbox.scripted_events = vec![(0, EventTypes::Party), (3, EventTypes::Cleanup)];
+        sandbox.run_headless(5, |_, _| ());
+        let log: Vec<(usize, &str, usize, Option<&str>)> = sandbox
+            .event_log()
+            .iter()
+            .map(|a| (a.tick, a.event.as_str(), a.answer_number(), a.policy))
+            .collect();
+        assert_eq!(
+            log,
+            [
+                (1, "party", 1, Some("first")),
+                (4, "cleanup", 1, Some("first"))
+            ]
+        );
+        let replay = EventPolicy::replaying(sandbox.event_log());
+        assert_eq!(replay.to_string(), "by_tick:1=1,4=1");
+    }
+}
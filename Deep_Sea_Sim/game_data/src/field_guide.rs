@@ -0,0 +1,220 @@
+// The field guide: a page on every species there is, put together from the species themselves and the rules they follow
+// (see interaction_matrix.rs) instead of being written up by hand. Whatever a species config, a plugin or a change to the
+// game does to a species shows up on its page the next time the guide's opened.
+//
+// Pages only hold names and numbers. Putting them into words is up to whatever shows them, so they can be read in any
+// language.
+
+use crate::entities::{NonAbstractTaxonomy, PTUIDisplay, Species, MAX_PREGNANCY_LEVEL};
+use crate::interaction_matrix::{Appetite, InteractionMatrix, Pairing, WanderStyle};
+
+/// Everything there is to know about one species.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuidePage {
+    pub species: Species,
+    pub glyph: char,
+    /// Numbers worth knowing about it, by name, like its hp or how long it lives. Anything that doesn't apply to it is
+    /// left out.
+    pub stats: Vec<(&'static str, String)>,
+    /// Everything it eats, and when it'll go for them.
+    pub eats: Vec<(Species, Appetite)>,
+    /// Everything that eats it, and when they'll go for it.
+    pub eaten_by: Vec<(Species, Appetite)>,
+    /// How much hunger eating one restores.
+    pub food_value: usize,
+    /// Who it has young with, and which of them it'll pair up with.
+    pub mates_with: Vec<(Species, Pairing)>,
+    /// The most there can be of it on the board, if there's a limit.
+    pub population_cap: Option<usize>,
+    /// How it gets about while it's idle, if it gets about at all.
+    pub wander: Option<WanderStyle>,
+    /// What the average one's like, as quirks (see Personality::quirks).
+    pub temperament: Vec<&'static str>,
+    /// Anything else it's known for, by name, like fighting back or giving cover.
+    pub behaviors: Vec<&'static str>,
+}
+
+impl GuidePage {
+    /// Look a species up, going by the given rules, on a board with this many tiles.
+    pub fn new(species: Species, rules: &InteractionMatrix, tiles: usize) -> Self {
+        let entity = species.create_new(None);
+        let everything = Species::all();
+        let animals: Vec<Species> = everything
+            .iter()
+            .copied()
+            .filter(Species::is_animal)
+            .collect();
+
+        let mut stats = vec![];
+        let mut behaviors = vec![];
+        if let Some(health) = entity.health() {
+            stats.push(("hp", health.hp_max.to_string()));
+        }
+        if let Some(max) = entity.age().and_then(|a| a.max) {
+            stats.push(("lifespan", max.to_string()));
+        }
+        if let Some(mobility) = entity.mobility() {
+            stats.push(("speed", format!("{}/{}", mobility.max_x, mobility.max_y)));
+        }
+        if let Some(animal) = entity.animal() {
+            let combat = &animal.body().combat;
+            stats.push(("attack", combat.attack.to_string()));
+            stats.push(("defense", combat.defense.to_string()));
+            stats.push(("evasion", format!("{:.0}%", combat.evasion * 100.0)));
+            if combat.reach > 1 {
+                stats.push(("reach", combat.reach.to_string()));
+                behaviors.push("lunges");
+            }
+            if combat.smell > 0 {
+                stats.push(("smell", combat.smell.to_string()));
+                behaviors.push("smells_blood");
+            }
+            behaviors.push("lays_eggs");
+            if combat.fights_back {
+                behaviors.push("fights_back");
+            }
+            if animal.territorial() {
+                behaviors.push("territorial");
+            }
+            if animal.wears_shells() {
+                behaviors.push("wears_shells");
+            }
+        }
+        if let Some(r) = entity.reproduction().filter(|r| r.pregnancy_step > 0) {
+            let ticks = MAX_PREGNANCY_LEVEL.div_ceil(r.pregnancy_step);
+            stats.push(("pregnancy", ticks.to_string()));
+            stats.push(("mating_cooldown", r.mating_cooldown.to_string()));
+        }
+        if let Some(growth) = entity.growth() {
+            stats.push(("growth", growth.max.to_string()));
+        }
+        if let Some(hazard) = entity.hazard() {
+            stats.push(("damage", hazard.damage.to_string()));
+            behaviors.push("hurts_neighbours");
+        }
+        if entity.gives_cover() {
+            behaviors.push("gives_cover");
+        }
+
+        let is_animal = species.is_animal();
+        let eats = match is_animal {
+            true => everything
+                .iter()
+                .map(|prey| (*prey, rules.appetite(species, *prey)))
+                .filter(|(_, appetite)| *appetite != Appetite::Never)
+                .collect(),
+            false => vec![],
+        };
+        let eaten_by = animals
+            .iter()
+            .map(|predator| (*predator, rules.appetite(*predator, species)))
+            .filter(|(_, appetite)| *appetite != Appetite::Never)
+            .collect();
+        let mates_with = match is_animal {
+            true => animals
+                .iter()
+                .map(|mate| (*mate, rules.pairing(species, *mate)))
+                .filter(|(_, pairing)| *pairing != Pairing::Never)
+                .collect(),
+            false => vec![],
+        };
+        Self {
+            species,
+            glyph: entity.get_display_char(),
+            stats,
+            eats,
+            eaten_by,
+            food_value: rules.food_value(species),
+            mates_with,
+            population_cap: rules.population_cap(species, tiles),
+            wander: is_animal.then(|| rules.wander_style(species)),
+            temperament: match is_animal {
+                true => rules.temperament(species).quirks(),
+                false => vec![],
+            },
+            behaviors,
+        }
+    }
+}
+
+/// A page on every species, going by the rules in use, on a board with this many tiles.
+pub fn field_guide(rules: &InteractionMatrix, tiles: usize) -> Vec<GuidePage> {
+    Species::all()
+        .into_iter()
+        .map(|species| GuidePage::new(species, rules, tiles))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(species: Species, rules: &InteractionMatrix) -> GuidePage {
+        GuidePage::new(species, rules, 100)
+    }
+
+    fn stat(page: &GuidePage, name: &str) -> Option<String> {
+        page.stats
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    #[test]
+    fn test_pages() {
+        let rules = InteractionMatrix::default();
+        let fish = page(Species::Fish, &rules);
+        assert_eq!(fish.glyph, '🐠');
+        assert_eq!(stat(&fish, "hp").as_deref(), Some("100"));
+        assert_eq!(stat(&fish, "pregnancy").as_deref(), Some("20"));
+        assert!(fish.eats.contains(&(Species::Crab, Appetite::Always)));
+        assert!(fish.eats.contains(&(Species::Kelp, Appetite::Hungry)));
+        assert!(fish.eaten_by.contains(&(Species::Shark, Appetite::Always)));
+        assert_eq!(fish.mates_with, [(Species::Fish, Pairing::OppositeSex)]);
+        assert_eq!(fish.population_cap, Some(40));
+        assert_eq!(fish.wander, Some(WanderStyle::Hover));
+
+        let crab = page(Species::Crab, &rules);
+        assert_eq!(stat(&crab, "speed").as_deref(), Some("3/1"));
+        for behavior in ["fights_back", "wears_shells", "lays_eggs"] {
+            assert!(crab.behaviors.contains(&behavior), "{behavior}");
+        }
+        let shark = page(Species::Shark, &rules);
+        assert_eq!(stat(&shark, "reach").as_deref(), Some("2"));
+        assert!(shark.behaviors.contains(&"territorial"));
+        assert_eq!(shark.temperament, ["bold", "aloof"]);
+        assert!(!shark.eats.iter().any(|(prey, _)| *prey == Species::Shark));
+
+        // plants and everything else only get what applies to them
+        let kelp = page(Species::Kelp, &rules);
+        assert!(kelp.eats.is_empty() && kelp.mates_with.is_empty());
+        assert_eq!(kelp.wander, None);
+        assert_eq!(kelp.food_value, 100);
+        assert!(kelp.behaviors.contains(&"gives_cover"));
+        let urchin = page(Species::Urchin, &rules);
+        assert!(stat(&urchin, "damage").is_some());
+        assert!(stat(&urchin, "hp").is_none());
+        assert!(urchin.eaten_by.is_empty());
+
+        assert_eq!(field_guide(&rules, 100).len(), Species::all().len());
+    }
+
+    #[test]
+    fn test_pages_follow_the_rules() {
+        let (rules, ignored) = InteractionMatrix::parse(
+            "eats fish shark hungry\nmates fish crab any\ncap fish 12\nwander fish still\nfood fish 7",
+        );
+        assert!(ignored.is_empty());
+        let fish = page(Species::Fish, &rules);
+        assert!(fish.eats.contains(&(Species::Shark, Appetite::Hungry)));
+        assert!(fish.mates_with.contains(&(Species::Crab, Pairing::Any)));
+        assert_eq!(fish.population_cap, Some(12));
+        assert_eq!(fish.wander, Some(WanderStyle::Still));
+        assert_eq!(fish.food_value, 7);
+        // and the other side of every rule too
+        let shark = page(Species::Shark, &rules);
+        assert!(shark.eaten_by.contains(&(Species::Fish, Appetite::Hungry)));
+        let crab = page(Species::Crab, &rules);
+        assert!(crab.mates_with.contains(&(Species::Fish, Pairing::Any)));
+    }
+}
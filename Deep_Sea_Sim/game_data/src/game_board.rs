@@ -1,20 +1,49 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fmt::{Display, Write};
 use std::sync::{Arc, RwLock};
 
 use log::debug;
+use rand::seq::IteratorRandom;
 use rand::Rng;
 
+use crate::arena::{EntityArena, Lent};
+use crate::chunks::Chunks;
 use crate::entities::animals::ConcreteAnimals;
+use crate::entities::display_spec::GlyphStyle;
 use crate::entities::nonliving::ConcreteDecorations;
-use crate::entities::plants::ConcretePlants;
-use crate::entities::{generate_creatures, Entity, NonAbstractTaxonomy, PTUIDisplay};
-use crate::entity_control::{EntityManager, TrackedEntity};
+use crate::entities::plants::{ConcretePlants, Plants};
+use crate::entities::{Entity, NonAbstractTaxonomy, NonLiving, PTUIDisplay, Species};
+use crate::entity_control::{EntityID, EntityManager, EntityUpdates, TrackedEntity};
+use crate::error::Error;
+use crate::interaction_matrix::InteractionMatrix;
+use crate::setup::SetupError;
+use crate::weather::Weather;
 
 /// Percentage of tiles to fill with decorations after adding creatures.
 const DECORATION_PERCENT: f64 = 0.1;
 
-/// Percentage of tiles to fill with plants after adding creatures.
-const PLANT_PERCENTAGE: f64 = 0.15;
+/// What an empty tile looks like.
+pub const EMPTY_GLYPH: char = '⬛';
+/// What an empty tile looks like on a board drawn in ASCII.
+pub const EMPTY_ASCII: char = '.';
+
+/// How much light reaches the top row of the board. Plants there grow a level every tick.
+pub const SURFACE_LIGHT: f64 = 1.0;
+
+/// How much light reaches the bottom row of the board. Rows in between get somewhere between this and SURFACE_LIGHT.
+pub const SEABED_LIGHT: f64 = 0.4;
+
+/// How much light each plant next to a tile blocks out.
+pub const SHADE_PER_PLANT: f64 = 0.1;
+
+/// The least light a tile can get, however deep and crowded it is, so nothing stops growing altogether.
+pub const MIN_LIGHT: f64 = 0.1;
+
+/// How many full grown kelp have to be growing together before they count as a forest.
+pub const FOREST_SIZE: usize = 6;
+
+/// Percentage of tiles to fill with plants after adding creatures, unless told otherwise.
+pub const PLANT_PERCENTAGE: f64 = 0.15;
 
 /// A position somewhere on the board.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -39,175 +68,1012 @@ impl Pos {
             (other.x as f64 - self.x as f64).powf(2.0) + (other.y as f64 - self.y as f64).powf(2.0);
         sum.sqrt().floor() as usize
     }
+
+    /// Get the number of steps to another position on a hex board, where every odd row is shifted half a tile right.
+    pub fn hex_dist_to(&self, other: &Self) -> usize {
+        // switch over to axial coordinates, where the distance is easy to work out
+        let axial = |p: &Pos| {
+            let (x, y) = (p.x as i64, p.y as i64);
+            (x - (y - (y & 1)) / 2, y)
+        };
+        let (q1, r1) = axial(self);
+        let (q2, r2) = axial(other);
+        let (dq, dr) = (q1 - q2, r1 - r2);
+        ((dq.abs() + dr.abs() + (dq + dr).abs()) / 2) as usize
+    }
+
+    /// The tiles a straight line to another position passes over, leaving out both ends. Uses Bresenham's line,
+    /// which doesn't know about hexes, so see Board::tiles_between for a line that follows the board's tiles.
+    pub fn tiles_between(&self, other: &Self) -> Vec<Pos> {
+        let (mut x, mut y) = (self.x as i64, self.y as i64);
+        let (x1, y1) = (other.x as i64, other.y as i64);
+        let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+        let (sx, sy) = ((x1 - x).signum(), (y1 - y).signum());
+        let mut err = dx + dy;
+        let mut tiles = vec![];
+        loop {
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+            if (x, y) == (x1, y1) {
+                return tiles;
+            }
+            tiles.push(Pos {
+                x: x as usize,
+                y: y as usize,
+            });
+        }
+    }
+
+    /// The tiles a straight line to another position passes over on a hex board, leaving out both ends. Every tile
+    /// along it touches the one before it, so there's no slipping through the gap between two hexes.
+    pub fn hex_tiles_between(&self, other: &Self) -> Vec<Pos> {
+        self.hex_line(other, 1e-6)
+    }
+
+    /// A line over the hexes, nudged a little off of the edges between them so that a line running right along one
+    /// always lands on the same side of it. Which side depends on which way it's nudged.
+    fn hex_line(&self, other: &Self, nudge: f64) -> Vec<Pos> {
+        let axial = |p: &Pos| {
+            let (x, y) = (p.x as i64, p.y as i64);
+            ((x - (y - (y & 1)) / 2) as f64, y as f64)
+        };
+        let (q1, r1) = axial(self);
+        let (q2, r2) = axial(other);
+        let steps = self.hex_dist_to(other);
+        (1..steps)
+            .map(|i| {
+                let t = i as f64 / steps as f64;
+                let q = q1 + (q2 - q1) * t + nudge;
+                let r = r1 + (r2 - r1) * t + nudge;
+                let s = -q - r;
+                let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+                let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+                if dq > dr && dq > ds {
+                    rq = -rr - rs;
+                } else if dr > ds {
+                    rr = -rq - rs;
+                }
+                let (q, r) = (rq as i64, rr as i64);
+                Pos {
+                    x: (q + (r - (r & 1)) / 2) as usize,
+                    y: r as usize,
+                }
+            })
+            .collect()
+    }
+}
+
+/// How the tiles on a board fit together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Topology {
+    /// A plain grid, where every tile touches the eight around it.
+    #[default]
+    Square,
+    /// Hexagons, with every odd row shifted half a tile to the right. Every tile touches six others.
+    Hex,
+}
+
+impl Topology {
+    pub const ALL: [Topology; 2] = [Self::Square, Self::Hex];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Square => "square",
+            Self::Hex => "hex",
+        }
+    }
+}
+
+/// Which way the water flows across a board, carrying drifting seeds along with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Current {
+    /// No current to speak of, so seeds drift every which way.
+    #[default]
+    Still,
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Current {
+    pub const ALL: [Current; 5] = [
+        Self::Still,
+        Self::North,
+        Self::East,
+        Self::South,
+        Self::West,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Still => "still",
+            Self::North => "north",
+            Self::East => "east",
+            Self::South => "south",
+            Self::West => "west",
+        }
+    }
+
+    /// The next tile downstream of a position, or None if the water's still or it'd be off the top or left of the board.
+    /// North is up, towards row 0.
+    pub fn downstream(&self, pos: Pos) -> Option<Pos> {
+        let Pos { x, y } = pos;
+        match self {
+            Self::Still => None,
+            Self::North => y.checked_sub(1).map(|y| Pos { x, y }),
+            Self::East => Some(Pos { x: x + 1, y }),
+            Self::South => Some(Pos { x, y: y + 1 }),
+            Self::West => x.checked_sub(1).map(|x| Pos { x, y }),
+        }
+    }
+}
+
+/// One of the four sides of the board, where things can come and go from off the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Edge {
+    pub const ALL: [Edge; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::North => "north",
+            Self::East => "east",
+            Self::South => "south",
+            Self::West => "west",
+        }
+    }
+
+    /// The edge on the far side of the board.
+    pub fn opposite(&self) -> Self {
+        match self {
+            Self::North => Self::South,
+            Self::East => Self::West,
+            Self::South => Self::North,
+            Self::West => Self::East,
+        }
+    }
+}
+
+/// What's on a tile.
+#[derive(Debug, Clone)]
+enum Occupant {
+    /// Something tracked, living in a slot in the board's arena.
+    Tracked(EntityID, usize),
+    /// Scenery without an ID of its own, living right on the tile.
+    Untracked(Box<Entity>),
 }
 
 /// A tile on the game board.
 #[derive(Debug, Clone)]
 pub struct Tile {
-    /// The entity on a board.
-    entity: Option<Entity>,
-    /// The entity manager for a board.
-    entity_manager: Arc<RwLock<EntityManager>>,
+    /// Whatever's on the tile, if anything.
+    occupant: Option<Occupant>,
     /// This tile's position.
     position: Pos,
+    /// How polluted the water here is, from 0 (clean) to 1.
+    pollution: f64,
+    /// How strongly the water here smells of blood, from 0 (not at all) to 1 (fresh).
+    scent: f64,
 }
 
 impl Tile {
+    /// Whether there's anything on the tile, counting anything that's been lent out to be worked on.
     pub fn is_occupied(&self) -> bool {
-        self.entity.is_some()
+        self.occupant.is_some()
     }
 
-    pub fn get_entity(&self) -> &Option<Entity> {
-        &self.entity
+    /// The ID of whatever's on the tile, as long as it's tracked.
+    pub fn get_id(&self) -> Option<EntityID> {
+        match self.occupant {
+            Some(Occupant::Tracked(id, _)) => Some(id),
+            _ => None,
+        }
     }
 
-    pub fn get_entity_mut(&mut self) -> &mut Option<Entity> {
-        &mut self.entity
+    pub fn get_pollution(&self) -> f64 {
+        self.pollution
     }
 
-    pub fn remove_entity(&mut self) -> Option<Entity> {
-        let mut res = self.entity.take();
-        if let Some(ent) = &mut res {
-            if ent.tracked() {
-                let mut em = self.entity_manager.write().unwrap();
-                if let Some(id) = ent.get_id() {
-                    em.update_position(id, None)
-                }
-            }
-        }
-
-        res
+    /// Change how polluted the water here is, keeping it between clean and as bad as it gets.
+    pub fn set_pollution(&mut self, level: f64) {
+        self.pollution = level.clamp(0.0, 1.0);
     }
 
-    #[allow(clippy::result_large_err)]
-    /// Insert an entry. If we're already occupied, returns an error with the passed value.
-    pub fn add_entity(&mut self, mut entity: Entity) -> Result<(), Entity> {
-        if self.entity.is_some() {
-            Err(entity)
-        } else {
-            if entity.tracked() {
-                let mut em = self.entity_manager.write().unwrap();
-                let id = if let Some(ent_id) = entity.get_id() {
-                    ent_id
-                } else {
-                    em.register_new_entity(self.position, &mut entity)
-                };
+    pub fn get_scent(&self) -> f64 {
+        self.scent
+    }
 
-                em.update_position(id, Some(self.position));
-            } else {
-                debug!("Added an untracked entity to the tile")
-            }
-            self.entity = Some(entity);
-            Ok(())
-        }
+    /// Change how strongly the water here smells of blood, keeping it between nothing and fresh.
+    pub fn set_scent(&mut self, level: f64) {
+        self.scent = level.clamp(0.0, 1.0);
     }
 }
 
-/// The board, holding the 2-D vector representation of the game tiles.
+/// The board, holding the game tiles in a single flat vector, one row after another.
 #[derive(Debug)]
 pub struct Board {
-    /// Game tiles making up the game board.
-    board: Vec<Vec<Tile>>,
+    /// Game tiles making up the game board. The tile at (x, y) lives at index y * cols + x.
+    tiles: Vec<Tile>,
+    rows: usize,
+    cols: usize,
+    topology: Topology,
+    current: Current,
+    weather: Weather,
+    entity_manager: Arc<RwLock<EntityManager>>,
+    /// Changes for the entity manager saved up since batch_updates, if they're being batched.
+    pending: Option<EntityUpdates>,
+    /// Every tracked entity on the board. Their tiles only hold onto their IDs.
+    arena: EntityArena,
+    /// Full grown kelp that's part of a forest, as of the last update_forests.
+    forest: HashSet<Pos>,
+    /// The kelp the forests were last worked out from, so they only get worked out again when it changes.
+    forest_kelp: HashSet<Pos>,
+    /// Which parts of the board are being simulated, if it's big enough to be split up.
+    chunks: Chunks,
+    /// How many of everything there is on the board, counting anything that's been lent out.
+    head_counts: HeadCounts,
 }
 
-impl Board {
-    pub fn new(rows: usize, cols: usize, entity_manager: Arc<RwLock<EntityManager>>) -> Self {
-        let mut board = vec![
-            vec![
-                Tile {
-                    entity: None,
-                    entity_manager: Arc::clone(&entity_manager),
-                    position: Pos { x: 0, y: 0 }
-                };
-                cols
-            ];
-            rows
-        ];
-        // whatever you say, clippy
-        for (i, row) in board.iter_mut().enumerate().take(rows) {
-            for (j, tile) in row.iter_mut().enumerate().take(cols) {
-                tile.position = Pos { x: j, y: i }
+/// How many of each species there are on a board, kept up to date as things are put on and taken off it, so counting
+/// them never means looking over every tile.
+#[derive(Debug, Clone, Default)]
+struct HeadCounts {
+    species: HashMap<Species, usize>,
+    /// How many of each species of animal there are from each generation.
+    generations: HashMap<Species, BTreeMap<usize, usize>>,
+}
+
+impl HeadCounts {
+    /// Count something that's just been put on the board.
+    fn add(&mut self, entity: &Entity) {
+        let species = Species::of(entity);
+        *self.species.entry(species).or_default() += 1;
+        if let Some(animal) = entity.animal() {
+            let generations = self.generations.entry(species).or_default();
+            *generations.entry(animal.get_generation()).or_default() += 1;
+        }
+    }
+
+    /// Stop counting something that's just been taken off the board.
+    fn remove(&mut self, entity: &Entity) {
+        let species = Species::of(entity);
+        if let Some(count) = self.species.get_mut(&species) {
+            *count = count.saturating_sub(1);
+        }
+        let (Some(animal), Some(generations)) =
+            (entity.animal(), self.generations.get_mut(&species))
+        else {
+            return;
+        };
+        if let Some(count) = generations.get_mut(&animal.get_generation()) {
+            *count -= 1;
+            if *count == 0 {
+                generations.remove(&animal.get_generation());
             }
         }
+    }
+}
+
+impl Board {
+    pub fn new(rows: usize, cols: usize, entity_manager: Arc<RwLock<EntityManager>>) -> Self {
+        let tiles = (0..rows * cols)
+            .map(|i| Tile {
+                occupant: None,
+                position: Pos {
+                    x: i % cols,
+                    y: i / cols,
+                },
+                pollution: 0.0,
+                scent: 0.0,
+            })
+            .collect();
         Self {
-            // positions are dummy values and will be updated shortly
-            board,
+            tiles,
+            rows,
+            cols,
+            topology: Topology::Square,
+            current: Current::Still,
+            weather: Weather::Calm,
+            entity_manager,
+            pending: None,
+            arena: EntityArena::default(),
+            forest: HashSet::new(),
+            forest_kelp: HashSet::new(),
+            chunks: Chunks::for_board(rows, cols),
+            head_counts: HeadCounts::default(),
+        }
+    }
+
+    /// A copy of the board that has nothing to do with this one, along with its own copy of the entity manager keeping
+    /// track of it. Entity IDs are still handed out from the same count, so the copy never hands out one that's been used.
+    pub(crate) fn deep_copy(&self) -> (Board, Arc<RwLock<EntityManager>>) {
+        let entity_manager = Arc::new(RwLock::new(self.entity_manager.read().unwrap().clone()));
+        let board = Board {
+            tiles: self.tiles.clone(),
+            rows: self.rows,
+            cols: self.cols,
+            topology: self.topology,
+            current: self.current,
+            weather: self.weather,
+            entity_manager: Arc::clone(&entity_manager),
+            pending: self.pending.clone(),
+            arena: self.arena.clone(),
+            forest: self.forest.clone(),
+            forest_kelp: self.forest_kelp.clone(),
+            chunks: self.chunks.clone(),
+            head_counts: self.head_counts.clone(),
+        };
+        (board, entity_manager)
+    }
+
+    /// Which parts of the board are awake.
+    pub fn chunks(&self) -> &Chunks {
+        &self.chunks
+    }
+
+    pub(crate) fn chunks_mut(&mut self) -> &mut Chunks {
+        &mut self.chunks
+    }
+
+    /// Where every tracked entity that something at the given position could take an interest in is. On a board that's
+    /// been split into chunks, that's only the ones in the chunks around it, and otherwise it's every one there is.
+    pub fn tracked_near(&self, pos: Pos) -> Vec<Pos> {
+        if !self.chunks.is_chunked() {
+            return self.arena.positions().collect();
+        }
+        self.chunks
+            .tiles_around(pos, self.dims())
+            .filter(|p| self.get_tile_from_pos(*p).get_id().is_some())
+            .collect()
+    }
+
+    pub fn get_topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Change how the tiles fit together. Nothing on the board moves, but what counts as adjacent does.
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    pub fn get_current(&self) -> Current {
+        self.current
+    }
+
+    pub fn set_current(&mut self, current: Current) {
+        self.current = current;
+    }
+
+    pub fn get_weather(&self) -> Weather {
+        self.weather
+    }
+
+    pub fn set_weather(&mut self, weather: Weather) {
+        self.weather = weather;
+    }
+
+    /// The number of steps it'd take to get from one position to another, ignoring anything in the way.
+    pub fn distance(&self, from: Pos, to: Pos) -> usize {
+        match self.topology {
+            Topology::Square => from.dist_to(&to),
+            Topology::Hex => from.hex_dist_to(&to),
         }
     }
 
     /// Get the dimensions of the game board. Returned as (x, y)
     pub fn dims(&self) -> (usize, usize) {
-        let y = self.board.len();
-        let x = self.board[0].len();
-        (x, y)
+        (self.cols, self.rows)
+    }
+
+    /// Where in the list of tiles a position lives.
+    /// Panics if the position is off the board, rather than quietly wrapping around to the next row.
+    fn index(&self, pos: Pos) -> usize {
+        assert!(
+            self.is_valid_pos(pos),
+            "{pos:?} is off of a {}x{} board",
+            self.cols,
+            self.rows
+        );
+        pos.y * self.cols + pos.x
     }
 
     pub fn get_tile(&self, row: usize, col: usize) -> &Tile {
-        &self.board[row][col]
+        self.get_tile_from_pos(Pos { x: col, y: row })
     }
 
     pub fn get_tile_mut(&mut self, row: usize, col: usize) -> &mut Tile {
-        &mut self.board[row][col]
+        self.get_tile_mut_from_pos(Pos { x: col, y: row })
     }
 
     pub fn get_tile_from_pos(&self, pos: Pos) -> &Tile {
-        &self.board[pos.y][pos.x]
+        &self.tiles[self.index(pos)]
     }
 
     pub fn get_tile_mut_from_pos(&mut self, pos: Pos) -> &mut Tile {
-        &mut self.board[pos.y][pos.x]
+        let index = self.index(pos);
+        &mut self.tiles[index]
     }
 
     /// simply check if a given position is valid insofar as it's in bounds. Don't worry about entities.
     pub fn is_valid_pos(&self, pos: Pos) -> bool {
         // no need to check if less than zero because of usize
-        pos.y < self.board.len() && pos.x < self.board[0].len()
+        pos.y < self.rows && pos.x < self.cols
     }
 
-    pub fn range(&self, radius: usize, include_self: bool, center: Pos) -> Vec<Pos> {
-        let mut ret = Vec::<Pos>::new();
-        let max_y = self.board.len() - 1;
-        let max_x = self.board[max_y].len() - 1;
+    /// Every tile on the board, one row at a time.
+    pub fn iter_tiles(&self) -> impl Iterator<Item = &Tile> {
+        self.tiles.iter()
+    }
 
-        // if both are zero and we're in include self then just return ourselves
-        // if you're doing this though...why
+    /// Every position on the board, one row at a time.
+    pub fn iter_positions(&self) -> impl Iterator<Item = Pos> {
+        let cols = self.cols;
+        (0..self.tiles.len()).map(move |i| Pos {
+            x: i % cols,
+            y: i / cols,
+        })
+    }
 
-        // Yes, this is busy.
-        // what we're doing is going from at least 0 to at most the maximum range of the board - 1, in a radius around us.
-        for i in (f64::max(center.y as f64 - radius as f64, 0.0) as usize)
-            ..=usize::min(center.y + radius, max_y)
-        {
-            for j in (f64::max(center.x as f64 - radius as f64, 0.0) as usize)
-                ..=usize::min(center.x + radius, max_x)
-            {
-                if !include_self && i == center.y && j == center.x {
-                    continue;
+    /// Every entity on the board, along with where it is. Anything that's been lent out to be worked on is left out.
+    pub fn iter_occupied(&self) -> impl Iterator<Item = (Pos, &Entity)> {
+        self.tiles
+            .iter()
+            .filter_map(|tile| Some((tile.position, self.occupant_of(tile)?)))
+    }
+
+    /// What every tile looks like, row by row.
+    pub fn glyphs(&self) -> impl Iterator<Item = char> + '_ {
+        self.tiles.iter().map(|tile| self.glyph_of(tile))
+    }
+
+    fn glyph_of(&self, tile: &Tile) -> char {
+        self.occupant_of(tile)
+            .map_or(EMPTY_GLYPH, Entity::get_display_char)
+    }
+
+    fn occupant_of<'a>(&'a self, tile: &'a Tile) -> Option<&'a Entity> {
+        match tile.occupant.as_ref()? {
+            Occupant::Tracked(_, slot) => self.arena.at(*slot),
+            Occupant::Untracked(entity) => Some(entity),
+        }
+    }
+
+    /// Whatever's on the tile at a position. Anything that's been lent out to be worked on isn't there to be seen.
+    pub fn entity_at(&self, pos: Pos) -> Option<&Entity> {
+        self.occupant_of(self.get_tile_from_pos(pos))
+    }
+
+    pub fn entity_at_mut(&mut self, pos: Pos) -> Option<&mut Entity> {
+        let index = self.index(pos);
+        match self.tiles[index].occupant.as_mut()? {
+            Occupant::Tracked(_, slot) => self.arena.at_mut(*slot),
+            Occupant::Untracked(entity) => Some(entity),
+        }
+    }
+
+    /// The tracked entity with the given ID, wherever it is on the board.
+    pub fn entity(&self, id: EntityID) -> Option<&Entity> {
+        self.arena.get(id)
+    }
+
+    /// Every tracked entity on the board.
+    pub fn arena(&self) -> &EntityArena {
+        &self.arena
+    }
+
+    /// Take whatever's on the tile at a position off the board. Anything that's been lent out stays where it is until
+    /// it's been given back.
+    pub fn remove_entity(&mut self, pos: Pos) -> Option<Entity> {
+        let index = self.index(pos);
+        let entity = match self.tiles[index].occupant.take()? {
+            Occupant::Tracked(id, slot) => {
+                let Some(entity) = self.arena.remove(slot) else {
+                    // lent out, so it has to stay put
+                    self.tiles[index].occupant = Some(Occupant::Tracked(id, slot));
+                    return None;
+                };
+                self.update_position(id, None);
+                entity
+            }
+            Occupant::Untracked(entity) => *entity,
+        };
+        if Species::of(&entity) == Species::Kelp {
+            self.update_vegetation(pos, false);
+        }
+        self.head_counts.remove(&entity);
+        Some(entity)
+    }
+
+    /// Move whatever's on one tile over to another, empty one. Anything tracked only has its ID moved.
+    pub fn move_entity(&mut self, from: Pos, to: Pos) -> Result<(), Error> {
+        if !self.is_valid_pos(to) {
+            return Err(Error::OutOfBounds(to));
+        }
+        let Some(entity) = self.entity_at(from) else {
+            return Err(Error::CantMove { from, to });
+        };
+        let kelp = Species::of(entity) == Species::Kelp;
+        let to_index = self.index(to);
+        if self.tiles[to_index].is_occupied() {
+            return Err(Error::CantMove { from, to });
+        }
+        let from_index = self.index(from);
+        let occupant = self.tiles[from_index].occupant.take();
+        if let Some(Occupant::Tracked(id, _)) = occupant {
+            self.arena.move_to(id, to);
+            self.update_position(id, Some(to));
+        }
+        if kelp {
+            self.update_vegetation(from, false);
+            self.update_vegetation(to, true);
+        }
+        self.tiles[to_index].occupant = occupant;
+        Ok(())
+    }
+
+    /// Lend out whatever's tracked at a position to be worked on. Its tile is kept for it until it's given back.
+    pub(crate) fn lend(&mut self, pos: Pos) -> Option<Lent> {
+        let Some(Occupant::Tracked(id, slot)) = self.get_tile_from_pos(pos).occupant else {
+            return None;
+        };
+        let entity = self.arena.lend(slot)?;
+        Some(Lent {
+            id,
+            slot,
+            pos,
+            entity,
+        })
+    }
+
+    /// Put something that was lent out back on its tile, which has been kept for it the whole time.
+    pub(crate) fn give_back(&mut self, lent: Lent) {
+        self.arena.give_back(lent.slot, lent.entity);
+    }
+
+    /// Give up on something that was lent out and never given back, freeing up the tile that was kept for it.
+    pub(crate) fn forget_lent(&mut self, id: EntityID) {
+        if !self.arena.is_lent(id) {
+            return;
+        }
+        let pos = self.arena.position(id);
+        self.arena.forget(id);
+        if let Some(pos) = pos {
+            let index = self.index(pos);
+            if self.tiles[index].get_id() == Some(id) {
+                self.tiles[index].occupant = None;
+            }
+        }
+        self.update_position(id, None);
+        // there's no telling what it was any more, so everything has to be counted up again
+        let mut head_counts = HeadCounts::default();
+        for (_, entity) in self.iter_occupied() {
+            head_counts.add(entity);
+        }
+        self.head_counts = head_counts;
+    }
+
+    /// Save up every change to the entity manager from here on, instead of taking its lock for each one, until
+    /// apply_updates. Anything on the board that asks where tracked entities are still sees the changes straight away.
+    pub fn batch_updates(&mut self) {
+        self.pending.get_or_insert_with(EntityUpdates::default);
+    }
+
+    /// Make every change saved up since batch_updates to the entity manager, all under one lock.
+    pub fn apply_updates(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            self.entity_manager.write().unwrap().apply(pending);
+        }
+    }
+
+    /// Tell the entity manager where something is now, or save it for later if updates are being batched.
+    fn update_position(&mut self, id: EntityID, pos: Option<Pos>) {
+        match &mut self.pending {
+            Some(pending) => pending.update_position(id, pos),
+            None => self
+                .entity_manager
+                .write()
+                .unwrap()
+                .update_position(id, pos),
+        }
+    }
+
+    /// Tell the entity manager whether there's full grown kelp somewhere, or save it for later if updates are being
+    /// batched.
+    fn update_vegetation(&mut self, pos: Pos, mature_kelp: bool) {
+        match &mut self.pending {
+            Some(pending) => pending.update_vegetation(pos, mature_kelp),
+            None => self
+                .entity_manager
+                .write()
+                .unwrap()
+                .update_vegetation(pos, mature_kelp),
+        }
+    }
+
+    /// Every tile with any pollution on it, along with how much.
+    pub fn iter_polluted(&self) -> impl Iterator<Item = (Pos, f64)> + '_ {
+        self.tiles
+            .iter()
+            .filter(|tile| tile.pollution > 0.0)
+            .map(|tile| (tile.position, tile.pollution))
+    }
+
+    /// Every tile with any scent of blood on it, along with how strong it is.
+    pub fn iter_scented(&self) -> impl Iterator<Item = (Pos, f64)> + '_ {
+        self.tiles
+            .iter()
+            .filter(|tile| tile.scent > 0.0)
+            .map(|tile| (tile.position, tile.scent))
+    }
+
+    /// How many of a species there are on the board, not counting any eggs.
+    pub fn head_count(&self, species: Species) -> usize {
+        self.head_counts.species.get(&species).copied().unwrap_or(0)
+    }
+
+    /// How many of every species of some kind there are on the board, like `Species::is_plant` for every plant.
+    pub fn head_count_of(&self, kind: impl Fn(&Species) -> bool) -> usize {
+        self.head_counts
+            .species
+            .iter()
+            .filter(|(species, _)| kind(species))
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// The furthest generation of a species there is on the board, or 0 if there aren't any of them.
+    pub fn furthest_generation(&self, species: Species) -> usize {
+        self.head_counts
+            .generations
+            .get(&species)
+            .and_then(|generations| generations.keys().next_back())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// How many of a species there are on the board, counting any eggs that are going to hatch into one.
+    pub fn population(&self, species: Species) -> usize {
+        self.iter_occupied()
+            .filter(|(_, e)| {
+                Species::of(e) == species
+                    || e.plant().and_then(Plants::hatches_into) == Some(species)
+            })
+            .count()
+    }
+
+    /// If there are already as many of a species on the board as its population cap allows, what the cap is.
+    pub fn at_population_cap(&self, species: Species, rules: &InteractionMatrix) -> Option<usize> {
+        let (cols, rows) = self.dims();
+        let cap = rules.population_cap(species, cols * rows)?;
+        (self.population(species) >= cap).then_some(cap)
+    }
+
+    /// Every position on the board within some number of steps of a center point, the center included.
+    /// On a square board that's a square around the center, and on a hex board it's a hexagon.
+    /// Positions come one row at a time, and anything that would be off the edge of the board is left out.
+    pub fn positions_within(&self, radius: usize, center: Pos) -> impl Iterator<Item = Pos> {
+        let (cols, rows) = self.dims();
+        let hex = self.topology == Topology::Hex;
+        let xs = center.x.saturating_sub(radius)..center.x.saturating_add(radius + 1).min(cols);
+        let ys = center.y.saturating_sub(radius)..center.y.saturating_add(radius + 1).min(rows);
+        ys.flat_map(move |y| xs.clone().map(move |x| Pos { x, y }))
+            // a hexagon always fits inside the square, so we just need to trim off its corners
+            .filter(move |p| !hex || p.hex_dist_to(&center) <= radius)
+    }
+
+    /// The positions touching this one: eight on a square board (diagonals included) or six on a hex board.
+    /// There's fewer of them along the edges.
+    pub fn neighbors(&self, pos: Pos) -> impl Iterator<Item = Pos> {
+        self.positions_within(1, pos).filter(move |p| *p != pos)
+    }
+
+    /// How much light reaches a tile, between MIN_LIGHT and SURFACE_LIGHT. It fades with depth, from the top row down,
+    /// and every plant on a neighbouring tile shades it a little more.
+    pub fn light_at(&self, pos: Pos) -> f64 {
+        let depth = match self.rows {
+            0 | 1 => 0.0,
+            rows => pos.y as f64 / (rows - 1) as f64,
+        };
+        let light = SURFACE_LIGHT - depth * (SURFACE_LIGHT - SEABED_LIGHT);
+        let shading = self
+            .neighbors(pos)
+            .filter(|p| self.entity_at(*p).is_some_and(|e| e.plant().is_some()))
+            .count();
+        (light - shading as f64 * SHADE_PER_PLANT).max(MIN_LIGHT)
+    }
+
+    /// How many animals of the same species as the one on a tile are right next to it. 0 if there's no animal there.
+    pub fn crowding_at(&self, pos: Pos) -> usize {
+        let Some(species) = self
+            .entity_at(pos)
+            .filter(|e| e.animal().is_some())
+            .map(Species::of)
+        else {
+            return 0;
+        };
+        self.neighbors(pos)
+            .filter(|p| {
+                self.entity_at(*p)
+                    .is_some_and(|e| Species::of(e) == species)
+            })
+            .count()
+    }
+
+    /// Work out which kelp is growing in a forest, if it's changed since we last checked.
+    /// Every patch of full grown kelp with at least FOREST_SIZE touching each other counts as one.
+    pub fn update_forests(&mut self) {
+        let kelp = self
+            .entity_manager
+            .read()
+            .unwrap()
+            .get_mature_kelp()
+            .clone();
+        if kelp == self.forest_kelp {
+            return;
+        }
+        self.forest.clear();
+        let mut seen = HashSet::new();
+        for start in &kelp {
+            if !seen.insert(*start) {
+                continue;
+            }
+            // flood fill out to the rest of the patch
+            let mut patch = vec![*start];
+            let mut queue = VecDeque::from([*start]);
+            while let Some(pos) = queue.pop_front() {
+                for next in self.neighbors(pos) {
+                    if kelp.contains(&next) && seen.insert(next) {
+                        patch.push(next);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            if patch.len() >= FOREST_SIZE {
+                self.forest.extend(patch);
+            }
+        }
+        self.forest_kelp = kelp;
+    }
+
+    /// Whether a tile is in a kelp forest: either part of one, or right up against one.
+    /// Only as up to date as the last update_forests.
+    pub fn in_forest(&self, pos: Pos) -> bool {
+        self.forest.contains(&pos) || self.neighbors(pos).any(|p| self.forest.contains(&p))
+    }
+
+    /// Whether a tile is out of the worst of a storm: in a kelp forest, or tucked up against something that gives cover.
+    pub fn sheltered(&self, pos: Pos) -> bool {
+        self.in_forest(pos)
+            || self
+                .neighbors(pos)
+                .any(|p| self.entity_at(p).is_some_and(Entity::gives_cover))
+    }
+
+    /// The tiles a straight line from one position to another passes over, leaving out both ends.
+    pub fn tiles_between(&self, from: Pos, to: Pos) -> Vec<Pos> {
+        match self.topology {
+            Topology::Square => from.tiles_between(&to),
+            // odd rows stick out past the even ones, so a line running along the edge between two hexes might
+            // go around the outside of the board on one side of it, but never on both
+            Topology::Hex => {
+                let line = from.hex_line(&to, 1e-6);
+                if line.iter().all(|p| self.is_valid_pos(*p)) {
+                    line
+                } else {
+                    from.hex_line(&to, -1e-6)
                 }
-                ret.push(Pos { x: j, y: i });
             }
         }
+    }
+
+    /// Whether something at one position could see something at another, without any cover in between.
+    pub fn line_of_sight(&self, from: Pos, to: Pos) -> bool {
+        self.tiles_between(from, to)
+            .into_iter()
+            .all(|pos| !self.entity_at(pos).is_some_and(Entity::gives_cover))
+    }
+
+    /// How much everything dangerous next to a position would hurt something that moved there.
+    pub fn hazard_damage_near(&self, pos: Pos) -> i64 {
+        self.neighbors(pos)
+            .filter_map(|p| self.entity_at(p)?.hazard())
+            .map(|h| h.damage)
+            .sum()
+    }
+
+    /// Whether moving to a position would get something hurt.
+    pub fn near_hazard(&self, pos: Pos) -> bool {
+        self.hazard_damage_near(pos) > 0
+    }
+
+    /// Knock the rock at a position about. Once it's taken all it can, it shatters into rubble, which spills out onto
+    /// a couple of the empty tiles around it. Returns everywhere that rubble ended up.
+    pub fn knock_rock(&mut self, pos: Pos, knocks: usize, rng: &mut impl Rng) -> Vec<Pos> {
+        let Some(Entity::NonLiving(rock @ NonLiving::Rock(_))) = self.entity_at_mut(pos) else {
+            return vec![];
+        };
+        if !rock.decoration_mut().is_some_and(|d| d.wear(knocks)) {
+            return vec![];
+        }
+        self.remove_entity(pos);
+        self.place_entity(pos, ConcreteDecorations::Rubble.create_new(None))
+            .unwrap(); // we just emptied it
+        let mut rubble = vec![pos];
+        let mut empty: Vec<Pos> = self
+            .neighbors(pos)
+            .filter(|p| !self.get_tile_from_pos(*p).is_occupied())
+            .collect();
+        for _ in 0..rng.gen_range(1..=2) {
+            if empty.is_empty() {
+                break;
+            }
+            let spot = empty.swap_remove(rng.gen_range(0..empty.len()));
+            self.place_entity(spot, ConcreteDecorations::Rubble.create_new(None))
+                .unwrap(); // it's empty, we checked
+            rubble.push(spot);
+        }
+        rubble
+    }
+
+    /// Put an entity on the tile at a given position, as long as it's on the board and empty.
+    pub fn place_entity(&mut self, pos: Pos, mut entity: Entity) -> Result<(), Error> {
+        if !self.is_valid_pos(pos) {
+            return Err(Error::OutOfBounds(pos));
+        }
+        let index = self.index(pos);
+        if self.tiles[index].is_occupied() {
+            return Err(Error::TileOccupied {
+                pos,
+                entity: Box::new(entity),
+            });
+        }
+        if Species::of(&entity) == Species::Kelp {
+            self.update_vegetation(pos, true);
+        }
+        self.head_counts.add(&entity);
+        let occupant = if entity.tracked() {
+            let id = match entity.get_id() {
+                Some(id) => id,
+                None => self.entity_manager.read().unwrap().hand_out_id(&mut entity),
+            };
+            self.update_position(id, Some(pos));
+            Occupant::Tracked(id, self.arena.insert(id, pos, entity))
+        } else {
+            debug!("Added an untracked entity to the tile");
+            Occupant::Untracked(Box::new(entity))
+        };
+        self.tiles[index].occupant = Some(occupant);
+        Ok(())
+    }
 
-        ret
+    /// How many rows or columns in from an edge a position is. Anything on the edge itself is 0 away.
+    pub fn distance_to_edge(&self, pos: Pos, edge: Edge) -> usize {
+        match edge {
+            Edge::North => pos.y,
+            Edge::East => self.cols.saturating_sub(pos.x + 1),
+            Edge::South => self.rows.saturating_sub(pos.y + 1),
+            Edge::West => pos.x,
+        }
+    }
+
+    /// Every position along one edge of the board.
+    pub fn edge_positions(&self, edge: Edge) -> impl Iterator<Item = Pos> + '_ {
+        self.iter_positions()
+            .filter(move |p| self.distance_to_edge(*p, edge) == 0)
+    }
+
+    /// Bring something onto the board from off one of its edges, on any empty tile along it. Returns where it ended up.
+    /// Only fails if there's no room anywhere along the edge.
+    pub fn spawn_on_edge(
+        &mut self,
+        edge: Edge,
+        entity: Entity,
+        rng: &mut impl Rng,
+    ) -> Result<Pos, Error> {
+        let along: Vec<Pos> = self.edge_positions(edge).collect();
+        let free = along
+            .iter()
+            .copied()
+            .filter(|p| !self.get_tile_from_pos(*p).is_occupied())
+            .choose(rng);
+        match free {
+            Some(free) => self.place_entity(free, entity).map(|()| free),
+            // boards are never empty, so there's always at least one tile along every edge
+            None => Err(Error::TileOccupied {
+                pos: along[0],
+                entity: Box::new(entity),
+            }),
+        }
+    }
+
+    /// Send whatever's at a position off the board for good, as long as it's on the given edge.
+    /// Returns what left, if anything did.
+    pub fn despawn_at_edge(&mut self, pos: Pos, edge: Edge) -> Option<Entity> {
+        if self.distance_to_edge(pos, edge) != 0 {
+            return None;
+        }
+        self.remove_entity(pos)
+    }
+
+    /// Put an entity on the tile at a given position, or the closest empty tile to it if that one's taken.
+    /// Returns where it ended up. Only fails if it's out of bounds, or if the board is completely full.
+    pub fn place_nearby(&mut self, pos: Pos, entity: Entity) -> Result<Pos, Error> {
+        if !self.is_valid_pos(pos) {
+            return Err(Error::OutOfBounds(pos));
+        }
+        let (cols, rows) = self.dims();
+        let free = (0..rows.max(cols)).find_map(|radius| {
+            self.positions_within(radius, pos)
+                .find(|p| !self.get_tile_from_pos(*p).is_occupied())
+        });
+        match free {
+            Some(free) => self.place_entity(free, entity).map(|()| free),
+            None => Err(Error::TileOccupied {
+                pos,
+                entity: Box::new(entity),
+            }),
+        }
     }
 }
 
 impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for y in 0..self.board.len() {
-            for x in 0..self.board[y].len() {
-                let tile = self.get_tile(y, x);
-                f.write_char('\u{200B}')?; // zero width space
-                if let Some(ent) = &tile.entity {
-                    let ch = ent.get_display_char();
-                    f.write_char(ch)?;
-                    // some glyphs are (annoyingly) half-size. Try to add a half-space to pad them out.
-                    // note: this is why I made rocks moyai in the first place.
-                    // if matches!(ch, '🪴' | '🪨') {
-                    //     f.write_char(' ')?;
-                    // }
-                } else {
-                    f.write_char('⬛')?;
+        self.render(GlyphStyle::Emoji).fmt(f)
+    }
+}
+
+/// A board drawn in one style or the other, one line per row. See Board::render.
+pub struct RenderedBoard<'a> {
+    board: &'a Board,
+    style: GlyphStyle,
+}
+
+impl Board {
+    /// Draw the board with every species' emoji (the same as printing the board itself), or with plain ASCII
+    /// characters and a `.` for every empty tile. ASCII boards line up in any terminal and only change when the board
+    /// does, so they're what to print in logs and test failures, or compare against in tests.
+    pub fn render(&self, style: GlyphStyle) -> RenderedBoard<'_> {
+        RenderedBoard { board: self, style }
+    }
+}
+
+impl Display for RenderedBoard<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let board = self.board;
+        if self.style == GlyphStyle::Ascii {
+            for row in board.tiles.chunks(board.cols.max(1)) {
+                for tile in row {
+                    f.write_char(board.occupant_of(tile).map_or(EMPTY_ASCII, |entity| {
+                        Species::of(entity).display_spec().ascii
+                    }))?;
                 }
+                f.write_char('\n')?;
+            }
+            return Ok(());
+        }
+        for row in board.tiles.chunks(board.cols.max(1)) {
+            for tile in row {
+                f.write_char('\u{200B}')?; // zero width space
+                f.write_char(board.glyph_of(tile))?;
+                // some glyphs are (annoyingly) half-size. Try to add a half-space to pad them out.
+                // note: this is why I made rocks moyai in the first place.
+                // if matches!(ch, '🪴' | '🪨') {
+                //     f.write_char(' ')?;
+                // }
                 // f.write_char(c)?;
             }
             // f.write_char('.')?;  // use periods to mark grid spaces
@@ -218,29 +1084,64 @@ impl Display for Board {
 }
 
 /// Attempt to populate the board as best as possible.
-/// Returns a vector of the locations of new elements, as well as a usize of the elements we were unable to place in time.
-pub fn populate_board(board: &mut Board, fish: usize, crab: usize, shark: usize) -> Vec<Pos> {
-    let board_rows = board.board.len();
-    let board_cols = board.board[board_rows - 1].len();
+/// Returns the locations of the new elements, or why the board can't hold everything that was asked for.
+pub fn populate_board(
+    board: &mut Board,
+    fish: usize,
+    crab: usize,
+    shark: usize,
+) -> Result<Vec<Pos>, SetupError> {
+    populate_board_with_rng(board, fish, crab, shark, &mut rand::thread_rng())
+}
+
+/// Same as populate_board, but using a given source of randomness, so the same seed always gives the same board.
+pub fn populate_board_with_rng(
+    board: &mut Board,
+    fish: usize,
+    crab: usize,
+    shark: usize,
+    rng: &mut impl Rng,
+) -> Result<Vec<Pos>, SetupError> {
+    populate_board_with_plants(board, fish, crab, shark, PLANT_PERCENTAGE, rng)
+}
+
+/// Same as populate_board_with_rng, but with a say in how much kelp there is.
+/// plant_chance is the odds of any tile that's left over (and didn't get a decoration) starting out with kelp on it.
+pub fn populate_board_with_plants(
+    board: &mut Board,
+    fish: usize,
+    crab: usize,
+    shark: usize,
+    plant_chance: f64,
+    rng: &mut impl Rng,
+) -> Result<Vec<Pos>, SetupError> {
+    let (board_cols, board_rows) = board.dims();
     let board_size = board_rows * board_cols;
 
     if board_size == 0 {
-        panic!("Cannot generate a zero-size board!");
+        return Err(SetupError::BadDimensions {
+            rows: board_rows,
+            cols: board_cols,
+        });
     }
 
+    let overcrowded = SetupError::Overcrowded {
+        creatures: fish + crab + shark,
+        tiles: board_size,
+    };
     if board_size < fish + crab + shark {
-        panic!(
-            "More creatures were given ({}) than there are spaces on the board ({})!",
-            fish + crab + shark,
-            board_size
-        )
+        return Err(overcrowded);
     }
 
-    let fish = generate_creatures(fish, ConcreteAnimals::Fish);
-    let crab = generate_creatures(crab, ConcreteAnimals::Crab);
-    let shark = generate_creatures(shark, ConcreteAnimals::Shark);
-
-    let mut rng = rand::thread_rng();
+    // the creatures come from the same randomness as everything else, so a seeded board always turns out the same
+    let mut generate = |number: usize, kind: ConcreteAnimals| -> Vec<Entity> {
+        (0..number)
+            .map(|_| kind.create_new_with_rng(None, rng))
+            .collect()
+    };
+    let fish = generate(fish, ConcreteAnimals::Fish);
+    let crab = generate(crab, ConcreteAnimals::Crab);
+    let shark = generate(shark, ConcreteAnimals::Shark);
 
     // let's be clever about this and select a random set of tiles
 
@@ -248,89 +1149,69 @@ pub fn populate_board(board: &mut Board, fish: usize, crab: usize, shark: usize)
     let attempts = 10;
     let mut important_tiles = vec![];
 
-    // set up tiles
-    for row in 0..board.board.len() {
-        for col in 0..board.board[row].len() {
-            let tile = &mut board.board[row][col];
-            tile.position = Pos { x: col, y: row };
-        }
-    }
-
     for creatures_of_kind in creatures {
         'creature: for creature in creatures_of_kind {
             // try 5 times to place a creature, or give up if we've gotten horribly unlucky.
             for _ in 0..attempts {
-                let selected_row = rng.gen_range(0..board.board.len());
-                let selected_col = rng.gen_range(0..board.board[selected_row].len());
-                if !board.board[selected_row][selected_col].is_occupied() {
-                    board.board[selected_row][selected_col]
-                        .add_entity(creature)
-                        .unwrap();
-                    important_tiles.push(Pos::from((selected_col, selected_row))); // x, y
+                let y = rng.gen_range(0..board_rows);
+                let x = rng.gen_range(0..board_cols);
+                if !board.get_tile_from_pos(Pos { x, y }).is_occupied() {
+                    board.place_entity(Pos { x, y }, creature).unwrap();
+                    important_tiles.push(Pos { x, y });
                     continue 'creature;
                 }
             }
             // if we failed five times, then just slot it into the first available spot
-            for row in 0..board.board.len() {
-                for col in 0..board.board[row].len() {
-                    let tile = board.get_tile_mut(row, col);
-                    if !tile.is_occupied() {
-                        tile.add_entity(creature).unwrap();
-                        continue 'creature;
-                    }
-                }
+            if let Some(pos) = board
+                .iter_positions()
+                .find(|pos| !board.get_tile_from_pos(*pos).is_occupied())
+            {
+                board.place_entity(pos, creature).unwrap();
+                continue 'creature;
             }
-            // if we make it this far, something's really wrong
-            panic!("Gave up while trying to place a {creature:?} after {attempts} attempts, and iteration.")
+            // we made sure there was room for everyone, so this shouldn't ever happen
+            return Err(overcrowded);
         }
     }
 
     // With all of the creatures placed that we need, we can start to insert some of the other Things in our game board.
     // of course, this is after everything has been placed, so there's a perfectly good chance that we'll end up with less space for material if there's too many creatures
-    for row in 0..board.board.len() {
-        for col in 0..board.board[row].len() {
-            if board.board[row][col].is_occupied() {
-                continue;
-            }
-            if rng.gen_bool(DECORATION_PERCENT) {
-                let decoration = if rng.gen_bool(0.5) {
-                    ConcreteDecorations::Rock.create_new(None)
-                } else {
-                    ConcreteDecorations::Shell.create_new(None)
-                };
-                board.board[row][col].add_entity(decoration).unwrap(); // we've checked! it's unoccupied.
-            } else if rng.gen_bool(PLANT_PERCENTAGE) {
-                let plant_life = ConcretePlants::Kelp.create_new(None);
-                board.board[row][col].add_entity(plant_life).unwrap();
-                important_tiles.push(Pos::from((col, row)))
-            }
+    for pos in board.iter_positions() {
+        if board.get_tile_from_pos(pos).is_occupied() {
+            continue;
+        }
+        if rng.gen_bool(DECORATION_PERCENT) {
+            let decoration = if rng.gen_bool(0.5) {
+                ConcreteDecorations::Rock.create_new(None)
+            } else {
+                ConcreteDecorations::Shell.create_new(None)
+            };
+            board.place_entity(pos, decoration).unwrap(); // we've checked! it's unoccupied.
+        } else if rng.gen_bool(plant_chance) {
+            let plant_life = ConcretePlants::Kelp.create_new(None);
+            board.place_entity(pos, plant_life).unwrap();
+            important_tiles.push(pos)
         }
     }
 
-    important_tiles
+    Ok(important_tiles)
 }
 
 /// A set of testing utilities for manipulating the board.
 pub mod test_utils {
     use super::*;
+    use crate::settings::Strictness;
     use crate::{Pos, Sandbox};
 
     pub fn get_positions_of_type<T: NonAbstractTaxonomy>(
         board: &Board,
         creature_type: T,
     ) -> Vec<Pos> {
-        let mut positions = vec![];
-        for row in 0..board.board.len() {
-            for col in 0..board.board[row].len() {
-                let tile = board.get_tile(row, col);
-                if let Some(ent) = &tile.entity {
-                    if creature_type.same_kind(ent) {
-                        positions.push(Pos { x: col, y: row })
-                    }
-                }
-            }
-        }
-        positions
+        board
+            .iter_occupied()
+            .filter(|(_, ent)| creature_type.same_kind(ent))
+            .map(|(pos, _)| pos)
+            .collect()
     }
 
     /// Create a simple board, placing entities where they belong.
@@ -346,8 +1227,7 @@ pub mod test_utils {
         let mut board = Board::new(rows, cols, Arc::clone(em));
 
         for (pos, entity) in entities {
-            let tile = board.get_tile_mut_from_pos(pos);
-            tile.add_entity(entity).unwrap();
+            board.place_entity(pos, entity).unwrap();
         }
         board
     }
@@ -357,7 +1237,10 @@ pub mod test_utils {
         tick_rate: f64,
         em: &Arc<RwLock<EntityManager>>,
     ) -> Sandbox {
-        Sandbox::new(board, tick_rate, Arc::clone(em))
+        let mut sandbox = Sandbox::new(board, tick_rate, Arc::clone(em));
+        // tests should hear about broken invariants, not have them quietly patched over
+        sandbox.settings.strictness = Strictness::Panic;
+        sandbox
     }
 }
 
@@ -392,15 +1275,11 @@ mod tests {
 
         let center = Pos::from((3, 3));
 
-        let range = testbed.sandbox.board.range(1, false, center);
-
-        assert!(range.iter().all(|p| p.dist_to(&center) == 1)); // also checks that ignore center is working
-        assert_eq!(range.len(), 8);
-
-        let range = testbed.sandbox.board.range(2, true, center);
+        let range: Vec<Pos> = testbed.sandbox.board.positions_within(2, center).collect();
         assert_eq!(range.len(), 25);
 
-        assert!(range.iter().all(|p| p.dist_to(&center) <= 2)); // also checks that ignore center is working
+        assert!(range.iter().all(|p| p.dist_to(&center) <= 2));
+        assert!(range.contains(&center));
     }
 
     #[test]
@@ -408,15 +1287,137 @@ mod tests {
         let testbed = TestBed::new_default(6, 6, 0, 0, 0);
         let center = Pos::from((0, 0));
 
-        let range = testbed.sandbox.board.range(2, false, center);
+        let range = testbed.sandbox.board.positions_within(2, center);
 
-        assert_eq!(range.len(), 8);
+        assert_eq!(range.count(), 9);
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        let testbed = TestBed::builder(2, 3)
+            .terrain(&["r..", "..k"])
+            .with(Pos { x: 1, y: 0 }, Species::Fish)
+            .with(Pos { x: 0, y: 1 }, Species::Crab)
+            .with(Pos { x: 2, y: 0 }, Species::Shark)
+            .build();
+        let board = &testbed.sandbox.board;
+        assert_eq!(board.render(GlyphStyle::Ascii).to_string(), "#FS\nC.k\n");
+        // printing the board itself still draws emoji
+        assert_eq!(
+            board.render(GlyphStyle::Emoji).to_string(),
+            board.to_string()
+        );
+        assert!(board.to_string().contains('🐠'));
     }
 
     #[test]
     pub fn test_board_is_occupied() {
         let testbed = TestBed::new_populated(6, 6, vec![(Pos::from((0, 0)), ConcretePlants::Kelp)]);
-        assert!(testbed.sandbox.board.board[0][0].is_occupied())
+        assert!(testbed.sandbox.board.get_tile(0, 0).is_occupied())
+    }
+
+    #[test]
+    fn test_moving_keeps_arena_entry() {
+        let from = Pos::from((0, 0));
+        let to = Pos::from((2, 1));
+        let mut testbed = TestBed::new_populated(3, 3, vec![(from, ConcretePlants::Kelp)]);
+        let board = &mut testbed.sandbox.board;
+        let id = board.get_tile_from_pos(from).get_id().unwrap();
+
+        board.move_entity(from, to).unwrap();
+        assert!(board.entity_at(from).is_none());
+        assert_eq!(board.get_tile_from_pos(to).get_id(), Some(id));
+        assert_eq!(board.entity(id).and_then(Entity::get_id), Some(id));
+        assert_eq!(board.arena().len(), 1);
+        // nothing left behind to move, and nowhere to move onto
+        assert!(board.move_entity(from, to).is_err());
+        assert!(board.move_entity(to, Pos::from((5, 5))).is_err());
+    }
+
+    #[test]
+    fn test_lent_tiles_stay_taken() {
+        let pos = Pos::from((1, 1));
+        let mut testbed = TestBed::new_populated(3, 3, vec![(pos, ConcretePlants::Kelp)]);
+        let board = &mut testbed.sandbox.board;
+
+        let lent = board.lend(pos).unwrap();
+        assert!(board.arena().is_lent(lent.id));
+        assert!(board.entity_at(pos).is_none());
+        assert!(board.get_tile_from_pos(pos).is_occupied());
+        assert!(board.remove_entity(pos).is_none());
+        assert!(board
+            .place_entity(pos, Species::Rock.create_new(None))
+            .is_err());
+
+        board.give_back(lent);
+        assert_eq!(board.arena().lent_out().count(), 0);
+        assert!(board.remove_entity(pos).is_some());
+        assert!(board.arena().is_empty());
+    }
+
+    #[test]
+    fn test_head_counts() {
+        let kelp = Pos::from((0, 0));
+        let shark = Pos::from((2, 2));
+        let mut testbed = TestBed::new_populated(3, 3, vec![(kelp, ConcretePlants::Kelp)]);
+        let board = &mut testbed.sandbox.board;
+        let mut entity = Species::Shark.create_new(None);
+        entity.reproduction_mut().unwrap().generation = 3;
+        board.place_entity(shark, entity).unwrap();
+        board
+            .place_entity(Pos::from((1, 0)), Species::KelpSeed.create_new(None))
+            .unwrap();
+        assert_eq!(board.head_count(Species::Kelp), 1);
+        assert_eq!(board.head_count_of(Species::is_plant), 2);
+        assert_eq!(board.furthest_generation(Species::Shark), 3);
+        assert_eq!(board.furthest_generation(Species::Fish), 0);
+
+        // moving or lending something out doesn't change how many there are
+        board.move_entity(kelp, Pos::from((0, 1))).unwrap();
+        let lent = board.lend(shark).unwrap();
+        assert_eq!(board.head_count(Species::Kelp), 1);
+        assert_eq!(board.head_count(Species::Shark), 1);
+        board.forget_lent(lent.id);
+        assert_eq!(board.head_count(Species::Shark), 0);
+        assert_eq!(board.furthest_generation(Species::Shark), 0);
+        assert_eq!(board.head_count_of(Species::is_plant), 2);
+
+        board.remove_entity(Pos::from((0, 1)));
+        assert_eq!(board.head_count(Species::Kelp), 0);
+    }
+
+    #[test]
+    fn test_batched_updates_wait_to_be_applied() {
+        let from = Pos::from((0, 0));
+        let to = Pos::from((2, 1));
+        let mut testbed = TestBed::new_populated(3, 3, vec![(from, ConcretePlants::Kelp)]);
+        let board = &mut testbed.sandbox.board;
+        let id = board.get_tile_from_pos(from).get_id().unwrap();
+        let where_is = |board: &Board, id| {
+            let em = board.entity_manager.read().unwrap();
+            em.get_active_entries().get(&id).copied()
+        };
+
+        board.batch_updates();
+        board.move_entity(from, to).unwrap();
+        let added = Pos::from((1, 2));
+        board
+            .place_entity(added, Species::Kelp.create_new(None))
+            .unwrap();
+        let new_id = board.get_tile_from_pos(added).get_id().unwrap();
+        // the manager hasn't heard about any of it yet, but the board already knows where everything is
+        assert_eq!(where_is(board, id), Some(from));
+        assert_eq!(where_is(board, new_id), None);
+        let mut near = board.tracked_near(from);
+        near.sort_by_key(|p| (p.y, p.x));
+        assert_eq!(near, vec![to, added]);
+
+        board.apply_updates();
+        assert_eq!(where_is(board, id), Some(to));
+        assert_eq!(where_is(board, new_id), Some(added));
+        // and once it's been applied, changes go straight through again
+        board.remove_entity(added);
+        assert_eq!(where_is(board, new_id), None);
     }
 
     #[should_panic]
@@ -424,4 +1425,233 @@ mod tests {
     pub fn test_board_too_many_ents() {
         TestBed::new_default(1, 1, 5, 5, 5);
     }
+
+    #[test]
+    pub fn test_board_iterators() {
+        let kelp = Pos::from((2, 1));
+        let testbed = TestBed::new_populated(2, 3, vec![(kelp, ConcretePlants::Kelp)]);
+        let board = &testbed.sandbox.board;
+
+        let positions: Vec<Pos> = board.iter_positions().collect();
+        assert_eq!(positions.len(), 6);
+        assert_eq!(
+            positions[3],
+            Pos::from((0, 1)),
+            "positions should go row by row"
+        );
+        assert!(board
+            .iter_tiles()
+            .zip(&positions)
+            .all(|(tile, pos)| tile.position == *pos));
+
+        let occupied: Vec<Pos> = board.iter_occupied().map(|(pos, _)| pos).collect();
+        assert_eq!(occupied, vec![kelp]);
+    }
+
+    #[test]
+    pub fn test_board_neighbors() {
+        let testbed = TestBed::new_default(6, 6, 0, 0, 0);
+        let board = &testbed.sandbox.board;
+
+        let center = Pos::from((3, 3));
+        let neighbors: Vec<Pos> = board.neighbors(center).collect();
+        assert_eq!(neighbors.len(), 8);
+        assert!(neighbors.iter().all(|p| p.dist_to(&center) == 1));
+
+        assert_eq!(board.neighbors(Pos::from((0, 0))).count(), 3);
+        assert_eq!(board.neighbors(Pos::from((5, 2))).count(), 5);
+    }
+
+    #[test]
+    pub fn test_hex_neighbors() {
+        let testbed = TestBed::builder(6, 6).topology(Topology::Hex).build();
+        let board = &testbed.sandbox.board;
+
+        // odd rows are shifted right, so they lean right to touch the rows around them
+        let odd: Vec<Pos> = board.neighbors(Pos::from((3, 3))).collect();
+        let expected: Vec<Pos> = [(3, 2), (4, 2), (2, 3), (4, 3), (3, 4), (4, 4)]
+            .into_iter()
+            .map(Pos::from)
+            .collect();
+        assert_eq!(odd, expected);
+
+        let even: Vec<Pos> = board.neighbors(Pos::from((2, 2))).collect();
+        let expected: Vec<Pos> = [(1, 1), (2, 1), (1, 2), (3, 2), (1, 3), (2, 3)]
+            .into_iter()
+            .map(Pos::from)
+            .collect();
+        assert_eq!(even, expected);
+        assert!(even
+            .iter()
+            .all(|p| board.distance(*p, Pos::from((2, 2))) == 1));
+
+        assert_eq!(board.neighbors(Pos::from((0, 0))).count(), 2);
+        assert_eq!(board.neighbors(Pos::from((5, 1))).count(), 3);
+        assert_eq!(board.positions_within(2, Pos::from((2, 2))).count(), 19);
+
+        // lines follow the hexes rather than cutting across the gaps between them
+        let (from, to) = (Pos::from((0, 0)), Pos::from((2, 2)));
+        let line = board.tiles_between(from, to);
+        assert_eq!(line, vec![Pos::from((0, 1)), Pos::from((1, 1))]);
+        let line = board.tiles_between(Pos::from((0, 5)), Pos::from((5, 0)));
+        let steps: Vec<Pos> = [Pos::from((0, 5))]
+            .into_iter()
+            .chain(line)
+            .chain([Pos::from((5, 0))])
+            .collect();
+        assert!(steps.windows(2).all(|w| board.distance(w[0], w[1]) == 1));
+        // straight down the right hand side, where the line could clip the corner off the even row in between
+        let line = board.tiles_between(Pos::from((5, 1)), Pos::from((5, 3)));
+        assert_eq!(line, vec![Pos::from((5, 2))]);
+    }
+
+    #[test]
+    pub fn test_hex_distance() {
+        assert_eq!(Pos::from((0, 0)).hex_dist_to(&Pos::from((0, 0))), 0);
+        assert_eq!(Pos::from((0, 0)).hex_dist_to(&Pos::from((5, 0))), 5);
+        // going straight down zigzags, so it's no further than it is on a square board
+        assert_eq!(Pos::from((0, 0)).hex_dist_to(&Pos::from((0, 4))), 4);
+        // but diagonals cost more, since you can only go half a tile sideways per row
+        assert_eq!(Pos::from((0, 0)).hex_dist_to(&Pos::from((4, 4))), 6);
+        assert_eq!(Pos::from((4, 4)).hex_dist_to(&Pos::from((0, 0))), 6);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn test_board_no_wraparound() {
+        let testbed = TestBed::new_default(6, 6, 0, 0, 0);
+        // this would be the first tile of the next row, if we weren't careful
+        testbed.sandbox.board.get_tile_from_pos(Pos::from((6, 0)));
+    }
+
+    #[test]
+    pub fn test_placement_errors() {
+        let center = Pos::from((1, 1));
+        let mut testbed = TestBed::new_populated(3, 3, vec![(center, ConcretePlants::Kelp)]);
+        let board = &mut testbed.sandbox.board;
+
+        let rock = ConcreteDecorations::Rock.create_new(None);
+        let err = board.place_entity(center, rock).unwrap_err();
+        assert!(matches!(err, Error::TileOccupied { pos, .. } if pos == center));
+        // the rock shouldn't be lost just because it didn't fit
+        let rock = err.into_entity().unwrap();
+
+        let off_board = Pos::from((3, 0));
+        assert!(matches!(
+            board.place_nearby(off_board, rock.clone()),
+            Err(Error::OutOfBounds(pos)) if pos == off_board
+        ));
+
+        let placed = board.place_nearby(center, rock).unwrap();
+        assert_eq!(placed.dist_to(&center), 1);
+        assert!(board.get_tile_from_pos(placed).is_occupied());
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        let (from, to) = (Pos::from((0, 0)), Pos::from((4, 2)));
+        assert_eq!(
+            from.tiles_between(&to),
+            vec![Pos::from((1, 1)), Pos::from((2, 1)), Pos::from((3, 2))]
+        );
+        assert!(from.tiles_between(&Pos::from((1, 1))).is_empty());
+        assert!(from.tiles_between(&from).is_empty());
+
+        // fish don't block the view, but kelp and rocks do
+        let mut testbed =
+            TestBed::new_populated(3, 5, vec![(Pos::from((1, 1)), ConcreteAnimals::Fish)]);
+        let board = &mut testbed.sandbox.board;
+        assert!(board.line_of_sight(from, to));
+        assert!(board.line_of_sight(to, from));
+        board
+            .place_entity(Pos::from((2, 1)), ConcretePlants::Kelp.create_new(None))
+            .unwrap();
+        assert!(!board.line_of_sight(from, to));
+        assert!(board.line_of_sight(from, Pos::from((4, 0))));
+        board
+            .place_entity(
+                Pos::from((2, 0)),
+                ConcreteDecorations::Rock.create_new(None),
+            )
+            .unwrap();
+        assert!(!board.line_of_sight(from, Pos::from((4, 0))));
+    }
+
+    #[test]
+    fn test_light_levels() {
+        let mut testbed = TestBed::new_with_entities(5, 3, vec![]);
+        let board = &mut testbed.sandbox.board;
+        assert_eq!(board.light_at(Pos::from((1, 0))), SURFACE_LIGHT);
+        assert_eq!(board.light_at(Pos::from((1, 4))), SEABED_LIGHT);
+        let middle = board.light_at(Pos::from((1, 2)));
+        assert!(SEABED_LIGHT < middle && middle < SURFACE_LIGHT);
+
+        // a crowd of kelp shades out the tile they're all around, but never blocks out all the light
+        for x in [0, 2] {
+            for y in 3..5 {
+                board
+                    .place_entity(Pos::from((x, y)), ConcretePlants::Kelp.create_new(None))
+                    .unwrap();
+            }
+        }
+        assert_eq!(board.light_at(Pos::from((1, 4))), MIN_LIGHT);
+        assert!(board.light_at(Pos::from((1, 2))) < middle);
+    }
+
+    #[test]
+    fn test_forests() {
+        let mut testbed = TestBed::new_with_entities(3, 4, vec![]);
+        let board = &mut testbed.sandbox.board;
+        // kelp around the edges of the board, with a gap in the middle of the bottom row
+        let patch = [(0, 0), (1, 0), (2, 0), (3, 0), (0, 1), (0, 2), (3, 2)];
+        for (x, y) in patch {
+            board
+                .place_entity(Pos::from((x, y)), ConcretePlants::Kelp.create_new(None))
+                .unwrap();
+        }
+        board.update_forests();
+        assert!(board.in_forest(Pos::from((0, 0))));
+        assert!(board.in_forest(Pos::from((1, 1))));
+        // the kelp off on its own in the corner isn't part of it
+        assert!(!board.in_forest(Pos::from((3, 2))));
+
+        // growing kelp doesn't count until it's full grown
+        board.remove_entity(Pos::from((0, 0)));
+        board
+            .place_entity(Pos::from((0, 0)), ConcretePlants::KelpLeaf.create_new(None))
+            .unwrap();
+        board.update_forests();
+        assert!(!board.in_forest(Pos::from((1, 1))));
+    }
+
+    #[test]
+    fn test_edges() {
+        let mut testbed = TestBed::new_with_entities(3, 4, vec![]);
+        let board = &mut testbed.sandbox.board;
+        let corner = Pos::from((3, 2));
+        assert_eq!(board.distance_to_edge(corner, Edge::East), 0);
+        assert_eq!(board.distance_to_edge(corner, Edge::South), 0);
+        assert_eq!(board.distance_to_edge(corner, Edge::West), 3);
+        assert_eq!(board.distance_to_edge(corner, Edge::North), 2);
+        assert_eq!(board.edge_positions(Edge::North).count(), 4);
+        assert_eq!(board.edge_positions(Edge::West).count(), 3);
+
+        // things can only come on along the edge, and only leave from it
+        let mut rng = rand::thread_rng();
+        for _ in 0..3 {
+            let pos = board
+                .spawn_on_edge(Edge::West, ConcretePlants::Kelp.create_new(None), &mut rng)
+                .unwrap();
+            assert_eq!(pos.x, 0);
+        }
+        let full = board.spawn_on_edge(Edge::West, ConcretePlants::Kelp.create_new(None), &mut rng);
+        assert!(full.unwrap_err().into_entity().is_some());
+        assert!(board
+            .despawn_at_edge(Pos::from((0, 1)), Edge::East)
+            .is_none());
+        assert!(board
+            .despawn_at_edge(Pos::from((0, 1)), Edge::West)
+            .is_some());
+        assert!(!board.get_tile_from_pos(Pos::from((0, 1))).is_occupied());
+    }
 }
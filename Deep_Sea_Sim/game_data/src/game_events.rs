@@ -1,15 +1,19 @@
-use crate::element_traits::Growing;
 use crate::element_traits::Lives;
-use crate::entities::Entity;
-use crate::entities::Living;
-use crate::interactions::Mates;
+use crate::entities::animals::ConcreteAnimals;
+use crate::entities::nonliving::ConcreteHazards;
+use crate::entities::NonAbstractTaxonomy;
+use crate::game_board::Pos;
+use crate::interactions::{Fights, Mates};
+use crate::locale::{tr, tr_with};
+use crate::pollution::{OIL_DAMAGE, POLLUTION_DECAY};
+use crate::settings::Difficulty;
 use crate::Sandbox;
 use rand::Rng;
 
 /// All events will implement this trait
 pub trait Event {
-    /// What the event displays to the user
-    fn get_event_display(&self) -> String;
+    /// What the event asks the player, and what each answer is expected to do at this difficulty.
+    fn get_prompt(&self, difficulty: Difficulty) -> EventPrompt;
 
     /// Process the event
     fn process_event(&self, user_decision: bool, sb: &mut Sandbox);
@@ -21,6 +25,169 @@ pub enum EventTypes {
     OilSpill,
     InvasiveFish,
     Party,
+    VentField,
+    Cleanup,
+    /// One of the events added by a script, along with what it had to say when it went off.
+    Scripted {
+        name: String,
+        prompt: Box<EventPrompt>,
+    },
+}
+
+impl EventTypes {
+    pub const ALL: [EventTypes; 5] = [
+        EventTypes::OilSpill,
+        EventTypes::InvasiveFish,
+        EventTypes::Party,
+        EventTypes::VentField,
+        EventTypes::Cleanup,
+    ];
+
+    /// The name we use for this event in files and commands. Scripted events are named after their script.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::OilSpill => "oil_spill",
+            Self::InvasiveFish => "invasive_fish",
+            Self::Party => "party",
+            Self::VentField => "vent_field",
+            Self::Cleanup => "cleanup",
+            Self::Scripted { name, .. } => name,
+        }
+    }
+
+    /// Only finds the built in events, since scripted ones can't go off without their script.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.name() == name)
+    }
+
+    /// The answer that gets picked when the player doesn't pick one in time: whichever puts the colony out the least.
+    pub fn default_answer(&self) -> bool {
+        match self {
+            Self::OilSpill | Self::InvasiveFish => false,
+            Self::Party | Self::VentField | Self::Cleanup => true,
+            Self::Scripted { prompt, .. } => prompt.default,
+        }
+    }
+}
+
+/// Something an event's answer changes about the colony.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stat {
+    /// How many of the colony's plants make it.
+    Plants,
+    /// The health of anything caught up in it.
+    Health,
+    /// How many animals have to fight off an invader.
+    Fights,
+    /// How hungry the animals get, in ticks' worth of hunger.
+    Hunger,
+    /// How far along the animals are to mating again.
+    Breeding,
+    /// How polluted the water is.
+    Pollution,
+    /// How wide a field of vents opens up.
+    Vents,
+}
+
+impl Stat {
+    pub const ALL: [Stat; 7] = [
+        Self::Plants,
+        Self::Health,
+        Self::Fights,
+        Self::Hunger,
+        Self::Breeding,
+        Self::Pollution,
+        Self::Vents,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Plants => "plants",
+            Self::Health => "health",
+            Self::Fights => "fights",
+            Self::Hunger => "hunger",
+            Self::Breeding => "breeding",
+            Self::Pollution => "pollution",
+            Self::Vents => "vents",
+        }
+    }
+}
+
+/// One of the things an answer is expected to do, like "-67% plants" or "+100% pollution, 200 ticks".
+#[derive(Debug, Clone, PartialEq)]
+pub struct Consequence {
+    pub stat: Stat,
+    /// How much the stat goes up or down by. Whether that's a percentage or a count depends on the stat.
+    pub change: i64,
+    /// How many ticks it lasts for, or None if it's over and done with straight away.
+    pub ticks: Option<usize>,
+}
+
+impl Consequence {
+    pub fn new(stat: Stat, change: i64) -> Self {
+        Self {
+            stat,
+            change,
+            ticks: None,
+        }
+    }
+
+    pub fn lasting(self, ticks: usize) -> Self {
+        Self {
+            ticks: Some(ticks),
+            ..self
+        }
+    }
+
+    /// The consequence in a few words, in the language being played in.
+    pub fn describe(&self) -> String {
+        let text = tr_with(
+            &format!("stat.{}", self.stat.name()),
+            &[
+                ("change", &format!("{:+}", self.change)),
+                ("amount", &self.change.abs()),
+            ],
+        );
+        match self.ticks {
+            Some(ticks) => tr_with(
+                "event.lasting",
+                &[("consequence", &text), ("ticks", &ticks)],
+            ),
+            None => text,
+        }
+    }
+}
+
+/// One of the ways the player can answer an event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventChoice {
+    pub label: String,
+    /// Which picture goes with the choice. The GUI works out what to draw for it, so scripts can use their own.
+    pub icon: String,
+    /// What the choice is expected to do. Empty if it doesn't change anything.
+    pub consequences: Vec<Consequence>,
+    /// What the player gets told once they've picked it.
+    pub outcome: String,
+}
+
+/// Everything the player gets shown when an event goes off.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventPrompt {
+    /// What just happened.
+    pub intro: String,
+    /// The question being put to the player.
+    pub prompt: String,
+    /// The first choice answers false, and the second answers true.
+    pub choices: [EventChoice; 2],
+    /// The answer that gets picked if the player runs out of time.
+    pub default: bool,
+}
+
+impl EventPrompt {
+    /// The choice that gets picked if the player runs out of time.
+    pub fn default_choice(&self) -> &EventChoice {
+        &self.choices[usize::from(self.default)]
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -28,135 +195,93 @@ pub struct GameEvents {
     pub(crate) kind: EventTypes,
 }
 
-#[allow(clippy::format_in_format_args)]
+impl GameEvents {
+    /// What each of a built in event's choices is expected to do, going by what process_event does with them.
+    fn consequences(&self, difficulty: Difficulty) -> [Vec<Consequence>; 2] {
+        let percent = |chance: f64| (difficulty.scale_chance(chance) * 100.0).round() as i64;
+        let hungrier = Consequence::new(Stat::Hunger, 1);
+        let oil = Consequence::new(Stat::Pollution, 100).lasting((1.0 / POLLUTION_DECAY) as usize);
+        match self.kind {
+            EventTypes::OilSpill => [
+                vec![oil.clone()],
+                vec![oil, Consequence::new(Stat::Health, -OIL_DAMAGE)],
+            ],
+            EventTypes::InvasiveFish => [
+                vec![Consequence::new(Stat::Plants, -percent(2.0 / 3.0))],
+                vec![Consequence::new(Stat::Fights, percent(1.0 / 2.0))],
+            ],
+            // parties pull mating along by a quarter (see slow_mate)
+            EventTypes::Party => [vec![Consequence::new(Stat::Breeding, 25), hungrier], vec![]],
+            // capping the vents keeps the field to a radius of 1 rather than 2
+            EventTypes::VentField => [
+                vec![Consequence::new(Stat::Vents, 3), hungrier],
+                vec![Consequence::new(Stat::Vents, 5)],
+            ],
+            EventTypes::Cleanup => [
+                vec![Consequence::new(Stat::Pollution, -100), hungrier],
+                vec![],
+            ],
+            EventTypes::Scripted { .. } => [vec![], vec![]],
+        }
+    }
+}
+
 impl Event for GameEvents {
-    fn get_event_display(&self) -> String {
-        match &self.kind {
-            EventTypes::OilSpill => {
-                format!("{}\n\n{}\n*{}\n*{}",
-                    "Oh no! An oil spill has occurred on the surface of the ocean causing havoc on your colony.", 
-                    "The oil spill is going to impact the growth of your ecosystem. How do you wish to respond?\n\t1. Hide under the plants\n\t2. Continue as normal.",
-                    format!(
-                        "Your fish use the plants for cover, allowing them to survive the brunt of the impact.\nFish reproduction slowed by {}%, Plant reproduction slowed by {}%",
-                        20, 33
-                    ),
-                    format!(
-                        "Your fish continue on as normal, however the toxic effects of the oil take their toll.\nFish reproduction slowed by {}%, Plant reproduction slowed by {}%.",
-                        33, 20
-                    )
-                    )
-            }
-            EventTypes::InvasiveFish => {
-                format!("{}\n\n{}\n*{}\n*{}", 
-                    "A roaming band of fish has come across your colony. They don't look friendly...",
-                    "The invaders are going to do everything in their power to take what is not theirs!\nDo you want your colony to run or fight?\n\t1. Run and live another day!\n\t2. Defend our home!",
-                    format!(
-                        "Your fish hid from the invaders as best they could, unfortunetly your plants were not so lucky.\nYour colony loses plants."
-                    ),
-                    format!(
-                        "Your colony rose to the challenge and fought valiantly.\nYou were able to protect your resources at the cost of your fishes life.\nYou lost fish.",
-                    ))
-            }
-            EventTypes::Party => {
-                format!(
-                        "{}\n\n{}\n*{}\n*{}",
-                        "Your colony want to throw a party!",
-                        "While the party will provide a much needed break for the colony, it might be a considerable cost of resources.\nDo you allow your colony to party?\n\t1. Party like it's 1999!\n\t2. Maybe some other time...",
-                        format!(
-                            "Your fish threw a grand party that was the envy of all seafolk.\nReproduction rate increased.\nHunger increased."
-                        ),
-                        format!(
-                            "Your fish, albiet sad, continued on as normal."
-                        )
-                    )
-            }
+    fn get_prompt(&self, difficulty: Difficulty) -> EventPrompt {
+        if let EventTypes::Scripted { prompt, .. } = &self.kind {
+            return *prompt.clone();
+        }
+        let name = self.kind.name();
+        let text = |part: &str| tr(&format!("event.{name}.{part}"));
+        let [first, second] = self.consequences(difficulty);
+        let choice = |part: &str, consequences| EventChoice {
+            label: text(&format!("{part}_label")),
+            icon: format!("{name}.{part}"),
+            consequences,
+            outcome: text(part),
+        };
+        EventPrompt {
+            intro: text("intro"),
+            prompt: text("prompt"),
+            choices: [choice("first", first), choice("second", second)],
+            default: self.kind.default_answer(),
         }
     }
 
     fn process_event(&self, user_decision: bool, sandbox: &mut Sandbox) {
+        let difficulty = sandbox.settings.difficulty;
         match &self.kind {
-            EventTypes::OilSpill => match user_decision {
-                true => {
-                    // We are going to limit animal reproduction more
-                    for pos in sandbox.get_important_entities() {
-                        let entity = sandbox
-                            .board
-                            .get_tile_mut_from_pos(pos)
-                            .get_entity_mut()
-                            .as_mut()
-                            .unwrap();
-                        match entity {
-                            Entity::Living(l) => match l {
-                                Living::Plants(plant) => plant.slow_growth(5),
-                                Living::Animals(animal) => animal.slow_mate(3.0),
-                            },
-                            Entity::NonLiving(_) => (),
-                        }
-                    }
-                }
-                false => {
-                    // We are going to limit plant reproduction more
-                    for pos in sandbox.get_important_entities() {
-                        let entity = sandbox
-                            .board
-                            .get_tile_mut_from_pos(pos)
-                            .get_entity_mut()
-                            .as_mut()
-                            .unwrap();
-                        match entity {
-                            Entity::Living(l) => match l {
-                                Living::Plants(plant) => plant.slow_growth(3),
-                                Living::Animals(animal) => animal.slow_mate(5.0),
-                            },
-                            Entity::NonLiving(_) => (),
-                        }
-                    }
-                }
-            },
+            EventTypes::Scripted { name, .. } => sandbox.answer_scripted_event(name, user_decision),
+            EventTypes::OilSpill => {
+                // hiding keeps the animals out of the worst of it, but the oil settles either way
+                sandbox.spill_oil(user_decision);
+            }
             EventTypes::InvasiveFish => match user_decision {
                 false => {
                     // We lose plants
                     for pos in sandbox.get_important_entities() {
-                        let entity = sandbox
-                            .board
-                            .get_tile_mut_from_pos(pos)
-                            .get_entity_mut()
-                            .as_mut()
-                            .unwrap();
-                        match entity {
-                            Entity::Living(l) => match l {
-                                Living::Plants(plant) => {
-                                    let mut rng = rand::thread_rng();
-                                    if rng.gen_bool(2.0 / 3.0) {
-                                        plant.die("thievery!");
-                                    }
-                                }
-                                Living::Animals(_) => (),
-                            },
-                            Entity::NonLiving(_) => (),
+                        let entity = sandbox.board.entity_at_mut(pos).unwrap();
+                        if let Some(plant) = entity.plant_mut() {
+                            let mut rng = sandbox.rng.lock().unwrap();
+                            if rng.gen_bool(difficulty.scale_chance(2.0 / 3.0)) {
+                                plant.die("thievery!");
+                            }
                         }
                     }
                 }
                 true => {
-                    // Fish die
+                    // Fish have to fight off the invaders, and not all of them will win
                     for pos in sandbox.get_important_entities() {
-                        let entity = sandbox
-                            .board
-                            .get_tile_mut_from_pos(pos)
-                            .get_entity_mut()
-                            .as_mut()
-                            .unwrap();
-                        match entity {
-                            Entity::Living(l) => match l {
-                                Living::Plants(_) => (),
-                                Living::Animals(animal) => {
-                                    let mut rng = rand::thread_rng();
-                                    if rng.gen_bool(1.0 / 4.0) {
-                                        animal.die("a fight!");
-                                    }
+                        let entity = sandbox.board.entity_at_mut(pos).unwrap();
+                        if let Some(animal) = entity.animal_mut() {
+                            let mut rng = sandbox.rng.lock().unwrap();
+                            if rng.gen_bool(difficulty.scale_chance(1.0 / 2.0)) {
+                                let mut invader =
+                                    ConcreteAnimals::Fish.create_new_with_rng(None, &mut *rng);
+                                if let Some(invader) = invader.animal_mut() {
+                                    invader.fight(animal, &mut *rng);
                                 }
-                            },
-                            Entity::NonLiving(_) => (),
+                            }
                         }
                     }
                 }
@@ -164,31 +289,60 @@ impl Event for GameEvents {
             EventTypes::Party => match user_decision {
                 false => {
                     for pos in sandbox.get_important_entities() {
-                        let entity = sandbox
-                            .board
-                            .get_tile_mut_from_pos(pos)
-                            .get_entity_mut()
-                            .as_mut()
-                            .unwrap();
-                        match entity {
-                            Entity::Living(e) => match e {
-                                Living::Plants(_) => (),
-                                Living::Animals(a) => {
-                                    a.slow_mate(0.8);
-                                    a.process_hunger();
-                                }
-                            },
-                            Entity::NonLiving(_) => (),
+                        let entity = sandbox.board.entity_at_mut(pos).unwrap();
+                        if let Some(a) = entity.animal_mut() {
+                            a.slow_mate(0.8);
+                            a.process_hunger(difficulty);
                         }
                     }
                 }
                 // No party fish sad =(
                 true => (),
             },
+            EventTypes::VentField => {
+                if !user_decision {
+                    for pos in sandbox.get_important_entities() {
+                        let entity = sandbox.board.entity_at_mut(pos).unwrap();
+                        if let Some(a) = entity.animal_mut() {
+                            a.process_hunger(difficulty);
+                        }
+                    }
+                }
+                let radius = if user_decision { 2 } else { 1 };
+                spawn_vent_field(sandbox, radius);
+            }
+            EventTypes::Cleanup => {
+                if !user_decision {
+                    sandbox.scrub_pollution();
+                }
+            }
         }
     }
 }
 
+/// Open up vents on the empty tiles around a random spot on the board. Harder difficulties open up more of them.
+/// The eruption knocks any rocks around the field about too, and the bigger it is, the more likely they are to shatter.
+fn spawn_vent_field(sandbox: &mut Sandbox, radius: usize) {
+    let chance = sandbox.settings.difficulty.scale_chance(1.0 / 2.0);
+    let mut rng = sandbox.rng.lock().unwrap();
+    let (cols, rows) = sandbox.board.dims();
+    let center = Pos {
+        x: rng.gen_range(0..cols),
+        y: rng.gen_range(0..rows),
+    };
+    let field: Vec<Pos> = sandbox.board.positions_within(radius, center).collect();
+    for pos in field {
+        if !sandbox.board.get_tile_from_pos(pos).is_occupied() && rng.gen_bool(chance) {
+            let vent = ConcreteHazards::Vent.create_new(None);
+            sandbox.board.place_entity(pos, vent).unwrap(); // we've checked! it's unoccupied.
+        }
+    }
+    let shaken: Vec<Pos> = sandbox.board.positions_within(radius + 1, center).collect();
+    for pos in shaken {
+        sandbox.board.knock_rock(pos, radius + 1, &mut *rng);
+    }
+}
+
 pub fn get_rand_event(rand_num: usize) -> GameEvents {
     // TODO update this when new events are added
     match rand_num {
@@ -201,6 +355,12 @@ pub fn get_rand_event(rand_num: usize) -> GameEvents {
         2 => GameEvents {
             kind: EventTypes::Party,
         },
+        3 => GameEvents {
+            kind: EventTypes::VentField,
+        },
+        4 => GameEvents {
+            kind: EventTypes::Cleanup,
+        },
         _ => panic!("Unkown event generated!"),
     }
 }
@@ -0,0 +1,170 @@
+// Rewinding the game a few ticks, to get a better look at whatever just happened. Before every tick, the sandbox puts
+// away a copy of every region as it stands, and hangs on to the last few of them. Rewinding puts one of those copies back
+// and forgets everything that came after it. The randomness gets reseeded on the way, so carrying on from there forks off
+// a new timeline instead of playing the same one out again. Anything that's about the colony as a whole rather than its
+// boards, like missions, achievements and family trees, stays as it is.
+// Copying every board every tick isn't free, so nothing gets kept unless asked for (see Sandbox::keep_history).
+
+use std::collections::VecDeque;
+
+use log::info;
+use rand::Rng;
+
+use crate::error::Error;
+use crate::region::Region;
+use crate::Sandbox;
+
+/// How many ticks back the game can be rewound while it's being played.
+pub const REWIND_TICKS: usize = 50;
+
+impl Region {
+    /// A copy of the region that has nothing to do with this one.
+    fn deep_copy(&self) -> Self {
+        let (board, entity_context) = self.board.deep_copy();
+        Self {
+            name: self.name.clone(),
+            board,
+            entity_context,
+            last_event: self.last_event,
+            scripted_events: self.scripted_events.clone(),
+            gateways: self.gateways.clone(),
+        }
+    }
+}
+
+/// Every region as it was before a tick.
+#[derive(Debug)]
+struct Snapshot {
+    clock: usize,
+    resources: usize,
+    /// The region that was loaded at the time.
+    loaded: Region,
+    others: Vec<Region>,
+}
+
+/// The last few ticks, most recent last.
+#[derive(Debug, Default)]
+pub struct History {
+    snapshots: VecDeque<Snapshot>,
+    /// How many ticks to keep. Nothing gets kept if this is 0.
+    capacity: usize,
+}
+
+impl History {
+    /// How many ticks back the game could be rewound right now.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
+
+impl Sandbox {
+    /// Start keeping the last `ticks` ticks around, so the game can be rewound. 0 stops keeping any at all.
+    pub fn keep_history(&mut self, ticks: usize) {
+        self.history.capacity = ticks;
+        while self.history.snapshots.len() > ticks {
+            self.history.snapshots.pop_front();
+        }
+    }
+
+    pub fn get_history(&self) -> &History {
+        &self.history
+    }
+
+    /// Put a copy of every region away, if we're keeping track.
+    pub fn record_history(&mut self) {
+        if self.history.capacity == 0 {
+            return;
+        }
+        let (board, entity_context) = self.board.deep_copy();
+        let loaded = Region {
+            name: self.region_name.clone(),
+            board,
+            entity_context,
+            last_event: self.last_event,
+            scripted_events: self.scripted_events.clone(),
+            gateways: self.gateways.clone(),
+        };
+        self.history.snapshots.push_back(Snapshot {
+            clock: self.clock,
+            resources: self.resources,
+            loaded,
+            others: self.regions.iter().map(Region::deep_copy).collect(),
+        });
+        if self.history.snapshots.len() > self.history.capacity {
+            self.history.snapshots.pop_front();
+        }
+    }
+
+    /// Go back `ticks` ticks, forgetting everything that happened since, and reseed so things can turn out differently
+    /// from here. Returns the tick we ended up back on.
+    pub fn rewind(&mut self, ticks: usize) -> Result<usize, Error> {
+        let kept = self.history.snapshots.len();
+        if ticks == 0 || ticks > kept {
+            return Err(Error::CantRewind { ticks, kept });
+        }
+        self.history.snapshots.truncate(kept - ticks + 1);
+        // we just made sure there was at least one
+        let snapshot = self.history.snapshots.pop_back().unwrap();
+        let loaded = snapshot.loaded;
+        self.region_name = loaded.name;
+        self.board = loaded.board;
+        self.entity_context = loaded.entity_context;
+        self.last_event = loaded.last_event;
+        self.scripted_events = loaded.scripted_events;
+        self.gateways = loaded.gateways;
+        self.regions = snapshot.others;
+        self.clock = snapshot.clock;
+        self.resources = snapshot.resources;
+        // anything that was waiting on an answer hasn't happened yet
+        self.answered_event = None;
+        self.queued_events.clear();
+        self.event_log.retain(|answered| answered.tick < self.clock);
+        self.health.rewind(self.clock);
+        self.populations.rewind(self.clock);
+        let seed = self.rng.lock().unwrap().gen();
+        self.reseed(seed);
+        info!(target: &self.log_target, "Rewound to tick {}", self.clock);
+        Ok(self.clock)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entity_control::TrackedEntity;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_rewind() {
+        let mut sandbox = TestBed::new_default(6, 6, 4, 2, 0).sandbox;
+        assert!(sandbox.rewind(1).is_err());
+        sandbox.keep_history(3);
+        let mut boards = vec![];
+        for _ in 0..4 {
+            boards.push(sandbox.board.to_string());
+            sandbox.tick();
+        }
+        assert_eq!(sandbox.get_history().len(), 3);
+        // the very first tick has fallen off the end
+        assert!(sandbox.rewind(4).is_err());
+
+        assert_eq!(sandbox.rewind(2).unwrap(), 2);
+        assert_eq!(sandbox.clock, 2);
+        assert_eq!(sandbox.board.to_string(), boards[2]);
+        assert_eq!(sandbox.get_history().len(), 1);
+        // everything on the board is still being kept track of, and the game carries on from here
+        assert_eq!(
+            sandbox.get_important_entities().len(),
+            sandbox
+                .board
+                .iter_occupied()
+                .filter(|(_, e)| e.get_id().is_some())
+                .count()
+        );
+        sandbox.tick();
+        assert_eq!(sandbox.clock, 3);
+    }
+}
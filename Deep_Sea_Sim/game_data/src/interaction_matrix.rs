@@ -0,0 +1,612 @@
+// Who eats whom, how filling they are, who can mate with whom, how many of each there can be, what each one puts first
+// how it gets about when it's got nothing better to do and what it's like on average, kept as rows of data instead of spread across trait impls.
+// The built in species' rows are in DEFAULT_RULES, and a species config file can add rows on top of those or override them,
+// so adding a species is a matter of adding rows. Plugin species without any rows fall back on their own eats and eaten_by.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use log::warn;
+
+use crate::digest::fnv1a;
+use crate::entities::components::{Hunger, HungerLevel, Personality};
+use crate::entities::custom::plugin_diet;
+use crate::entities::{Sex, Species};
+
+/// Where the species config is read from, next to the settings.
+pub const SPECIES_FILE: &str = "species.txt";
+
+/// The fewest a share of the board can come to, however small the board is.
+const MIN_SHARE_CAP: usize = 4;
+
+/// The rows for the built in species, written the same way as a species config file.
+pub const DEFAULT_RULES: &str = "\
+# eats <predator> <prey> <when>: prey can also be `animals` or `plants`, to cover every species of either.
+# A row for a specific prey always wins out over one for a whole group.
+eats shark animals always   # sharks can eat anything that isn't themselves
+eats shark shark never
+eats fish crab always       # but they might be killed by them in the process!
+eats fish fish desperate    # fish are a bit more careful about trying to eat other fish
+eats fish plants hungry     # save it for the crabs
+eats crab plants hungry
+eats crab kelp_seed desperate # seeds are only worth digging up once there's nothing else
+eats crab crab desperate    # and once they're famished, nothing's off the menu
+eats crab egg always        # crabs will raid any nest they come across
+eats fish egg hungry
+eats shark egg hungry
+
+# food <prey> <hunger restored>
+food fish 100
+food crab 50
+food shark 500
+food kelp 100               # full kelp is very fulfilling
+food kelp_leaf 25
+food kelp_seed 10           # barely worth it
+food egg 30
+
+# mates <species> <species> <who with>
+mates fish fish opposite_sex
+mates shark shark opposite_sex
+mates crab crab any         # crabs don't need gender
+
+# cap <species> <most>: once there are this many, no more get born. Either a head count, or a share of the board like 25%
+cap fish 40%
+cap crab 30%
+cap shark 15%
+
+# weights <species> <hunger> <mating> <idle> <distance>: how much each weighs on what an animal decides to do next.
+# Distance is how much less something's worth for every tile further away it is.
+weights fish 1 1 0.1 0.1
+weights crab 1.2 0.8 0.1 0.2  # slow going, so anything far off isn't worth the trip
+weights shark 1.5 0.8 0.1 0.05 # they'll cross the whole board for a meal
+
+# wander <species> <style>: how it potters about while idle. One of roam (anywhere nearby, now and then nowhere at all),
+# drift (wherever the current takes it), patrol (round a small loop), hover (never far from a plant) or still.
+wander fish hover           # never far from cover
+wander crab roam
+wander shark patrol         # circling
+
+# temperament <species> <boldness> <laziness> <sociability>: what the species is like on average, each from -1 to 1.
+# Every animal is born somewhere around it, so no two are quite the same.
+temperament fish -0.2 0 0.3  # skittish, and happiest in a crowd
+temperament crab 0.2 0.4 0   # in no hurry
+temperament shark 0.4 0 -0.4 # fear nothing, and keep to themselves
+";
+
+/// When a predator will go for its prey.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Appetite {
+    Never,
+    /// Only when it's hungry or starving.
+    Hungry,
+    /// Only once it's famished, or when it's starving and sure to survive whatever the prey does back.
+    Desperate,
+    Always,
+}
+
+impl Appetite {
+    pub const ALL: [Appetite; 4] = [Self::Never, Self::Hungry, Self::Desperate, Self::Always];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Never => "never",
+            Self::Hungry => "hungry",
+            Self::Desperate => "desperate",
+            Self::Always => "always",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.name() == name)
+    }
+
+    /// Whether a predator this hungry and healthy would go for prey that does this much damage back.
+    pub fn allows(&self, hunger: &Hunger, hp: i64, retaliation: usize) -> bool {
+        match self {
+            Self::Never => false,
+            Self::Hungry => hunger.is_hungry(),
+            Self::Desperate => match hunger.state {
+                HungerLevel::Famished => true,
+                HungerLevel::Starving => hp > retaliation as i64,
+                HungerLevel::Hungry | HungerLevel::Full => false,
+            },
+            Self::Always => true,
+        }
+    }
+}
+
+/// Who a species is willing to mate with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pairing {
+    Never,
+    Any,
+    OppositeSex,
+}
+
+impl Pairing {
+    pub const ALL: [Pairing; 3] = [Self::Never, Self::Any, Self::OppositeSex];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Never => "never",
+            Self::Any => "any",
+            Self::OppositeSex => "opposite_sex",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.name() == name)
+    }
+
+    pub fn allows(&self, a: Sex, b: Sex) -> bool {
+        match self {
+            Self::Never => false,
+            Self::Any => true,
+            Self::OppositeSex => a != b,
+        }
+    }
+}
+
+/// The most of a species there can be before it stops having children.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PopulationCap {
+    /// A head count, however big the board is.
+    Count(usize),
+    /// A share of the board's tiles, from 0 to 1.
+    Share(f64),
+}
+
+impl PopulationCap {
+    /// Read a cap in, either as a head count or as a percentage of the board.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.strip_suffix('%') {
+            Some(percent) => percent
+                .parse::<f64>()
+                .ok()
+                .filter(|p| (0.0..=100.0).contains(p))
+                .map(|p| Self::Share(p / 100.0)),
+            None => text.parse().ok().map(Self::Count),
+        }
+    }
+
+    /// How many that comes to on a board with this many tiles. A share never comes to fewer than a few, so a tiny
+    /// board doesn't stop everything breeding.
+    pub fn on_board(&self, tiles: usize) -> usize {
+        match self {
+            Self::Count(count) => *count,
+            Self::Share(share) => ((tiles as f64 * share).floor() as usize).max(MIN_SHARE_CAP),
+        }
+    }
+}
+
+/// How much a species cares about each of the things it could be doing. Every option an animal has is scored by how
+/// badly it wants to do it (see AIAction::urgency) scaled by these, and the best scoring one wins.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BehaviorWeights {
+    pub hunger: f64,
+    pub mating: f64,
+    /// What just pottering about is worth, which anything else has to beat.
+    pub idle: f64,
+    /// How much less something's worth for every tile further away it is.
+    pub distance: f64,
+}
+
+impl Default for BehaviorWeights {
+    fn default() -> Self {
+        Self {
+            hunger: 1.0,
+            mating: 1.0,
+            idle: 0.1,
+            distance: 0.1,
+        }
+    }
+}
+
+impl BehaviorWeights {
+    /// Read a weights row's numbers in, none of which can be negative.
+    fn parse(words: [&str; 4]) -> Option<Self> {
+        let mut numbers = words
+            .into_iter()
+            .map(|w| w.parse::<f64>().ok().filter(|n| *n >= 0.0));
+        Some(Self {
+            hunger: numbers.next()??,
+            mating: numbers.next()??,
+            idle: numbers.next()??,
+            distance: numbers.next()??,
+        })
+    }
+
+    /// What something this urgent is worth once how far off it is has been taken into account.
+    pub fn score(&self, urgency: f64, distance: usize) -> f64 {
+        urgency / (1.0 + self.distance * distance as f64)
+    }
+}
+
+/// How a species gets about while it's idle. Anything without a row roams.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum WanderStyle {
+    /// Off to any empty tile in reach, or nowhere at all now and then.
+    #[default]
+    Roam,
+    /// A tile at a time downstream, or any which way if the water's still.
+    Drift,
+    /// Round and round a small loop about wherever it started idling.
+    Patrol,
+    /// Roaming, but never too far from the nearest plant.
+    Hover,
+    /// Staying put.
+    Still,
+}
+
+impl WanderStyle {
+    pub const ALL: [WanderStyle; 5] = [
+        Self::Roam,
+        Self::Drift,
+        Self::Patrol,
+        Self::Hover,
+        Self::Still,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Roam => "roam",
+            Self::Drift => "drift",
+            Self::Patrol => "patrol",
+            Self::Hover => "hover",
+            Self::Still => "still",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|w| w.name() == name)
+    }
+}
+
+/// What a diet row's prey covers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Prey {
+    Species(Species),
+    Animals,
+    Plants,
+}
+
+impl Prey {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "animals" => Some(Self::Animals),
+            "plants" => Some(Self::Plants),
+            _ => Species::from_name(name).map(Self::Species),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InteractionMatrix {
+    diet: HashMap<(Species, Prey), Appetite>,
+    food: HashMap<Species, usize>,
+    mating: HashMap<(Species, Species), Pairing>,
+    caps: HashMap<Species, PopulationCap>,
+    weights: HashMap<Species, BehaviorWeights>,
+    wander: HashMap<Species, WanderStyle>,
+    temperament: HashMap<Species, Personality>,
+}
+
+impl Default for InteractionMatrix {
+    fn default() -> Self {
+        let mut matrix = Self::empty();
+        let ignored = matrix.add_rows(DEFAULT_RULES);
+        debug_assert!(ignored.is_empty(), "bad default rows: {ignored:?}");
+        matrix
+    }
+}
+
+impl InteractionMatrix {
+    /// A matrix with no rows at all, so nothing eats or mates with anything unless a plugin says so.
+    pub fn empty() -> Self {
+        Self {
+            diet: HashMap::new(),
+            food: HashMap::new(),
+            mating: HashMap::new(),
+            caps: HashMap::new(),
+            weights: HashMap::new(),
+            wander: HashMap::new(),
+            temperament: HashMap::new(),
+        }
+    }
+
+    /// Load a species config on top of the default rows, falling back on just the defaults if the file can't be read.
+    /// Plugin species need to be registered before this, or their rows won't make sense.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(_) => return Self::default(),
+        };
+        let (matrix, ignored) = Self::parse(&contents);
+        for line in ignored {
+            warn!("Ignoring species rule '{line}'");
+        }
+        matrix
+    }
+
+    /// Read a species config on top of the default rows, along with every line that couldn't be made sense of.
+    pub fn parse(contents: &str) -> (Self, Vec<String>) {
+        let mut matrix = Self::default();
+        let ignored = matrix.add_rows(contents);
+        (matrix, ignored)
+    }
+
+    /// A fingerprint of every rule, which comes out the same for any two matrices with the same rules, however they were
+    /// put together.
+    pub fn fingerprint(&self) -> u64 {
+        let mut rules: Vec<String> = self
+            .diet
+            .iter()
+            .map(|r| format!("{r:?}"))
+            .chain(self.food.iter().map(|r| format!("{r:?}")))
+            .chain(self.mating.iter().map(|r| format!("{r:?}")))
+            .chain(self.caps.iter().map(|r| format!("{r:?}")))
+            .chain(self.weights.iter().map(|r| format!("{r:?}")))
+            .chain(self.wander.iter().map(|r| format!("{r:?}")))
+            .chain(self.temperament.iter().map(|r| format!("{r:?}")))
+            .collect();
+        rules.sort();
+        fnv1a(rules.join("\n").as_bytes())
+    }
+
+    /// Add every row in a species config, replacing any we already had for the same pair.
+    /// Returns the lines that couldn't be made sense of.
+    pub fn add_rows(&mut self, contents: &str) -> Vec<String> {
+        let mut ignored = vec![];
+        for line in contents.lines() {
+            let row = line.split('#').next().unwrap().trim();
+            if row.is_empty() {
+                continue;
+            }
+            let words: Vec<&str> = row.split_whitespace().collect();
+            let ok = match words[..] {
+                ["eats", predator, prey, when] => Species::from_name(predator)
+                    .zip(Prey::from_name(prey))
+                    .zip(Appetite::from_name(when))
+                    .map(|(pair, appetite)| self.diet.insert(pair, appetite))
+                    .is_some(),
+                ["food", prey, amount] => Species::from_name(prey)
+                    .zip(amount.parse().ok())
+                    .map(|(prey, amount)| self.food.insert(prey, amount))
+                    .is_some(),
+                ["mates", a, b, who] => Species::from_name(a)
+                    .zip(Species::from_name(b))
+                    .zip(Pairing::from_name(who))
+                    .map(|(pair, pairing)| self.mating.insert(pair, pairing))
+                    .is_some(),
+                ["cap", species, most] => Species::from_name(species)
+                    .zip(PopulationCap::parse(most))
+                    .map(|(species, cap)| self.caps.insert(species, cap))
+                    .is_some(),
+                ["weights", species, hunger, mating, idle, distance] => Species::from_name(species)
+                    .zip(BehaviorWeights::parse([hunger, mating, idle, distance]))
+                    .map(|(species, weights)| self.weights.insert(species, weights))
+                    .is_some(),
+                ["wander", species, style] => Species::from_name(species)
+                    .zip(WanderStyle::from_name(style))
+                    .map(|(species, style)| self.wander.insert(species, style))
+                    .is_some(),
+                ["temperament", species, boldness, laziness, sociability] => {
+                    Species::from_name(species)
+                        .zip(Personality::parse([boldness, laziness, sociability]))
+                        .map(|(species, average)| self.temperament.insert(species, average))
+                        .is_some()
+                }
+                _ => false,
+            };
+            if !ok {
+                ignored.push(row.to_owned());
+            }
+        }
+        ignored
+    }
+
+    /// When a predator will go for its prey. Rows for the exact prey come first, then rows for its group,
+    /// and if there aren't any of either, it's up to the plugins involved.
+    pub fn appetite(&self, predator: Species, prey: Species) -> Appetite {
+        let group = if prey.is_animal() {
+            Some(Prey::Animals)
+        } else if prey.is_plant() {
+            Some(Prey::Plants)
+        } else {
+            None
+        };
+        let row = self
+            .diet
+            .get(&(predator, Prey::Species(prey)))
+            .or_else(|| group.and_then(|g| self.diet.get(&(predator, g))));
+        match row {
+            Some(appetite) => *appetite,
+            None if plugin_diet(predator, prey) => Appetite::Always,
+            None => Appetite::Never,
+        }
+    }
+
+    /// How much hunger eating the prey restores.
+    pub fn food_value(&self, prey: Species) -> usize {
+        match (self.food.get(&prey), prey) {
+            (Some(amount), _) => *amount,
+            (None, Species::Custom(c)) => c.food_value(),
+            (None, _) => 0,
+        }
+    }
+
+    /// Who two species are willing to mate with, which goes both ways.
+    pub fn pairing(&self, a: Species, b: Species) -> Pairing {
+        let row = self
+            .mating
+            .get(&(a, b))
+            .or_else(|| self.mating.get(&(b, a)));
+        match (row, a) {
+            (Some(pairing), _) => *pairing,
+            // plugin species only ever mate with their own kind
+            (None, Species::Custom(c)) if a == b && c.is_animal() => {
+                if c.animal_stats().sexed {
+                    Pairing::OppositeSex
+                } else {
+                    Pairing::Any
+                }
+            }
+            (None, _) => Pairing::Never,
+        }
+    }
+
+    /// The most of a species there can be on a board with this many tiles, or None if there's no limit.
+    pub fn population_cap(&self, species: Species, tiles: usize) -> Option<usize> {
+        self.caps.get(&species).map(|cap| cap.on_board(tiles))
+    }
+
+    /// What a species puts first when deciding what to do, or the defaults if it doesn't have a row.
+    pub fn weights(&self, species: Species) -> BehaviorWeights {
+        self.weights.get(&species).copied().unwrap_or_default()
+    }
+
+    /// How a species gets about while it's idle, which is roaming unless it has a row saying otherwise.
+    pub fn wander_style(&self, species: Species) -> WanderStyle {
+        self.wander.get(&species).copied().unwrap_or_default()
+    }
+
+    /// What a species is like on average, which is perfectly average unless it has a row saying otherwise.
+    pub fn temperament(&self, species: Species) -> Personality {
+        self.temperament.get(&species).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rows() {
+        let matrix = InteractionMatrix::default();
+        assert_eq!(
+            matrix.appetite(Species::Shark, Species::Fish),
+            Appetite::Always
+        );
+        assert_eq!(
+            matrix.appetite(Species::Shark, Species::Shark),
+            Appetite::Never
+        );
+        assert_eq!(
+            matrix.appetite(Species::Shark, Species::Kelp),
+            Appetite::Never
+        );
+        assert_eq!(
+            matrix.appetite(Species::Crab, Species::KelpSeed),
+            Appetite::Desperate
+        );
+        assert_eq!(
+            matrix.appetite(Species::Crab, Species::KelpLeaf),
+            Appetite::Hungry
+        );
+        assert_eq!(
+            matrix.appetite(Species::Crab, Species::Crab),
+            Appetite::Desperate
+        );
+        assert_eq!(
+            matrix.appetite(Species::Crab, Species::Fish),
+            Appetite::Never
+        );
+        assert_eq!(matrix.food_value(Species::KelpLeaf), 25);
+        assert_eq!(matrix.food_value(Species::Rock), 0);
+        assert_eq!(
+            matrix.pairing(Species::Fish, Species::Fish),
+            Pairing::OppositeSex
+        );
+        assert_eq!(
+            matrix.pairing(Species::Fish, Species::Shark),
+            Pairing::Never
+        );
+        assert_eq!(matrix.population_cap(Species::Shark, 100), Some(15));
+        assert_eq!(
+            matrix.population_cap(Species::Shark, 4),
+            Some(MIN_SHARE_CAP)
+        );
+        assert_eq!(matrix.population_cap(Species::Kelp, 100), None);
+        assert_eq!(matrix.weights(Species::Shark).hunger, 1.5);
+        assert_eq!(matrix.wander_style(Species::Fish), WanderStyle::Hover);
+        assert_eq!(matrix.wander_style(Species::Kelp), WanderStyle::Roam);
+        assert_eq!(matrix.temperament(Species::Crab).laziness, 0.4);
+        assert_eq!(matrix.temperament(Species::Kelp), Personality::default());
+    }
+
+    #[test]
+    fn test_config_rows_override_defaults() {
+        let (matrix, ignored) = InteractionMatrix::parse(
+            "eats crab fish hungry\n\
+             eats shark fish never # sharks have gone off fish\n\
+             food kelp 5\n\
+             mates shark fish any\n\
+             eats crab\n\
+             food kelp lots\n\
+             eats squid fish always\n\
+             cap shark 12\n\
+             cap fish 150%\n\
+             weights crab 2 0 0.5 0\n\
+             weights fish 1 1 -1 0\n\
+             wander crab still\n\
+             wander fish sideways\n\
+             temperament crab 1 -1 0\n\
+             temperament fish 2 0 0",
+        );
+        assert_eq!(
+            ignored,
+            vec![
+                "eats crab",
+                "food kelp lots",
+                "eats squid fish always",
+                "cap fish 150%",
+                "weights fish 1 1 -1 0",
+                "wander fish sideways",
+                "temperament fish 2 0 0"
+            ]
+        );
+        assert_eq!(
+            matrix.appetite(Species::Crab, Species::Fish),
+            Appetite::Hungry
+        );
+        assert_eq!(
+            matrix.appetite(Species::Shark, Species::Fish),
+            Appetite::Never
+        );
+        // the rest of the sharks' group row is still there
+        assert_eq!(
+            matrix.appetite(Species::Shark, Species::Crab),
+            Appetite::Always
+        );
+        assert_eq!(matrix.food_value(Species::Kelp), 5);
+        assert_eq!(matrix.pairing(Species::Fish, Species::Shark), Pairing::Any);
+        assert_eq!(matrix.population_cap(Species::Shark, 1000), Some(12));
+        assert_eq!(matrix.population_cap(Species::Fish, 1000), Some(400));
+        let crab = matrix.weights(Species::Crab);
+        assert_eq!((crab.hunger, crab.mating, crab.distance), (2.0, 0.0, 0.0));
+        assert_eq!(
+            matrix.weights(Species::Fish),
+            InteractionMatrix::default().weights(Species::Fish)
+        );
+        assert_eq!(matrix.weights(Species::Kelp), BehaviorWeights::default());
+        assert_eq!(matrix.wander_style(Species::Crab), WanderStyle::Still);
+        assert_eq!(matrix.wander_style(Species::Fish), WanderStyle::Hover);
+        let crab = matrix.temperament(Species::Crab);
+        assert_eq!((crab.boldness, crab.laziness), (1.0, -1.0));
+    }
+
+    #[test]
+    fn test_appetites() {
+        let mut hunger = Hunger::new(100);
+        assert!(!Appetite::Hungry.allows(&hunger, 100, 0));
+        assert!(Appetite::Always.allows(&hunger, 100, 0));
+        hunger.set(-10);
+        assert!(Appetite::Hungry.allows(&hunger, 100, 0));
+        assert!(Appetite::Desperate.allows(&hunger, 100, 25));
+        assert!(!Appetite::Desperate.allows(&hunger, 20, 25));
+        hunger.set(-50);
+        assert!(Appetite::Desperate.allows(&hunger, 20, 25));
+        assert!(!Appetite::Hungry.allows(&hunger, 100, 0));
+    }
+}
@@ -1,6 +1,34 @@
 // Managing interactions with others.
 
+use rand::Rng;
+
 use crate::element_traits::{Lives, Reproducing};
+use crate::entity_control::{EntityID, TrackedEntity};
+use crate::interaction_matrix::InteractionMatrix;
+
+/// How many blows each side gets in before a fight peters out.
+pub const MAX_ROUNDS: usize = 5;
+
+/// Anything that drops below this share of its health will run instead of carrying on.
+pub const FLEE_THRESHOLD: f64 = 0.25;
+
+/// How many ticks something keeps running for once it's fled.
+pub const FLEE_TICKS: usize = 5;
+
+/// How much stamina anything has once it's fully rested.
+pub const MAX_STAMINA: usize = 100;
+
+/// How much stamina comes back every tick.
+pub const STAMINA_RECOVERY: usize = 5;
+
+/// How much stamina getting out of the way of an attack takes.
+pub const DODGE_STAMINA: usize = 20;
+
+/// How much stamina a missed attack costs the attacker.
+pub const MISS_STAMINA: usize = 25;
+
+/// The best odds anything gets of dodging an attack, however quick and fresh it is.
+pub const MAX_DODGE_CHANCE: f64 = 0.9;
 
 /// Possible results of an action. This can be returned in a vector to possibly signal multiple different types of events.
 pub enum ActionResult {
@@ -22,7 +50,11 @@ pub enum EatResult {
 /// Generic on the creature type being eaten, so different behavior can be defined for eating different kinds of entities.
 pub trait EatsCreatures<T: Lives + Eaten>: Lives {
     /// Try to eat the other entity, possibly dealing damage back to ourselves.
-    fn eat(&mut self, target: &mut T) -> Option<crate::interactions::ActionResult> {
+    fn eat(
+        &mut self,
+        target: &mut T,
+        rules: &InteractionMatrix,
+    ) -> Option<crate::interactions::ActionResult> {
         // plant HP
         // let res = target.on_eat();
         match target.on_eat(self.get_attack(target)) {
@@ -33,7 +65,7 @@ pub trait EatsCreatures<T: Lives + Eaten>: Lives {
                         EatResult::DealDamage(dam) => {
                             self.modify_health(-(dam as i64), "attacked by something")
                         }
-                        EatResult::Eaten => self.restore_hunger(target),
+                        EatResult::Eaten => self.restore_hunger(target, rules),
                     }
                 }
             }
@@ -45,13 +77,13 @@ pub trait EatsCreatures<T: Lives + Eaten>: Lives {
         }
     }
     /// Return whether or not we'd try to eat.
-    fn can_eat(&self, target: &T) -> bool;
+    fn can_eat(&self, target: &T, rules: &InteractionMatrix) -> bool;
 
     /// Restore our own health value based on the target eaten.
-    fn restore_hunger(&mut self, target: &T);
+    fn restore_hunger(&mut self, target: &T, rules: &InteractionMatrix);
 
     /// Get the amount of hunger restored by eating the target.
-    fn hunger_restored(&self, target: &T) -> usize;
+    fn hunger_restored(&self, target: &T, rules: &InteractionMatrix) -> usize;
 
     /// Get the amount of damage dealt to a creature
     fn get_attack(&self, target: &T) -> usize;
@@ -67,10 +99,10 @@ pub trait Eaten: Lives {
 }
 
 /// Defining behavior for something that can mate with other similar entities.
-pub trait Mates: Lives + Reproducing {
+pub trait Mates: Lives + Reproducing + TrackedEntity {
     /// Check if the other target is a compatible mate.
     /// Note that the type bounds restrict us to only be able to mate with something else that lives and reproduces, and is of our own type.
-    fn compatible_mate(&self, target: &Self) -> bool;
+    fn compatible_mate(&self, target: &Self, rules: &InteractionMatrix) -> bool;
 
     /// Don't even bother checking if we can't mate in the first place!
     fn can_mate(&self) -> bool;
@@ -79,18 +111,126 @@ pub trait Mates: Lives + Reproducing {
     fn slow_mate(&mut self, factor: f64);
 
     /// Do the do
-    fn mate(&mut self, target: &mut Self) {
-        if !self.compatible_mate(target) {
+    fn mate(&mut self, target: &mut Self, rules: &InteractionMatrix) {
+        if !self.compatible_mate(target, rules) {
             return;
         }
 
-        self.on_successful_mate();
-        target.on_successful_mate();
+        let (ours, theirs) = (self.get_id(), target.get_id());
+        self.on_successful_mate(theirs);
+        target.on_successful_mate(ours);
     }
 
     /// Update any pregnancy-related stuff for this creature
     fn process_mating(&mut self);
 
-    /// Function called when something successfully mates with this creature.
-    fn on_successful_mate(&mut self);
+    /// Function called when something successfully mates with this creature. The partner's ID is kept so any child
+    /// can be traced back to both of its parents.
+    fn on_successful_mate(&mut self, partner: Option<EntityID>);
+}
+
+/// How a fight turned out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FightOutcome {
+    /// The defender didn't make it.
+    AttackerWon,
+    /// The attacker didn't make it.
+    DefenderWon,
+    AttackerFled,
+    DefenderFled,
+    /// Neither of them gave in before the fight petered out.
+    Draw,
+}
+
+/// Defining behavior for things that can fight each other, without either of them trying to eat the other.
+pub trait Fights: Lives + Sized {
+    /// How hard our blows land, before the roll.
+    fn attack_power(&self) -> usize;
+
+    /// How much of every blow we shrug off.
+    fn defense(&self) -> usize;
+
+    /// Whether we're hurt badly enough that we'd rather run.
+    fn should_flee(&self) -> bool;
+
+    /// Break off from the fight and get away for a while.
+    fn flee(&mut self);
+
+    /// The odds of getting out of the way of an attack, before how quick and how worn out everyone is comes into it.
+    fn evasion(&self) -> f64;
+
+    /// How many tiles we cover in a tick, for working out who can get out of whose way.
+    fn quickness(&self) -> f64;
+
+    /// How much stamina we've got left, out of MAX_STAMINA.
+    fn stamina(&self) -> usize;
+
+    /// Use up some of our stamina.
+    fn tire(&mut self, by: usize);
+
+    /// Start bleeding if we came out of an attack badly hurt (see scent.rs).
+    fn check_wounds(&mut self);
+
+    /// The odds of us getting out of the way of an attacker: our evasion, scaled by how much quicker we are than them
+    /// and by how much we've got left in us. Anything worn out can't get away at all, and a worn out attacker is up to
+    /// twice as easy to get away from.
+    fn dodge_chance(&self, attacker: &Self) -> f64 {
+        let speed = self.quickness() / attacker.quickness().max(1.0);
+        let fresh = |s: &Self| s.stamina() as f64 / MAX_STAMINA as f64;
+        (self.evasion() * speed * fresh(self) * (2.0 - fresh(attacker)))
+            .clamp(0.0, MAX_DODGE_CHANCE)
+    }
+
+    /// Roll to get out of the way of an attack. Dodging takes it out of us, and missing takes it out of the attacker,
+    /// so nobody can keep it up forever.
+    fn dodge(&mut self, attacker: &mut Self, rng: &mut impl Rng) -> bool {
+        if !rng.gen_bool(self.dodge_chance(attacker)) {
+            return false;
+        }
+        self.tire(DODGE_STAMINA);
+        attacker.tire(MISS_STAMINA);
+        true
+    }
+
+    /// Roll for how much damage one of our blows does to the target: somewhere between half and one and a half times
+    /// our attack, less their defense. Every blow that lands does at least a little.
+    fn roll_damage(&self, target: &Self, rng: &mut impl Rng) -> i64 {
+        let roll = self.attack_power() as f64 * rng.gen_range(0.5..=1.5);
+        (roll.round() as i64 - target.defense() as i64).max(1)
+    }
+
+    /// Trade blows until one of us dies or runs, or it all peters out. We always get the first blow in. Whoever lives
+    /// through it badly hurt is left wounded.
+    fn fight(&mut self, target: &mut Self, rng: &mut impl Rng) -> FightOutcome {
+        let outcome = self.trade_blows(target, rng);
+        self.check_wounds();
+        target.check_wounds();
+        outcome
+    }
+
+    /// The blow by blow of a fight.
+    fn trade_blows(&mut self, target: &mut Self, rng: &mut impl Rng) -> FightOutcome {
+        for _ in 0..MAX_ROUNDS {
+            let damage = self.roll_damage(target, rng);
+            target.modify_health(-damage, "a fight");
+            if target.is_dead() {
+                return FightOutcome::AttackerWon;
+            }
+            if target.should_flee() {
+                target.flee();
+                return FightOutcome::DefenderFled;
+            }
+
+            let damage = target.roll_damage(self, rng);
+            self.modify_health(-damage, "a fight");
+            if self.is_dead() {
+                return FightOutcome::DefenderWon;
+            }
+            if self.should_flee() {
+                self.flee();
+                return FightOutcome::AttackerFled;
+            }
+        }
+        FightOutcome::Draw
+    }
 }
@@ -1,38 +1,256 @@
+pub mod achievements;
 mod ai_controller;
+pub mod ancestry;
+pub mod arena;
+pub mod brains;
+pub mod checkpoint;
+pub mod chunks;
+pub mod colony_code;
+pub mod colony_health;
+pub mod commands;
+pub mod console;
+pub mod crash;
+pub mod debug_overlay;
+pub mod digest;
+pub mod editor;
 pub mod element_traits;
 pub mod entities;
 mod entity_control;
+pub mod equilibrium;
+mod error;
+pub mod event_policy;
+pub mod field_guide;
 pub mod game_board;
 pub mod game_events;
+pub mod history;
+pub mod interaction_matrix;
 mod interactions;
+pub mod locale;
+pub mod missions;
+pub mod pollution;
+pub mod pool;
+pub mod profiling;
+pub mod region;
+pub mod runner;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod save_file;
+pub mod save_format;
+pub mod scenario;
+pub mod scent;
+pub mod screenshot;
+pub mod scripting;
+pub mod seasons;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod settings;
+pub mod setup;
+pub mod snapshot;
+pub mod spotlight;
+pub mod statistics;
+pub mod structures;
+pub mod sweep;
+mod systems;
+#[cfg(test)]
 mod test_utils;
 mod tests;
-use ai_controller::AIControlled;
+pub mod tile_report;
+pub mod timelapse;
+pub mod tutorial;
+pub mod voting;
+pub mod weather;
+pub mod world_view;
+use achievements::{Achievement, AchievementTracker, ACHIEVEMENTS_FILE};
+pub use ai_controller::find_path;
+use ancestry::{Lineage, DYNASTIES_SHOWN};
+use brains::Brains;
+use colony_code::ColonyCode;
+use colony_health::{ColonyHealth, HealthTracker, Trend};
+use commands::SandboxCommand;
+use crash::CrashReport;
+use debug_overlay::DebugOverlay;
 use eframe::egui;
+pub use entity_control::EntityID;
 use entity_control::{EntityManager, TrackedEntity};
-use std::thread::sleep;
+pub use error::Error;
 use std::{
-    sync::{mpsc::Sender, Arc, RwLock},
+    collections::VecDeque,
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc, Mutex, RwLock,
+    },
     time::Duration,
 };
 
-use futures::{executor::block_on, future::join_all};
+use futures::executor::block_on;
+use instant::Instant;
 // use async_std;
 
-use element_traits::{Lives, PostProcessResult, Processing, ProcessingContext};
-use entities::{Entity, Living, PTUIDisplay};
-use game_board::{populate_board, Board, Pos, Tile};
-use game_events::GameEvents;
+use editor::EditHistory;
+use element_traits::{Lives, ProcessingContext, SimRng};
+use entities::{animals::Animals, Entity, Living, PTUIDisplay, Species};
+use equilibrium::{PopulationHistory, SpeciesOutlook};
+use event_policy::{AnsweredEvent, EventPolicy};
+use game_board::{populate_board_with_plants, Board, Pos, Topology};
+use game_events::{EventPrompt, EventTypes, GameEvents};
+use history::{History, REWIND_TICKS};
+use interaction_matrix::{InteractionMatrix, SPECIES_FILE};
+use missions::MissionTracker;
+use profiling::{Phase, PhaseTimings};
+use region::{Gateway, Region, DEFAULT_REGION};
+use runner::GuiRunner;
+use scenario::{Scenario, ScenarioError, AUTOSAVE_FILE};
+use scripting::{ScriptRegistry, SCRIPTS_DIR};
+use settings::{SimulationSettings, Strictness};
+use setup::{SetupConfig, SetupError};
+use snapshot::{BoardSnapshot, SnapshotHandle};
+use spotlight::Spotlight;
+use statistics::{entity_stats, EntityStats, GenerationAverages, TraitHistory};
+use structures::STARTING_RESOURCES;
+use tile_report::TileReport;
+use timelapse::Timelapse;
+use voting::Tally;
+use weather::Weather;
 
-use log::{debug, error, info}; // todo configure logging framework
+use log::{debug, error, info, warn}; // todo configure logging framework
 
-use rand::{self, Rng};
+use rand::{self, rngs::StdRng, Rng, SeedableRng};
 
 use crate::game_events::Event;
 
+/// Everything the GUI needs to know about the state of the game after a tick.
+pub struct GameUpdate {
+    /// What every tile on the board looks like.
+    pub board: Arc<BoardSnapshot>,
+    /// A line of info for each of the animals on the board.
+    pub entity_info: Vec<String>,
+    /// What to ask the player about the event that just went off, if there was one.
+    pub event: Option<EventPrompt>,
+    /// Send the user's response to an event back to the game loop through here.
+    pub loop_tx: Sender<bool>,
+    /// Titles of every achievement unlocked so far.
+    pub achievements: Vec<String>,
+    /// Responses to any commands that were run since the last update.
+    pub console: Vec<String>,
+    /// Where every tracked entity is, by ID, so their movement can be matched up between ticks.
+    pub positions: Vec<(EntityID, Pos)>,
+    /// If set, the game loop has crashed, and this is the last update that will be sent.
+    pub crash: Option<CrashReport>,
+    /// How long each part of the last few ticks took.
+    pub timings: PhaseTimings,
+    /// How the board's tiles fit together, so it can be drawn the right way.
+    pub topology: Topology,
+    /// The name of the region the board belongs to.
+    pub region: String,
+    /// Every region that can be switched over to, or empty if there's only the one.
+    pub regions: Vec<String>,
+    /// What the weather's doing over the region being shown.
+    pub weather: Weather,
+    /// Every polluted tile on the board, and how badly.
+    pub pollution: Vec<(Pos, f64)>,
+    /// How many resources the colony has to build with.
+    pub resources: usize,
+    /// The mission the colony's working on, if any, followed by the last few it finished.
+    pub missions: Vec<String>,
+    /// How the traits of every species have averaged out, generation by generation.
+    pub traits: Vec<GenerationAverages>,
+    /// A line for each of the longest running families, longest first.
+    pub dynasties: Vec<String>,
+    /// Where every animal that's carrying a child is.
+    pub pregnant: Vec<Pos>,
+    /// How every animal on the board is doing, oldest first.
+    pub stats: Vec<EntityStats>,
+    /// The animal being followed around, if there is one.
+    pub spotlight: Option<Spotlight>,
+    /// Everything going on at the tile being looked at, if there is one.
+    pub tile_report: Option<TileReport>,
+    /// Whether there's an edit to the board that can be undone, and one that can be redone.
+    pub can_undo: bool,
+    pub can_redo: bool,
+    /// What every animal's AI is up to, if the debug overlay is switched on.
+    pub debug: Option<DebugOverlay>,
+    /// How well the colony's doing as of the last tick, if there's been one yet.
+    pub health: Option<ColonyHealth>,
+    /// Which way the colony's health has been going lately.
+    pub health_trend: Trend,
+    /// Every species whose population looks to be heading for trouble.
+    pub outlook: Vec<SpeciesOutlook>,
+    /// How many of every animal species there were on each of the last few ticks, as (tick, count) pairs, oldest first.
+    pub populations: Vec<(Species, Vec<(usize, usize)>)>,
+    /// The running tally, if the event that just went off is being voted on. It keeps up with the votes as they come in.
+    pub votes: Option<Tally>,
+}
+
+impl GameUpdate {
+    /// Let the GUI know that the game loop is dead. Everything but the report is left empty.
+    fn crashed(report: CrashReport) -> Self {
+        let (loop_tx, _) = std::sync::mpsc::channel();
+        Self {
+            board: Arc::default(),
+            entity_info: vec![],
+            event: None,
+            loop_tx,
+            achievements: vec![],
+            console: vec![],
+            positions: vec![],
+            crash: Some(report),
+            timings: PhaseTimings::default(),
+            topology: Topology::default(),
+            region: String::new(),
+            regions: vec![],
+            weather: Weather::default(),
+            pollution: vec![],
+            resources: 0,
+            missions: vec![],
+            traits: vec![],
+            dynasties: vec![],
+            pregnant: vec![],
+            stats: vec![],
+            spotlight: None,
+            tile_report: None,
+            can_undo: false,
+            can_redo: false,
+            debug: None,
+            health: None,
+            health_trend: Trend::default(),
+            outlook: vec![],
+            populations: vec![],
+            votes: None,
+        }
+    }
+}
+
+/// An event that went off during a tick, waiting to be answered.
+#[derive(Debug, PartialEq)]
+pub struct PendingEvent {
+    /// The region it went off in.
+    pub region: String,
+    pub event: GameEvents,
+}
+
+impl PendingEvent {
+    pub fn kind(&self) -> &EventTypes {
+        &self.event.kind
+    }
+}
+
+/// Everything that happened over the course of a tick.
+#[derive(Debug)]
+pub struct TickReport {
+    /// The tick that was just run, counting up from 1.
+    pub tick: usize,
+    pub event: Option<PendingEvent>,
+    /// Achievements that were unlocked this tick.
+    pub unlocked: Vec<Achievement>,
+    /// How long the whole tick took, across every region.
+    pub elapsed: Duration,
+}
+
 /// Our sandbox is like our "game engine"
+/// The world can be split up into several regions, but only one is loaded at a time: the board, entity context, events and gateways here all belong to it.
 #[derive(Debug)]
 pub struct Sandbox {
+    /// The name of the loaded region.
+    region_name: String,
     /// The game board
     board: Board,
     /// How many ticks we've performed so far.
@@ -43,41 +261,272 @@ pub struct Sandbox {
     last_event: usize,
     /// The general entity context.
     entity_context: Arc<RwLock<EntityManager>>,
+    /// Milestones that the colony has hit so far.
+    achievements: AchievementTracker,
+    /// Events that will fire on a specific tick, no matter what.
+    scripted_events: Vec<(usize, EventTypes)>,
+    /// Custom events and behaviors loaded from script files, if there are any. Every region runs the same ones.
+    scripts: Option<Arc<ScriptRegistry>>,
+    /// Every species whose decisions have been handed over to something other than the built in AI.
+    brains: Brains,
+    /// The species rules the game's played by: who eats whom, who mates with whom and so on. Every region plays by the
+    /// same ones.
+    rules: Arc<InteractionMatrix>,
+    /// Tiles that lead out of the loaded region.
+    gateways: Vec<Gateway>,
+    /// Every region other than the loaded one.
+    regions: Vec<Region>,
+    /// If true, the game loop won't tick, but will still listen for commands.
+    paused: bool,
+    /// Ticks left to run before we stay paused.
+    pending_steps: usize,
+    /// An event the player has answered, which gets carried out at the start of the next tick.
+    answered_event: Option<(PendingEvent, bool)>,
+    /// Events that went off in a few regions at once, waiting for their turn to be asked about, oldest first.
+    queued_events: VecDeque<PendingEvent>,
+    /// Responses to commands that haven't been sent out yet.
+    console_log: Vec<String>,
+    /// The player's preferences.
+    settings: SimulationSettings,
+    /// How long each phase of the last few ticks took.
+    timings: PhaseTimings,
+    /// Where all the randomness in this sandbox comes from. Every region shares it.
+    rng: SimRng,
+    /// Everything this sandbox logs goes out under this target, so several running side by side can be told apart.
+    log_target: String,
+    /// What the colony has gathered to build with. Every region pays into the same stockpile.
+    resources: usize,
+    /// Optional goals handed out to the colony every so often.
+    missions: MissionTracker,
+    /// The average traits of every generation of animals seen in the loaded region.
+    traits: TraitHistory,
+    /// Who's related to who, for every animal seen in any region.
+    lineage: Lineage,
+    /// The animal being followed around, and what it was busy doing as of the last tick.
+    spotlight: Option<(EntityID, String)>,
+    /// The tile the player's looking at, if any, which every update comes with a report on.
+    inspected: Option<Pos>,
+    /// Changes made to the board by hand since the last tick, which can be undone.
+    edits: EditHistory,
+    /// If true, updates come with a look at what every animal's AI is up to.
+    debug_overlay: bool,
+    /// The last few ticks, so the game can be rewound.
+    history: History,
+    /// Screenshots taken every so many ticks, while a timelapse is being recorded.
+    timelapse: Option<Timelapse>,
+    /// How to answer events when nobody else does, if it's been picked.
+    event_policy: Option<EventPolicy>,
+    /// Every event that's been answered, and how.
+    event_log: Vec<AnsweredEvent>,
+    /// How well the colony's been doing over the last few ticks.
+    health: HealthTracker,
+    /// How many of every living species there have been over the last few ticks.
+    populations: PopulationHistory,
+    /// How many threads moves on a chunked board get worked out across.
+    worker_threads: usize,
+    /// The latest picture of the board, patched up whenever the GUI needs a new one.
+    snapshot: Arc<BoardSnapshot>,
+    /// Where new snapshots get published for the GUI to pick up.
+    snapshots: SnapshotHandle,
+    /// The votes on the event that's waiting to be answered, while it's being voted on.
+    poll: Option<Tally>,
+    /// The save being written out in the background, if there's been one.
+    #[cfg(not(target_arch = "wasm32"))]
+    saving: Option<std::thread::JoinHandle<()>>,
 }
 
 impl Sandbox {
     pub fn new(board: Board, tick_rate: f64, entity_context: Arc<RwLock<EntityManager>>) -> Self {
         Self {
+            region_name: String::from(DEFAULT_REGION),
             board,
             clock: 0,
             tick_rate,
             last_event: 0,
             entity_context,
+            achievements: AchievementTracker::default(),
+            scripted_events: vec![],
+            scripts: None,
+            brains: Brains::default(),
+            rules: Arc::new(InteractionMatrix::default()),
+            gateways: vec![],
+            regions: vec![],
+            paused: false,
+            pending_steps: 0,
+            answered_event: None,
+            queued_events: VecDeque::new(),
+            console_log: vec![],
+            settings: SimulationSettings::default(),
+            timings: PhaseTimings::default(),
+            rng: Arc::new(Mutex::new(StdRng::from_entropy())),
+            log_target: String::from(module_path!()),
+            resources: STARTING_RESOURCES,
+            missions: MissionTracker::default(),
+            traits: TraitHistory::default(),
+            lineage: Lineage::default(),
+            spotlight: None,
+            inspected: None,
+            edits: EditHistory::default(),
+            debug_overlay: false,
+            history: History::default(),
+            timelapse: None,
+            event_policy: None,
+            event_log: vec![],
+            health: HealthTracker::default(),
+            populations: PopulationHistory::default(),
+            worker_threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            snapshot: Arc::default(),
+            snapshots: SnapshotHandle::default(),
+            poll: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            saving: None,
+        }
+    }
+
+    /// Set up a sandbox with a randomly filled board, drawing from the given source of randomness.
+    pub fn generate(
+        rows: usize,
+        cols: usize,
+        fish: usize,
+        crab: usize,
+        shark: usize,
+        rng: &mut impl Rng,
+    ) -> Result<Self, SetupError> {
+        let setup = SetupConfig {
+            fish,
+            crab,
+            shark,
+            ..SetupConfig::new(rows, cols)
+        };
+        Self::from_setup(&setup, rng)
+    }
+
+    /// Set up a sandbox with a randomly filled board, as laid out by the setup.
+    /// Fails if the board can't hold everything the setup asks for.
+    pub fn from_setup(setup: &SetupConfig, rng: &mut impl Rng) -> Result<Self, SetupError> {
+        let entity_manager = EntityManager::new();
+        let mut board = Board::new(setup.rows, setup.cols, Arc::clone(&entity_manager));
+        board.set_topology(setup.topology);
+        populate_board_with_plants(
+            &mut board,
+            setup.fish,
+            setup.crab,
+            setup.shark,
+            setup.kelp_chance,
+            rng,
+        )?;
+        let mut sandbox = Self::new(
+            board,
+            SimulationSettings::default().tick_rate,
+            entity_manager,
+        );
+        // carry on from the same randomness, so a seeded setup gives a repeatable game
+        sandbox.reseed(rng.gen());
+        Ok(sandbox)
+    }
+
+    /// Start this sandbox's randomness over from a seed. Two sandboxes set up the same way and given the same seed play out the same.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = Arc::new(Mutex::new(StdRng::seed_from_u64(seed)));
+    }
+
+    /// Send everything this sandbox logs out under a different target.
+    pub fn set_log_target(&mut self, target: &str) {
+        self.log_target = target.to_owned();
+    }
+
+    fn log_target(&self) -> &str {
+        &self.log_target
+    }
+
+    /// Play by a different set of species rules from now on.
+    pub fn set_rules(&mut self, rules: impl Into<Arc<InteractionMatrix>>) {
+        self.rules = rules.into();
+    }
+
+    /// The species rules in use.
+    pub fn rules(&self) -> &InteractionMatrix {
+        &self.rules
+    }
+
+    /// Everything an entity at the given position needs to know to get processed.
+    pub(crate) fn processing_context(&self, position: Pos) -> ProcessingContext {
+        ProcessingContext {
+            position,
+            entity_context: Arc::clone(&self.entity_context),
+            rng: Arc::clone(&self.rng),
+            brains: self.brains.clone(),
+            rules: Arc::clone(&self.rules),
+        }
+    }
+
+    /// Take a look at the board, without being able to change anything.
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+
+    /// How long each part of the last few ticks took.
+    pub fn get_timings(&self) -> &PhaseTimings {
+        &self.timings
+    }
+
+    /// Run one phase of a tick, keeping track of how long it took.
+    fn timed<R>(&mut self, phase: Phase, run: impl FnOnce(&mut Self) -> R) -> R {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("phase", name = phase.name(), tick = self.clock).entered();
+        let start = Instant::now();
+        let result = run(self);
+        self.timings.record(phase, start.elapsed());
+        result
+    }
+
+    /// Switch over to a new set of preferences. This resets the tick rate, too.
+    pub fn apply_settings(&mut self, settings: SimulationSettings) {
+        self.tick_rate = settings.tick_rate;
+        locale::set_language(settings.language);
+        self.settings = settings;
+    }
+
+    /// Write the current state of the game out, so it can be loaded up again later.
+    fn autosave(&mut self) {
+        // big boards take a while to write out, so it's done without holding up the game wherever there are threads
+        #[cfg(not(target_arch = "wasm32"))]
+        if !self.save_in_background(AUTOSAVE_FILE, "Autosave") {
+            warn!(target: self.log_target(), "Skipped an autosave, the last one's still being written");
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let save = Scenario::capture(self, "Autosave");
+            match std::fs::write(AUTOSAVE_FILE, save.to_string()) {
+                Ok(()) => info!(target: self.log_target(), "Autosaved on tick {}", self.clock),
+                Err(e) => error!(target: self.log_target(), "Failed to autosave: {e}"),
+            }
         }
     }
 
     /// Get a list of all the important entities currently on the board.
+    /// They come oldest first, so everything gets processed in the same order every time.
     pub fn get_important_entities(&self) -> Vec<Pos> {
         self.entity_context.read().unwrap().get_active_positions()
     }
 
     fn get_entity_info(&self) -> Vec<String> {
         let mut entities_info = Vec::new();
-        for pos in self.get_important_entities() {
-            let entity = self
-                .board
-                .get_tile_from_pos(pos)
-                .get_entity()
-                .as_ref()
-                .unwrap();
+        // nothing changes on the board while we're looking, so there's no need to copy the list out first
+        let em = self.entity_context.read().unwrap();
+        for pos in em.get_active_entries().values() {
+            let Some(entity) = self.board.entity_at(*pos) else {
+                continue;
+            };
             match entity {
                 Entity::Living(e) => match e {
                     Living::Plants(_) => (),
                     Living::Animals(a) => entities_info.push(format!(
-                        "{}: {} Health = {}",
+                        "{}: {} Health = {}{}",
                         a.get_id().unwrap().get_id_val(),
                         a.get_display_char(),
                         a.get_health(),
+                        if a.is_pregnant() { " (expecting)" } else { "" },
                     )),
                 },
                 // Don't care about living entities
@@ -90,318 +539,366 @@ impl Sandbox {
 
     /// Perform some sanity checks in between different segments of the game loop.
     /// These are mostly checks to make sure our invariants are being upheld.
-    /// This should probably be removed once we go gold.
     /// after: The step this one followed.
-    /// Note that this function will panic if its invariants fail! It's to ensure that we don't end up with bad behavior
-    fn sanity_check(&self, after: &str) {
-        if !cfg!(debug_assertions) {
-            // disable these checks in release
+    /// In strict mode this will panic if its invariants fail, to make sure we don't end up with bad behavior.
+    /// Otherwise, it drops any entries in the processing list that point at tiles that something else (or nothing) is on.
+    /// This runs after every step of every tick, so it goes over the list as it is, without copying anything.
+    pub(crate) fn sanity_check(&mut self, after: &str) {
+        let strictness = self.settings.strictness;
+        if strictness == Strictness::Panic && !cfg!(debug_assertions) {
+            // don't go panicking in release
             return;
         }
-        let important_entities = self.get_important_entities();
-        if important_entities.is_empty() {
-            info!("Important entities list is empty!");
+        let mut problems = vec![];
+        let mut stale = vec![];
+        // nothing should still be out being worked on in between steps
+        let lent: Vec<EntityID> = self.board.arena().lent_out().collect();
+        for id in &lent {
+            problems.push(format!("{id:?} was lent out and never given back!"));
         }
-        for pos in &important_entities {
-            let tile = self.board.get_tile_from_pos(*pos);
-            if !tile.is_occupied() {
-                panic!("Checking after {after}: {tile:?} at pos {pos:?} at was in the processing list, while its entity was none!")
+        {
+            let em = self.entity_context.read().unwrap();
+            if em.get_active_entries().is_empty() {
+                info!(target: self.log_target(), "Important entities list is empty!");
             }
-        }
-        let slice = &[&important_entities];
-        let duplicates_exist = (1..slice.len()).any(|i| slice[i..].contains(&slice[i - 1])); // borrowed from stack overflow
-        if duplicates_exist {
-            panic!("Checking after {after}: Duplicate positions exist in the active list!")
-        }
-    }
-
-    pub fn run_game_loop(
-        &mut self,
-        tx: Sender<(String, Vec<String>, String, Sender<bool>)>,
-        ctx: egui::Context,
-    ) {
-        let sleep_time = (1000.0 / self.tick_rate).floor() as u64;
-        let (loop_tx, loop_rx) = std::sync::mpsc::channel();
-        loop {
-            let loop_start = std::time::Instant::now();
-            self.handle_moves();
-            self.sanity_check("moves");
-            self.handle_processing();
-            self.sanity_check("processing");
-
-            block_on(self.handle_late_processing());
-            self.sanity_check("late_processing");
-
-            let entity_info = self.get_entity_info();
-
-            let event = self.handle_events();
-            let pause = event.is_some();
-            self.sanity_check("Events");
-
-            let time_elapsed = loop_start.elapsed();
-            let tickrate_in_ms = (1.0 / self.tick_rate) * 1000.0;
-            let tickrate_consumed = ((time_elapsed.as_millis() as f64) / tickrate_in_ms) * 100.0; //
-
-            println!("Event loop took {}ms to execute, given a tickrate of {}hz it consumed {:.4}% of the tick.", time_elapsed.as_millis(), self.tick_rate, tickrate_consumed);
-
-            self.clock += 1;
-            sleep(Duration::from_millis(sleep_time));
-            if !pause {
-                let _ = tx.send((
-                    self.board.to_string(),
-                    entity_info,
-                    String::new(),
-                    loop_tx.clone(),
-                ));
-                ctx.request_repaint();
-            } else {
-                let _ = tx.send((
-                    self.board.to_string(),
-                    entity_info,
-                    event.as_ref().unwrap().get_event_display().clone(),
-                    loop_tx.clone(),
-                ));
-                ctx.request_repaint();
-                'outer: loop {
-                    if let Ok(user_inp) = loop_rx.try_recv() {
-                        event.unwrap().process_event(user_inp, self);
-                        loop {
-                            if loop_rx.try_recv().is_ok() {
-                                break 'outer;
-                            }
-                            std::thread::sleep(std::time::Duration::from_millis(sleep_time));
-                        }
+            for (id, pos) in em.get_active_entries() {
+                // every tile keeps hold of the ID of whatever's on it, even while it's lent out
+                let tile = self.board.get_tile_from_pos(*pos);
+                match tile.get_id() {
+                    Some(here) if here == *id => (),
+                    _ if !tile.is_occupied() => {
+                        problems.push(format!("{id:?} at pos {pos:?} was in the processing list, while its entity was none!"));
+                        stale.push(*id);
+                    }
+                    _ => {
+                        problems.push(format!(
+                            "{id:?} was in the active list at {pos:?}, but something else is there!"
+                        ));
+                        stale.push(*id);
                     }
-                    std::thread::sleep(std::time::Duration::from_millis(sleep_time));
                 }
             }
         }
-    }
-
-    /// Handle the movement for everything interesting on the board
-    fn handle_moves(&mut self) {
-        // run through all of our pieces and see where they would like to move
-        for pos in &self.get_important_entities() {
-            let x = pos.x;
-            let y = pos.y;
-            let tile = self.board.get_tile(y, x);
-            let ctx = ProcessingContext {
-                position: *pos,
-                entity_context: Arc::clone(&self.entity_context),
-            };
-            let new_move = match tile.get_entity() {
-                None => None, // should this panic?
-                Some(ent) => {
-                    match ent {
-                        Entity::NonLiving(_) => None,
-                        Entity::Living(l) => {
-                            match l {
-                                Living::Plants(_) => None, // plants don't move (yet)
-                                Living::Animals(a) => a.get_desired_move(&ctx, &self.board),
-                            }
-                        }
-                    }
+        if problems.is_empty() {
+            return;
+        }
+        match strictness {
+            Strictness::Panic => panic!("Checking after {after}: {}", problems.join(" ")),
+            Strictness::Repair => {
+                for problem in problems {
+                    warn!(target: self.log_target(), "Checking after {after}: {problem} Repairing.");
                 }
-            };
-            if let Some(new_pos) = new_move {
-                // check that the new position is available
-                if !self.board.is_valid_pos(new_pos) {
-                    println!(
-                        "Failed to move {:?}: tried to move out of bounds!",
-                        tile.get_entity()
-                    );
-                    continue;
+                // whatever never came back is gone for good, so its tile's freed up
+                for id in lent {
+                    self.board.forget_lent(id);
                 }
-                let other_tile = self.board.get_tile(new_pos.y, new_pos.x);
-                if other_tile.is_occupied() {
-                    println!(
-                        "Failed to move {:?} from {pos:?}: space {new_pos:?} already occupied by {:?}!",
-                        tile.get_entity(),
-                        other_tile.get_entity()
-                    );
-                    continue;
-                } else {
-                    let tile_mut = self.board.get_tile_mut(y, x);
-                    let our_entity = tile_mut.remove_entity();
-                    let other_tile_mut = self.board.get_tile_mut(new_pos.y, new_pos.x);
-                    let _ = other_tile_mut.add_entity(our_entity.unwrap());
+                let mut em = self.entity_context.write().unwrap();
+                for id in stale {
+                    em.update_position(id, None);
                 }
             }
         }
     }
 
-    /// Run processing, possibly on a few different entities across the board.
-    fn handle_processing(&mut self) {
-        // need this before the loop since we're immutably running over it
-        for pos in &self.get_important_entities() {
-            let tile = self.board.get_tile_mut_from_pos(*pos);
-            // pop the entity out from the tile.
-            // we can't get a mutable ref to the board with a mutable borrow of the tile outstanding,
-            // so we pull it out and return it later.
-            // if it doesn't get returned to some tile, then it'll be automatically dropped from the processing list.
-            let mut entity = tile.remove_entity();
-            let ctx = ProcessingContext {
-                position: *pos,
-                entity_context: Arc::clone(&self.entity_context),
-            };
-            let action_hint = match &mut entity {
-                None => panic!("Entity at pos {pos:?} was none!"),
-                Some(ent) => {
-                    match ent {
-                        Entity::NonLiving(_) => None,
-                        Entity::Living(l) => {
-                            match l {
-                                Living::Plants(p) => p.process(&mut self.board, ctx),
-                                Living::Animals(a) => a.process(&mut self.board, ctx), // returns an option
-                            }
-                        }
-                    }
-                }
-            };
-
-            // Use this helper variable to indicate that you want to automatically add the entity back afterwards.
-            // Note that since we removed the entity from the board entirely, we'd probably end up in a nasty situation if there are duplicate entities
-            let mut add_self_after = true;
-
-            match action_hint {
-                None => (),
-                Some(h) => {
-                    match h {
-                        PostProcessResult::TryToAddEntities(_)
-                        | PostProcessResult::TryToAddEntitiesAndKillMe(_) => todo!(),
-                        PostProcessResult::MarkTheseAsInteresting(these) => {
-                            info!("Marked these ({these:?}) as interesting");
-                            add_self_after = true;
-                        }
-                        PostProcessResult::Delete => {
-                            info!("entity {entity:?} at {pos:?} was deleted in process");
-                            add_self_after = false;
-                        }
-                        PostProcessResult::ReplaceMeWith(e) => {
-                            let tile = self.board.get_tile_mut_from_pos(*pos);
-                            let old_e = tile.remove_entity(); // drop it on the floor
-                            info!("Replacing {old_e:?} with {e:?}");
-                            let _ = tile.add_entity(e); // and slap the new one in
-                            add_self_after = false; // but don't manually re-add our entity to the tile. It's gone.
-                        }
-                    };
+    /// Put an entity back on the board at its position, or as close to it as we can get if something else got there first.
+    /// Returns where it ended up. Only if the board is completely full does the entity get lost.
+    fn put_back(&mut self, pos: Pos, entity: Entity) -> Option<Pos> {
+        match self.board.place_nearby(pos, entity) {
+            Ok(placed) => {
+                if placed != pos {
+                    warn!(target: self.log_target(), "{pos:?} was taken, so its entity was moved over to {placed:?}");
                 }
+                Some(placed)
             }
-
-            if add_self_after {
-                // borrow again, so hopefully the compiler realizes it can drop the old board borrow
-                let tile = self.board.get_tile_mut_from_pos(*pos);
-                if let Err(ent) = tile.add_entity(entity.unwrap()) {
-                    error!("While processing, {:?} ended up on tile {pos:?}, which is occupied by {:?}", tile.get_entity(), ent);
-                    error!("{ent:?} will be dropped!");
-                }
+            Err(e) => {
+                error!(target: self.log_target(), "Couldn't put an entity back near {pos:?} ({e}), so it's been lost!");
+                None
             }
         }
     }
 
-    /// Helper function to create futures for late-processing entities.
-    /// This function takes in an owned entity, runs its late processing, and then returns all the components.
-    /// It takes in and returns its position so that we can reconstruct its place later on after we've joined all the futures
-    async fn late_process_entity(
-        ent: Entity,
-        position: Pos,
-    ) -> Option<(Entity, Pos, Option<PostProcessResult>)> {
-        let mut ent = ent;
-        match &mut ent {
-            Entity::Living(l) => match l {
-                Living::Plants(p) => {
-                    let hint = p.late_process().await;
-                    Some((ent, position, hint))
-                }
-                Living::Animals(a) => {
-                    let hint = a.late_process().await;
-                    Some((ent, position, hint))
-                }
-            },
-            _ => None,
+    /// How the traits of every species have averaged out over the generations so far.
+    pub fn get_trait_history(&self) -> &TraitHistory {
+        &self.traits
+    }
+
+    /// How well the colony's been doing over the last few ticks.
+    pub fn get_colony_health(&self) -> &HealthTracker {
+        &self.health
+    }
+
+    /// How many of every living species there have been over the last few ticks.
+    pub fn get_population_history(&self) -> &PopulationHistory {
+        &self.populations
+    }
+
+    /// Every species whose population looks to be heading for trouble, going by the last few ticks.
+    pub fn population_warnings(&self) -> Vec<SpeciesOutlook> {
+        self.populations
+            .outlook(&self.rules)
+            .into_iter()
+            .filter(SpeciesOutlook::is_warning)
+            .collect()
+    }
+
+    /// Who's related to who, for every animal seen so far.
+    pub fn get_lineage(&self) -> &Lineage {
+        &self.lineage
+    }
+
+    /// A line for each of the few longest running families, longest first.
+    fn get_dynasty_summary(&self) -> Vec<String> {
+        self.lineage
+            .longest_dynasties(DYNASTIES_SHOWN)
+            .into_iter()
+            .filter_map(|(id, depth)| {
+                let founder = self.lineage.get(id)?;
+                Some(format!(
+                    "{} #{}: {depth} generations",
+                    founder.species.name(),
+                    id.get_id_val()
+                ))
+            })
+            .collect()
+    }
+
+    /// Get the titles of every achievement unlocked so far.
+    fn get_achievement_titles(&self) -> Vec<String> {
+        self.achievements
+            .get_unlocked()
+            .iter()
+            .map(|a| a.title())
+            .collect()
+    }
+
+    /// Bundle up the current state of the game to send over to the GUI.
+    fn make_update(
+        &mut self,
+        entity_info: Vec<String>,
+        event: Option<EventPrompt>,
+        loop_tx: &Sender<bool>,
+    ) -> GameUpdate {
+        GameUpdate {
+            board: self.refresh_snapshot(),
+            entity_info,
+            event,
+            loop_tx: loop_tx.clone(),
+            achievements: self.get_achievement_titles(),
+            console: std::mem::take(&mut self.console_log),
+            positions: self
+                .entity_context
+                .read()
+                .unwrap()
+                .get_active_entries()
+                .iter()
+                .map(|(id, pos)| (*id, *pos))
+                .collect(),
+            crash: None,
+            timings: self.timings.clone(),
+            topology: self.board.get_topology(),
+            region: self.region_name.clone(),
+            regions: self.get_region_names(),
+            weather: self.board.get_weather(),
+            pollution: self.board.iter_polluted().collect(),
+            resources: self.resources,
+            missions: self.get_mission_summary(),
+            traits: self.traits.get_generations().to_vec(),
+            dynasties: self.get_dynasty_summary(),
+            pregnant: self
+                .board
+                .iter_occupied()
+                .filter(|(_, e)| e.animal().is_some_and(Animals::is_pregnant))
+                .map(|(pos, _)| pos)
+                .collect(),
+            stats: entity_stats(&self.board),
+            spotlight: self.get_spotlight(),
+            tile_report: self
+                .inspected
+                .and_then(|pos| self.board.tile_report(pos).ok()),
+            can_undo: self.edits.can_undo(),
+            can_redo: self.edits.can_redo(),
+            debug: self.get_debug_overlay(),
+            health: self.health.latest().cloned(),
+            health_trend: self.health.trend(),
+            outlook: self.population_warnings(),
+            populations: Species::all()
+                .into_iter()
+                .filter(Species::is_animal)
+                .map(|species| (species, self.populations.series(species)))
+                .collect(),
+            votes: self.poll.clone(),
         }
     }
 
-    /// Run all of our late-processing behavior.
-    /// Stuff in here should be designed as atomically as possible, and should only involve things that deal with the creature itself.
-    async fn handle_late_processing(&mut self) {
-        // need this before the loop since we're immutably running over it
-        let mut new_important_entites: Vec<Pos> = vec![];
-        // Hang onto all the futures we'll be working with.
-        let mut futures = vec![];
-        // run through all the important entities and slurp all the entities out into futures.
-        for pos in &self.get_important_entities() {
-            let x = pos.x;
-            let y = pos.y;
-            let tile = self.board.get_tile_mut(y, x);
-            let entity = tile.remove_entity();
-            if let Some(e) = entity {
-                futures.push(Self::late_process_entity(e, *pos));
+    /// Carry out a command from the GUI. If we're sitting paused, the GUI hears about it straight away, since there's no tick coming to tell it.
+    fn take_command(
+        &mut self,
+        command: SandboxCommand,
+        tx: &Sender<GameUpdate>,
+        loop_tx: &Sender<bool>,
+        ctx: &egui::Context,
+    ) -> Result<(), Error> {
+        match self.apply_command(command) {
+            Ok(msg) => {
+                info!(target: self.log_target(), "{msg}");
+                self.console_log.push(msg);
+            }
+            Err(msg) => {
+                error!(target: self.log_target(), "Command failed: {msg}");
+                self.console_log.push(format!("Error: {msg}"));
             }
         }
+        if self.paused && self.pending_steps == 0 {
+            let entity_info = self.get_entity_info();
+            let update = self.make_update(entity_info, None, loop_tx);
+            tx.send(update).map_err(|_| Error::ChannelClosed)?;
+            ctx.request_repaint();
+        }
+        Ok(())
+    }
 
-        // wait for them all to finish
-        let results = join_all(futures);
-        let results = async_std::task::spawn(async move { results.await }).await;
+    /// Save the game, if it's been long enough since the last time.
+    fn autosave_if_due(&mut self) {
+        // an interval of 0 turns autosaving off
+        if self.clock.checked_rem(self.settings.autosave_interval) == Some(0) {
+            self.autosave();
+        }
+    }
 
-        // run through the results. it returns the positions that the new entities are on.
-        for res in results {
-            let mut re_insert_self = true;
-            if res.is_none() {
-                continue; // there was nothing of real note here, keep going
-            }
-            let (ent, pos, hint) = res.unwrap();
-            let tile = self.board.get_tile_mut_from_pos(pos);
-            // check to see if we're looking at any special behavior
-            match hint {
-                None => (),
-                Some(PostProcessResult::Delete) => {
-                    // Since we have a mutable reference, we can just. destroy ourselves
-                    let entity = tile.remove_entity();
-                    debug!("{entity:?} was deleted.");
-                    re_insert_self = false;
-                    // don't push to the new important entities list, since we're removing ourselves
-                    // make sure we remove ourselves from the processing list, too.
-                }
-                Some(PostProcessResult::ReplaceMeWith(e)) => {
-                    // same as before.
-                    // drop the old entity on the floor lol
-                    tile.remove_entity();
-                    let _ = tile.add_entity(e);
-                    new_important_entites.push(pos);
-                    re_insert_self = false;
-                }
-                Some(
-                    PostProcessResult::TryToAddEntities(_)
-                    | PostProcessResult::TryToAddEntitiesAndKillMe(_),
-                ) => (),
-                Some(PostProcessResult::MarkTheseAsInteresting(mut interest)) => {
-                    new_important_entites.append(&mut interest);
-                    if !tile.is_occupied() {
-                        error!("Our current entity was none after late processing and marking interesting")
-                    }
-                }
+    /// How an event gets shown to the player. If there's more than one region, they're told which one it went off in.
+    fn describe_event(&self, pending: &PendingEvent) -> EventPrompt {
+        let mut prompt = pending.event.get_prompt(self.settings.difficulty);
+        if !self.regions.is_empty() {
+            prompt.intro = locale::tr_with(
+                "event.in_region",
+                &[("region", &pending.region), ("event", &prompt.intro)],
+            );
+        }
+        prompt
+    }
+
+    /// Move every region forward by one tick, without waiting around or talking to anyone.
+    /// Only one event can be dealt with at a time, so if a few regions had one go off, the rest wait their turn and come
+    /// out one at a time on the ticks after. The event won't do anything until it's been answered with answer_event.
+    pub fn tick(&mut self) -> TickReport {
+        let start = Instant::now();
+        self.record_history();
+        self.edits.clear();
+        if let Some((pending, choice)) = self.answered_event.take() {
+            let result = self.in_region(&pending.region, |sandbox| {
+                pending.event.process_event(choice, sandbox)
+            });
+            if let Err(e) = result {
+                error!(target: self.log_target(), "Couldn't carry out {}: {e}", pending.kind().name());
             }
+        }
+        let events = self.for_each_region(|sandbox| {
+            sandbox.timed(Phase::Moves, |sandbox| {
+                sandbox.handle_chunks();
+                sandbox.handle_moves();
+            });
+            sandbox.sanity_check("moves");
+            sandbox.timed(Phase::Processing, |sandbox| {
+                sandbox.handle_processing();
+                sandbox.handle_scenery();
+                sandbox.handle_forests();
+                sandbox.handle_seasons();
+                sandbox.handle_weather();
+                sandbox.handle_pollution();
+                sandbox.handle_scent();
+                sandbox.handle_structures();
+            });
+            sandbox.sanity_check("processing");
 
-            if re_insert_self {
-                let tile = self.board.get_tile_mut_from_pos(pos);
-                if let Err(ent) = tile.add_entity(ent) {
-                    error!("While processing, {:?} ended up on tile {pos:?}, which is occupied by {:?}", tile.get_entity(), ent);
-                    error!("{ent:?} will be dropped!");
-                    // god what do we even do here, delete it? Hopefully this should never happen
-                }
-                new_important_entites.push(pos);
+            sandbox.timed(Phase::LateProcessing, |sandbox| {
+                block_on(sandbox.handle_late_processing())
+            });
+            sandbox.sanity_check("late_processing");
+            sandbox.lineage.record(&sandbox.board);
+
+            let event = sandbox.timed(Phase::Events, |sandbox| {
+                sandbox.run_behaviors();
+                sandbox.handle_events()
+            });
+            sandbox.sanity_check("Events");
+            event.map(|event| PendingEvent {
+                region: sandbox.region_name.clone(),
+                event,
+            })
+        });
+        self.handle_migration();
+        self.clock += 1;
+        self.handle_missions();
+        self.handle_spotlight();
+        self.traits.record(&self.board);
+        let boards: Vec<&Board> = std::iter::once(&self.board)
+            .chain(self.regions.iter().map(|r| &r.board))
+            .collect();
+        self.health.record(self.clock, boards.iter().copied());
+        self.populations.record(self.clock, boards.into_iter());
+        self.record_timelapse();
+        self.queued_events.extend(events.into_iter().flatten());
+        let event = self.queued_events.pop_front();
+        if event.is_some() && self.settings.event_voting {
+            self.poll = Some(Tally::default());
+        }
+        TickReport {
+            tick: self.clock,
+            event,
+            unlocked: self.achievements.check(self.clock, &self.board),
+            elapsed: start.elapsed(),
+        }
+    }
+
+    /// Settle an event that went off during a tick, one way or the other. It gets carried out at the start of the next tick.
+    /// Answering again before then changes our mind.
+    pub fn answer_event(&mut self, pending: PendingEvent, choice: bool) {
+        let answered = AnsweredEvent {
+            tick: self.clock,
+            region: pending.region.clone(),
+            event: pending.kind().name().to_owned(),
+            answer: choice,
+            policy: None,
+        };
+        if self.answered_event.take().is_some() {
+            self.event_log.pop();
+        }
+        self.event_log.push(answered);
+        self.answered_event = Some((pending, choice));
+        self.poll = None;
+    }
+
+    /// Run for a number of ticks with nobody watching. Whenever an event goes off, the event policy makes the call.
+    /// After every tick, `after_tick` gets a look at the sandbox, along with what happened during the tick.
+    pub fn run_headless(&mut self, ticks: usize, mut after_tick: impl FnMut(&Self, &TickReport)) {
+        for _ in 0..ticks {
+            let mut report = self.tick();
+            after_tick(self, &report);
+            if let Some(pending) = report.event.take() {
+                self.answer_with_policy(pending);
             }
         }
     }
 
     /// Determine if an event occurs
     fn handle_events(&mut self) -> Option<GameEvents> {
-        let mut rng = rand::thread_rng();
+        // scripted events always take precedence over random ones
+        if let Some(i) = self
+            .scripted_events
+            .iter()
+            .position(|(tick, _)| *tick == self.clock)
+        {
+            let (_, kind) = self.scripted_events.remove(i);
+            return Some(GameEvents { kind });
+        }
+
+        let difficulty = self.settings.difficulty;
+        let ramp_up = difficulty.scale_ramp_up(self.settings.event_frequency.ramp_up()?);
+        let mut rng = self.rng.lock().unwrap();
         let event_chance = rng.gen_range(1..=1000);
-        dbg!(event_chance + self.last_event);
-        // We start with a 1% chance of generating an event
-        if event_chance + self.last_event >= 995 {
+        debug!(target: self.log_target(), "Event roll: {}", event_chance + self.last_event);
+        // We start with a small chance of generating an event, which depends on the difficulty
+        if event_chance + self.last_event >= difficulty.event_threshold() {
             // Reset back to 5% chance
             self.last_event = 0;
 
@@ -410,54 +907,121 @@ impl Sandbox {
             // iteration of the sandbox that it is reliant on.  The problem is that we need to stop
             // the loop when we get an event, otherwise it will keep on computing without
             // the event result.
-            return Some(game_events::get_rand_event(rng.gen_range(0..3)));
+            // scripts can add events of their own, which get rolled after the built in ones
+            let roll = rng.gen_range(0..EventTypes::ALL.len() + self.scripted_event_count());
+            drop(rng);
+            return match roll.checked_sub(EventTypes::ALL.len()) {
+                Some(index) => self.scripted_event(index),
+                None => Some(game_events::get_rand_event(roll)),
+            };
         } else if self.clock % 10 == 0 {
-            // Increase the chance of getting an event by 1%
-            self.last_event += 10;
+            // Increase the chance of getting an event, by 1% at normal frequency and difficulty
+            self.last_event += ramp_up;
         }
         None
     }
 }
 
-/// Initialize a game board.
+/// A game that's been started up, and is off running somewhere.
+pub enum Simulation {
+    /// Running on a thread of its own, so there's nothing to do but listen for updates.
+    Threaded,
+    /// Running a bit at a time whenever it gets pumped, for when there aren't any threads to spare (like in a browser).
+    Pumped(Box<GuiRunner>),
+}
+
+impl Simulation {
+    /// Give the game a chance to run, if it needs one. Call this every frame.
+    pub fn pump(&mut self) {
+        if let Self::Pumped(runner) = self {
+            runner.pump();
+        }
+    }
+}
+
+/// Initialize a game board from a colony code.
+/// If the setup doesn't pass validation, nothing gets started.
 pub fn initialize_board(
-    row: usize,
-    col: usize,
-    fish: usize,
-    crab: usize,
-    shark: usize,
-    tx: Sender<(String, Vec<String>, String, Sender<bool>)>,
+    code: &ColonyCode,
+    settings: SimulationSettings,
+    tx: Sender<GameUpdate>,
+    cmd_rx: Receiver<SandboxCommand>,
     ctx: egui::Context,
-) {
-    let entity_manager = EntityManager::new();
-    let mut game_board = Board::new(row, col, Arc::clone(&entity_manager));
-    let important_entities = populate_board(&mut game_board, fish, crab, shark);
+) -> Result<Simulation, SetupError> {
+    Ok(run_simulation(code.build()?, settings, tx, cmd_rx, ctx))
+}
+
+/// Initialize a game board from a scenario file.
+pub fn initialize_from_scenario(
+    path: &str,
+    settings: SimulationSettings,
+    tx: Sender<GameUpdate>,
+    cmd_rx: Receiver<SandboxCommand>,
+    ctx: egui::Context,
+) -> Result<Simulation, ScenarioError> {
+    let scenario = Scenario::load(path)?;
+    info!("Loading scenario {}", scenario.get_name());
+    Ok(run_simulation(
+        scenario.build(settings.tick_rate),
+        settings,
+        tx,
+        cmd_rx,
+        ctx,
+    ))
+}
 
+/// Start the guided first run (see tutorial.rs). Some of the player's settings are set aside while it's being played.
+pub fn initialize_tutorial(
+    settings: SimulationSettings,
+    tx: Sender<GameUpdate>,
+    cmd_rx: Receiver<SandboxCommand>,
+    ctx: egui::Context,
+) -> Simulation {
+    let settings = tutorial::settings(settings);
     run_simulation(
-        game_board,
-        important_entities,
-        3.0,
-        false,
-        entity_manager,
+        tutorial::scenario().build(settings.tick_rate),
+        settings,
         tx,
+        cmd_rx,
         ctx,
-    );
+    )
 }
 
-/// Spin off the simulation in a new thread.
+/// Spin off the simulation in a new thread, or hand it back to be pumped if we can't.
 fn run_simulation(
-    board: Board,
-    _: Vec<Pos>,
-    tick_rate: f64,
-    _: bool,
-    entity_context: Arc<RwLock<EntityManager>>,
-    tx: Sender<(String, Vec<String>, String, Sender<bool>)>,
+    mut sandbox: Sandbox,
+    settings: SimulationSettings,
+    tx: Sender<GameUpdate>,
+    cmd_rx: Receiver<SandboxCommand>,
     ctx: egui::Context,
-) {
+) -> Simulation {
     println!("Starting!");
-    println!("{}", board);
-    // Spawn the game loop thread
-    std::thread::spawn(move || {
-        Sandbox::new(board, tick_rate, entity_context).run_game_loop(tx.clone(), ctx);
-    });
+    println!("{}", sandbox.board);
+    // only the real game should be writing achievements out to disk
+    sandbox.achievements = AchievementTracker::load(ACHIEVEMENTS_FILE);
+    sandbox.load_scripts(SCRIPTS_DIR);
+    sandbox.keep_history(REWIND_TICKS);
+    sandbox.set_rules(InteractionMatrix::load(SPECIES_FILE));
+    #[cfg(feature = "server")]
+    let serve_address = settings.serve_address.clone();
+    sandbox.apply_settings(settings);
+    let runner = GuiRunner::new(sandbox, tx, cmd_rx, ctx);
+    #[cfg(feature = "server")]
+    let mut runner = runner;
+    #[cfg(feature = "server")]
+    if !serve_address.is_empty() {
+        match server::ObservationServer::bind(&serve_address) {
+            Ok(server) => {
+                info!("Serving the game at ws://{}", server.local_addr());
+                runner.serve(server);
+            }
+            Err(e) => error!("Couldn't start serving the game at {serve_address}: {e}"),
+        }
+    }
+    // browsers can't spawn threads, so the GUI has to drive the game itself
+    if cfg!(target_arch = "wasm32") {
+        return Simulation::Pumped(Box::new(runner));
+    }
+    std::thread::spawn(move || runner.run());
+    Simulation::Threaded
 }
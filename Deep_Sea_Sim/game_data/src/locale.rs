@@ -0,0 +1,204 @@
+// Everything the player reads, kept in catalogs keyed by name so the game can be played in more than one language. Every
+// language has a catalog under game_data/locales, one `key = text` per line, and whatever a catalog is missing falls back
+// on the English one, then on the key itself, so a half finished translation is still playable. Text can have `{name}`
+// placeholders in it, which get filled in by tr_with.
+// Only what the player reads goes through here; log lines, error messages and the developer console stay in English,
+// since they end up in bug reports.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{Arc, RwLock};
+
+/// A language the game can be played in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Self::English, Self::Spanish];
+
+    /// The name we use for this language in the settings file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::English => "english",
+            Self::Spanish => "spanish",
+        }
+    }
+
+    /// What the language calls itself, so it can be found in the list whatever language the game's in.
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::Spanish => "Español",
+        }
+    }
+
+    /// The contents of the catalog that comes with the game.
+    fn builtin(&self) -> &'static str {
+        match self {
+            Self::English => include_str!("../locales/en.txt"),
+            Self::Spanish => include_str!("../locales/es.txt"),
+        }
+    }
+}
+
+/// Every bit of text in one language, by key.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Catalog {
+    entries: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// The catalog that comes with the game for a language.
+    pub fn builtin(language: Language) -> Self {
+        Self::parse(language.builtin()).0
+    }
+
+    /// Read a catalog in, along with every line that couldn't be made sense of.
+    /// `\n` and `\t` in the text stand for a new line and a tab.
+    pub fn parse(contents: &str) -> (Self, Vec<String>) {
+        let mut catalog = Self::default();
+        let mut ignored = vec![];
+        for line in contents.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let Some((key, text)) = trimmed.split_once('=') else {
+                ignored.push(line.to_owned());
+                continue;
+            };
+            let text = text.trim().replace("\\n", "\n").replace("\\t", "\t");
+            catalog.entries.insert(key.trim().to_owned(), text);
+        }
+        (catalog, ignored)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+}
+
+/// The catalog for the language being played in, and the English one to fall back on.
+#[derive(Debug)]
+struct Catalogs {
+    language: Language,
+    chosen: Catalog,
+    english: Catalog,
+}
+
+static CATALOGS: RwLock<Option<Arc<Catalogs>>> = RwLock::new(None);
+
+/// Switch every bit of text over to another language.
+pub fn set_language(language: Language) {
+    let catalogs = Catalogs {
+        language,
+        chosen: Catalog::builtin(language),
+        english: Catalog::builtin(Language::English),
+    };
+    *CATALOGS.write().unwrap() = Some(Arc::new(catalogs));
+}
+
+fn catalogs() -> Arc<Catalogs> {
+    if let Some(catalogs) = CATALOGS.read().unwrap().as_ref() {
+        return catalogs.clone();
+    }
+    set_language(Language::default());
+    catalogs()
+}
+
+/// The language the game's being played in.
+pub fn language() -> Language {
+    catalogs().language
+}
+
+/// Look some text up in the language being played in, falling back on English. None if neither has it.
+pub fn lookup(key: &str) -> Option<String> {
+    let catalogs = catalogs();
+    catalogs
+        .chosen
+        .get(key)
+        .or_else(|| catalogs.english.get(key))
+        .map(str::to_owned)
+}
+
+/// Look some text up, or hand back the key if there's nothing for it.
+pub fn tr(key: &str) -> String {
+    lookup(key).unwrap_or_else(|| key.to_owned())
+}
+
+/// Look some text up and fill in its placeholders.
+pub fn tr_with(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    fill(&tr(key), args)
+}
+
+/// Fill every `{name}` in some text in with its value.
+pub fn fill(text: &str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter().fold(text.to_owned(), |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), &value.to_string())
+    })
+}
+
+/// The name of one of a group of things, like a species or a difficulty, in the language being played in.
+/// Anything without a translation, like a species added by a plugin, keeps the name it was given.
+pub fn tr_name(group: &str, name: &str) -> String {
+    let key = format!("{group}.{}", name.to_lowercase().replace(' ', "_"));
+    lookup(&key).unwrap_or_else(|| name.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every `{name}` in some text, in order.
+    fn placeholders(text: &str) -> Vec<&str> {
+        text.split('{')
+            .skip(1)
+            .filter_map(|s| s.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+
+    #[test]
+    fn test_catalogs_line_up() {
+        for language in Language::ALL {
+            let (_, ignored) = Catalog::parse(language.builtin());
+            assert!(ignored.is_empty(), "{}: {ignored:?}", language.name());
+        }
+        let english = Catalog::builtin(Language::English);
+        let spanish = Catalog::builtin(Language::Spanish);
+        for key in english.keys() {
+            let translated = spanish
+                .get(key)
+                .unwrap_or_else(|| panic!("spanish is missing {key}"));
+            let mut expected = placeholders(english.get(key).unwrap());
+            let mut found = placeholders(translated);
+            expected.sort();
+            found.sort();
+            assert_eq!(expected, found, "{key} has different placeholders");
+        }
+        assert!(spanish.keys().all(|key| english.get(key).is_some()));
+    }
+
+    #[test]
+    fn test_lookup() {
+        let (catalog, ignored) =
+            Catalog::parse("# a comment\ngreeting = Hello,\\n{name}!\nnonsense\n");
+        assert_eq!(ignored, ["nonsense"]);
+        let greeting = fill(catalog.get("greeting").unwrap(), &[("name", &"crab")]);
+        assert_eq!(greeting, "Hello,\ncrab!");
+
+        // nothing here ever switches the language, since every test shares it
+        assert_eq!(tr("menu.quit"), "Quit");
+        assert_eq!(tr("no.such.key"), "no.such.key");
+        assert_eq!(tr_name("preset", "Predator Heavy"), "Predator Heavy");
+        assert_eq!(tr_name("species", "kelp_seed"), "kelp seed");
+        assert_eq!(tr_name("species", "squid"), "squid");
+    }
+}
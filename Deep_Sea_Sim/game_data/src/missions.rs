@@ -0,0 +1,302 @@
+// Every so often the colony gets handed a mission: grow a population up to some size before the time runs out, or keep one
+// alive until it does. They're optional, so ignoring one costs nothing, but finishing one pays out resources to build with
+// (see structures.rs). Only one mission runs at a time, and it's checked against the loaded region after every tick, the same
+// way achievements are, going off of the head counts the board keeps rather than counting everything up again.
+
+use log::info;
+use rand::Rng;
+
+use crate::entities::Species;
+use crate::game_board::Board;
+use crate::locale::{tr, tr_name, tr_with};
+use crate::weather::Weather;
+use crate::Sandbox;
+
+/// How often a new mission gets handed out, in ticks, as long as there isn't one still running.
+pub const MISSION_INTERVAL: usize = 150;
+
+/// How many ticks the colony gets to finish a mission in.
+pub const MISSION_TICKS: usize = 100;
+
+/// A population goal asks for at least this many more than there are when it's handed out.
+pub const MIN_GROWTH: usize = 5;
+
+/// How many finished missions are kept around to show the player.
+const HISTORY_LENGTH: usize = 5;
+
+/// What a mission asks the colony to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    /// Get at least `target` of a species on the board before the deadline.
+    Population { species: Species, target: usize },
+    /// Never let a species drop below `count` until the deadline.
+    Survive { species: Species, count: usize },
+}
+
+impl Goal {
+    /// How many resources finishing this pays out.
+    pub fn reward(&self) -> usize {
+        match self {
+            Self::Population { .. } => 60,
+            Self::Survive { .. } => 40,
+        }
+    }
+
+    /// Whether the board still looks good for this goal, and if so, whether it's already been met.
+    /// Survival goals are only met by making it to the deadline, so they never say they're met.
+    fn progress(&self, board: &Board) -> (bool, bool) {
+        match *self {
+            Self::Population { species, target } => (true, board.head_count(species) >= target),
+            Self::Survive {
+                species,
+                count: needed,
+            } => (board.head_count(species) >= needed, false),
+        }
+    }
+}
+
+/// A goal, and the tick it has to be done by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mission {
+    pub goal: Goal,
+    pub deadline: usize,
+    /// Set if the mission was handed out during a storm, and only lasts until it blows over.
+    pub storm: bool,
+}
+
+impl Mission {
+    /// What the player's being asked to do, in words.
+    pub fn description(&self) -> String {
+        let until = match self.storm {
+            true => tr("mission.through_storm"),
+            false => tr_with("mission.until_tick", &[("deadline", &self.deadline)]),
+        };
+        match self.goal {
+            Goal::Population { species, target } => tr_with(
+                "mission.reach",
+                &[
+                    ("target", &target),
+                    ("species", &tr_name("species", species.name())),
+                    ("deadline", &self.deadline),
+                ],
+            ),
+            Goal::Survive { species, count } => tr_with(
+                "mission.keep",
+                &[
+                    ("count", &count),
+                    ("species", &tr_name("species", species.name())),
+                    ("until", &until),
+                ],
+            ),
+        }
+    }
+}
+
+/// Hands out missions, and keeps track of how the colony's doing on them.
+#[derive(Debug, Default)]
+pub struct MissionTracker {
+    /// The mission being worked on, if there is one.
+    current: Option<Mission>,
+    /// The last few missions to finish, most recent last, and whether they were completed.
+    finished: Vec<(Mission, bool)>,
+}
+
+impl MissionTracker {
+    pub fn get_current(&self) -> Option<&Mission> {
+        self.current.as_ref()
+    }
+
+    pub fn get_finished(&self) -> &[(Mission, bool)] {
+        &self.finished
+    }
+
+    /// Start working on a mission, dropping whatever was being worked on before.
+    pub fn issue(&mut self, mission: Mission) {
+        self.current = Some(mission);
+    }
+
+    /// Check the current mission against the board, returning it if it's just been completed or failed.
+    pub fn check(&mut self, clock: usize, board: &Board) -> Option<(Mission, bool)> {
+        let mission = self.current.as_ref()?;
+        let (on_track, met) = mission.goal.progress(board);
+        let outcome = match (on_track, met) {
+            (false, _) => false,
+            (true, true) => true,
+            // anything still on track at the deadline made it, unless it still had something to reach
+            _ if clock >= mission.deadline => matches!(mission.goal, Goal::Survive { .. }),
+            _ => return None,
+        };
+        let mission = self.current.take()?;
+        self.finished.push((mission.clone(), outcome));
+        if self.finished.len() > HISTORY_LENGTH {
+            self.finished.remove(0);
+        }
+        Some((mission, outcome))
+    }
+
+    /// Every line the missions panel should show: the current mission first, then the last few to finish.
+    pub fn summary(&self, clock: usize) -> Vec<String> {
+        let current = self.current.iter().map(|m| {
+            let left = m.deadline.saturating_sub(clock);
+            tr_with(
+                "mission.current",
+                &[
+                    ("mission", &m.description()),
+                    ("left", &left),
+                    ("reward", &m.goal.reward()),
+                ],
+            )
+        });
+        let finished = self.finished.iter().rev().map(|(m, completed)| {
+            let mark = if *completed { "✅" } else { "❌" };
+            format!("{mark} {}", m.description())
+        });
+        current.chain(finished).collect()
+    }
+}
+
+impl Sandbox {
+    /// Settle the current mission if it's done one way or the other, paying out if it was completed, and hand out a new one
+    /// if it's time.
+    pub fn handle_missions(&mut self) {
+        if let Some((mission, completed)) = self.missions.check(self.clock, &self.board) {
+            let reward = mission.goal.reward();
+            let msg = match completed {
+                true => {
+                    self.resources += reward;
+                    tr_with(
+                        "mission.complete",
+                        &[("mission", &mission.description()), ("reward", &reward)],
+                    )
+                }
+                false => tr_with("mission.failed", &[("mission", &mission.description())]),
+            };
+            info!(target: &self.log_target, "{msg}");
+            self.console_log.push(msg);
+        }
+        if self.missions.get_current().is_none() && self.clock.is_multiple_of(MISSION_INTERVAL) {
+            let mission = self.new_mission();
+            info!(target: &self.log_target, "New mission: {}", mission.description());
+            self.missions.issue(mission);
+        }
+    }
+
+    /// Come up with a mission for the colony as it stands. If a storm's blowing, it's always about keeping the sharks
+    /// alive through it.
+    pub fn new_mission(&self) -> Mission {
+        let sharks = self.board.head_count(Species::Shark);
+        if let (Weather::Storm(ticks_left), 1..) = (self.board.get_weather(), sharks) {
+            return Mission {
+                goal: Goal::Survive {
+                    species: Species::Shark,
+                    count: sharks,
+                },
+                deadline: self.clock + ticks_left,
+                storm: true,
+            };
+        }
+        let mut rng = self.rng.lock().unwrap();
+        let goal = match rng.gen_range(0..3) {
+            0 if sharks > 0 => Goal::Survive {
+                species: Species::Shark,
+                count: sharks,
+            },
+            roll => {
+                let species = if roll == 1 {
+                    Species::Kelp
+                } else {
+                    Species::Fish
+                };
+                let now = self.board.head_count(species);
+                Goal::Population {
+                    species,
+                    target: (now + now / 2).max(now + MIN_GROWTH),
+                }
+            }
+        };
+        Mission {
+            goal,
+            deadline: self.clock + MISSION_TICKS,
+            storm: false,
+        }
+    }
+
+    /// Every line the missions panel should show.
+    pub fn get_mission_summary(&self) -> Vec<String> {
+        self.missions.summary(self.clock)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::NonAbstractTaxonomy;
+    use crate::game_board::Pos;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_population_mission() {
+        let mut testbed = TestBed::new_with_entities(
+            2,
+            2,
+            vec![(Pos { x: 0, y: 0 }, Species::Fish.create_new(None))],
+        );
+        let mut tracker = MissionTracker::default();
+        let goal = Goal::Population {
+            species: Species::Fish,
+            target: 2,
+        };
+        tracker.issue(Mission {
+            goal,
+            deadline: 10,
+            storm: false,
+        });
+        assert_eq!(tracker.check(0, &testbed.sandbox.board), None);
+
+        testbed
+            .sandbox
+            .board
+            .place_entity(Pos { x: 1, y: 1 }, Species::Fish.create_new(None))
+            .unwrap();
+        let (mission, completed) = tracker.check(1, &testbed.sandbox.board).unwrap();
+        assert_eq!(mission.goal, goal);
+        assert!(completed);
+        assert!(tracker.get_current().is_none());
+        assert_eq!(
+            tracker.summary(1),
+            vec![format!("✅ {}", mission.description())]
+        );
+    }
+
+    #[test]
+    fn test_survival_mission() {
+        let board = TestBed::new_with_entities(
+            2,
+            2,
+            vec![(Pos { x: 0, y: 0 }, Species::Shark.create_new(None))],
+        )
+        .sandbox
+        .board;
+        let mission = Mission {
+            goal: Goal::Survive {
+                species: Species::Shark,
+                count: 1,
+            },
+            deadline: 10,
+            storm: true,
+        };
+        assert_eq!(
+            mission.description(),
+            "Keep at least 1 shark alive through the storm"
+        );
+        let mut tracker = MissionTracker::default();
+        tracker.issue(mission.clone());
+        assert_eq!(tracker.check(9, &board), None);
+        assert_eq!(tracker.check(10, &board), Some((mission.clone(), true)));
+
+        // losing the shark fails it straight away
+        tracker.issue(mission.clone());
+        let empty = TestBed::new_with_entities(2, 2, vec![]).sandbox.board;
+        assert_eq!(tracker.check(0, &empty), Some((mission, false)));
+    }
+}
@@ -0,0 +1,99 @@
+// Oil spills leave pollution behind on the tiles they cover, which hangs around long after the event itself is over.
+// Every tile keeps track of how polluted it is, from 0 (clean) to 1 (thick with oil). Polluted water slowly clears up on its
+// own, but until it does it eats away at the plants growing in it and makes it less likely anything new is born there
+// (see Reproducing::create_offspring). Crabs can be put to work scrubbing it up when a cleanup crew comes by.
+
+use log::info;
+use rand::Rng;
+
+use crate::element_traits::Lives;
+use crate::entities::{Entity, Species};
+use crate::game_board::Pos;
+use crate::Sandbox;
+
+/// How far out from where it lands an oil spill spreads.
+pub const SPILL_RADIUS: usize = 2;
+
+/// How much pollution clears up on its own every tick. Oil takes a couple hundred ticks to go away entirely.
+pub const POLLUTION_DECAY: f64 = 0.005;
+
+/// The odds of a plant on a fully polluted tile losing a bite each tick. Less polluted tiles are less likely to hurt.
+pub const POLLUTION_DAMAGE_CHANCE: f64 = 0.05;
+
+/// How much damage animals caught out in a spill take.
+pub const OIL_DAMAGE: i64 = 30;
+
+/// How far around itself a crab scrubs clean when it's put to work.
+pub const SCRUB_RADIUS: usize = 2;
+
+impl Sandbox {
+    /// Let the pollution clear up a little, and hurt any plants still growing in it.
+    pub fn handle_pollution(&mut self) {
+        let mut rng = self.rng.lock().unwrap();
+        let polluted: Vec<(Pos, f64)> = self.board.iter_polluted().collect();
+        for (pos, level) in polluted {
+            self.board
+                .get_tile_mut_from_pos(pos)
+                .set_pollution(level - POLLUTION_DECAY);
+            if !rng.gen_bool(level * POLLUTION_DAMAGE_CHANCE) {
+                continue;
+            }
+            if let Some(plant) = self.board.entity_at_mut(pos).and_then(Entity::plant_mut) {
+                plant.modify_health(-1, "pollution");
+            }
+        }
+    }
+
+    /// Spill oil over a random patch of the board, fouling every tile in it. If `harmful`, any animal caught in it
+    /// gets hurt too. Returns where the spill landed.
+    pub fn spill_oil(&mut self, harmful: bool) -> Pos {
+        let (cols, rows) = self.board.dims();
+        let center = {
+            let mut rng = self.rng.lock().unwrap();
+            Pos {
+                x: rng.gen_range(0..cols),
+                y: rng.gen_range(0..rows),
+            }
+        };
+        let slick: Vec<Pos> = self.board.positions_within(SPILL_RADIUS, center).collect();
+        for pos in slick {
+            self.board.get_tile_mut_from_pos(pos).set_pollution(1.0);
+            if !harmful {
+                continue;
+            }
+            if let Some(animal) = self.board.entity_at_mut(pos).and_then(Entity::animal_mut) {
+                animal.modify_health(-OIL_DAMAGE, "an oil spill");
+            }
+        }
+        info!(target: &self.log_target, "Oil spilled around {center:?}");
+        center
+    }
+
+    /// Put every crab to work scrubbing the water around it clean, which leaves them hungry.
+    /// Returns how many tiles got cleaned up.
+    pub fn scrub_pollution(&mut self) -> usize {
+        let difficulty = self.settings.difficulty;
+        let crabs: Vec<Pos> = self
+            .board
+            .iter_occupied()
+            .filter(|(_, e)| Species::of(e) == Species::Crab)
+            .map(|(pos, _)| pos)
+            .collect();
+        let mut cleaned = 0;
+        for crab in crabs {
+            let around: Vec<Pos> = self.board.positions_within(SCRUB_RADIUS, crab).collect();
+            for pos in around {
+                let tile = self.board.get_tile_mut_from_pos(pos);
+                if tile.get_pollution() > 0.0 {
+                    tile.set_pollution(0.0);
+                    cleaned += 1;
+                }
+            }
+            if let Some(animal) = self.board.entity_at_mut(crab).and_then(Entity::animal_mut) {
+                animal.process_hunger(difficulty);
+            }
+        }
+        info!(target: &self.log_target, "Crabs scrubbed {cleaned} tiles clean");
+        cleaned
+    }
+}
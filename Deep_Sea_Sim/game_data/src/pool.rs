@@ -0,0 +1,193 @@
+// Running a whole bunch of headless sandboxes side by side, for comparing how different setups play out.
+// Every sandbox brings along its own entities, randomness and log target, so they can't step on each other.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::colony_health::{ColonyHealth, Trend};
+use crate::entities::Species;
+use crate::event_policy::EventPolicy;
+use crate::interaction_matrix::InteractionMatrix;
+use crate::settings::SimulationSettings;
+use crate::setup::{SetupConfig, SetupError};
+use crate::Sandbox;
+
+/// One simulation for a pool to run.
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    pub setup: SetupConfig,
+    pub settings: SimulationSettings,
+    /// Every bit of randomness in the run comes from this, so the same config always plays out the same.
+    pub seed: u64,
+    /// How events get answered, if not the sandbox's usual way.
+    pub event_policy: Option<EventPolicy>,
+    /// The species rules the run's played by.
+    pub rules: Arc<InteractionMatrix>,
+}
+
+impl SimulationConfig {
+    /// Run a setup with the default settings.
+    pub fn new(setup: SetupConfig, seed: u64) -> Self {
+        Self {
+            setup,
+            settings: SimulationSettings::default(),
+            seed,
+            event_policy: None,
+            rules: Arc::new(InteractionMatrix::default()),
+        }
+    }
+
+    /// Set the game up, ready to be run.
+    pub fn build(&self) -> Result<Sandbox, SetupError> {
+        let mut sandbox = Sandbox::from_setup(&self.setup, &mut StdRng::seed_from_u64(self.seed))?;
+        sandbox.apply_settings(self.settings.clone());
+        sandbox.set_rules(Arc::clone(&self.rules));
+        if let Some(policy) = &self.event_policy {
+            sandbox.set_event_policy(policy.clone());
+        }
+        Ok(sandbox)
+    }
+}
+
+/// How many of everything were on the board when a run finished, across every region.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Census {
+    /// How many ticks the run lasted.
+    pub ticks: usize,
+    pub populations: HashMap<Species, usize>,
+    /// How well the colony was doing as of the last tick, or None if it's never been ticked.
+    pub health: Option<ColonyHealth>,
+    /// Which way the colony's health had been going.
+    pub health_trend: Trend,
+}
+
+impl Census {
+    /// How many of a species were left. Anything that never showed up at all counts as 0.
+    pub fn count(&self, species: Species) -> usize {
+        self.populations.get(&species).copied().unwrap_or(0)
+    }
+
+    /// The colony's health score out of 100, or 0 if it's never been ticked.
+    pub fn health_score(&self) -> usize {
+        self.health.as_ref().map_or(0, |h| h.score)
+    }
+}
+
+impl Sandbox {
+    /// Count up everything on the board, in every region.
+    pub fn census(&self) -> Census {
+        let mut populations = HashMap::new();
+        let boards = std::iter::once(&self.board).chain(self.regions.iter().map(|r| &r.board));
+        for board in boards {
+            for (_, entity) in board.iter_occupied() {
+                *populations.entry(Species::of(entity)).or_default() += 1;
+            }
+        }
+        Census {
+            ticks: self.clock,
+            populations,
+            health: self.health.latest().cloned(),
+            health_trend: self.health.trend(),
+        }
+    }
+}
+
+/// Runs several sandboxes at once, spread out over however many threads the machine has to spare.
+pub struct SimulationPool;
+
+impl SimulationPool {
+    /// Run every config for the given number of ticks, and take a census of each once it's done.
+    /// The results come back in the same order as the configs. If any of the setups aren't valid, nothing gets run.
+    pub fn run(configs: &[SimulationConfig], ticks: usize) -> Result<Vec<Census>, SetupError> {
+        for config in configs {
+            config.setup.validate()?;
+        }
+        let threads = thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(configs.len());
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(vec![None; configs.len()]);
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(config) = configs.get(index) else {
+                        break;
+                    };
+                    let census = Self::run_one(config, ticks, index);
+                    results.lock().unwrap()[index] = Some(census);
+                });
+            }
+        });
+        // every thread has been joined by now, so every slot got filled
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect()
+    }
+
+    fn run_one(
+        config: &SimulationConfig,
+        ticks: usize,
+        index: usize,
+    ) -> Result<Census, SetupError> {
+        let mut sandbox = config.build()?;
+        sandbox.set_log_target(&format!("{}::{index}", module_path!()));
+        sandbox.run_headless(ticks, |_, _| ());
+        Ok(sandbox.census())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(seed: u64) -> SimulationConfig {
+        let setup = SetupConfig {
+            fish: 6,
+            crab: 3,
+            shark: 1,
+            ..SetupConfig::new(8, 8)
+        };
+        SimulationConfig::new(setup, seed)
+    }
+
+    #[test]
+    fn test_pool_runs_everything() {
+        let configs = vec![config(1), config(2), config(1)];
+        let censuses = SimulationPool::run(&configs, 10).unwrap();
+        assert_eq!(censuses.len(), 3);
+        for census in &censuses {
+            assert_eq!(census.ticks, 10);
+        }
+        // the same seed plays out the same, even with another game running alongside it
+        assert_eq!(censuses[0], censuses[2]);
+    }
+
+    #[test]
+    fn test_pool_rejects_bad_setups() {
+        let mut bad = config(1);
+        bad.setup.shark = 100;
+        assert!(matches!(
+            SimulationPool::run(&[config(1), bad], 10),
+            Err(SetupError::TooMany { .. })
+        ));
+    }
+
+    #[test]
+    fn test_census() {
+        let setup = config(3).setup;
+        let sandbox = Sandbox::from_setup(&setup, &mut StdRng::seed_from_u64(3)).unwrap();
+        let census = sandbox.census();
+        assert_eq!(census.ticks, 0);
+        assert_eq!(census.count(Species::Fish), 6);
+        assert_eq!(census.count(Species::Crab), 3);
+        assert_eq!(census.count(Species::Shark), 1);
+    }
+}
@@ -0,0 +1,138 @@
+// Keeping track of how long each part of the game loop takes, so we can tell where the time goes.
+// Build with the `tracing` feature to also get a span for every phase, for use with tracing-subscriber or tracy.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// How many ticks' worth of timings to hang onto.
+pub const TIMING_WINDOW: usize = 120;
+
+/// One of the separately timed steps of a tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Moves,
+    Processing,
+    LateProcessing,
+    Events,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 4] = [
+        Self::Moves,
+        Self::Processing,
+        Self::LateProcessing,
+        Self::Events,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Moves => "moves",
+            Self::Processing => "processing",
+            Self::LateProcessing => "late processing",
+            Self::Events => "events",
+        }
+    }
+}
+
+/// A quick summary of how long a phase has been taking lately.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhaseStats {
+    pub last: Duration,
+    pub mean: Duration,
+    /// 95% of recent ticks took this long or less.
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+/// The last few timings for every phase.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PhaseTimings {
+    /// Samples for each phase, in the same order as Phase::ALL, oldest first.
+    samples: [VecDeque<Duration>; 4],
+}
+
+impl PhaseTimings {
+    fn samples(&self, phase: Phase) -> &VecDeque<Duration> {
+        &self.samples[phase as usize]
+    }
+
+    /// Add a new timing for a phase, forgetting the oldest one if we've got a full window.
+    pub fn record(&mut self, phase: Phase, time: Duration) {
+        let samples = &mut self.samples[phase as usize];
+        if samples.len() == TIMING_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(time);
+    }
+
+    /// Get a summary of a phase's recent timings, or None if it hasn't been timed yet.
+    pub fn stats(&self, phase: Phase) -> Option<PhaseStats> {
+        let samples = self.samples(phase);
+        let last = *samples.back()?;
+        let mut sorted: Vec<Duration> = samples.iter().copied().collect();
+        sorted.sort();
+        let p95 = sorted[(sorted.len() * 95 / 100).min(sorted.len() - 1)];
+        Some(PhaseStats {
+            last,
+            mean: sorted.iter().sum::<Duration>() / sorted.len() as u32,
+            p95,
+            max: *sorted.last()?,
+        })
+    }
+
+    /// Sort a phase's recent timings into evenly sized buckets, from zero up to the slowest timing.
+    /// Returns how many timings landed in each bucket.
+    pub fn histogram(&self, phase: Phase, buckets: usize) -> Vec<usize> {
+        let mut counts = vec![0; buckets];
+        let samples = self.samples(phase);
+        let Some(max) = samples.iter().max() else {
+            return counts;
+        };
+        if buckets == 0 || max.is_zero() {
+            return counts;
+        }
+        for sample in samples {
+            let bucket = (sample.as_secs_f64() / max.as_secs_f64() * buckets as f64) as usize;
+            counts[bucket.min(buckets - 1)] += 1;
+        }
+        counts
+    }
+
+    /// How long the whole last tick took, across every phase.
+    pub fn last_total(&self) -> Duration {
+        self.samples.iter().filter_map(|s| s.back()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_timings() {
+        let mut timings = PhaseTimings::default();
+        assert_eq!(timings.stats(Phase::Moves), None);
+
+        for ms in 1..=TIMING_WINDOW as u64 + 10 {
+            timings.record(Phase::Moves, Duration::from_millis(ms));
+        }
+        timings.record(Phase::Events, Duration::from_millis(5));
+
+        let stats = timings.stats(Phase::Moves).unwrap();
+        let newest = TIMING_WINDOW as u64 + 10;
+        assert_eq!(stats.last, Duration::from_millis(newest));
+        assert_eq!(stats.max, Duration::from_millis(newest));
+        // the first ten should have rolled out of the window
+        assert_eq!(stats.mean, Duration::from_micros((11 + newest) * 500));
+        assert!(stats.p95 < stats.max && stats.p95 > stats.mean);
+
+        let histogram = timings.histogram(Phase::Moves, 10);
+        assert_eq!(histogram.iter().sum::<usize>(), TIMING_WINDOW);
+        assert!(histogram.iter().all(|count| *count > 0));
+        assert_eq!(
+            timings.last_total(),
+            Duration::from_millis(newest + 5),
+            "phases that were never timed shouldn't count"
+        );
+    }
+}
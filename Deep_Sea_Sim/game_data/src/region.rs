@@ -0,0 +1,299 @@
+// Splitting the world up into several boards, connected by gateways that animals can wander through.
+// Only one region is loaded into the sandbox at a time. The rest wait here, and get swapped in whenever they need to be worked on.
+
+use std::mem;
+use std::sync::{Arc, RwLock};
+
+use log::{info, warn};
+
+use crate::entities::{Entity, Living};
+use crate::entity_control::EntityManager;
+use crate::error::Error;
+use crate::game_board::{Board, Pos};
+use crate::game_events::EventTypes;
+use crate::Sandbox;
+
+/// What a region is called if nobody gave it a name.
+pub const DEFAULT_REGION: &str = "main";
+
+/// A tile that leads to another region. Any animal that ends a tick on one gets carried over to the other side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gateway {
+    /// Where the gateway is in its own region.
+    pub pos: Pos,
+    /// The name of the region it leads to.
+    pub to: String,
+    /// Where anything coming through ends up, or as close as we can get to it.
+    pub to_pos: Pos,
+}
+
+/// Everything that belongs to a region that isn't currently loaded into the sandbox.
+#[derive(Debug)]
+pub struct Region {
+    pub(crate) name: String,
+    pub(crate) board: Board,
+    pub(crate) entity_context: Arc<RwLock<EntityManager>>,
+    /// Every region works its way up to its own events.
+    pub(crate) last_event: usize,
+    pub(crate) scripted_events: Vec<(usize, EventTypes)>,
+    pub(crate) gateways: Vec<Gateway>,
+}
+
+impl Region {
+    pub fn new(
+        name: &str,
+        board: Board,
+        entity_context: Arc<RwLock<EntityManager>>,
+        gateways: Vec<Gateway>,
+    ) -> Self {
+        Self {
+            name: name.to_owned(),
+            board,
+            entity_context,
+            last_event: 0,
+            scripted_events: vec![],
+            gateways,
+        }
+    }
+}
+
+impl Sandbox {
+    /// The name of the region that's currently loaded, which is the one being shown and poked at by commands.
+    pub fn get_region_name(&self) -> &str {
+        &self.region_name
+    }
+
+    /// The names of every region, in alphabetical order. Empty if there's only the one.
+    pub fn get_region_names(&self) -> Vec<String> {
+        if self.regions.is_empty() {
+            return vec![];
+        }
+        let mut names: Vec<String> = self.regions.iter().map(|r| r.name.clone()).collect();
+        names.push(self.region_name.clone());
+        names.sort();
+        names
+    }
+
+    pub fn get_gateways(&self) -> &[Gateway] {
+        &self.gateways
+    }
+
+    /// Add another region to the world. It'll start ticking along with the rest of them right away.
+    /// Its entity manager should hand out IDs from the same pool as everyone else's (see EntityManager::new_sharing_ids).
+    pub fn add_region(&mut self, region: Region) {
+        self.regions.push(region);
+    }
+
+    /// Swap the loaded region out for one of the others.
+    fn swap_region(&mut self, index: usize) {
+        let region = &mut self.regions[index];
+        mem::swap(&mut self.region_name, &mut region.name);
+        mem::swap(&mut self.board, &mut region.board);
+        mem::swap(&mut self.entity_context, &mut region.entity_context);
+        mem::swap(&mut self.last_event, &mut region.last_event);
+        mem::swap(&mut self.scripted_events, &mut region.scripted_events);
+        mem::swap(&mut self.gateways, &mut region.gateways);
+    }
+
+    fn region_index(&self, name: &str) -> Result<usize, Error> {
+        self.regions
+            .iter()
+            .position(|r| r.name == name)
+            .ok_or_else(|| Error::UnknownRegion(name.to_owned()))
+    }
+
+    /// Load up another region, so it's the one that gets shown and poked at.
+    pub fn view_region(&mut self, name: &str) -> Result<(), Error> {
+        if name != self.region_name {
+            let index = self.region_index(name)?;
+            self.swap_region(index);
+        }
+        Ok(())
+    }
+
+    /// Do something with another region loaded, then go back to the one we were on.
+    pub fn in_region<R>(&mut self, name: &str, f: impl FnOnce(&mut Self) -> R) -> Result<R, Error> {
+        if name == self.region_name {
+            return Ok(f(self));
+        }
+        let index = self.region_index(name)?;
+        self.swap_region(index);
+        let result = f(self);
+        self.swap_region(index);
+        Ok(result)
+    }
+
+    /// Do something in every region, starting with the one that's loaded. Returns whatever each run gave back, in the same order.
+    pub fn for_each_region<R>(&mut self, mut f: impl FnMut(&mut Self) -> R) -> Vec<R> {
+        let mut results = vec![f(self)];
+        for index in 0..self.regions.len() {
+            self.swap_region(index);
+            results.push(f(self));
+            self.swap_region(index);
+        }
+        results
+    }
+
+    /// Get at a region's board and gateways without loading it. 0 is the loaded region, and the rest are offset by one.
+    fn region_parts(&mut self, index: usize) -> (&mut Board, &[Gateway]) {
+        match index {
+            0 => (&mut self.board, &self.gateways),
+            _ => {
+                let region = &mut self.regions[index - 1];
+                (&mut region.board, &region.gateways)
+            }
+        }
+    }
+
+    /// Carry every animal standing on a gateway over to the region on the other side.
+    /// If there's no room over there, they stay where they are. Returns how many made it across.
+    pub fn handle_migration(&mut self) -> usize {
+        // pull everyone off first, so nobody gets carried across twice in one tick
+        let mut travelers = vec![];
+        for index in 0..=self.regions.len() {
+            let (board, gateways) = self.region_parts(index);
+            for gateway in gateways {
+                if let Some(Entity::Living(Living::Animals(_))) = board.entity_at(gateway.pos) {
+                    let entity = board.remove_entity(gateway.pos).unwrap();
+                    travelers.push((index, gateway.clone(), entity));
+                }
+            }
+        }
+
+        let mut migrated = 0;
+        for (from, gateway, entity) in travelers {
+            let to = if gateway.to == self.region_name {
+                Ok(0)
+            } else {
+                self.region_index(&gateway.to).map(|i| i + 1)
+            };
+            let entity = match to {
+                Ok(to) => match self.region_parts(to).0.place_nearby(gateway.to_pos, entity) {
+                    Ok(pos) => {
+                        info!(target: self.log_target(), "Something wandered through to {} at {pos:?}", gateway.to);
+                        migrated += 1;
+                        continue;
+                    }
+                    Err(e) => e.into_entity(),
+                },
+                Err(e) => {
+                    warn!(target: self.log_target(), "Gateway at {:?} doesn't go anywhere: {e}", gateway.pos);
+                    Some(entity)
+                }
+            };
+            // something else might have come through onto the gateway in the meantime, so this can get bumped over a bit
+            if let Some(entity) = entity {
+                if let Err(e) = self.region_parts(from).0.place_nearby(gateway.pos, entity) {
+                    warn!(target: self.log_target(), "Couldn't send an entity back from a gateway ({e}), so it's been lost!");
+                }
+            }
+        }
+        migrated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::animals::ConcreteAnimals;
+    use crate::entities::{NonAbstractTaxonomy, Species};
+    use crate::entity_control::TrackedEntity;
+    use crate::test_utils::TestBed;
+
+    /// A 3x3 testbed with a fish on a gateway, leading over to an empty reef.
+    fn two_regions() -> TestBed {
+        let gate = Pos { x: 2, y: 1 };
+        let mut testbed = TestBed::new_populated(3, 3, vec![(gate, ConcreteAnimals::Fish)]);
+        testbed.sandbox.gateways.push(Gateway {
+            pos: gate,
+            to: String::from("reef"),
+            to_pos: Pos { x: 0, y: 1 },
+        });
+        let em = EntityManager::new_sharing_ids(&testbed.sandbox.entity_context);
+        let board = Board::new(3, 3, Arc::clone(&em));
+        testbed
+            .sandbox
+            .add_region(Region::new("reef", board, em, vec![]));
+        testbed
+    }
+
+    #[test]
+    fn test_migration() {
+        let mut testbed = two_regions();
+        let id = testbed
+            .get_entity_at_pos(Pos { x: 2, y: 1 })
+            .unwrap()
+            .get_id();
+        assert_eq!(testbed.sandbox.handle_migration(), 1);
+        assert!(testbed.sandbox.get_important_entities().is_empty());
+
+        testbed.sandbox.view_region("reef").unwrap();
+        assert_eq!(testbed.sandbox.get_region_name(), "reef");
+        let fish = testbed.get_entity_at_pos(Pos { x: 0, y: 1 }).unwrap();
+        assert_eq!(Species::of(fish), Species::Fish);
+        // it's still the same fish, now tracked by the reef
+        assert_eq!(fish.get_id(), id);
+        assert_eq!(
+            testbed.sandbox.get_important_entities(),
+            vec![Pos { x: 0, y: 1 }]
+        );
+        assert_eq!(testbed.sandbox.get_region_names(), vec!["main", "reef"]);
+        assert!(testbed.sandbox.view_region("abyss").is_err());
+    }
+
+    #[test]
+    fn test_migration_blocked() {
+        let mut testbed = two_regions();
+        // fill the reef up completely, so there's nowhere to go
+        testbed
+            .sandbox
+            .in_region("reef", |sandbox| {
+                for pos in sandbox.board.iter_positions().collect::<Vec<_>>() {
+                    sandbox
+                        .board
+                        .place_entity(pos, Species::Rock.create_new(None))
+                        .unwrap();
+                }
+            })
+            .unwrap();
+        assert_eq!(testbed.sandbox.handle_migration(), 0);
+        assert_eq!(
+            testbed
+                .get_entity_at_pos(Pos { x: 2, y: 1 })
+                .map(Species::of),
+            Some(Species::Fish)
+        );
+    }
+
+    #[test]
+    fn test_ids_stay_unique() {
+        let mut testbed = two_regions();
+        let ids = testbed.sandbox.for_each_region(|sandbox| {
+            sandbox
+                .board
+                .place_entity(Pos { x: 0, y: 0 }, Species::Crab.create_new(None))
+                .unwrap();
+            sandbox
+                .board
+                .entity_at(Pos { x: 0, y: 0 })
+                .and_then(|e| e.get_id())
+        });
+        assert_eq!(ids.len(), 2);
+        assert_ne!(ids[0], ids[1]);
+    }
+
+    #[test]
+    fn test_events_from_every_region() {
+        let mut testbed = two_regions();
+        testbed.sandbox.for_each_region(|sandbox| {
+            sandbox
+                .scripted_events
+                .push((0, crate::game_events::EventTypes::Party))
+        });
+        // only one gets asked about at a time, and the other waits for the next tick
+        let first = testbed.sandbox.tick().event.unwrap();
+        let second = testbed.sandbox.tick().event.unwrap();
+        assert_eq!(first.region, "main");
+        assert_eq!(second.region, "reef");
+    }
+}
@@ -0,0 +1,325 @@
+// Driving a sandbox for the GUI: passing commands and event answers in, and sending the board back out after every tick.
+// The sandbox only moves forward when the runner gets pumped, so the same runner works on a thread of its own,
+// or from the GUI's update loop when there's no thread to hand it off to (like in a browser).
+
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::time::Duration;
+
+use eframe::egui;
+use instant::Instant;
+use log::{error, info};
+
+use crate::commands::SandboxCommand;
+use crate::crash;
+use crate::locale::tr_with;
+#[cfg(feature = "server")]
+use crate::server::ObservationServer;
+use crate::settings::Verbosity;
+use crate::{Error, GameUpdate, PendingEvent, Sandbox};
+
+/// How long to wait before checking in again, when there's nothing to do but wait on the player.
+const IDLE_WAIT: Duration = Duration::from_millis(100);
+
+/// Where we're at with asking the player about an event. Ticking holds off until they're done, but commands keep coming through.
+enum Prompt {
+    Idle,
+    /// Waiting on the player to pick what to do.
+    Asking(PendingEvent),
+    /// The player's answered, and we're waiting on them to dismiss the result. The answer gets carried out on the next tick.
+    Answered,
+}
+
+/// What came of pumping the runner once.
+#[derive(Debug)]
+enum Step {
+    /// Carry on, pumping again after this long.
+    Wait(Duration),
+    /// The GUI asked for the game to stop.
+    Shutdown,
+}
+
+/// Runs a sandbox for the GUI, a tick at a time whenever it gets pumped.
+/// Nothing in here ever blocks, so it's safe to pump from a GUI's update loop.
+pub struct GuiRunner {
+    sandbox: Sandbox,
+    tx: Sender<GameUpdate>,
+    cmd_rx: Receiver<SandboxCommand>,
+    ctx: egui::Context,
+    loop_tx: Sender<bool>,
+    loop_rx: Receiver<bool>,
+    /// When the last tick was run, or None if there hasn't been one yet.
+    last_tick: Option<Instant>,
+    prompt: Prompt,
+    /// Set once the game's crashed or the GUI's hung up, after which pumping does nothing.
+    stopped: bool,
+    /// Anyone watching from somewhere else, if the game's being served.
+    #[cfg(feature = "server")]
+    server: Option<ObservationServer>,
+}
+
+impl GuiRunner {
+    pub fn new(
+        sandbox: Sandbox,
+        tx: Sender<GameUpdate>,
+        cmd_rx: Receiver<SandboxCommand>,
+        ctx: egui::Context,
+    ) -> Self {
+        let (loop_tx, loop_rx) = std::sync::mpsc::channel();
+        Self {
+            sandbox,
+            tx,
+            cmd_rx,
+            ctx,
+            loop_tx,
+            loop_rx,
+            last_tick: None,
+            prompt: Prompt::Idle,
+            stopped: false,
+            #[cfg(feature = "server")]
+            server: None,
+        }
+    }
+
+    /// Let everyone connected to a server watch the game, and send in commands (and votes) of their own.
+    #[cfg(feature = "server")]
+    pub fn serve(&mut self, server: ObservationServer) {
+        self.server = Some(server);
+    }
+
+    /// Keep pumping on this thread until the game crashes or the GUI hangs up.
+    pub fn run(mut self) {
+        while !self.stopped {
+            let wait = self.pump();
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Catch up on any commands and answers from the GUI, and run a tick if one's due.
+    /// Returns how long it'll be until there's any point pumping again.
+    pub fn pump(&mut self) -> Duration {
+        if self.stopped {
+            return IDLE_WAIT;
+        }
+        let mut result = Ok(Step::Wait(IDLE_WAIT));
+        let crashed = crash::run_contained(
+            |runner: &mut Self| result = runner.step(),
+            self,
+            |runner| runner.sandbox.clock,
+        );
+        if let Err(report) = crashed {
+            error!(
+                target: self.sandbox.log_target(),
+                "The game crashed on tick {}: {}",
+                report.tick, report.message
+            );
+            let _ = self.tx.send(GameUpdate::crashed(report));
+            self.ctx.request_repaint();
+            self.stopped = true;
+            return IDLE_WAIT;
+        }
+        match result {
+            Ok(Step::Wait(wait)) => return wait,
+            Ok(Step::Shutdown) => {
+                info!(target: self.sandbox.log_target(), "Stopping the game loop, as asked")
+            }
+            // the GUI went away, so there's nobody left to play for
+            Err(e) => info!(target: self.sandbox.log_target(), "Stopping the game loop: {e}"),
+        }
+        self.stopped = true;
+        IDLE_WAIT
+    }
+
+    fn step(&mut self) -> Result<Step, Error> {
+        loop {
+            match self.cmd_rx.try_recv() {
+                Ok(SandboxCommand::Shutdown) => return Ok(Step::Shutdown),
+                Ok(command) => {
+                    self.sandbox
+                        .take_command(command, &self.tx, &self.loop_tx, &self.ctx)?
+                }
+                Err(TryRecvError::Empty) => break,
+                // a new game's been started in our place
+                Err(TryRecvError::Disconnected) => return Err(Error::ChannelClosed),
+            }
+        }
+        #[cfg(feature = "server")]
+        if let Some(server) = &mut self.server {
+            server.take_commands(&mut self.sandbox);
+        }
+
+        // the first thing back from the GUI is the player's answer, and the second is them dismissing the result
+        if !matches!(self.prompt, Prompt::Idle) {
+            let Ok(choice) = self.loop_rx.try_recv() else {
+                return Ok(Step::Wait(IDLE_WAIT));
+            };
+            match std::mem::replace(&mut self.prompt, Prompt::Answered) {
+                Prompt::Asking(pending) => {
+                    self.sandbox.answer_event(pending, choice);
+                    return Ok(Step::Wait(IDLE_WAIT));
+                }
+                _ => self.prompt = Prompt::Idle,
+            }
+        }
+
+        if self.sandbox.paused && self.sandbox.pending_steps == 0 {
+            return Ok(Step::Wait(IDLE_WAIT));
+        }
+        // the tick rate can be changed between ticks, so work this out every time
+        let tick_time = Duration::from_secs_f64(1.0 / self.sandbox.tick_rate);
+        if let Some(last_tick) = self.last_tick {
+            let since = last_tick.elapsed();
+            if since < tick_time {
+                // make sure we get pumped again once the next tick is due, even if nothing else is going on
+                self.ctx.request_repaint_after(tick_time - since);
+                return Ok(Step::Wait(tick_time - since));
+            }
+        }
+        self.last_tick = Some(Instant::now());
+        // if we're stepping, this is one of the ticks we were let through
+        self.sandbox.pending_steps = self.sandbox.pending_steps.saturating_sub(1);
+
+        let report = self.sandbox.tick();
+        let verbosity = self.sandbox.settings.verbosity;
+        if verbosity >= Verbosity::Verbose {
+            let consumed = report.elapsed.as_secs_f64() / tick_time.as_secs_f64() * 100.0;
+            println!(
+                "Event loop took {}ms to execute, given a tickrate of {}hz it consumed {consumed:.4}% of the tick.",
+                report.elapsed.as_millis(),
+                self.sandbox.tick_rate
+            );
+        }
+        if verbosity >= Verbosity::Normal {
+            for achievement in &report.unlocked {
+                self.sandbox.console_log.push(tr_with(
+                    "achievement.unlocked",
+                    &[("title", &achievement.title())],
+                ));
+            }
+        }
+        self.sandbox.autosave_if_due();
+        #[cfg(feature = "server")]
+        if let Some(server) = &mut self.server {
+            server.publish(&self.sandbox, &report);
+        }
+
+        let entity_info = self.sandbox.get_entity_info();
+        let event = report
+            .event
+            .as_ref()
+            .map(|pending| self.sandbox.describe_event(pending));
+        let update = self.sandbox.make_update(entity_info, event, &self.loop_tx);
+        self.tx.send(update).map_err(|_| Error::ChannelClosed)?;
+        self.ctx.request_repaint();
+        if let Some(pending) = report.event {
+            self.prompt = Prompt::Asking(pending);
+        }
+        Ok(Step::Wait(tick_time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{EventFrequency, SimulationSettings};
+    use crate::setup::SetupConfig;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn runner() -> (GuiRunner, Receiver<GameUpdate>, Sender<SandboxCommand>) {
+        let setup = SetupConfig {
+            fish: 4,
+            crab: 2,
+            ..SetupConfig::new(8, 8)
+        };
+        let mut sandbox = Sandbox::from_setup(&setup, &mut StdRng::seed_from_u64(5)).unwrap();
+        sandbox.apply_settings(SimulationSettings {
+            tick_rate: 1000.0,
+            event_frequency: EventFrequency::Never,
+            autosave_interval: 0,
+            ..SimulationSettings::default()
+        });
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (cmd_tx, cmd_rx) = std::sync::mpsc::channel();
+        let runner = GuiRunner::new(sandbox, tx, cmd_rx, egui::Context::default());
+        (runner, rx, cmd_tx)
+    }
+
+    #[test]
+    fn test_pumping() {
+        let (mut runner, rx, cmd_tx) = runner();
+
+        // the first tick is due straight away
+        runner.pump();
+        assert_eq!(rx.try_iter().count(), 1);
+        assert_eq!(runner.sandbox.clock, 1);
+
+        // pausing sends an update of its own, but then nothing else happens
+        cmd_tx.send(SandboxCommand::SetPaused(true)).unwrap();
+        runner.pump();
+        assert_eq!(rx.try_iter().count(), 1);
+        std::thread::sleep(Duration::from_millis(5));
+        runner.pump();
+        assert_eq!(rx.try_iter().count(), 0);
+        assert_eq!(runner.sandbox.clock, 1);
+
+        cmd_tx.send(SandboxCommand::SetPaused(false)).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        runner.pump();
+        assert_eq!(runner.sandbox.clock, 2);
+
+        // once the GUI hangs up, there's no point carrying on
+        drop(rx);
+        std::thread::sleep(Duration::from_millis(5));
+        runner.pump();
+        assert!(runner.stopped);
+    }
+
+    #[test]
+    fn test_events_wait_on_the_player() {
+        let (mut runner, rx, cmd_tx) = runner();
+        runner
+            .sandbox
+            .scripted_events
+            .push((0, crate::game_events::EventTypes::Party));
+        runner.pump();
+        let update = rx.try_recv().unwrap();
+        assert!(update.event.is_some());
+
+        // nothing ticks while we're waiting on the player, but commands still get through
+        cmd_tx.send(SandboxCommand::SetTickRate(500.0)).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        runner.pump();
+        assert_eq!(runner.sandbox.clock, 1);
+        assert_eq!(runner.sandbox.tick_rate, 500.0);
+
+        // the answer waits around for the next tick, which comes once the result is dismissed
+        update.loop_tx.send(true).unwrap();
+        runner.pump();
+        assert!(runner.sandbox.answered_event.is_some());
+        assert_eq!(runner.sandbox.clock, 1);
+        update.loop_tx.send(true).unwrap();
+        runner.pump();
+        assert!(runner.sandbox.answered_event.is_none());
+        assert_eq!(runner.sandbox.clock, 2);
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let (mut runner, _rx, cmd_tx) = runner();
+        cmd_tx.send(SandboxCommand::Shutdown).unwrap();
+        assert!(matches!(runner.step(), Ok(Step::Shutdown)));
+        cmd_tx.send(SandboxCommand::Shutdown).unwrap();
+        runner.pump();
+        assert!(runner.stopped);
+        assert_eq!(runner.sandbox.clock, 0);
+    }
+
+    #[test]
+    fn test_new_game_stops_the_old_one() {
+        let (mut runner, _rx, cmd_tx) = runner();
+        runner.pump();
+        drop(cmd_tx);
+        assert!(matches!(runner.step(), Err(Error::ChannelClosed)));
+        runner.pump();
+        assert!(runner.stopped);
+    }
+}
@@ -0,0 +1,344 @@
+// Save files for boards too big to write out as plain text every so often. The game's saved the same way as a scenario
+// (see scenario.rs), only split up: every region's board is cut into chunks of CHUNK_ROWS rows, and each chunk's
+// entities get compressed with zstd and written out on their own, one chunk after another, so there's never more than
+// one chunk's worth of text around at a time. Everything else about the regions, along with where each of their chunks
+// ended up, goes in an index at the end:
+//
+//     DEEPSEA1                   the magic bytes, to tell a save file apart from a text one
+//     chunk, chunk, chunk...     every chunk's compressed entity lines, one after the other
+//     index                      the compressed index
+//     where the index starts     8 bytes, little endian
+//
+// The index is written like a scenario too, with "chunk <offset> <length>" lines where a region's entities would go.
+// Opening a save only reads the index, and a region's chunks aren't read until something asks for them.
+//
+// Compressing and writing out a big board takes a while, so the game only stops long enough to copy everything down,
+// and leaves the rest to a thread of its own (see Sandbox::save_in_background). Browsers have neither threads nor
+// files, so none of this is built for them.
+
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use log::{error, info};
+
+use crate::entities::Entity;
+use crate::game_board::Pos;
+use crate::save_format::SAVE_VERSION;
+use crate::scenario::{write_entity, Scenario, ScenarioError};
+use crate::Sandbox;
+
+/// What every save file starts with.
+pub const MAGIC: &[u8; 8] = b"DEEPSEA1";
+
+/// How many rows of a board go in each chunk.
+pub const CHUNK_ROWS: usize = 64;
+
+/// How hard zstd tries. Anything higher takes a lot longer, for saves that aren't much smaller.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Write a scenario out to a save file. It's written somewhere else first and then moved into place, so a save that
+/// gets cut off partway through never replaces the last good one.
+pub fn write_save(path: impl AsRef<Path>, scenario: &Scenario) -> io::Result<()> {
+    let path = path.as_ref();
+    let partial = path.with_extension("partial");
+    let mut out = BufWriter::new(File::create(&partial)?);
+    out.write_all(MAGIC)?;
+    let mut offset = MAGIC.len() as u64;
+    let mut index = format!("version {SAVE_VERSION}\nname {}\n", scenario.get_name());
+    // writing to a String can't fail
+    let mut text = String::new();
+    for region in scenario.get_regions() {
+        region.write_header(&mut index, true).unwrap();
+        region.write_footer(&mut index).unwrap();
+        let (rows, _) = region.dims();
+        let mut chunks: Vec<Vec<&(Pos, Entity)>> = vec![vec![]; rows.div_ceil(CHUNK_ROWS)];
+        for placed in region.get_entities() {
+            chunks[placed.0.y / CHUNK_ROWS].push(placed);
+        }
+        // there's no point keeping track of anywhere there's nothing at all
+        for chunk in chunks.into_iter().filter(|c| !c.is_empty()) {
+            text.clear();
+            for (pos, entity) in chunk {
+                write_entity(&mut text, *pos, entity).unwrap();
+            }
+            let compressed = zstd::encode_all(text.as_bytes(), COMPRESSION_LEVEL)?;
+            out.write_all(&compressed)?;
+            index += &format!("chunk {offset} {}\n", compressed.len());
+            offset += compressed.len() as u64;
+        }
+    }
+    out.write_all(&zstd::encode_all(index.as_bytes(), COMPRESSION_LEVEL)?)?;
+    out.write_all(&offset.to_le_bytes())?;
+    out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    fs::rename(&partial, path)
+}
+
+/// Whether a file's a save file, rather than a scenario written out as text.
+pub fn is_save_file(path: impl AsRef<Path>) -> bool {
+    let mut magic = [0; MAGIC.len()];
+    File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .is_ok()
+        && &magic == MAGIC
+}
+
+/// Everything the index says about one region.
+#[derive(Debug, Clone)]
+struct RegionIndex {
+    name: String,
+    /// Every line about the region that isn't one of its chunks, starting with its name.
+    lines: Vec<String>,
+    /// Where each of its chunks is in the file, as (offset, length).
+    chunks: Vec<(u64, usize)>,
+}
+
+/// A save file that's been opened, but not read in yet.
+#[derive(Debug, Clone)]
+pub struct SaveFile {
+    path: PathBuf,
+    /// The lines at the top of the index, before any of the regions.
+    preamble: String,
+    name: String,
+    regions: Vec<RegionIndex>,
+}
+
+fn damaged(path: &Path, what: impl Display) -> ScenarioError {
+    ScenarioError::new(0, format!("{path:?} is damaged: {what}"))
+}
+
+impl SaveFile {
+    /// Read a save file's index, leaving the rest of it for later.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        let path = path.as_ref();
+        let mut file = File::open(path)
+            .map_err(|e| ScenarioError::new(0, format!("couldn't read {path:?}: {e}")))?;
+        if !is_save_file(path) {
+            return Err(ScenarioError::new(0, format!("{path:?} isn't a save file")));
+        }
+        let mut start = [0; 8];
+        let end = file
+            .seek(SeekFrom::End(-(start.len() as i64)))
+            .and_then(|end| file.read_exact(&mut start).map(|_| end))
+            .map_err(|e| damaged(path, e))?;
+        let start = u64::from_le_bytes(start);
+        if start < MAGIC.len() as u64 || start > end {
+            return Err(damaged(path, "the index is missing"));
+        }
+        let index =
+            read_frame(&mut file, start, (end - start) as usize).map_err(|e| damaged(path, e))?;
+
+        let mut preamble = String::new();
+        let mut name = String::new();
+        let mut regions: Vec<RegionIndex> = vec![];
+        for line in index.lines() {
+            let mut words = line.split_whitespace();
+            match (words.next(), regions.last_mut()) {
+                (Some("region"), _) => regions.push(RegionIndex {
+                    name: words.collect::<Vec<&str>>().join(" "),
+                    lines: vec![line.to_owned()],
+                    chunks: vec![],
+                }),
+                (Some("chunk"), Some(region)) => {
+                    let chunk = words
+                        .next()
+                        .and_then(|o| o.parse().ok())
+                        .zip(words.next().and_then(|l| l.parse().ok()));
+                    let chunk = chunk.ok_or_else(|| damaged(path, format!("'{line}'")))?;
+                    region.chunks.push(chunk);
+                }
+                (_, Some(region)) => region.lines.push(line.to_owned()),
+                (word, None) => {
+                    if word == Some("name") {
+                        name = line["name".len()..].trim().to_owned();
+                    }
+                    preamble += line;
+                    preamble.push('\n');
+                }
+            }
+        }
+        if regions.is_empty() {
+            return Err(damaged(path, "there aren't any regions in it"));
+        }
+        Ok(Self {
+            path: path.to_owned(),
+            preamble,
+            name,
+            regions,
+        })
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// The name of every region, in the order they were saved in. The first is where the game picks back up.
+    pub fn region_names(&self) -> impl Iterator<Item = &str> {
+        self.regions.iter().map(|r| r.name.as_str())
+    }
+
+    /// Everything in a region, written out like a scenario, with or without its gateways.
+    fn region_text(
+        &self,
+        file: &mut File,
+        region: &RegionIndex,
+        gateways: bool,
+    ) -> Result<String, ScenarioError> {
+        let mut text = String::new();
+        for line in &region.lines {
+            if gateways || !line.starts_with("gateway") {
+                text += line;
+                text.push('\n');
+            }
+        }
+        for (offset, len) in &region.chunks {
+            text += &read_frame(file, *offset, *len).map_err(|e| damaged(&self.path, e))?;
+        }
+        Ok(text)
+    }
+
+    fn reopen(&self) -> Result<File, ScenarioError> {
+        File::open(&self.path)
+            .map_err(|e| ScenarioError::new(0, format!("couldn't read {:?}: {e}", self.path)))
+    }
+
+    /// Read the whole save in.
+    pub fn load(&self) -> Result<Scenario, ScenarioError> {
+        let mut file = self.reopen()?;
+        let mut text = self.preamble.clone();
+        for region in &self.regions {
+            text += &self.region_text(&mut file, region, true)?;
+        }
+        text.parse()
+    }
+
+    /// Read in just the one region, leaving the rest of the save alone. It comes without its gateways, since the
+    /// regions they lead to aren't there to check them against.
+    pub fn load_region(&self, name: &str) -> Result<Scenario, ScenarioError> {
+        let region = self
+            .regions
+            .iter()
+            .find(|r| r.name == name)
+            .ok_or_else(|| ScenarioError::new(0, format!("there's no region called '{name}'")))?;
+        let mut file = self.reopen()?;
+        let text = self.preamble.clone() + self.region_text(&mut file, region, false)?.as_str();
+        text.parse()
+    }
+}
+
+/// Read and decompress one chunk, or the index.
+fn read_frame(file: &mut File, offset: u64, len: usize) -> io::Result<String> {
+    let mut compressed = vec![0; len];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut compressed)?;
+    let text = zstd::decode_all(compressed.as_slice())?;
+    String::from_utf8(text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+impl Sandbox {
+    /// Save the game without holding it up: copying everything down is all that happens here, and the rest happens on
+    /// a thread of its own. If the last save is still being written out, nothing gets saved, and false is returned.
+    pub fn save_in_background(&mut self, path: impl Into<PathBuf>, name: &str) -> bool {
+        if self.saving.as_ref().is_some_and(|s| !s.is_finished()) {
+            return false;
+        }
+        let save = Scenario::capture(self, name);
+        let path = path.into();
+        let target = self.log_target().to_owned();
+        let tick = self.clock;
+        self.saving = Some(thread::spawn(move || match write_save(&path, &save) {
+            Ok(()) => info!(target: &target, "Saved tick {tick} to {}", path.display()),
+            Err(e) => error!(target: &target, "Couldn't save to {}: {e}", path.display()),
+        }));
+        true
+    }
+
+    /// Wait for whatever's being saved in the background to be written out.
+    pub fn finish_saving(&mut self) {
+        if let Some(saving) = self.saving.take() {
+            // anything that went wrong has already been logged
+            let _ = saving.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::Species;
+    use crate::test_utils::TestBed;
+
+    /// A tall region, spread over a few chunks, next to a small one.
+    fn tall_scenario() -> Scenario {
+        let mut text = String::from("name Trench\nregion Trench\nsize 150 4\n");
+        for y in [0, 5, 63, 64, 149] {
+            text += &format!("entity fish 1 {y} hunger={}\n", y % 100);
+        }
+        text += "event 10 party\ngateway 3 0 0 0 Shallows\nregion Shallows\nsize 2 2\nentity kelp 1 1\n";
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_save_files() {
+        let path = std::env::temp_dir().join("deep_sea_sim_test_save.sav");
+        let scenario = tall_scenario();
+        write_save(&path, &scenario).unwrap();
+        assert!(is_save_file(&path));
+
+        let save = SaveFile::open(&path).unwrap();
+        assert_eq!(save.get_name(), "Trench");
+        assert_eq!(
+            save.region_names().collect::<Vec<&str>>(),
+            ["Trench", "Shallows"]
+        );
+        // rows 0 to 63, and then 64 to 127, and then the rest
+        assert_eq!(save.regions[0].chunks.len(), 3);
+        assert_eq!(save.load().unwrap().to_string(), scenario.to_string());
+        assert_eq!(
+            Scenario::load(&path).unwrap().to_string(),
+            scenario.to_string()
+        );
+
+        let shallows = save.load_region("Shallows").unwrap();
+        assert_eq!(shallows.get_regions().len(), 1);
+        assert_eq!(shallows.get_entities().len(), 1);
+        let trench = save.load_region("Trench").unwrap();
+        assert_eq!(trench.get_entities().len(), 5);
+        assert_eq!(trench.get_events().len(), 1);
+        assert!(trench.get_regions()[0].get_gateways().is_empty());
+        assert!(save.load_region("Abyss").is_err());
+    }
+
+    #[test]
+    fn test_damaged_saves() {
+        let path = std::env::temp_dir().join("deep_sea_sim_test_damaged.sav");
+        write_save(&path, &tall_scenario()).unwrap();
+        let whole = fs::read(&path).unwrap();
+        fs::write(&path, &whole[..whole.len() - 3]).unwrap();
+        assert!(SaveFile::open(&path).is_err());
+        // with the index in one piece but a chunk gone bad, it opens but doesn't load
+        let mut broken = whole.clone();
+        broken[MAGIC.len() + 2] ^= 0xff;
+        fs::write(&path, &broken).unwrap();
+        assert!(SaveFile::open(&path).unwrap().load().is_err());
+        fs::write(&path, "name Plain\nsize 1 1\n").unwrap();
+        assert!(!is_save_file(&path));
+        assert!(SaveFile::open(&path).is_err());
+    }
+
+    #[test]
+    fn test_saving_in_background() {
+        let path = std::env::temp_dir().join("deep_sea_sim_test_background.sav");
+        let mut testbed = TestBed::builder(3, 3)
+            .with(Pos { x: 0, y: 0 }, Species::Crab)
+            .with(Pos { x: 2, y: 2 }, Species::Rock)
+            .build();
+        assert!(testbed.sandbox.save_in_background(&path, "Later"));
+        testbed.sandbox.finish_saving();
+        let saved = Scenario::load(&path).unwrap();
+        assert_eq!(saved.get_name(), "Later");
+        assert_eq!(saved.get_entities().len(), 2);
+    }
+}
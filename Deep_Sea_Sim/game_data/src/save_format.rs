@@ -0,0 +1,160 @@
+// Keeping old saves loadable. Saved games (see scenario.rs) start with the version of the format they were written in,
+// and whenever the format changes in a way older saves won't fit, like an entity losing or renaming one of its stats,
+// SAVE_VERSION goes up by one and a migration gets added to MIGRATIONS to bring saves from the version before up to
+// date. Loading runs a save through every migration from its version on, so one saved any number of versions ago still
+// loads, as long as it isn't from a newer version of the game than this one. Anything without a version at all is
+// from before saves had one, which counts as version 0. That includes every hand written scenario.
+//
+// Migrations rewrite a save a line at a time, so the line numbers in any errors still point at the right place in the
+// file. Stats that are new in a version don't need a migration: anything a save leaves out gets its usual starting
+// value.
+//
+// Every version so far:
+// 0. Before saves had a version.
+// 1. Saves start with "version 1". Nothing else changed.
+//
+// Every version keeps an example save around in tests/saves, to make sure it still loads.
+
+use crate::scenario::ScenarioError;
+
+/// The version of the format saves get written in.
+pub const SAVE_VERSION: usize = 1;
+
+/// Rewrites one line of a save from the version before into the next one.
+type Migration = fn(&str) -> String;
+
+/// Every migration, oldest first. The first one brings saves from version 0 up to version 1, and so on.
+const MIGRATIONS: [Migration; SAVE_VERSION] = [
+    // nothing but the version itself was new
+    str::to_owned,
+];
+
+/// Bring a save up to the current version, leaving out the version line. Everything else stays on the line it was on.
+pub fn upgrade(contents: &str) -> Result<String, ScenarioError> {
+    upgrade_with(contents, &MIGRATIONS)
+}
+
+fn upgrade_with(contents: &str, migrations: &[Migration]) -> Result<String, ScenarioError> {
+    let latest = migrations.len();
+    // the version comes first, if there is one
+    let first = contents
+        .lines()
+        .enumerate()
+        .map(|(i, l)| (i + 1, l.split('#').next().unwrap().trim()))
+        .find(|(_, l)| !l.is_empty());
+    let (header, version) = match first.map(|(line, l)| (line, l.split_once(' '))) {
+        Some((line, Some(("version", value)))) => {
+            let version: usize = value.trim().parse().map_err(|_| {
+                ScenarioError::new(line, format!("'{}' isn't a valid version", value.trim()))
+            })?;
+            if version > latest {
+                return Err(ScenarioError::new(
+                    line,
+                    format!(
+                        "this was saved by a newer version of the game (version {version}), and only saves up to version {latest} can be loaded"
+                    ),
+                ));
+            }
+            (line, version)
+        }
+        _ => (0, 0),
+    };
+
+    let mut upgraded = String::with_capacity(contents.len());
+    for (i, line) in contents.lines().enumerate() {
+        if i + 1 != header {
+            let line = migrations[version..]
+                .iter()
+                .fold(line.to_owned(), |line, migrate| migrate(&line));
+            upgraded += &line;
+        }
+        upgraded.push('\n');
+    }
+    Ok(upgraded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::Species;
+    use crate::scenario::Scenario;
+
+    /// An example save from every version, oldest first.
+    const SAVES: [&str; SAVE_VERSION + 1] = [
+        include_str!("tests/saves/v0.txt"),
+        include_str!("tests/saves/v1.txt"),
+    ];
+
+    #[test]
+    fn test_every_version_loads() {
+        for (version, save) in SAVES.iter().enumerate() {
+            let scenario: Scenario = save
+                .parse()
+                .unwrap_or_else(|e| panic!("version {version} didn't load: {e}"));
+            assert_eq!(scenario.get_name(), "Tide Pools");
+            let names: Vec<&str> = scenario
+                .get_regions()
+                .iter()
+                .map(|r| r.get_name())
+                .collect();
+            assert_eq!(names, ["Reef", "Open Ocean"]);
+            let stats_of = |species| {
+                let (_, entity) = scenario
+                    .get_regions()
+                    .iter()
+                    .flat_map(|r| r.get_entities())
+                    .find(|(_, e)| Species::of(e) == species)
+                    .unwrap();
+                entity.get_stats()
+            };
+            assert!(stats_of(Species::Fish).starts_with("hp=80 hunger=64 age=12 generation=2"));
+            assert!(stats_of(Species::Shark).starts_with("hp=180 hunger=40 age=50"));
+
+            // saving it again brings it up to date, and loading that gives back the same thing
+            let saved = scenario.to_string();
+            assert!(saved.starts_with(&format!("version {SAVE_VERSION}\n")));
+            let reloaded: Scenario = saved.parse().unwrap();
+            assert_eq!(reloaded.to_string(), saved);
+            let sandbox = reloaded.build(1.0);
+            assert_eq!(sandbox.census().count(Species::Crab), 1);
+        }
+    }
+
+    #[test]
+    fn test_migrations() {
+        // a made up history, where hunger got renamed to fullness and then the fish all became sharks
+        let migrations: [Migration; 2] = [
+            |line| line.replace("hunger=", "fullness="),
+            |line| line.replace("fish", "shark"),
+        ];
+        let unversioned = "name Old\n\nentity fish 0 0 hunger=3\n";
+        assert_eq!(
+            upgrade_with(unversioned, &migrations).unwrap(),
+            "name Old\n\nentity shark 0 0 fullness=3\n"
+        );
+        // only whatever came after the save's version gets run, and the version line's left blank
+        let versioned = "# saved\nversion 1\nentity fish 0 0 hunger=3";
+        assert_eq!(
+            upgrade_with(versioned, &migrations).unwrap(),
+            "# saved\n\nentity shark 0 0 hunger=3\n"
+        );
+        assert_eq!(
+            upgrade_with("version 2\nentity fish 0 0", &migrations).unwrap(),
+            "\nentity fish 0 0\n"
+        );
+    }
+
+    #[test]
+    fn test_unloadable_versions() {
+        let newer = format!("version {}\nname Future\nsize 1 1", SAVE_VERSION + 1);
+        let err = newer.parse::<Scenario>().unwrap_err();
+        assert_eq!(err.line, 1);
+        assert!(err.message.contains("newer version"));
+        assert_eq!(upgrade("\n  version one").unwrap_err().line, 2);
+        // the version only counts at the top
+        let err = "name Late\nversion 1\nsize 1 1"
+            .parse::<Scenario>()
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+}
@@ -0,0 +1,637 @@
+// Loading predefined maps and starting populations from scenario files.
+
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+use crate::entities::{Entity, NonAbstractTaxonomy, Species};
+use crate::entity_control::{EntityID, EntityManager, TrackedEntity};
+use crate::game_board::{Board, Current, Pos, Topology};
+use crate::game_events::EventTypes;
+use crate::region::{Gateway, Region, DEFAULT_REGION};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::save_file::{self, SaveFile};
+use crate::save_format::{self, SAVE_VERSION};
+use crate::Sandbox;
+
+/// Where the game gets saved to every so often.
+pub const AUTOSAVE_FILE: &str = "autosave.sav";
+
+/// Something went wrong while reading a scenario.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScenarioError {
+    /// The line the problem was found on, starting from 1. If 0, the problem wasn't with any one line.
+    pub line: usize,
+    pub message: String,
+}
+
+impl ScenarioError {
+    pub(crate) fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ScenarioError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+/// A hand-crafted starting point for a game: a map, the creatures on it, and any events that should happen along the way.
+///
+/// Scenario files are read line by line, and anything after a `#` is ignored.
+/// ```text
+/// version 1                           # optional, saved games start with the version of the format they were saved in
+/// name Overfished Reef
+/// region Reef                         # optional, everything up to the next region line belongs to this one
+/// size 6 10                           # rows, then columns
+/// topology hex                        # optional, square or hex (square if left out)
+/// current east                        # optional, which way seeds drift: still, north, east, south or west (still if left out)
+/// map                                 # optional, one line per row
+/// ..r...k.v.                          # . is empty, r is a rock, s is a shell, b is rubble, o is bones, u is an urchin, v is a vent, k is kelp
+/// ...
+/// end
+/// entity shark 3 4 hunger=20 hp=150   # species, x, y, then any stat overrides. Saved games give living things an id= too
+/// event 50 oil_spill                  # fire this event on tick 50
+/// gateway 9 3 0 3 Open Ocean          # x, y, then where it comes out in the named region
+/// region Open Ocean                   # ...and so on for every other region
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    name: String,
+    /// Always has at least one region in it. The first one is where the game starts out looking.
+    regions: Vec<RegionLayout>,
+}
+
+/// Everything a scenario says about one region.
+#[derive(Debug, Clone)]
+pub struct RegionLayout {
+    name: String,
+    rows: usize,
+    cols: usize,
+    topology: Topology,
+    current: Current,
+    /// Every entity to place, and where to put it.
+    entities: Vec<(Pos, Entity)>,
+    /// Events that should fire at a given tick, regardless of the usual odds.
+    events: Vec<(usize, EventTypes)>,
+    gateways: Vec<Gateway>,
+}
+
+impl RegionLayout {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            rows: 0,
+            cols: 0,
+            topology: Topology::Square,
+            current: Current::Still,
+            entities: vec![],
+            events: vec![],
+            gateways: vec![],
+        }
+    }
+
+    /// Write down everything in a region, with its scripted events brought in line with the current tick.
+    fn capture(
+        name: &str,
+        board: &Board,
+        scripted_events: &[(usize, EventTypes)],
+        gateways: &[Gateway],
+        clock: usize,
+    ) -> Self {
+        let (cols, rows) = board.dims();
+        let entities = board
+            .iter_occupied()
+            .map(|(pos, entity)| (pos, entity.clone()))
+            .collect();
+        // scripted events are relative to the start of the scenario, so bring them in line with the current tick
+        let events = scripted_events
+            .iter()
+            .filter(|(tick, _)| *tick >= clock)
+            .map(|(tick, kind)| (tick - clock, kind.clone()))
+            .collect();
+        Self {
+            name: name.to_owned(),
+            rows,
+            cols,
+            topology: board.get_topology(),
+            current: board.get_current(),
+            entities,
+            events,
+            gateways: gateways.to_vec(),
+        }
+    }
+
+    /// Lay the region's board out, with everything in its place.
+    fn build_board(&self, em: &Arc<RwLock<EntityManager>>) -> Board {
+        let mut board = Board::new(self.rows, self.cols, Arc::clone(em));
+        board.set_topology(self.topology);
+        board.set_current(self.current);
+        for (pos, entity) in &self.entities {
+            // positions were all checked when we parsed the file
+            board.place_entity(*pos, entity.clone()).unwrap();
+        }
+        board
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the dimensions of the region's board, as (rows, cols).
+    pub fn dims(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    pub fn get_gateways(&self) -> &[Gateway] {
+        &self.gateways
+    }
+
+    pub fn get_entities(&self) -> &[(Pos, Entity)] {
+        &self.entities
+    }
+
+    fn contains(&self, pos: Pos) -> bool {
+        pos.x < self.cols && pos.y < self.rows
+    }
+
+    /// Place an entity, making sure that it actually fits on the board.
+    fn place(
+        &mut self,
+        line: usize,
+        pos: Pos,
+        entity: Entity,
+        taken: &mut HashSet<Pos>,
+    ) -> Result<(), ScenarioError> {
+        if !self.contains(pos) {
+            return Err(ScenarioError::new(
+                line,
+                format!(
+                    "{pos:?} is outside of the {}x{} board",
+                    self.rows, self.cols
+                ),
+            ));
+        }
+        if !taken.insert(pos) {
+            return Err(ScenarioError::new(
+                line,
+                format!("{pos:?} already has something on it"),
+            ));
+        }
+        self.entities.push((pos, entity));
+        Ok(())
+    }
+}
+
+impl Scenario {
+    /// Read a scenario in from a file, which can be a save file (see save_file.rs) too.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        let path = path.as_ref();
+        #[cfg(not(target_arch = "wasm32"))]
+        if save_file::is_save_file(path) {
+            return SaveFile::open(path)?.load();
+        }
+        let contents = fs::read_to_string(path)
+            .map_err(|e| ScenarioError::new(0, format!("couldn't read {path:?}: {e}")))?;
+        contents.parse()
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// The region the game starts out in.
+    fn first_region(&self) -> &RegionLayout {
+        &self.regions[0]
+    }
+
+    /// Get the dimensions of the starting region's board, as (rows, cols).
+    pub fn dims(&self) -> (usize, usize) {
+        self.first_region().dims()
+    }
+
+    pub fn get_topology(&self) -> Topology {
+        self.first_region().topology
+    }
+
+    pub fn get_entities(&self) -> &[(Pos, Entity)] {
+        &self.first_region().entities
+    }
+
+    pub fn get_events(&self) -> &[(usize, EventTypes)] {
+        &self.first_region().events
+    }
+
+    pub fn get_regions(&self) -> &[RegionLayout] {
+        &self.regions
+    }
+
+    /// Capture everything in a sandbox, so it can be written out and picked back up later.
+    /// The region that's loaded comes first, so that's where the game picks back up.
+    pub fn capture(sandbox: &Sandbox, name: &str) -> Self {
+        let mut regions = vec![RegionLayout::capture(
+            &sandbox.region_name,
+            &sandbox.board,
+            &sandbox.scripted_events,
+            &sandbox.gateways,
+            sandbox.clock,
+        )];
+        for region in &sandbox.regions {
+            regions.push(RegionLayout::capture(
+                &region.name,
+                &region.board,
+                &region.scripted_events,
+                &region.gateways,
+                sandbox.clock,
+            ));
+        }
+        Self {
+            name: name.to_owned(),
+            regions,
+        }
+    }
+
+    /// Set up a fresh sandbox from this scenario.
+    pub fn build(&self, tick_rate: f64) -> Sandbox {
+        let first = self.first_region();
+        let em = EntityManager::new();
+        // anything that was saved with an ID keeps it, so new IDs have to start after the last of those
+        let saved_ids = self.regions.iter().flat_map(|r| &r.entities);
+        if let Some(last) = saved_ids.filter_map(|(_, e)| e.get_id()).max() {
+            em.write().unwrap().skip_past(last);
+        }
+        let board = first.build_board(&em);
+        let mut sandbox = Sandbox::new(board, tick_rate, Arc::clone(&em));
+        sandbox.region_name = first.name.clone();
+        sandbox.scripted_events = first.events.clone();
+        sandbox.gateways = first.gateways.clone();
+        for layout in &self.regions[1..] {
+            let region_em = EntityManager::new_sharing_ids(&em);
+            let board = layout.build_board(&region_em);
+            let mut region = Region::new(&layout.name, board, region_em, layout.gateways.clone());
+            region.scripted_events = layout.events.clone();
+            sandbox.add_region(region);
+        }
+        sandbox
+    }
+
+    /// Make sure a gateway leads somewhere sensible.
+    fn check_gateway(&self, from: usize, gateway: &Gateway) -> Result<(), String> {
+        let region = &self.regions[from];
+        if !region.contains(gateway.pos) {
+            return Err(format!("{:?} is outside of the board", gateway.pos));
+        }
+        let to = self
+            .regions
+            .iter()
+            .find(|r| r.name == gateway.to)
+            .ok_or_else(|| format!("there's no region called '{}'", gateway.to))?;
+        if to.name == region.name {
+            return Err(String::from(
+                "a gateway can't lead back into its own region",
+            ));
+        }
+        if !to.contains(gateway.to_pos) {
+            return Err(format!(
+                "{:?} is outside of {}'s board",
+                gateway.to_pos, to.name
+            ));
+        }
+        // otherwise anything coming through would get bounced straight back on the next tick
+        if to.gateways.iter().any(|g| g.pos == gateway.to_pos) {
+            return Err(format!(
+                "{:?} in {} is a gateway itself",
+                gateway.to_pos, to.name
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Write the scenario back out in the same format it's read in.
+impl Display for Scenario {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "version {SAVE_VERSION}")?;
+        writeln!(f, "name {}", self.name)?;
+        let named = self.regions.len() > 1 || self.first_region().name != DEFAULT_REGION;
+        for region in &self.regions {
+            region.write_header(f, named)?;
+            for (pos, entity) in &region.entities {
+                write_entity(f, *pos, entity)?;
+            }
+            region.write_footer(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl RegionLayout {
+    /// Write out the lines that start a region off, which have to come before anything gets placed in it. The name's
+    /// only left out when there's just the one region, with the default name.
+    pub(crate) fn write_header(
+        &self,
+        f: &mut impl std::fmt::Write,
+        named: bool,
+    ) -> std::fmt::Result {
+        if named {
+            writeln!(f, "region {}", self.name)?;
+        }
+        writeln!(f, "size {} {}", self.rows, self.cols)?;
+        if self.topology != Topology::Square {
+            writeln!(f, "topology {}", self.topology.name())?;
+        }
+        if self.current != Current::Still {
+            writeln!(f, "current {}", self.current.name())?;
+        }
+        Ok(())
+    }
+
+    /// Write out the region's scripted events and gateways.
+    pub(crate) fn write_footer(&self, f: &mut impl std::fmt::Write) -> std::fmt::Result {
+        for (tick, kind) in &self.events {
+            writeln!(f, "event {tick} {}", kind.name())?;
+        }
+        for gateway in &self.gateways {
+            writeln!(
+                f,
+                "gateway {} {} {} {} {}",
+                gateway.pos.x, gateway.pos.y, gateway.to_pos.x, gateway.to_pos.y, gateway.to
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Write out the line that places an entity, with its id and every one of its stats.
+pub(crate) fn write_entity(
+    f: &mut impl std::fmt::Write,
+    pos: Pos,
+    entity: &Entity,
+) -> std::fmt::Result {
+    write!(
+        f,
+        "entity {} {} {}",
+        Species::of(entity).name(),
+        pos.x,
+        pos.y
+    )?;
+    if let Some(id) = entity.get_id() {
+        write!(f, " id={}", id.get_id_val())?;
+    }
+    let stats = entity.get_stats();
+    if !stats.is_empty() {
+        write!(f, " {stats}")?;
+    }
+    writeln!(f)
+}
+
+/// Get the species for a tile on the scenario's map, or None if it's empty.
+pub(crate) fn map_species(ch: char) -> Result<Option<Species>, String> {
+    match ch {
+        '.' => Ok(None),
+        'r' => Ok(Some(Species::Rock)),
+        's' => Ok(Some(Species::Shell)),
+        'b' => Ok(Some(Species::Rubble)),
+        'o' => Ok(Some(Species::Bones)),
+        'u' => Ok(Some(Species::Urchin)),
+        'v' => Ok(Some(Species::Vent)),
+        'k' => Ok(Some(Species::Kelp)),
+        _ => Err(format!("'{ch}' isn't a valid map tile")),
+    }
+}
+
+fn parse_usize(line: usize, value: Option<&str>, what: &str) -> Result<usize, ScenarioError> {
+    let value = value.ok_or_else(|| ScenarioError::new(line, format!("missing {what}")))?;
+    value
+        .parse()
+        .map_err(|_| ScenarioError::new(line, format!("'{value}' isn't a valid {what}")))
+}
+
+impl FromStr for Scenario {
+    type Err = ScenarioError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // anything saved by an older version of the game gets brought up to date first (see save_format.rs)
+        let upgraded = save_format::upgrade(s)?;
+        let mut scenario = Scenario {
+            name: String::from("Untitled"),
+            regions: vec![RegionLayout::new(DEFAULT_REGION)],
+        };
+        let mut taken = HashSet::new();
+        let mut ids = HashSet::new();
+        // gateways can point at regions further down the file, so they're all checked at the end
+        let mut gateway_lines = vec![];
+        // lines are numbered from 1 for the sake of whoever's reading the errors
+        let mut lines = upgraded
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l.split('#').next().unwrap().trim()))
+            .filter(|(_, l)| !l.is_empty());
+
+        while let Some((line, contents)) = lines.next() {
+            let mut words = contents.split_whitespace();
+            let keyword = words.next().unwrap();
+            let region_count = scenario.regions.len();
+            let region = scenario.regions.last_mut().unwrap();
+            if !["name", "size", "topology", "current", "region"].contains(&keyword)
+                && region.rows == 0
+            {
+                return Err(ScenarioError::new(
+                    line,
+                    "the board size needs to be set before anything is placed on it",
+                ));
+            }
+            match keyword {
+                "name" => scenario.name = words.collect::<Vec<&str>>().join(" "),
+                "region" => {
+                    let name = words.collect::<Vec<&str>>().join(" ");
+                    if name.is_empty() {
+                        return Err(ScenarioError::new(line, "missing region name"));
+                    }
+                    // nothing's been put in the first region yet, so this is just giving it a name
+                    let naming_first = region_count == 1 && region.rows == 0;
+                    if !naming_first && scenario.regions.iter().any(|r| r.name == name) {
+                        return Err(ScenarioError::new(
+                            line,
+                            format!("there's already a region called '{name}'"),
+                        ));
+                    }
+                    let region = scenario.regions.last_mut().unwrap();
+                    if naming_first {
+                        region.name = name;
+                    } else if region.rows == 0 {
+                        return Err(ScenarioError::new(
+                            line,
+                            format!("region '{}' never set a board size", region.name),
+                        ));
+                    } else {
+                        scenario.regions.push(RegionLayout::new(&name));
+                        taken.clear();
+                    }
+                }
+                "size" => {
+                    region.rows = parse_usize(line, words.next(), "row count")?;
+                    region.cols = parse_usize(line, words.next(), "column count")?;
+                    if region.rows == 0 || region.cols == 0 {
+                        return Err(ScenarioError::new(line, "the board can't be empty"));
+                    }
+                }
+                "topology" => {
+                    let name = words
+                        .next()
+                        .ok_or_else(|| ScenarioError::new(line, "missing topology"))?;
+                    region.topology = Topology::ALL
+                        .into_iter()
+                        .find(|t| t.name() == name)
+                        .ok_or_else(|| {
+                            ScenarioError::new(line, format!("'{name}' isn't a known topology"))
+                        })?;
+                }
+                "current" => {
+                    let name = words
+                        .next()
+                        .ok_or_else(|| ScenarioError::new(line, "missing current"))?;
+                    region.current = Current::ALL
+                        .into_iter()
+                        .find(|c| c.name() == name)
+                        .ok_or_else(|| {
+                            ScenarioError::new(line, format!("'{name}' isn't a known current"))
+                        })?;
+                }
+                "map" => {
+                    for y in 0..region.rows {
+                        let (line, row) = lines.next().ok_or_else(|| {
+                            ScenarioError::new(line, "the map ended before every row was given")
+                        })?;
+                        if row.chars().count() != region.cols {
+                            return Err(ScenarioError::new(
+                                line,
+                                format!("map rows need to be {} tiles wide", region.cols),
+                            ));
+                        }
+                        for (x, ch) in row.chars().enumerate() {
+                            let species =
+                                map_species(ch).map_err(|e| ScenarioError::new(line, e))?;
+                            if let Some(species) = species {
+                                region.place(
+                                    line,
+                                    Pos { x, y },
+                                    species.create_new(None),
+                                    &mut taken,
+                                )?;
+                            }
+                        }
+                    }
+                    match lines.next() {
+                        Some((_, "end")) => (),
+                        Some((line, _)) => {
+                            return Err(ScenarioError::new(line, "expected 'end' after the map"))
+                        }
+                        None => return Err(ScenarioError::new(line, "the map was never ended")),
+                    }
+                }
+                "entity" => {
+                    let name = words
+                        .next()
+                        .ok_or_else(|| ScenarioError::new(line, "missing species"))?;
+                    let species = Species::from_name(name).ok_or_else(|| {
+                        ScenarioError::new(line, format!("'{name}' isn't a known species"))
+                    })?;
+                    let x = parse_usize(line, words.next(), "x position")?;
+                    let y = parse_usize(line, words.next(), "y position")?;
+                    let mut entity = species.create_new(None);
+                    for stat_override in words {
+                        let (stat, value) = stat_override.split_once('=').ok_or_else(|| {
+                            ScenarioError::new(
+                                line,
+                                format!(
+                                    "stat overrides look like stat=value, not '{stat_override}'"
+                                ),
+                            )
+                        })?;
+                        if stat == "id" {
+                            let id = EntityID::from(parse_usize(line, Some(value), "id")?);
+                            if !ids.insert(id) {
+                                return Err(ScenarioError::new(
+                                    line,
+                                    format!("id {value} is already taken"),
+                                ));
+                            }
+                            entity.register(id).map_err(|_| {
+                                ScenarioError::new(line, format!("a {name} can't have an id"))
+                            })?;
+                            continue;
+                        }
+                        entity
+                            .set_stat(stat, value)
+                            .map_err(|e| ScenarioError::new(line, e))?;
+                    }
+                    region.place(line, Pos { x, y }, entity, &mut taken)?;
+                }
+                "event" => {
+                    let tick = parse_usize(line, words.next(), "tick")?;
+                    let name = words
+                        .next()
+                        .ok_or_else(|| ScenarioError::new(line, "missing event name"))?;
+                    let kind = EventTypes::from_name(name).ok_or_else(|| {
+                        ScenarioError::new(line, format!("'{name}' isn't a known event"))
+                    })?;
+                    region.events.push((tick, kind));
+                }
+                "gateway" => {
+                    let x = parse_usize(line, words.next(), "x position")?;
+                    let y = parse_usize(line, words.next(), "y position")?;
+                    let to_x = parse_usize(line, words.next(), "x position")?;
+                    let to_y = parse_usize(line, words.next(), "y position")?;
+                    let to = words.collect::<Vec<&str>>().join(" ");
+                    if to.is_empty() {
+                        return Err(ScenarioError::new(line, "missing region name"));
+                    }
+                    let gateway = Gateway {
+                        pos: Pos { x, y },
+                        to,
+                        to_pos: Pos { x: to_x, y: to_y },
+                    };
+                    region.gateways.push(gateway.clone());
+                    gateway_lines.push((line, region_count - 1, gateway));
+                }
+                _ => {
+                    return Err(ScenarioError::new(
+                        line,
+                        format!("didn't understand '{keyword}'"),
+                    ))
+                }
+            }
+        }
+
+        match scenario.regions.iter().find(|r| r.rows == 0) {
+            Some(_) if scenario.regions.len() == 1 => {
+                return Err(ScenarioError::new(0, "the scenario never set a board size"))
+            }
+            Some(region) => {
+                return Err(ScenarioError::new(
+                    0,
+                    format!("region '{}' never set a board size", region.name),
+                ))
+            }
+            None => (),
+        }
+        for (line, from, gateway) in gateway_lines {
+            scenario
+                .check_gateway(from, &gateway)
+                .map_err(|e| ScenarioError::new(line, e))?;
+        }
+        Ok(scenario)
+    }
+}
@@ -0,0 +1,54 @@
+// Blood in the water. Anything that comes out of a fight with less than WOUNDED_THRESHOLD of its health left is wounded,
+// and bleeds onto every tile it passes over until it's healed back up. Every tile keeps track of how strong the scent on
+// it is, from 0 (nothing) to 1 (fresh), and it fades away a little every tick. Anything with a nose for it (see
+// Combat::smell) goes after wounded prey before anything else, and follows the trail to wherever it's freshest when
+// there's nothing to see at all.
+
+use crate::game_board::Pos;
+use crate::Sandbox;
+
+/// Anything left with less than this much of its health after a fight is wounded.
+pub const WOUNDED_THRESHOLD: f64 = 0.5;
+
+/// How much the scent on a tile fades every tick. A trail goes cold ten ticks after whatever left it passed by.
+pub const SCENT_DECAY: f64 = 0.1;
+
+/// How much more worth going after prey on the freshest of trails is, for anything that can smell it.
+pub const BLOOD_LURE: f64 = 2.0;
+
+impl Sandbox {
+    /// Let the scent on every tile fade a little, and leave fresh scent everywhere a wounded animal went this tick.
+    pub fn handle_scent(&mut self) {
+        let scented: Vec<(Pos, f64)> = self.board.iter_scented().collect();
+        for (pos, level) in scented {
+            // anything fainter than half a tick's fading is only left over from rounding
+            let faded = level - SCENT_DECAY;
+            let faded = if faded < SCENT_DECAY / 2.0 {
+                0.0
+            } else {
+                faded
+            };
+            self.board.get_tile_mut_from_pos(pos).set_scent(faded);
+        }
+
+        let bleeding: Vec<(Pos, Option<Pos>)> = self
+            .board
+            .iter_occupied()
+            .filter_map(|(pos, e)| {
+                e.animal()
+                    .filter(|a| a.wounded())
+                    .map(|a| (pos, a.came_from()))
+            })
+            .collect();
+        for (pos, came_from) in bleeding {
+            // the whole way here, not just where it ended up. Wherever it came from already got some last tick.
+            let mut trail = came_from
+                .map(|from| from.tiles_between(&pos))
+                .unwrap_or_default();
+            trail.push(pos);
+            for tile in trail {
+                self.board.get_tile_mut_from_pos(tile).set_scent(1.0);
+            }
+        }
+    }
+}
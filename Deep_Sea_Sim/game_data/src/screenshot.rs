@@ -0,0 +1,180 @@
+// Pictures of the board, for sharing a colony's milestones or giving a replay a thumbnail. Every tile gets painted as a
+// small square in its species' color (see DisplaySpec), over water that goes murky where
+// it's polluted. Nothing here needs a window, so it works the same from the GUI, the console and the command line.
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::entities::Species;
+use crate::game_board::{Pos, Topology};
+use crate::Sandbox;
+
+/// How many pixels across every tile is drawn.
+pub const TILE_PIXELS: usize = 12;
+/// Empty water.
+const WATER_COLOR: [u8; 3] = [10, 30, 60];
+/// Water as polluted as it gets. Anything in between gets a mix of the two.
+const POLLUTED_COLOR: [u8; 3] = [70, 60, 20];
+
+/// Somewhere between two colors, `amount` of the way from the first to the second.
+fn mix(from: [u8; 3], to: [u8; 3], amount: f64) -> [u8; 3] {
+    let amount = amount.clamp(0.0, 1.0);
+    [0, 1, 2].map(|i| (from[i] as f64 + (to[i] as f64 - from[i] as f64) * amount).round() as u8)
+}
+
+/// The board as it was on one tick, painted out pixel by pixel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Screenshot {
+    pub tick: usize,
+    pub width: usize,
+    pub height: usize,
+    /// Every pixel as RGB, row by row from the top left.
+    pub pixels: Vec<u8>,
+}
+
+impl Screenshot {
+    /// What the picture gets saved as, going by the tick it was taken on.
+    pub fn file_name(&self) -> String {
+        format!("screenshot_tick_{}.png", self.tick)
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        let i = (y * self.width + x) * 3;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    fn fill(&mut self, left: usize, top: usize, size: usize, color: [u8; 3]) {
+        for y in top..top + size {
+            for x in left..left + size {
+                let i = (y * self.width + x) * 3;
+                self.pixels[i..i + 3].copy_from_slice(&color);
+            }
+        }
+    }
+
+    /// Write the picture out as a PNG.
+    pub fn write_png(&self, writer: impl io::Write) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        Ok(())
+    }
+}
+
+impl Sandbox {
+    /// Paint the region that's loaded. On a hex board every odd row is shifted half a tile to the right, like it is in
+    /// the GUI, so the picture is half a tile wider than the board.
+    pub fn screenshot(&self) -> Screenshot {
+        let view = self.view();
+        let (rows, cols) = view.dims();
+        let hex = self.board.get_topology() == Topology::Hex;
+        let width = cols * TILE_PIXELS + if hex { TILE_PIXELS / 2 } else { 0 };
+        let height = rows * TILE_PIXELS;
+        let mut shot = Screenshot {
+            tick: view.tick(),
+            width,
+            height,
+            pixels: WATER_COLOR.repeat(width * height),
+        };
+        let last = Pos {
+            x: cols.saturating_sub(1),
+            y: rows.saturating_sub(1),
+        };
+        for (pos, tile, entity) in view.tiles_in_rect(Pos { x: 0, y: 0 }, last) {
+            let shift = match hex && pos.y % 2 == 1 {
+                true => TILE_PIXELS / 2,
+                false => 0,
+            };
+            let left = pos.x * TILE_PIXELS + shift;
+            let top = pos.y * TILE_PIXELS;
+            let water = mix(WATER_COLOR, POLLUTED_COLOR, tile.get_pollution());
+            shot.fill(left, top, TILE_PIXELS, water);
+            // leave a border of water around anything on the tile, so neighbors don't run together
+            if let Some(entity) = entity {
+                shot.fill(
+                    left + 1,
+                    top + 1,
+                    TILE_PIXELS - 2,
+                    Species::of(entity).display_spec().color,
+                );
+            }
+        }
+        shot
+    }
+
+    /// Save a picture of the region that's loaded into a folder, named after the tick it was taken on.
+    /// Returns where it ended up.
+    pub fn save_screenshot(&self, dir: &Path) -> io::Result<PathBuf> {
+        let shot = self.screenshot();
+        let path = dir.join(shot.file_name());
+        shot.write_png(BufWriter::new(File::create(&path)?))?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_screenshot() {
+        let (shark, kelp) = (Pos { x: 0, y: 0 }, Pos { x: 2, y: 1 });
+        let mut testbed = TestBed::builder(2, 3)
+            .with(shark, Species::Shark)
+            .with(kelp, Species::Kelp)
+            .build();
+        testbed.sandbox.clock = 7;
+        testbed
+            .sandbox
+            .board
+            .get_tile_mut_from_pos(Pos { x: 1, y: 1 })
+            .set_pollution(1.0);
+
+        let shot = testbed.sandbox.screenshot();
+        assert_eq!(
+            (shot.width, shot.height),
+            (3 * TILE_PIXELS, 2 * TILE_PIXELS)
+        );
+        assert_eq!(shot.file_name(), "screenshot_tick_7.png");
+        let middle = |pos: Pos| {
+            shot.pixel(
+                pos.x * TILE_PIXELS + TILE_PIXELS / 2,
+                pos.y * TILE_PIXELS + TILE_PIXELS / 2,
+            )
+        };
+        assert_eq!(middle(shark), Species::Shark.display_spec().color);
+        assert_eq!(middle(kelp), Species::Kelp.display_spec().color);
+        assert_eq!(middle(Pos { x: 1, y: 0 }), WATER_COLOR);
+        assert_eq!(middle(Pos { x: 1, y: 1 }), POLLUTED_COLOR);
+        // the edge of an occupied tile is left as water
+        assert_eq!(shot.pixel(0, 0), WATER_COLOR);
+
+        let mut png = vec![];
+        shot.write_png(&mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+
+    #[test]
+    fn test_hex_screenshot() {
+        let pos = Pos { x: 0, y: 1 };
+        let testbed = TestBed::builder(2, 2)
+            .topology(Topology::Hex)
+            .with(pos, Species::Fish)
+            .build();
+        let shot = testbed.sandbox.screenshot();
+        assert_eq!(shot.width, 2 * TILE_PIXELS + TILE_PIXELS / 2);
+        // the odd row is shifted over by half a tile
+        assert_eq!(
+            shot.pixel(TILE_PIXELS / 2 - 1, TILE_PIXELS + 2),
+            WATER_COLOR
+        );
+        assert_eq!(
+            shot.pixel(TILE_PIXELS / 2 + 2, TILE_PIXELS + 2),
+            Species::Fish.display_spec().color
+        );
+    }
+}
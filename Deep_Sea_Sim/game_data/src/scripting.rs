@@ -0,0 +1,575 @@
+// Custom events and behaviors, written as rhai scripts and loaded when the game starts up, so the game can be modded without recompiling.
+// Scripts only get a small API to work with: counting up what's in a region, changing stats for a whole species, and spawning things.
+// Anything a script asks for gets carried out once it's done running, so a script that fails halfway leaves the board untouched.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use log::{error, info};
+use rand::Rng;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Dynamic, Engine, EvalAltResult, FuncArgs, Scope, AST};
+
+use crate::commands::SandboxCommand;
+use crate::element_traits::SimRng;
+use crate::entities::Species;
+use crate::game_board::Pos;
+use crate::game_events::{EventChoice, EventPrompt, EventTypes, GameEvents};
+use crate::Sandbox;
+
+/// Where the game looks for scripts when it starts up.
+pub const SCRIPTS_DIR: &str = "scripts";
+
+/// How much work a script gets to do in one go, so a runaway loop can't hang the game.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// The functions a script needs to define to be an event, and how many arguments each takes.
+const EVENT_HOOKS: [(&str, usize); 3] = [("describe", 0), ("outcome", 1), ("on_answer", 2)];
+
+/// What an event script's choices get drawn with. Scripts can't say what their choices are going to do, so they don't get a preview.
+const SCRIPT_ICON: &str = "script";
+
+/// A script that couldn't be loaded, or went wrong while running.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptError {
+    /// The name of the script, which is its file name without the extension.
+    pub script: String,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.script, self.message)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// Something a script asked for, which gets carried out once it's finished running.
+#[derive(Debug, Clone, PartialEq)]
+enum ScriptAction {
+    SetStat(Species, String, String),
+    Spawn(Pos, Species),
+}
+
+/// What a script can see of the region it's running in, along with everything it's asked for so far.
+struct ScriptWorld {
+    clock: usize,
+    rows: usize,
+    cols: usize,
+    populations: HashMap<Species, usize>,
+    rng: SimRng,
+    actions: Vec<ScriptAction>,
+}
+
+/// The region a script is running in, handed to it as `sea`.
+#[derive(Clone)]
+struct Sea(Arc<Mutex<ScriptWorld>>);
+
+impl Sea {
+    fn world(&self) -> MutexGuard<'_, ScriptWorld> {
+        self.0.lock().unwrap()
+    }
+}
+
+fn species(name: &str) -> Result<Species, Box<EvalAltResult>> {
+    Species::from_name(name).ok_or_else(|| format!("there's no species called '{name}'").into())
+}
+
+struct Script {
+    name: String,
+    ast: AST,
+    /// Defines everything in EVENT_HOOKS, so it can be picked as a random event.
+    is_event: bool,
+    /// Defines on_tick, so it gets run every tick.
+    has_behavior: bool,
+    /// Defines label(choice), so its choices get named. Otherwise they're just numbered.
+    has_labels: bool,
+}
+
+/// Every script that's been loaded, along with the engine that runs them. Sandboxes share one of these between them.
+pub struct ScriptRegistry {
+    engine: Engine,
+    scripts: Vec<Script>,
+}
+
+impl fmt::Debug for ScriptRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.scripts.iter().map(|s| &s.name))
+            .finish()
+    }
+}
+
+impl Default for ScriptRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScriptRegistry {
+    /// An empty registry, with an engine that only knows about the scripting API.
+    pub fn new() -> Self {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        // scripts can't go reading other files, or compiling more code on the fly
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.on_print(|text| info!("{text}"));
+
+        engine
+            .register_type_with_name::<Sea>("Sea")
+            .register_get("clock", |sea: &mut Sea| sea.world().clock as i64)
+            .register_get("rows", |sea: &mut Sea| sea.world().rows as i64)
+            .register_get("cols", |sea: &mut Sea| sea.world().cols as i64)
+            .register_fn(
+                "count",
+                |sea: &mut Sea, name: &str| -> Result<i64, Box<EvalAltResult>> {
+                    let species = species(name)?;
+                    Ok(sea.world().populations.get(&species).copied().unwrap_or(0) as i64)
+                },
+            )
+            .register_fn(
+                "chance",
+                |sea: &mut Sea, odds: f64| -> Result<bool, Box<EvalAltResult>> {
+                    if !odds.is_finite() {
+                        return Err(format!("{odds} isn't a chance of anything").into());
+                    }
+                    let rng = sea.world().rng.clone();
+                    let chance = rng.lock().unwrap().gen_bool(odds.clamp(0.0, 1.0));
+                    Ok(chance)
+                },
+            )
+            .register_fn(
+                "set_stat",
+                |sea: &mut Sea,
+                 name: &str,
+                 stat: &str,
+                 value: Dynamic|
+                 -> Result<(), Box<EvalAltResult>> {
+                    let action =
+                        ScriptAction::SetStat(species(name)?, stat.into(), value.to_string());
+                    sea.world().actions.push(action);
+                    Ok(())
+                },
+            )
+            .register_fn(
+                "place",
+                |sea: &mut Sea, name: &str, x: i64, y: i64| -> Result<(), Box<EvalAltResult>> {
+                    let species = species(name)?;
+                    let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) else {
+                        return Err(format!("({x}, {y}) is off the board").into());
+                    };
+                    sea.world()
+                        .actions
+                        .push(ScriptAction::Spawn(Pos { x, y }, species));
+                    Ok(())
+                },
+            );
+        Self {
+            engine,
+            scripts: vec![],
+        }
+    }
+
+    /// Load every `.rhai` file in a folder. A folder that isn't there just means there are no scripts.
+    /// Scripts that can't be loaded get skipped, and returned alongside everything that could.
+    pub fn load_dir(dir: impl AsRef<Path>) -> (Self, Vec<ScriptError>) {
+        let mut registry = Self::new();
+        let mut errors = vec![];
+        let Ok(entries) = fs::read_dir(dir) else {
+            return (registry, errors);
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .collect();
+        // the order decides which roll picks which event, so don't leave it up to the filesystem
+        paths.sort();
+        for path in paths {
+            let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            let result = fs::read_to_string(&path)
+                .map_err(|e| ScriptError {
+                    script: name.clone(),
+                    message: e.to_string(),
+                })
+                .and_then(|source| registry.add(&name, &source));
+            if let Err(e) = result {
+                errors.push(e);
+            }
+        }
+        (registry, errors)
+    }
+
+    /// Compile a script and add it under the given name, which is also the name of its event if it has one.
+    pub fn add(&mut self, name: &str, source: &str) -> Result<(), ScriptError> {
+        let error = |message: String| ScriptError {
+            script: name.to_string(),
+            message,
+        };
+        if EventTypes::from_name(name).is_some() || self.scripts.iter().any(|s| s.name == name) {
+            return Err(error(format!("there's already an event called '{name}'")));
+        }
+        let ast = self
+            .engine
+            .compile(source)
+            .map_err(|e| error(e.to_string()))?;
+        let defines = |hook: &str, args: usize| {
+            ast.iter_functions()
+                .any(|f| f.name == hook && f.params.len() == args)
+        };
+
+        let missing: Vec<String> = EVENT_HOOKS
+            .iter()
+            .filter(|(hook, args)| !defines(hook, *args))
+            .map(|(hook, args)| format!("{hook} (with {args} arguments)"))
+            .collect();
+        let is_event = missing.is_empty();
+        let has_behavior = defines("on_tick", 1);
+        let has_labels = defines("label", 1);
+        if missing.len() < EVENT_HOOKS.len() && !is_event {
+            return Err(error(format!("events also need {}", missing.join(", "))));
+        }
+        if !is_event && !has_behavior {
+            return Err(error(String::from(
+                "scripts need either on_tick(sea), or describe(), outcome(choice) and on_answer(sea, choice)",
+            )));
+        }
+        self.scripts.push(Script {
+            name: name.to_string(),
+            ast,
+            is_event,
+            has_behavior,
+            has_labels,
+        });
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.scripts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    /// The names of every event the scripts add, in the order they get rolled.
+    pub fn event_names(&self) -> impl Iterator<Item = &str> {
+        self.events().map(|s| s.name.as_str())
+    }
+
+    fn events(&self) -> impl Iterator<Item = &Script> {
+        self.scripts.iter().filter(|s| s.is_event)
+    }
+
+    fn call<T: Clone + Send + Sync + 'static>(
+        &self,
+        script: &Script,
+        hook: &str,
+        args: impl FuncArgs,
+    ) -> Result<T, ScriptError> {
+        self.engine
+            .call_fn(&mut Scope::new(), &script.ast, hook, args)
+            .map_err(|e| ScriptError {
+                script: script.name.clone(),
+                message: e.to_string(),
+            })
+    }
+
+    /// Ask an event script what it has to say, putting it together the same way as the built in events.
+    fn describe(&self, script: &Script) -> Result<EventTypes, ScriptError> {
+        // scripts say what happened and what to do about it all in one go
+        let intro: String = self.call(script, "describe", ())?;
+        let choice = |answer: bool| -> Result<EventChoice, ScriptError> {
+            let label = match script.has_labels {
+                true => self.call(script, "label", (answer,))?,
+                false => String::from(if answer { "2" } else { "1" }),
+            };
+            Ok(EventChoice {
+                label,
+                icon: String::from(SCRIPT_ICON),
+                consequences: vec![],
+                outcome: self.call(script, "outcome", (answer,))?,
+            })
+        };
+        Ok(EventTypes::Scripted {
+            name: script.name.clone(),
+            prompt: Box::new(EventPrompt {
+                intro,
+                prompt: String::new(),
+                choices: [choice(false)?, choice(true)?],
+                // scripts can't say which is safer, so it's whichever comes first
+                default: false,
+            }),
+        })
+    }
+}
+
+impl Sandbox {
+    /// Load every script in a folder, to be run from here on out. Anything that couldn't be loaded gets reported on the console.
+    pub fn load_scripts(&mut self, dir: impl AsRef<Path>) {
+        let (registry, errors) = ScriptRegistry::load_dir(dir);
+        for e in errors {
+            self.report_script_error("Couldn't load script", e);
+        }
+        if !registry.is_empty() {
+            info!(target: self.log_target(), "Loaded {} scripts", registry.len());
+            self.scripts = Some(Arc::new(registry));
+        }
+    }
+
+    /// Use scripts that have already been loaded, like when several sandboxes are running the same mod.
+    pub fn set_scripts(&mut self, scripts: Arc<ScriptRegistry>) {
+        self.scripts = Some(scripts);
+    }
+
+    /// How many events the scripts add on top of the built in ones.
+    pub(crate) fn scripted_event_count(&self) -> usize {
+        self.scripts.as_ref().map_or(0, |s| s.events().count())
+    }
+
+    /// Set off one of the scripts' events, counting from 0 in the order they were loaded.
+    pub(crate) fn scripted_event(&mut self, index: usize) -> Option<GameEvents> {
+        let scripts = self.scripts.clone()?;
+        let script = scripts.events().nth(index)?;
+        match scripts.describe(script) {
+            Ok(kind) => Some(GameEvents { kind }),
+            Err(e) => {
+                self.report_script_error("Script error in", e);
+                None
+            }
+        }
+    }
+
+    /// Carry out the player's answer to a scripted event.
+    pub(crate) fn answer_scripted_event(&mut self, name: &str, choice: bool) {
+        let Some(scripts) = self.scripts.clone() else {
+            return;
+        };
+        let Some(script) = scripts.events().find(|s| s.name == name) else {
+            error!(target: self.log_target(), "There's no script for the event {name}");
+            return;
+        };
+        let sea = self.sea();
+        match scripts.call::<Dynamic>(script, "on_answer", (sea.clone(), choice)) {
+            Ok(_) => self.carry_out(name, sea),
+            Err(e) => self.report_script_error("Script error in", e),
+        }
+    }
+
+    /// Run every script's on_tick for the loaded region.
+    pub(crate) fn run_behaviors(&mut self) {
+        let Some(scripts) = self.scripts.clone() else {
+            return;
+        };
+        for script in scripts.scripts.iter().filter(|s| s.has_behavior) {
+            let sea = self.sea();
+            match scripts.call::<Dynamic>(script, "on_tick", (sea.clone(),)) {
+                Ok(_) => self.carry_out(&script.name, sea),
+                Err(e) => self.report_script_error("Script error in", e),
+            }
+        }
+    }
+
+    /// A look at the loaded region, for a script to work with.
+    fn sea(&self) -> Sea {
+        let view = self.view();
+        let (rows, cols) = view.dims();
+        Sea(Arc::new(Mutex::new(ScriptWorld {
+            clock: view.tick(),
+            rows,
+            cols,
+            populations: view.populations(),
+            rng: self.rng.clone(),
+            actions: vec![],
+        })))
+    }
+
+    /// Do everything a script asked for, once it's finished running.
+    fn carry_out(&mut self, script: &str, sea: Sea) {
+        let actions = std::mem::take(&mut sea.world().actions);
+        for action in actions {
+            let result = match action {
+                ScriptAction::SetStat(species, stat, value) => {
+                    self.set_species_stat(species, &stat, &value)
+                }
+                ScriptAction::Spawn(pos, species) => self
+                    .apply_command(SandboxCommand::Spawn(pos, species))
+                    .map(|_| ()),
+            };
+            if let Err(message) = result {
+                let e = ScriptError {
+                    script: script.to_string(),
+                    message,
+                };
+                self.report_script_error("Script error in", e);
+            }
+        }
+    }
+
+    /// Change a stat for every one of a species in the loaded region.
+    fn set_species_stat(
+        &mut self,
+        species: Species,
+        stat: &str,
+        value: &str,
+    ) -> Result<(), String> {
+        let positions: Vec<Pos> = self
+            .board
+            .iter_occupied()
+            .filter(|(_, entity)| Species::of(entity) == species)
+            .map(|(pos, _)| pos)
+            .collect();
+        for pos in positions {
+            if let Some(entity) = self.board.entity_at_mut(pos) {
+                entity.set_stat(stat, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn report_script_error(&mut self, context: &str, e: ScriptError) {
+        error!(target: self.log_target(), "{context} {e}");
+        self.console_log.push(format!("{context} {e}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_events::Event;
+    use crate::settings::Difficulty;
+    use crate::setup::SetupConfig;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    const SPAWNER: &str = r#"
+        fn describe() { "Something stirs in the deep.\n\t1. Ignore it\n\t2. Investigate" }
+        fn outcome(choice) { if choice { "A shark!" } else { "Nothing happens." } }
+        fn on_answer(sea, choice) {
+            if choice && sea.count("shark") == 0 {
+                sea.place("shark", 0, 0);
+            }
+        }
+    "#;
+
+    fn scripted_sandbox(scripts: &[(&str, &str)]) -> Sandbox {
+        let setup = SetupConfig {
+            fish: 3,
+            kelp_chance: 0.0,
+            ..SetupConfig::new(6, 6)
+        };
+        let mut sandbox = Sandbox::from_setup(&setup, &mut StdRng::seed_from_u64(3)).unwrap();
+        let mut registry = ScriptRegistry::new();
+        for (name, source) in scripts {
+            registry.add(name, source).unwrap();
+        }
+        sandbox.set_scripts(Arc::new(registry));
+        sandbox
+    }
+
+    #[test]
+    fn test_loading() {
+        let mut registry = ScriptRegistry::new();
+        registry.add("spawner", SPAWNER).unwrap();
+        registry
+            .add(
+                "red_tide",
+                include_str!("../../example_scripts/red_tide.rhai"),
+            )
+            .unwrap();
+        assert_eq!(
+            registry.event_names().collect::<Vec<_>>(),
+            ["spawner", "red_tide"]
+        );
+
+        // names can't clash with other events
+        assert!(registry.add("spawner", SPAWNER).is_err());
+        assert!(registry.add("party", SPAWNER).is_err());
+        // half an event is probably a mistake
+        let e = registry
+            .add("half", "fn describe() { \"hi\" }")
+            .unwrap_err();
+        assert!(e.message.contains("on_answer"));
+        assert!(registry.add("nothing", "let x = 1;").is_err());
+        assert!(registry.add("broken", "fn on_tick(sea) {").is_err());
+        assert_eq!(registry.len(), 2);
+    }
+
+    #[test]
+    fn test_scripted_events() {
+        let mut sandbox = scripted_sandbox(&[("spawner", SPAWNER)]);
+        assert_eq!(sandbox.scripted_event_count(), 1);
+        let event = sandbox.scripted_event(0).unwrap();
+        assert_eq!(event.kind.name(), "spawner");
+        let prompt = event.get_prompt(Difficulty::Normal);
+        assert_eq!(prompt.choices[0].label, "1");
+        assert_eq!(prompt.choices[1].outcome, "A shark!");
+        let tide = scripted_sandbox(&[(
+            "red_tide",
+            include_str!("../../example_scripts/red_tide.rhai"),
+        )])
+        .scripted_event(0)
+        .unwrap()
+        .get_prompt(Difficulty::Normal);
+        assert_eq!(tide.choices[0].label, "Wait it out");
+
+        sandbox.board.remove_entity(Pos { x: 0, y: 0 });
+        event.process_event(true, &mut sandbox);
+        assert_eq!(sandbox.census().count(Species::Shark), 1);
+        // the script checks for a shark before spawning another
+        event.process_event(true, &mut sandbox);
+        assert_eq!(sandbox.census().count(Species::Shark), 1);
+    }
+
+    #[test]
+    fn test_behaviors() {
+        let mut sandbox = scripted_sandbox(&[(
+            "aging",
+            r#"fn on_tick(sea) { sea.set_stat("fish", "age", sea.clock + 10); }"#,
+        )]);
+        sandbox.run_behaviors();
+        let fish: Vec<Pos> = sandbox
+            .board
+            .iter_occupied()
+            .filter(|(_, e)| Species::of(e) == Species::Fish)
+            .map(|(pos, _)| pos)
+            .collect();
+        assert_eq!(fish.len(), 3);
+        for pos in fish {
+            let entity = sandbox.board.entity_at_mut(pos);
+            assert!(entity.as_ref().unwrap().get_stats().contains("age=10"));
+        }
+    }
+
+    #[test]
+    fn test_failing_scripts_leave_the_board_alone() {
+        let mut sandbox = scripted_sandbox(&[(
+            "bad",
+            r#"fn on_tick(sea) { sea.place("shark", 0, 1); sea.place("dragon", 0, 0); }"#,
+        )]);
+        let before = sandbox.census();
+        sandbox.run_behaviors();
+        assert_eq!(sandbox.census().populations, before.populations);
+        assert!(sandbox.console_log[0].contains("dragon"));
+
+        // odds that aren't a number stop the script instead of the whole game
+        let mut sandbox = scripted_sandbox(&[(
+            "nan",
+            r#"fn on_tick(sea) { if sea.chance(0.0 / 0.0) { sea.place("shark", 0, 1); } }"#,
+        )]);
+        sandbox.run_behaviors();
+        assert_eq!(sandbox.census().populations, before.populations);
+        assert!(sandbox.console_log[0].contains("NaN"));
+
+        // scripts that never stop, or go looking for other files, get stopped too
+        let mut sandbox = scripted_sandbox(&[
+            ("forever", "fn on_tick(sea) { loop { } }"),
+            ("sneaky", r#"fn on_tick(sea) { import "secrets" as s; }"#),
+        ]);
+        sandbox.run_behaviors();
+        assert_eq!(sandbox.console_log.len(), 2);
+    }
+}
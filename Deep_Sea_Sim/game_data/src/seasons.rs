@@ -0,0 +1,102 @@
+// Every SEASON_LENGTH ticks a new season comes around, and with it a migration: a school of fish turns up along one edge
+// of the board and tries to make it across to the far side over the next while, with a few hungry sharks lying in wait
+// along the way. Migrants know where they're headed (see components::Heading), so the rest of it is just bringing them on
+// and seeing them off.
+
+use log::info;
+use rand::seq::SliceRandom;
+
+use crate::entities::animals::ConcreteAnimals;
+use crate::entities::components::Heading;
+use crate::entities::Species;
+use crate::game_board::{Edge, Pos};
+use crate::Sandbox;
+
+/// How many ticks a season lasts. A migration starts at the beginning of every one but the first.
+pub const SEASON_LENGTH: usize = 500;
+
+/// How many fish join a migration, compared to how many are already on the board.
+pub const SCHOOL_SHARE: f64 = 0.25;
+
+/// The fewest fish that'll ever turn up for a migration, even on an empty board.
+pub const MIN_SCHOOL: usize = 3;
+
+/// How long migrants keep trying to get across before giving up and settling wherever they've got to.
+pub const MIGRATION_TICKS: usize = 60;
+
+/// How many sharks turn up along the middle of the board to pick off migrants as they go by.
+pub const AMBUSHERS: usize = 2;
+
+/// How hungry the sharks lying in wait are when they show up.
+const AMBUSHER_HUNGER: i64 = 20;
+
+impl Sandbox {
+    /// Start a migration whenever a new season comes around, and see off any migrants that have made it across.
+    pub fn handle_seasons(&mut self) {
+        if self.clock > 0 && self.clock.is_multiple_of(SEASON_LENGTH) {
+            let from = *Edge::ALL.choose(&mut *self.rng.lock().unwrap()).unwrap();
+            self.start_migration(from);
+        }
+        self.see_off_migrants();
+    }
+
+    /// Bring a school of fish on along one edge, headed for the opposite one, with sharks waiting for them halfway across.
+    /// Returns how many fish made it onto the board.
+    pub fn start_migration(&mut self, from: Edge) -> usize {
+        let to = from.opposite();
+        let fish = self
+            .board
+            .iter_occupied()
+            .filter(|(_, e)| Species::of(e) == Species::Fish)
+            .count();
+        let school = ((fish as f64 * SCHOOL_SHARE).round() as usize).max(MIN_SCHOOL);
+        let mut rng = self.rng.lock().unwrap();
+        let mut arrived = 0;
+        for _ in 0..school {
+            let mut fish = ConcreteAnimals::Fish.create_new_with_rng(None, &mut *rng);
+            fish.mobility_mut().unwrap().heading = Some(Heading {
+                to,
+                ticks_left: MIGRATION_TICKS,
+            });
+            if self.board.spawn_on_edge(from, fish, &mut *rng).is_err() {
+                break;
+            }
+            arrived += 1;
+        }
+
+        // the sharks know the way they'll come, and wait for them in the middle
+        let halfway = self
+            .board
+            .distance_to_edge(self.board.edge_positions(to).next().unwrap(), from)
+            / 2;
+        let mut ambush_spots: Vec<Pos> = self
+            .board
+            .iter_positions()
+            .filter(|p| self.board.distance_to_edge(*p, from) == halfway)
+            .filter(|p| !self.board.get_tile_from_pos(*p).is_occupied())
+            .collect();
+        ambush_spots.shuffle(&mut *rng);
+        for pos in ambush_spots.into_iter().take(AMBUSHERS) {
+            let mut shark = ConcreteAnimals::Shark.create_new_with_rng(None, &mut *rng);
+            shark.hunger_mut().unwrap().set(AMBUSHER_HUNGER);
+            // we just checked that this was empty
+            self.board.place_entity(pos, shark).unwrap();
+        }
+        info!(target: &self.log_target, "{arrived} fish are migrating in from the {} edge", from.name());
+        arrived
+    }
+
+    /// Migrants that have reached the edge they were headed for carry on off the board.
+    fn see_off_migrants(&mut self) {
+        let leaving: Vec<(Pos, Edge)> = self
+            .board
+            .iter_occupied()
+            .filter_map(|(pos, e)| Some((pos, e.mobility()?.heading.as_ref()?.to)))
+            .collect();
+        for (pos, edge) in leaving {
+            if let Some(migrant) = self.board.despawn_at_edge(pos, edge) {
+                info!(target: &self.log_target, "{migrant:?} made it across to the {} edge", edge.name());
+            }
+        }
+    }
+}
@@ -0,0 +1,447 @@
+// An observation server, for watching and controlling a game that's running somewhere else, like a headless run on a
+// server. Only built with the server feature. Anyone can connect over a WebSocket: once the next tick's done they're
+// sent the whole board, and after that a message for every tick with the census, every tile that changed and anything
+// that went off. Anything they send back is run as a console command (see console::CONSOLE_HELP), and they're told how
+// it went. When events are being voted on (see voting.rs), they're sent the choices and how long they've got to vote
+// with "vote 1" or "vote 2", and everyone hears about every vote that comes in. Messages are plain text, one line per
+// thing:
+//
+//     board 20x20
+//     (a line of glyphs for every row)
+//
+//     tick 12
+//     census fish=10 crab=3 shark=2
+//     health 70
+//     changed 3,4 🐠
+//     event oil_spill
+//     choice 1 Hide from the spill
+//     choice 2 Clean it up
+//     voting 30
+//     unlocked first_hundred_ticks
+//
+// and the answer to a command is "ok" or "error", followed by whatever the console would have said. Every vote gets
+// everyone the new tally, like "votes 2 1".
+//
+// Every client gets a thread of its own, with whatever the game has to tell it queued up, so a slow one never holds the
+// game up. Anyone who falls so far behind that their queue fills up gets hung up on. Votes are counted by IP address,
+// so nobody can vote more than once by connecting again.
+
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::info;
+use tungstenite::handshake::HandshakeError;
+use tungstenite::Message;
+
+use crate::commands::SandboxCommand;
+use crate::console::parse_command;
+use crate::entities::Species;
+use crate::{Sandbox, TickReport};
+
+/// How long a client's thread waits on them to say something before checking whether there's anything to send them.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// How many messages can be waiting to go out to a client before they're given up on.
+pub const OUTBOX_LIMIT: usize = 64;
+
+/// A console command from one of the clients, and who to send the answer to.
+struct Request {
+    line: String,
+    /// The IP address it came from, which is who any vote in it is counted as.
+    from: String,
+    client: usize,
+}
+
+/// Someone who's connected, and the messages waiting to go out to them.
+struct Client {
+    id: usize,
+    outbox: SyncSender<String>,
+}
+
+impl Client {
+    /// Queue up a message for them. Fails if they've hung up, or have fallen too far behind to be worth keeping.
+    fn send(&self, message: String) -> bool {
+        self.outbox.try_send(message).is_ok()
+    }
+}
+
+/// Streams every tick out to anyone who connects, and takes console commands from them.
+pub struct ObservationServer {
+    addr: SocketAddr,
+    /// Clients that have connected since the last tick, who haven't been sent the board yet.
+    joining: Arc<Mutex<Vec<Client>>>,
+    clients: Vec<Client>,
+    requests: Receiver<Request>,
+    /// How big the board was and what was on every tile as of the last tick, to work out what's changed since.
+    dims: (usize, usize),
+    glyphs: Vec<char>,
+}
+
+impl ObservationServer {
+    /// Start listening for clients.
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let joining = Arc::new(Mutex::new(vec![]));
+        let (request_tx, requests) = mpsc::channel();
+        let waiting = Arc::clone(&joining);
+        thread::spawn(move || {
+            for (id, stream) in listener.incoming().flatten().enumerate() {
+                let Ok(from) = stream.peer_addr() else {
+                    continue;
+                };
+                let (tx, outbox) = mpsc::sync_channel(OUTBOX_LIMIT);
+                waiting.lock().unwrap().push(Client { id, outbox: tx });
+                let requests = request_tx.clone();
+                thread::spawn(move || {
+                    let from = from.ip().to_string();
+                    if let Err(e) = serve_client(stream, from, id, outbox, requests) {
+                        info!("A client hung up: {e}");
+                    }
+                });
+            }
+        });
+        info!("Observation server listening on {addr}");
+        Ok(Self {
+            addr,
+            joining,
+            clients: vec![],
+            requests,
+            dims: (0, 0),
+            glyphs: vec![],
+        })
+    }
+
+    /// Where clients can find us.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Tell everyone watching what happened during a tick. Anyone who's only just connected gets the whole board first.
+    pub fn publish(&mut self, sandbox: &Sandbox, report: &TickReport) {
+        let board = sandbox.get_board();
+        let dims = board.dims();
+        let glyphs: Vec<char> = board.glyphs().collect();
+        let joining = std::mem::take(&mut *self.joining.lock().unwrap());
+        if !joining.is_empty() {
+            let whole = describe_board(dims, &glyphs);
+            self.clients.extend(
+                joining
+                    .into_iter()
+                    .filter(|client| client.send(whole.clone())),
+            );
+        }
+
+        let mut message = format!("tick {}\n", report.tick);
+        let census = sandbox.census();
+        let counts: Vec<String> = Species::all()
+            .into_iter()
+            .filter(|s| census.count(*s) > 0)
+            .map(|s| format!("{}={}", s.name(), census.count(s)))
+            .collect();
+        message += &format!("census {}\n", counts.join(" "));
+        if let Some(health) = &census.health {
+            message += &format!("health {}\n", health.score);
+        }
+        if dims == self.dims {
+            let (cols, _) = dims;
+            for (i, (old, new)) in self.glyphs.iter().zip(&glyphs).enumerate() {
+                if old != new {
+                    message += &format!("changed {},{} {new}\n", i % cols, i / cols);
+                }
+            }
+        } else {
+            // a different region, most likely, so none of what was there before is any use
+            message += &describe_board(dims, &glyphs);
+        }
+        if let Some(pending) = &report.event {
+            message += &format!("event {}\n", pending.kind().name());
+            if let (Some(_), Some(seconds)) = (sandbox.votes(), sandbox.settings.event_window()) {
+                let prompt = sandbox.describe_event(pending);
+                for (number, choice) in prompt.choices.iter().enumerate() {
+                    message += &format!("choice {} {}\n", number + 1, choice.label);
+                }
+                message += &format!("voting {seconds}\n");
+            }
+        }
+        for achievement in &report.unlocked {
+            message += &format!("unlocked {}\n", achievement.key());
+        }
+        // anyone who's hung up or can't keep up stops getting anything
+        self.clients.retain(|client| client.send(message.clone()));
+        self.dims = dims;
+        self.glyphs = glyphs;
+    }
+
+    /// Run every command that's come in since the last time, answering whoever sent each one. Returns how many there
+    /// were.
+    pub fn take_commands(&mut self, sandbox: &mut Sandbox) -> usize {
+        let mut taken = 0;
+        while let Ok(Request { line, from, client }) = self.requests.try_recv() {
+            let command = parse_command(&line).map(|command| match command {
+                // everyone gets a vote of their own
+                SandboxCommand::Vote(_, choice) => SandboxCommand::Vote(from.clone(), choice),
+                command => command,
+            });
+            let voted = matches!(command, Ok(SandboxCommand::Vote(..)));
+            let answer = match command.and_then(|c| sandbox.apply_command(c)) {
+                Ok(msg) => format!("ok {msg}"),
+                Err(msg) => format!("error {msg}"),
+            };
+            info!("Remote command '{line}' from {from}: {answer}");
+            // they might have hung up in the meantime, which is their loss
+            let joining = self.joining.lock().unwrap();
+            if let Some(client) = self
+                .clients
+                .iter()
+                .chain(&*joining)
+                .find(|c| c.id == client)
+            {
+                client.send(answer);
+            }
+            drop(joining);
+            if let (true, Some(tally)) = (voted, sandbox.votes()) {
+                let [first, second] = tally.counts();
+                let votes = format!("votes {first} {second}\n");
+                self.clients.retain(|client| client.send(votes.clone()));
+            }
+            taken += 1;
+        }
+        taken
+    }
+
+    /// Hold everything up until the event that's being voted on has been open for as long as it gets, taking commands
+    /// (and votes) the whole time. Does nothing if there's no vote going on.
+    pub fn hold_vote(&mut self, sandbox: &mut Sandbox) {
+        let (Some(_), Some(seconds)) = (sandbox.votes(), sandbox.settings.event_window()) else {
+            return;
+        };
+        let closes = Instant::now() + Duration::from_secs(seconds);
+        while Instant::now() < closes {
+            self.take_commands(sandbox);
+            thread::sleep(POLL_INTERVAL);
+        }
+        self.take_commands(sandbox);
+    }
+}
+
+/// The whole board, as the size of it followed by a line of glyphs for every row.
+fn describe_board((cols, rows): (usize, usize), glyphs: &[char]) -> String {
+    let mut description = format!("board {cols}x{rows}\n");
+    for row in glyphs.chunks(cols.max(1)) {
+        description.extend(row);
+        description.push('\n');
+    }
+    description
+}
+
+/// Pass everything queued up for a client along to them, and everything they send back along to the game, until one
+/// side or the other hangs up.
+fn serve_client(
+    stream: TcpStream,
+    from: String,
+    id: usize,
+    outbox: Receiver<String>,
+    requests: Sender<Request>,
+) -> Result<(), Box<tungstenite::Error>> {
+    let mut socket = tungstenite::accept(stream).map_err(|e| match e {
+        HandshakeError::Failure(e) => e,
+        // only non-blocking streams get interrupted
+        HandshakeError::Interrupted(_) => io::Error::from(ErrorKind::WouldBlock).into(),
+    })?;
+    socket
+        .get_ref()
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .map_err(tungstenite::Error::from)?;
+    loop {
+        loop {
+            match outbox.try_recv() {
+                Ok(message) => socket.send(Message::Text(message))?,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Ok(()),
+            }
+        }
+        match socket.read() {
+            Ok(Message::Text(line)) => {
+                let request = Request {
+                    line,
+                    from: from.clone(),
+                    client: id,
+                };
+                // the game's over, so there's nobody left to answer
+                if requests.send(request).is_err() {
+                    return Ok(());
+                }
+            }
+            // pings get answered on their own, and there's nothing else worth listening to
+            Ok(_) => (),
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tungstenite::stream::MaybeTlsStream;
+
+    use super::*;
+    use crate::entities::animals::ConcreteAnimals;
+    use crate::game_board::Pos;
+    use crate::game_events::EventTypes;
+    use crate::settings::{EventFrequency, SimulationSettings};
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_observing_and_commanding() {
+        let fish = Pos { x: 1, y: 0 };
+        let mut testbed = TestBed::new_populated(2, 3, vec![(fish, ConcreteAnimals::Fish)]);
+        let mut server = ObservationServer::bind("127.0.0.1:0").unwrap();
+        let (mut client, _) =
+            tungstenite::connect(format!("ws://{}", server.local_addr())).unwrap();
+        if let MaybeTlsStream::Plain(stream) = client.get_mut() {
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+        }
+        let mut next = || client.read().unwrap().into_text().unwrap();
+
+        let report = testbed.sandbox.tick();
+        server.publish(&testbed.sandbox, &report);
+        let board = next();
+        assert!(board.starts_with("board 3x2\n"));
+        assert_eq!(board.lines().count(), 3);
+        let tick = next();
+        assert!(tick.starts_with("tick 1\ncensus "));
+        assert!(tick.contains("fish=1"));
+    }
+
+    #[test]
+    fn test_remote_commands() {
+        let mut testbed = TestBed::new_with_entities(2, 3, vec![]);
+        let mut server = ObservationServer::bind("127.0.0.1:0").unwrap();
+        let (mut client, _) =
+            tungstenite::connect(format!("ws://{}", server.local_addr())).unwrap();
+        if let MaybeTlsStream::Plain(stream) = client.get_mut() {
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+        }
+        let report = testbed.sandbox.tick();
+        server.publish(&testbed.sandbox, &report);
+        client.read().unwrap();
+        client.read().unwrap();
+
+        client
+            .send(Message::Text(String::from("spawn rock 0 1")))
+            .unwrap();
+        client.send(Message::Text(String::from("dance"))).unwrap();
+        let start = Instant::now();
+        let mut taken = 0;
+        while taken < 2 && start.elapsed() < Duration::from_secs(5) {
+            taken += server.take_commands(&mut testbed.sandbox);
+        }
+        assert_eq!(taken, 2);
+        assert!(client
+            .read()
+            .unwrap()
+            .into_text()
+            .unwrap()
+            .starts_with("ok "));
+        assert!(client
+            .read()
+            .unwrap()
+            .into_text()
+            .unwrap()
+            .starts_with("error "));
+
+        // and everyone hears about it after the next tick
+        let report = testbed.sandbox.tick();
+        server.publish(&testbed.sandbox, &report);
+        let rock = Species::Rock.display_spec().emoji;
+        let tick = client.read().unwrap().into_text().unwrap();
+        assert!(tick.starts_with("tick 2\n"));
+        assert!(tick.contains(&format!("changed 0,1 {rock}\n")));
+    }
+
+    #[test]
+    fn test_remote_votes() {
+        let mut testbed = TestBed::new_with_entities(2, 3, vec![]);
+        testbed.sandbox.apply_settings(SimulationSettings {
+            event_frequency: EventFrequency::Never,
+            event_timer: 1,
+            event_voting: true,
+            ..SimulationSettings::default()
+        });
+        testbed.sandbox.scripted_events.push((0, EventTypes::Party));
+        let mut server = ObservationServer::bind("127.0.0.1:0").unwrap();
+        let (mut client, _) =
+            tungstenite::connect(format!("ws://{}", server.local_addr())).unwrap();
+        if let MaybeTlsStream::Plain(stream) = client.get_mut() {
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+        }
+        let mut report = testbed.sandbox.tick();
+        server.publish(&testbed.sandbox, &report);
+        client.read().unwrap();
+        let tick = client.read().unwrap().into_text().unwrap();
+        assert!(tick.contains("event party\nchoice 1 "));
+        assert!(tick.contains("\nvoting 1\n"));
+
+        client.send(Message::Text(String::from("vote 2"))).unwrap();
+        server.hold_vote(&mut testbed.sandbox);
+        let answer = client.read().unwrap().into_text().unwrap();
+        assert!(answer.starts_with("ok 127.0.0.1 "));
+        assert_eq!(client.read().unwrap().into_text().unwrap(), "votes 0 1\n");
+        testbed
+            .sandbox
+            .answer_with_policy(report.event.take().unwrap());
+        assert!(testbed.sandbox.event_log()[0].answer);
+        assert_eq!(testbed.sandbox.event_log()[0].policy, Some("votes"));
+    }
+
+    #[test]
+    fn test_one_vote_per_address() {
+        let mut testbed = TestBed::new_with_entities(2, 3, vec![]);
+        testbed.sandbox.apply_settings(SimulationSettings {
+            event_frequency: EventFrequency::Never,
+            event_timer: 1,
+            event_voting: true,
+            ..SimulationSettings::default()
+        });
+        testbed.sandbox.scripted_events.push((0, EventTypes::Party));
+        let mut server = ObservationServer::bind("127.0.0.1:0").unwrap();
+        let report = testbed.sandbox.tick();
+        server.publish(&testbed.sandbox, &report);
+
+        // connecting again doesn't get anyone another vote
+        for _ in 0..3 {
+            let (mut client, _) =
+                tungstenite::connect(format!("ws://{}", server.local_addr())).unwrap();
+            client.send(Message::Text(String::from("vote 2"))).unwrap();
+        }
+        server.hold_vote(&mut testbed.sandbox);
+        assert_eq!(testbed.sandbox.votes().unwrap().counts(), [0, 1]);
+    }
+
+    #[test]
+    fn test_slow_clients_dropped() {
+        let mut testbed = TestBed::new_with_entities(2, 3, vec![]);
+        let mut server = ObservationServer::bind("127.0.0.1:0").unwrap();
+        // nobody's ever reading what gets queued up for this one
+        let (outbox, _never_read) = mpsc::sync_channel(OUTBOX_LIMIT);
+        server.clients.push(Client { id: 0, outbox });
+        let report = testbed.sandbox.tick();
+        for _ in 0..OUTBOX_LIMIT {
+            server.publish(&testbed.sandbox, &report);
+        }
+        assert_eq!(server.clients.len(), 1);
+        server.publish(&testbed.sandbox, &report);
+        assert!(server.clients.is_empty());
+    }
+}
@@ -0,0 +1,374 @@
+// Player preferences, shared between the simulation and the GUI and kept between runs.
+
+use std::fs;
+use std::path::Path;
+
+use log::warn;
+
+use crate::locale::Language;
+use crate::voting::VOTING_WINDOW;
+
+/// Where settings are kept between runs.
+pub const SETTINGS_FILE: &str = "settings.txt";
+
+/// How often random events should happen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventFrequency {
+    Never,
+    Rare,
+    Normal,
+    Often,
+}
+
+impl EventFrequency {
+    pub const ALL: [EventFrequency; 4] = [Self::Never, Self::Rare, Self::Normal, Self::Often];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Never => "never",
+            Self::Rare => "rare",
+            Self::Normal => "normal",
+            Self::Often => "often",
+        }
+    }
+
+    /// How much more likely an event gets (out of 1000) every 10 ticks without one.
+    /// If None, events never happen on their own.
+    pub fn ramp_up(&self) -> Option<usize> {
+        match self {
+            Self::Never => None,
+            Self::Rare => Some(5),
+            Self::Normal => Some(10),
+            Self::Often => Some(25),
+        }
+    }
+}
+
+/// How hard the ocean is on the colony. Picked when a game is set up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Self::Easy, Self::Normal, Self::Hard];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+        }
+    }
+
+    /// How much harsher than normal everything is.
+    fn harshness(&self) -> f64 {
+        match self {
+            Self::Easy => 0.5,
+            Self::Normal => 1.0,
+            Self::Hard => 1.5,
+        }
+    }
+
+    /// What a roll out of 1000 (plus however much the odds have ramped up) has to reach for a random event to happen.
+    pub fn event_threshold(&self) -> usize {
+        match self {
+            Self::Easy => 998,
+            Self::Normal => 995,
+            Self::Hard => 990,
+        }
+    }
+
+    /// Scale how much more likely an event gets every 10 ticks without one.
+    pub fn scale_ramp_up(&self, ramp_up: usize) -> usize {
+        (ramp_up as f64 * self.harshness()).round() as usize
+    }
+
+    /// How much hunger something that normally loses `base` a tick should lose. Everything still gets at least a little hungry.
+    pub fn hunger_drain(&self, base: i64) -> i64 {
+        ((base as f64 * self.harshness()).round() as i64).max(1)
+    }
+
+    /// Scale the odds of an event taking something away from the colony.
+    pub fn scale_chance(&self, chance: f64) -> f64 {
+        (chance * self.harshness()).clamp(0.0, 1.0)
+    }
+
+    /// Scale what an event divides growth or mating progress by before taking it away.
+    /// A smaller divisor takes more away, but never more than half.
+    pub fn scale_slowdown(&self, factor: f64) -> f64 {
+        (factor / self.harshness()).max(2.0)
+    }
+}
+
+/// How much the game should tell the player about what's going on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only events.
+    Quiet,
+    /// Events and achievements.
+    Normal,
+    /// Everything, including how long each tick took.
+    Verbose,
+}
+
+impl Verbosity {
+    pub const ALL: [Verbosity; 3] = [Self::Quiet, Self::Normal, Self::Verbose];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Quiet => "quiet",
+            Self::Normal => "normal",
+            Self::Verbose => "verbose",
+        }
+    }
+}
+
+/// Color scheme for the GUI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorTheme {
+    Dark,
+    Light,
+    /// Pure black and white, for anyone who has trouble telling the other themes apart.
+    HighContrast,
+}
+
+impl ColorTheme {
+    pub const ALL: [ColorTheme; 3] = [Self::Dark, Self::Light, Self::HighContrast];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Dark => "dark",
+            Self::Light => "light",
+            Self::HighContrast => "high_contrast",
+        }
+    }
+}
+
+/// What to do when the game finds that its own bookkeeping has gone wrong.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strictness {
+    /// Panic on the spot. Handy for tests, where we want to know about these right away.
+    Panic,
+    /// Log what went wrong, patch things up, and keep going.
+    Repair,
+}
+
+impl Strictness {
+    pub const ALL: [Strictness; 2] = [Self::Panic, Self::Repair];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Panic => "panic",
+            Self::Repair => "repair",
+        }
+    }
+}
+
+/// Everything the player can tweak from the settings screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationSettings {
+    /// How many ticks to run per second.
+    pub tick_rate: f64,
+    pub event_frequency: EventFrequency,
+    /// Scales how often events happen, how hard they hit, and how quickly animals get hungry.
+    pub difficulty: Difficulty,
+    /// Save the game every this many ticks. If 0, the game is never saved automatically.
+    pub autosave_interval: usize,
+    pub verbosity: Verbosity,
+    pub theme: ColorTheme,
+    /// Draw entities as colored letters instead of emoji, for colorblind players.
+    pub letter_glyphs: bool,
+    /// How to handle a broken invariant between steps of the game loop.
+    pub strictness: Strictness,
+    /// What language everything the player reads is in.
+    pub language: Language,
+    /// How many seconds the player gets to answer an event before its default answer is picked for them.
+    /// If 0, they get as long as they need.
+    pub event_timer: u64,
+    /// Put events to a vote, for anyone watching the game over the network, rather than leaving them to the player.
+    /// See voting.rs.
+    pub event_voting: bool,
+    /// Where the GUI serves the game for anyone to watch over a WebSocket, like 0.0.0.0:9001 (see server.rs).
+    /// Left empty, nobody can. Only builds with the server feature can serve the game at all.
+    pub serve_address: String,
+}
+
+impl Default for SimulationSettings {
+    fn default() -> Self {
+        Self {
+            tick_rate: 3.0,
+            event_frequency: EventFrequency::Normal,
+            difficulty: Difficulty::Normal,
+            autosave_interval: 50,
+            verbosity: Verbosity::Normal,
+            theme: ColorTheme::Light,
+            letter_glyphs: false,
+            strictness: Strictness::Repair,
+            language: Language::English,
+            event_timer: 0,
+            event_voting: false,
+            serve_address: String::new(),
+        }
+    }
+}
+
+/// Find the option with the given name, for reading enums back in from the settings file.
+fn from_name<T: Copy>(all: &[T], name_of: fn(&T) -> &'static str, name: &str) -> Option<T> {
+    all.iter().find(|t| name_of(t) == name).copied()
+}
+
+impl SimulationSettings {
+    /// Load settings from a file, falling back on the defaults for anything that's missing or unreadable.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(_) => return Self::default(),
+        };
+        let (settings, ignored) = Self::parse(&contents);
+        for line in ignored {
+            warn!("Ignoring setting '{line}'");
+        }
+        settings
+    }
+
+    /// Read settings in from the contents of a settings file, along with every line that couldn't be made sense of.
+    /// Anything that's missing or unreadable is left at the default.
+    pub fn parse(contents: &str) -> (Self, Vec<String>) {
+        let mut settings = Self::default();
+        let mut ignored = vec![];
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            let Some((key, value)) = line.split_once('=') else {
+                ignored.push(line.to_owned());
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let ok = match key {
+                "tick_rate" => value.parse().map(|v| settings.tick_rate = v).is_ok(),
+                "event_frequency" => from_name(&EventFrequency::ALL, EventFrequency::name, value)
+                    .map(|v| settings.event_frequency = v)
+                    .is_some(),
+                "difficulty" => from_name(&Difficulty::ALL, Difficulty::name, value)
+                    .map(|v| settings.difficulty = v)
+                    .is_some(),
+                "autosave_interval" => value
+                    .parse()
+                    .map(|v| settings.autosave_interval = v)
+                    .is_ok(),
+                "verbosity" => from_name(&Verbosity::ALL, Verbosity::name, value)
+                    .map(|v| settings.verbosity = v)
+                    .is_some(),
+                "theme" => from_name(&ColorTheme::ALL, ColorTheme::name, value)
+                    .map(|v| settings.theme = v)
+                    .is_some(),
+                "letter_glyphs" => value.parse().map(|v| settings.letter_glyphs = v).is_ok(),
+                "strictness" => from_name(&Strictness::ALL, Strictness::name, value)
+                    .map(|v| settings.strictness = v)
+                    .is_some(),
+                "language" => from_name(&Language::ALL, Language::name, value)
+                    .map(|v| settings.language = v)
+                    .is_some(),
+                "event_timer" => value.parse().map(|v| settings.event_timer = v).is_ok(),
+                "event_voting" => value.parse().map(|v| settings.event_voting = v).is_ok(),
+                "serve_address" => {
+                    settings.serve_address = value.to_owned();
+                    true
+                }
+                _ => false,
+            };
+            if !ok {
+                ignored.push(line.to_owned());
+            }
+        }
+        (settings, ignored)
+    }
+
+    /// How many seconds events are left waiting for an answer before one's picked for the player, or None if they can
+    /// take as long as they like. Votes always close eventually.
+    pub fn event_window(&self) -> Option<u64> {
+        match (self.event_timer, self.event_voting) {
+            (0, false) => None,
+            (0, true) => Some(VOTING_WINDOW),
+            (timer, _) => Some(timer),
+        }
+    }
+
+    /// Write these settings out to a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let contents = format!(
+            "tick_rate={}\nevent_frequency={}\ndifficulty={}\nautosave_interval={}\nverbosity={}\ntheme={}\nletter_glyphs={}\nstrictness={}\nlanguage={}\nevent_timer={}\nevent_voting={}\nserve_address={}\n",
+            self.tick_rate,
+            self.event_frequency.name(),
+            self.difficulty.name(),
+            self.autosave_interval,
+            self.verbosity.name(),
+            self.theme.name(),
+            self.letter_glyphs,
+            self.strictness.name(),
+            self.language.name(),
+            self.event_timer,
+            self.event_voting,
+            self.serve_address
+        );
+        fs::write(path, contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_round_trip() {
+        let path = std::env::temp_dir().join("deep_sea_sim_test_settings.txt");
+        let settings = SimulationSettings {
+            tick_rate: 7.5,
+            event_frequency: EventFrequency::Never,
+            difficulty: Difficulty::Hard,
+            autosave_interval: 0,
+            verbosity: Verbosity::Verbose,
+            theme: ColorTheme::Dark,
+            letter_glyphs: true,
+            strictness: Strictness::Panic,
+            language: Language::Spanish,
+            event_timer: 20,
+            event_voting: true,
+            serve_address: String::from("127.0.0.1:9001"),
+        };
+        settings.save(&path).unwrap();
+        assert_eq!(SimulationSettings::load(&path), settings);
+
+        // anything we can't read should just fall back on the default
+        fs::write(&path, "tick_rate=fast\ntheme=dark\nnonsense").unwrap();
+        let loaded = SimulationSettings::load(&path);
+        assert_eq!(loaded.tick_rate, SimulationSettings::default().tick_rate);
+        assert_eq!(loaded.theme, ColorTheme::Dark);
+        let (_, ignored) = SimulationSettings::parse("tick_rate=fast\ntheme=dark\nnonsense");
+        assert_eq!(ignored, vec!["tick_rate=fast", "nonsense"]);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_difficulty_scaling() {
+        let (easy, normal, hard) = (Difficulty::Easy, Difficulty::Normal, Difficulty::Hard);
+        // normal is how things always used to be
+        assert_eq!(normal.event_threshold(), 995);
+        assert_eq!(normal.scale_ramp_up(10), 10);
+        assert_eq!(normal.hunger_drain(3), 3);
+        assert_eq!(normal.scale_slowdown(5.0), 5.0);
+
+        assert!(easy.event_threshold() > normal.event_threshold());
+        assert!(hard.event_threshold() < normal.event_threshold());
+        assert!(easy.scale_ramp_up(10) < hard.scale_ramp_up(10));
+        for base in 1..=3 {
+            assert!(easy.hunger_drain(base) >= 1);
+            assert!(easy.hunger_drain(base) <= base && base < hard.hunger_drain(base));
+        }
+        assert!(easy.scale_chance(0.25) < hard.scale_chance(0.25));
+        assert_eq!(hard.scale_chance(2.0 / 3.0), 1.0);
+        assert!(easy.scale_slowdown(3.0) > hard.scale_slowdown(3.0));
+        assert_eq!(hard.scale_slowdown(3.0), 2.0);
+    }
+}
@@ -0,0 +1,361 @@
+// The rules for what a fresh game can start out with, along with a few ready-made starting populations.
+// Everything that sets up a game, from the setup screen to the command line, goes through the same checks here, so a
+// board that gets past them is one that can actually be filled.
+
+use std::fmt::Display;
+
+use rand::Rng;
+
+use crate::entities::Species;
+use crate::game_board::{Topology, PLANT_PERCENTAGE};
+use crate::locale::{tr_name, tr_with};
+
+/// The longest a side of the board can be.
+pub const MAX_DIMENSION: usize = 1000;
+
+/// The animals a game can be set up with, in the order they're usually asked for.
+pub const STARTING_SPECIES: [Species; 3] = [Species::Fish, Species::Crab, Species::Shark];
+
+/// Everything needed to set up a fresh, randomly filled board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetupConfig {
+    pub rows: usize,
+    pub cols: usize,
+    pub fish: usize,
+    pub crab: usize,
+    pub shark: usize,
+    /// The odds of an empty tile starting out with kelp on it.
+    pub kelp_chance: f64,
+    pub topology: Topology,
+}
+
+/// Why a setup can't be used.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SetupError {
+    /// Something typed in for a size or a population wasn't a whole number.
+    NotANumber(String),
+    /// Both sides of the board need to be somewhere between 1 and MAX_DIMENSION.
+    BadDimensions { rows: usize, cols: usize },
+    /// Asked for more of an animal than the board has room for.
+    TooMany {
+        species: Species,
+        count: usize,
+        limit: usize,
+    },
+    /// The kelp chance has to be somewhere from 0 to 1.
+    BadKelpChance(f64),
+    /// Asked for more animals all together than there are tiles to put them on.
+    Overcrowded { creatures: usize, tiles: usize },
+}
+
+impl Display for SetupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotANumber(text) => write!(f, "'{text}' isn't a whole number"),
+            Self::BadDimensions { rows, cols } => write!(
+                f,
+                "A {rows}x{cols} board won't work, both sides need to be between 1 and {MAX_DIMENSION}"
+            ),
+            Self::TooMany {
+                species,
+                count,
+                limit,
+            } => write!(
+                f,
+                "{count} is too many {}, the limit for this board is {limit}",
+                species.name()
+            ),
+            Self::BadKelpChance(chance) => {
+                write!(f, "A kelp chance of {chance} doesn't make sense")
+            }
+            Self::Overcrowded { creatures, tiles } => write!(
+                f,
+                "{creatures} animals won't fit on a board with only {tiles} tiles"
+            ),
+        }
+    }
+}
+
+impl SetupError {
+    /// What went wrong, in the language being played in.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::NotANumber(text) => tr_with("setup.error.not_a_number", &[("text", text)]),
+            Self::BadDimensions { rows, cols } => tr_with(
+                "setup.error.bad_dimensions",
+                &[("rows", rows), ("cols", cols), ("max", &MAX_DIMENSION)],
+            ),
+            Self::TooMany {
+                species,
+                count,
+                limit,
+            } => tr_with(
+                "setup.error.too_many",
+                &[
+                    ("count", count),
+                    ("species", &tr_name("species", species.name())),
+                    ("limit", limit),
+                ],
+            ),
+            Self::BadKelpChance(chance) => {
+                tr_with("setup.error.bad_kelp_chance", &[("chance", chance)])
+            }
+            Self::Overcrowded { creatures, tiles } => tr_with(
+                "setup.error.overcrowded",
+                &[("creatures", creatures), ("tiles", tiles)],
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SetupError {}
+
+/// Read in a size or a population the way it was typed in.
+pub fn parse_count(text: &str) -> Result<usize, SetupError> {
+    let text = text.trim();
+    text.parse()
+        .map_err(|_| SetupError::NotANumber(text.to_string()))
+}
+
+impl SetupConfig {
+    /// An empty board of the given size, with the usual amount of kelp.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            fish: 0,
+            crab: 0,
+            shark: 0,
+            kelp_chance: PLANT_PERCENTAGE,
+            topology: Topology::Square,
+        }
+    }
+
+    /// A board of the given size with some starting populations, as long as it passes validation.
+    /// Anything that isn't a starting species can't be asked for at all.
+    pub fn try_new(
+        rows: usize,
+        cols: usize,
+        populations: &[(Species, usize)],
+    ) -> Result<Self, SetupError> {
+        let mut setup = Self::new(rows, cols);
+        for (species, count) in populations {
+            match species {
+                Species::Fish => setup.fish = *count,
+                Species::Crab => setup.crab = *count,
+                Species::Shark => setup.shark = *count,
+                _ => {
+                    return Err(SetupError::TooMany {
+                        species: *species,
+                        count: *count,
+                        limit: 0,
+                    })
+                }
+            }
+        }
+        setup.validate()?;
+        Ok(setup)
+    }
+
+    /// The most of an animal this board can start out with. Anything that isn't a starting species can't be asked for at all.
+    pub fn limit(&self, species: Species) -> usize {
+        let size = self.rows * self.cols;
+        match species {
+            Species::Fish => size / 5,
+            Species::Crab => size / 7,
+            Species::Shark => size / 10,
+            _ => 0,
+        }
+    }
+
+    /// How many of an animal we're starting out with.
+    pub fn count(&self, species: Species) -> usize {
+        match species {
+            Species::Fish => self.fish,
+            Species::Crab => self.crab,
+            Species::Shark => self.shark,
+            _ => 0,
+        }
+    }
+
+    /// Make sure the board can actually be set up like this.
+    pub fn validate(&self) -> Result<(), SetupError> {
+        let fits = 1..=MAX_DIMENSION;
+        if !fits.contains(&self.rows) || !fits.contains(&self.cols) {
+            return Err(SetupError::BadDimensions {
+                rows: self.rows,
+                cols: self.cols,
+            });
+        }
+        for species in STARTING_SPECIES {
+            let (count, limit) = (self.count(species), self.limit(species));
+            if count > limit {
+                return Err(SetupError::TooMany {
+                    species,
+                    count,
+                    limit,
+                });
+            }
+        }
+        if !(0.0..=1.0).contains(&self.kelp_chance) {
+            return Err(SetupError::BadKelpChance(self.kelp_chance));
+        }
+        Ok(())
+    }
+}
+
+/// A ready-made starting population, scaled to fit whatever size the board is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Balanced,
+    /// Plenty of sharks, and not much for them to eat.
+    PredatorHeavy,
+    /// Lots of kelp, and nothing to hunt the fish.
+    KelpGarden,
+    /// A handful of fish, a lot of sharks and hardly any kelp.
+    Hardcore,
+    /// Something different every time, but always with a fighting chance.
+    RandomViable,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 5] = [
+        Self::Balanced,
+        Self::PredatorHeavy,
+        Self::KelpGarden,
+        Self::Hardcore,
+        Self::RandomViable,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Balanced => "Balanced",
+            Self::PredatorHeavy => "Predator Heavy",
+            Self::KelpGarden => "Kelp Garden",
+            Self::Hardcore => "Hardcore",
+            Self::RandomViable => "Random",
+        }
+    }
+
+    /// Fill in the populations for a setup, leaving the board's size and shape alone.
+    pub fn apply(&self, setup: &mut SetupConfig) {
+        self.apply_with_rng(setup, &mut rand::thread_rng())
+    }
+
+    /// Same as apply, but using a given source of randomness for the random preset.
+    pub fn apply_with_rng(&self, setup: &mut SetupConfig, rng: &mut impl Rng) {
+        let fish = setup.limit(Species::Fish);
+        let crab = setup.limit(Species::Crab);
+        let shark = setup.limit(Species::Shark);
+        // two fish is the least that can keep a colony going, if the board has room for them
+        let breeding_pair = 2.min(fish);
+        (setup.fish, setup.crab, setup.shark, setup.kelp_chance) = match self {
+            Self::Balanced => (fish / 2, crab / 2, shark / 4, PLANT_PERCENTAGE),
+            Self::PredatorHeavy => (fish / 2, crab / 4, shark, PLANT_PERCENTAGE),
+            Self::KelpGarden => (fish / 2, crab / 2, 0, 0.4),
+            Self::Hardcore => (fish / 6, 0, shark / 2, 0.03),
+            Self::RandomViable => {
+                let fish = rng.gen_range((fish / 3).max(breeding_pair)..=fish);
+                // keep the sharks outnumbered, so they don't eat everything in the first few ticks
+                let shark = rng.gen_range(0..=(fish / 4).min(shark));
+                (
+                    fish,
+                    rng.gen_range(0..=crab),
+                    shark,
+                    rng.gen_range(0.1..0.3),
+                )
+            }
+        };
+        setup.fish = setup.fish.max(breeding_pair);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_validate() {
+        let mut setup = SetupConfig::new(10, 10);
+        assert_eq!(setup.validate(), Ok(()));
+
+        setup.shark = 11;
+        assert_eq!(
+            setup.validate(),
+            Err(SetupError::TooMany {
+                species: Species::Shark,
+                count: 11,
+                limit: 10
+            })
+        );
+        setup.shark = 10;
+        setup.fish = 20;
+        setup.crab = 14;
+        assert_eq!(setup.validate(), Ok(()));
+
+        setup.kelp_chance = 1.5;
+        assert!(setup.validate().is_err());
+        setup.kelp_chance = 0.0;
+
+        for (rows, cols) in [(0, 10), (10, 0), (MAX_DIMENSION + 1, 10)] {
+            setup.rows = rows;
+            setup.cols = cols;
+            assert_eq!(
+                setup.validate(),
+                Err(SetupError::BadDimensions { rows, cols })
+            );
+        }
+    }
+
+    #[test]
+    fn test_try_new() {
+        let setup =
+            SetupConfig::try_new(10, 10, &[(Species::Fish, 5), (Species::Shark, 2)]).unwrap();
+        assert_eq!((setup.fish, setup.crab, setup.shark), (5, 0, 2));
+        assert_eq!(
+            SetupConfig::try_new(0, 10, &[]),
+            Err(SetupError::BadDimensions { rows: 0, cols: 10 })
+        );
+        assert_eq!(
+            SetupConfig::try_new(10, 10, &[(Species::Kelp, 1)]),
+            Err(SetupError::TooMany {
+                species: Species::Kelp,
+                count: 1,
+                limit: 0
+            })
+        );
+
+        assert_eq!(parse_count(" 12 "), Ok(12));
+        for text in ["-3", "2.5", "lots", ""] {
+            assert_eq!(
+                parse_count(text),
+                Err(SetupError::NotANumber(text.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_presets_are_valid() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for (rows, cols) in [(1, 1), (3, 4), (10, 10), (40, 25)] {
+            for preset in Preset::ALL {
+                // the random one gets a few goes, since it could land anywhere
+                for _ in 0..20 {
+                    let mut setup = SetupConfig::new(rows, cols);
+                    preset.apply_with_rng(&mut setup, &mut rng);
+                    assert_eq!(
+                        setup.validate(),
+                        Ok(()),
+                        "{} on {rows}x{cols}",
+                        preset.name()
+                    );
+                    assert_eq!((setup.rows, setup.cols), (rows, cols));
+                    if setup.limit(Species::Fish) >= 2 {
+                        assert!(setup.fish >= 2, "{} can't breed", preset.name());
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+// A picture of what's on every tile, for the GUI to draw from. Rendering the board itself needs the sandbox to stop and
+// hand it over, so instead the sandbox keeps its own copy of every tile's glyph, patches in whatever changed since the
+// last one, and publishes it behind an Arc. Anyone holding onto a snapshot can read it for as long as they like, and the
+// sandbox only copies it again when it needs to change something while the old one's still being looked at.
+
+use std::sync::{Arc, Mutex};
+
+use crate::game_board::{Board, Pos};
+use crate::Sandbox;
+
+/// What every tile on the loaded region's board looked like as of a tick.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BoardSnapshot {
+    tick: usize,
+    rows: usize,
+    cols: usize,
+    /// Every tile's glyph, row by row.
+    glyphs: Vec<char>,
+}
+
+impl BoardSnapshot {
+    fn take(board: &Board, tick: usize) -> Self {
+        let (cols, rows) = board.dims();
+        Self {
+            tick,
+            rows,
+            cols,
+            glyphs: board.glyphs().collect(),
+        }
+    }
+
+    /// The tick this was taken on.
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    /// How many columns and rows there are, the same way round as Board::dims.
+    pub fn dims(&self) -> (usize, usize) {
+        (self.cols, self.rows)
+    }
+
+    /// What's on the tile at a position, or None if it's off the board.
+    pub fn glyph(&self, pos: Pos) -> Option<char> {
+        if pos.x >= self.cols {
+            return None;
+        }
+        self.glyphs.get(pos.y * self.cols + pos.x).copied()
+    }
+
+    /// Every row of glyphs, from the top down.
+    pub fn rows(&self) -> impl Iterator<Item = &[char]> {
+        self.glyphs.chunks(self.cols.max(1))
+    }
+
+    /// Bring this up to date with a board. Only the glyphs that changed get touched, unless the board's a different
+    /// size altogether.
+    fn patch(&mut self, board: &Board, tick: usize) {
+        self.tick = tick;
+        if board.dims() != self.dims() {
+            *self = Self::take(board, tick);
+            return;
+        }
+        for (old, new) in self.glyphs.iter_mut().zip(board.glyphs()) {
+            if *old != new {
+                *old = new;
+            }
+        }
+    }
+}
+
+/// Where the sandbox puts every new snapshot, for anyone on any thread to pick up whenever suits them.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotHandle {
+    latest: Arc<Mutex<Arc<BoardSnapshot>>>,
+}
+
+impl SnapshotHandle {
+    /// The latest snapshot. The lock's only held long enough to grab another reference to it, so neither side is ever
+    /// kept waiting on the other.
+    pub fn latest(&self) -> Arc<BoardSnapshot> {
+        Arc::clone(&self.latest.lock().unwrap())
+    }
+
+    fn publish(&self, snapshot: Arc<BoardSnapshot>) {
+        *self.latest.lock().unwrap() = snapshot;
+    }
+}
+
+impl Sandbox {
+    /// Somewhere to pick up the latest snapshot of the board from, which can be held onto and read from any thread.
+    pub fn snapshots(&self) -> SnapshotHandle {
+        self.snapshots.clone()
+    }
+
+    /// Patch whatever changed on the board since the last snapshot into a new one, and publish it.
+    pub fn refresh_snapshot(&mut self) -> Arc<BoardSnapshot> {
+        let unchanged = self.snapshot.tick() == self.clock
+            && self.snapshot.dims() == self.board.dims()
+            && self.board.glyphs().eq(self.snapshot.glyphs.iter().copied());
+        if !unchanged {
+            // only copied if someone's still looking at the last one
+            Arc::make_mut(&mut self.snapshot).patch(&self.board, self.clock);
+            self.snapshots.publish(Arc::clone(&self.snapshot));
+        }
+        Arc::clone(&self.snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::entities::animals::ConcreteAnimals;
+    use crate::entities::{NonAbstractTaxonomy, Species};
+    use crate::game_board::EMPTY_GLYPH;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_snapshots_are_patched() {
+        let fish = Pos { x: 1, y: 0 };
+        let mut testbed = TestBed::new_populated(2, 3, vec![(fish, ConcreteAnimals::Fish)]);
+        let first = testbed.sandbox.refresh_snapshot();
+        assert_eq!(first.dims(), (3, 2));
+        assert_eq!(first.rows().count(), 2);
+        assert_eq!(first.glyph(fish), Some(Species::Fish.display_spec().emoji));
+        assert_eq!(first.glyph(Pos { x: 3, y: 0 }), None);
+        // nothing changed, so there's nothing new to hand out
+        assert!(Arc::ptr_eq(&first, &testbed.sandbox.refresh_snapshot()));
+
+        let rock = Pos { x: 0, y: 1 };
+        testbed
+            .sandbox
+            .board
+            .place_entity(rock, Species::Rock.create_new(None))
+            .unwrap();
+        let second = testbed.sandbox.refresh_snapshot();
+        // the first one's still being held onto, so it's left the way it was
+        assert_eq!(first.glyph(rock), Some(EMPTY_GLYPH));
+        assert_ne!(second.glyph(rock), Some(EMPTY_GLYPH));
+        assert_eq!(second.glyph(fish), first.glyph(fish));
+        assert!(Arc::ptr_eq(&second, &testbed.sandbox.snapshots().latest()));
+    }
+
+    #[test]
+    fn test_snapshots_read_from_another_thread() {
+        let mut testbed = TestBed::new_default(8, 8, 4, 2, 1);
+        let snapshots = testbed.sandbox.snapshots();
+        testbed.sandbox.refresh_snapshot();
+        thread::scope(|s| {
+            s.spawn(|| {
+                for _ in 0..10 {
+                    testbed.sandbox.tick();
+                    testbed.sandbox.refresh_snapshot();
+                }
+            });
+            s.spawn(|| {
+                // every snapshot is whole, however far along the sandbox is
+                for _ in 0..100 {
+                    let snapshot = snapshots.latest();
+                    assert_eq!(snapshot.rows().count(), 8);
+                    assert!(snapshot.rows().all(|row| row.len() == 8));
+                }
+            });
+        });
+        assert_eq!(snapshots.latest().tick(), 10);
+        assert_eq!(
+            *snapshots.latest(),
+            BoardSnapshot::take(&testbed.sandbox.board, 10)
+        );
+    }
+}
@@ -0,0 +1,148 @@
+// Following one animal around closely, to get a look at what it's thinking. Once an animal is put in the spotlight, every
+// update tells the GUI where it is, the way it's headed and how far around itself it keeps a lookout, so the camera can
+// stay on it and draw all of that over the board. Whenever it changes its mind about what to do, that goes in the console.
+// Only animals in the loaded region can be followed; anything that dies or wanders off into another region is dropped.
+
+use log::info;
+
+use crate::ai_controller::{AIAction, AIControlled};
+use crate::entities::animals::{Animals, LOOKOUT_RADIUS};
+use crate::entities::PTUIDisplay;
+use crate::entity_control::EntityID;
+use crate::error::Error;
+use crate::game_board::Pos;
+use crate::Sandbox;
+
+/// Everything needed to follow an animal around the board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spotlight {
+    pub id: EntityID,
+    pub pos: Pos,
+    /// The way it's headed, starting from where it is now. Empty if it isn't after anything in particular.
+    pub path: Vec<Pos>,
+    /// How far around itself it watches out for anything that might eat it.
+    pub perception: usize,
+}
+
+impl Sandbox {
+    /// The animal being followed and where it is, if it's still on the board.
+    fn spotlit(&self) -> Option<(Pos, &Animals)> {
+        let (id, _) = self.spotlight.as_ref()?;
+        let pos = *self
+            .entity_context
+            .read()
+            .unwrap()
+            .get_active_entries()
+            .get(id)?;
+        let animal = self.board.entity_at(pos)?.animal()?;
+        Some((pos, animal))
+    }
+
+    /// Start following an animal, or stop following whichever one was if given None.
+    pub fn set_spotlight(&mut self, id: Option<EntityID>) -> Result<(), Error> {
+        let Some(id) = id else {
+            self.spotlight = None;
+            return Ok(());
+        };
+        self.spotlight = Some((id, String::new()));
+        let Some((_, animal)) = self.spotlit() else {
+            self.spotlight = None;
+            return Err(Error::StaleEntityId(id));
+        };
+        let doing = animal.get_current_behavior().get_action_desc();
+        self.spotlight = Some((id, doing));
+        Ok(())
+    }
+
+    /// Where the animal being followed is and what it's up to, if anything's being followed.
+    pub fn get_spotlight(&self) -> Option<Spotlight> {
+        let (id, _) = self.spotlight.as_ref()?;
+        let (pos, animal) = self.spotlit()?;
+        Some(Spotlight {
+            id: *id,
+            pos,
+            path: animal
+                .get_current_behavior()
+                .plan(animal, pos, &self.board, &self.rules)
+                .path,
+            perception: LOOKOUT_RADIUS,
+        })
+    }
+
+    /// Catch up with the animal being followed, writing down anything it's decided to do differently since last tick.
+    pub fn handle_spotlight(&mut self) {
+        let Some((id, was_doing)) = self.spotlight.clone() else {
+            return;
+        };
+        let msg = match self.spotlit() {
+            None => {
+                self.spotlight = None;
+                format!(
+                    "#{} is gone, so it's not being followed any more",
+                    id.get_id_val()
+                )
+            }
+            Some((pos, animal)) => {
+                let doing = animal.get_current_behavior().get_action_desc();
+                if doing == was_doing {
+                    return;
+                }
+                let msg = format!(
+                    "#{} ({}) at {pos:?} went from {was_doing} to {doing}",
+                    id.get_id_val(),
+                    animal.get_display_char()
+                );
+                self.spotlight = Some((id, doing));
+                msg
+            }
+        };
+        info!(target: &self.log_target, "{msg}");
+        self.console_log.push(msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_controller::{AIConcreteBehaviors, EatAction};
+    use crate::entities::Species;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_spotlight() {
+        let (shark, fish) = (Pos { x: 0, y: 0 }, Pos { x: 4, y: 0 });
+        let mut testbed = TestBed::builder(1, 5)
+            .with_stats(shark, Species::Shark, &[("hunger", "20")])
+            .with(fish, Species::Fish)
+            .build();
+        let id = testbed.id_at(shark);
+        assert!(testbed.sandbox.get_spotlight().is_none());
+        testbed.sandbox.set_spotlight(Some(id)).unwrap();
+
+        // the shark's idle until it's had a chance to notice it's hungry
+        let spotlight = testbed.sandbox.get_spotlight().unwrap();
+        assert_eq!((spotlight.id, spotlight.pos), (id, shark));
+        assert!(spotlight.path.is_empty());
+        assert_eq!(spotlight.perception, LOOKOUT_RADIUS);
+
+        testbed
+            .animal_at_mut(shark)
+            .set_current_behavior(AIConcreteBehaviors::Eating(EatAction::new()));
+        let mut sandbox = testbed.sandbox;
+        // the way to the fish goes right along the row, stopping next to it
+        let spotlight = sandbox.get_spotlight().unwrap();
+        assert_eq!(spotlight.path.first(), Some(&shark));
+        assert_eq!(spotlight.path.last(), Some(&Pos { x: 3, y: 0 }));
+
+        sandbox.handle_spotlight();
+        assert_eq!(sandbox.console_log.len(), 1);
+        assert!(sandbox.console_log[0].contains("from idle to eating"));
+        sandbox.handle_spotlight();
+        assert_eq!(sandbox.console_log.len(), 1);
+
+        sandbox.board.remove_entity(shark);
+        sandbox.handle_spotlight();
+        assert!(sandbox.get_spotlight().is_none());
+        assert!(sandbox.set_spotlight(Some(id)).is_err());
+    }
+}
@@ -0,0 +1,364 @@
+// Averages of the traits animals carry, broken down by species and by generation, so it's possible to watch a population
+// change as it breeds. For now every animal of a species is born the same, since children take after their species rather
+// than their parents, so these stay flat. Once animals start inheriting their traits, whatever the sharks are selecting for
+// will show up here without anything else changing. Generations that have died out keep the last averages they were seen
+// with. There's also a snapshot of how every animal on the board is doing right now, for the GUI to draw bars from.
+
+use std::cmp::Ordering;
+
+use crate::ai_controller::{AIAction, AIControlled};
+use crate::element_traits::Lives;
+use crate::entities::animals::{Animals, HungerLevel};
+use crate::entities::components::Personality;
+use crate::entities::{Entity, Living, PTUIDisplay, Species};
+use crate::entity_control::{EntityID, TrackedEntity};
+use crate::game_board::{Board, Pos};
+
+/// Something about an animal that can be averaged out over a generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trait {
+    /// How far it can move in a tick, averaged between the two directions.
+    Speed,
+    Attack,
+    Defense,
+    /// Its max hp.
+    Toughness,
+}
+
+impl Trait {
+    pub const ALL: [Trait; 4] = [
+        Trait::Speed,
+        Trait::Attack,
+        Trait::Defense,
+        Trait::Toughness,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Speed => "speed",
+            Self::Attack => "attack",
+            Self::Defense => "defense",
+            Self::Toughness => "toughness",
+        }
+    }
+
+    /// How much of this trait an animal has.
+    pub fn of(&self, animal: &Animals) -> f64 {
+        let body = animal.body();
+        match self {
+            Self::Speed => (body.mobility.max_x + body.mobility.max_y) as f64 / 2.0,
+            Self::Attack => body.combat.attack as f64,
+            Self::Defense => body.combat.defense as f64,
+            Self::Toughness => body.health.hp_max as f64,
+        }
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|t| t == self).unwrap()
+    }
+}
+
+/// The average of every trait over one generation of a species.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerationAverages {
+    pub species: Species,
+    pub generation: usize,
+    /// How many animals went into the averages.
+    pub count: usize,
+    /// The average of each trait, in the same order as Trait::ALL.
+    means: [f64; Trait::ALL.len()],
+}
+
+impl GenerationAverages {
+    pub fn mean(&self, of: Trait) -> f64 {
+        self.means[of.index()]
+    }
+}
+
+/// Average out the traits of every living animal on the board, by species and then by generation.
+pub fn trait_averages(board: &Board) -> Vec<GenerationAverages> {
+    let mut averages: Vec<GenerationAverages> = vec![];
+    for (_, entity) in board.iter_occupied() {
+        let Entity::Living(Living::Animals(animal)) = entity else {
+            continue;
+        };
+        if animal.is_dead() {
+            continue;
+        }
+        let (species, generation) = (Species::of(entity), animal.get_generation());
+        let i = match averages
+            .iter()
+            .position(|a| a.species == species && a.generation == generation)
+        {
+            Some(i) => i,
+            None => {
+                averages.push(GenerationAverages {
+                    species,
+                    generation,
+                    count: 0,
+                    means: [0.0; Trait::ALL.len()],
+                });
+                averages.len() - 1
+            }
+        };
+        // keep a running mean, so nothing needs adding up afterwards
+        let entry = &mut averages[i];
+        entry.count += 1;
+        for of in Trait::ALL {
+            let mean = &mut entry.means[of.index()];
+            *mean += (of.of(animal) - *mean) / entry.count as f64;
+        }
+    }
+    averages.sort_by(by_species_and_generation);
+    averages
+}
+
+fn by_species_and_generation(a: &GenerationAverages, b: &GenerationAverages) -> Ordering {
+    (a.species.name(), a.generation).cmp(&(b.species.name(), b.generation))
+}
+
+/// Every generation of every species seen so far, with the averages it had when it was last seen alive.
+#[derive(Debug, Default)]
+pub struct TraitHistory {
+    generations: Vec<GenerationAverages>,
+}
+
+impl TraitHistory {
+    /// Catch up with the animals on the board right now.
+    pub fn record(&mut self, board: &Board) {
+        for averages in trait_averages(board) {
+            match self
+                .generations
+                .iter_mut()
+                .find(|a| a.species == averages.species && a.generation == averages.generation)
+            {
+                Some(seen) => *seen = averages,
+                None => self.generations.push(averages),
+            }
+        }
+        self.generations.sort_by(by_species_and_generation);
+    }
+
+    /// Every generation recorded so far, ordered by species name and then by generation.
+    pub fn get_generations(&self) -> &[GenerationAverages] {
+        &self.generations
+    }
+
+    /// How a trait has gone for a species, as (generation, average) pairs in order.
+    pub fn series(&self, species: Species, of: Trait) -> Vec<(usize, f64)> {
+        self.generations
+            .iter()
+            .filter(|a| a.species == species)
+            .map(|a| (a.generation, a.mean(of)))
+            .collect()
+    }
+}
+
+/// How one animal on the board is doing, boiled down so it can be drawn as bars and icons.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityStats {
+    pub id: EntityID,
+    pub species: Species,
+    pub glyph: char,
+    pub pos: Pos,
+    /// How much of its hp it has left, from 0 to 1.
+    pub health: f64,
+    pub hunger: HungerLevel,
+    /// How far through its life it is, from 0 to 1, or None if it'll never die of old age.
+    pub age: Option<f64>,
+    /// What it's busy doing, like "idle" or "courting".
+    pub behavior: String,
+    pub pregnant: bool,
+    /// What sets it apart from the rest of its kind.
+    pub personality: Personality,
+}
+
+impl EntityStats {
+    pub fn of(animal: &Animals, pos: Pos) -> Option<Self> {
+        let body = animal.body();
+        Some(Self {
+            id: animal.get_id()?,
+            species: animal.species(),
+            glyph: animal.get_display_char(),
+            pos,
+            health: body.health.hp as f64 / body.health.hp_max.max(1) as f64,
+            hunger: body.hunger.state,
+            age: body
+                .age
+                .max
+                .map(|max| (body.age.ticks as f64 / max.max(1) as f64).min(1.0)),
+            behavior: animal.get_current_behavior().get_action_desc(),
+            pregnant: animal.is_pregnant(),
+            personality: animal.body().personality,
+        })
+    }
+}
+
+impl EntityStats {
+    /// Whether the animal goes by something containing `search`, either its species name or its ID. An empty search
+    /// matches everything.
+    pub fn matches(&self, search: &str) -> bool {
+        let search = search.trim().to_lowercase();
+        search.is_empty()
+            || self.species.name().contains(&search)
+            || self.id.get_id_val().to_string().contains(&search)
+    }
+}
+
+/// Ways the list of animals can be put in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsOrder {
+    /// Oldest first, the order they come in.
+    Id,
+    /// Worst off first.
+    Health,
+    /// Closest to dying of old age first.
+    Age,
+    /// Hungriest first.
+    Hunger,
+}
+
+impl StatsOrder {
+    pub const ALL: [StatsOrder; 4] = [Self::Id, Self::Health, Self::Age, Self::Hunger];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::Health => "health",
+            Self::Age => "age",
+            Self::Hunger => "hunger",
+        }
+    }
+
+    /// Put the animals in this order. Anything that ties stays oldest first.
+    pub fn sort(&self, stats: &mut [EntityStats]) {
+        let hunger = |s: &EntityStats| HungerLevel::ALL.iter().position(|h| *h == s.hunger);
+        stats.sort_by(|a, b| {
+            let by = match self {
+                Self::Id => Ordering::Equal,
+                Self::Health => a.health.total_cmp(&b.health),
+                // anything that never gets old goes last
+                Self::Age => b.age.unwrap_or(-1.0).total_cmp(&a.age.unwrap_or(-1.0)),
+                Self::Hunger => hunger(b).cmp(&hunger(a)),
+            };
+            by.then(a.id.cmp(&b.id))
+        });
+    }
+}
+
+/// How every animal on the board is doing, oldest first.
+pub fn entity_stats(board: &Board) -> Vec<EntityStats> {
+    let mut stats: Vec<EntityStats> = board
+        .iter_occupied()
+        .filter_map(|(pos, e)| EntityStats::of(e.animal()?, pos))
+        .collect();
+    stats.sort_unstable_by_key(|s| s.id);
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::NonAbstractTaxonomy;
+    use crate::game_board::Pos;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_averages_by_generation() {
+        let mut child = Species::Fish.create_new(None);
+        child.reproduction_mut().unwrap().generation = 2;
+        child.mobility_mut().unwrap().max_x = 4;
+        let mut testbed = TestBed::new_with_entities(
+            2,
+            2,
+            vec![
+                (Pos { x: 0, y: 0 }, Species::Fish.create_new(None)),
+                (Pos { x: 1, y: 0 }, Species::Fish.create_new(None)),
+                (Pos { x: 0, y: 1 }, child),
+                (Pos { x: 1, y: 1 }, Species::Shark.create_new(None)),
+            ],
+        );
+        let mut history = TraitHistory::default();
+        history.record(&testbed.sandbox.board);
+
+        let fish = history.series(Species::Fish, Trait::Speed);
+        assert_eq!(fish.len(), 2);
+        assert_eq!(fish[0].0, 1);
+        assert_eq!(fish[1], (2, (4 + 1) as f64 / 2.0));
+        assert_eq!(history.get_generations()[0].count, 2);
+        assert_eq!(history.series(Species::Shark, Trait::Attack).len(), 1);
+
+        // the second generation dying out doesn't wipe its history
+        testbed.sandbox.board.remove_entity(Pos { x: 0, y: 1 });
+        history.record(&testbed.sandbox.board);
+        assert_eq!(history.series(Species::Fish, Trait::Speed), fish);
+    }
+
+    #[test]
+    fn test_entity_stats() {
+        let mut shark = Species::Shark.create_new(None);
+        shark.set_stat("hp", "50").unwrap();
+        shark.set_stat("hunger", "20").unwrap();
+        shark.set_stat("pregnant", "true").unwrap();
+        let testbed = TestBed::new_with_entities(
+            2,
+            2,
+            vec![
+                (Pos { x: 0, y: 0 }, Species::Fish.create_new(None)),
+                (Pos { x: 1, y: 1 }, shark),
+                (Pos { x: 1, y: 0 }, Species::Kelp.create_new(None)),
+            ],
+        );
+        let stats = entity_stats(&testbed.sandbox.board);
+        assert_eq!(stats.len(), 2);
+        let (fish, shark) = (&stats[0], &stats[1]);
+        assert_eq!(fish.species, Species::Fish);
+        assert_eq!(fish.health, 1.0);
+        assert_eq!(fish.hunger, HungerLevel::Full);
+        assert_eq!(fish.behavior, "idle");
+        assert!(!fish.pregnant);
+        assert_eq!(shark.pos, Pos { x: 1, y: 1 });
+        assert!(shark.health < 1.0);
+        assert_eq!(shark.hunger, HungerLevel::Hungry);
+        assert_eq!(shark.age, Some(0.0));
+        assert!(shark.pregnant);
+    }
+
+    #[test]
+    fn test_sorting_and_searching_stats() {
+        let mut shark = Species::Shark.create_new(None);
+        shark.set_stat("hp", "50").unwrap();
+        let mut crab = Species::Crab.create_new(None);
+        crab.set_stat("hunger", "-30").unwrap();
+        let testbed = TestBed::new_with_entities(
+            2,
+            2,
+            vec![
+                (Pos { x: 0, y: 0 }, Species::Fish.create_new(None)),
+                (Pos { x: 1, y: 0 }, shark),
+                (Pos { x: 0, y: 1 }, crab),
+            ],
+        );
+        let mut stats = entity_stats(&testbed.sandbox.board);
+        let species = |stats: &[EntityStats]| stats.iter().map(|s| s.species).collect::<Vec<_>>();
+
+        StatsOrder::Health.sort(&mut stats);
+        assert_eq!(stats[0].species, Species::Shark);
+        StatsOrder::Hunger.sort(&mut stats);
+        assert_eq!(stats[0].species, Species::Crab);
+        StatsOrder::Id.sort(&mut stats);
+        assert_eq!(
+            species(&stats),
+            vec![Species::Fish, Species::Shark, Species::Crab]
+        );
+
+        assert!(stats.iter().all(|s| s.matches(" ")));
+        assert_eq!(
+            stats.iter().filter(|s| s.matches("SHA")).count(),
+            1,
+            "searches by species name, ignoring case"
+        );
+        let id = stats[2].id.get_id_val().to_string();
+        assert!(stats[2].matches(&id));
+    }
+}
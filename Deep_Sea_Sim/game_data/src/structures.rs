@@ -0,0 +1,145 @@
+// Structures the player can build for the colony, paid for out of resources the colony gathers as it goes. Each kind does
+// something to the tiles around it every tick: reefs feed the plants nearby, barriers keep sharks from coming near, and
+// feeders put out a bit of food every FEEDER_INTERVAL ticks. Structures are plain NonLiving entities once they're down, so
+// they get saved, loaded and drawn like any other scenery.
+
+use log::info;
+use rand::seq::IteratorRandom;
+
+use crate::entities::nonliving::FEEDER_INTERVAL;
+use crate::entities::{Entity, NonAbstractTaxonomy, NonLiving, Species};
+use crate::error::Error;
+use crate::game_board::{Board, Pos};
+use crate::Sandbox;
+
+/// Every species the player can build.
+pub const BUILDABLE: [Species; 3] = [Species::Reef, Species::Barrier, Species::Feeder];
+
+/// How many resources a colony starts out with, so there's something to build with straight away.
+pub const STARTING_RESOURCES: usize = 100;
+
+/// The colony gathers a resource every tick for every this many animals living in it.
+pub const ANIMALS_PER_RESOURCE: usize = 5;
+
+/// How far a reef reaches.
+pub const REEF_RADIUS: usize = 2;
+
+/// How many ticks of growth at full light the plants around a reef get from it every tick.
+pub const REEF_NUTRIENTS: f64 = 0.5;
+
+/// How close sharks can come to a barrier. Anything nearer is off limits to them.
+pub const BARRIER_REACH: usize = 1;
+
+/// What a feeder puts out.
+const FEEDER_FOOD: Species = Species::KelpLeaf;
+
+/// How many resources it takes to build something, or None if it can't be built at all.
+pub fn build_cost(species: Species) -> Option<usize> {
+    match species {
+        Species::Reef => Some(150),
+        Species::Barrier => Some(40),
+        Species::Feeder => Some(100),
+        _ => None,
+    }
+}
+
+impl Board {
+    /// Whether a shark would be kept away from a position by a barrier.
+    pub fn blocks_sharks(&self, pos: Pos) -> bool {
+        self.positions_within(BARRIER_REACH, pos).any(|p| {
+            matches!(
+                self.entity_at(p),
+                Some(Entity::NonLiving(NonLiving::Barrier(_)))
+            )
+        })
+    }
+}
+
+impl Sandbox {
+    /// How many resources the colony has to build with.
+    pub fn get_resources(&self) -> usize {
+        self.resources
+    }
+
+    /// Spend the colony's resources on building something on an empty tile. Returns how many resources are left.
+    pub fn build(&mut self, pos: Pos, species: Species) -> Result<usize, Error> {
+        let cost = build_cost(species).ok_or(Error::NotBuildable(species.name().to_owned()))?;
+        if cost > self.resources {
+            return Err(Error::CantAfford {
+                cost,
+                resources: self.resources,
+            });
+        }
+        self.board.place_entity(pos, species.create_new(None))?;
+        self.resources -= cost;
+        info!(target: &self.log_target, "Built a {} at {pos:?} for {cost}", species.name());
+        Ok(self.resources)
+    }
+
+    /// Gather resources from the animals in the colony, and let every structure on the board do its thing.
+    pub fn handle_structures(&mut self) {
+        let animals = self
+            .board
+            .iter_occupied()
+            .filter(|(_, e)| e.animal().is_some())
+            .count();
+        self.resources += animals / ANIMALS_PER_RESOURCE;
+
+        let structures: Vec<(Pos, Species)> = self
+            .board
+            .iter_occupied()
+            .map(|(pos, e)| (pos, Species::of(e)))
+            .filter(|(_, species)| BUILDABLE.contains(species))
+            .collect();
+        for (pos, species) in structures {
+            match species {
+                Species::Reef => self.feed_plants_around(pos),
+                Species::Feeder => self.run_feeder(pos),
+                // barriers only get in the way, which is taken care of while moving
+                _ => (),
+            }
+        }
+    }
+
+    /// Give every plant within reach of a reef a little extra growth.
+    fn feed_plants_around(&mut self, reef: Pos) {
+        let around: Vec<Pos> = self.board.positions_within(REEF_RADIUS, reef).collect();
+        for pos in around {
+            if let Some(growth) = self.board.entity_at_mut(pos).and_then(Entity::growth_mut) {
+                growth.fertilize(REEF_NUTRIENTS);
+            }
+        }
+    }
+
+    /// Count a feeder down, and put some food out next to it whenever it's ready.
+    fn run_feeder(&mut self, feeder: Pos) {
+        if !self
+            .board
+            .entity_at_mut(feeder)
+            .and_then(Entity::structure_mut)
+            .is_some_and(|s| s.count_down())
+        {
+            return;
+        }
+        let spot = self
+            .board
+            .neighbors(feeder)
+            .filter(|p| !self.board.get_tile_from_pos(*p).is_occupied())
+            .choose(&mut *self.rng.lock().unwrap());
+        // if there's nowhere to put it, the feeder tries again next tick
+        let Some(spot) = spot else {
+            return;
+        };
+        // we just checked that it was empty
+        self.board
+            .place_entity(spot, FEEDER_FOOD.create_new(None))
+            .unwrap();
+        if let Some(structure) = self
+            .board
+            .entity_at_mut(feeder)
+            .and_then(Entity::structure_mut)
+        {
+            structure.cooldown = FEEDER_INTERVAL;
+        }
+    }
+}
@@ -0,0 +1,425 @@
+// Batch experiments: running every combination of a grid of parameters a few times over, and boiling the results down.
+// Handy for balancing, or for anyone who wants to know how a colony fares across a whole range of setups.
+
+use std::fmt::Write;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::entities::Species;
+use crate::game_board::PLANT_PERCENTAGE;
+use crate::interaction_matrix::InteractionMatrix;
+use crate::pool::{Census, SimulationConfig, SimulationPool};
+use crate::settings::{Difficulty, SimulationSettings};
+use crate::setup::{SetupConfig, SetupError};
+
+/// Everything a sweep keeps track of at the end of a run. Kelp only counts once it's fully grown.
+pub const TRACKED_SPECIES: [Species; 4] =
+    [Species::Fish, Species::Crab, Species::Shark, Species::Kelp];
+
+/// Every value to try for each parameter. Every combination gets run once per seed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterGrid {
+    /// Board sizes, as (rows, cols).
+    pub sizes: Vec<(usize, usize)>,
+    pub fish: Vec<usize>,
+    pub crab: Vec<usize>,
+    pub shark: Vec<usize>,
+    pub kelp_chances: Vec<f64>,
+    pub difficulties: Vec<Difficulty>,
+    pub species: Vec<SpeciesRules>,
+    pub seeds: Vec<u64>,
+}
+
+/// A set of species rules to try, and what to call it in the results.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeciesRules {
+    pub name: String,
+    pub rules: Arc<InteractionMatrix>,
+}
+
+impl Default for SpeciesRules {
+    /// Just the built in rules.
+    fn default() -> Self {
+        Self {
+            name: String::from("default"),
+            rules: Arc::new(InteractionMatrix::default()),
+        }
+    }
+}
+
+impl SpeciesRules {
+    /// Read a species config in on top of the built in rules, named after the file it came from. Unlike when a game's
+    /// started, a config that can't be read or has rules that don't make sense is an error, since the results would be
+    /// for something other than what was asked for.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| format!("Couldn't read {path}: {e}"))?;
+        let (rules, ignored) = InteractionMatrix::parse(&contents);
+        if let Some(line) = ignored.first() {
+            return Err(format!("Can't make sense of '{line}' in {path}"));
+        }
+        Ok(Self {
+            name: path.to_owned(),
+            rules: Arc::new(rules),
+        })
+    }
+}
+
+impl Default for ParameterGrid {
+    fn default() -> Self {
+        Self {
+            sizes: vec![(20, 20)],
+            fish: vec![20],
+            crab: vec![10],
+            shark: vec![5],
+            kelp_chances: vec![PLANT_PERCENTAGE],
+            difficulties: vec![Difficulty::Normal],
+            species: vec![SpeciesRules::default()],
+            seeds: (0..10).collect(),
+        }
+    }
+}
+
+/// One combination of parameters, to be run once for every seed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridPoint {
+    pub setup: SetupConfig,
+    pub difficulty: Difficulty,
+    pub species: SpeciesRules,
+}
+
+/// Split up a comma separated list, where anything of the form `a..b` stands for every number from a up to (but not including) b.
+fn parse_list<T: std::str::FromStr>(value: &str) -> Result<Vec<T>, String>
+where
+    Range<T>: Iterator<Item = T>,
+{
+    let mut values = vec![];
+    for item in value.split(',').map(str::trim) {
+        let parse = |s: &str| s.parse::<T>().map_err(|_| format!("'{s}' isn't a number"));
+        match item.split_once("..") {
+            Some((start, end)) => values.extend(parse(start)?..parse(end)?),
+            None => values.push(parse(item)?),
+        }
+    }
+    Ok(values)
+}
+
+impl ParameterGrid {
+    /// Swap out the values for one parameter, from a comma separated list (like `fish=5,10,20` or `seeds=0..50`).
+    /// Sizes are written as `ROWSxCOLS`, difficulties by name, and species rules as the config files to read them from.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "sizes" => {
+                self.sizes = value
+                    .split(',')
+                    .map(|size| {
+                        let bad_size = || format!("'{size}' should look like 10x20");
+                        let (rows, cols) = size.trim().split_once('x').ok_or_else(bad_size)?;
+                        match (rows.parse(), cols.parse()) {
+                            (Ok(rows), Ok(cols)) => Ok((rows, cols)),
+                            _ => Err(bad_size()),
+                        }
+                    })
+                    .collect::<Result<_, String>>()?
+            }
+            "fish" => self.fish = parse_list(value)?,
+            "crab" => self.crab = parse_list(value)?,
+            "shark" => self.shark = parse_list(value)?,
+            "kelp" => {
+                self.kelp_chances = value
+                    .split(',')
+                    .map(|v| {
+                        v.trim()
+                            .parse()
+                            .map_err(|_| format!("'{v}' isn't a chance"))
+                    })
+                    .collect::<Result<_, String>>()?
+            }
+            "difficulty" => {
+                self.difficulties = value
+                    .split(',')
+                    .map(|name| {
+                        Difficulty::ALL
+                            .into_iter()
+                            .find(|d| d.name() == name.trim())
+                            .ok_or_else(|| format!("There's no difficulty called '{name}'"))
+                    })
+                    .collect::<Result<_, String>>()?
+            }
+            "species" => {
+                self.species = value
+                    .split(',')
+                    .map(|path| SpeciesRules::load(path.trim()))
+                    .collect::<Result<_, String>>()?
+            }
+            "seeds" => self.seeds = parse_list(value)?,
+            _ => return Err(format!("There's nothing called '{key}' to sweep over")),
+        }
+        Ok(())
+    }
+
+    /// Every combination of parameters in the grid, not counting seeds.
+    pub fn points(&self) -> Vec<GridPoint> {
+        let mut points = vec![];
+        for &(rows, cols) in &self.sizes {
+            for &fish in &self.fish {
+                for &crab in &self.crab {
+                    for &shark in &self.shark {
+                        for &kelp_chance in &self.kelp_chances {
+                            for &difficulty in &self.difficulties {
+                                for species in &self.species {
+                                    let setup = SetupConfig {
+                                        fish,
+                                        crab,
+                                        shark,
+                                        kelp_chance,
+                                        ..SetupConfig::new(rows, cols)
+                                    };
+                                    points.push(GridPoint {
+                                        setup,
+                                        difficulty,
+                                        species: species.clone(),
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        points
+    }
+
+    /// Run every point in the grid once per seed, for the given number of ticks, and sum up how each one went.
+    /// Points that can't be set up (say, too many sharks for the board) are skipped, rather than holding everything else up.
+    /// Without any seeds there'd be nothing to run at all, so that's an error.
+    pub fn run(&self, ticks: usize) -> Result<SweepReport, String> {
+        if self.seeds.is_empty() {
+            return Err(String::from("There aren't any seeds to run the grid with"));
+        }
+        let mut runnable = vec![];
+        let mut skipped = vec![];
+        for point in self.points() {
+            match point.setup.validate() {
+                Ok(()) => runnable.push(point),
+                Err(e) => skipped.push((point, e)),
+            }
+        }
+
+        let configs: Vec<SimulationConfig> = runnable
+            .iter()
+            .flat_map(|point| {
+                self.seeds.iter().map(|&seed| SimulationConfig {
+                    setup: point.setup.clone(),
+                    settings: SimulationSettings {
+                        difficulty: point.difficulty,
+                        ..SimulationSettings::default()
+                    },
+                    seed,
+                    event_policy: None,
+                    rules: Arc::clone(&point.species.rules),
+                })
+            })
+            .collect();
+        // everything's been validated, so the pool won't turn any of it down
+        let censuses = SimulationPool::run(&configs, ticks).unwrap();
+
+        let results = runnable
+            .into_iter()
+            .zip(censuses.chunks(self.seeds.len()))
+            .map(|(point, censuses)| PointStats::new(point, censuses))
+            .collect();
+        Ok(SweepReport {
+            ticks,
+            results,
+            skipped,
+        })
+    }
+}
+
+/// How one species fared over every run of a grid point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeciesStats {
+    pub species: Species,
+    /// The average number left at the end of a run.
+    pub mean: f64,
+    pub min: usize,
+    pub max: usize,
+    /// The fraction of runs that still had at least one of them at the end.
+    pub survival: f64,
+}
+
+impl SpeciesStats {
+    fn new(species: Species, censuses: &[Census]) -> Self {
+        let counts: Vec<usize> = censuses.iter().map(|c| c.count(species)).collect();
+        let runs = counts.len().max(1) as f64;
+        Self {
+            species,
+            mean: counts.iter().sum::<usize>() as f64 / runs,
+            min: counts.iter().copied().min().unwrap_or(0),
+            max: counts.iter().copied().max().unwrap_or(0),
+            survival: counts.iter().filter(|c| **c > 0).count() as f64 / runs,
+        }
+    }
+}
+
+/// Everything a sweep found out about one grid point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointStats {
+    pub point: GridPoint,
+    pub runs: usize,
+    /// One entry for each of the tracked species, in the same order.
+    pub species: Vec<SpeciesStats>,
+}
+
+impl PointStats {
+    fn new(point: GridPoint, censuses: &[Census]) -> Self {
+        Self {
+            point,
+            runs: censuses.len(),
+            species: TRACKED_SPECIES
+                .iter()
+                .map(|s| SpeciesStats::new(*s, censuses))
+                .collect(),
+        }
+    }
+}
+
+/// The results of a whole sweep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepReport {
+    /// How long every run went on for.
+    pub ticks: usize,
+    pub results: Vec<PointStats>,
+    /// Grid points that were never run, and why.
+    pub skipped: Vec<(GridPoint, SetupError)>,
+}
+
+impl SweepReport {
+    /// Lay the results out as CSV, with one row for each grid point.
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("rows,cols,fish,crab,shark,kelp_chance,difficulty,species,runs,ticks");
+        for species in TRACKED_SPECIES {
+            let name = species.name();
+            write!(csv, ",{name}_mean,{name}_min,{name}_max,{name}_survival").unwrap();
+        }
+        csv.push('\n');
+        for result in &self.results {
+            let setup = &result.point.setup;
+            write!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{}",
+                setup.rows,
+                setup.cols,
+                setup.fish,
+                setup.crab,
+                setup.shark,
+                setup.kelp_chance,
+                result.point.difficulty.name(),
+                result.point.species.name,
+                result.runs,
+                self.ticks
+            )
+            .unwrap();
+            for stats in &result.species {
+                write!(
+                    csv,
+                    ",{:.2},{},{},{:.2}",
+                    stats.mean, stats.min, stats.max, stats.survival
+                )
+                .unwrap();
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Write the results out to a CSV file.
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_parsing() {
+        let mut grid = ParameterGrid::default();
+        grid.set("sizes", "8x8, 10x12").unwrap();
+        grid.set("fish", "4,6").unwrap();
+        grid.set("seeds", "0..3,10").unwrap();
+        grid.set("difficulty", "easy,hard").unwrap();
+        assert_eq!(grid.sizes, vec![(8, 8), (10, 12)]);
+        assert_eq!(grid.seeds, vec![0, 1, 2, 10]);
+        assert_eq!(grid.difficulties, vec![Difficulty::Easy, Difficulty::Hard]);
+        // 2 sizes, 2 fish counts and 2 difficulties
+        assert_eq!(grid.points().len(), 8);
+
+        assert!(grid.set("sizes", "8by8").is_err());
+        assert!(grid.set("fish", "lots").is_err());
+        assert!(grid.set("difficulty", "nightmare").is_err());
+        assert!(grid.set("whales", "3").is_err());
+    }
+
+    #[test]
+    fn test_sweep() {
+        let grid = ParameterGrid {
+            sizes: vec![(3, 3), (8, 8)],
+            fish: vec![4],
+            crab: vec![1],
+            shark: vec![1],
+            seeds: vec![1, 2],
+            ..ParameterGrid::default()
+        };
+        let report = grid.run(5).unwrap();
+        // 4 fish don't fit on a 3x3 board
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.results.len(), 1);
+        let result = &report.results[0];
+        assert_eq!(result.runs, 2);
+        assert_eq!(result.species.len(), TRACKED_SPECIES.len());
+        for stats in &result.species {
+            assert!(stats.min as f64 <= stats.mean && stats.mean <= stats.max as f64);
+            assert!((0.0..=1.0).contains(&stats.survival));
+        }
+
+        let csv = report.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("8,8,4,1,1,"));
+        assert!(lines[1].contains(",normal,default,2,"));
+        assert_eq!(lines[0].split(',').count(), lines[1].split(',').count());
+
+        let no_seeds = ParameterGrid {
+            seeds: vec![],
+            ..grid
+        };
+        assert!(no_seeds.run(5).is_err());
+    }
+
+    #[test]
+    fn test_species_configs() {
+        let path = std::env::temp_dir().join("deep_sea_sim_test_sweep_species.txt");
+        fs::write(&path, "food fish 50\ncap shark 12\n").unwrap();
+        let path = path.to_str().unwrap().to_owned();
+        let mut grid = ParameterGrid::default();
+        grid.set("species", &path).unwrap();
+        assert_eq!(grid.species.len(), 1);
+        assert_eq!(grid.species[0].name, path);
+        assert_ne!(*grid.species[0].rules, InteractionMatrix::default());
+        // every set of rules gets run against every other combination
+        grid.set("difficulty", "easy,hard").unwrap();
+        grid.species.push(SpeciesRules::default());
+        assert_eq!(grid.points().len(), 4);
+
+        fs::write(&path, "food fish lots\n").unwrap();
+        assert!(grid.set("species", &path).is_err());
+        assert!(grid.set("species", "no/such/species.txt").is_err());
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,295 @@
+// The systems a tick is made of. Each one runs over every important entity on the board (or on a really big board, every
+// one in an awake chunk, see chunks.rs) and only looks at the parts of it that it cares about: moving only needs something
+// with a mind of its own or a seed out on the current, while processing covers anything living.
+// Entities get pulled off the board while they're being worked on, so they're free to change the board around them.
+
+use futures::future::join_all;
+use log::{debug, error, info, warn};
+use rand::Rng;
+
+use crate::ai_controller::AIControlled;
+use crate::arena::Lent;
+use crate::element_traits::{Lives, PostProcessResult, Processing, ProcessingContext};
+use crate::entities::animals::{Animals, FOREST_HEALING};
+use crate::entities::nonliving::{BONES_CRUMBLE_CHANCE, BONE_NUTRIENTS, RUBBLE_WASH_AWAY_CHANCE};
+use crate::entities::{Entity, Living, NonLiving, Species};
+use crate::game_board::{Board, Pos};
+use crate::settings::Difficulty;
+use crate::Sandbox;
+
+impl Sandbox {
+    /// Handle the movement for everything interesting on the board
+    pub fn handle_moves(&mut self) {
+        // everything that moves gets handed to the entity manager in one go once they're all done
+        self.board.batch_updates();
+        // big boards work out their moves a chunk at a time, side by side
+        if self.board.chunks().is_chunked() {
+            self.handle_chunked_moves();
+        } else {
+            // run through all of our pieces and see where they would like to move
+            for pos in &self.get_awake_entities() {
+                let ctx = self.processing_context(*pos);
+                if let Some(new_pos) = desired_move(&self.board, &ctx) {
+                    self.try_move(*pos, new_pos);
+                }
+            }
+        }
+        self.board.apply_updates();
+    }
+
+    /// Move whatever's at a position to a new one, going tile by tile in a straight line. Nothing can swim through
+    /// whatever's in the way, so it stops on the last free tile before it, or stays put if there isn't one.
+    pub(crate) fn try_move(&mut self, pos: Pos, new_pos: Pos) {
+        let entity = self.board.entity_at(pos);
+        // check that the new position is available
+        if !self.board.is_valid_pos(new_pos) {
+            warn!(
+                target: self.log_target(),
+                "Failed to move {entity:?}: tried to move out of bounds!"
+            );
+            return;
+        }
+        let shark = entity.map(Species::of) == Some(Species::Shark);
+        let mut reached = pos;
+        let mut bumped = None;
+        let path = self.board.tiles_between(pos, new_pos);
+        for step in path.into_iter().chain([new_pos]) {
+            let other = self.board.entity_at(step);
+            // trying to swim straight through a hazard goes about as well as you'd expect
+            if let Some(hazard) = other.and_then(Entity::hazard) {
+                bumped = Some((hazard.damage, format!("swimming into a {}", hazard.name)));
+                break;
+            }
+            if shark && self.board.blocks_sharks(step) {
+                debug!(
+                    target: self.log_target(),
+                    "Shark at {pos:?} was kept away from {step:?} by a barrier"
+                );
+                break;
+            }
+            if self.board.get_tile_from_pos(step).is_occupied() {
+                debug!(
+                    target: self.log_target(),
+                    "{entity:?} moving from {pos:?} to {new_pos:?} was stopped at {step:?} by {other:?}!"
+                );
+                break;
+            }
+            reached = step;
+        }
+        if reached != pos {
+            if let Err(e) = self.board.move_entity(pos, reached) {
+                error!(target: self.log_target(), "Failed to move from {pos:?}: {e}");
+                return;
+            }
+        }
+        // whatever we ran into already hurt us, so being next to it doesn't count again
+        match bumped {
+            Some((damage, cause)) => self.hurt_by_hazard(reached, damage, &cause),
+            None if reached != pos => {
+                let damage = self.board.hazard_damage_near(reached);
+                if damage > 0 {
+                    self.hurt_by_hazard(reached, damage, "swimming too close to a hazard");
+                }
+            }
+            None => (),
+        }
+    }
+
+    /// Hurt whatever animal's at a position for getting too close to something dangerous.
+    fn hurt_by_hazard(&mut self, pos: Pos, damage: i64, cause: &str) {
+        if let Some(animal) = self.board.entity_at_mut(pos).and_then(Entity::animal_mut) {
+            debug!(target: &self.log_target, "{animal:?} at {pos:?} took {damage} damage from {cause}");
+            animal.modify_health(-damage, cause);
+        }
+    }
+
+    /// Scenery doesn't have an ID to get processed by, so it's all looked over in one go: rubble slowly washes away,
+    /// and bones crumble into the plants around them.
+    pub fn handle_scenery(&mut self) {
+        let mut rng = self.rng.lock().unwrap();
+        let scenery: Vec<(Pos, bool)> = self
+            .board
+            .iter_occupied()
+            .filter_map(|(pos, e)| match e {
+                Entity::NonLiving(NonLiving::Rubble(_)) => Some((pos, false)),
+                Entity::NonLiving(NonLiving::Bones(_)) => Some((pos, true)),
+                _ => None,
+            })
+            .collect();
+        for (pos, bones) in scenery {
+            let chance = if bones {
+                BONES_CRUMBLE_CHANCE
+            } else {
+                RUBBLE_WASH_AWAY_CHANCE
+            };
+            if !rng.gen_bool(chance) {
+                continue;
+            }
+            self.board.remove_entity(pos);
+            if bones {
+                let neighbors: Vec<Pos> = self.board.neighbors(pos).collect();
+                for neighbor in neighbors {
+                    if let Some(growth) = self
+                        .board
+                        .entity_at_mut(neighbor)
+                        .and_then(Entity::growth_mut)
+                    {
+                        growth.fertilize(BONE_NUTRIENTS);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Keep track of where the kelp forests are, and let the fish sheltering in them heal up.
+    pub fn handle_forests(&mut self) {
+        self.board.update_forests();
+        let sheltered: Vec<Pos> = self
+            .board
+            .iter_occupied()
+            .filter(|(pos, e)| {
+                e.animal().is_some_and(Animals::shelters_in_forest) && self.board.in_forest(*pos)
+            })
+            .map(|(pos, _)| pos)
+            .collect();
+        for pos in sheltered {
+            if let Some(animal) = self.board.entity_at_mut(pos).and_then(Entity::animal_mut) {
+                animal.modify_health(FOREST_HEALING, "resting in a kelp forest");
+            }
+        }
+    }
+
+    /// Run processing, possibly on a few different entities across the board.
+    pub fn handle_processing(&mut self) {
+        // need this before the loop since we're immutably running over it
+        let processing = self.get_awake_entities();
+        // anything born, eaten or killed gets handed to the entity manager in one go once everything's been processed
+        self.board.batch_updates();
+        for pos in &processing {
+            // lend the entity out of the board while it's being worked on, so it's free to change the board around it.
+            // its tile's kept for it the whole time, so there's always somewhere to give it back to
+            let Some(mut lent) = self.board.lend(*pos) else {
+                panic!("Entity at pos {pos:?} was none!");
+            };
+            let ctx = self.processing_context(*pos);
+            // decorations never do anything
+            let action_hint = lent
+                .entity
+                .living_mut()
+                .and_then(|l| l.process(&mut self.board, ctx));
+            self.board.give_back(lent);
+            self.handle_post_process(*pos, action_hint);
+        }
+        self.board.apply_updates();
+    }
+
+    /// Carry out whatever an entity asked for once it was done being processed. Anything on its way out leaves the
+    /// board before anything else gets added, so whatever replaces it can have its tile.
+    fn handle_post_process(&mut self, pos: Pos, hint: Option<PostProcessResult>) {
+        match hint {
+            None => (),
+            Some(PostProcessResult::TryToAddEntities(these)) => {
+                self.try_to_add(these);
+            }
+            Some(PostProcessResult::TryToAddEntitiesAndKillMe(these)) => {
+                self.board.remove_entity(pos);
+                self.try_to_add(these);
+            }
+            Some(PostProcessResult::MarkTheseAsInteresting(these)) => {
+                info!(target: self.log_target(), "Marked these ({these:?}) as interesting");
+            }
+            Some(PostProcessResult::Delete) => {
+                let entity = self.board.remove_entity(pos);
+                info!(target: self.log_target(), "entity {entity:?} at {pos:?} was deleted in process");
+            }
+            Some(PostProcessResult::ReplaceMeWith(e)) => {
+                let old_e = self.board.remove_entity(pos);
+                info!(target: self.log_target(), "Replacing {old_e:?} with {e:?}");
+                self.put_back(pos, *e);
+            }
+        }
+    }
+
+    /// Put down whatever something asked to add to the board, dropping anything that doesn't have room.
+    fn try_to_add(&mut self, entities: Vec<(Pos, Entity)>) {
+        for (pos, entity) in entities {
+            if let Err(e) = self.board.place_entity(pos, entity) {
+                debug!(target: self.log_target(), "Couldn't add an entity at {pos:?}: {e}")
+            }
+        }
+    }
+
+    /// Helper function to create futures for late-processing entities.
+    /// This function takes in an entity that's been lent out, runs its late processing, and then hands it back along
+    /// with whatever it asked for, so it can be given back to the board once all the futures have been joined.
+    async fn late_process_entity(
+        mut lent: Lent,
+        difficulty: Difficulty,
+    ) -> (Lent, Option<PostProcessResult>) {
+        let hint = match lent.entity.living_mut() {
+            Some(living) => living.late_process(difficulty).await,
+            None => None,
+        };
+        (lent, hint)
+    }
+
+    /// Run all of our late-processing behavior.
+    /// Stuff in here should be designed as atomically as possible, and should only involve things that deal with the creature itself.
+    pub async fn handle_late_processing(&mut self) {
+        // Hang onto all the futures we'll be working with.
+        let mut futures = vec![];
+        let difficulty = self.settings.difficulty;
+        // plants grow by however much light they're getting and animals sicken when they're packed in too tight, which
+        // both depend on what's around them, so it all needs working out before anything gets lent out
+        let positions = self.get_awake_entities();
+        let light: Vec<f64> = positions.iter().map(|p| self.board.light_at(*p)).collect();
+        let crowding: Vec<usize> = positions
+            .iter()
+            .map(|p| self.board.crowding_at(*p))
+            .collect();
+        // run through all the important entities and lend them all out into futures.
+        for ((pos, light), crowding) in positions.iter().zip(light).zip(crowding) {
+            let Some(mut lent) = self.board.lend(*pos) else {
+                continue;
+            };
+            if let Some(growth) = lent.entity.growth_mut() {
+                growth.light = light;
+            }
+            if let Some(animal) = lent.entity.animal_mut() {
+                animal.body_mut().health.crowding = crowding;
+            }
+            futures.push(Self::late_process_entity(lent, difficulty));
+        }
+
+        // wait for them all to finish
+        let results = join_all(futures);
+        #[cfg(not(target_arch = "wasm32"))]
+        let results = async_std::task::spawn(results).await;
+        // a browser would only get around to running a spawned task once we've stopped blocking on it, so just wait here instead
+        #[cfg(target_arch = "wasm32")]
+        let results = results.await;
+
+        // everything goes back where it came from before anything gets added or taken away
+        let mut hints = vec![];
+        for (lent, hint) in results {
+            hints.push((lent.pos, hint));
+            self.board.give_back(lent);
+        }
+        for (pos, hint) in hints {
+            self.handle_post_process(pos, hint);
+        }
+    }
+}
+
+/// Where whatever's at the context's position would like to go, if anywhere. Only looks at the board, so it can be
+/// worked out from any thread.
+pub(crate) fn desired_move(board: &Board, ctx: &ProcessingContext) -> Option<Pos> {
+    // animals have an AI to move them, and seeds get carried along by the current until they root
+    match board.entity_at(ctx.position) {
+        Some(Entity::Living(Living::Animals(a))) => a.get_desired_move(ctx, board),
+        Some(Entity::Living(Living::Plants(p))) => {
+            p.drift_move(ctx.position, board, &mut *ctx.rng.lock().unwrap())
+        }
+        _ => None,
+    }
+}
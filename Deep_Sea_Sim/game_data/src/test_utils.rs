@@ -1,11 +1,20 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_std::task::block_on;
+use proptest::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
 
 use crate::{
-    entities::{Entity, NonAbstractTaxonomy},
-    entity_control::EntityManager,
-    game_events, populate_board, Board, Pos, Sandbox,
+    entities::{animals::Animals, display_spec::GlyphStyle, Entity, NonAbstractTaxonomy, Species},
+    entity_control::{EntityID, EntityManager, TrackedEntity},
+    game_board::{populate_board, Current, Topology},
+    game_events,
+    scenario::map_species,
+    settings::Strictness,
+    setup::SetupConfig,
+    weather::{STORM_GUST, TOSS_DISTANCE},
+    Board, Pos, Sandbox,
 };
 
 use crate::game_board::test_utils::*;
@@ -17,6 +26,18 @@ pub struct TestBed {
 
 #[allow(dead_code)]
 impl TestBed {
+    /// Start building up a testbed piece by piece, see TestBedBuilder.
+    pub fn builder(rows: usize, cols: usize) -> TestBedBuilder {
+        TestBedBuilder {
+            rows,
+            cols,
+            entities: vec![],
+            topology: Topology::Square,
+            current: Current::Still,
+            seed: None,
+        }
+    }
+
     /// Create a new testbed with exactly the entities you want.
     pub fn new_with_entities(rows: usize, cols: usize, entities: Vec<(Pos, Entity)>) -> Self {
         let em = EntityManager::new();
@@ -42,7 +63,8 @@ impl TestBed {
     pub fn new_default(rows: usize, cols: usize, fish: usize, crab: usize, shark: usize) -> Self {
         let em = EntityManager::new();
         let mut board = Board::new(rows, cols, Arc::clone(&em));
-        populate_board(&mut board, fish, crab, shark);
+        populate_board(&mut board, fish, crab, shark)
+            .expect("the creatures should fit on the board");
         Self {
             sandbox: create_sandbox(board, 1.0, &em),
         }
@@ -95,21 +117,21 @@ impl TestBed {
     {
         for step in 0..steps {
             if run_moves {
-                self.sandbox.handle_moves();
+                self.step_moves();
                 post_step_check(&self.sandbox)
             }
             if process {
-                self.sandbox.handle_processing();
+                self.step_processing();
                 post_step_check(&self.sandbox)
             }
 
             if late_process {
-                block_on(self.sandbox.handle_late_processing());
+                self.step_late_processing();
                 post_step_check(&self.sandbox)
             }
 
             if run_events {
-                self.sandbox.handle_events();
+                self.step_events();
                 post_step_check(&self.sandbox)
             }
 
@@ -119,6 +141,30 @@ impl TestBed {
         }
     }
 
+    /// Run just the part of the game loop where everything moves.
+    pub fn step_moves(&mut self) -> &mut Self {
+        self.sandbox.handle_moves();
+        self
+    }
+
+    /// Run just the part of the game loop where everything works out what it's doing next.
+    pub fn step_processing(&mut self) -> &mut Self {
+        self.sandbox.handle_processing();
+        self
+    }
+
+    /// Run just the part of the game loop where everything eats, ages, breeds and dies.
+    pub fn step_late_processing(&mut self) -> &mut Self {
+        block_on(self.sandbox.handle_late_processing());
+        self
+    }
+
+    /// Roll for an event, without running whatever comes up.
+    pub fn step_events(&mut self) -> &mut Self {
+        self.sandbox.handle_events();
+        self
+    }
+
     /// Separate function to test events in order to simulate
     /// input and test board update
     pub fn run_n_steps_events<F>(&mut self, steps: usize, post_step_check: F) -> bool
@@ -137,18 +183,261 @@ impl TestBed {
     }
 
     pub fn get_entity_at_pos(&self, pos: Pos) -> Option<&Entity> {
-        self.sandbox
-            .board
-            .get_tile_from_pos(pos)
-            .get_entity()
-            .as_ref()
+        self.sandbox.board.entity_at(pos)
     }
 
     pub fn get_entity_at_pos_mut(&mut self, pos: Pos) -> Option<&mut Entity> {
-        self.sandbox
+        self.sandbox.board.entity_at_mut(pos)
+    }
+
+    /// The animal at a position. Panics if there isn't one there, since any test asking is counting on it.
+    pub fn animal_at(&self, pos: Pos) -> &Animals {
+        self.get_entity_at_pos(pos)
+            .and_then(Entity::animal)
+            .unwrap_or_else(|| panic!("There's no animal at {pos:?}"))
+    }
+
+    pub fn animal_at_mut(&mut self, pos: Pos) -> &mut Animals {
+        self.get_entity_at_pos_mut(pos)
+            .and_then(Entity::animal_mut)
+            .unwrap_or_else(|| panic!("There's no animal at {pos:?}"))
+    }
+
+    /// The ID of whatever's at a position. Panics if there's nothing there being kept track of.
+    pub fn id_at(&self, pos: Pos) -> EntityID {
+        self.get_entity_at_pos(pos)
+            .and_then(|e| e.get_id())
+            .unwrap_or_else(|| panic!("There's nothing being kept track of at {pos:?}"))
+    }
+
+    /// Run the game for a given amount of ticks the way it runs headless, checking after every tick that:
+    /// - nothing being kept track of shares a tile with anything else,
+    /// - the entity manager and the board agree on where everything is,
+    /// - nothing got further in one tick than it can move (or a storm can throw it).
+    ///
+    /// Returns the first thing that went wrong, if anything did.
+    pub fn run_checked(&mut self, ticks: usize) -> Result<(), String> {
+        let mut before = Whereabouts::of(&self.sandbox);
+        let mut problem = Ok(());
+        self.sandbox.run_headless(ticks, |sandbox, report| {
+            if problem.is_ok() {
+                problem = before.check(sandbox).map_err(|e| {
+                    let board = sandbox.board.render(GlyphStyle::Ascii);
+                    format!("after tick {}: {e}\n{board}", report.tick)
+                });
+                before = Whereabouts::of(sandbox);
+            }
+        });
+        problem
+    }
+}
+
+/// Builds up a testbed with exactly what a test needs on it, one piece at a time:
+/// ```ignore
+/// let testbed = TestBed::builder(3, 3)
+///     .with_stats(Pos { x: 2, y: 2 }, Species::Shark, &[("hunger", "20")])
+///     .with_stats(Pos { x: 0, y: 1 }, Species::Crab, &[("pregnant", "true")])
+///     .terrain(&["r..", "...", "..k"])
+///     .seed(7)
+///     .build();
+/// ```
+pub struct TestBedBuilder {
+    rows: usize,
+    cols: usize,
+    entities: Vec<(Pos, Entity)>,
+    topology: Topology,
+    current: Current,
+    seed: Option<u64>,
+}
+
+impl TestBedBuilder {
+    /// Put a fresh one of a species on the board.
+    pub fn with(self, pos: Pos, species: Species) -> Self {
+        self.with_stats(pos, species, &[])
+    }
+
+    /// Put one of a species on the board with some of its stats overridden, by the same names scenarios use.
+    pub fn with_stats(self, pos: Pos, species: Species, stats: &[(&str, &str)]) -> Self {
+        let mut entity = species.create_new(None);
+        for (stat, value) in stats {
+            entity.set_stat(stat, value).unwrap_or_else(|e| {
+                panic!("Couldn't set up the {} at {pos:?}: {e}", species.name())
+            });
+        }
+        self.with_entity(pos, entity)
+    }
+
+    /// Put something that's already been made on the board.
+    pub fn with_entity(mut self, pos: Pos, entity: Entity) -> Self {
+        self.entities.push((pos, entity));
+        self
+    }
+
+    /// Lay out the rocks, kelp and so on, one string per row, drawn the same way as a scenario's map.
+    pub fn terrain(mut self, map: &[&str]) -> Self {
+        for (y, row) in map.iter().enumerate() {
+            for (x, ch) in row.chars().enumerate() {
+                if let Some(species) = map_species(ch).unwrap() {
+                    self.entities.push((Pos { x, y }, species.create_new(None)));
+                }
+            }
+        }
+        self
+    }
+
+    pub fn topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn current(mut self, current: Current) -> Self {
+        self.current = current;
+        self
+    }
+
+    /// Seed the sandbox's randomness, so the test plays out exactly the same every time.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> TestBed {
+        let em = EntityManager::new();
+        let mut board = Board::new(self.rows, self.cols, Arc::clone(&em));
+        board.set_topology(self.topology);
+        board.set_current(self.current);
+        for (pos, entity) in self.entities {
+            if let Err(e) = board.place_entity(pos, entity) {
+                panic!("Couldn't put something at {pos:?}: {e}");
+            }
+        }
+        let mut sandbox = create_sandbox(board, 1.0, &em);
+        if let Some(seed) = self.seed {
+            sandbox.reseed(seed);
+        }
+        TestBed { sandbox }
+    }
+}
+
+/// Where everything being kept track of was, to check the board against after a tick.
+struct Whereabouts {
+    /// Each entity's position, and how far it could get from there in one tick as (x, y).
+    reach: HashMap<EntityID, (Pos, (usize, usize))>,
+    stormy: bool,
+}
+
+impl Whereabouts {
+    fn of(sandbox: &Sandbox) -> Self {
+        let reach = sandbox
             .board
-            .get_tile_mut_from_pos(pos)
-            .get_entity_mut()
-            .as_mut()
+            .iter_occupied()
+            .filter_map(|(pos, entity)| {
+                // plants only ever drift a tile at a time
+                let speed = entity
+                    .animal()
+                    .map_or((1, 1), |a| a.body().get_max_movespeed());
+                Some((entity.get_id()?, (pos, speed)))
+            })
+            .collect();
+        Self {
+            reach,
+            stormy: sandbox.board.get_weather().is_stormy(),
+        }
+    }
+
+    fn check(&self, sandbox: &Sandbox) -> Result<(), String> {
+        let em = sandbox.entity_context.read().unwrap();
+        let mut seen: HashMap<Pos, EntityID> = HashMap::new();
+        for (id, pos) in em.get_active_entries() {
+            if let Some(other) = seen.insert(*pos, *id) {
+                return Err(format!("{id:?} and {other:?} are both at {pos:?}"));
+            }
+            let found = sandbox.board.entity_at(*pos).and_then(|e| e.get_id());
+            if found != Some(*id) {
+                return Err(format!(
+                    "{id:?} should be at {pos:?}, but the board has {found:?} there"
+                ));
+            }
+        }
+        for (pos, entity) in sandbox.board.iter_occupied() {
+            let Some(id) = entity.get_id() else {
+                continue;
+            };
+            if seen.get(&pos) != Some(&id) {
+                return Err(format!(
+                    "{id:?} is on the board at {pos:?}, but isn't being kept track of there"
+                ));
+            }
+        }
+        let slack = if self.stormy || sandbox.board.get_weather().is_stormy() {
+            STORM_GUST + TOSS_DISTANCE
+        } else {
+            0
+        };
+        for (pos, id) in seen {
+            let Some((was, (speed_x, speed_y))) = self.reach.get(&id) else {
+                continue;
+            };
+            if pos.x.abs_diff(was.x) > speed_x + slack || pos.y.abs_diff(was.y) > speed_y + slack {
+                return Err(format!(
+                    "{id:?} got from {was:?} to {pos:?}, but can only move {:?} a tick",
+                    (speed_x, speed_y)
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A game set up at random, for checking that nothing ever ends up somewhere it shouldn't however the dice land.
+#[derive(Debug, Clone)]
+pub struct RandomGame {
+    pub setup: SetupConfig,
+    pub seed: u64,
+}
+
+impl RandomGame {
+    /// Boards of any shape up to `max_side` tiles a side, filled with anywhere from none to as many of each animal as they
+    /// can start out with.
+    pub fn strategy(max_side: usize) -> impl Strategy<Value = Self> {
+        (
+            1..=max_side,
+            1..=max_side,
+            any::<bool>(),
+            0.0..=0.5,
+            any::<u64>(),
+        )
+            .prop_flat_map(|(rows, cols, hex, kelp_chance, seed)| {
+                let mut setup = SetupConfig {
+                    kelp_chance,
+                    ..SetupConfig::new(rows, cols)
+                };
+                if hex {
+                    setup.topology = Topology::Hex;
+                }
+                let counts = (
+                    0..=setup.limit(Species::Fish),
+                    0..=setup.limit(Species::Crab),
+                    0..=setup.limit(Species::Shark),
+                );
+                (Just(setup), counts, Just(seed))
+            })
+            .prop_map(|(setup, (fish, crab, shark), seed)| Self {
+                setup: SetupConfig {
+                    fish,
+                    crab,
+                    shark,
+                    ..setup
+                },
+                seed,
+            })
+    }
+
+    /// Set the game up, with anything the sanity checks turn up panicking instead of getting quietly repaired.
+    pub fn build(&self) -> TestBed {
+        let mut sandbox = Sandbox::from_setup(&self.setup, &mut StdRng::seed_from_u64(self.seed))
+            .expect("the setup should fit on the board");
+        sandbox.settings.strictness = Strictness::Panic;
+        TestBed { sandbox }
     }
 }
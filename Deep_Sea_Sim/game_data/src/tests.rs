@@ -1,5 +1,8 @@
 mod test_ai;
 mod test_game_engine;
 mod test_game_events;
+mod test_golden;
 mod test_interactions;
+mod test_invariants;
 mod test_late_process;
+mod test_scenarios;
@@ -1 +1,737 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ai_controller::{
+            AIAction, AIConcreteBehaviors, AIControlled, EatAction, IdleAction, MateAction,
+            Pathfinder, COURTSHIP_TICKS, HOVER_RADIUS, PATROL_RADIUS,
+        },
+        element_traits::{Lives, Mobile},
+        entities::{
+            animals::{FOREST_HEALING, STUCK_TICKS, WANDER_TICKS},
+            nonliving::{ConcreteDecorations, ConcreteHazards},
+            plants::ConcretePlants,
+            NonAbstractTaxonomy, Sex, Species,
+        },
+        entity_control::TrackedEntity,
+        find_path,
+        game_board::{Current, Pos, Topology},
+        interaction_matrix::WanderStyle,
+        test_utils::TestBed,
+    };
 
+    /// What the animal at a position would rather be doing than idling about.
+    fn decide(testbed: &TestBed, pos: Pos) -> Option<AIConcreteBehaviors> {
+        let animal = testbed.get_entity_at_pos(pos).unwrap().animal().unwrap();
+        let ctx = testbed.sandbox.processing_context(pos);
+        animal.get_next_action(&ctx, &testbed.sandbox.board)
+    }
+
+    #[test]
+    /// Paths should be able to run straight along a row, not just diagonally
+    fn verify_orthogonal_pathfinding() {
+        let testbed = TestBed::new_with_entities(1, 5, vec![]);
+        let goal = Pos { x: 4, y: 0 };
+        let path = find_path(Pos { x: 0, y: 0 }, &testbed.sandbox.board, |pos, _| {
+            pos == goal
+        })
+        .unwrap();
+        assert_eq!(path, (0..4).map(|x| Pos { x, y: 0 }).collect::<Vec<Pos>>());
+        // and anything following it heads off down it, as far as it can go in a tick
+        let step = |speed| {
+            EatAction::get_next_node(
+                Pos { x: 0, y: 0 },
+                &testbed.sandbox.board,
+                speed,
+                speed,
+                EatAction::find_path_bfs,
+                |pos, _| pos == goal,
+            )
+        };
+        assert_eq!(step(1), Some(Pos { x: 1, y: 0 }));
+        assert_eq!(step(2), Some(Pos { x: 2, y: 0 }));
+
+        // with a rock in the way, there's no getting there
+        let rock = ConcreteDecorations::Rock.create_new(None);
+        let testbed = TestBed::new_with_entities(1, 5, vec![(Pos { x: 2, y: 0 }, rock)]);
+        assert_eq!(
+            find_path(Pos { x: 0, y: 0 }, &testbed.sandbox.board, |pos, _| {
+                pos == goal
+            }),
+            None
+        );
+    }
+
+    #[test]
+    /// Paths should give hazards a wide berth when there's room to, and only go past them when there isn't
+    fn verify_pathing_around_hazards() {
+        let (start, goal) = (Pos { x: 0, y: 1 }, Pos { x: 4, y: 1 });
+        let urchin = || vec![(Pos { x: 2, y: 0 }, ConcreteHazards::Urchin.create_new(None))];
+
+        let testbed = TestBed::new_with_entities(3, 5, urchin());
+        let board = &testbed.sandbox.board;
+        let path = find_path(start, board, |pos, _| pos == goal).unwrap();
+        assert!(path.iter().all(|pos| !board.near_hazard(*pos)), "{path:?}");
+
+        // with only two rows, there's no way around
+        let testbed = TestBed::new_with_entities(2, 5, urchin());
+        let board = &testbed.sandbox.board;
+        let path = find_path(start, board, |pos, _| pos == goal).unwrap();
+        assert!(path.iter().any(|pos| board.near_hazard(*pos)), "{path:?}");
+    }
+
+    #[test]
+    /// Sharks can't go after fish they can't see, and a rock in the way is enough to hide behind
+    fn verify_prey_hides_behind_cover() {
+        let (shark_pos, rock_pos, fish_pos) =
+            (Pos { x: 0, y: 1 }, Pos { x: 2, y: 1 }, Pos { x: 4, y: 1 });
+        // hungry enough to go looking for food
+        let shark = || {
+            let mut shark = Species::Shark.create_new(None);
+            shark.set_stat("hunger", "10").unwrap();
+            shark
+        };
+        let fish = || Species::Fish.create_new(None);
+        let rock = || ConcreteDecorations::Rock.create_new(None);
+        let sees_prey = |testbed: &TestBed| {
+            let shark = testbed
+                .get_entity_at_pos(shark_pos)
+                .unwrap()
+                .animal()
+                .unwrap();
+            let ctx = testbed.sandbox.processing_context(shark_pos);
+            EatAction::new().is_valid(shark, &ctx, &testbed.sandbox.board)
+        };
+
+        let testbed =
+            TestBed::new_with_entities(3, 5, vec![(shark_pos, shark()), (fish_pos, fish())]);
+        assert!(sees_prey(&testbed));
+        let testbed = TestBed::new_with_entities(
+            3,
+            5,
+            vec![(shark_pos, shark()), (rock_pos, rock()), (fish_pos, fish())],
+        );
+        assert!(!sees_prey(&testbed));
+    }
+
+    #[test]
+    /// Anything chasing something that gets eaten out from under it should go straight after something else, instead
+    /// of carrying on towards wherever it was
+    fn verify_chasers_hand_off_lost_targets() {
+        // both out of reach to begin with
+        let (shark_pos, near, far) = (Pos { x: 5, y: 1 }, Pos { x: 2, y: 1 }, Pos { x: 9, y: 1 });
+        let mut shark = Species::Shark.create_new(None);
+        shark.set_stat("hunger", "10").unwrap();
+        let fish = || Species::Fish.create_new(None);
+        let mut testbed = TestBed::new_with_entities(
+            3,
+            10,
+            vec![(shark_pos, shark), (near, fish()), (far, fish())],
+        );
+        let (near_id, far_id) = (testbed.id_at(near), testbed.id_at(far));
+        let step = |testbed: &TestBed| {
+            let ctx = testbed.sandbox.processing_context(shark_pos);
+            testbed
+                .animal_at(shark_pos)
+                .get_desired_move(&ctx, &testbed.sandbox.board)
+                .unwrap()
+        };
+
+        let Some(AIConcreteBehaviors::Eating(chase)) = decide(&testbed, shark_pos) else {
+            panic!("The shark should have gone after a fish");
+        };
+        assert_eq!(chase.target(), Some(near_id));
+        testbed
+            .animal_at_mut(shark_pos)
+            .set_current_behavior(AIConcreteBehaviors::Eating(chase.clone()));
+        assert!(step(&testbed).x < shark_pos.x);
+
+        // something else gets to it first
+        testbed.sandbox.board.remove_entity(near).unwrap();
+        let ctx = testbed.sandbox.processing_context(shark_pos);
+        let shark = testbed.animal_at(shark_pos);
+        assert!(chase.lost_target(shark, shark_pos, &testbed.sandbox.board, &ctx.rules));
+        assert!(!chase.is_valid(shark, &ctx, &testbed.sandbox.board));
+        assert!(step(&testbed).x > shark_pos.x);
+        let Some(AIConcreteBehaviors::Eating(chase)) = decide(&testbed, shark_pos) else {
+            panic!("The shark should have gone after the other fish");
+        };
+        assert_eq!(chase.target(), Some(far_id));
+    }
+
+    #[test]
+    /// Bold fish will go for food with a shark nearby, where timid ones would rather go the extra distance for a safer
+    /// meal. Lazy ones would rather not bother with anything much
+    fn verify_personalities() {
+        let (shark, fish_pos, risky, safe) = (
+            Pos { x: 1, y: 0 },
+            Pos { x: 5, y: 0 },
+            Pos { x: 3, y: 0 },
+            Pos { x: 8, y: 0 },
+        );
+        let testbed = |boldness: &str| {
+            TestBed::builder(1, 12)
+                .with(shark, Species::Shark)
+                .with_stats(
+                    fish_pos,
+                    Species::Fish,
+                    &[
+                        ("hunger", "10"),
+                        ("boldness", boldness),
+                        ("laziness", "0"),
+                        ("sociability", "0"),
+                    ],
+                )
+                .with(risky, Species::KelpLeaf)
+                .with(safe, Species::KelpLeaf)
+                .build()
+        };
+        let meal = |testbed: &TestBed| match decide(testbed, fish_pos) {
+            Some(AIConcreteBehaviors::Eating(e)) => e.target(),
+            other => panic!("The fish should have gone for some kelp, not {other:?}"),
+        };
+        let bold = testbed("1");
+        assert_eq!(meal(&bold), Some(bold.id_at(risky)));
+        let timid = testbed("-1");
+        assert_eq!(meal(&timid), Some(timid.id_at(safe)));
+        let rules = timid.sandbox.rules();
+        assert!(
+            timid.animal_at(fish_pos).wariness(rules) > bold.animal_at(fish_pos).wariness(rules)
+        );
+
+        let mut lazy = testbed("0");
+        let weights = lazy.animal_at(fish_pos).behavior_weights(rules);
+        lazy.animal_at_mut(fish_pos)
+            .body_mut()
+            .set_stat("laziness", "1")
+            .unwrap();
+        assert!(lazy.animal_at(fish_pos).behavior_weights(rules).idle > weights.idle);
+    }
+
+    #[test]
+    /// Sharks go after wounded prey before anything else they can see, and follow the trail when they can't see any
+    fn verify_sharks_follow_blood() {
+        let (shark_pos, healthy, wounded) =
+            (Pos { x: 0, y: 1 }, Pos { x: 3, y: 1 }, Pos { x: 7, y: 1 });
+        let mut testbed = TestBed::builder(3, 12)
+            .with_stats(shark_pos, Species::Shark, &[("hunger", "10")])
+            .with(healthy, Species::Fish)
+            .with_stats(wounded, Species::Fish, &[("wounded", "true")])
+            .build();
+        testbed.sandbox.handle_scent();
+        let Some(AIConcreteBehaviors::Eating(chase)) = decide(&testbed, shark_pos) else {
+            panic!("The shark should have gone after a fish");
+        };
+        assert_eq!(chase.target(), Some(testbed.id_at(wounded)));
+
+        // the fish are gone, but the blood's still in the water
+        testbed.sandbox.board.remove_entity(healthy).unwrap();
+        testbed.sandbox.board.remove_entity(wounded).unwrap();
+        let Some(AIConcreteBehaviors::Eating(chase)) = decide(&testbed, shark_pos) else {
+            panic!("The shark should have followed the trail");
+        };
+        assert_eq!(chase.target(), None);
+        testbed
+            .animal_at_mut(shark_pos)
+            .set_current_behavior(AIConcreteBehaviors::Eating(chase));
+        let ctx = testbed.sandbox.processing_context(shark_pos);
+        let step = testbed
+            .animal_at(shark_pos)
+            .get_desired_move(&ctx, &testbed.sandbox.board)
+            .unwrap();
+        assert!(step.x > shark_pos.x);
+
+        // nothing else can smell it
+        let fish = Species::Fish.create_new(None);
+        let fish = fish.animal().unwrap();
+        assert_eq!(
+            EatAction::scent_trail(fish, shark_pos, &testbed.sandbox.board),
+            None
+        );
+    }
+
+    #[test]
+    /// Fish next to a big enough patch of kelp can only be spotted up close, and heal up quicker while they're there
+    fn verify_fish_shelter_in_kelp_forests() {
+        let (shark_pos, fish_pos) = (Pos { x: 0, y: 2 }, Pos { x: 4, y: 1 });
+        let mut shark = Species::Shark.create_new(None);
+        shark.set_stat("hunger", "10").unwrap();
+        let mut fish = Species::Fish.create_new(None);
+        fish.set_stat("hp", "50").unwrap();
+        let mut entities = vec![(shark_pos, shark), (fish_pos, fish)];
+        entities.extend((2..8).map(|x| (Pos { x, y: 0 }, ConcretePlants::Kelp.create_new(None))));
+        let mut testbed = TestBed::new_with_entities(3, 8, entities);
+        let sees_prey = |testbed: &TestBed| {
+            let shark = testbed
+                .get_entity_at_pos(shark_pos)
+                .unwrap()
+                .animal()
+                .unwrap();
+            let ctx = testbed.sandbox.processing_context(shark_pos);
+            EatAction::new().is_valid(shark, &ctx, &testbed.sandbox.board)
+        };
+
+        testbed.sandbox.handle_forests();
+        assert!(testbed.sandbox.board.in_forest(fish_pos));
+        assert!(!sees_prey(&testbed));
+        let fish_hp = |testbed: &TestBed| {
+            testbed
+                .get_entity_at_pos(fish_pos)
+                .unwrap()
+                .health()
+                .unwrap()
+                .hp
+        };
+        assert_eq!(fish_hp(&testbed), 50 + FOREST_HEALING);
+
+        // one less kelp, and it's just a patch
+        testbed.sandbox.board.remove_entity(Pos { x: 7, y: 0 });
+        testbed.sandbox.handle_forests();
+        assert!(!testbed.sandbox.board.in_forest(fish_pos));
+        assert!(sees_prey(&testbed));
+        assert_eq!(fish_hp(&testbed), 50 + FOREST_HEALING);
+    }
+
+    #[test]
+    /// Anything on the run heads for somewhere its hunter can't see it
+    fn verify_fleeing_breaks_line_of_sight() {
+        let (shark_pos, rock_pos, fish_pos) =
+            (Pos { x: 0, y: 1 }, Pos { x: 2, y: 1 }, Pos { x: 2, y: 0 });
+        let mut fish = Species::Fish.create_new(None);
+        fish.animal_mut().unwrap().body_mut().combat.fleeing = 3;
+        let testbed = TestBed::new_with_entities(
+            2,
+            5,
+            vec![
+                (shark_pos, Species::Shark.create_new(None)),
+                (rock_pos, ConcreteDecorations::Rock.create_new(None)),
+                (fish_pos, fish),
+            ],
+        );
+        let board = &testbed.sandbox.board;
+        let fish = testbed
+            .get_entity_at_pos(fish_pos)
+            .unwrap()
+            .animal()
+            .unwrap();
+        let hiding_spot = fish
+            .get_desired_move(&testbed.sandbox.processing_context(fish_pos), board)
+            .unwrap();
+        assert_eq!(hiding_spot, Pos { x: 3, y: 1 });
+        assert!(!board.line_of_sight(shark_pos, hiding_spot));
+    }
+
+    #[test]
+    /// A pair has to court side by side for a few ticks before they'll mate, and a predator turning up puts a stop to it
+    fn verify_courtship() {
+        let (female_pos, male_pos, shark_pos) =
+            (Pos { x: 0, y: 0 }, Pos { x: 1, y: 0 }, Pos { x: 4, y: 0 });
+        let fish = |sex| {
+            let mut fish = Species::Fish.create_new(None);
+            let r = fish.reproduction_mut().unwrap();
+            r.sex = sex;
+            r.ticks_since_last_mating = r.mating_cooldown;
+            fish
+        };
+        // whether she's expecting after courting for a while
+        let court = |testbed: &mut TestBed, ticks: usize| {
+            let ctx = testbed.sandbox.processing_context(female_pos);
+            let board = &mut testbed.sandbox.board;
+            let mut female = board.remove_entity(female_pos).unwrap();
+            let mut courtship = MateAction::new();
+            for _ in 0..ticks {
+                courtship.tick(female.animal_mut().unwrap(), &ctx, board);
+            }
+            let pregnant = female.animal().unwrap().is_pregnant();
+            board.place_entity(female_pos, female).unwrap();
+            pregnant
+        };
+
+        let pair = || vec![(female_pos, fish(Sex::Female)), (male_pos, fish(Sex::Male))];
+        let mut testbed = TestBed::new_with_entities(1, 8, pair());
+        assert!(!court(&mut testbed, COURTSHIP_TICKS - 1));
+        assert!(court(&mut testbed, COURTSHIP_TICKS));
+
+        let mut entities = pair();
+        entities.push((shark_pos, Species::Shark.create_new(None)));
+        let mut testbed = TestBed::new_with_entities(1, 8, entities);
+        assert!(!court(&mut testbed, COURTSHIP_TICKS));
+    }
+
+    #[test]
+    /// Anything getting hungry stops chasing after a mate to go and find food, sooner the more hurt it is
+    fn verify_hunger_outweighs_mating() {
+        let (female_pos, male_pos, food_pos) =
+            (Pos { x: 0, y: 0 }, Pos { x: 1, y: 0 }, Pos { x: 5, y: 0 });
+        let female = |hunger: &str, hp: &str| {
+            let mut fish = Species::Fish.create_new(None);
+            let r = fish.reproduction_mut().unwrap();
+            r.sex = Sex::Female;
+            r.ticks_since_last_mating = r.mating_cooldown;
+            fish.set_stat("hunger", hunger).unwrap();
+            fish.set_stat("hp", hp).unwrap();
+            fish
+        };
+        let male = || {
+            let mut fish = Species::Fish.create_new(None);
+            let r = fish.reproduction_mut().unwrap();
+            r.sex = Sex::Male;
+            r.ticks_since_last_mating = r.mating_cooldown;
+            fish
+        };
+        let decides = |hunger, hp| {
+            let testbed = TestBed::new_with_entities(
+                1,
+                8,
+                vec![
+                    (female_pos, female(hunger, hp)),
+                    (male_pos, male()),
+                    (food_pos, Species::KelpLeaf.create_new(None)),
+                ],
+            );
+            decide(&testbed, female_pos)
+        };
+
+        // a little peckish, she'd still rather court the male right next to her
+        assert!(matches!(
+            decides("40", "100"),
+            Some(AIConcreteBehaviors::Mating(_))
+        ));
+        // but not if she's been badly hurt
+        assert!(matches!(
+            decides("40", "20"),
+            Some(AIConcreteBehaviors::Eating(_))
+        ));
+        // and never while she's starving, however close he is
+        assert!(matches!(
+            decides("-10", "100"),
+            Some(AIConcreteBehaviors::Eating(_))
+        ));
+    }
+
+    #[test]
+    /// Food a long way off isn't worth the trip for anything that's only a bit hungry, but is once it's starving
+    fn verify_distant_food_only_worth_it_when_starving() {
+        let (fish_pos, near, far) = (Pos { x: 0, y: 0 }, Pos { x: 2, y: 0 }, Pos { x: 39, y: 0 });
+        let decides = |hunger: &str, food_pos| {
+            let mut fish = Species::Fish.create_new(None);
+            fish.set_stat("hunger", hunger).unwrap();
+            let testbed = TestBed::new_with_entities(
+                1,
+                40,
+                vec![
+                    (fish_pos, fish),
+                    (food_pos, Species::KelpLeaf.create_new(None)),
+                ],
+            );
+            decide(&testbed, fish_pos)
+        };
+
+        assert!(matches!(
+            decides("40", near),
+            Some(AIConcreteBehaviors::Eating(_))
+        ));
+        assert_eq!(decides("40", far), None);
+        assert!(matches!(
+            decides("-10", far),
+            Some(AIConcreteBehaviors::Eating(_))
+        ));
+    }
+
+    #[test]
+    /// Out of all the shortest ways to go, animals should pick the one that keeps them going the way they already were
+    fn verify_paths_carry_on_straight() {
+        let testbed = TestBed::new_with_entities(5, 7, vec![]);
+        let board = &testbed.sandbox.board;
+        let (start, goal) = (Pos { x: 3, y: 2 }, Pos { x: 6, y: 2 });
+        let first_step = |came_from| {
+            let path =
+                EatAction::find_smooth_path(start, board, |pos, _| pos == goal, came_from, (1, 1))
+                    .unwrap();
+            // going whichever way shouldn't make it any longer
+            assert_eq!(path.len(), 3, "{path:?}");
+            path[1]
+        };
+
+        assert_eq!(first_step(Some(Pos { x: 2, y: 2 })), Pos { x: 4, y: 2 });
+        assert_eq!(first_step(Some(Pos { x: 2, y: 1 })), Pos { x: 4, y: 3 });
+        // turning around is fine when that's the only way to get there
+        assert_eq!(first_step(Some(Pos { x: 4, y: 2 })).x, 4);
+    }
+
+    #[test]
+    /// Animals that can't get any closer to what they're after should give up and wander off for a bit
+    fn verify_stuck_animals_wander_off() {
+        let (fish_pos, food_pos) = (Pos { x: 0, y: 0 }, Pos { x: 9, y: 0 });
+        let wandering = |speed| {
+            let mut fish = Species::Fish.create_new(None);
+            fish.set_stat("hunger", "-10").unwrap();
+            let mobility = fish.mobility_mut().unwrap();
+            (mobility.max_x, mobility.max_y) = (speed, speed);
+            let mut testbed = TestBed::new_with_entities(
+                1,
+                10,
+                vec![
+                    (fish_pos, fish),
+                    (food_pos, Species::KelpLeaf.create_new(None)),
+                ],
+            );
+            // a storm blowing through would carry it along whether it could swim or not
+            testbed.sandbox.reseed(1);
+            let id = testbed.get_entity_at_pos(fish_pos).unwrap().get_id();
+            (0..=STUCK_TICKS)
+                .map(|_| {
+                    testbed.sandbox.tick();
+                    let (_, fish) = testbed
+                        .sandbox
+                        .board
+                        .iter_occupied()
+                        .find(|(_, e)| e.get_id() == id)
+                        .unwrap();
+                    fish.mobility().unwrap().progress.wander_ticks
+                })
+                .collect::<Vec<usize>>()
+        };
+
+        // the first tick is spent deciding to go after the food, and it's only stuck after that
+        let mut expected = vec![0; STUCK_TICKS];
+        expected.push(WANDER_TICKS);
+        assert_eq!(wandering(0), expected);
+        // anything that's getting somewhere carries on
+        assert!(wandering(1).iter().all(|ticks| *ticks == 0));
+    }
+
+    #[test]
+    /// Crabs are quicker going sideways than up and down, so they'd rather take the long way round than climb
+    fn verify_crabs_path_sideways() {
+        let rocks = [Pos { x: 4, y: 1 }, Pos { x: 4, y: 2 }];
+        let rocks = rocks
+            .iter()
+            .map(|pos| (*pos, ConcreteDecorations::Rock.create_new(None)))
+            .collect();
+        let testbed = TestBed::new_with_entities(5, 7, rocks);
+        let board = &testbed.sandbox.board;
+        let (start, goal) = (Pos { x: 1, y: 3 }, Pos { x: 5, y: 1 });
+        let path = |speeds| {
+            EatAction::find_smooth_path(start, board, |pos, _| pos == goal, None, speeds).unwrap()
+        };
+
+        // anything just as quick both ways cuts straight over the top
+        let over = [(1, 3), (2, 2), (3, 1), (4, 0)].map(|(x, y)| Pos { x, y });
+        assert_eq!(path((1, 1)), over);
+        assert_eq!(path((3, 3)), over);
+        // but it's quicker for a crab to go along the bottom, even though it's further
+        let crab = Species::Crab.create_new(None);
+        let (x, y) = crab.animal().unwrap().max_speeds();
+        assert!(x > y);
+        let under = path((x, y));
+        assert!(under.iter().all(|pos| pos.y >= 2), "{under:?}");
+        assert!(under.len() > over.len());
+    }
+
+    #[test]
+    /// Crabs chasing something down should get there quicker going sideways than up and down
+    fn verify_crabs_scuttle_sideways() {
+        let crab_pos = Pos { x: 0, y: 0 };
+        let step_towards = |food_pos| {
+            let mut crab = Species::Crab.create_new(None);
+            crab.set_stat("hunger", "40").unwrap();
+            let animal = crab.animal_mut().unwrap();
+            animal.set_current_behavior(AIConcreteBehaviors::Eating(EatAction::new()));
+            let testbed = TestBed::new_with_entities(
+                7,
+                7,
+                vec![
+                    (crab_pos, crab),
+                    (food_pos, Species::KelpLeaf.create_new(None)),
+                ],
+            );
+            let ctx = testbed.sandbox.processing_context(crab_pos);
+            let animal = testbed
+                .get_entity_at_pos(crab_pos)
+                .unwrap()
+                .animal()
+                .unwrap();
+            animal.get_desired_move(&ctx, &testbed.sandbox.board)
+        };
+
+        assert_eq!(step_towards(Pos { x: 6, y: 0 }), Some(Pos { x: 3, y: 0 }));
+        assert_eq!(step_towards(Pos { x: 0, y: 6 }), Some(Pos { x: 0, y: 1 }));
+        // heading off at an angle, it still makes the most of going sideways
+        assert_eq!(step_towards(Pos { x: 6, y: 3 }).map(|pos| pos.x), Some(3));
+    }
+
+    #[test]
+    /// Every wander style should get an idle animal about the way it says on the tin
+    fn verify_wander_styles() {
+        let (fish_pos, kelp) = (Pos { x: 1, y: 1 }, Pos { x: 5, y: 5 });
+        let mut testbed = TestBed::builder(8, 8)
+            .with(fish_pos, Species::Fish)
+            .with(kelp, Species::Kelp)
+            .current(Current::East)
+            .seed(1)
+            .build();
+        let ctx = testbed.sandbox.processing_context(fish_pos);
+        let mut fish = testbed.animal_at(fish_pos).clone();
+        let mut idle = IdleAction::new(false);
+        let board = &mut testbed.sandbox.board;
+        let wander = |idle: &IdleAction, style| idle.wander(style, &fish, &ctx, board);
+
+        assert_eq!(wander(&idle, WanderStyle::Still), None);
+        assert_eq!(wander(&idle, WanderStyle::Drift), Some(Pos { x: 2, y: 1 }));
+        // nowhere to patrol around until we've settled on somewhere
+        assert_eq!(wander(&idle, WanderStyle::Patrol), None);
+
+        // fish hover by default, which settles on the nearest plant
+        idle.tick(&mut fish, &ctx, board);
+        assert_eq!(idle.home(), Some(kelp));
+        // and anything too far from it only ever heads back in
+        let mut moved = false;
+        for _ in 0..20 {
+            if let Some(p) = idle.wander(WanderStyle::Hover, &fish, &ctx, board) {
+                assert!(board.distance(p, kelp) < board.distance(fish_pos, kelp));
+                moved = true;
+            }
+        }
+        assert!(moved);
+        assert!(board.distance(fish_pos, kelp) > HOVER_RADIUS);
+    }
+
+    #[test]
+    /// Sharks patrol by default, so one with nothing else to do should go round and round where it started
+    fn verify_sharks_patrol() {
+        let start = Pos { x: 4, y: 4 };
+        let mut testbed = TestBed::builder(9, 9).with(start, Species::Shark).build();
+        testbed.sandbox.reseed(1);
+        let id = testbed.id_at(start);
+        let mut visited = vec![];
+        for _ in 0..20 {
+            testbed.sandbox.tick();
+            let (pos, _) = testbed
+                .sandbox
+                .board
+                .iter_occupied()
+                .find(|(_, e)| e.get_id() == Some(id))
+                .unwrap();
+            visited.push(pos);
+        }
+        assert!(visited
+            .iter()
+            .all(|p| testbed.sandbox.board.distance(*p, start) <= PATROL_RADIUS));
+        // it made it all the way round at least once
+        for corner in [
+            Pos { x: 2, y: 2 },
+            Pos { x: 6, y: 2 },
+            Pos { x: 6, y: 6 },
+            Pos { x: 2, y: 6 },
+        ] {
+            assert!(visited
+                .iter()
+                .any(|p| testbed.sandbox.board.distance(*p, corner) <= 1));
+        }
+    }
+
+    #[test]
+    /// Sharks can lunge at anything a couple of tiles off and crabs have to be right next to it, but nothing can lunge
+    /// through cover or off the edge of the board
+    fn verify_eating_reach() {
+        let (corner, rock) = (Pos { x: 0, y: 0 }, Pos { x: 1, y: 1 });
+        let testbed = TestBed::builder(4, 4)
+            .with(corner, Species::Shark)
+            .with(rock, Species::Rock)
+            .build();
+        let board = &testbed.sandbox.board;
+        let shark = testbed.animal_at(corner);
+        assert_eq!(shark.reach(), 2);
+        assert!(shark.within_reach(corner, Pos { x: 2, y: 0 }, board));
+        assert!(shark.within_reach(corner, Pos { x: 0, y: 2 }, board));
+        assert!(!shark.within_reach(corner, Pos { x: 3, y: 0 }, board));
+        assert!(!shark.within_reach(corner, corner, board));
+        // the rock's in the way
+        assert!(!shark.within_reach(corner, Pos { x: 2, y: 2 }, board));
+        assert!(shark
+            .in_reach(corner, board)
+            .all(|p| board.is_valid_pos(p) && board.distance(corner, p) <= 2 && p != corner));
+
+        let crab = Species::Crab.create_new(None);
+        let crab = crab.animal().unwrap();
+        assert_eq!(crab.reach(), 1);
+        assert!(crab.within_reach(corner, Pos { x: 1, y: 0 }, board));
+        assert!(!crab.within_reach(corner, Pos { x: 2, y: 0 }, board));
+    }
+
+    #[test]
+    /// A shark tucked into the corner should still get at a fish two tiles off without having to move
+    fn verify_sharks_eat_at_range() {
+        let (shark_pos, fish_pos) = (Pos { x: 0, y: 0 }, Pos { x: 2, y: 0 });
+        let mut shark = Species::Shark.create_new(None);
+        shark.set_stat("hunger", "10").unwrap();
+        let mut fish = Species::Fish.create_new(None);
+        let mobility = fish.mobility_mut().unwrap();
+        (mobility.max_x, mobility.max_y) = (0, 0);
+        let mut testbed =
+            TestBed::new_with_entities(2, 4, vec![(shark_pos, shark), (fish_pos, fish)]);
+        testbed.sandbox.reseed(1);
+        let fish_id = testbed.id_at(fish_pos);
+
+        testbed.sandbox.tick();
+        assert!(testbed
+            .sandbox
+            .board
+            .entity(fish_id)
+            .is_none_or(|fish| fish.animal().unwrap().is_dead()));
+        assert!(testbed.animal_at(shark_pos).body().hunger.level > 10);
+    }
+
+    #[test]
+    /// Chases should stop as soon as whatever's being chased is in reach, even where the board runs out or the tiles
+    /// are hexes and the diagonals aren't next to each other
+    fn verify_chases_stop_in_reach() {
+        let chasing = |testbed: &mut TestBed, pos| {
+            testbed
+                .animal_at_mut(pos)
+                .set_current_behavior(AIConcreteBehaviors::Eating(EatAction::new()));
+            let ctx = testbed.sandbox.processing_context(pos);
+            testbed
+                .animal_at(pos)
+                .get_desired_move(&ctx, &testbed.sandbox.board)
+        };
+
+        // sharks stop a lunge away along the edge, then stay put
+        let fish_pos = Pos { x: 0, y: 0 };
+        let shark = |pos| {
+            let mut shark = Species::Shark.create_new(None);
+            shark.set_stat("hunger", "10").unwrap();
+            TestBed::new_with_entities(
+                1,
+                6,
+                vec![(pos, shark), (fish_pos, Species::Fish.create_new(None))],
+            )
+        };
+        let far = Pos { x: 5, y: 0 };
+        assert_eq!(chasing(&mut shark(far), far), Some(Pos { x: 2, y: 0 }));
+        let close = Pos { x: 2, y: 0 };
+        assert_eq!(chasing(&mut shark(close), close), None);
+
+        // crabs only ever stop right next to their food, wherever the hexes take them
+        let (mut crab_pos, leaf) = (Pos { x: 3, y: 0 }, Pos { x: 0, y: 2 });
+        let mut crab = Species::Crab.create_new(None);
+        crab.set_stat("hunger", "40").unwrap();
+        let mut testbed = TestBed::builder(4, 4)
+            .topology(Topology::Hex)
+            .with_entity(crab_pos, crab)
+            .with(leaf, Species::KelpLeaf)
+            .build();
+        for _ in 0..6 {
+            let Some(next) = chasing(&mut testbed, crab_pos) else {
+                break;
+            };
+            testbed.sandbox.board.move_entity(crab_pos, next).unwrap();
+            crab_pos = next;
+        }
+        let board = &testbed.sandbox.board;
+        assert!(testbed
+            .animal_at(crab_pos)
+            .within_reach(crab_pos, leaf, board));
+        assert_eq!(board.distance(crab_pos, leaf), 1);
+    }
+}
@@ -2,9 +2,24 @@
 mod tests {
     use std::{collections::HashSet, sync::Arc};
 
+    use rand::{rngs::StdRng, SeedableRng};
+
     use crate::{
-        entities::animals::ConcreteAnimals, entity_control::EntityManager, populate_board,
-        test_utils::TestBed, Board, Sandbox,
+        element_traits::{Lives, Reproducing},
+        entities::{
+            animals::ConcreteAnimals,
+            nonliving::{ConcreteDecorations, ConcreteHazards, BONE_NUTRIENTS},
+            plants::{ConcretePlants, SEED_DRIFT_TICKS},
+            Entity, NonAbstractTaxonomy, Species,
+        },
+        entity_control::{EntityManager, TrackedEntity},
+        game_board::{populate_board, Current, Topology},
+        game_events::EventTypes,
+        interaction_matrix::InteractionMatrix,
+        settings::Strictness,
+        setup::SetupError,
+        test_utils::TestBed,
+        Board, EntityID, Pos, Sandbox,
     };
 
     use crate::game_board::test_utils::get_positions_of_type;
@@ -20,7 +35,7 @@ mod tests {
             let (fish, crab, shark) = creature_count[i];
             let mut board = Board::new(row, col, Arc::clone(&em));
 
-            populate_board(&mut board, fish, crab, shark);
+            populate_board(&mut board, fish, crab, shark).unwrap();
 
             let fish_count = get_positions_of_type(&board, ConcreteAnimals::Fish);
             let crab_count = get_positions_of_type(&board, ConcreteAnimals::Crab);
@@ -33,12 +48,22 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
     fn invalid_generation() {
         let em = EntityManager::new();
-        let mut board = Board::new(0, 5, em);
+        let mut board = Board::new(0, 5, Arc::clone(&em));
+        assert!(matches!(
+            populate_board(&mut board, 0, 5, 0),
+            Err(SetupError::BadDimensions { .. })
+        ));
 
-        populate_board(&mut board, 0, 5, 0);
+        let mut board = Board::new(2, 2, em);
+        assert_eq!(
+            populate_board(&mut board, 3, 1, 1),
+            Err(SetupError::Overcrowded {
+                creatures: 5,
+                tiles: 4
+            })
+        );
     }
 
     #[test]
@@ -56,4 +81,400 @@ mod tests {
         };
         testbed.run_n_steps(100, true, true, true, true, check, |_| ());
     }
+
+    /// Make a testbed with a single fish, plus one entry pointing at an empty tile and one sharing the fish's tile.
+    fn corrupted_testbed() -> TestBed {
+        let fish_pos = Pos { x: 1, y: 1 };
+        let testbed = TestBed::new_populated(3, 3, vec![(fish_pos, ConcreteAnimals::Fish)]);
+        {
+            let mut em = testbed.sandbox.entity_context.write().unwrap();
+            em.update_position(EntityID::from(100), Some(Pos { x: 0, y: 0 }));
+            em.update_position(EntityID::from(101), Some(fish_pos));
+        }
+        testbed
+    }
+
+    #[test]
+    fn sanity_check_repairs_bookkeeping() {
+        let mut testbed = corrupted_testbed();
+        testbed.sandbox.settings.strictness = Strictness::Repair;
+        testbed.sandbox.sanity_check("corruption");
+
+        let em = testbed.sandbox.entity_context.read().unwrap();
+        let entries = em.get_active_entries();
+        assert_eq!(entries.len(), 1);
+        let fish_id = testbed
+            .get_entity_at_pos(Pos { x: 1, y: 1 })
+            .and_then(|fish| fish.get_id());
+        assert_eq!(entries.keys().next().copied(), fish_id);
+    }
+
+    #[test]
+    fn sanity_check_drops_entries_pointing_at_someone_else() {
+        let fish_pos = Pos { x: 1, y: 1 };
+        let mut testbed = TestBed::new_populated(3, 3, vec![(fish_pos, ConcreteAnimals::Fish)]);
+        testbed.sandbox.settings.strictness = Strictness::Repair;
+        let fish_id = testbed.get_entity_at_pos(fish_pos).and_then(|f| f.get_id());
+        // the fish is where it should be, but something long gone still thinks it's at the next tile over, where a rock is
+        let rock_pos = Pos { x: 2, y: 1 };
+        testbed
+            .sandbox
+            .board
+            .place_entity(rock_pos, Species::Rock.create_new(None))
+            .unwrap();
+        testbed
+            .sandbox
+            .entity_context
+            .write()
+            .unwrap()
+            .update_position(EntityID::from(100), Some(rock_pos));
+        testbed.sandbox.sanity_check("corruption");
+
+        let em = testbed.sandbox.entity_context.read().unwrap();
+        let ids: Vec<Option<EntityID>> =
+            em.get_active_entries().keys().map(|id| Some(*id)).collect();
+        assert_eq!(ids, vec![fish_id]);
+    }
+
+    #[test]
+    fn sanity_check_frees_lost_loans() {
+        let fish_pos = Pos { x: 1, y: 1 };
+        let mut testbed = TestBed::new_populated(3, 3, vec![(fish_pos, ConcreteAnimals::Fish)]);
+        testbed.sandbox.settings.strictness = Strictness::Repair;
+        // lent out and dropped on the floor, which should never happen
+        let _ = testbed.sandbox.board.lend(fish_pos).unwrap();
+        testbed.sandbox.sanity_check("lending");
+
+        assert!(!testbed
+            .sandbox
+            .board
+            .get_tile_from_pos(fish_pos)
+            .is_occupied());
+        assert!(testbed.sandbox.board.arena().is_empty());
+        let em = testbed.sandbox.entity_context.read().unwrap();
+        assert!(em.get_active_entries().is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn sanity_check_panics_when_strict() {
+        let mut testbed = corrupted_testbed();
+        testbed.sandbox.sanity_check("corruption");
+    }
+
+    #[test]
+    fn tick_reports_events_without_running_them() {
+        let mut testbed = TestBed::new_default(10, 10, 5, 2, 0);
+        testbed.sandbox.scripted_events.push((1, EventTypes::Party));
+
+        let report = testbed.sandbox.tick();
+        assert_eq!(report.tick, 1);
+        assert!(report.event.is_none());
+
+        let report = testbed.sandbox.tick();
+        assert_eq!(report.tick, 2);
+        let pending = report
+            .event
+            .expect("the scripted event should have gone off");
+        assert_eq!(pending.kind(), &EventTypes::Party);
+        testbed.sandbox.answer_event(pending, true);
+        assert!(testbed.sandbox.answered_event.is_some());
+        // answers get carried out on the next tick
+        testbed.sandbox.tick();
+        assert!(testbed.sandbox.answered_event.is_none());
+    }
+
+    #[test]
+    /// Anything that moves up next to a hazard gets hurt for it
+    fn verify_hazards_hurt() {
+        let fish_pos = Pos { x: 0, y: 0 };
+        let mut testbed = TestBed::new_with_entities(
+            1,
+            3,
+            vec![
+                (fish_pos, ConcreteAnimals::Fish.create_new(None)),
+                (Pos { x: 2, y: 0 }, ConcreteHazards::Vent.create_new(None)),
+            ],
+        );
+        assert_eq!(testbed.sandbox.board.hazard_damage_near(fish_pos), 0);
+        assert!(testbed.sandbox.board.near_hazard(Pos { x: 1, y: 0 }));
+
+        // the only place the fish can go is right next to the vent
+        for _ in 0..30 {
+            testbed.sandbox.tick();
+            let hurt = testbed
+                .sandbox
+                .board
+                .iter_occupied()
+                .find_map(|(_, e)| e.health())
+                .is_none_or(|h| h.hp < h.hp_max);
+            if hurt {
+                return;
+            }
+        }
+        panic!("the fish never got hurt");
+    }
+
+    #[test]
+    /// Anything moving more than a tile at a time stops short of whatever's in the way, rather than going through it
+    fn verify_moves_stop_at_obstacles() {
+        let (fish_pos, goal) = (Pos { x: 0, y: 0 }, Pos { x: 3, y: 0 });
+        let moved_to = |blocker: Option<Entity>| {
+            let mut entities = vec![(fish_pos, ConcreteAnimals::Fish.create_new(None))];
+            entities.extend(blocker.map(|b| (Pos { x: 2, y: 0 }, b)));
+            let mut testbed = TestBed::new_with_entities(2, 5, entities);
+            testbed.sandbox.try_move(fish_pos, goal);
+            let (pos, fish) = testbed
+                .sandbox
+                .board
+                .iter_occupied()
+                .find(|(_, e)| Species::of(e) == Species::Fish)
+                .unwrap();
+            let health = fish.health().unwrap();
+            (pos, health.hp < health.hp_max)
+        };
+
+        assert_eq!(moved_to(None), (goal, false));
+        let rock = ConcreteDecorations::Rock.create_new(None);
+        assert_eq!(moved_to(Some(rock)), (Pos { x: 1, y: 0 }, false));
+        let crab = ConcreteAnimals::Crab.create_new(None);
+        assert_eq!(moved_to(Some(crab)), (Pos { x: 1, y: 0 }, false));
+        // swimming into a vent on the way still hurts
+        let vent = ConcreteHazards::Vent.create_new(None);
+        assert_eq!(moved_to(Some(vent)), (Pos { x: 1, y: 0 }, true));
+
+        // on a hex board, the way there follows the hexes, so there's no squeezing past something between two of them
+        let (from, to) = (Pos { x: 0, y: 0 }, Pos { x: 2, y: 2 });
+        let mut testbed = TestBed::builder(3, 3)
+            .topology(Topology::Hex)
+            .with(from, Species::Fish)
+            .with(Pos { x: 0, y: 1 }, Species::Rock)
+            .build();
+        testbed.sandbox.try_move(from, to);
+        assert!(testbed.sandbox.board.entity_at(from).is_some());
+        assert!(testbed.sandbox.board.entity_at(to).is_none());
+    }
+
+    #[test]
+    /// Rocks hold up to a few knocks, but shatter into rubble once they've had enough
+    fn verify_shattering_rocks() {
+        let center = Pos { x: 1, y: 1 };
+        let mut testbed = TestBed::new_with_entities(
+            3,
+            3,
+            vec![
+                (center, ConcreteDecorations::Rock.create_new(None)),
+                (
+                    Pos { x: 0, y: 0 },
+                    ConcreteDecorations::Shell.create_new(None),
+                ),
+            ],
+        );
+        let mut rng = StdRng::seed_from_u64(4);
+        let board = &mut testbed.sandbox.board;
+        assert!(board.knock_rock(center, 1, &mut rng).is_empty());
+        assert!(board
+            .knock_rock(Pos { x: 0, y: 0 }, 10, &mut rng)
+            .is_empty());
+
+        let rubble = board.knock_rock(center, 2, &mut rng);
+        assert!(rubble.contains(&center));
+        assert!((2..=3).contains(&rubble.len()), "{rubble:?}");
+        for pos in rubble {
+            let entity = board.entity_at(pos).unwrap();
+            assert_eq!(Species::of(entity), Species::Rubble);
+        }
+    }
+
+    #[test]
+    /// Once there are as many of a species as the sea can hold, nobody of that species gets any more children
+    fn verify_population_cap() {
+        // 15% of forty tiles is room for six sharks, and 40% is room for sixteen fish
+        let mut builder = TestBed::builder(4, 10).with(Pos { x: 9, y: 3 }, Species::Fish);
+        // one more than that, since whoever's breeding is off the board while it does
+        for x in 0..7 {
+            builder = builder.with(Pos { x, y: 0 }, Species::Shark);
+        }
+        let mut testbed = builder.build();
+        let board = &mut testbed.sandbox.board;
+        let rules = InteractionMatrix::default();
+        assert_eq!(board.at_population_cap(Species::Shark, &rules), Some(6));
+        assert_eq!(board.at_population_cap(Species::Fish, &rules), None);
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut shark = board.remove_entity(Pos { x: 0, y: 0 }).unwrap();
+        let eggs =
+            shark
+                .animal_mut()
+                .unwrap()
+                .lay_eggs(board, Pos { x: 0, y: 0 }, &mut rng, &rules);
+        assert!(eggs.is_empty());
+
+        let mut fish = board.remove_entity(Pos { x: 9, y: 3 }).unwrap();
+        let eggs = fish
+            .animal_mut()
+            .unwrap()
+            .lay_eggs(board, Pos { x: 9, y: 3 }, &mut rng, &rules);
+        assert!(!eggs.is_empty());
+    }
+
+    #[test]
+    /// Kelp spreads right over shells and rubble, but not rocks
+    fn verify_kelp_overgrowth() {
+        let kelp_pos = Pos { x: 1, y: 0 };
+        let mut testbed = TestBed::new_with_entities(
+            1,
+            3,
+            vec![
+                (
+                    Pos { x: 0, y: 0 },
+                    ConcreteDecorations::Shell.create_new(None),
+                ),
+                (kelp_pos, ConcretePlants::Kelp.create_new(None)),
+                (
+                    Pos { x: 2, y: 0 },
+                    ConcreteDecorations::Rock.create_new(None),
+                ),
+            ],
+        );
+        let board = &mut testbed.sandbox.board;
+        let mut kelp = board.remove_entity(kelp_pos).unwrap();
+        let spread = kelp.plant_mut().unwrap().create_offspring(
+            board,
+            kelp_pos,
+            &mut StdRng::seed_from_u64(5),
+            &InteractionMatrix::default(),
+        );
+        assert_eq!(spread, vec![Pos { x: 0, y: 0 }]);
+        let seed = board.entity_at(Pos { x: 0, y: 0 }).unwrap();
+        assert_eq!(Species::of(seed), Species::KelpSeed);
+        let rock = board.entity_at(Pos { x: 2, y: 0 }).unwrap();
+        assert_eq!(Species::of(rock), Species::Rock);
+    }
+
+    #[test]
+    fn children_move_over_when_their_tile_is_held() {
+        let (mother_pos, held) = (Pos { x: 0, y: 0 }, Pos { x: 1, y: 1 });
+        let mut testbed = TestBed::new_populated(
+            3,
+            3,
+            vec![
+                (mother_pos, ConcreteAnimals::Fish),
+                (held, ConcreteAnimals::Fish),
+            ],
+        );
+        let board = &mut testbed.sandbox.board;
+        let mut mother = board.remove_entity(mother_pos).unwrap();
+        let lent = board.lend(held).unwrap();
+        let placed = mother
+            .animal_mut()
+            .unwrap()
+            .have_child(board, held, 0, &mut StdRng::seed_from_u64(0))
+            .unwrap();
+        board.give_back(lent);
+        // the closest free tile is the one the mother left
+        assert_eq!(placed, Some(mother_pos));
+        let egg = board.entity_at(mother_pos).unwrap();
+        assert_eq!(Species::of(egg), Species::Egg);
+    }
+
+    #[test]
+    /// New seeds float downstream for a while before rooting, and only start growing once they have
+    fn verify_seeds_drift_with_current() {
+        let mut testbed = TestBed::builder(1, 10)
+            .with(Pos { x: 0, y: 0 }, Species::KelpSeed)
+            .current(Current::East)
+            .build();
+        for _ in 0..SEED_DRIFT_TICKS + 3 {
+            testbed.sandbox.tick();
+        }
+        let rooted = Pos {
+            x: SEED_DRIFT_TICKS,
+            y: 0,
+        };
+        let seed = testbed.get_entity_at_pos(rooted).unwrap();
+        assert_eq!(Species::of(seed), Species::KelpSeed);
+        assert!(!seed.plant().unwrap().drifting());
+        assert!(seed.growth().unwrap().level > 0);
+
+        // with no current, they wander off in any direction
+        let center = Pos { x: 1, y: 1 };
+        let mut testbed = TestBed::builder(3, 3)
+            .with(center, Species::KelpSeed)
+            .seed(2)
+            .build();
+        testbed.sandbox.tick();
+        assert!(testbed.get_entity_at_pos(center).is_none());
+    }
+
+    #[test]
+    /// Plants up in the light grow faster than ones down on the seabed
+    fn verify_light_drives_growth() {
+        let (shallow, deep) = (Pos { x: 0, y: 0 }, Pos { x: 0, y: 9 });
+        let mut testbed = TestBed::new_with_entities(
+            10,
+            1,
+            vec![
+                (shallow, ConcretePlants::KelpLeaf.create_new(None)),
+                (deep, ConcretePlants::KelpLeaf.create_new(None)),
+            ],
+        );
+        for _ in 0..10 {
+            testbed.sandbox.tick();
+        }
+        let growth = |pos| {
+            testbed
+                .get_entity_at_pos(pos)
+                .unwrap()
+                .growth()
+                .unwrap()
+                .level
+        };
+        assert_eq!(growth(shallow), 10);
+        assert_eq!(growth(deep), 4);
+    }
+
+    #[test]
+    /// Dead crabs leave their shells behind and dead sharks leave bones, which crumble away into the plants around them
+    fn verify_remains() {
+        let (crab_pos, fish_pos, shark_pos, kelp_pos) = (
+            Pos { x: 0, y: 0 },
+            Pos { x: 2, y: 0 },
+            Pos { x: 4, y: 0 },
+            Pos { x: 4, y: 1 },
+        );
+        let mut testbed = TestBed::new_with_entities(
+            2,
+            5,
+            vec![
+                (crab_pos, Species::Crab.create_new(None)),
+                (fish_pos, Species::Fish.create_new(None)),
+                (shark_pos, Species::Shark.create_new(None)),
+                (kelp_pos, ConcretePlants::KelpLeaf.create_new(None)),
+            ],
+        );
+        for pos in [crab_pos, fish_pos, shark_pos] {
+            let animal = testbed.get_entity_at_pos_mut(pos).unwrap();
+            animal.animal_mut().unwrap().modify_health(-1000, "testing");
+        }
+        testbed.sandbox.tick();
+        let species_at = |testbed: &TestBed, pos| testbed.get_entity_at_pos(pos).map(Species::of);
+        assert_eq!(species_at(&testbed, crab_pos), Some(Species::Shell));
+        assert_eq!(species_at(&testbed, fish_pos), None);
+        assert_eq!(species_at(&testbed, shark_pos), Some(Species::Bones));
+
+        let progress = |testbed: &TestBed| {
+            testbed
+                .get_entity_at_pos(kelp_pos)
+                .unwrap()
+                .growth()
+                .unwrap()
+                .progress
+        };
+        let before = progress(&testbed);
+        while species_at(&testbed, shark_pos).is_some() {
+            testbed.sandbox.handle_scenery();
+        }
+        assert_eq!(progress(&testbed), before + BONE_NUTRIENTS);
+    }
 }
@@ -1,9 +1,27 @@
 #[cfg(test)]
 mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
     use crate::{
-        element_traits::{LifeStatus, Lives},
-        game_events::{self, Event},
+        commands::SandboxCommand,
+        element_traits::{LifeStatus, Lives, Reproducing},
+        entities::{
+            nonliving::FEEDER_INTERVAL, plants::INCUBATION_TICKS, NonAbstractTaxonomy, Sex, Species,
+        },
+        entity_control::TrackedEntity,
+        error::Error,
+        game_board::{Edge, Pos},
+        game_events::{self, Event, EventTypes},
+        interaction_matrix::InteractionMatrix,
+        interactions::{EatsCreatures, Mates},
+        missions::{Goal, MISSION_INTERVAL},
+        pollution::{OIL_DAMAGE, POLLUTION_DECAY, SPILL_RADIUS},
+        seasons::{AMBUSHERS, MIN_SCHOOL, SEASON_LENGTH},
+        settings::{Difficulty, SimulationSettings},
+        structures::{ANIMALS_PER_RESOURCE, REEF_NUTRIENTS, STARTING_RESOURCES},
         test_utils::TestBed,
+        weather::{Weather, STORM_TICKS},
         Sandbox,
     };
 
@@ -21,6 +39,14 @@ mod tests {
         // Get third event type (Party)
         let event = game_events::get_rand_event(2);
         assert_eq!(event.kind, game_events::EventTypes::Party);
+
+        // Get fourth event type (Vent Field)
+        let event = game_events::get_rand_event(3);
+        assert_eq!(event.kind, game_events::EventTypes::VentField);
+
+        // Get fifth event type (Cleanup)
+        let event = game_events::get_rand_event(4);
+        assert_eq!(event.kind, game_events::EventTypes::Cleanup);
     }
 
     #[test]
@@ -30,18 +56,45 @@ mod tests {
     }
 
     #[test]
-    fn verify_display() {
-        // Get first event type (Oil Spill)
-        let event = game_events::get_rand_event(0);
-        assert_eq!(event.get_event_display().len(), 540);
+    /// Verify every event has two labelled choices, and that what they're expected to do reads right
+    fn verify_prompts() {
+        for kind in game_events::EventTypes::ALL {
+            let prompt =
+                game_events::GameEvents { kind: kind.clone() }.get_prompt(Difficulty::Normal);
+            for choice in &prompt.choices {
+                assert!(!choice.label.starts_with("event."), "{}", kind.name());
+                assert!(!choice.outcome.starts_with("event."), "{}", kind.name());
+            }
+            assert!(
+                !prompt.choices[0].consequences.is_empty(),
+                "{}",
+                kind.name()
+            );
+        }
 
-        // Get second event type (Invasive Fish)
-        let event = game_events::get_rand_event(1);
-        assert_eq!(event.get_event_display().len(), 523);
+        // Running from invaders loses more plants the harder the game is
+        let run = |difficulty| {
+            game_events::get_rand_event(1)
+                .get_prompt(difficulty)
+                .choices[0]
+                .consequences[0]
+                .describe()
+        };
+        assert_eq!(run(Difficulty::Normal), "-67% plants");
+        assert_eq!(run(Difficulty::Easy), "-33% plants");
 
-        // Get third event type (Party)
-        let event = game_events::get_rand_event(2);
-        assert_eq!(event.get_event_display().len(), 396);
+        // Hiding from an oil spill still leaves the water polluted
+        let prompt = game_events::get_rand_event(0).get_prompt(Difficulty::Normal);
+        let [hide, carry_on] = &prompt.choices;
+        assert_eq!(hide.icon, "oil_spill.first");
+        assert_eq!(
+            hide.consequences[0].describe(),
+            "+100% pollution, 200 ticks"
+        );
+        assert_eq!(
+            carry_on.consequences[1].describe(),
+            "-30 health for anything caught in it"
+        );
     }
 
     #[test]
@@ -79,72 +132,13 @@ mod tests {
         // Get the simulation running
         testbed.run_n_steps_no_checks(20, false, true, true, false);
 
-        // Get initial data
-        let mut init_repo_rate = Vec::new();
-        for pos in testbed.sandbox.get_important_entities() {
-            match testbed
-                .sandbox
-                .board
-                .get_tile_from_pos(pos)
-                .get_entity()
-                .as_ref()
-                .unwrap()
-            {
-                crate::entities::Entity::Living(ent) => match ent {
-                    crate::entities::Living::Plants(p) => match p {
-                        crate::entities::plants::Plants::Kelp(p)
-                        | crate::entities::plants::Plants::KelpSeed(p)
-                        | crate::entities::plants::Plants::KelpLeaf(p) => {
-                            init_repo_rate.push(p.growth_level)
-                        }
-                    },
-                    crate::entities::Living::Animals(a) => match a {
-                        crate::entities::animals::Animals::Fish(a)
-                        | crate::entities::animals::Animals::Crab(a)
-                        | crate::entities::animals::Animals::Shark(a) => {
-                            init_repo_rate.push(a.ticks_since_last_mating)
-                        }
-                    },
-                },
-                crate::entities::Entity::NonLiving(_) => (),
-            }
-        }
         let event = game_events::get_rand_event(0);
         event.process_event(input, &mut testbed.sandbox);
-        // Get new data
-        let mut new_repo_rate = Vec::new();
-        for pos in testbed.sandbox.get_important_entities() {
-            match testbed
-                .sandbox
-                .board
-                .get_tile_from_pos(pos)
-                .get_entity()
-                .as_ref()
-                .unwrap()
-            {
-                crate::entities::Entity::Living(ent) => match ent {
-                    crate::entities::Living::Plants(p) => match p {
-                        crate::entities::plants::Plants::Kelp(p)
-                        | crate::entities::plants::Plants::KelpSeed(p)
-                        | crate::entities::plants::Plants::KelpLeaf(p) => {
-                            new_repo_rate.push(p.growth_level)
-                        }
-                    },
-                    crate::entities::Living::Animals(a) => match a {
-                        crate::entities::animals::Animals::Fish(a)
-                        | crate::entities::animals::Animals::Crab(a)
-                        | crate::entities::animals::Animals::Shark(a) => {
-                            new_repo_rate.push(a.ticks_since_last_mating)
-                        }
-                    },
-                },
-                crate::entities::Entity::NonLiving(_) => (),
-            }
-        }
-        // Verify the entities were affected accordingly
-        for i in 0..new_repo_rate.len() {
-            assert!(new_repo_rate[i] < init_repo_rate[i]);
-        }
+        // Verify the oil settled over a patch of the board, no bigger than a spill can reach
+        let polluted: Vec<(Pos, f64)> = testbed.sandbox.board.iter_polluted().collect();
+        assert!(!polluted.is_empty());
+        assert!(polluted.len() <= (2 * SPILL_RADIUS + 1).pow(2));
+        assert!(polluted.iter().all(|(_, level)| *level == 1.0));
     }
 
     fn verify_invasive_fish(input: bool) {
@@ -156,14 +150,7 @@ mod tests {
         let mut init_plant_num = 0;
         let mut init_animal_num = 0;
         for pos in testbed.sandbox.get_important_entities() {
-            match testbed
-                .sandbox
-                .board
-                .get_tile_from_pos(pos)
-                .get_entity()
-                .as_ref()
-                .unwrap()
-            {
+            match testbed.sandbox.board.entity_at(pos).unwrap() {
                 crate::entities::Entity::Living(ent) => match ent {
                     crate::entities::Living::Plants(p) => {
                         if p.get_life_status() == LifeStatus::Alive {
@@ -185,14 +172,7 @@ mod tests {
         let mut new_plant_num = 0;
         let mut new_animal_num = 0;
         for pos in testbed.sandbox.get_important_entities() {
-            match testbed
-                .sandbox
-                .board
-                .get_tile_from_pos(pos)
-                .get_entity()
-                .as_ref()
-                .unwrap()
-            {
+            match testbed.sandbox.board.entity_at(pos).unwrap() {
                 crate::entities::Entity::Living(ent) => match ent {
                     crate::entities::Living::Plants(p) => {
                         if p.get_life_status() == LifeStatus::Alive {
@@ -231,26 +211,12 @@ mod tests {
         let mut init_repo_rate = Vec::new();
         let mut init_hunger = Vec::new();
         for pos in testbed.sandbox.get_important_entities() {
-            match testbed
-                .sandbox
-                .board
-                .get_tile_from_pos(pos)
-                .get_entity()
-                .as_ref()
-                .unwrap()
-            {
-                crate::entities::Entity::Living(ent) => match ent {
-                    crate::entities::Living::Plants(_) => (),
-                    crate::entities::Living::Animals(a) => match a {
-                        crate::entities::animals::Animals::Fish(a)
-                        | crate::entities::animals::Animals::Crab(a)
-                        | crate::entities::animals::Animals::Shark(a) => {
-                            init_repo_rate.push(a.ticks_since_last_mating);
-                            init_hunger.push(a.hunger_level);
-                        }
-                    },
-                },
-                crate::entities::Entity::NonLiving(_) => (),
+            let entity = testbed.sandbox.board.entity_at(pos).unwrap();
+            if let Some(r) = entity.reproduction() {
+                init_repo_rate.push(r.ticks_since_last_mating);
+            }
+            if let Some(hunger) = entity.hunger() {
+                init_hunger.push(hunger.level);
             }
         }
         let event = game_events::get_rand_event(2);
@@ -259,26 +225,12 @@ mod tests {
         let mut new_repo_rate = Vec::new();
         let mut new_hunger = Vec::new();
         for pos in testbed.sandbox.get_important_entities() {
-            match testbed
-                .sandbox
-                .board
-                .get_tile_from_pos(pos)
-                .get_entity()
-                .as_ref()
-                .unwrap()
-            {
-                crate::entities::Entity::Living(ent) => match ent {
-                    crate::entities::Living::Plants(_) => (),
-                    crate::entities::Living::Animals(a) => match a {
-                        crate::entities::animals::Animals::Fish(a)
-                        | crate::entities::animals::Animals::Crab(a)
-                        | crate::entities::animals::Animals::Shark(a) => {
-                            new_repo_rate.push(a.ticks_since_last_mating);
-                            new_hunger.push(a.hunger_level);
-                        }
-                    },
-                },
-                crate::entities::Entity::NonLiving(_) => (),
+            let entity = testbed.sandbox.board.entity_at(pos).unwrap();
+            if let Some(r) = entity.reproduction() {
+                new_repo_rate.push(r.ticks_since_last_mating);
+            }
+            if let Some(hunger) = entity.hunger() {
+                new_hunger.push(hunger.level);
             }
         }
         // Verify the entities were affected accordingly
@@ -287,4 +239,438 @@ mod tests {
             assert!(new_hunger[i] < init_hunger[i]);
         }
     }
+
+    #[test]
+    /// Verify that nobody answering an event on a timer gets its default answer, rather than a random one
+    fn verify_event_timer() {
+        let prompt = game_events::get_rand_event(2).get_prompt(Difficulty::Normal);
+        assert_eq!(prompt.default_choice().label, "Maybe some other time...");
+
+        for timer in [0, 10] {
+            let mut testbed = TestBed::new_default(6, 6, 4, 0, 0);
+            testbed.sandbox.apply_settings(SimulationSettings {
+                event_timer: timer,
+                ..SimulationSettings::default()
+            });
+            let answers: Vec<bool> = (0..20)
+                .map(|seed| {
+                    testbed.sandbox.reseed(seed);
+                    testbed.sandbox.scripted_events =
+                        vec![(testbed.sandbox.clock, EventTypes::Party)];
+                    testbed.sandbox.run_headless(1, |_, _| ());
+                    testbed.sandbox.answered_event.as_ref().unwrap().1
+                })
+                .collect();
+            match timer {
+                0 => assert!(answers.contains(&false) && answers.contains(&true)),
+                _ => assert!(answers.iter().all(|answer| *answer)),
+            }
+        }
+    }
+
+    #[test]
+    /// Verify that vent fields open up on empty tiles, and that capping them keeps the field small
+    fn verify_vent_field() {
+        let count_vents = |sandbox: &Sandbox| {
+            sandbox
+                .board
+                .iter_occupied()
+                .filter(|(_, e)| Species::of(e) == Species::Vent)
+                .count()
+        };
+        let fish = Pos { x: 0, y: 0 };
+        let hunger = |testbed: &TestBed| {
+            testbed
+                .get_entity_at_pos(fish)
+                .unwrap()
+                .hunger()
+                .unwrap()
+                .level
+        };
+        // the first answer caps the vents, which leaves the colony hungrier
+        for (answer, max_vents, hungrier) in [(false, 9, true), (true, 25, false)] {
+            let entities = vec![(fish, Species::Fish.create_new(None))];
+            let mut testbed = TestBed::new_with_entities(20, 20, entities);
+            testbed.sandbox.reseed(3);
+            let init_hunger = hunger(&testbed);
+            let event = game_events::get_rand_event(3);
+            event.process_event(answer, &mut testbed.sandbox);
+            let vents = count_vents(&testbed.sandbox);
+            assert!(vents > 0 && vents <= max_vents, "{vents} vents opened up");
+            assert_eq!(hunger(&testbed) < init_hunger, hungrier);
+        }
+
+        // there's no room for any vents on a full board
+        let fish = (0..4)
+            .map(|i| (Pos { x: i % 2, y: i / 2 }, Species::Fish.create_new(None)))
+            .collect();
+        let mut testbed = TestBed::new_with_entities(2, 2, fish);
+        game_events::get_rand_event(3).process_event(false, &mut testbed.sandbox);
+        assert_eq!(count_vents(&testbed.sandbox), 0);
+    }
+
+    #[test]
+    /// A new season brings a school of fish in along one edge, which heads across to the other past some waiting sharks
+    fn verify_migrations() {
+        let count = |sandbox: &Sandbox, species| {
+            sandbox
+                .board
+                .iter_occupied()
+                .filter(|(_, e)| Species::of(e) == species)
+                .count()
+        };
+        let migrants = |sandbox: &Sandbox| {
+            sandbox
+                .board
+                .iter_occupied()
+                .filter(|(_, e)| e.mobility().is_some_and(|m| m.heading.is_some()))
+                .map(|(pos, _)| pos)
+                .collect::<Vec<_>>()
+        };
+
+        let mut testbed = TestBed::new_with_entities(5, 12, vec![]);
+        testbed.sandbox.reseed(4);
+        assert_eq!(testbed.sandbox.start_migration(Edge::West), MIN_SCHOOL);
+        assert!(migrants(&testbed.sandbox).iter().all(|pos| pos.x == 0));
+        let sharks: Vec<Pos> = testbed
+            .sandbox
+            .board
+            .iter_occupied()
+            .filter(|(_, e)| Species::of(e) == Species::Shark)
+            .map(|(pos, _)| pos)
+            .collect();
+        assert_eq!(sharks.len(), AMBUSHERS);
+        assert!(sharks.iter().all(|pos| pos.x == 5));
+
+        // without the sharks in the way, they all make it across and off the board
+        for pos in sharks {
+            testbed.sandbox.board.remove_entity(pos);
+        }
+        for _ in 0..20 {
+            testbed.sandbox.tick();
+        }
+        assert!(migrants(&testbed.sandbox).is_empty());
+        assert_eq!(count(&testbed.sandbox, Species::Fish), 0);
+
+        // and they come around on their own once a season
+        let mut testbed = TestBed::new_with_entities(5, 12, vec![]);
+        testbed.sandbox.clock = SEASON_LENGTH;
+        testbed.sandbox.tick();
+        assert_eq!(migrants(&testbed.sandbox).len(), MIN_SCHOOL);
+        assert_eq!(count(&testbed.sandbox, Species::Shark), AMBUSHERS);
+    }
+
+    #[test]
+    /// Storms batter plants out in the open but not ones tucked up against a rock, and blow over after a while
+    fn verify_storms() {
+        let (open, sheltered, rock) = (Pos { x: 0, y: 0 }, Pos { x: 5, y: 4 }, Pos { x: 5, y: 5 });
+        let mut testbed = TestBed::new_with_entities(
+            6,
+            6,
+            vec![
+                (open, Species::Kelp.create_new(None)),
+                (sheltered, Species::Kelp.create_new(None)),
+                (rock, Species::Rock.create_new(None)),
+            ],
+        );
+        testbed.sandbox.reseed(2);
+        let hp = |testbed: &TestBed, pos| {
+            testbed
+                .get_entity_at_pos(pos)
+                .and_then(|e| e.health())
+                .map_or(0, |h| h.hp)
+        };
+        let full = hp(&testbed, open);
+
+        testbed.sandbox.start_storm(STORM_TICKS);
+        testbed.sandbox.tick();
+        assert_eq!(
+            testbed.sandbox.board.get_weather(),
+            Weather::Storm(STORM_TICKS - 1)
+        );
+        for _ in 1..STORM_TICKS {
+            testbed.sandbox.tick();
+        }
+        assert_eq!(testbed.sandbox.board.get_weather(), Weather::Calm);
+        assert_eq!(hp(&testbed, sheltered), full);
+        assert!(hp(&testbed, open) < full);
+    }
+
+    #[test]
+    /// Oil spills foul the water and hurt anything caught in them, the pollution wears away plants until it clears up,
+    /// and crabs can scrub it up
+    fn verify_pollution() {
+        let (fish, kelp) = (Pos { x: 0, y: 0 }, Pos { x: 2, y: 2 });
+        // a spill reaches every tile on a board this small, wherever it lands
+        let mut testbed = TestBed::new_with_entities(
+            3,
+            3,
+            vec![
+                (fish, Species::Fish.create_new(None)),
+                (kelp, Species::Kelp.create_new(None)),
+            ],
+        );
+        testbed.sandbox.reseed(3);
+        let hp = |testbed: &TestBed, pos| {
+            testbed
+                .get_entity_at_pos(pos)
+                .and_then(|e| e.health())
+                .map_or((0, 0), |h| (h.hp, h.hp_max))
+        };
+        testbed.sandbox.spill_oil(true);
+        assert_eq!(testbed.sandbox.board.iter_polluted().count(), 9);
+        let (fish_hp, fish_max) = hp(&testbed, fish);
+        assert_eq!(fish_hp, fish_max - OIL_DAMAGE);
+
+        let (_, kelp_max) = hp(&testbed, kelp);
+        let mut ticks = 0;
+        while hp(&testbed, kelp).0 == kelp_max {
+            testbed.sandbox.tick();
+            ticks += 1;
+            assert!(ticks < 100, "the pollution never hurt the kelp");
+        }
+        let level = testbed
+            .sandbox
+            .board
+            .get_tile_from_pos(kelp)
+            .get_pollution();
+        assert!((level - (1.0 - ticks as f64 * POLLUTION_DECAY)).abs() < 1e-9);
+
+        testbed
+            .sandbox
+            .board
+            .place_entity(Pos { x: 1, y: 1 }, Species::Crab.create_new(None))
+            .unwrap();
+        assert_eq!(testbed.sandbox.scrub_pollution(), 9);
+        assert_eq!(testbed.sandbox.board.iter_polluted().count(), 0);
+    }
+
+    #[test]
+    /// Hiding from an oil spill keeps the animals out of harm's way, and lending out the crabs scrubs the water clean
+    fn verify_pollution_answers() {
+        let fish = Pos { x: 0, y: 0 };
+        // the first answer hides from the spill, the second carries on through it
+        for (answer, damage) in [(false, 0), (true, OIL_DAMAGE)] {
+            let entities = vec![(fish, Species::Fish.create_new(None))];
+            let mut testbed = TestBed::new_with_entities(3, 3, entities);
+            game_events::get_rand_event(0).process_event(answer, &mut testbed.sandbox);
+            let health = testbed.get_entity_at_pos(fish).unwrap().health().unwrap();
+            assert_eq!(health.hp, health.hp_max - damage);
+            assert_eq!(testbed.sandbox.board.iter_polluted().count(), 9);
+        }
+
+        let crab = Pos { x: 1, y: 1 };
+        let hunger = |testbed: &TestBed| {
+            testbed
+                .get_entity_at_pos(crab)
+                .unwrap()
+                .hunger()
+                .unwrap()
+                .level
+        };
+        // the first answer puts the crabs to work, the second leaves the pollution be
+        for (answer, scrubbed) in [(false, true), (true, false)] {
+            let entities = vec![(crab, Species::Crab.create_new(None))];
+            let mut testbed = TestBed::new_with_entities(3, 3, entities);
+            testbed.sandbox.spill_oil(false);
+            let init_hunger = hunger(&testbed);
+            game_events::get_rand_event(4).process_event(answer, &mut testbed.sandbox);
+            assert_eq!(testbed.sandbox.board.iter_polluted().count() == 0, scrubbed);
+            assert_eq!(hunger(&testbed) < init_hunger, scrubbed);
+        }
+    }
+
+    #[test]
+    /// Structures cost resources to build, which the colony's animals gather, and each one does its own thing once it's down
+    fn verify_structures() {
+        let (barrier, reef, kelp, feeder) = (
+            Pos { x: 0, y: 0 },
+            Pos { x: 4, y: 0 },
+            Pos { x: 4, y: 2 },
+            Pos { x: 0, y: 4 },
+        );
+        let mut testbed =
+            TestBed::new_with_entities(5, 5, vec![(kelp, Species::Kelp.create_new(None))]);
+        let sandbox = &mut testbed.sandbox;
+        assert_eq!(sandbox.get_resources(), STARTING_RESOURCES);
+        assert!(matches!(
+            sandbox.build(reef, Species::Reef),
+            Err(Error::CantAfford { .. })
+        ));
+        assert!(matches!(
+            sandbox.build(reef, Species::Fish),
+            Err(Error::NotBuildable(_))
+        ));
+        assert_eq!(sandbox.build(barrier, Species::Barrier).unwrap(), 60);
+        assert!(sandbox.board.blocks_sharks(Pos { x: 1, y: 1 }));
+        assert!(!sandbox.board.blocks_sharks(Pos { x: 2, y: 2 }));
+
+        sandbox.resources = 250;
+        sandbox.build(reef, Species::Reef).unwrap();
+        sandbox.build(feeder, Species::Feeder).unwrap();
+        assert_eq!(sandbox.get_resources(), 0);
+
+        let leaves = |sandbox: &Sandbox| {
+            sandbox
+                .board
+                .iter_occupied()
+                .filter(|(_, e)| Species::of(e) == Species::KelpLeaf)
+                .count()
+        };
+        for _ in 1..FEEDER_INTERVAL {
+            sandbox.handle_structures();
+        }
+        assert_eq!(leaves(sandbox), 0);
+        sandbox.handle_structures();
+        assert_eq!(leaves(sandbox), 1);
+        let growth = sandbox.board.entity_at(kelp);
+        let progress = growth.and_then(|e| e.growth()).unwrap().progress;
+        assert!((progress - FEEDER_INTERVAL as f64 * REEF_NUTRIENTS).abs() < 1e-9);
+
+        // nothing's been living here to gather anything, until now
+        assert_eq!(sandbox.get_resources(), 0);
+        for x in 0..ANIMALS_PER_RESOURCE {
+            sandbox
+                .board
+                .place_entity(Pos { x, y: 1 }, Species::Fish.create_new(None))
+                .unwrap();
+        }
+        sandbox.handle_structures();
+        assert_eq!(sandbox.get_resources(), 1);
+    }
+
+    #[test]
+    /// Missions get handed out every so often, and pay out resources when they're done. One handed out during a storm
+    /// lasts until it blows over.
+    fn verify_missions() {
+        let mut testbed = TestBed::new_with_entities(
+            3,
+            3,
+            vec![(Pos { x: 1, y: 1 }, Species::Shark.create_new(None))],
+        );
+        let sandbox = &mut testbed.sandbox;
+        sandbox.clock = MISSION_INTERVAL - 1;
+        sandbox.handle_missions();
+        assert!(sandbox.missions.get_current().is_none());
+
+        sandbox.start_storm(5);
+        sandbox.clock = MISSION_INTERVAL;
+        sandbox.handle_missions();
+        let mission = sandbox.missions.get_current().unwrap().clone();
+        assert_eq!(
+            mission.goal,
+            Goal::Survive {
+                species: Species::Shark,
+                count: 1
+            }
+        );
+        assert_eq!(mission.deadline, MISSION_INTERVAL + 5);
+
+        let resources = sandbox.get_resources();
+        sandbox.clock = mission.deadline;
+        sandbox.handle_missions();
+        assert_eq!(sandbox.get_resources(), resources + mission.goal.reward());
+        assert!(sandbox.console_log[0].starts_with("Mission complete"));
+        assert!(sandbox.get_mission_summary()[0].starts_with("✅"));
+    }
+
+    #[test]
+    /// A child knows both of its parents from the moment it's laid, and the family tree picks it up once it's hatched
+    fn verify_family_tree() {
+        let (mother_pos, father_pos) = (Pos { x: 0, y: 0 }, Pos { x: 1, y: 0 });
+        let mut parents = vec![];
+        for (pos, sex) in [(mother_pos, Sex::Female), (father_pos, Sex::Male)] {
+            let mut fish = Species::Fish.create_new(None);
+            let r = fish.reproduction_mut().unwrap();
+            r.sex = sex;
+            r.ticks_since_last_mating = r.mating_cooldown;
+            parents.push((pos, fish));
+        }
+        let mut testbed = TestBed::new_with_entities(2, 2, parents);
+        let board = &mut testbed.sandbox.board;
+        let mut mother = board.remove_entity(mother_pos).unwrap();
+        let father = board.entity_at_mut(father_pos).unwrap();
+        let mother_animal = mother.animal_mut().unwrap();
+        let rules = InteractionMatrix::default();
+        mother_animal.mate(father.animal_mut().unwrap(), &rules);
+        let (mother_id, father_id) = (mother_animal.get_id().unwrap(), father.get_id().unwrap());
+        while !mother_animal.ready_to_reproduce() {
+            mother_animal.process_mating();
+        }
+        let born = mother_animal.create_offspring(
+            board,
+            mother_pos,
+            &mut StdRng::seed_from_u64(0),
+            &rules,
+        );
+        assert_eq!(born.len(), 1);
+        board.place_entity(mother_pos, mother).unwrap();
+        // the egg's just about to hatch
+        let egg = board.entity_at_mut(born[0]).unwrap();
+        egg.set_stat("growth", &(INCUBATION_TICKS - 1).to_string())
+            .unwrap();
+        let child_id = egg.get_id().unwrap();
+
+        testbed.sandbox.tick();
+        let child = testbed.get_entity_at_pos(born[0]).unwrap();
+        assert_eq!(child.get_id(), Some(child_id));
+        assert_eq!(
+            child.reproduction().unwrap().parents,
+            vec![mother_id, father_id]
+        );
+        let lineage = testbed.sandbox.get_lineage();
+        assert_eq!(lineage.get(father_id).unwrap().children, vec![child_id]);
+        assert_eq!(lineage.longest_dynasties(1)[0].1, 1);
+        let family = testbed
+            .sandbox
+            .apply_command(SandboxCommand::Family(child_id))
+            .unwrap();
+        assert!(family.ends_with(&format!(
+            "parents #{}, #{}; children none",
+            mother_id.get_id_val(),
+            father_id.get_id_val()
+        )));
+    }
+
+    #[test]
+    /// Animals lay eggs rather than giving birth, which hatch after a while unless something eats them first
+    fn verify_eggs() {
+        let mut mother = Species::Shark.create_new(None);
+        // she stays put, so the egg can only end up next to her
+        let mobility = mother.mobility_mut().unwrap();
+        (mobility.max_x, mobility.max_y) = (0, 0);
+        let shark = mother.animal_mut().unwrap();
+        shark.body_mut().reproduction.sex = Sex::Female;
+        shark.body_mut().reproduction.pregnant = true;
+        while !shark.ready_to_reproduce() {
+            shark.process_mating();
+        }
+        let (mother_pos, nest) = (Pos { x: 0, y: 0 }, Pos { x: 1, y: 0 });
+        let mut testbed = TestBed::new_with_entities(1, 2, vec![(mother_pos, mother)]);
+        testbed.sandbox.tick();
+        let egg = testbed.get_entity_at_pos(nest).unwrap();
+        assert_eq!(Species::of(egg), Species::Egg);
+        let egg_id = egg.get_id();
+
+        // anything hungry enough will go for it, and crabs always will
+        let crab = Species::Crab.create_new(None);
+        let rules = testbed.sandbox.rules();
+        assert!(crab.animal().unwrap().can_eat(egg.plant().unwrap(), rules));
+        let shark = testbed.get_entity_at_pos(mother_pos).unwrap();
+        assert!(!shark.animal().unwrap().can_eat(egg.plant().unwrap(), rules));
+        assert!(!shark.animal().unwrap().is_pregnant());
+
+        // left alone, it hatches into a shark of its own
+        testbed.sandbox.board.remove_entity(mother_pos);
+        for _ in 1..INCUBATION_TICKS - 1 {
+            testbed.sandbox.tick();
+        }
+        let egg = testbed.get_entity_at_pos(nest).unwrap();
+        assert_eq!(Species::of(egg), Species::Egg);
+        testbed.sandbox.tick();
+        let hatchling = testbed.get_entity_at_pos(nest).unwrap();
+        assert_eq!(Species::of(hatchling), Species::Shark);
+        assert_eq!(hatchling.get_id(), egg_id);
+        assert_eq!(hatchling.reproduction().unwrap().generation, 2);
+    }
 }
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, env, fs};
+
+    use crate::{
+        game_board::Topology,
+        pool::SimulationConfig,
+        setup::{Preset, SetupConfig},
+    };
+
+    /// What each golden run ended up as last time its digest was written down, one run to a line.
+    const GOLDEN: &str = include_str!("golden_runs.txt");
+
+    /// Set this to write out fresh digests, after changing how the game plays on purpose.
+    const UPDATE_VAR: &str = "UPDATE_GOLDEN";
+
+    /// Every golden run, as (name, setup, seed, ticks).
+    fn golden_runs() -> Vec<(&'static str, SetupConfig, u64, usize)> {
+        // none of these presets roll anything, so they always fill the board the same way
+        let preset = |preset: Preset, rows, cols| {
+            let mut setup = SetupConfig::new(rows, cols);
+            preset.apply(&mut setup);
+            setup
+        };
+        let hex = SetupConfig {
+            topology: Topology::Hex,
+            ..preset(Preset::Balanced, 10, 10)
+        };
+        vec![
+            ("balanced", preset(Preset::Balanced, 12, 12), 1, 60),
+            (
+                "predator_heavy",
+                preset(Preset::PredatorHeavy, 12, 12),
+                2,
+                40,
+            ),
+            ("hex", hex, 3, 60),
+            ("long_run", preset(Preset::Balanced, 16, 16), 4, 150),
+        ]
+    }
+
+    #[test]
+    /// The same seeds should keep playing out exactly the same, unless the game's been changed on purpose
+    fn verify_golden_runs() {
+        let mut digests = vec![];
+        for (name, setup, seed, ticks) in golden_runs() {
+            let mut sandbox = SimulationConfig::new(setup, seed).build().unwrap();
+            sandbox.run_headless(ticks, |_, _| ());
+            digests.push((name, sandbox.digest().to_string()));
+        }
+
+        if env::var_os(UPDATE_VAR).is_some() {
+            let lines: Vec<String> = digests
+                .iter()
+                .map(|(name, digest)| format!("{name} {digest}\n"))
+                .collect();
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/golden_runs.txt");
+            fs::write(path, lines.concat()).unwrap();
+            return;
+        }
+        let golden: HashMap<&str, &str> = GOLDEN
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .collect();
+        let changed: Vec<String> = digests
+            .iter()
+            .filter(|(name, digest)| golden.get(name) != Some(&digest.as_str()))
+            .map(|(name, digest)| {
+                let was = golden.get(name).unwrap_or(&"nothing");
+                format!("{name}: was {was}, now {digest}")
+            })
+            .collect();
+        assert!(
+            changed.is_empty(),
+            "Golden runs played out differently, rerun with {UPDATE_VAR}=1 if that was meant to happen:\n{}",
+            changed.join("\n")
+        );
+    }
+}
@@ -1 +1,239 @@
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
 
+    use crate::{
+        element_traits::Lives,
+        entities::{
+            animals::Animals,
+            nonliving::{ConcreteDecorations, SHELL_DEFENSE_BONUS},
+            Entity, NonAbstractTaxonomy, Species,
+        },
+        game_board::Pos,
+        interaction_matrix::InteractionMatrix,
+        interactions::{
+            FightOutcome, Fights, DODGE_STAMINA, FLEE_TICKS, MAX_DODGE_CHANCE, MAX_STAMINA,
+            MISS_STAMINA, STAMINA_RECOVERY,
+        },
+        scent::SCENT_DECAY,
+        test_utils::TestBed,
+    };
+
+    fn animal(species: Species) -> Animals {
+        match species.create_new(None) {
+            Entity::Living(crate::entities::Living::Animals(a)) => a,
+            e => panic!("{e:?} isn't an animal"),
+        }
+    }
+
+    #[test]
+    /// A fish doesn't stand a chance against a shark, however the rolls go
+    fn verify_sharks_win_fights() {
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut shark = animal(Species::Shark);
+            let mut fish = animal(Species::Fish);
+            let outcome = shark.fight(&mut fish, &mut rng);
+            assert!(
+                matches!(
+                    outcome,
+                    FightOutcome::AttackerWon | FightOutcome::DefenderFled
+                ),
+                "seed {seed} went {outcome:?}"
+            );
+            assert!(shark.get_health() > 0);
+        }
+    }
+
+    #[test]
+    /// Every blow lands for something, no matter how tough the target
+    fn verify_damage_rolls() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let fish = animal(Species::Fish);
+        let crab = animal(Species::Crab);
+        for _ in 0..100 {
+            let damage = fish.roll_damage(&crab, &mut rng);
+            // fish hit for 38 at best, and crabs shrug off 30 of it
+            assert!((1..=8).contains(&damage), "{damage}");
+            let damage = crab.roll_damage(&fish, &mut rng);
+            assert!((20..=70).contains(&damage), "{damage}");
+        }
+    }
+
+    #[test]
+    /// Anything badly hurt runs from the fight instead of carrying on, and stays out of trouble for a while
+    fn verify_fleeing() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut shark = animal(Species::Shark);
+        let mut crab = animal(Species::Crab);
+        crab.modify_health(-120, "an earlier fight");
+        assert!(crab.should_flee());
+        shark.modify_health(-10, "an earlier fight");
+        assert!(!shark.should_flee());
+
+        // the crab only lives through the first blow if it runs straight after
+        if crab.fight(&mut shark, &mut rng) == FightOutcome::AttackerFled {
+            assert!(crab.fleeing());
+            assert_eq!(crab.body().combat.fleeing, FLEE_TICKS);
+        } else {
+            assert!(crab.is_dead());
+        }
+    }
+
+    #[test]
+    /// Crabs fight back when something goes to eat them, and the hunter only gets a meal if it wins
+    fn verify_crabs_fight_back() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut fish = animal(Species::Fish);
+        fish.body_mut().hunger.set(-30);
+        let mut crab = animal(Species::Crab);
+        let rules = InteractionMatrix::default();
+        while !fish.hunt(&mut crab, &mut rng, &rules) {}
+        // fish barely scratch crabs, so the fish had better have run for it
+        assert!(!crab.is_dead());
+        assert!(fish.is_dead() || fish.fleeing());
+        // and anything that ran for it is bleeding
+        assert!(fish.is_dead() || fish.wounded());
+        assert_eq!(fish.body().hunger.level, -30);
+
+        // sharks don't have any such trouble with fish
+        let mut shark = animal(Species::Shark);
+        let mut fish = animal(Species::Fish);
+        while !shark.hunt(&mut fish, &mut rng, &rules) {}
+        assert!(fish.is_dead());
+    }
+
+    #[test]
+    /// Quick, fresh prey can get out of the way of an attack, but every dodge wears it down and every miss wears down
+    /// whatever's after it
+    fn verify_dodging() {
+        let mut shark = animal(Species::Shark);
+        let mut fish = animal(Species::Fish);
+        let fresh = fish.dodge_chance(&shark);
+        assert!(fresh > 0.0 && fresh <= MAX_DODGE_CHANCE);
+        // crabs are too slow to get out of the way of much
+        assert!(animal(Species::Crab).dodge_chance(&shark) < fresh);
+
+        // a worn out shark is easier to get away from
+        shark.tire(MAX_STAMINA);
+        assert!(fish.dodge_chance(&shark) > fresh);
+        // and a worn out fish can't get away at all
+        fish.tire(MAX_STAMINA);
+        assert_eq!(fish.dodge_chance(&shark), 0.0);
+        assert_eq!(fish.stamina(), 0);
+
+        // keep going after a fresh fish until it gets away once
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut shark = animal(Species::Shark);
+        let rules = InteractionMatrix::default();
+        loop {
+            let mut fish = animal(Species::Fish);
+            if !shark.hunt(&mut fish, &mut rng, &rules) {
+                assert!(!fish.is_dead());
+                assert_eq!(fish.stamina(), MAX_STAMINA - DODGE_STAMINA);
+                assert_eq!(shark.stamina(), MAX_STAMINA - MISS_STAMINA);
+                break;
+            }
+            assert!(fish.is_dead());
+        }
+
+        // it all comes back with a bit of rest
+        for _ in 0..MISS_STAMINA.div_ceil(STAMINA_RECOVERY) {
+            shark.process_life_misc();
+        }
+        assert_eq!(shark.stamina(), MAX_STAMINA);
+    }
+
+    #[test]
+    /// Wounded animals leave a trail of blood behind them until they heal up, which fades away once they're gone
+    fn verify_wounds_bleed() {
+        let fish_pos = Pos { x: 0, y: 0 };
+        let mut testbed = TestBed::builder(1, 6)
+            .with_stats(
+                fish_pos,
+                Species::Fish,
+                &[("hp", "10"), ("wounded", "true")],
+            )
+            .build();
+        testbed.sandbox.tick();
+        let (fish_pos, _) = testbed.sandbox.board.iter_occupied().next().unwrap();
+        assert_eq!(
+            testbed
+                .sandbox
+                .board
+                .get_tile_from_pos(fish_pos)
+                .get_scent(),
+            1.0
+        );
+
+        // anything healed back up stops bleeding
+        let fish = testbed.animal_at_mut(fish_pos);
+        fish.modify_health(fish.body().health.hp_max, "a rest");
+        testbed.sandbox.tick();
+        let (fish_pos, fish) = testbed.sandbox.board.iter_occupied().next().unwrap();
+        assert!(!fish.animal().unwrap().wounded());
+
+        testbed.sandbox.board.remove_entity(fish_pos).unwrap();
+        for _ in 0..(1.0 / SCENT_DECAY).ceil() as usize {
+            testbed.sandbox.tick();
+        }
+        assert_eq!(testbed.sandbox.board.iter_scented().count(), 0);
+    }
+
+    #[test]
+    /// Two sharks of the same sex next to each other will come to blows before long
+    fn verify_territorial_disputes() {
+        let (left, right) = (Pos { x: 0, y: 0 }, Pos { x: 1, y: 0 });
+        // there's nowhere for them to go
+        let mut testbed = TestBed::builder(1, 2)
+            .with_stats(left, Species::Shark, &[("sex", "male")])
+            .with_stats(right, Species::Shark, &[("sex", "male")])
+            .seed(5)
+            .build();
+
+        let hurt = |testbed: &TestBed| {
+            [left, right].iter().any(|pos| {
+                testbed
+                    .get_entity_at_pos(*pos)
+                    .and_then(|e| e.health())
+                    .is_none_or(|h| h.hp < h.hp_max)
+            })
+        };
+        for _ in 0..40 {
+            testbed.sandbox.tick();
+            if hurt(&testbed) {
+                return;
+            }
+        }
+        panic!("the sharks never fought");
+    }
+
+    #[test]
+    /// Crabs pick up shells lying next to them and wear them, which makes them harder to hurt
+    fn verify_crabs_wear_shells() {
+        let (crab_pos, shell_pos) = (Pos { x: 0, y: 0 }, Pos { x: 1, y: 0 });
+        let mut testbed = TestBed::new_with_entities(
+            1,
+            2,
+            vec![
+                (crab_pos, Species::Crab.create_new(None)),
+                (shell_pos, ConcreteDecorations::Shell.create_new(None)),
+            ],
+        );
+        let bare_defense = animal(Species::Crab).defense();
+        testbed.sandbox.tick();
+
+        assert!(testbed.get_entity_at_pos(shell_pos).is_none());
+        let crab = testbed
+            .get_entity_at_pos(crab_pos)
+            .unwrap()
+            .animal()
+            .unwrap();
+        assert_eq!(crab.defense(), bare_defense + SHELL_DEFENSE_BONUS);
+        assert!(crab
+            .body()
+            .get_stats()
+            .ends_with(&format!("shell={SHELL_DEFENSE_BONUS}")));
+    }
+}
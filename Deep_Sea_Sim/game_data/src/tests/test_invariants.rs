@@ -0,0 +1,22 @@
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::test_utils::RandomGame;
+
+    /// How many ticks each random game gets run for.
+    const TICKS: usize = 60;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        /// Whatever the board looks like and however the dice land, the board and the entity manager should always agree
+        fn verify_invariants_hold(game in RandomGame::strategy(12)) {
+            let mut testbed = game.build();
+            if let Err(problem) = testbed.run_checked(TICKS) {
+                prop_assert!(false, "{problem}");
+            }
+        }
+    }
+}
@@ -0,0 +1,247 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        commands::SandboxCommand,
+        element_traits::Lives,
+        entities::{animals::Animals, Entity, Living, Species},
+        entity_control::{EntityID, TrackedEntity},
+        game_board::{Current, Pos, Topology},
+        game_events::EventTypes,
+        scenario::Scenario,
+        test_utils::TestBed,
+    };
+
+    const OVERFISHED_REEF: &str = include_str!("../../../scenarios/overfished_reef.txt");
+    const SHARK_INVASION: &str = include_str!("../../../scenarios/shark_invasion.txt");
+    const REEF_AND_OPEN_OCEAN: &str = include_str!("../../../scenarios/reef_and_open_ocean.txt");
+
+    #[test]
+    /// Make sure everything we ship actually loads
+    fn verify_shipped_scenarios() {
+        let reef: Scenario = OVERFISHED_REEF.parse().unwrap();
+        assert_eq!(reef.get_name(), "Overfished Reef");
+        assert_eq!(reef.dims(), (8, 12));
+        assert_eq!(reef.get_events(), &[(40, EventTypes::InvasiveFish)]);
+
+        let invasion: Scenario = SHARK_INVASION.parse().unwrap();
+        assert_eq!(invasion.dims(), (10, 10));
+        let sharks = invasion
+            .get_entities()
+            .iter()
+            .filter(|(_, e)| Species::of(e) == Species::Shark)
+            .count();
+        assert_eq!(sharks, 4);
+
+        let regions: Scenario = REEF_AND_OPEN_OCEAN.parse().unwrap();
+        let names: Vec<&str> = regions.get_regions().iter().map(|r| r.get_name()).collect();
+        assert_eq!(names, vec!["Reef", "Open Ocean"]);
+        assert_eq!(regions.dims(), (8, 8));
+        assert_eq!(regions.get_regions()[1].dims(), (12, 16));
+    }
+
+    #[test]
+    /// Every region gets built, and anything wandering onto a gateway comes out the other side
+    fn verify_regions() {
+        let scenario: Scenario = REEF_AND_OPEN_OCEAN.parse().unwrap();
+        let mut testbed = TestBed {
+            sandbox: scenario.build(1.0),
+        };
+        assert_eq!(testbed.sandbox.get_region_name(), "Reef");
+        assert_eq!(
+            testbed.sandbox.get_region_names(),
+            vec!["Open Ocean", "Reef"]
+        );
+
+        let gate = testbed.sandbox.get_gateways()[0].pos;
+        testbed
+            .sandbox
+            .apply_command(SandboxCommand::Spawn(gate, Species::Crab))
+            .unwrap();
+        assert_eq!(testbed.sandbox.handle_migration(), 1);
+        assert!(testbed.get_entity_at_pos(gate).is_none());
+
+        testbed
+            .sandbox
+            .apply_command(SandboxCommand::ViewRegion(String::from("Open Ocean")))
+            .unwrap();
+        let crab = testbed.get_entity_at_pos(Pos { x: 1, y: 5 }).unwrap();
+        assert_eq!(Species::of(crab), Species::Crab);
+
+        // saving should keep every region, starting with the one we're looking at
+        let saved = Scenario::capture(&testbed.sandbox, "Saved").to_string();
+        let loaded: Scenario = saved.parse().unwrap();
+        let names: Vec<&str> = loaded.get_regions().iter().map(|r| r.get_name()).collect();
+        assert_eq!(names, vec!["Open Ocean", "Reef"]);
+        assert_eq!(loaded.get_regions()[1].get_gateways().len(), 1);
+        assert_eq!(loaded.get_events(), &[(60, EventTypes::OilSpill)]);
+    }
+
+    #[test]
+    fn verify_bad_gateways() {
+        let errors = [
+            ("size 3 3\ngateway 0 0 0 0 nowhere", 2),
+            (
+                "region a\nsize 3 3\ngateway 0 0 9 9 b\nregion b\nsize 3 3",
+                3,
+            ),
+            ("region a\nsize 3 3\ngateway 0 0 0 0 a", 3),
+            (
+                "region a\nsize 3 3\ngateway 0 0 1 1 b\nregion b\nsize 3 3\ngateway 1 1 0 0 a",
+                3,
+            ),
+            ("region a\nsize 3 3\nregion a", 3),
+            ("region a\nsize 3 3\nregion b\nregion c", 4),
+        ];
+        for (scenario, line) in errors {
+            let err = scenario.parse::<Scenario>().unwrap_err();
+            assert_eq!(err.line, line, "{scenario}: {err}");
+        }
+    }
+
+    #[test]
+    /// Entities should land exactly where they're told, with their stats overridden
+    fn verify_placement_and_overrides() {
+        let scenario: Scenario =
+            "size 3 4\nmap\nr...\n....\n...k\nend\nentity shark 1 1 hunger=-10 hp=20"
+                .parse()
+                .unwrap();
+        let mut testbed = TestBed {
+            sandbox: scenario.build(1.0),
+        };
+
+        let rock = testbed.get_entity_at_pos(Pos { x: 0, y: 0 }).unwrap();
+        assert_eq!(Species::of(rock), Species::Rock);
+        let kelp = testbed.get_entity_at_pos(Pos { x: 3, y: 2 }).unwrap();
+        assert_eq!(Species::of(kelp), Species::Kelp);
+
+        match testbed.get_entity_at_pos_mut(Pos { x: 1, y: 1 }) {
+            Some(Entity::Living(Living::Animals(a))) => {
+                assert!(matches!(a, Animals::Shark(_)));
+                assert!(a.starving());
+                assert_eq!(a.get_health(), 20);
+            }
+            e => panic!("Expected a shark, found {e:?}"),
+        }
+
+        // only the living things should be tracked
+        assert_eq!(testbed.sandbox.get_important_entities().len(), 2);
+    }
+
+    #[test]
+    /// Saved IDs should be kept, and nothing new should ever be handed one of them
+    fn verify_saved_ids() {
+        let scenario: Scenario = "size 1 3\nentity fish 0 0 id=7\nentity crab 1 0"
+            .parse()
+            .unwrap();
+        let testbed = TestBed {
+            sandbox: scenario.build(1.0),
+        };
+        let id = |x| {
+            testbed
+                .get_entity_at_pos(Pos { x, y: 0 })
+                .unwrap()
+                .get_id()
+                .unwrap()
+        };
+        assert_eq!(id(0), EntityID::from(7));
+        assert!(id(1) > id(0));
+        assert!(Scenario::capture(&testbed.sandbox, "Saved")
+            .to_string()
+            .contains("entity fish 0 0 id=7 "));
+    }
+
+    #[test]
+    fn verify_scenario_errors() {
+        let bad_scenarios = [
+            ("entity fish 0 0", 1),                                      // no size yet
+            ("size 2 2\nentity fish 2 0", 2),                            // out of bounds
+            ("size 2 2\nentity fish 0 0\nentity crab 0 0", 3),           // overlapping
+            ("size 2 2\n\nentity fish 0 0 wings=2", 3),                  // no such stat
+            ("size 2 2\nmap\n..\n.x\nend", 4),                           // bad tile
+            ("size 2 2\nevent 5 tsunami", 2),                            // no such event
+            ("size 2 2\nentity fish 0 0 id=3\nentity crab 1 0 id=3", 3), // same id twice
+        ];
+        for (scenario, line) in bad_scenarios {
+            let err = scenario.parse::<Scenario>().unwrap_err();
+            assert_eq!(err.line, line, "{scenario:?} failed with {err}");
+        }
+    }
+
+    #[test]
+    /// Scripted events should go off on exactly the tick they were given
+    fn verify_scripted_events() {
+        let scenario: Scenario = SHARK_INVASION.parse().unwrap();
+        let mut testbed = TestBed {
+            sandbox: scenario.build(1.0),
+        };
+
+        testbed.sandbox.clock = 25;
+        let event = testbed.sandbox.handle_events().unwrap();
+        assert_eq!(event.kind, EventTypes::Party);
+
+        testbed.sandbox.clock = 75;
+        let event = testbed.sandbox.handle_events().unwrap();
+        assert_eq!(event.kind, EventTypes::OilSpill);
+        assert!(testbed.sandbox.scripted_events.is_empty());
+    }
+
+    #[test]
+    /// Saving a game and loading it back up should get us the same board
+    fn verify_capture_round_trip() {
+        let scenario: Scenario = OVERFISHED_REEF.parse().unwrap();
+        let mut sandbox = scenario.build(1.0);
+        sandbox.clock = 10;
+
+        let saved = Scenario::capture(&sandbox, "Saved").to_string();
+        let loaded: Scenario = saved.parse().unwrap();
+        assert_eq!(loaded.get_name(), "Saved");
+        assert_eq!(loaded.dims(), scenario.dims());
+        // the invasive fish were due on tick 40, which is now 30 ticks away
+        assert_eq!(loaded.get_events(), &[(30, EventTypes::InvasiveFish)]);
+
+        let describe = |s: &Scenario| {
+            let mut entities = s
+                .get_entities()
+                .iter()
+                .map(|(pos, e)| (pos.x, pos.y, Species::of(e), e.get_stats()))
+                .collect::<Vec<_>>();
+            entities.sort_by_key(|(x, y, _, _)| (*y, *x));
+            entities
+        };
+        assert_eq!(describe(&loaded), describe(&scenario));
+    }
+
+    #[test]
+    /// Hex boards should stay hex boards after a save and load
+    fn verify_hex_topology() {
+        let scenario: Scenario = "topology hex\nsize 3 3\nentity kelp 1 1".parse().unwrap();
+        assert_eq!(scenario.get_topology(), Topology::Hex);
+        let sandbox = scenario.build(1.0);
+        assert_eq!(sandbox.board.get_topology(), Topology::Hex);
+        assert_eq!(sandbox.board.neighbors(Pos::from((1, 1))).count(), 6);
+
+        let saved = Scenario::capture(&sandbox, "Saved").to_string();
+        let loaded: Scenario = saved.parse().unwrap();
+        assert_eq!(loaded.get_topology(), Topology::Hex);
+
+        let err = "size 3 3\ntopology triangle"
+            .parse::<Scenario>()
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    /// Currents are kept with the region they flow through
+    fn verify_currents() {
+        let scenario: Scenario = "size 3 3\ncurrent south".parse().unwrap();
+        let sandbox = scenario.build(1.0);
+        assert_eq!(sandbox.board.get_current(), Current::South);
+        let saved = Scenario::capture(&sandbox, "Saved").to_string();
+        assert!(saved.contains("current south"));
+
+        let err = "size 3 3\ncurrent sideways"
+            .parse::<Scenario>()
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+}
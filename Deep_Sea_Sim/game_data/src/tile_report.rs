@@ -0,0 +1,158 @@
+// Everything there is to know about one tile, gathered up from every layer the board keeps track of: what's on it, what
+// the water around it is like, how much light gets down to it, how much the reefs nearby feed whatever grows there, how
+// polluted it is, how strongly it smells of blood and which way the current carries things off of it. The GUI shows one
+// for whichever tile was clicked on, and `tile <x> <y>` prints one to the console.
+
+use std::fmt::Display;
+
+use crate::entities::Species;
+use crate::error::Error;
+use crate::game_board::{Board, Current, Pos};
+use crate::structures::{REEF_NUTRIENTS, REEF_RADIUS};
+
+/// What the water around a tile is like, for anything living there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Terrain {
+    /// Out in the open, with nothing to hide behind.
+    Open,
+    /// Tucked up against a rock or some kelp, out of the worst of a storm.
+    Sheltered,
+    /// In or right up against a kelp forest.
+    Forest,
+    /// Next to something that hurts anything that comes near it.
+    Hazardous,
+}
+
+impl Terrain {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Sheltered => "sheltered",
+            Self::Forest => "forest",
+            Self::Hazardous => "hazardous",
+        }
+    }
+}
+
+/// Everything going on at one tile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileReport {
+    pub pos: Pos,
+    /// Whatever's on the tile, if anything.
+    pub occupant: Option<Species>,
+    pub terrain: Terrain,
+    /// How many ticks of extra growth a plant here gets every tick from the reefs around it.
+    pub nutrients: f64,
+    /// How strongly the water here smells of blood, from 0 (not at all) to 1 (fresh).
+    pub scent: f64,
+    /// How polluted the water here is, from 0 (clean) to 1.
+    pub pollution: f64,
+    /// How much light reaches the tile. See Board::light_at.
+    pub light: f64,
+    /// Which way the water's flowing across the board.
+    pub current: Current,
+    /// The tile the current carries things on to from here, unless the water's still or it'd carry them off the board.
+    pub downstream: Option<Pos>,
+}
+
+impl Board {
+    /// Gather up everything going on at a tile.
+    pub fn tile_report(&self, pos: Pos) -> Result<TileReport, Error> {
+        if !self.is_valid_pos(pos) {
+            return Err(Error::OutOfBounds(pos));
+        }
+        let terrain = if self.near_hazard(pos) {
+            Terrain::Hazardous
+        } else if self.in_forest(pos) {
+            Terrain::Forest
+        } else if self.sheltered(pos) {
+            Terrain::Sheltered
+        } else {
+            Terrain::Open
+        };
+        let reefs = self
+            .positions_within(REEF_RADIUS, pos)
+            .filter(|p| {
+                self.entity_at(*p)
+                    .is_some_and(|e| Species::of(e) == Species::Reef)
+            })
+            .count();
+        let tile = self.get_tile_from_pos(pos);
+        let current = self.get_current();
+        Ok(TileReport {
+            pos,
+            occupant: self.entity_at(pos).map(Species::of),
+            terrain,
+            nutrients: reefs as f64 * REEF_NUTRIENTS,
+            scent: tile.get_scent(),
+            pollution: tile.get_pollution(),
+            light: self.light_at(pos),
+            current,
+            downstream: current.downstream(pos).filter(|p| self.is_valid_pos(*p)),
+        })
+    }
+}
+
+impl Display for TileReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?}: {}, {} water, light {:.2}, nutrients {:.2}, scent {:.2}, pollution {:.2}, current {}",
+            self.pos,
+            self.occupant.as_ref().map_or("nothing", Species::name),
+            self.terrain.name(),
+            self.light,
+            self.nutrients,
+            self.scent,
+            self.pollution,
+            self.current.name(),
+        )?;
+        if let Some(downstream) = self.downstream {
+            write!(f, " (on to {downstream:?})")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_tile_report() {
+        let mut testbed = TestBed::builder(5, 5)
+            .with(Pos { x: 1, y: 1 }, Species::Reef)
+            .with(Pos { x: 3, y: 3 }, Species::Rock)
+            .current(Current::East)
+            .build();
+        let board = &mut testbed.sandbox.board;
+        board
+            .get_tile_mut_from_pos(Pos { x: 2, y: 2 })
+            .set_pollution(0.25);
+        board
+            .get_tile_mut_from_pos(Pos { x: 2, y: 2 })
+            .set_scent(0.5);
+
+        let report = board.tile_report(Pos { x: 2, y: 2 }).unwrap();
+        assert_eq!(report.occupant, None);
+        assert_eq!(report.terrain, Terrain::Sheltered);
+        assert_eq!(report.nutrients, REEF_NUTRIENTS);
+        assert_eq!(report.scent, 0.5);
+        assert_eq!(report.pollution, 0.25);
+        assert_eq!(report.light, board.light_at(Pos { x: 2, y: 2 }));
+        assert_eq!(report.current, Current::East);
+        assert_eq!(report.downstream, Some(Pos { x: 3, y: 2 }));
+
+        // out of reach of the reef, and nowhere downstream to go
+        let corner = board.tile_report(Pos { x: 4, y: 0 }).unwrap();
+        assert_eq!(corner.terrain, Terrain::Open);
+        assert_eq!(corner.nutrients, 0.0);
+        assert_eq!(corner.downstream, None);
+        assert_eq!(
+            board.tile_report(Pos { x: 3, y: 3 }).unwrap().occupant,
+            Some(Species::Rock)
+        );
+        assert!(board.tile_report(Pos { x: 5, y: 0 }).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+// The whole history of a colony at a glance. While a timelapse is recording, the sandbox takes a screenshot every so many
+// ticks, and once it's stopped they're strung together into an animated PNG that plays in any browser. Long runs would
+// pile up a lot of frames, so once there are too many every other one gets thrown out and the gap between them doubles;
+// the timelapse stays the same length no matter how long the game goes on for.
+// Every frame has to be the same size, so anything taken while looking at a differently sized region gets left out.
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::screenshot::Screenshot;
+use crate::Sandbox;
+
+/// The most frames a timelapse holds on to before it starts thinning them out.
+pub const MAX_FRAMES: usize = 300;
+/// How long every frame is shown for when the timelapse plays, in milliseconds.
+const FRAME_DELAY_MS: u16 = 100;
+
+/// Screenshots taken every so many ticks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timelapse {
+    /// How many ticks apart the frames are.
+    every: usize,
+    frames: Vec<Screenshot>,
+}
+
+impl Timelapse {
+    /// Start a timelapse taking a frame every `every` ticks. Anything less than 1 is taken as 1.
+    pub fn new(every: usize) -> Self {
+        Self {
+            every: every.max(1),
+            frames: vec![],
+        }
+    }
+
+    pub fn every(&self) -> usize {
+        self.every
+    }
+
+    pub fn frames(&self) -> &[Screenshot] {
+        &self.frames
+    }
+
+    /// Take a frame, if one's due on this tick.
+    pub fn capture(&mut self, sandbox: &Sandbox) {
+        if sandbox.clock.checked_rem(self.every) != Some(0) {
+            return;
+        }
+        let shot = sandbox.screenshot();
+        if self
+            .frames
+            .first()
+            .is_some_and(|first| (first.width, first.height) != (shot.width, shot.height))
+        {
+            return;
+        }
+        self.frames.push(shot);
+        if self.frames.len() > MAX_FRAMES {
+            self.every *= 2;
+            let every = self.every;
+            self.frames
+                .retain(|frame| frame.tick.checked_rem(every) == Some(0));
+        }
+    }
+
+    /// What the timelapse gets saved as, going by the ticks it covers.
+    pub fn file_name(&self) -> String {
+        let first = self.frames.first().map_or(0, |f| f.tick);
+        let last = self.frames.last().map_or(0, |f| f.tick);
+        format!("timelapse_tick_{first}-{last}.png")
+    }
+
+    /// Write every frame out as an animated PNG, which loops forever.
+    pub fn write_apng(&self, writer: impl io::Write) -> io::Result<()> {
+        let Some(first) = self.frames.first() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the timelapse doesn't have any frames yet",
+            ));
+        };
+        let mut encoder = png::Encoder::new(writer, first.width as u32, first.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(self.frames.len() as u32, 0)?;
+        encoder.set_frame_delay(FRAME_DELAY_MS, 1000)?;
+        let mut writer = encoder.write_header()?;
+        for frame in &self.frames {
+            writer.write_image_data(&frame.pixels)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Save the timelapse into a folder, returning where it ended up.
+    pub fn save(&self, dir: &Path) -> io::Result<PathBuf> {
+        let path = dir.join(self.file_name());
+        self.write_apng(BufWriter::new(File::create(&path)?))?;
+        Ok(path)
+    }
+}
+
+impl Sandbox {
+    /// Start recording a timelapse, taking a frame every `every` ticks starting with this one.
+    /// Anything that was already being recorded is thrown away.
+    pub fn start_timelapse(&mut self, every: usize) {
+        let mut timelapse = Timelapse::new(every);
+        timelapse.frames.push(self.screenshot());
+        self.timelapse = Some(timelapse);
+    }
+
+    /// Stop recording, handing back whatever was recorded.
+    pub fn stop_timelapse(&mut self) -> Option<Timelapse> {
+        self.timelapse.take()
+    }
+
+    pub fn get_timelapse(&self) -> Option<&Timelapse> {
+        self.timelapse.as_ref()
+    }
+
+    /// Take the next frame of the timelapse, if we're recording and one's due.
+    pub fn record_timelapse(&mut self) {
+        if let Some(mut timelapse) = self.timelapse.take() {
+            timelapse.capture(self);
+            self.timelapse = Some(timelapse);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_timelapse() {
+        let mut sandbox = TestBed::new_default(4, 4, 2, 1, 0).sandbox;
+        sandbox.start_timelapse(3);
+        sandbox.run_headless(7, |_, _| ());
+        let timelapse = sandbox.stop_timelapse().unwrap();
+        let ticks: Vec<usize> = timelapse.frames().iter().map(|f| f.tick).collect();
+        assert_eq!(ticks, [0, 3, 6]);
+        assert_eq!(timelapse.file_name(), "timelapse_tick_0-6.png");
+        // nothing more gets taken once it's stopped
+        sandbox.run_headless(3, |_, _| ());
+        assert!(sandbox.get_timelapse().is_none());
+
+        let mut apng = vec![];
+        timelapse.write_apng(&mut apng).unwrap();
+        assert!(apng.starts_with(b"\x89PNG"));
+        assert!(apng.windows(4).any(|chunk| chunk == b"acTL"));
+        assert!(Timelapse::new(1).write_apng(vec![]).is_err());
+    }
+
+    #[test]
+    fn test_timelapse_thins_out() {
+        let mut sandbox = TestBed::new_default(2, 2, 0, 0, 0).sandbox;
+        sandbox.start_timelapse(1);
+        for _ in 0..MAX_FRAMES {
+            sandbox.clock += 1;
+            sandbox.record_timelapse();
+        }
+        let timelapse = sandbox.get_timelapse().unwrap();
+        assert_eq!(timelapse.every(), 2);
+        assert_eq!(timelapse.frames().len(), MAX_FRAMES / 2 + 1);
+        assert!(timelapse.frames().iter().all(|f| f.tick % 2 == 0));
+    }
+}
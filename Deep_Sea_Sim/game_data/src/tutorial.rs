@@ -0,0 +1,132 @@
+// The guided first run. The tutorial is a scenario like any other (see tutorial.txt), a small and quiet colony with one
+// event scheduled to go off early on, played with a handful of steps the player is walked through along the way:
+// pausing, reading the colony info panel, answering that event and reading the population graph. Whatever's showing the
+// game points out the part of the screen each step is about, and lets the tutorial know once the player's done it.
+//
+// Steps can be done in any order, since nothing stops a new player from clicking around, or the event from going off
+// before they've got to it. Whichever one hasn't been done yet and comes first is the one being shown, except that an
+// event showing up jumps straight to the step about events.
+
+use crate::scenario::Scenario;
+use crate::settings::{EventFrequency, SimulationSettings};
+
+/// The scenario the tutorial's played on.
+const TUTORIAL_SCENARIO: &str = include_str!("tutorial.txt");
+
+/// Something the tutorial walks the player through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Pausing the game.
+    Pause,
+    /// Picking an animal out of the colony info panel, to follow it around.
+    ColonyInfo,
+    /// Answering an event.
+    Event,
+    /// Looking over the population graph.
+    Graph,
+}
+
+impl Step {
+    pub const ALL: [Step; 4] = [Self::Pause, Self::ColonyInfo, Self::Event, Self::Graph];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Pause => "pause",
+            Self::ColonyInfo => "colony_info",
+            Self::Event => "event",
+            Self::Graph => "graph",
+        }
+    }
+}
+
+/// How far through the tutorial the player's got.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tutorial {
+    done: Vec<Step>,
+}
+
+impl Tutorial {
+    /// The step to show the player, if there's any left. An event that's waiting to be answered comes first.
+    pub fn current(&self, event_showing: bool) -> Option<Step> {
+        if event_showing && !self.done.contains(&Step::Event) {
+            return Some(Step::Event);
+        }
+        Step::ALL.into_iter().find(|step| !self.done.contains(step))
+    }
+
+    /// The player's done this, whether or not it's the step they were being shown.
+    pub fn complete(&mut self, step: Step) {
+        if !self.done.contains(&step) {
+            self.done.push(step);
+        }
+    }
+
+    /// How many steps have been done.
+    pub fn progress(&self) -> usize {
+        self.done.len()
+    }
+
+    /// Whether every step has been done.
+    pub fn finished(&self) -> bool {
+        self.current(false).is_none()
+    }
+}
+
+/// The scenario the tutorial's played on.
+pub fn scenario() -> Scenario {
+    TUTORIAL_SCENARIO
+        .parse()
+        .expect("the tutorial scenario should always load")
+}
+
+/// The player's settings, with anything that'd get in the way of the tutorial switched off: nothing but the tutorial's own
+/// event goes off, nothing puts a timer on answering it or puts it to a vote, and the player's autosave is left alone.
+pub fn settings(mut settings: SimulationSettings) -> SimulationSettings {
+    settings.event_frequency = EventFrequency::Never;
+    settings.event_timer = 0;
+    settings.event_voting = false;
+    settings.autosave_interval = 0;
+    settings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::Species;
+
+    #[test]
+    fn test_steps() {
+        let mut tutorial = Tutorial::default();
+        assert_eq!(tutorial.current(false), Some(Step::Pause));
+        tutorial.complete(Step::Pause);
+        assert_eq!(tutorial.current(false), Some(Step::ColonyInfo));
+        // an event jumps the queue, and doing anything early counts
+        assert_eq!(tutorial.current(true), Some(Step::Event));
+        tutorial.complete(Step::Graph);
+        tutorial.complete(Step::Graph);
+        assert_eq!(tutorial.progress(), 2);
+        tutorial.complete(Step::ColonyInfo);
+        assert_eq!(tutorial.current(false), Some(Step::Event));
+        assert!(!tutorial.finished());
+        tutorial.complete(Step::Event);
+        assert_eq!(tutorial.current(true), None);
+        assert!(tutorial.finished());
+    }
+
+    #[test]
+    fn test_tutorial_scenario() {
+        let scenario = scenario();
+        assert_eq!(scenario.get_name(), "Tutorial");
+        let mut sandbox = scenario.build(1.0);
+        sandbox.apply_settings(settings(SimulationSettings::default()));
+        assert_eq!(sandbox.census().count(Species::Shark), 1);
+        assert_eq!(sandbox.scripted_events.len(), 1);
+        // a quiet start, with nothing lost before the event's had a chance to go off
+        let before = sandbox.census().count(Species::Fish);
+        for _ in 0..20 {
+            sandbox.tick();
+        }
+        assert!(sandbox.census().count(Species::Fish) >= before);
+        assert_eq!(sandbox.census().count(Species::Crab), 2);
+    }
+}
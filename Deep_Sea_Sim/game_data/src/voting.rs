@@ -0,0 +1,133 @@
+// Letting an audience decide events. With event_voting turned on, every event that goes off is put to a vote instead of
+// being left to the player: anyone can vote for one of its choices with the console's vote command, whether they're
+// at the console or connected to the observation server (see server.rs), until the event's time runs out. Whichever
+// choice has the most votes by then wins, and a tie goes to the event's default answer. If nobody votes at all, the
+// event's answered the same way it would've been with nobody around to answer it.
+//
+// Everyone gets one vote per event. Voting again changes it.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::Sandbox;
+
+/// How many seconds an event's open for voting, when there's no event timer to go by.
+pub const VOTING_WINDOW: u64 = 30;
+
+/// The votes cast on an event so far, shared with everyone who wants to keep an eye on how it's going.
+#[derive(Clone, Default)]
+pub struct Tally(Arc<Mutex<HashMap<String, bool>>>);
+
+impl Tally {
+    /// Record someone's vote for the first choice (false) or the second (true), replacing any they'd cast already.
+    pub fn cast(&self, voter: &str, choice: bool) {
+        self.0.lock().unwrap().insert(voter.to_owned(), choice);
+    }
+
+    /// How many votes each choice has, in order.
+    pub fn counts(&self) -> [usize; 2] {
+        let votes = self.0.lock().unwrap();
+        let second = votes.values().filter(|choice| **choice).count();
+        [votes.len() - second, second]
+    }
+
+    /// The choice with the most votes, with ties going to the default. None if nobody's voted.
+    pub fn winner(&self, default: bool) -> Option<bool> {
+        match self.counts() {
+            [0, 0] => None,
+            [first, second] if first == second => Some(default),
+            [first, second] => Some(second > first),
+        }
+    }
+}
+
+impl fmt::Debug for Tally {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [first, second] = self.counts();
+        write!(f, "Tally({first} to {second})")
+    }
+}
+
+impl Sandbox {
+    /// The votes cast on the event that's waiting to be answered, if it's being voted on.
+    pub fn votes(&self) -> Option<&Tally> {
+        self.poll.as_ref()
+    }
+
+    /// Vote on the event that's waiting to be answered.
+    pub fn vote(&self, voter: &str, choice: bool) -> Result<String, String> {
+        let tally = self
+            .poll
+            .as_ref()
+            .ok_or("There's nothing being voted on right now")?;
+        tally.cast(voter, choice);
+        let [first, second] = tally.counts();
+        Ok(format!(
+            "{voter} voted for option {}, making it {first} to {second}",
+            usize::from(choice) + 1
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_events::EventTypes;
+    use crate::settings::{EventFrequency, SimulationSettings};
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_tally() {
+        let tally = Tally::default();
+        assert_eq!(tally.winner(true), None);
+        tally.cast("a", false);
+        tally.cast("b", true);
+        assert_eq!(tally.counts(), [1, 1]);
+        assert_eq!(tally.winner(true), Some(true));
+        assert_eq!(tally.winner(false), Some(false));
+        // changing your mind doesn't count twice
+        tally.cast("b", false);
+        tally.cast("b", false);
+        assert_eq!(tally.counts(), [2, 0]);
+        assert_eq!(tally.winner(true), Some(false));
+    }
+
+    #[test]
+    fn test_votes_decide_events() {
+        let mut testbed = TestBed::new_with_entities(3, 3, vec![]);
+        testbed.sandbox.apply_settings(SimulationSettings {
+            event_frequency: EventFrequency::Never,
+            event_voting: true,
+            ..SimulationSettings::default()
+        });
+        assert!(testbed.sandbox.vote("a", true).is_err());
+        testbed.sandbox.scripted_events.push((0, EventTypes::Party));
+        let mut report = testbed.sandbox.tick();
+        let pending = report.event.take().unwrap();
+        let default = testbed.sandbox.describe_event(&pending).default;
+
+        // the shared tally keeps up with everyone's votes
+        let tally = testbed.sandbox.votes().unwrap().clone();
+        testbed.sandbox.vote("a", !default).unwrap();
+        testbed.sandbox.vote("b", !default).unwrap();
+        testbed.sandbox.vote("c", default).unwrap();
+        assert_eq!(tally.winner(default), Some(!default));
+        testbed.sandbox.answer_with_policy(pending);
+        let answered = testbed.sandbox.event_log().last().unwrap();
+        assert_eq!(answered.answer, !default);
+        assert_eq!(answered.policy, Some("votes"));
+        // and once it's been answered, there's nothing left to vote on
+        assert!(testbed.sandbox.votes().is_none());
+        assert!(testbed.sandbox.vote("a", true).is_err());
+
+        // without voting turned on, events are left to the player like always
+        testbed.sandbox.apply_settings(SimulationSettings {
+            event_frequency: EventFrequency::Never,
+            ..SimulationSettings::default()
+        });
+        testbed.sandbox.scripted_events.push((1, EventTypes::Party));
+        assert!(testbed.sandbox.tick().event.is_some());
+        assert!(testbed.sandbox.votes().is_none());
+    }
+}
@@ -0,0 +1,139 @@
+// Every so often a storm blows through a region and stirs the whole board up for a while. Anything out wandering gets thrown
+// further than it meant to go (see Animals::random_walk), plants out in the open get battered, and the smallest things on the
+// board get picked up and dropped a few tiles away. Kelp forests and anything tucked up against cover ride it out.
+
+use log::info;
+use rand::seq::IteratorRandom;
+use rand::Rng;
+
+use crate::element_traits::Lives;
+use crate::entities::{Entity, Species};
+use crate::game_board::Pos;
+use crate::Sandbox;
+
+/// The odds of a storm blowing in on any calm tick.
+pub const STORM_CHANCE: f64 = 0.002;
+
+/// How many ticks a storm blows for.
+pub const STORM_TICKS: usize = 30;
+
+/// How much further, at most, a storm can throw something on a random walk in either direction.
+pub const STORM_GUST: usize = 2;
+
+/// The odds of a plant out in the open losing a bite to a storm, each tick.
+pub const BATTER_CHANCE: f64 = 0.03;
+
+/// The odds of something small out in the open getting picked up by a storm, each tick.
+pub const TOSS_CHANCE: f64 = 0.1;
+
+/// How far a storm can carry something it picks up.
+pub const TOSS_DISTANCE: usize = 3;
+
+/// Anything small enough to get picked up by a storm. Crabs hang on, and everything else is too heavy.
+const TOSSED: [Species; 4] = [
+    Species::Fish,
+    Species::KelpSeed,
+    Species::KelpLeaf,
+    Species::Shell,
+];
+
+/// What the weather's doing over a board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Weather {
+    #[default]
+    Calm,
+    /// A storm, with however many more ticks it has to blow.
+    Storm(usize),
+}
+
+impl Weather {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Calm => "calm",
+            Self::Storm(_) => "storm",
+        }
+    }
+
+    pub fn is_stormy(&self) -> bool {
+        matches!(self, Self::Storm(_))
+    }
+}
+
+impl Sandbox {
+    /// Maybe blow a storm in, or keep the one we've got going until it blows itself out.
+    pub fn handle_weather(&mut self) {
+        match self.board.get_weather() {
+            Weather::Calm => {
+                if self.rng.lock().unwrap().gen_bool(STORM_CHANCE) {
+                    self.start_storm(STORM_TICKS);
+                }
+            }
+            Weather::Storm(ticks_left) => {
+                self.batter_plants();
+                self.toss_small_things();
+                let next = match ticks_left {
+                    0 | 1 => Weather::Calm,
+                    _ => Weather::Storm(ticks_left - 1),
+                };
+                if next == Weather::Calm {
+                    info!(target: &self.log_target, "The storm has blown over");
+                }
+                self.board.set_weather(next);
+            }
+        }
+    }
+
+    /// Blow a storm in over the loaded region, for however many ticks. Replaces any storm already blowing.
+    pub fn start_storm(&mut self, ticks: usize) {
+        info!(target: &self.log_target, "A storm is blowing in for {ticks} ticks");
+        self.board.set_weather(Weather::Storm(ticks));
+    }
+
+    /// Plants out in the open take a beating, a bite at a time.
+    fn batter_plants(&mut self) {
+        let mut rng = self.rng.lock().unwrap();
+        let exposed: Vec<Pos> = self
+            .board
+            .iter_occupied()
+            .filter(|(pos, e)| e.plant().is_some() && !self.board.sheltered(*pos))
+            .map(|(pos, _)| pos)
+            .collect();
+        for pos in exposed {
+            if !rng.gen_bool(BATTER_CHANCE) {
+                continue;
+            }
+            if let Some(plant) = self.board.entity_at_mut(pos).and_then(Entity::plant_mut) {
+                plant.modify_health(-1, "a storm");
+            }
+        }
+    }
+
+    /// Pick up some of the small things out in the open, and drop them on an empty tile a little way off.
+    fn toss_small_things(&mut self) {
+        let mut rng = self.rng.lock().unwrap();
+        let exposed: Vec<Pos> = self
+            .board
+            .iter_occupied()
+            .filter(|(pos, e)| TOSSED.contains(&Species::of(e)) && !self.board.sheltered(*pos))
+            .map(|(pos, _)| pos)
+            .collect();
+        for pos in exposed {
+            if !rng.gen_bool(TOSS_CHANCE) {
+                continue;
+            }
+            let Some(to) = self
+                .board
+                .positions_within(TOSS_DISTANCE, pos)
+                .filter(|p| !self.board.get_tile_from_pos(*p).is_occupied())
+                .choose(&mut *rng)
+            else {
+                continue;
+            };
+            let Some(entity) = self.board.remove_entity(pos) else {
+                continue;
+            };
+            // we just checked that it was empty
+            self.board.place_entity(to, entity).unwrap();
+        }
+    }
+}
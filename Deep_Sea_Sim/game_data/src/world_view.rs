@@ -0,0 +1,195 @@
+// A read-only look at the game in between ticks. Anything that wants to know what's going on (the GUI, scripts, tests,
+// anything keeping an eye on the game) can ask it, without being handed the board itself and the chance to change it.
+// Everything it hands back borrows from the sandbox, so the view has to be let go of before the next tick can run.
+
+use std::collections::HashMap;
+
+use crate::entities::{Entity, Species};
+use crate::entity_control::EntityID;
+use crate::game_board::{Current, Pos, Tile};
+use crate::game_events::EventTypes;
+use crate::pool::Census;
+use crate::weather::Weather;
+use crate::Sandbox;
+
+/// Something going on across the board that isn't down to any one entity.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Effect {
+    /// A storm, with however many more ticks it has to blow.
+    Storm(usize),
+    /// The water's flowing one way, carrying drifting seeds along with it.
+    Current(Current),
+    /// Some of the water is polluted, on this many tiles.
+    Pollution { tiles: usize },
+    /// A school of fish is making its way across the board, this many strong.
+    Migration { migrants: usize },
+    /// An event that's been set to go off on a certain tick.
+    Scheduled { tick: usize, event: EventTypes },
+}
+
+/// A read-only look at the region that's loaded, see Sandbox::view.
+#[derive(Clone, Copy)]
+pub struct WorldView<'a> {
+    sandbox: &'a Sandbox,
+}
+
+impl Sandbox {
+    pub fn view(&self) -> WorldView<'_> {
+        WorldView { sandbox: self }
+    }
+}
+
+impl<'a> WorldView<'a> {
+    pub fn tick(&self) -> usize {
+        self.sandbox.clock
+    }
+
+    pub fn region_name(&self) -> &'a str {
+        &self.sandbox.region_name
+    }
+
+    /// The size of the board, as (rows, cols).
+    pub fn dims(&self) -> (usize, usize) {
+        let (cols, rows) = self.sandbox.board.dims();
+        (rows, cols)
+    }
+
+    /// Every one of a species on the board, and where it is.
+    pub fn entities_by_species(&self, species: Species) -> Vec<(Pos, &'a Entity)> {
+        self.sandbox
+            .board
+            .iter_occupied()
+            .filter(|(_, e)| Species::of(e) == species)
+            .collect()
+    }
+
+    /// Whatever has this ID, and where it is. None if it's gone, or isn't in this region.
+    pub fn entity(&self, id: EntityID) -> Option<(Pos, &'a Entity)> {
+        let pos = *self
+            .sandbox
+            .entity_context
+            .read()
+            .unwrap()
+            .get_active_entries()
+            .get(&id)?;
+        let entity = self.sandbox.board.entity_at(pos)?;
+        Some((pos, entity))
+    }
+
+    /// Every tile between two corners, both included, row by row, along with whatever's on it. Anything past the edge
+    /// of the board is left out.
+    pub fn tiles_in_rect(&self, from: Pos, to: Pos) -> Vec<(Pos, &'a Tile, Option<&'a Entity>)> {
+        let board = &self.sandbox.board;
+        let (cols, rows) = board.dims();
+        let xs = from.x.min(to.x)..=from.x.max(to.x).min(cols.saturating_sub(1));
+        let ys = from.y.min(to.y)..=from.y.max(to.y).min(rows.saturating_sub(1));
+        ys.flat_map(|y| xs.clone().map(move |x| Pos { x, y }))
+            .map(|pos| (pos, board.get_tile_from_pos(pos), board.entity_at(pos)))
+            .collect()
+    }
+
+    /// How many of each species there are in this region.
+    pub fn populations(&self) -> HashMap<Species, usize> {
+        let mut populations = HashMap::new();
+        for (_, entity) in self.sandbox.board.iter_occupied() {
+            *populations.entry(Species::of(entity)).or_default() += 1;
+        }
+        populations
+    }
+
+    /// How many of each species there are across every region.
+    pub fn census(&self) -> Census {
+        self.sandbox.census()
+    }
+
+    /// Everything going on across the region right now, storms first and scheduled events last.
+    pub fn active_effects(&self) -> Vec<Effect> {
+        let board = &self.sandbox.board;
+        let mut effects = vec![];
+        if let Weather::Storm(ticks) = board.get_weather() {
+            effects.push(Effect::Storm(ticks));
+        }
+        if board.get_current() != Current::Still {
+            effects.push(Effect::Current(board.get_current()));
+        }
+        let (cols, rows) = board.dims();
+        let polluted = (0..rows)
+            .flat_map(|y| (0..cols).map(move |x| Pos { x, y }))
+            .filter(|pos| board.get_tile_from_pos(*pos).get_pollution() > 0.0)
+            .count();
+        if polluted > 0 {
+            effects.push(Effect::Pollution { tiles: polluted });
+        }
+        let migrants = board
+            .iter_occupied()
+            .filter_map(|(_, e)| e.animal())
+            .filter(|a| a.body().mobility.heading.is_some())
+            .count();
+        if migrants > 0 {
+            effects.push(Effect::Migration { migrants });
+        }
+        let mut scheduled = self.sandbox.scripted_events.clone();
+        scheduled.sort_by_key(|(tick, _)| *tick);
+        effects.extend(
+            scheduled
+                .into_iter()
+                .map(|(tick, event)| Effect::Scheduled { tick, event }),
+        );
+        effects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestBed;
+
+    #[test]
+    fn test_world_view() {
+        let (shark, fish) = (Pos { x: 0, y: 0 }, Pos { x: 2, y: 1 });
+        let mut testbed = TestBed::builder(3, 4)
+            .with(shark, Species::Shark)
+            .with(fish, Species::Fish)
+            .terrain(&["...r", "....", "k..."])
+            .build();
+        let id = testbed.id_at(fish);
+        testbed.sandbox.scripted_events = vec![(40, EventTypes::OilSpill), (10, EventTypes::Party)];
+        testbed.sandbox.board.set_weather(Weather::Storm(5));
+
+        let view = testbed.sandbox.view();
+        assert_eq!(view.dims(), (3, 4));
+        let sharks = view.entities_by_species(Species::Shark);
+        assert_eq!(sharks.len(), 1);
+        assert_eq!(sharks[0].0, shark);
+        assert_eq!(view.entity(id).map(|(pos, _)| pos), Some(fish));
+        assert_eq!(view.populations()[&Species::Rock], 1);
+        assert_eq!(view.census().count(Species::Kelp), 1);
+
+        // the corners can be given either way round, and anything off the board is dropped
+        let tiles = view.tiles_in_rect(Pos { x: 9, y: 1 }, Pos { x: 2, y: 0 });
+        let positions: Vec<Pos> = tiles.iter().map(|(pos, _, _)| *pos).collect();
+        assert_eq!(
+            positions,
+            [(2, 0), (3, 0), (2, 1), (3, 1)].map(Pos::from).to_vec()
+        );
+        assert_eq!(tiles.iter().filter(|(_, t, _)| t.is_occupied()).count(), 2);
+        assert_eq!(tiles.iter().filter(|(_, _, e)| e.is_some()).count(), 2);
+
+        assert_eq!(
+            view.active_effects(),
+            vec![
+                Effect::Storm(5),
+                Effect::Scheduled {
+                    tick: 10,
+                    event: EventTypes::Party
+                },
+                Effect::Scheduled {
+                    tick: 40,
+                    event: EventTypes::OilSpill
+                },
+            ]
+        );
+        testbed.sandbox.board.remove_entity(fish);
+        assert!(testbed.sandbox.view().entity(id).is_none());
+    }
+}